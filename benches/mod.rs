@@ -3,10 +3,12 @@ use criterion::{criterion_group, criterion_main};
 mod concurrent;
 mod order_book;
 mod simple;
+mod snapshot;
 
 use concurrent::register_benchmarks as register_concurrent_benchmarks;
 use order_book::register_benchmarks as register_order_book_benchmarks;
 use simple::basic::benchmark_data;
+use snapshot::register_benchmarks as register_snapshot_benchmarks;
 
 // Define the benchmark groups
 criterion_group!(
@@ -14,6 +16,7 @@ criterion_group!(
     benchmark_data,
     register_order_book_benchmarks,
     register_concurrent_benchmarks,
+    register_snapshot_benchmarks,
 );
 
 criterion_main!(benches);
@@ -0,0 +1,144 @@
+use criterion::{BenchmarkId, Criterion};
+use orderbook_rs::OrderBook;
+use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Quotes per side kept by the simulated market maker
+const QUOTES: [usize; 2] = [10, 100];
+
+/// A book with `quotes` bids below 10_000 and asks above it, one per level,
+/// returning the bid and ask ids
+fn setup_quotes(quotes: usize) -> (OrderBook, Vec<OrderId>, Vec<OrderId>) {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    let mut bids = Vec::with_capacity(quotes);
+    let mut asks = Vec::with_capacity(quotes);
+    for i in 0..quotes as u64 {
+        let bid = OrderId::new_uuid();
+        book.add_limit_order(bid, 9_999 - i, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        bids.push(bid);
+        let ask = OrderId::new_uuid();
+        book.add_limit_order(ask, 10_001 + i, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        asks.push(ask);
+    }
+    (book, bids, asks)
+}
+
+/// Amend-heavy quoting: a market maker re-pricing its quotes on every tick
+/// without trading. Each iteration moves one bid and one ask by a tick and
+/// back, through price updates, replaces, or an explicit cancel followed by a
+/// new order, the pattern of venues without native amends.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Amend Quoting");
+
+    for quotes in QUOTES {
+        group.bench_with_input(
+            BenchmarkId::new("update_price", quotes),
+            &quotes,
+            |b, &quotes| {
+                let (book, bids, asks) = setup_quotes(quotes);
+                let mut tick = 0usize;
+                b.iter(|| {
+                    let i = tick % quotes;
+                    let offset = (tick / quotes % 2) as u64;
+                    tick += 1;
+                    let _ = black_box(book.update_order(OrderUpdate::UpdatePrice {
+                        order_id: bids[i],
+                        new_price: 9_999 - i as u64 - offset,
+                    }));
+                    black_box(book.update_order(OrderUpdate::UpdatePrice {
+                        order_id: asks[i],
+                        new_price: 10_001 + i as u64 + offset,
+                    }))
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("update_price_and_quantity", quotes),
+            &quotes,
+            |b, &quotes| {
+                let (book, bids, asks) = setup_quotes(quotes);
+                let mut tick = 0usize;
+                b.iter(|| {
+                    let i = tick % quotes;
+                    let offset = (tick / quotes % 2) as u64;
+                    tick += 1;
+                    let _ = black_box(book.update_order(OrderUpdate::UpdatePriceAndQuantity {
+                        order_id: bids[i],
+                        new_price: 9_999 - i as u64 - offset,
+                        new_quantity: 10 + offset,
+                    }));
+                    black_box(book.update_order(OrderUpdate::UpdatePriceAndQuantity {
+                        order_id: asks[i],
+                        new_price: 10_001 + i as u64 + offset,
+                        new_quantity: 10 + offset,
+                    }))
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("replace", quotes),
+            &quotes,
+            |b, &quotes| {
+                let (book, bids, asks) = setup_quotes(quotes);
+                let mut tick = 0usize;
+                b.iter(|| {
+                    let i = tick % quotes;
+                    let offset = (tick / quotes % 2) as u64;
+                    tick += 1;
+                    let _ = black_box(book.update_order(OrderUpdate::Replace {
+                        order_id: bids[i],
+                        price: 9_999 - i as u64 - offset,
+                        quantity: 10,
+                        side: Side::Buy,
+                    }));
+                    black_box(book.update_order(OrderUpdate::Replace {
+                        order_id: asks[i],
+                        price: 10_001 + i as u64 + offset,
+                        quantity: 10,
+                        side: Side::Sell,
+                    }))
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("cancel_then_add", quotes),
+            &quotes,
+            |b, &quotes| {
+                let (book, mut bids, mut asks) = setup_quotes(quotes);
+                let mut tick = 0usize;
+                b.iter(|| {
+                    let i = tick % quotes;
+                    let offset = (tick / quotes % 2) as u64;
+                    tick += 1;
+                    let _ = black_box(book.cancel_order(bids[i]));
+                    bids[i] = OrderId::new_uuid();
+                    let _ = black_box(book.add_limit_order(
+                        bids[i],
+                        9_999 - i as u64 - offset,
+                        10,
+                        Side::Buy,
+                        TimeInForce::Gtc,
+                        None,
+                    ));
+                    let _ = black_box(book.cancel_order(asks[i]));
+                    asks[i] = OrderId::new_uuid();
+                    black_box(book.add_limit_order(
+                        asks[i],
+                        10_001 + i as u64 + offset,
+                        10,
+                        Side::Sell,
+                        TimeInForce::Gtc,
+                        None,
+                    ))
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
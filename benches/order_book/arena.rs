@@ -0,0 +1,111 @@
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use orderbook_rs::{ArenaOrderBook, OrderBook};
+use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Resting orders per ask level
+const ORDERS_PER_LEVEL: u64 = 5;
+
+fn ask(price: u64) -> OrderType<()> {
+    OrderType::Standard {
+        id: OrderId::new_uuid(),
+        price,
+        quantity: 10,
+        side: Side::Sell,
+        timestamp: 0,
+        time_in_force: TimeInForce::Gtc,
+        extra_fields: (),
+    }
+}
+
+/// An arena book with `levels` ask levels from 10_001 up, each holding
+/// [`ORDERS_PER_LEVEL`] orders of 10
+fn setup_arena_asks(levels: u64) -> ArenaOrderBook {
+    let mut book: ArenaOrderBook = ArenaOrderBook::with_capacity("BENCH_SYMBOL", 8_192);
+    for level in 0..levels {
+        for _ in 0..ORDERS_PER_LEVEL {
+            book.add_order(ask(10_001 + level)).unwrap();
+        }
+    }
+    book
+}
+
+/// The same book as [`setup_arena_asks`], on the concurrent `OrderBook`
+fn setup_book_asks(levels: u64) -> OrderBook {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    for level in 0..levels {
+        for _ in 0..ORDERS_PER_LEVEL {
+            book.add_order(ask(10_001 + level)).unwrap();
+        }
+    }
+    book
+}
+
+/// Arena storage against an `Arc` per order: resting order churn, where the
+/// arena recycles the slots of cancelled orders, and deep sweeps walking
+/// every order of a side.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Arena");
+
+    group.bench_function("arena_add_cancel_churn", |b| {
+        let mut book = setup_arena_asks(100);
+        b.iter(|| {
+            let order = ask(10_050);
+            let id = order.id();
+            black_box(book.add_order(order).unwrap());
+            black_box(book.cancel_order(id));
+        })
+    });
+
+    group.bench_function("arc_add_cancel_churn", |b| {
+        let book = setup_book_asks(100);
+        b.iter(|| {
+            let order = ask(10_050);
+            let id = order.id();
+            black_box(book.add_order(order).unwrap());
+            black_box(book.cancel_order(id).unwrap());
+        })
+    });
+
+    for levels in [10u64, 100, 1_000] {
+        let depth_quantity = levels * ORDERS_PER_LEVEL * 10;
+
+        group.bench_with_input(
+            BenchmarkId::new("arena_market_sweep", levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || setup_arena_asks(levels),
+                    |mut book| {
+                        black_box(book.submit_market_order(
+                            OrderId::new_uuid(),
+                            depth_quantity,
+                            Side::Buy,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("arc_market_sweep", levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || setup_book_asks(levels),
+                    |book| {
+                        black_box(book.submit_market_order(
+                            OrderId::new_uuid(),
+                            depth_quantity,
+                            Side::Buy,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
@@ -0,0 +1,92 @@
+use criterion::{BenchmarkId, Criterion};
+use orderbook_rs::{CacheInvalidation, OrderBook};
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+const GRANULARITIES: [CacheInvalidation; 3] = [
+    CacheInvalidation::Wholesale,
+    CacheInvalidation::PerSide,
+    CacheInvalidation::PriceAware,
+];
+
+/// A book with `levels` ask levels of 10 from 10_001 up and bids below them
+fn setup_book(granularity: CacheInvalidation, levels: u64) -> OrderBook {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    book.set_cache_invalidation(granularity);
+    for i in 0..levels {
+        book.add_limit_order(
+            OrderId::new_uuid(),
+            10_001 + i,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::new_uuid(),
+            10_000 - i,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+    book
+}
+
+/// Latency of order entry followed by a top of book read, for each way of
+/// invalidating the best price cache. Adds away from the touch keep the
+/// cache warm under price-aware invalidation, and a sweep through several
+/// levels invalidates the swept side once.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Cache Invalidation");
+
+    for granularity in GRANULARITIES {
+        group.bench_with_input(
+            BenchmarkId::new("add_away_from_touch", format!("{granularity:?}")),
+            &granularity,
+            |b, &granularity| {
+                let book = setup_book(granularity, 100);
+                let mut price = 9_000;
+                b.iter(|| {
+                    price = if price > 8_000 { price - 1 } else { 9_000 };
+                    let _ = black_box(book.add_limit_order(
+                        OrderId::new_uuid(),
+                        price,
+                        1,
+                        Side::Buy,
+                        TimeInForce::Gtc,
+                        None,
+                    ));
+                    black_box((book.best_bid(), book.best_ask()))
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("sweep_ten_levels", format!("{granularity:?}")),
+            &granularity,
+            |b, &granularity| {
+                b.iter_batched(
+                    || setup_book(granularity, 20),
+                    |book| {
+                        let _ = black_box(book.add_limit_order(
+                            OrderId::new_uuid(),
+                            10_010,
+                            105,
+                            Side::Buy,
+                            TimeInForce::Gtc,
+                            None,
+                        ));
+                        black_box((book.best_bid(), book.best_ask()))
+                    },
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
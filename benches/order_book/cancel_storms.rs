@@ -0,0 +1,122 @@
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use orderbook_rs::OrderBook;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+/// A book with `count` orders spread over 50 bid and 50 ask levels, with the ids
+/// in the order they were added
+fn setup_book(count: u64) -> (OrderBook, Vec<OrderId>) {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    let ids: Vec<OrderId> = (0..count)
+        .map(|i| {
+            let id = OrderId::new_uuid();
+            let (side, price) = if i % 2 == 0 {
+                (Side::Buy, 10_000 - (i / 2) % 50)
+            } else {
+                (Side::Sell, 10_001 + (i / 2) % 50)
+            };
+            book.add_limit_order(id, price, 10, side, TimeInForce::Gtc, None)
+                .unwrap();
+            id
+        })
+        .collect();
+    (book, ids)
+}
+
+/// A book with `count` orders queued at a single bid level
+fn setup_single_level(count: u64) -> (OrderBook, Vec<OrderId>) {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    let ids: Vec<OrderId> = (0..count)
+        .map(|_| {
+            let id = OrderId::new_uuid();
+            book.add_limit_order(id, 10_000, 10, Side::Buy, TimeInForce::Gtc, None)
+                .unwrap();
+            id
+        })
+        .collect();
+    (book, ids)
+}
+
+/// Cancel storms: every resting order pulled back to back, as when a venue
+/// or a large participant mass-cancels. Oldest-first, newest-first and
+/// interleaved orders stress different positions within the level queues.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Cancel Storms");
+
+    for count in [100u64, 1_000, 10_000] {
+        group.bench_with_input(
+            BenchmarkId::new("cancel_all_oldest_first", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || setup_book(count),
+                    |(book, ids)| {
+                        for id in ids {
+                            let _ = black_box(book.cancel_order(id));
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("cancel_all_newest_first", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || setup_book(count),
+                    |(book, ids)| {
+                        for id in ids.into_iter().rev() {
+                            let _ = black_box(book.cancel_order(id));
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("cancel_all_interleaved", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || setup_book(count),
+                    |(book, ids)| {
+                        // Every seventh id, wrapping around, touches each level out of order
+                        let len = ids.len();
+                        for i in 0..len {
+                            let _ = black_box(book.cancel_order(ids[(i * 7) % len]));
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    for count in [100u64, 1_000] {
+        group.bench_with_input(
+            BenchmarkId::new("cancel_all_single_level", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || setup_single_level(count),
+                    |(book, ids)| {
+                        for id in ids {
+                            let _ = black_box(book.cancel_order(id));
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.bench_function("cancel_unknown_ids", |b| {
+        let (book, _) = setup_book(1_000);
+        b.iter(|| black_box(book.cancel_order(OrderId::new_uuid())))
+    });
+
+    group.finish();
+}
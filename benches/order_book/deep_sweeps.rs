@@ -0,0 +1,98 @@
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use orderbook_rs::OrderBook;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Resting orders per ask level
+const ORDERS_PER_LEVEL: u64 = 5;
+
+/// A book with `levels` ask levels from 10_001 up, each holding
+/// [`ORDERS_PER_LEVEL`] orders of 10
+fn setup_deep_asks(levels: u64) -> OrderBook {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    for level in 0..levels {
+        for _ in 0..ORDERS_PER_LEVEL {
+            book.add_limit_order(
+                OrderId::new_uuid(),
+                10_001 + level,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+    }
+    book
+}
+
+/// Deep-book sweeps: a single aggressive order consuming every level of a
+/// deep side. Market orders empty the side, while a limit order priced past
+/// the last level also rests its remainder, and a half-depth sweep leaves the
+/// deeper levels behind.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Deep Sweeps");
+
+    for levels in [10u64, 100, 1_000] {
+        let depth_quantity = levels * ORDERS_PER_LEVEL * 10;
+
+        group.bench_with_input(
+            BenchmarkId::new("market_sweep_all_levels", levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || setup_deep_asks(levels),
+                    |book| {
+                        black_box(book.submit_market_order(
+                            OrderId::new_uuid(),
+                            depth_quantity,
+                            Side::Buy,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("limit_sweep_and_rest", levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || setup_deep_asks(levels),
+                    |book| {
+                        black_box(book.add_limit_order(
+                            OrderId::new_uuid(),
+                            10_001 + levels,
+                            depth_quantity + 10,
+                            Side::Buy,
+                            TimeInForce::Gtc,
+                            None,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("market_sweep_half_depth", levels),
+            &levels,
+            |b, &levels| {
+                b.iter_batched(
+                    || setup_deep_asks(levels),
+                    |book| {
+                        black_box(book.submit_market_order(
+                            OrderId::new_uuid(),
+                            depth_quantity / 2,
+                            Side::Buy,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
@@ -1,14 +1,28 @@
 pub mod add_orders;
+pub mod amend_quoting;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod cache_invalidation;
+pub mod cancel_storms;
+pub mod deep_sweeps;
 pub mod match_orders;
 pub mod matching;
 pub mod mixed_operations;
+pub mod queue_priority;
 pub mod update_orders;
 
 // Import common benchmarks into the main bench group
 pub fn register_benchmarks(c: &mut criterion::Criterion) {
     add_orders::register_benchmarks(c);
+    cache_invalidation::register_benchmarks(c);
     match_orders::register_benchmarks(c);
     update_orders::register_benchmarks(c);
     mixed_operations::register_benchmarks(c);
     matching::register_benchmarks(c);
+    cancel_storms::register_benchmarks(c);
+    amend_quoting::register_benchmarks(c);
+    deep_sweeps::register_benchmarks(c);
+    queue_priority::register_benchmarks(c);
+    #[cfg(feature = "arena")]
+    arena::register_benchmarks(c);
 }
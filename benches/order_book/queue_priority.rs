@@ -0,0 +1,72 @@
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use orderbook_rs::OrderBook;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Price of the level the takers trade against
+const LEVEL_PRICE: u64 = 10_001;
+
+/// A book with one ask level holding `orders` orders of 10, plus an iceberg
+/// order at `iceberg_price` if one is given
+fn setup_level(orders: u64, iceberg_price: Option<u64>) -> OrderBook {
+    let book: OrderBook = OrderBook::new("BENCH_SYMBOL");
+    for _ in 0..orders {
+        book.add_limit_order(
+            OrderId::new_uuid(),
+            LEVEL_PRICE,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+    if let Some(price) = iceberg_price {
+        book.add_iceberg_order(
+            OrderId::new_uuid(),
+            price,
+            10,
+            90,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+    book
+}
+
+/// Matching a level in acceptance order. A level whose queue is in
+/// acceptance order is matched by the level's own FIFO queue, even while
+/// another level of the book holds an iceberg order; a level holding an
+/// iceberg order itself is walked order by order in acceptance order.
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("OrderBook - Queue Priority");
+
+    for orders in [10u64, 100, 1_000] {
+        // Half the level, ending on a partial fill
+        let quantity = orders * 5 + 5;
+
+        for (name, iceberg_price) in [
+            ("fifo_level", None),
+            ("fifo_level_beside_iceberg", Some(LEVEL_PRICE + 1)),
+            ("level_with_iceberg", Some(LEVEL_PRICE)),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, orders), &orders, |b, &orders| {
+                b.iter_batched(
+                    || setup_level(orders, iceberg_price),
+                    |book| {
+                        black_box(book.submit_market_order(
+                            OrderId::new_uuid(),
+                            quantity,
+                            Side::Buy,
+                        ))
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+
+    group.finish();
+}
@@ -0,0 +1,91 @@
+use criterion::{BenchmarkId, Criterion};
+use orderbook_rs::{OrderBook, OrderBookL3Snapshot, OrderBookSnapshot};
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Book with `levels` price levels per side and 10 orders per level
+fn deep_book(levels: u64) -> OrderBook {
+    let order_book: OrderBook = OrderBook::new("TEST-SYMBOL");
+    for level in 0..levels {
+        for _ in 0..10 {
+            let _ = order_book.add_limit_order(
+                OrderId::new_uuid(),
+                100_000 - level,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            );
+            let _ = order_book.add_limit_order(
+                OrderId::new_uuid(),
+                100_001 + level,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            );
+        }
+    }
+    order_book
+}
+
+/// Compare the cost of encoding and decoding snapshots as JSON and, with the
+/// `binary` feature, in the compact binary form
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Snapshot - Encoding");
+
+    for levels in [100u64, 1_000] {
+        let order_book = deep_book(levels);
+        let snapshot = order_book.create_snapshot(usize::MAX);
+        let l3_snapshot = order_book.create_l3_snapshot(usize::MAX);
+
+        let json = serde_json::to_vec(&snapshot).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("json_encode", levels),
+            &snapshot,
+            |b, s| b.iter(|| black_box(serde_json::to_vec(s).unwrap())),
+        );
+        group.bench_with_input(BenchmarkId::new("json_decode", levels), &json, |b, j| {
+            b.iter(|| black_box(serde_json::from_slice::<OrderBookSnapshot>(j).unwrap()))
+        });
+
+        let l3_json = serde_json::to_vec(&l3_snapshot).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("l3_json_encode", levels),
+            &l3_snapshot,
+            |b, s| b.iter(|| black_box(serde_json::to_vec(s).unwrap())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("l3_json_decode", levels),
+            &l3_json,
+            |b, j| b.iter(|| black_box(serde_json::from_slice::<OrderBookL3Snapshot>(j).unwrap())),
+        );
+
+        #[cfg(feature = "binary")]
+        {
+            let bytes = snapshot.to_bytes().unwrap();
+            group.bench_with_input(
+                BenchmarkId::new("binary_encode", levels),
+                &snapshot,
+                |b, s| b.iter(|| black_box(s.to_bytes().unwrap())),
+            );
+            group.bench_with_input(BenchmarkId::new("binary_decode", levels), &bytes, |b, d| {
+                b.iter(|| black_box(OrderBookSnapshot::from_bytes(d).unwrap()))
+            });
+
+            let l3_bytes = l3_snapshot.to_bytes().unwrap();
+            group.bench_with_input(
+                BenchmarkId::new("l3_binary_encode", levels),
+                &l3_snapshot,
+                |b, s| b.iter(|| black_box(s.to_bytes().unwrap())),
+            );
+            group.bench_with_input(
+                BenchmarkId::new("l3_binary_decode", levels),
+                &l3_bytes,
+                |b, d| b.iter(|| black_box(OrderBookL3Snapshot::from_bytes(d).unwrap())),
+            );
+        }
+    }
+
+    group.finish();
+}
@@ -0,0 +1,9 @@
+pub mod encoding;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+
+pub fn register_benchmarks(c: &mut criterion::Criterion) {
+    encoding::register_benchmarks(c);
+    #[cfg(feature = "parallel")]
+    parallel::register_benchmarks(c);
+}
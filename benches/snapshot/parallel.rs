@@ -0,0 +1,45 @@
+use criterion::{BenchmarkId, Criterion};
+use orderbook_rs::OrderBook;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::hint::black_box;
+
+/// Compare sequential and parallel snapshot construction at increasing depths,
+/// to find the depth from which the parallel path wins
+pub fn register_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Snapshot - Parallel");
+
+    let mut order_book: OrderBook = OrderBook::new("TEST-SYMBOL");
+    for level in 0..4_096u64 {
+        for _ in 0..4 {
+            let _ = order_book.add_limit_order(
+                OrderId::new_uuid(),
+                100_000 - level,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            );
+            let _ = order_book.add_limit_order(
+                OrderId::new_uuid(),
+                100_001 + level,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            );
+        }
+    }
+
+    for depth in [16usize, 64, 256, 1_024, 4_096] {
+        order_book.set_parallel_snapshot_depth(usize::MAX);
+        group.bench_with_input(BenchmarkId::new("sequential", depth), &depth, |b, &d| {
+            b.iter(|| black_box(order_book.create_snapshot(d)))
+        });
+        order_book.set_parallel_snapshot_depth(0);
+        group.bench_with_input(BenchmarkId::new("parallel", depth), &depth, |b, &d| {
+            b.iter(|| black_box(order_book.create_snapshot(d)))
+        });
+    }
+
+    group.finish();
+}
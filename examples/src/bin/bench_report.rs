@@ -0,0 +1,291 @@
+//! Structured benchmark reports built from criterion results.
+//!
+//! Usage:
+//!
+//! ```text
+//! bench_report run [--filter <pattern>] [--output <report.json>]
+//! bench_report collect [--criterion-dir <dir>] [--output <report.json>]
+//! bench_report compare <baseline.json> <current.json> [--threshold <percent>]
+//! ```
+//!
+//! `run` executes the criterion benches and then collects their results,
+//! `collect` only reads the results criterion already left under
+//! `target/criterion`, and `compare` diffs two reports and exits with a
+//! non-zero status when any scenario regressed by more than the threshold.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use tracing::{error, info, warn};
+
+const DEFAULT_CRITERION_DIR: &str = "target/criterion";
+const DEFAULT_REPORT_PATH: &str = "target/bench-report.json";
+const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+const USAGE: &str = "usage: bench_report <run|collect|compare> [options]
+    run [--filter <pattern>] [--output <file>]
+    collect [--criterion-dir <dir>] [--output <file>]
+    compare <baseline> <current> [--threshold <percent>]";
+
+/// Measurements for a single criterion benchmark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScenarioResult {
+    /// Criterion group the benchmark belongs to
+    group: String,
+    /// Mean time per iteration in nanoseconds
+    mean_ns: f64,
+    /// Median time per iteration in nanoseconds
+    p50_ns: f64,
+    /// 99th percentile time per iteration in nanoseconds
+    p99_ns: f64,
+    /// Iterations per second derived from the mean
+    ops_per_sec: f64,
+    /// Number of samples the percentiles are computed from
+    samples: usize,
+}
+
+/// A full benchmark report, keyed by the criterion benchmark id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BenchReport {
+    /// Seconds since the Unix epoch when the report was collected
+    created_at: u64,
+    scenarios: BTreeMap<String, ScenarioResult>,
+}
+
+fn main() -> ExitCode {
+    pricelevel::setup_logger();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("collect") => collect_command(&args[1..]),
+        Some("compare") => compare_command(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            error!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Return the value following `flag`, if present
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
+
+fn run(args: &[String]) -> Result<ExitCode, String> {
+    let mut command = Command::new("cargo");
+    command.args(["bench", "-p", "orderbook-rs", "--bench", "benches"]);
+    if let Some(filter) = option_value(args, "--filter") {
+        command.args(["--", filter]);
+    }
+
+    info!("Running criterion benches...");
+    let status = command
+        .status()
+        .map_err(|e| format!("failed to run cargo bench: {e}"))?;
+    if !status.success() {
+        return Err(format!("cargo bench exited with {status}"));
+    }
+
+    collect_command(args)
+}
+
+fn collect_command(args: &[String]) -> Result<ExitCode, String> {
+    let criterion_dir = option_value(args, "--criterion-dir").unwrap_or(DEFAULT_CRITERION_DIR);
+    let output = option_value(args, "--output").unwrap_or(DEFAULT_REPORT_PATH);
+
+    let report = collect(Path::new(criterion_dir))?;
+    if report.scenarios.is_empty() {
+        return Err(format!("no criterion results found under {criterion_dir}"));
+    }
+
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("failed to serialize report: {e}"))?;
+    fs::write(output, json).map_err(|e| format!("failed to write {output}: {e}"))?;
+    info!("Wrote {} scenarios to {}", report.scenarios.len(), output);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Build a report from every `new/` result directory under `criterion_dir`
+fn collect(criterion_dir: &Path) -> Result<BenchReport, String> {
+    let mut result_dirs = Vec::new();
+    find_result_dirs(criterion_dir, &mut result_dirs)
+        .map_err(|e| format!("failed to read {}: {e}", criterion_dir.display()))?;
+
+    let mut report = BenchReport {
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        scenarios: BTreeMap::new(),
+    };
+
+    for dir in result_dirs {
+        match read_scenario(&dir) {
+            Ok((id, scenario)) => {
+                report.scenarios.insert(id, scenario);
+            }
+            Err(message) => warn!("Skipping {}: {}", dir.display(), message),
+        }
+    }
+
+    Ok(report)
+}
+
+fn find_result_dirs(dir: &Path, found: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().is_some_and(|name| name == "new") {
+            if path.join("benchmark.json").exists() {
+                found.push(path);
+            }
+        } else if path.file_name().is_none_or(|name| name != "report") {
+            find_result_dirs(&path, found)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&content).map_err(|e| format!("invalid JSON in {}: {e}", path.display()))
+}
+
+fn read_scenario(dir: &Path) -> Result<(String, ScenarioResult), String> {
+    let benchmark = read_json(&dir.join("benchmark.json"))?;
+    let estimates = read_json(&dir.join("estimates.json"))?;
+    let sample = read_json(&dir.join("sample.json"))?;
+
+    let id = benchmark["full_id"]
+        .as_str()
+        .ok_or("benchmark.json has no full_id")?
+        .to_string();
+    let group = benchmark["group_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let mean_ns = estimates["mean"]["point_estimate"]
+        .as_f64()
+        .ok_or("estimates.json has no mean")?;
+
+    let iters = number_array(&sample["iters"]);
+    let times = number_array(&sample["times"]);
+    let mut per_iteration: Vec<f64> = iters
+        .iter()
+        .zip(&times)
+        .filter(|(iters, _)| **iters > 0.0)
+        .map(|(iters, time)| time / iters)
+        .collect();
+    if per_iteration.is_empty() {
+        return Err("sample.json has no samples".to_string());
+    }
+    per_iteration.sort_by(f64::total_cmp);
+
+    let scenario = ScenarioResult {
+        group,
+        mean_ns,
+        p50_ns: percentile(&per_iteration, 50.0),
+        p99_ns: percentile(&per_iteration, 99.0),
+        ops_per_sec: if mean_ns > 0.0 { 1e9 / mean_ns } else { 0.0 },
+        samples: per_iteration.len(),
+    };
+    Ok((id, scenario))
+}
+
+fn number_array(value: &Value) -> Vec<f64> {
+    value
+        .as_array()
+        .map(|values| values.iter().filter_map(Value::as_f64).collect())
+        .unwrap_or_default()
+}
+
+/// Nearest-rank percentile of already sorted values
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn read_report(path: &str) -> Result<BenchReport, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("failed to read {path}: {e}"))?;
+    serde_json::from_str(&content).map_err(|e| format!("invalid report {path}: {e}"))
+}
+
+fn compare_command(args: &[String]) -> Result<ExitCode, String> {
+    let (Some(baseline_path), Some(current_path)) = (args.first(), args.get(1)) else {
+        return Err("compare needs a baseline and a current report".to_string());
+    };
+    let threshold = match option_value(args, "--threshold") {
+        Some(value) => value
+            .parse::<f64>()
+            .map_err(|e| format!("invalid threshold {value}: {e}"))?,
+        None => DEFAULT_THRESHOLD_PERCENT,
+    };
+
+    let baseline = read_report(baseline_path)?;
+    let current = read_report(current_path)?;
+
+    info!(
+        "{:<70} {:>12} {:>12} {:>9} {:>9}",
+        "scenario", "base p50", "curr p50", "p50 %", "p99 %"
+    );
+    let mut regressions = 0;
+    for (id, current_result) in &current.scenarios {
+        let Some(baseline_result) = baseline.scenarios.get(id) else {
+            info!("{:<70} (new)", id);
+            continue;
+        };
+
+        let p50_change = percent_change(baseline_result.p50_ns, current_result.p50_ns);
+        let p99_change = percent_change(baseline_result.p99_ns, current_result.p99_ns);
+        let regressed = p50_change > threshold;
+        if regressed {
+            regressions += 1;
+        }
+
+        info!(
+            "{:<70} {:>12.1} {:>12.1} {:>+8.2}% {:>+8.2}%{}",
+            id,
+            baseline_result.p50_ns,
+            current_result.p50_ns,
+            p50_change,
+            p99_change,
+            if regressed { "  REGRESSION" } else { "" }
+        );
+    }
+    for id in baseline.scenarios.keys() {
+        if !current.scenarios.contains_key(id) {
+            info!("{:<70} (missing)", id);
+        }
+    }
+
+    if regressions > 0 {
+        error!(
+            "{} scenario(s) regressed by more than {}% at p50",
+            regressions, threshold
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    info!("No regressions above {}%", threshold);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn percent_change(baseline: f64, current: f64) -> f64 {
+    if baseline <= 0.0 {
+        return 0.0;
+    }
+    (current - baseline) / baseline * 100.0
+}
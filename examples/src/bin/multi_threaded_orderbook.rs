@@ -1,10 +1,9 @@
-use orderbook_rs::OrderBook;
+use orderbook_rs::{MetricsReport, OrderBook};
 use pricelevel::{OrderId, Side, TimeInForce, setup_logger};
 use std::sync::{Arc, Barrier};
 use std::thread;
 use std::time::{Duration, Instant};
 use tracing::info;
-use uuid::Uuid;
 
 // Number of threads to use for the test
 const THREAD_COUNT: usize = 8;
@@ -31,7 +30,7 @@ fn run_performance_test() {
     populate_orderbook(&book, 1000);
 
     // Create thread performance counters
-    let mut operation_counters = vec![0; THREAD_COUNT];
+    let mut operation_counters = [0; THREAD_COUNT];
 
     // Synchronization barrier to ensure all threads start at the same time
     let barrier = Arc::new(Barrier::new(THREAD_COUNT + 1)); // +1 for main thread
@@ -181,10 +180,33 @@ fn run_performance_test() {
         info!("Thread {} ({}): {} operations", i, thread_type, count);
     }
 
+    // Print the latencies recorded by the book itself
+    print_latency_report(&book.metrics_report());
+
     // Print order book state after the test
     print_orderbook_state(&book);
 }
 
+fn print_latency_report(report: &MetricsReport) {
+    info!("\nLatencies (ns):");
+    for (operation, stats) in [
+        ("add", report.add_order),
+        ("cancel", report.cancel_order),
+        ("match", report.match_order),
+    ] {
+        info!(
+            "{:<6} count={} p50={} p90={} p99={} p99.9={} max={}",
+            operation,
+            stats.count,
+            stats.p50_ns,
+            stats.p90_ns,
+            stats.p99_ns,
+            stats.p999_ns,
+            stats.max_ns
+        );
+    }
+}
+
 fn populate_orderbook(book: &OrderBook, order_count: usize) {
     info!(
         "Populating OrderBook with {} initial orders...",
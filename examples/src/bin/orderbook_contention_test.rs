@@ -8,7 +8,6 @@ use std::sync::{Arc, Barrier, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use tracing::info;
-use uuid::Uuid;
 
 // Test parameters
 const THREAD_COUNT: usize = 12;
@@ -178,10 +177,10 @@ fn test_read_write_ratio() -> Result<(), String> {
                 }
 
                 // Update the operation counter
-                if let Ok(mut counters) = thread_counters.lock() {
-                    if thread_id < counters.len() {
-                        counters[thread_id] = local_counter;
-                    }
+                if let Ok(mut counters) = thread_counters.lock()
+                    && thread_id < counters.len()
+                {
+                    counters[thread_id] = local_counter;
                 }
 
                 local_counter
@@ -334,10 +333,10 @@ fn test_hot_spot_contention() -> Result<(), String> {
                 }
 
                 // Update the operation counter
-                if let Ok(mut counters) = thread_counters.lock() {
-                    if thread_id < counters.len() {
-                        counters[thread_id] = local_counter;
-                    }
+                if let Ok(mut counters) = thread_counters.lock()
+                    && thread_id < counters.len()
+                {
+                    counters[thread_id] = local_counter;
                 }
 
                 local_counter
@@ -489,9 +488,9 @@ fn test_price_level_distribution() -> Result<(), String> {
                             // Add limit buy/sell
                             let side = if op_type == 0 { Side::Buy } else { Side::Sell };
                             let price = if side == Side::Buy {
-                                10000 - (local_counter % max_level as u64) as u64 * 10
+                                10000 - (local_counter % max_level as u64) * 10
                             } else {
-                                10100 + (local_counter % max_level as u64) as u64 * 10
+                                10100 + (local_counter % max_level as u64) * 10
                             };
                             let _ = thread_book.add_limit_order(
                                 OrderId::new_uuid(),
@@ -542,10 +541,10 @@ fn test_price_level_distribution() -> Result<(), String> {
                 }
 
                 // Update the operation counter
-                if let Ok(mut counters) = thread_counters.lock() {
-                    if thread_id < counters.len() {
-                        counters[thread_id] = local_counter as usize;
-                    }
+                if let Ok(mut counters) = thread_counters.lock()
+                    && thread_id < counters.len()
+                {
+                    counters[thread_id] = local_counter as usize;
                 }
 
                 info!(
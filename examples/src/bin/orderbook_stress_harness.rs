@@ -0,0 +1,52 @@
+use orderbook_rs::{OrderBook, RunLength, StressConfig, StressHarness};
+use pricelevel::setup_logger;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+// Soak test parameters
+const SYMBOL: &str = "BTC/USD";
+const SEED: u64 = 2024;
+const THREADS: usize = 8;
+const DURATION_MS: u64 = 2000;
+
+fn main() {
+    setup_logger();
+    info!("OrderBook Stress Harness");
+    info!("========================");
+
+    let book: Arc<OrderBook<()>> = Arc::new(OrderBook::new(SYMBOL));
+    let config = StressConfig::default()
+        .with_threads(THREADS)
+        .with_ratios(5, 3, 2)
+        .with_seed(SEED)
+        .with_prices(10_000, 10, 20)
+        .with_preload(500)
+        .with_run_length(RunLength::Time(Duration::from_millis(DURATION_MS)));
+
+    info!("Running {:?}", config);
+    let report = StressHarness::new(config).run(&book);
+
+    info!("Orders added: {}", report.orders_added);
+    info!("Orders rejected: {}", report.orders_rejected);
+    info!(
+        "Market orders filled: {}/{}",
+        report.market_orders_filled, report.market_orders
+    );
+    info!(
+        "Orders cancelled: {}/{}",
+        report.orders_cancelled, report.cancels_attempted
+    );
+    info!(
+        "Throughput: {:.2} operations per second",
+        report.operations_per_second()
+    );
+    info!(
+        "Best bid: {:?}, best ask: {:?}",
+        report.best_bid, report.best_ask
+    );
+    info!("Validation: {}", report.validation);
+
+    report.assert_invariants();
+    info!("All invariants hold");
+}
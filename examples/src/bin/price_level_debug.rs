@@ -1,7 +1,7 @@
 // examples/src/bin/price_level_debug.rs
 
-use orderbook_rs::OrderBook;
-use pricelevel::{OrderId, Side, TimeInForce, setup_logger};
+use orderbook_rs::{BookBuilder, OrderBook};
+use pricelevel::setup_logger;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Barrier};
 use std::thread;
@@ -22,9 +22,6 @@ fn main() {
 
     info!("\nTesting with {} price levels...", price_levels);
 
-    // Create a fresh order book
-    let order_book = Arc::new(OrderBook::new(SYMBOL));
-
     // Pre-populate with fewer orders to avoid memory issues
     let min_orders = 10; // 10 orders per level
 
@@ -32,7 +29,7 @@ fn main() {
         "Setting up orders: {} per level x {} levels",
         min_orders, price_levels
     );
-    setup_orders_for_test(&order_book, price_levels, min_orders);
+    let order_book = Arc::new(build_book_for_test(price_levels, min_orders));
 
     // Verify book state
     let snapshot = order_book.create_snapshot(price_levels as usize);
@@ -138,31 +135,16 @@ fn main() {
     info!("Test completed in {:?}", elapsed);
 }
 
-/// Sets up orders for the price level test
-fn setup_orders_for_test(order_book: &OrderBook, price_levels: i32, orders_per_level: i32) {
-    let mut order_id = 0;
-
-    // Buy orders
-    for level in 0..price_levels {
-        let price = 10000 - (level as u64 * 10);
-
-        for _ in 0..orders_per_level {
-            let id = OrderId::from_u64(order_id);
-            order_id += 1;
-
-            let _ = order_book.add_limit_order(id, price, 10, Side::Buy, TimeInForce::Gtc, None);
-        }
-    }
-
-    // Sell orders
-    for level in 0..price_levels {
-        let price = 10100 + (level as u64 * 10);
-
-        for _ in 0..orders_per_level {
-            let id = OrderId::from_u64(order_id);
-            order_id += 1;
-
-            let _ = order_book.add_limit_order(id, price, 10, Side::Sell, TimeInForce::Gtc, None);
-        }
-    }
+/// Builds a fresh order book with orders for the price level test
+fn build_book_for_test(price_levels: i32, orders_per_level: i32) -> OrderBook {
+    let level = |price: u64| std::iter::repeat_n((price, 10), orders_per_level as usize);
+    let bids = (0..price_levels).flat_map(|i| level(10000 - (i as u64 * 10)));
+    let asks = (0..price_levels).flat_map(|i| level(10100 + (i as u64 * 10)));
+
+    BookBuilder::new(SYMBOL)
+        .starting_id(0)
+        .bids(bids)
+        .asks(asks)
+        .build()
+        .expect("failed to set up the order book")
 }
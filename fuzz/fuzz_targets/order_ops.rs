@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use orderbook_rs::OrderBook;
+use orderbook_rs::orderbook::testing::{Op, apply_op};
+
+fuzz_target!(|ops: Vec<Op>| {
+    let book: OrderBook<()> = OrderBook::new("FUZZ");
+    for op in ops {
+        // Rejections are expected, only panics and broken invariants are bugs
+        let _ = apply_op(&book, op);
+    }
+    let report = book.validate();
+    assert!(report.is_valid(), "{report}");
+});
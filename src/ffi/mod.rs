@@ -0,0 +1,587 @@
+//! C ABI over `OrderBook<()>`, built with the `ffi` feature, for embedding the
+//! engine in C, C++ or C# trading stacks.
+//!
+//! Every type and function is prefixed with `ob_`/`Ob` and laid out for
+//! `cbindgen` (see `cbindgen.toml`). A book is an opaque handle created by
+//! [`ob_book_new`] and released by [`ob_book_free`]; handles may be shared
+//! between threads. Order ids are the 16 bytes of the order's UUID, and
+//! [`ob_order_id_from_u64`] builds one from an integer id.
+//!
+//! Functions return an [`ObStatus`]. When it is not `Ok`,
+//! [`ob_last_error_message`] describes the failure on the calling thread.
+
+mod tests;
+
+use crate::orderbook::{OrderBook, OrderBookError, OrderBookSnapshot, RejectReason};
+use pricelevel::{MatchResult, OrderId, PriceLevelSnapshot, Side, TimeInForce};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+use uuid::Uuid;
+
+/// Side value for buy orders
+pub const OB_SIDE_BUY: u8 = 0;
+/// Side value for sell orders
+pub const OB_SIDE_SELL: u8 = 1;
+
+/// Time in force value for good-till-cancelled orders
+pub const OB_TIF_GTC: u8 = 0;
+/// Time in force value for immediate-or-cancel orders
+pub const OB_TIF_IOC: u8 = 1;
+/// Time in force value for fill-or-kill orders
+pub const OB_TIF_FOK: u8 = 2;
+/// Time in force value for orders expiring at the market close
+pub const OB_TIF_DAY: u8 = 3;
+/// Time in force value for good-till-date orders, expiring at `expiry`
+pub const OB_TIF_GTD: u8 = 4;
+
+/// Outcome of a call
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = -1,
+    /// An argument was out of range, such as an unknown side
+    InvalidArgument = -2,
+    /// The order is not resting in the book
+    NotFound = -3,
+    /// The side of the book has no orders
+    Empty = -4,
+    /// The book rejected the order, see `ob_last_reject_reason`
+    Rejected = -5,
+    /// The engine panicked. The book should not be used any further
+    Panic = -6,
+}
+
+/// An order id: the 16 bytes of its UUID, in network order
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObOrderId {
+    /// UUID bytes
+    pub bytes: [u8; 16],
+}
+
+impl From<ObOrderId> for OrderId {
+    fn from(id: ObOrderId) -> Self {
+        OrderId::from_uuid(Uuid::from_bytes(id.bytes))
+    }
+}
+
+impl From<OrderId> for ObOrderId {
+    fn from(id: OrderId) -> Self {
+        Self {
+            bytes: id.as_bytes(),
+        }
+    }
+}
+
+/// One aggregated price level
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObLevel {
+    /// Price of the level
+    pub price: u64,
+    /// Quantity displayed at the level
+    pub visible_quantity: u64,
+    /// Quantity resting at the level but not displayed
+    pub hidden_quantity: u64,
+    /// Number of orders at the level
+    pub order_count: u64,
+}
+
+impl From<&PriceLevelSnapshot> for ObLevel {
+    fn from(level: &PriceLevelSnapshot) -> Self {
+        Self {
+            price: level.price,
+            visible_quantity: level.visible_quantity,
+            hidden_quantity: level.hidden_quantity,
+            order_count: level.order_count as u64,
+        }
+    }
+}
+
+/// One trade of a match
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObTrade {
+    /// Id of the incoming order
+    pub taker_order_id: ObOrderId,
+    /// Id of the resting order
+    pub maker_order_id: ObOrderId,
+    /// Price of the trade
+    pub price: u64,
+    /// Quantity traded
+    pub quantity: u64,
+    /// Side of the incoming order, `OB_SIDE_BUY` or `OB_SIDE_SELL`
+    pub taker_side: u8,
+}
+
+/// Totals of a match
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObMatchSummary {
+    /// Quantity executed
+    pub executed_quantity: u64,
+    /// Quantity of the incoming order left unexecuted
+    pub remaining_quantity: u64,
+    /// Number of trades, which may exceed the trades written to the caller's buffer
+    pub trade_count: usize,
+}
+
+/// Opaque handle to an order book
+pub struct ObOrderBook {
+    book: OrderBook<()>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(CString, Option<RejectReason>)>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl ToString, reason: Option<RejectReason>) {
+    let message =
+        CString::new(message.to_string().replace('\0', " ")).expect("nul bytes were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some((message, reason)));
+}
+
+fn fail(status: ObStatus, message: impl ToString) -> ObStatus {
+    set_last_error(message, None);
+    status
+}
+
+fn reject(error: OrderBookError) -> ObStatus {
+    let reason = RejectReason::from(&error);
+    set_last_error(&error, Some(reason));
+    match error {
+        OrderBookError::OrderNotFound(_) => ObStatus::NotFound,
+        _ => ObStatus::Rejected,
+    }
+}
+
+/// Run `call`, turning a panic into `ObStatus::Panic` instead of unwinding into C
+fn guarded(call: impl FnOnce() -> ObStatus) -> ObStatus {
+    catch_unwind(AssertUnwindSafe(call))
+        .unwrap_or_else(|_| fail(ObStatus::Panic, "engine panicked"))
+}
+
+fn parse_side(side: u8) -> Result<Side, ObStatus> {
+    match side {
+        OB_SIDE_BUY => Ok(Side::Buy),
+        OB_SIDE_SELL => Ok(Side::Sell),
+        _ => Err(fail(
+            ObStatus::InvalidArgument,
+            format!("invalid side {side}"),
+        )),
+    }
+}
+
+fn side_value(side: Side) -> u8 {
+    match side {
+        Side::Buy => OB_SIDE_BUY,
+        Side::Sell => OB_SIDE_SELL,
+    }
+}
+
+fn parse_time_in_force(time_in_force: u8, expiry: u64) -> Result<TimeInForce, ObStatus> {
+    match time_in_force {
+        OB_TIF_GTC => Ok(TimeInForce::Gtc),
+        OB_TIF_IOC => Ok(TimeInForce::Ioc),
+        OB_TIF_FOK => Ok(TimeInForce::Fok),
+        OB_TIF_DAY => Ok(TimeInForce::Day),
+        OB_TIF_GTD => Ok(TimeInForce::Gtd(expiry)),
+        _ => Err(fail(
+            ObStatus::InvalidArgument,
+            format!("invalid time in force {time_in_force}"),
+        )),
+    }
+}
+
+/// The book behind a handle, or `NullPointer`
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`.
+unsafe fn book_ref<'a>(book: *const ObOrderBook) -> Result<&'a OrderBook<()>, ObStatus> {
+    // SAFETY: the caller guarantees the pointer is null or a live handle
+    match unsafe { book.as_ref() } {
+        Some(handle) => Ok(&handle.book),
+        None => Err(fail(ObStatus::NullPointer, "null order book handle")),
+    }
+}
+
+/// Collapse the `Result` the wrappers build into the status returned to C
+fn status(result: Result<(), ObStatus>) -> ObStatus {
+    result.err().unwrap_or(ObStatus::Ok)
+}
+
+/// Build the id of an order numbered `id`, as `OrderId::from_u64` does
+#[unsafe(no_mangle)]
+pub extern "C" fn ob_order_id_from_u64(id: u64) -> ObOrderId {
+    OrderId::from_u64(id).into()
+}
+
+/// Create an empty book for `symbol`, a nul-terminated UTF-8 string. Returns
+/// null if `symbol` is null or not UTF-8.
+///
+/// # Safety
+/// `symbol` must be null or point to a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_book_new(symbol: *const c_char) -> *mut ObOrderBook {
+    if symbol.is_null() {
+        fail(ObStatus::NullPointer, "null symbol");
+        return ptr::null_mut();
+    }
+    // SAFETY: the caller guarantees a nul-terminated string
+    let Ok(symbol) = unsafe { CStr::from_ptr(symbol) }.to_str() else {
+        fail(ObStatus::InvalidArgument, "symbol is not valid UTF-8");
+        return ptr::null_mut();
+    };
+    Box::into_raw(Box::new(ObOrderBook {
+        book: OrderBook::new(symbol),
+    }))
+}
+
+/// Release a book. Null is ignored.
+///
+/// # Safety
+/// `book` must be null or a handle from `ob_book_new` that is not used
+/// afterwards, by this or any other thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_book_free(book: *mut ObOrderBook) {
+    if !book.is_null() {
+        // SAFETY: the handle came from Box::into_raw in ob_book_new
+        drop(unsafe { Box::from_raw(book) });
+    }
+}
+
+/// Add a limit order, matching it if it crosses. `expiry` is only read for
+/// `OB_TIF_GTD` orders.
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_add_limit_order(
+    book: *const ObOrderBook,
+    order_id: ObOrderId,
+    price: u64,
+    quantity: u64,
+    side: u8,
+    time_in_force: u8,
+    expiry: u64,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            let side = parse_side(side)?;
+            let time_in_force = parse_time_in_force(time_in_force, expiry)?;
+            book.add_limit_order(order_id.into(), price, quantity, side, time_in_force, None)
+                .map_err(reject)?;
+            Ok(())
+        })())
+    })
+}
+
+/// Add an iceberg order showing `visible_quantity` and hiding `hidden_quantity`
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ob_add_iceberg_order(
+    book: *const ObOrderBook,
+    order_id: ObOrderId,
+    price: u64,
+    visible_quantity: u64,
+    hidden_quantity: u64,
+    side: u8,
+    time_in_force: u8,
+    expiry: u64,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            let side = parse_side(side)?;
+            let time_in_force = parse_time_in_force(time_in_force, expiry)?;
+            book.add_iceberg_order(
+                order_id.into(),
+                price,
+                visible_quantity,
+                hidden_quantity,
+                side,
+                time_in_force,
+                None,
+            )
+            .map_err(reject)?;
+            Ok(())
+        })())
+    })
+}
+
+/// Add a post-only order, rejected if it would cross
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_add_post_only_order(
+    book: *const ObOrderBook,
+    order_id: ObOrderId,
+    price: u64,
+    quantity: u64,
+    side: u8,
+    time_in_force: u8,
+    expiry: u64,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            let side = parse_side(side)?;
+            let time_in_force = parse_time_in_force(time_in_force, expiry)?;
+            book.add_post_only_order(order_id.into(), price, quantity, side, time_in_force, None)
+                .map_err(reject)?;
+            Ok(())
+        })())
+    })
+}
+
+/// Cancel a resting order. Returns `NotFound` if it is not resting
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_cancel_order(
+    book: *const ObOrderBook,
+    order_id: ObOrderId,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            match book.cancel_order(order_id.into()).map_err(reject)? {
+                Some(_) => Ok(()),
+                None => Err(fail(ObStatus::NotFound, "order is not resting")),
+            }
+        })())
+    })
+}
+
+/// Copy the trades of `match_result` into the caller's buffer and fill in the summary
+///
+/// # Safety
+/// `trades` must be null or valid for `trades_capacity` writes, and `summary`
+/// null or valid for one write.
+unsafe fn write_match(
+    match_result: &MatchResult,
+    trades: *mut ObTrade,
+    trades_capacity: usize,
+    summary: *mut ObMatchSummary,
+) {
+    let transactions = match_result.transactions.as_vec();
+    if !trades.is_null() {
+        for (index, transaction) in transactions.iter().take(trades_capacity).enumerate() {
+            let trade = ObTrade {
+                taker_order_id: transaction.taker_order_id.into(),
+                maker_order_id: transaction.maker_order_id.into(),
+                price: transaction.price,
+                quantity: transaction.quantity,
+                taker_side: side_value(transaction.taker_side),
+            };
+            // SAFETY: index < trades_capacity, which the caller guarantees is writable
+            unsafe { trades.add(index).write(trade) };
+        }
+    }
+    if !summary.is_null() {
+        // SAFETY: the caller guarantees summary is writable
+        unsafe {
+            summary.write(ObMatchSummary {
+                executed_quantity: match_result.executed_quantity(),
+                remaining_quantity: match_result.remaining_quantity,
+                trade_count: transactions.len(),
+            })
+        };
+    }
+}
+
+/// Execute a market order against the book.
+///
+/// Up to `trades_capacity` trades are written to `trades` and the totals to
+/// `summary`; either may be null. Returns `Rejected` if the book has no
+/// liquidity on the other side.
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`, `trades` null or
+/// valid for `trades_capacity` writes, and `summary` null or valid for one write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_submit_market_order(
+    book: *const ObOrderBook,
+    order_id: ObOrderId,
+    quantity: u64,
+    side: u8,
+    trades: *mut ObTrade,
+    trades_capacity: usize,
+    summary: *mut ObMatchSummary,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            let side = parse_side(side)?;
+            let match_result = book
+                .submit_market_order(order_id.into(), quantity, side)
+                .map_err(reject)?;
+            // SAFETY: forwarded from the caller
+            unsafe { write_match(&match_result, trades, trades_capacity, summary) };
+            Ok(())
+        })())
+    })
+}
+
+/// Best price of one side, written to `price`
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`, and `price` null
+/// or valid for one write.
+unsafe fn best_price(book: *const ObOrderBook, price: *mut u64, side: Side) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            if price.is_null() {
+                return Err(fail(ObStatus::NullPointer, "null price"));
+            }
+            let best = match side {
+                Side::Buy => book.best_bid(),
+                Side::Sell => book.best_ask(),
+            };
+            let best = best.ok_or_else(|| fail(ObStatus::Empty, "side has no orders"))?;
+            // SAFETY: checked non-null, the caller guarantees it is writable
+            unsafe { price.write(best) };
+            Ok(())
+        })())
+    })
+}
+
+/// Write the highest bid to `price`. Returns `Empty` if there are no bids
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`, and `price` null
+/// or valid for one write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_best_bid(book: *const ObOrderBook, price: *mut u64) -> ObStatus {
+    // SAFETY: forwarded from the caller
+    unsafe { best_price(book, price, Side::Buy) }
+}
+
+/// Write the lowest ask to `price`. Returns `Empty` if there are no asks
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`, and `price` null
+/// or valid for one write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_best_ask(book: *const ObOrderBook, price: *mut u64) -> ObStatus {
+    // SAFETY: forwarded from the caller
+    unsafe { best_price(book, price, Side::Sell) }
+}
+
+/// Copy up to `capacity` levels to `out` and store how many were written in `len`
+///
+/// # Safety
+/// `out` must be null or valid for `capacity` writes, and `len` null or valid
+/// for one write.
+unsafe fn write_levels(
+    levels: &[PriceLevelSnapshot],
+    out: *mut ObLevel,
+    capacity: usize,
+    len: *mut usize,
+) {
+    let written = if out.is_null() {
+        0
+    } else {
+        levels.len().min(capacity)
+    };
+    for (index, level) in levels.iter().take(written).enumerate() {
+        // SAFETY: index < capacity, which the caller guarantees is writable
+        unsafe { out.add(index).write(level.into()) };
+    }
+    if !len.is_null() {
+        // SAFETY: the caller guarantees len is writable
+        unsafe { len.write(written) };
+    }
+}
+
+/// Copy the `depth` best levels of each side, best first, writing at most
+/// `bids_capacity` bids and `asks_capacity` asks and the number written to
+/// `bids_len` and `asks_len`. Any buffer or length pointer may be null.
+///
+/// # Safety
+/// `book` must be null or a live handle from `ob_book_new`; each buffer null
+/// or valid for its capacity in writes, and each length null or valid for one write.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ob_snapshot(
+    book: *const ObOrderBook,
+    depth: usize,
+    bids: *mut ObLevel,
+    bids_capacity: usize,
+    bids_len: *mut usize,
+    asks: *mut ObLevel,
+    asks_capacity: usize,
+    asks_len: *mut usize,
+) -> ObStatus {
+    guarded(|| {
+        status((|| {
+            // SAFETY: forwarded from the caller
+            let book = unsafe { book_ref(book) }?;
+            let snapshot: OrderBookSnapshot = book.create_snapshot(depth);
+            // SAFETY: forwarded from the caller
+            unsafe {
+                write_levels(&snapshot.bids, bids, bids_capacity, bids_len);
+                write_levels(&snapshot.asks, asks, asks_capacity, asks_len);
+            }
+            Ok(())
+        })())
+    })
+}
+
+/// Copy the message of the last failure on this thread into `buffer` as a
+/// nul-terminated string, truncated to `capacity` bytes.
+///
+/// Returns the size the full message needs, terminator included, or 0 if no
+/// call has failed on this thread.
+///
+/// # Safety
+/// `buffer` must be null or valid for `capacity` writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_last_error_message(buffer: *mut c_char, capacity: usize) -> usize {
+    LAST_ERROR.with(|last| {
+        let last = last.borrow();
+        let Some((message, _)) = last.as_ref() else {
+            return 0;
+        };
+        let bytes = message.as_bytes_with_nul();
+        if !buffer.is_null() && capacity > 0 {
+            let copied = bytes.len().min(capacity);
+            // SAFETY: copied <= capacity, which the caller guarantees is writable
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr().cast::<c_char>(), buffer, copied);
+                *buffer.add(copied - 1) = 0;
+            }
+        }
+        bytes.len()
+    })
+}
+
+/// Numeric code of the `RejectReason` of the last rejection on this thread,
+/// or 0 if the last failure was not a rejection by the book
+#[unsafe(no_mangle)]
+pub extern "C" fn ob_last_reject_reason() -> u16 {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .and_then(|(_, reason)| reason.as_ref())
+            .map_or(0, RejectReason::code)
+    })
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use std::ffi::{CStr, c_char};
+    use std::ptr;
+
+    fn new_book() -> *mut ObOrderBook {
+        let book = unsafe { ob_book_new(c"TEST".as_ptr()) };
+        assert!(!book.is_null());
+        book
+    }
+
+    fn id(value: u64) -> ObOrderId {
+        ob_order_id_from_u64(value)
+    }
+
+    fn last_error() -> String {
+        let mut buffer = [0 as c_char; 128];
+        let needed = unsafe { ob_last_error_message(buffer.as_mut_ptr(), buffer.len()) };
+        assert!(needed > 0);
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_new_rejects_null_symbol() {
+        assert!(unsafe { ob_book_new(ptr::null()) }.is_null());
+        assert_eq!(last_error(), "null symbol");
+        unsafe { ob_book_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_order_id_round_trips() {
+        let order_id = OrderId::from_u64(42);
+        assert_eq!(OrderId::from(ObOrderId::from(order_id)), order_id);
+        assert_eq!(id(42), ObOrderId::from(order_id));
+    }
+
+    #[test]
+    fn test_add_limit_orders_and_best_prices() {
+        let book = new_book();
+        unsafe {
+            assert_eq!(
+                ob_add_limit_order(book, id(1), 100, 10, OB_SIDE_BUY, OB_TIF_GTC, 0),
+                ObStatus::Ok
+            );
+            assert_eq!(
+                ob_add_limit_order(book, id(2), 105, 5, OB_SIDE_SELL, OB_TIF_GTC, 0),
+                ObStatus::Ok
+            );
+            let mut price = 0;
+            assert_eq!(ob_best_bid(book, &mut price), ObStatus::Ok);
+            assert_eq!(price, 100);
+            assert_eq!(ob_best_ask(book, &mut price), ObStatus::Ok);
+            assert_eq!(price, 105);
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_best_price_of_empty_side() {
+        let book = new_book();
+        unsafe {
+            let mut price = 7;
+            assert_eq!(ob_best_bid(book, &mut price), ObStatus::Empty);
+            assert_eq!(price, 7);
+            assert_eq!(ob_best_ask(book, ptr::null_mut()), ObStatus::NullPointer);
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let book = new_book();
+        unsafe {
+            assert_eq!(
+                ob_add_limit_order(book, id(1), 100, 10, 9, OB_TIF_GTC, 0),
+                ObStatus::InvalidArgument
+            );
+            assert_eq!(last_error(), "invalid side 9");
+            assert_eq!(
+                ob_add_limit_order(book, id(1), 100, 10, OB_SIDE_BUY, 9, 0),
+                ObStatus::InvalidArgument
+            );
+            assert_eq!(
+                ob_add_limit_order(ptr::null(), id(1), 100, 10, OB_SIDE_BUY, OB_TIF_GTC, 0),
+                ObStatus::NullPointer
+            );
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_cancel_order() {
+        let book = new_book();
+        unsafe {
+            ob_add_limit_order(book, id(1), 100, 10, OB_SIDE_BUY, OB_TIF_GTC, 0);
+            assert_eq!(ob_cancel_order(book, id(1)), ObStatus::Ok);
+            assert_eq!(ob_cancel_order(book, id(1)), ObStatus::NotFound);
+            assert_eq!(ob_best_bid(book, &mut 0), ObStatus::Empty);
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_market_order_writes_trades_up_to_capacity() {
+        let book = new_book();
+        unsafe {
+            ob_add_limit_order(book, id(1), 100, 5, OB_SIDE_SELL, OB_TIF_GTC, 0);
+            ob_add_limit_order(book, id(2), 101, 5, OB_SIDE_SELL, OB_TIF_GTC, 0);
+
+            let mut trades = [ObTrade {
+                taker_order_id: id(0),
+                maker_order_id: id(0),
+                price: 0,
+                quantity: 0,
+                taker_side: 0,
+            }; 1];
+            let mut summary = ObMatchSummary::default();
+            assert_eq!(
+                ob_submit_market_order(
+                    book,
+                    id(3),
+                    8,
+                    OB_SIDE_BUY,
+                    trades.as_mut_ptr(),
+                    trades.len(),
+                    &mut summary,
+                ),
+                ObStatus::Ok
+            );
+            assert_eq!(summary.executed_quantity, 8);
+            assert_eq!(summary.remaining_quantity, 0);
+            assert_eq!(summary.trade_count, 2);
+            assert_eq!(trades[0].maker_order_id, id(1));
+            assert_eq!(trades[0].taker_order_id, id(3));
+            assert_eq!(trades[0].price, 100);
+            assert_eq!(trades[0].quantity, 5);
+            assert_eq!(trades[0].taker_side, OB_SIDE_BUY);
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_rejection_reports_reason() {
+        let book = new_book();
+        unsafe {
+            assert_eq!(
+                ob_submit_market_order(
+                    book,
+                    id(1),
+                    5,
+                    OB_SIDE_BUY,
+                    ptr::null_mut(),
+                    0,
+                    ptr::null_mut(),
+                ),
+                ObStatus::Rejected
+            );
+            assert_ne!(ob_last_reject_reason(), 0);
+
+            ob_add_limit_order(book, id(2), 100, 5, OB_SIDE_SELL, OB_TIF_GTC, 0);
+            assert_eq!(
+                ob_add_post_only_order(book, id(3), 100, 5, OB_SIDE_BUY, OB_TIF_GTC, 0),
+                ObStatus::Rejected
+            );
+            assert_ne!(ob_last_reject_reason(), 0);
+
+            assert_eq!(ob_best_bid(book, &mut 0), ObStatus::Empty);
+            assert_eq!(ob_last_reject_reason(), 0);
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_copies_levels() {
+        let book = new_book();
+        unsafe {
+            ob_add_limit_order(book, id(1), 100, 10, OB_SIDE_BUY, OB_TIF_GTC, 0);
+            ob_add_limit_order(book, id(2), 99, 4, OB_SIDE_BUY, OB_TIF_GTC, 0);
+            ob_add_iceberg_order(book, id(3), 110, 2, 8, OB_SIDE_SELL, OB_TIF_GTC, 0);
+
+            let mut bids = [ObLevel::default(); 1];
+            let mut asks = [ObLevel::default(); 4];
+            let (mut bids_len, mut asks_len) = (0, 0);
+            assert_eq!(
+                ob_snapshot(
+                    book,
+                    10,
+                    bids.as_mut_ptr(),
+                    bids.len(),
+                    &mut bids_len,
+                    asks.as_mut_ptr(),
+                    asks.len(),
+                    &mut asks_len,
+                ),
+                ObStatus::Ok
+            );
+            assert_eq!(bids_len, 1);
+            assert_eq!(bids[0].price, 100);
+            assert_eq!(bids[0].visible_quantity, 10);
+            assert_eq!(asks_len, 1);
+            assert_eq!(
+                asks[0],
+                ObLevel {
+                    price: 110,
+                    visible_quantity: 2,
+                    hidden_quantity: 8,
+                    order_count: 1,
+                }
+            );
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_last_error_message_truncates() {
+        assert_eq!(
+            unsafe { ob_add_limit_order(ptr::null(), id(1), 1, 1, 0, 0, 0) },
+            ObStatus::NullPointer
+        );
+        let mut buffer = [1 as c_char; 5];
+        let needed = unsafe { ob_last_error_message(buffer.as_mut_ptr(), buffer.len()) };
+        assert_eq!(needed, "null order book handle".len() + 1);
+        let message = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        assert_eq!(message.to_str().unwrap(), "null");
+    }
+}
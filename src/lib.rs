@@ -12,9 +12,9 @@
 //!
 //! - **Advanced Order Matching**: Efficient matching algorithm for both market and limit orders, correctly handling complex order types and partial fills.
 //!
-//! - **Performance Metrics**: Built-in statistics tracking for benchmarking and monitoring system performance.
+//! - **Performance Metrics**: Built-in statistics tracking for benchmarking and monitoring system performance. Enable the `metrics` feature to record add, cancel and match latencies into HDR histograms, read back with `OrderBook::metrics_report()`.
 //!
-//! - **Memory Efficient**: Designed to scale to millions of orders with minimal memory overhead.
+//! - **Memory Efficient**: Designed to scale to millions of orders with minimal memory overhead. For extreme-scale simulations, `OrderBook::with_compact_storage` keeps each resting order as an interned id handle, price, quantity and sequence in 32 bytes.
 //!
 //! ## Design Goals
 //!
@@ -153,10 +153,61 @@
 
 pub mod orderbook;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "python")]
+pub mod python;
+
 mod utils;
 
-pub use orderbook::{OrderBook, OrderBookError, OrderBookSnapshot};
-pub use utils::current_time_millis;
+#[cfg(feature = "anonymize")]
+pub use orderbook::Anonymizer;
+#[cfg(feature = "audit")]
+pub use orderbook::AuditCheckpoint;
+#[cfg(not(target_arch = "wasm32"))]
+pub use orderbook::DepthFeedThread;
+pub use orderbook::{
+    AcceptedOrder, AccountNotional, Amendment, AskView, AuctionEquilibrium, AuctionResult,
+    AuctionTimeInForce, BboChange, BboListener, BidView, BookBuilder, BookStats,
+    CONSISTENT_READ_ATTEMPTS, CacheInvalidation, CancelledOrder, CapAction, ChecksumFormat,
+    ChildActivation, Command, CommandOutcome, CompactOrder, CompactOrderBook, ConflatedDepth,
+    ContingentOrders, DarkMatch, DarkMatching, DarkOrder, DepthLimitAction, DepthListener,
+    DepthTotals, DepthUpdate, DeterministicOrderBook, DuplicateOrderIdAction, EngineHandle,
+    EngineLoop, EventListener, ExecType, ExecutionReport, ExecutionState, ExpiredOrder,
+    FINISHED_ORDERS_RETAINED, FeeSchedule, FeedMessage, FillNotification, FollowerBook,
+    FollowerStatus, ImpliedExecution, ImpliedMatchingEngine, ImpliedQuote, ImpliedSpreadQuote,
+    KillReport, KillScope, L3Level, L3Order, LevelChange, LevelDelta, LevelFill, LevelIter,
+    LevelOperation, LevelSummary, MAX_PRICE_DECIMALS, MatchDepthLimit, MemoryPressure,
+    MemoryPressureEvent, MemoryPressureListener, MemoryStats, MemoryUsage, MemoryWatermarks,
+    MidpointPeg, MidpointRounding, MultiBookSnapshot, NotionalStats, OhlcvBar, OrderBook,
+    OrderBookError, OrderBookEvent, OrderBookL3Snapshot, OrderBookManager, OrderBookOptions,
+    OrderBookSnapshot, OrderConstraints, OrderIter, OrderPage, OrderReject, OrderStatus,
+    OverflowPolicy, PoolConfig, PoolStats, Price, PriceScale, PulledQuotes, Qty, Quote,
+    QuoteProtection, QuoteSide, RateLimit, RateLimitScope, RateLimiter, RejectReason, ReplayEngine,
+    ReplayOperation, ReplayRecord, ReplayStep, ReplayStop, RepricedOrder, ReserveRefresh,
+    RestingCap, RestingCaps, RoundingMode, RunLength, SNAPSHOT_CSV_HEADER, SequencedFeedMessage,
+    Session, SessionSchedule, SessionTransition, ShardExecutor, ShortSaleCheck, ShortSaleReference,
+    ShortSaleRule, SideMemory, SignalFired, SignalListener, SignalPredicate, SignedLevel,
+    SignedOrderUpdate, SignedSnapshot, SignedTrade, SnapshotCsvWriter, SnapshotDiff,
+    SpecialPriceOrder, SpecialPriceSettlement, StopLeg, StressConfig, StressHarness, StressReport,
+    StructureMemory, SubTickHandling, SweepGuard, SymbolInfo, SymbolRegistry, TRADE_CSV_HEADER,
+    TopOfBook, TradeChannel, TradeCondition, TradeConditions, TradeCsvWriter, TradeFees,
+    TradeReport, TradeTape, TradingHalt, TradingState, ValidationIssue, ValidationReport,
+    VersionedOptions, VersionedSnapshot, Watermark, crc32, execution_report_listener,
+    levels_checksum, short_sale_price_test,
+};
+#[cfg(feature = "arena")]
+pub use orderbook::{ArenaOrderBook, OrderArena, OrderHandle};
+#[cfg(feature = "async_api")]
+pub use orderbook::{AsyncOrderBook, OrderAck};
+#[cfg(feature = "itch")]
+pub use orderbook::{ItchMessage, ItchReader};
+#[cfg(feature = "metrics")]
+pub use orderbook::{LatencyStats, MetricsReport};
+#[cfg(feature = "parquet")]
+pub use orderbook::{SnapshotParquetWriter, TradeParquetWriter};
+pub use utils::{Clock, ManualClock, SystemClock, current_time_millis, current_time_nanos};
 
 /// Legacy type alias for `OrderBook<()>` to maintain backward compatibility.
 ///
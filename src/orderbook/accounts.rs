@@ -0,0 +1,117 @@
+//! Index of resting orders by the account that owns them
+
+use super::book::OrderBook;
+use super::constraints::OrderConstraints;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use pricelevel::{MatchResult, OrderId, OrderType, Side};
+use std::sync::Arc;
+use tracing::trace;
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Add an order owned by `account_id`, automatically matching it if it's aggressive.
+    ///
+    /// While any part of the order rests it is listed by
+    /// [`orders_for_account`](Self::orders_for_account). Price and quantity
+    /// amendments keep the owner.
+    pub fn add_order_for_account(
+        &self,
+        order: OrderType<T>,
+        account_id: &str,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order_as(order, OrderConstraints::default(), Some(account_id))
+    }
+
+    /// Submit a market order for `account_id`, refused if a kill switch
+    /// covers the account
+    pub fn submit_market_order_for_account(
+        &self,
+        id: OrderId,
+        quantity: u64,
+        side: Side,
+        account_id: &str,
+    ) -> Result<MatchResult, OrderBookError> {
+        self.match_market_order_as(id, quantity, side, Some(account_id))
+    }
+
+    /// Account owning a resting order, if it was added with one
+    pub fn order_account(&self, order_id: OrderId) -> Option<String> {
+        self.order_accounts
+            .get(&order_id)
+            .map(|account| account.clone())
+    }
+
+    /// Resting orders owned by `account_id`, in acceptance order
+    pub fn orders_for_account(&self, account_id: &str) -> Vec<Arc<OrderType<T>>> {
+        let mut orders: Vec<_> = self
+            .account_order_ids(account_id)
+            .into_iter()
+            .filter_map(|order_id| {
+                let sequence = self.order_sequence(order_id)?;
+                Some((sequence, self.get_order(order_id)?))
+            })
+            .collect();
+        orders.sort_unstable_by_key(|(sequence, _)| *sequence);
+        orders.into_iter().map(|(_, order)| order).collect()
+    }
+
+    /// Cancel every resting order owned by `account_id`, returning the cancelled
+    /// orders in acceptance order.
+    ///
+    /// Orders that trade away while the cancellation runs are skipped. A price
+    /// level refusing a cancel is reported as a `PriceLevelFault` event and the
+    /// order stays in the book.
+    pub fn cancel_all_for_account(&self, account_id: &str) -> Vec<Arc<OrderType<T>>> {
+        // Collect first: cancelling updates the index
+        let order_ids: Vec<OrderId> = self
+            .orders_for_account(account_id)
+            .iter()
+            .map(|order| order.id())
+            .collect();
+        trace!(
+            "Order book {}: Cancelling {} orders of account {}",
+            self.symbol,
+            order_ids.len(),
+            account_id
+        );
+
+        let mut cancelled = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            match self.cancel_order(order_id) {
+                Ok(Some(order)) => cancelled.push(order),
+                Ok(None) => {}
+                Err(error) => self.emit_event(&OrderBookEvent::PriceLevelFault(error)),
+            }
+        }
+        cancelled
+    }
+
+    fn account_order_ids(&self, account_id: &str) -> Vec<OrderId> {
+        self.account_orders
+            .get(account_id)
+            .map(|orders| orders.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record that a resting order belongs to `account_id`
+    pub(super) fn index_account(&self, order_id: OrderId, account_id: &str) {
+        self.order_accounts.insert(order_id, account_id.to_string());
+        self.account_orders
+            .entry(account_id.to_string())
+            .or_default()
+            .insert(order_id);
+    }
+
+    /// Drop an order that is no longer resting from the account index
+    pub(super) fn unindex_account(&self, order_id: OrderId) {
+        if let Some((_, account_id)) = self.order_accounts.remove(&order_id) {
+            self.account_orders.remove_if_mut(&account_id, |_, orders| {
+                orders.remove(&order_id);
+                orders.is_empty()
+            });
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! History of the price and quantity changes made to each order.
+//!
+//! Off unless [`OrderBook::enable_amendment_history`] is called. Every
+//! successful [`OrderBook::update_order`] other than a cancel is then recorded
+//! against the order's id, keeping the latest amendments of each order in a
+//! ring buffer. The history outlives the order, so amendments can be reviewed
+//! after it filled or was cancelled, until
+//! [`remove_amendment_history`](OrderBook::remove_amendment_history) drops it.
+
+use super::book::OrderBook;
+use super::modifications::OrderQuantity;
+use dashmap::DashMap;
+use pricelevel::{OrderId, OrderType};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::trace;
+
+/// A change made to an order's price or quantity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Amendment {
+    /// Position of the amendment among every amendment of the book, starting at 1
+    pub sequence: u64,
+    /// Id of the amended order
+    pub order_id: OrderId,
+    /// Price before the amendment
+    pub old_price: u64,
+    /// Price after the amendment
+    pub new_price: u64,
+    /// Total quantity, visible and hidden, before the amendment
+    pub old_quantity: u64,
+    /// Total quantity, visible and hidden, after the amendment
+    pub new_quantity: u64,
+    /// When the order was amended, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+/// Recent amendments of every amended order
+pub(super) struct AmendmentLog {
+    /// Amendments kept per order
+    capacity: usize,
+    last_sequence: AtomicU64,
+    orders: DashMap<OrderId, VecDeque<Amendment>>,
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Record the amendments made from now on, keeping the latest `capacity`
+    /// of each order, at least one. Enabling the history again forgets the
+    /// amendments recorded so far
+    pub fn enable_amendment_history(&mut self, capacity: usize) {
+        trace!(
+            "Order book {}: Keeping {} amendments per order",
+            self.symbol, capacity
+        );
+        self.amendments = Some(AmendmentLog {
+            capacity: capacity.max(1),
+            last_sequence: AtomicU64::new(0),
+            orders: DashMap::new(),
+        });
+    }
+
+    /// Amendments of an order still retained, oldest first. Empty if the
+    /// order was never amended or the history is not enabled
+    pub fn amendment_history(&self, order_id: OrderId) -> Vec<Amendment> {
+        self.amendments
+            .as_ref()
+            .and_then(|log| log.orders.get(&order_id))
+            .map(|history| history.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Stop keeping the amendments of an order, returning them
+    pub fn remove_amendment_history(&self, order_id: OrderId) -> Vec<Amendment> {
+        self.amendments
+            .as_ref()
+            .and_then(|log| log.orders.remove(&order_id))
+            .map(|(_, history)| history.into())
+            .unwrap_or_default()
+    }
+
+    /// Record an amendment applied to `before`, yielding `after`
+    pub(super) fn record_amendment(&self, before: &OrderType<T>, after: &OrderType<T>) {
+        let Some(log) = &self.amendments else {
+            return;
+        };
+        let amendment = Amendment {
+            sequence: log.last_sequence.fetch_add(1, Ordering::Relaxed) + 1,
+            order_id: before.id(),
+            old_price: before.price(),
+            new_price: after.price(),
+            old_quantity: before.total_quantity(),
+            new_quantity: after.total_quantity(),
+            timestamp: self.now(),
+        };
+        let mut history = log.orders.entry(amendment.order_id).or_default();
+        if history.len() == log.capacity {
+            history.pop_front();
+        }
+        history.push_back(amendment);
+    }
+}
@@ -0,0 +1,108 @@
+//! Market microstructure measures: volume imbalance, weighted mid price and order-flow imbalance
+
+use super::book::OrderBook;
+use super::snapshot::OrderBookSnapshot;
+use pricelevel::PriceLevelSnapshot;
+use tracing::trace;
+
+/// Visible quantity of the first `depth` levels, which snapshots list best first
+fn top_volume(levels: &[PriceLevelSnapshot], depth: usize) -> u64 {
+    levels
+        .iter()
+        .take(depth)
+        .map(|level| level.visible_quantity)
+        .sum()
+}
+
+impl OrderBookSnapshot {
+    /// Imbalance between the visible bid and ask quantity of the top `levels`
+    /// levels, `(bids - asks) / (bids + asks)`.
+    ///
+    /// Ranges from -1 (only asks) to 1 (only bids). Returns `None` if there is
+    /// no visible quantity on either side.
+    pub fn volume_imbalance(&self, levels: usize) -> Option<f64> {
+        let bid_volume = top_volume(&self.bids, levels) as f64;
+        let ask_volume = top_volume(&self.asks, levels) as f64;
+        let total = bid_volume + ask_volume;
+        let imbalance = (total > 0.0).then(|| (bid_volume - ask_volume) / total);
+        trace!("volume_imbalance({}): {:?}", levels, imbalance);
+        imbalance
+    }
+
+    /// Mid price weighted by the visible quantity at the top of book, also
+    /// known as the micro-price.
+    ///
+    /// Each price is weighted by the quantity on the opposite side, so the
+    /// result leans toward the side with less quantity, where the next trade
+    /// is more likely. Returns `None` unless both sides have visible quantity.
+    pub fn weighted_mid_price(&self) -> Option<f64> {
+        let (bid_price, bid_quantity) = self.best_bid()?;
+        let (ask_price, ask_quantity) = self.best_ask()?;
+        let total = (bid_quantity + ask_quantity) as f64;
+        let weighted = (total > 0.0).then(|| {
+            (bid_price as f64 * ask_quantity as f64 + ask_price as f64 * bid_quantity as f64)
+                / total
+        });
+        trace!("weighted_mid_price: {:?}", weighted);
+        weighted
+    }
+
+    /// Order-flow imbalance at the top of book between `previous` and this
+    /// snapshot (Cont, Kukanov and Stoikov).
+    ///
+    /// Positive values mean buying pressure: bid quantity added or a higher
+    /// bid, ask quantity removed or a higher ask. A side only contributes
+    /// when it has a best price in both snapshots.
+    pub fn order_flow_imbalance(&self, previous: &OrderBookSnapshot) -> i64 {
+        let bid_flow = match (previous.best_bid(), self.best_bid()) {
+            (Some((previous_price, previous_quantity)), Some((price, quantity))) => {
+                let added = if price >= previous_price { quantity } else { 0 };
+                let removed = if price <= previous_price {
+                    previous_quantity
+                } else {
+                    0
+                };
+                added as i64 - removed as i64
+            }
+            _ => 0,
+        };
+        let ask_flow = match (previous.best_ask(), self.best_ask()) {
+            (Some((previous_price, previous_quantity)), Some((price, quantity))) => {
+                let added = if price <= previous_price { quantity } else { 0 };
+                let removed = if price >= previous_price {
+                    previous_quantity
+                } else {
+                    0
+                };
+                added as i64 - removed as i64
+            }
+            _ => 0,
+        };
+        let imbalance = bid_flow - ask_flow;
+        trace!("order_flow_imbalance: {}", imbalance);
+        imbalance
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Imbalance between the visible bid and ask quantity of the top `levels`
+    /// levels. See [`OrderBookSnapshot::volume_imbalance`]
+    pub fn volume_imbalance(&self, levels: usize) -> Option<f64> {
+        self.create_snapshot(levels).volume_imbalance(levels)
+    }
+
+    /// Mid price weighted by the visible quantity at the top of book. See
+    /// [`OrderBookSnapshot::weighted_mid_price`]
+    pub fn weighted_mid_price(&self) -> Option<f64> {
+        self.create_snapshot(1).weighted_mid_price()
+    }
+
+    /// Order-flow imbalance at the top of book since `previous` was taken.
+    /// See [`OrderBookSnapshot::order_flow_imbalance`]
+    pub fn order_flow_imbalance_since(&self, previous: &OrderBookSnapshot) -> i64 {
+        self.create_snapshot(1).order_flow_imbalance(previous)
+    }
+}
@@ -0,0 +1,174 @@
+//! Pseudonymization of exported order flow, enabled by the `anonymize` feature
+
+use super::private::order_to_unit_type;
+use super::replay::{ReplayOperation, ReplayRecord};
+use super::snapshot::OrderBookL3Snapshot;
+use hmac::{Hmac, Mac};
+use pricelevel::{OrderId, OrderType, Transaction};
+use sha2::Sha256;
+use uuid::{Builder, Uuid};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Domains separating the pseudonyms of different kinds of identifiers, so the
+/// same input never maps to the same output across kinds
+const ORDER_DOMAIN: &[u8] = b"order";
+const TRANSACTION_DOMAIN: &[u8] = b"transaction";
+const ACCOUNT_DOMAIN: &[u8] = b"account";
+const CLIENT_TAG_DOMAIN: &[u8] = b"client-tag";
+const TIMESTAMP_DOMAIN: &[u8] = b"timestamp";
+
+/// Rewrites journals, tapes and snapshots so they can be shared as research
+/// datasets without revealing who traded.
+///
+/// Order ids, transaction ids, account ids and client tags are replaced by
+/// pseudonyms derived with HMAC-SHA256 under a secret key. The mapping is
+/// consistent, so every reference to an order in a dataset, or in several
+/// datasets anonymized with the same key, still points to the same pseudonym,
+/// while recovering the original ids requires the key. Extra fields of orders
+/// are dropped, and timestamps can be moved by a bounded pseudo-random jitter.
+pub struct Anonymizer {
+    key: Vec<u8>,
+    timestamp_jitter_ms: u64,
+}
+
+impl Anonymizer {
+    /// Create an anonymizer keyed with `key`, which should be random and kept secret
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: key.as_ref().to_vec(),
+            timestamp_jitter_ms: 0,
+        }
+    }
+
+    /// Move every timestamp by up to `max_jitter_ms` milliseconds either way.
+    ///
+    /// The jitter of a timestamp is derived from the key and the record it
+    /// belongs to, so anonymizing the same data twice gives the same result.
+    /// Records close in time may swap order.
+    pub fn with_timestamp_jitter(mut self, max_jitter_ms: u64) -> Self {
+        self.timestamp_jitter_ms = max_jitter_ms;
+        self
+    }
+
+    fn digest(&self, domain: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(domain);
+        for part in parts {
+            mac.update(&(part.len() as u64).to_be_bytes());
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    }
+
+    fn uuid(&self, domain: &[u8], uuid: Uuid) -> Uuid {
+        let digest = self.digest(domain, &[uuid.as_bytes()]);
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    fn text(&self, domain: &[u8], value: &str) -> String {
+        self.digest(domain, &[value.as_bytes()])[..12]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Pseudonym of an order id
+    pub fn order_id(&self, order_id: OrderId) -> OrderId {
+        OrderId::from_uuid(self.uuid(ORDER_DOMAIN, Uuid::from_bytes(order_id.as_bytes())))
+    }
+
+    /// Pseudonym of a transaction id
+    pub fn transaction_id(&self, transaction_id: Uuid) -> Uuid {
+        self.uuid(TRANSACTION_DOMAIN, transaction_id)
+    }
+
+    /// Pseudonym of an account id
+    pub fn account_id(&self, account_id: &str) -> String {
+        format!("acct-{}", self.text(ACCOUNT_DOMAIN, account_id))
+    }
+
+    /// Pseudonym of a free-form client tag
+    pub fn client_tag(&self, tag: &str) -> String {
+        format!("tag-{}", self.text(CLIENT_TAG_DOMAIN, tag))
+    }
+
+    /// Jitter a timestamp of the record identified by `salt`, within the configured bound
+    pub fn timestamp(&self, timestamp: u64, salt: Uuid) -> u64 {
+        if self.timestamp_jitter_ms == 0 {
+            return timestamp;
+        }
+        let digest = self.digest(
+            TIMESTAMP_DOMAIN,
+            &[salt.as_bytes(), &timestamp.to_be_bytes()],
+        );
+        let mut random = [0; 8];
+        random.copy_from_slice(&digest[..8]);
+        let span = self.timestamp_jitter_ms.saturating_mul(2).saturating_add(1);
+        let offset = u64::from_be_bytes(random) % span;
+        timestamp
+            .saturating_add(offset)
+            .saturating_sub(self.timestamp_jitter_ms)
+    }
+
+    /// Anonymize a trade of the tape
+    pub fn transaction(&self, transaction: &Transaction) -> Transaction {
+        let mut anonymized = *transaction;
+        anonymized.transaction_id = self.transaction_id(transaction.transaction_id);
+        anonymized.taker_order_id = self.order_id(transaction.taker_order_id);
+        anonymized.maker_order_id = self.order_id(transaction.maker_order_id);
+        anonymized.timestamp = self.timestamp(transaction.timestamp, transaction.transaction_id);
+        anonymized
+    }
+
+    /// Anonymize an order, dropping its extra fields
+    pub fn order<T>(&self, order: &OrderType<T>) -> OrderType<()> {
+        let mut anonymized = order_to_unit_type(order);
+        match &mut anonymized {
+            OrderType::Standard { id, timestamp, .. }
+            | OrderType::IcebergOrder { id, timestamp, .. }
+            | OrderType::PostOnly { id, timestamp, .. }
+            | OrderType::TrailingStop { id, timestamp, .. }
+            | OrderType::PeggedOrder { id, timestamp, .. }
+            | OrderType::MarketToLimit { id, timestamp, .. }
+            | OrderType::ReserveOrder { id, timestamp, .. } => {
+                *timestamp = self.timestamp(*timestamp, Uuid::from_bytes(id.as_bytes()));
+                *id = self.order_id(*id);
+            }
+        }
+        anonymized
+    }
+
+    /// Anonymize a market-by-order snapshot
+    pub fn l3_snapshot(&self, snapshot: &OrderBookL3Snapshot) -> OrderBookL3Snapshot {
+        let mut anonymized = snapshot.clone();
+        for level in anonymized.bids.iter_mut().chain(anonymized.asks.iter_mut()) {
+            for order in &mut level.orders {
+                order.timestamp =
+                    self.timestamp(order.timestamp, Uuid::from_bytes(order.id.as_bytes()));
+                order.id = self.order_id(order.id);
+            }
+        }
+        anonymized
+    }
+
+    /// Anonymize an entry of an operation log
+    pub fn replay_record(&self, record: &ReplayRecord) -> ReplayRecord {
+        let mut anonymized = *record;
+        match &mut anonymized.operation {
+            ReplayOperation::AddLimit { order_id, .. }
+            | ReplayOperation::AddIceberg { order_id, .. }
+            | ReplayOperation::AddPostOnly { order_id, .. }
+            | ReplayOperation::Market { order_id, .. }
+            | ReplayOperation::Cancel { order_id }
+            | ReplayOperation::UpdateQuantity { order_id, .. }
+            | ReplayOperation::UpdatePrice { order_id, .. } => {
+                *order_id = self.order_id(*order_id);
+            }
+        }
+        anonymized
+    }
+}
@@ -0,0 +1,383 @@
+//! Arena storage for resting orders, addressed by index handles instead of a
+//! reference-counted allocation per order.
+//!
+//! [`OrderArena`] keeps orders in one contiguous slab and recycles the slots
+//! of removed orders, so a book with a steady churn of orders stops calling
+//! the allocator once the slab has grown to its working size, and a matching
+//! sweep walks orders that sit next to each other in memory.
+//! [`ArenaOrderBook`] is a single-threaded book built on it, matching on the
+//! same `BTreeMap` core as [`DeterministicOrderBook`](super::DeterministicOrderBook).
+
+use super::btree_book::{BTreeBook, Execution, OrderStorage};
+use super::error::OrderBookError;
+use pricelevel::{MatchResult, OrderId, OrderType, Side, TimeInForce};
+use tracing::trace;
+use uuid::Uuid;
+
+/// Handle to an order stored in an [`OrderArena`].
+///
+/// A handle is an index into the arena plus the generation of its slot, so a
+/// handle kept after its order was removed never reaches the order that
+/// reuses the slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OrderHandle {
+    index: u32,
+    generation: u32,
+}
+
+/// A slot of the arena, empty while it waits on the free list
+#[derive(Debug)]
+struct Slot<T> {
+    generation: u32,
+    order: Option<OrderType<T>>,
+}
+
+/// Slab of orders addressed by [`OrderHandle`]s. Freed slots are reused, most
+/// recently freed first, before the slab grows
+#[derive(Debug)]
+pub struct OrderArena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> OrderArena<T> {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create an empty arena with room for `capacity` orders
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of orders stored
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no order is stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of orders the arena holds without growing
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Store an order, returning its handle
+    ///
+    /// # Panics
+    /// Panics if the arena already holds `u32::MAX` slots.
+    pub fn insert(&mut self, order: OrderType<T>) -> OrderHandle {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.order = Some(order);
+            return OrderHandle {
+                index,
+                generation: slot.generation,
+            };
+        }
+        let index = u32::try_from(self.slots.len()).expect("order arena is full");
+        self.slots.push(Slot {
+            generation: 0,
+            order: Some(order),
+        });
+        OrderHandle {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// The order behind a handle, or `None` if it was removed
+    pub fn get(&self, handle: OrderHandle) -> Option<&OrderType<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .order
+            .as_ref()
+    }
+
+    /// The order behind a handle, mutably, or `None` if it was removed
+    pub fn get_mut(&mut self, handle: OrderHandle) -> Option<&mut OrderType<T>> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .order
+            .as_mut()
+    }
+
+    /// Remove an order, returning it if the handle was still live
+    pub fn remove(&mut self, handle: OrderHandle) -> Option<OrderType<T>> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?;
+        let order = slot.order.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(order)
+    }
+
+    /// Remove every order. The slab keeps its capacity and every handle
+    /// handed out so far stops resolving
+    pub fn clear(&mut self) {
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.order.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+            }
+            self.free.push(index as u32);
+        }
+        // Hand out the lowest slots first
+        self.free.reverse();
+        self.len = 0;
+    }
+}
+
+impl<T> Default for OrderArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An order carrying `remaining` of its quantity, taken from the visible
+/// quantity first for an order with a hidden part
+fn with_remaining<T: Clone>(order: &OrderType<T>, remaining: u64) -> OrderType<T> {
+    let mut order = order.clone();
+    match &mut order {
+        OrderType::IcebergOrder {
+            visible_quantity,
+            hidden_quantity,
+            ..
+        }
+        | OrderType::ReserveOrder {
+            visible_quantity,
+            hidden_quantity,
+            ..
+        } => {
+            *visible_quantity = (*visible_quantity).min(remaining);
+            *hidden_quantity = remaining - *visible_quantity;
+            order
+        }
+        _ => order.with_reduced_quantity(remaining),
+    }
+}
+
+impl<T: Clone> OrderStorage for OrderArena<T> {
+    type Entry = OrderHandle;
+    type Order = OrderType<T>;
+
+    fn order_id(&self, handle: &OrderHandle) -> Option<OrderId> {
+        self.get(*handle).map(|order| order.id())
+    }
+
+    fn quantity(&self, handle: &OrderHandle) -> u64 {
+        self.get(*handle).map_or(0, |order| {
+            order.visible_quantity() + order.hidden_quantity()
+        })
+    }
+
+    fn execute(&mut self, handle: &mut OrderHandle, quantity: u64) -> (u64, Execution) {
+        let Some(order) = self.get_mut(*handle) else {
+            return (0, Execution::InPlace);
+        };
+        let (consumed, updated, hidden_reduced, _) = order.match_against(quantity);
+        if consumed == 0 {
+            return (0, Execution::InPlace);
+        }
+        match updated {
+            // Partially filled from its visible quantity: keeps its priority
+            Some(updated) if hidden_reduced == 0 => {
+                *order = updated;
+                (consumed, Execution::InPlace)
+            }
+            // Refreshed from hidden quantity: goes to the back of the queue
+            Some(updated) => {
+                *order = updated;
+                (consumed, Execution::Refreshed)
+            }
+            None => (consumed, Execution::Filled),
+        }
+    }
+
+    fn release(&mut self, handle: OrderHandle) -> Option<OrderType<T>> {
+        self.remove(handle)
+    }
+}
+
+/// A book storing its resting orders as full [`OrderType`]s in an
+/// [`OrderArena`], with each price level a FIFO queue of handles.
+///
+/// It matches like [`OrderBook`](super::OrderBook), icebergs and reserve
+/// orders refreshing to the back of their level, but keeps none of its side
+/// tables, so it does not offer:
+///
+/// - expiry of good-till-date and day orders, or execution constraints;
+/// - trade listeners, events, statistics or metrics;
+/// - amendments. Cancel and add again instead;
+/// - concurrent access: methods take `&mut self`, so the book is driven from
+///   a single thread.
+pub struct ArenaOrderBook<T: Clone = ()> {
+    symbol: String,
+    book: BTreeBook<OrderArena<T>>,
+}
+
+impl<T: Clone> ArenaOrderBook<T> {
+    /// Create a new arena book for the given symbol
+    pub fn new(symbol: &str) -> Self {
+        Self::with_capacity(symbol, 0)
+    }
+
+    /// Create a new arena book with room for `orders` resting orders
+    pub fn with_capacity(symbol: &str, orders: usize) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            book: BTreeBook::new(OrderArena::with_capacity(orders), orders, Uuid::new_v4()),
+        }
+    }
+
+    /// Get the symbol of this order book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Number of resting orders
+    pub fn order_count(&self) -> usize {
+        self.book.storage().len()
+    }
+
+    /// Number of orders the arena holds before it grows
+    pub fn arena_capacity(&self) -> usize {
+        self.book.storage().capacity()
+    }
+
+    /// Get the best bid price, if any
+    pub fn best_bid(&self) -> Option<u64> {
+        self.book.best_bid()
+    }
+
+    /// Get the best ask price, if any
+    pub fn best_ask(&self) -> Option<u64> {
+        self.book.best_ask()
+    }
+
+    /// Get the last trade price, if any
+    pub fn last_trade_price(&self) -> Option<u64> {
+        self.book.last_trade_price()
+    }
+
+    /// Get a resting order by its id
+    pub fn get_order(&self, order_id: OrderId) -> Option<&OrderType<T>> {
+        self.book.storage().get(*self.book.entry(order_id)?)
+    }
+
+    /// Get all orders at a specific price level, in priority order
+    pub fn get_orders_at_price(&self, price: u64, side: Side) -> Vec<&OrderType<T>> {
+        let orders = self.book.storage();
+        self.book
+            .levels(side)
+            .get(&price)
+            .map(|queue| {
+                queue
+                    .iter()
+                    .filter_map(|handle| orders.get(*handle))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add an order. The part that crosses the book is matched at once and the
+    /// remainder rests at the order's price, unless the order is immediate.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the quantity is zero or
+    /// an order with the same id is resting, `OrderBookError::PriceCrossing`
+    /// for a post-only order that would trade, and
+    /// `OrderBookError::InsufficientLiquidity` for a fill-or-kill order that
+    /// cannot be filled in full.
+    pub fn add_order(&mut self, order: OrderType<T>) -> Result<MatchResult, OrderBookError> {
+        let (id, price, side) = (order.id(), order.price(), order.side());
+        let quantity = order.visible_quantity() + order.hidden_quantity();
+        if quantity == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Order quantity must be positive".to_string(),
+            });
+        }
+        if self.book.location(id).is_some() {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Order {id} is already resting"),
+            });
+        }
+        if order.is_post_only()
+            && let Some(opposite_price) = self.book.crossed_price(price, side)
+        {
+            return Err(OrderBookError::PriceCrossing {
+                price,
+                side,
+                opposite_price,
+            });
+        }
+        if order.time_in_force() == TimeInForce::Fok {
+            let available = self.book.available(side, quantity, Some(price));
+            if available < quantity {
+                return Err(OrderBookError::InsufficientLiquidity {
+                    side,
+                    requested: quantity,
+                    available,
+                });
+            }
+        }
+        trace!(
+            "Arena order book {}: Adding order {} at price {}",
+            self.symbol, id, price
+        );
+
+        let match_result = self.book.match_order(id, side, quantity, Some(price));
+        let remaining = match_result.remaining_quantity;
+        if remaining > 0 && !order.is_immediate() {
+            let resting = if remaining == quantity {
+                order
+            } else {
+                with_remaining(&order, remaining)
+            };
+            let handle = self.book.storage_mut().insert(resting);
+            self.book.rest(id, price, side, handle);
+        }
+        Ok(match_result)
+    }
+
+    /// Submit a market order, executing against the best prices first
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InsufficientLiquidity` if the opposite side is empty.
+    pub fn submit_market_order(
+        &mut self,
+        id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        let match_result = self.book.match_order(id, side, quantity, None);
+        if match_result.remaining_quantity == quantity {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: quantity,
+                available: 0,
+            });
+        }
+        Ok(match_result)
+    }
+
+    /// Cancel a resting order, returning it if it was found
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Option<OrderType<T>> {
+        self.book.remove(order_id)
+    }
+}
@@ -0,0 +1,316 @@
+//! Tokio front end to a book, built with the `async_api` feature.
+//!
+//! [`AsyncOrderBook`] moves the book into a matching task and talks to it
+//! through a bounded command channel, so order entry from many tasks is
+//! serialized without locking and each call resolves once the book has
+//! processed it.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::{EventListener, OrderBookEvent};
+use super::fills::FillNotification;
+use super::snapshot::OrderBookSnapshot;
+use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::trace;
+
+/// Fills buffered for each subscriber before the slowest one starts missing them
+const FILL_BROADCAST_CAPACITY: usize = 1_024;
+
+type Command<T> = Box<dyn FnOnce(&mut OrderBook<T>) + Send>;
+
+enum Message<T> {
+    Run(Command<T>),
+    /// Process what is already queued, then stop accepting commands
+    Stop,
+}
+
+/// Result of an order accepted by the book
+#[derive(Debug, Clone)]
+pub struct OrderAck<T> {
+    /// Id of the submitted order
+    pub order_id: OrderId,
+    /// The order as submitted, `None` for market orders, which never rest
+    pub order: Option<Arc<OrderType<T>>>,
+    /// Fills of the submitted order while it was matched on entry. Fills of
+    /// the orders it traded against are published to
+    /// [`subscribe_fills`](AsyncOrderBook::subscribe_fills)
+    pub fills: Vec<FillNotification>,
+}
+
+impl<T> OrderAck<T> {
+    /// Quantity executed on entry
+    pub fn executed_quantity(&self) -> u64 {
+        self.fills.iter().map(|fill| fill.quantity).sum()
+    }
+}
+
+fn stopped() -> OrderBookError {
+    OrderBookError::InvalidOperation {
+        message: "the order book task has stopped".to_string(),
+    }
+}
+
+fn take_fills(fills: &Mutex<Vec<FillNotification>>) -> Vec<FillNotification> {
+    std::mem::take(
+        &mut *fills
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    )
+}
+
+/// Handle to a book owned by a matching task.
+///
+/// Cloning the handle is cheap and every clone submits to the same task. The
+/// task stops once every handle is dropped, or on [`shutdown`](Self::shutdown).
+pub struct AsyncOrderBook<T> {
+    symbol: Arc<str>,
+    commands: mpsc::Sender<Message<T>>,
+    entry_fills: Arc<Mutex<Vec<FillNotification>>>,
+    fills: broadcast::Sender<FillNotification>,
+    task: Arc<Mutex<Option<JoinHandle<OrderBook<T>>>>>,
+}
+
+impl<T> Clone for AsyncOrderBook<T> {
+    fn clone(&self) -> Self {
+        Self {
+            symbol: Arc::clone(&self.symbol),
+            commands: self.commands.clone(),
+            entry_fills: Arc::clone(&self.entry_fills),
+            fills: self.fills.clone(),
+            task: Arc::clone(&self.task),
+        }
+    }
+}
+
+impl<T> AsyncOrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Move `book` into a matching task on the current tokio runtime, queueing
+    /// up to `capacity` commands before callers wait.
+    ///
+    /// The book's event listener keeps receiving every event.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero or if called outside a tokio runtime.
+    pub fn spawn(mut book: OrderBook<T>, capacity: usize) -> Self {
+        let symbol: Arc<str> = Arc::from(book.symbol());
+        let entry_fills = Arc::new(Mutex::new(Vec::new()));
+        let (fills, _) = broadcast::channel(FILL_BROADCAST_CAPACITY);
+
+        let previous = book.event_listener.take();
+        let recorded = Arc::clone(&entry_fills);
+        let published = fills.clone();
+        let forwarded = previous.clone();
+        let listener: EventListener = Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event {
+                recorded
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(*fill);
+                // Nobody subscribed is not an error
+                let _ = published.send(*fill);
+            }
+            if let Some(listener) = &forwarded {
+                listener(event);
+            }
+        });
+        book.set_event_listener(listener);
+
+        let (commands, mut receiver) = mpsc::channel::<Message<T>>(capacity);
+        let task = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    Message::Run(command) => command(&mut book),
+                    Message::Stop => receiver.close(),
+                }
+            }
+            book.event_listener = previous;
+            trace!("Matching task for {} stopped", book.symbol());
+            book
+        });
+
+        Self {
+            symbol,
+            commands,
+            entry_fills,
+            fills,
+            task: Arc::new(Mutex::new(Some(task))),
+        }
+    }
+
+    /// Symbol of the book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Receive every fill from now on, makers and takers alike. A subscriber
+    /// more than 1024 fills behind skips the oldest ones
+    pub fn subscribe_fills(&self) -> broadcast::Receiver<FillNotification> {
+        self.fills.subscribe()
+    }
+
+    /// Run `command` on the matching task with exclusive access to the book,
+    /// resolving to what it returns
+    pub async fn execute<R, F>(&self, command: F) -> Result<R, OrderBookError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut OrderBook<T>) -> R + Send + 'static,
+    {
+        let (reply, response) = oneshot::channel();
+        let command: Command<T> = Box::new(move |book| {
+            // The caller may have stopped waiting
+            let _ = reply.send(command(book));
+        });
+        self.commands
+            .send(Message::Run(command))
+            .await
+            .map_err(|_| stopped())?;
+        response.await.map_err(|_| stopped())
+    }
+
+    /// Run `submit` for `order_id` and gather the fills it made for that order
+    async fn submit<F>(&self, order_id: OrderId, submit: F) -> Result<OrderAck<T>, OrderBookError>
+    where
+        F: FnOnce(&OrderBook<T>) -> Result<Option<Arc<OrderType<T>>>, OrderBookError>
+            + Send
+            + 'static,
+    {
+        let entry_fills = Arc::clone(&self.entry_fills);
+        self.execute(move |book| {
+            take_fills(&entry_fills);
+            let result = submit(book);
+            let fills = take_fills(&entry_fills)
+                .into_iter()
+                .filter(|fill| fill.order_id == order_id)
+                .collect();
+            result.map(|order| OrderAck {
+                order_id,
+                order,
+                fills,
+            })
+        })
+        .await?
+    }
+
+    /// Add a limit order, resolving once it has been matched and rested
+    pub async fn add_limit_order(
+        &self,
+        order_id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<OrderAck<T>, OrderBookError> {
+        self.submit(order_id, move |book| {
+            book.add_limit_order(order_id, price, quantity, side, time_in_force, extra_fields)
+                .map(Some)
+        })
+        .await
+    }
+
+    /// Add an iceberg order, resolving once it has been matched and rested
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_iceberg_order(
+        &self,
+        order_id: OrderId,
+        price: u64,
+        visible_quantity: u64,
+        hidden_quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<OrderAck<T>, OrderBookError> {
+        self.submit(order_id, move |book| {
+            book.add_iceberg_order(
+                order_id,
+                price,
+                visible_quantity,
+                hidden_quantity,
+                side,
+                time_in_force,
+                extra_fields,
+            )
+            .map(Some)
+        })
+        .await
+    }
+
+    /// Add a post-only order, rejected if it would cross
+    pub async fn add_post_only_order(
+        &self,
+        order_id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<OrderAck<T>, OrderBookError> {
+        self.submit(order_id, move |book| {
+            book.add_post_only_order(order_id, price, quantity, side, time_in_force, extra_fields)
+                .map(Some)
+        })
+        .await
+    }
+
+    /// Execute a market order, resolving with its fills
+    pub async fn submit_market_order(
+        &self,
+        order_id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<OrderAck<T>, OrderBookError> {
+        self.submit(order_id, move |book| {
+            book.submit_market_order(order_id, quantity, side)
+                .map(|_| None)
+        })
+        .await
+    }
+
+    /// Cancel a resting order, resolving to it, or `None` if it was not resting
+    pub async fn cancel_order(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        self.execute(move |book| book.cancel_order(order_id))
+            .await?
+    }
+
+    /// Snapshot of the top `depth` levels of each side
+    pub async fn snapshot(&self, depth: usize) -> Result<OrderBookSnapshot, OrderBookError> {
+        self.execute(move |book| book.create_snapshot(depth)).await
+    }
+
+    /// Highest bid price
+    pub async fn best_bid(&self) -> Result<Option<u64>, OrderBookError> {
+        self.execute(|book| book.best_bid()).await
+    }
+
+    /// Lowest ask price
+    pub async fn best_ask(&self) -> Result<Option<u64>, OrderBookError> {
+        self.execute(|book| book.best_ask()).await
+    }
+
+    /// Stop the matching task once the commands already queued are processed
+    /// and get the book back, with its own event listener restored.
+    ///
+    /// Other handles fail to submit from then on. Fails if the book was already taken back
+    /// through another handle or the task panicked.
+    pub async fn shutdown(self) -> Result<OrderBook<T>, OrderBookError> {
+        let task = self
+            .task
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+            .ok_or_else(stopped)?;
+        self.commands
+            .send(Message::Stop)
+            .await
+            .map_err(|_| stopped())?;
+        task.await.map_err(|_| stopped())
+    }
+}
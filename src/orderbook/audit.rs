@@ -0,0 +1,223 @@
+//! Hash chain over the events a book publishes, enabled by the `audit` feature.
+//!
+//! Once [`OrderBook::enable_audit_trail`] is called every published event is
+//! numbered and fed, in its [canonical encoding](encode_event), into a SHA-256
+//! digest. Every `interval` events the digest is closed and a checkpoint
+//! recorded, binding it to the previous checkpoint and to the book's
+//! [checksum](OrderBook::checksum) at that point. Anyone holding the event
+//! log can recompute the chain with [`events_hash`] and
+//! [`AuditCheckpoint::verify_chain`], and a book whose current checksum
+//! matches the last checkpoint is shown to be the product of that history.
+
+use super::book::OrderBook;
+use super::events::OrderBookEvent;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::{Mutex, MutexGuard};
+use tracing::trace;
+
+/// A link of the audit hash chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    /// Number of events hashed since the trail was enabled, this
+    /// checkpoint's last event included
+    pub sequence: u64,
+    /// Version of the book when the checkpoint was taken
+    pub version: u64,
+    /// [`OrderBook::checksum`] over every level when the checkpoint was taken
+    pub checksum: u32,
+    /// [`events_hash`] of the events since the previous checkpoint
+    pub events_hash: [u8; 32],
+    /// Hash of the previous checkpoint, zero for the first one, with the
+    /// sequence, checksum and events hash of this one
+    pub hash: [u8; 32],
+    /// When the checkpoint was taken, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+impl AuditCheckpoint {
+    fn chain_hash(
+        previous: &[u8; 32],
+        sequence: u64,
+        checksum: u32,
+        events: &[u8; 32],
+    ) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(previous);
+        hasher.update(sequence.to_be_bytes());
+        hasher.update(checksum.to_be_bytes());
+        hasher.update(events);
+        hasher.finalize().into()
+    }
+
+    /// Returns true if every checkpoint's hash follows from the one before
+    /// it, the first one chaining from zero
+    pub fn verify_chain(checkpoints: &[AuditCheckpoint]) -> bool {
+        let mut previous = [0u8; 32];
+        for checkpoint in checkpoints {
+            let expected = Self::chain_hash(
+                &previous,
+                checkpoint.sequence,
+                checkpoint.checksum,
+                &checkpoint.events_hash,
+            );
+            if checkpoint.hash != expected {
+                return false;
+            }
+            previous = checkpoint.hash;
+        }
+        true
+    }
+}
+
+/// Bytes an event is hashed as: the variant name, then its payload as JSON.
+/// Rejects and faults, whose errors do not serialize, carry the error's
+/// message instead
+pub fn encode_event(event: &OrderBookEvent) -> Vec<u8> {
+    let (kind, payload) = match event {
+        OrderBookEvent::OrderAccepted(accepted) => ("OrderAccepted", serde_json::to_vec(accepted)),
+        OrderBookEvent::OrderRejected(reject) => (
+            "OrderRejected",
+            serde_json::to_vec(&(
+                reject.order_id,
+                reject.reason,
+                reject.error.to_string(),
+                reject.timestamp,
+                reject.config_version,
+            )),
+        ),
+        OrderBookEvent::OptionsChanged(options) => ("OptionsChanged", serde_json::to_vec(options)),
+        OrderBookEvent::TradeExecuted(report) => ("TradeExecuted", serde_json::to_vec(report)),
+        OrderBookEvent::OrderFilled(fill) => ("OrderFilled", serde_json::to_vec(fill)),
+        OrderBookEvent::OrderExpired(expired) => ("OrderExpired", serde_json::to_vec(expired)),
+        OrderBookEvent::OrderCancelled(cancel) => ("OrderCancelled", serde_json::to_vec(cancel)),
+        OrderBookEvent::PriceLevelFault(error) => {
+            ("PriceLevelFault", serde_json::to_vec(&error.to_string()))
+        }
+        OrderBookEvent::TradingStateChanged(transition) => {
+            ("TradingStateChanged", serde_json::to_vec(transition))
+        }
+        OrderBookEvent::TradingHalted(halt) => ("TradingHalted", serde_json::to_vec(halt)),
+        OrderBookEvent::QuotesPulled(pulled) => ("QuotesPulled", serde_json::to_vec(pulled)),
+        OrderBookEvent::LevelAdded(level) => ("LevelAdded", serde_json::to_vec(level)),
+        OrderBookEvent::LevelRemoved(level) => ("LevelRemoved", serde_json::to_vec(level)),
+    };
+    let mut bytes = kind.as_bytes().to_vec();
+    bytes.push(b':');
+    bytes.extend(payload.expect("event payloads serialize to JSON"));
+    bytes
+}
+
+/// Hash of a run of events in their [canonical encoding](encode_event), as
+/// recorded in [`AuditCheckpoint::events_hash`]
+pub fn events_hash<'a>(events: impl IntoIterator<Item = &'a [u8]>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for event in events {
+        hash_event(&mut hasher, event);
+    }
+    hasher.finalize().into()
+}
+
+fn hash_event(hasher: &mut Sha256, encoded: &[u8]) {
+    hasher.update((encoded.len() as u64).to_be_bytes());
+    hasher.update(encoded);
+}
+
+struct AuditState {
+    /// Events hashed since the trail was enabled
+    sequence: u64,
+    /// Digest of the events since the last checkpoint
+    pending: Sha256,
+    pending_events: usize,
+    checkpoints: Vec<AuditCheckpoint>,
+}
+
+/// Audit trail of a book
+pub(super) struct AuditLog {
+    interval: usize,
+    state: Mutex<AuditState>,
+}
+
+impl AuditLog {
+    fn lock(&self) -> MutexGuard<'_, AuditState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Hash every event published from now on, recording a checkpoint every
+    /// `interval` events, or after every event for an interval of zero.
+    /// Enabling the trail again starts a new chain.
+    ///
+    /// Events are hashed whether or not an event listener is set, so the
+    /// book publishes them from then on.
+    pub fn enable_audit_trail(&mut self, interval: usize) {
+        trace!(
+            "Order book {}: Audit trail every {} events",
+            self.symbol, interval
+        );
+        self.audit = Some(AuditLog {
+            interval: interval.max(1),
+            state: Mutex::new(AuditState {
+                sequence: 0,
+                pending: Sha256::new(),
+                pending_events: 0,
+                checkpoints: Vec::new(),
+            }),
+        });
+    }
+
+    /// Checkpoints recorded so far, oldest first. Empty unless the trail is enabled
+    pub fn audit_trail(&self) -> Vec<AuditCheckpoint> {
+        self.audit
+            .as_ref()
+            .map(|audit| audit.lock().checkpoints.clone())
+            .unwrap_or_default()
+    }
+
+    /// Number of events hashed so far, including those not yet covered by a
+    /// checkpoint. `None` unless the trail is enabled
+    pub fn audit_sequence(&self) -> Option<u64> {
+        self.audit.as_ref().map(|audit| audit.lock().sequence)
+    }
+
+    /// Hash a published event, closing a checkpoint if the interval is reached
+    pub(super) fn audit_event(&self, event: &OrderBookEvent) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let mut state = audit.lock();
+        hash_event(&mut state.pending, &encode_event(event));
+        state.sequence += 1;
+        state.pending_events += 1;
+        if state.pending_events < audit.interval {
+            return;
+        }
+
+        let events_hash: [u8; 32] = std::mem::take(&mut state.pending).finalize().into();
+        state.pending_events = 0;
+        let previous = state
+            .checkpoints
+            .last()
+            .map_or([0u8; 32], |checkpoint| checkpoint.hash);
+        let checksum = self.checksum(usize::MAX);
+        let checkpoint = AuditCheckpoint {
+            sequence: state.sequence,
+            version: self.version(),
+            checksum,
+            events_hash,
+            hash: AuditCheckpoint::chain_hash(&previous, state.sequence, checksum, &events_hash),
+            timestamp: self.now(),
+        };
+        trace!(
+            "Order book {}: Audit checkpoint at event {}",
+            self.symbol, checkpoint.sequence
+        );
+        state.checkpoints.push(checkpoint);
+    }
+}
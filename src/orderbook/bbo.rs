@@ -0,0 +1,105 @@
+//! Top-of-book change notifications
+
+use super::book::OrderBook;
+use pricelevel::Side;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::trace;
+
+/// Best bid and best offer with the visible quantity resting at each
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopOfBook {
+    /// Best bid price, if any
+    pub bid_price: Option<u64>,
+    /// Visible quantity at the best bid
+    pub bid_quantity: u64,
+    /// Best ask price, if any
+    pub ask_price: Option<u64>,
+    /// Visible quantity at the best ask
+    pub ask_quantity: u64,
+}
+
+/// Emitted when the best bid, the best ask or the size at either changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BboChange {
+    /// Symbol of the book
+    pub symbol: String,
+    /// Top of book before the change
+    pub previous: TopOfBook,
+    /// Top of book after the change
+    pub current: TopOfBook,
+}
+
+/// Callback invoked whenever the top of book changes
+pub type BboListener = Arc<dyn Fn(&BboChange) + Send + Sync>;
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Current best bid and offer with their visible sizes
+    pub fn top_of_book(&self) -> TopOfBook {
+        if let Some(storage) = self.compact_storage() {
+            let best = |side| storage.depth(side, 1).first().copied();
+            let (bid, ask) = (best(Side::Buy), best(Side::Sell));
+            return TopOfBook {
+                bid_price: bid.map(|(price, _)| price),
+                bid_quantity: bid.map_or(0, |(_, quantity)| quantity),
+                ask_price: ask.map(|(price, _)| price),
+                ask_quantity: ask.map_or(0, |(_, quantity)| quantity),
+            };
+        }
+        let bid_price = self.best_bid();
+        let ask_price = self.best_ask();
+        TopOfBook {
+            bid_price,
+            bid_quantity: bid_price
+                .and_then(|price| self.bids.get(&price))
+                .map_or(0, |level| level.visible_quantity()),
+            ask_price,
+            ask_quantity: ask_price
+                .and_then(|price| self.asks.get(&price))
+                .map_or(0, |level| level.visible_quantity()),
+        }
+    }
+
+    /// Set the listener called whenever the best bid, the best ask or the
+    /// visible size at either changes, so quoting engines need not poll.
+    ///
+    /// Changes are detected after each add, cancel, update or match using the
+    /// cached best prices. Notifications are delivered one at a time, in the
+    /// order the changes were observed.
+    pub fn on_bbo_change(&mut self, listener: BboListener) {
+        *self
+            .last_bbo
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = self.top_of_book();
+        self.bbo_listener = Some(listener);
+    }
+
+    /// Notify the listener if the top of book moved since the last notification
+    pub(super) fn notify_bbo_change(&self) {
+        let Some(listener) = &self.bbo_listener else {
+            return;
+        };
+        let mut last = self
+            .last_bbo
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let current = self.top_of_book();
+        if current == *last {
+            return;
+        }
+        trace!(
+            "Order book {}: Top of book changed to {:?}",
+            self.symbol, current
+        );
+        let change = BboChange {
+            symbol: self.symbol.clone(),
+            previous: *last,
+            current,
+        };
+        *last = current;
+        listener(&change);
+    }
+}
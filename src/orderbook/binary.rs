@@ -0,0 +1,126 @@
+//! Compact binary encoding of snapshots, enabled by the `binary` feature
+
+use super::error::OrderBookError;
+use super::snapshot::{OrderBookL3Snapshot, OrderBookSnapshot};
+use bincode::config::{Configuration, standard};
+use pricelevel::{OrderType, PriceLevelSnapshot};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Encoding shared by every snapshot type: variable-length little-endian integers
+const CONFIG: Configuration = standard();
+
+fn encode<S: Serialize>(value: &S) -> Result<Vec<u8>, OrderBookError> {
+    bincode::serde::encode_to_vec(value, CONFIG).map_err(|error| {
+        OrderBookError::SerializationError {
+            message: error.to_string(),
+        }
+    })
+}
+
+fn decode<S: DeserializeOwned>(bytes: &[u8]) -> Result<S, OrderBookError> {
+    let (value, read) = bincode::serde::decode_from_slice(bytes, CONFIG).map_err(|error| {
+        OrderBookError::SerializationError {
+            message: error.to_string(),
+        }
+    })?;
+    if read != bytes.len() {
+        return Err(OrderBookError::SerializationError {
+            message: format!("{} trailing bytes after snapshot", bytes.len() - read),
+        });
+    }
+    Ok(value)
+}
+
+/// Binary form of a [`PriceLevelSnapshot`], whose own serde implementation
+/// can only be read back from self-describing formats
+#[derive(Serialize, Deserialize)]
+struct LevelRecord {
+    price: u64,
+    visible_quantity: u64,
+    hidden_quantity: u64,
+    order_count: usize,
+    orders: Vec<OrderType<()>>,
+}
+
+impl From<&PriceLevelSnapshot> for LevelRecord {
+    fn from(level: &PriceLevelSnapshot) -> Self {
+        Self {
+            price: level.price,
+            visible_quantity: level.visible_quantity,
+            hidden_quantity: level.hidden_quantity,
+            order_count: level.order_count,
+            orders: level.orders.iter().map(|order| **order).collect(),
+        }
+    }
+}
+
+impl From<LevelRecord> for PriceLevelSnapshot {
+    fn from(level: LevelRecord) -> Self {
+        Self {
+            price: level.price,
+            visible_quantity: level.visible_quantity,
+            hidden_quantity: level.hidden_quantity,
+            order_count: level.order_count,
+            orders: level.orders.into_iter().map(Arc::new).collect(),
+        }
+    }
+}
+
+/// Binary form of an [`OrderBookSnapshot`]
+#[derive(Serialize, Deserialize)]
+struct SnapshotRecord {
+    symbol: String,
+    timestamp: u64,
+    bids: Vec<LevelRecord>,
+    asks: Vec<LevelRecord>,
+}
+
+impl OrderBookSnapshot {
+    /// Encode the snapshot in a compact binary form, several times smaller and
+    /// faster to produce and parse than JSON for deep books
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` if the snapshot cannot be encoded.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OrderBookError> {
+        encode(&SnapshotRecord {
+            symbol: self.symbol.clone(),
+            timestamp: self.timestamp,
+            bids: self.bids.iter().map(LevelRecord::from).collect(),
+            asks: self.asks.iter().map(LevelRecord::from).collect(),
+        })
+    }
+
+    /// Decode a snapshot produced by [`to_bytes`](Self::to_bytes)
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` if the bytes are not a valid snapshot.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OrderBookError> {
+        let record: SnapshotRecord = decode(bytes)?;
+        Ok(Self {
+            symbol: record.symbol,
+            timestamp: record.timestamp,
+            bids: record.bids.into_iter().map(Into::into).collect(),
+            asks: record.asks.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl OrderBookL3Snapshot {
+    /// Encode the snapshot in a compact binary form, see [`OrderBookSnapshot::to_bytes`]
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` if the snapshot cannot be encoded.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OrderBookError> {
+        encode(self)
+    }
+
+    /// Decode a snapshot produced by [`to_bytes`](Self::to_bytes)
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` if the bytes are not a valid snapshot.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OrderBookError> {
+        decode(bytes)
+    }
+}
@@ -1,18 +1,64 @@
 //! Core OrderBook implementation for managing price levels and orders
 
-use super::cache::PriceLevelCache;
+use super::amendments::AmendmentLog;
+#[cfg(feature = "audit")]
+use super::audit::AuditLog;
+use super::bbo::{BboListener, TopOfBook};
+use super::cache::{CacheInvalidation, PriceLevelCache};
+use super::circuit_breaker::CircuitBreaker;
+use super::compact::CompactOrderBook;
+use super::consistency::WriteTracker;
+use super::constraints::OrderConstraints;
+use super::dark::DarkPool;
 use super::error::OrderBookError;
-use super::snapshot::OrderBookSnapshot;
-use crate::utils::current_time_millis;
-use dashmap::DashMap;
+use super::events::{EventListener, OrderBookEvent, OrderReject, RejectReason};
+use super::execution::ExecutionTracker;
+use super::execution_report::OrderStatus;
+use super::expiry::ExpiryLog;
+use super::kill_switch::KillSwitches;
+#[cfg(feature = "metrics")]
+use super::metrics::{LatencyMetrics, MetricsReport, Operation};
+use super::notional::NotionalTracker;
+use super::options::OptionsCell;
+use super::pool::{PoolConfig, PoolCounters, PoolStats};
+use super::price_scale::PriceScale;
+use super::quotes::Quotes;
+use super::rate_limit::RateLimiter;
+use super::session::{SessionState, TradingState};
+use super::short_sale::ShortSaleRule;
+use super::side::BookSide;
+use super::signals::Signals;
+use super::snapshot::{L3Level, L3Order, OrderBookL3Snapshot, OrderBookSnapshot};
+use super::special::SpecialPriceSection;
+use super::stats::{BookStats, StatCounters};
+use super::tape::TradeTape;
+use super::ticket::LevelTickets;
+use super::trade_channel::{OverflowPolicy, TradeChannel};
+use super::watermarks::{
+    MemoryPressure, MemoryPressureEvent, MemoryPressureListener, MemoryUsage, MemoryWatermarks,
+};
+use crate::utils::{Clock, SystemClock};
+use dashmap::{DashMap, DashSet};
 use pricelevel::{MatchResult, OrderId, OrderType, PriceLevel, Side, UuidGenerator};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::trace;
 use uuid::Uuid;
 
+/// Default number of levels from which snapshots are built in parallel
+#[cfg(feature = "parallel")]
+pub const DEFAULT_PARALLEL_SNAPSHOT_DEPTH: usize = 512;
+
+/// Sequence number and time a resting order was accepted under
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Acceptance {
+    pub(super) sequence: u64,
+    /// Nanoseconds since UNIX epoch
+    pub(super) nanos: u64,
+}
+
 /// The OrderBook manages a collection of price levels for both bid and ask sides.
 /// It supports adding, cancelling, and matching orders with lock-free operations where possible.
 pub struct OrderBook<T = ()> {
@@ -20,17 +66,69 @@ pub struct OrderBook<T = ()> {
     pub(super) symbol: String,
 
     /// Bid side price levels (buy orders), stored in a concurrent map for lock-free access
-    /// The map is keyed by price levels and stores Arc references to PriceLevel instances
-    pub(super) bids: DashMap<u64, Arc<PriceLevel>>,
+    /// and indexed by price for ordered traversal
+    pub(super) bids: BookSide,
 
     /// Ask side price levels (sell orders), stored in a concurrent map for lock-free access
-    /// The map is keyed by price levels and stores Arc references to PriceLevel instances
-    pub(super) asks: DashMap<u64, Arc<PriceLevel>>,
+    /// and indexed by price for ordered traversal
+    pub(super) asks: BookSide,
 
     /// A concurrent map from order ID to (price, side) for fast lookups
     /// This avoids having to search through all price levels to find an order
     pub(super) order_locations: DashMap<OrderId, (u64, Side)>,
 
+    /// Execution constraints (e.g. all-or-none) of resting orders that have any.
+    /// Orders without constraints are not stored
+    pub(super) order_constraints: DashMap<OrderId, OrderConstraints>,
+
+    /// Display size of resting iceberg and reserve orders as entered, restored
+    /// when an amendment re-adds them, see [`OrderBook::display_size`]
+    pub(super) display_sizes: DashMap<OrderId, u64>,
+
+    /// Acceptance sequence number and time of each resting order, see
+    /// [`OrderBook::order_sequence`] and [`OrderBook::accepted_at_nanos`]
+    pub(super) order_sequences: DashMap<OrderId, Acceptance>,
+
+    /// Resting orders that keep their price level off the level's own FIFO
+    /// matching, counted per level by [`BookSide::is_fifo`]
+    pub(super) irregular_orders: DashSet<OrderId>,
+
+    /// Orders being entered again under their id by an amendment or a
+    /// replacement, whose old place may still be in their level's queue
+    pub(super) reentries: DashSet<OrderId>,
+
+    /// Last acceptance sequence number handed out
+    pub(super) sequence: AtomicU64,
+
+    /// Last acceptance time handed out, in nanoseconds since UNIX epoch
+    pub(super) last_acceptance_nanos: AtomicU64,
+
+    /// Execution state of resting orders, see [`OrderBook::execution_state`]
+    pub(super) executions: ExecutionTracker,
+
+    /// Owner of each resting order added with an account, see [`OrderBook::order_account`]
+    pub(super) order_accounts: DashMap<OrderId, String>,
+
+    /// Resting orders of each account, see [`OrderBook::orders_for_account`]
+    pub(super) account_orders: DashMap<String, HashSet<OrderId>>,
+
+    /// Working order of each client order id, see [`OrderBook::find_by_client_id`]
+    pub(super) client_orders: DashMap<String, OrderId>,
+
+    /// Client order id of each working order added with one, see
+    /// [`OrderBook::client_order_id`]
+    pub(super) order_client_ids: DashMap<OrderId, String>,
+
+    /// Orders removed by the expiry sweeper, see [`OrderBook::expired_orders_since`]
+    pub(super) expiry_log: ExpiryLog,
+
+    /// Admits quantity updates to each level in arrival order
+    pub(super) level_tickets: LevelTickets,
+
+    /// Failures to inject into price level updates
+    #[cfg(test)]
+    pub(super) level_faults: super::private::LevelFaults,
+
     /// Generator for unique transaction IDs
     pub(super) transaction_id_generator: UuidGenerator,
 
@@ -40,22 +138,269 @@ pub struct OrderBook<T = ()> {
     /// Flag indicating if there was a trade
     pub(super) has_traded: AtomicBool,
 
-    /// The timestamp of market close, if applicable (for DAY orders)
-    pub(super) market_close_timestamp: AtomicU64,
+    /// A trade happened since the book was cleared or the trading day
+    /// started, so the next one is not an opening trade
+    pub(super) session_traded: AtomicBool,
+
+    /// Hot-reloadable options, stamped with a configuration version
+    pub(super) options: OptionsCell,
+
+    /// Sizing of the vectors borrowed from the matching pool
+    pub(super) pool_config: PoolConfig,
 
-    /// Flag indicating if market close is set
-    pub(super) has_market_close: AtomicBool,
+    /// Usage of the matching pool by this book
+    pub(super) pool_counters: PoolCounters,
+
+    /// Activity counters, read back with `stats()`
+    pub(super) stats: StatCounters,
 
     /// A cache for storing best bid/ask prices to avoid recalculation
     pub(super) cache: PriceLevelCache,
 
+    /// Mapping between decimal prices and the integer prices stored in the book
+    pub(super) price_scale: PriceScale,
+
+    /// Lowest signed price of the instrument, stored in the book as price 0
+    pub(super) price_floor: i64,
+
     /// listens to possible trades when an order is added
     pub trade_listener: Option<TradeListener>,
 
+    /// Bounded queue delivering match results to a consumer thread, off the order entry path
+    pub(super) trade_channel: Option<TradeChannel>,
+
+    /// Records every trade for OHLCV and VWAP aggregation, if attached
+    pub(super) trade_tape: Option<Arc<TradeTape>>,
+
+    /// Throttle applied to order entry, if any
+    pub(super) rate_limiter: Option<RateLimiter>,
+
+    /// Time source for order timestamps, expiry and rate limiting
+    pub(super) clock: Arc<dyn Clock>,
+
+    /// Thresholds on resting orders, levels and estimated memory, if monitored
+    pub(super) memory_watermarks: Option<MemoryWatermarks>,
+
+    /// Called when the memory pressure level changes
+    pub(super) memory_pressure_listener: Option<MemoryPressureListener>,
+
+    /// Current `MemoryPressure` level, stored as its discriminant
+    pub(super) memory_pressure: AtomicU8,
+
+    /// Receives the events published by the book
+    pub(super) event_listener: Option<EventListener>,
+
+    /// Incremented on every change to the resting orders, so readers can tell
+    /// whether the book changed between two observations
+    pub(super) version: AtomicU64,
+
+    /// Changes to the resting orders begun and finished, so a reader can tell
+    /// whether one overlapped it, see [`OrderBook::consistent_snapshot`]
+    pub(super) writes: WriteTracker,
+
+    /// Listener notified when the top of book changes
+    pub(super) bbo_listener: Option<BboListener>,
+
+    /// Top of book as of the last notification
+    pub(super) last_bbo: Mutex<TopOfBook>,
+
+    /// Orders priced off the settlement price, matched separately at the end of the day
+    pub(super) special_prices: SpecialPriceSection,
+
+    /// Non-displayed orders trading at the lit midpoint
+    pub(super) dark_pool: DarkPool,
+
+    /// Rule short sales are checked against on entry
+    pub(super) short_sale_rule: Option<ShortSaleRule>,
+
+    /// Whether a short sale restriction is in force
+    pub(super) short_sale_restricted: AtomicBool,
+
+    /// Trading state and the session schedule driving it
+    pub(super) session: SessionState,
+
+    /// Halt tripped by sweeps of the book and the executions counted towards it
+    pub(super) circuit_breaker: CircuitBreaker,
+
+    /// Two-sided quotes of market makers by quote id
+    pub(super) quotes: Quotes,
+
+    /// Predicates over the top of the book notified when they start to hold
+    pub(super) signals: Signals,
+
+    /// Notional traded, if tracked
+    pub(super) notional: Option<NotionalTracker>,
+
+    /// Kill switches refusing orders of accounts or of the whole book
+    pub(super) kill_switches: KillSwitches,
+
+    /// Recent amendments of each order, if recorded
+    pub(super) amendments: Option<AmendmentLog>,
+
+    /// Storage of the resting orders, if the book keeps them compactly
+    /// instead of in its price levels
+    pub(super) compact: Option<Mutex<CompactOrderBook>>,
+
+    /// Hash chain over the published events, if enabled
+    #[cfg(feature = "audit")]
+    pub(super) audit: Option<AuditLog>,
+
+    /// Latency histograms of add, cancel and match operations
+    #[cfg(feature = "metrics")]
+    pub(super) metrics: LatencyMetrics,
+
+    /// Number of levels from which snapshots are built in parallel
+    #[cfg(feature = "parallel")]
+    pub(super) parallel_snapshot_depth: usize,
+
     /// Phantom data to maintain generic type parameter
     _phantom: PhantomData<T>,
 }
 
+/// Convert an `OrderType<()>` as stored in price levels into an `OrderType<T>`
+/// with default extra fields
+pub(super) fn order_from_unit_type<T: Default>(order: &OrderType<()>) -> OrderType<T> {
+    match order {
+        OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::Standard {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: T::default(),
+        },
+        OrderType::IcebergOrder {
+            id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::IcebergOrder {
+            id: *id,
+            price: *price,
+            visible_quantity: *visible_quantity,
+            hidden_quantity: *hidden_quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: T::default(),
+        },
+        OrderType::PostOnly {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::PostOnly {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: T::default(),
+        },
+        OrderType::TrailingStop {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            trail_amount,
+            last_reference_price,
+            ..
+        } => OrderType::TrailingStop {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            trail_amount: *trail_amount,
+            last_reference_price: *last_reference_price,
+            extra_fields: T::default(),
+        },
+        OrderType::PeggedOrder {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            reference_price_offset,
+            reference_price_type,
+            ..
+        } => OrderType::PeggedOrder {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            reference_price_offset: *reference_price_offset,
+            reference_price_type: *reference_price_type,
+            extra_fields: T::default(),
+        },
+        OrderType::MarketToLimit {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::MarketToLimit {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: T::default(),
+        },
+        OrderType::ReserveOrder {
+            id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            timestamp,
+            time_in_force,
+            replenish_threshold,
+            replenish_amount,
+            auto_replenish,
+            ..
+        } => OrderType::ReserveOrder {
+            id: *id,
+            price: *price,
+            visible_quantity: *visible_quantity,
+            hidden_quantity: *hidden_quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            replenish_threshold: *replenish_threshold,
+            replenish_amount: *replenish_amount,
+            auto_replenish: *auto_replenish,
+            extra_fields: T::default(),
+        },
+    }
+}
+
 /// trade listener specification
 pub type TradeListener = fn(&MatchResult);
 
@@ -68,145 +413,7 @@ where
     where
         T: Default,
     {
-        match order {
-            OrderType::Standard {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::Standard {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: T::default(),
-            },
-            OrderType::IcebergOrder {
-                id,
-                price,
-                visible_quantity,
-                hidden_quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::IcebergOrder {
-                id: *id,
-                price: *price,
-                visible_quantity: *visible_quantity,
-                hidden_quantity: *hidden_quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: T::default(),
-            },
-            OrderType::PostOnly {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::PostOnly {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: T::default(),
-            },
-            OrderType::TrailingStop {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                trail_amount,
-                last_reference_price,
-                ..
-            } => OrderType::TrailingStop {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                trail_amount: *trail_amount,
-                last_reference_price: *last_reference_price,
-                extra_fields: T::default(),
-            },
-            OrderType::PeggedOrder {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                reference_price_offset,
-                reference_price_type,
-                ..
-            } => OrderType::PeggedOrder {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                reference_price_offset: *reference_price_offset,
-                reference_price_type: *reference_price_type,
-                extra_fields: T::default(),
-            },
-            OrderType::MarketToLimit {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::MarketToLimit {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: T::default(),
-            },
-            OrderType::ReserveOrder {
-                id,
-                price,
-                visible_quantity,
-                hidden_quantity,
-                side,
-                timestamp,
-                time_in_force,
-                replenish_threshold,
-                replenish_amount,
-                auto_replenish,
-                ..
-            } => OrderType::ReserveOrder {
-                id: *id,
-                price: *price,
-                visible_quantity: *visible_quantity,
-                hidden_quantity: *hidden_quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                replenish_threshold: *replenish_threshold,
-                replenish_amount: *replenish_amount,
-                auto_replenish: *auto_replenish,
-                extra_fields: T::default(),
-            },
-        }
+        order_from_unit_type(order)
     }
     /// Create a new order book for the given symbol
     pub fn new(symbol: &str) -> Self {
@@ -215,50 +422,545 @@ where
 
         Self {
             symbol: symbol.to_string(),
-            bids: DashMap::new(),
-            asks: DashMap::new(),
+            bids: BookSide::new(Side::Buy),
+            asks: BookSide::new(Side::Sell),
             order_locations: DashMap::new(),
+            order_constraints: DashMap::new(),
+            display_sizes: DashMap::new(),
+            order_sequences: DashMap::new(),
+            irregular_orders: DashSet::new(),
+            reentries: DashSet::new(),
+            sequence: AtomicU64::new(0),
+            last_acceptance_nanos: AtomicU64::new(0),
+            executions: ExecutionTracker::default(),
+            order_accounts: DashMap::new(),
+            account_orders: DashMap::new(),
+            client_orders: DashMap::new(),
+            order_client_ids: DashMap::new(),
+            expiry_log: ExpiryLog::default(),
+            level_tickets: LevelTickets::default(),
+            #[cfg(test)]
+            level_faults: Default::default(),
             transaction_id_generator: UuidGenerator::new(namespace),
             last_trade_price: AtomicU64::new(0),
             has_traded: AtomicBool::new(false),
-            market_close_timestamp: AtomicU64::new(0),
-            has_market_close: AtomicBool::new(false),
+            session_traded: AtomicBool::new(false),
+            options: OptionsCell::default(),
+            pool_config: PoolConfig::default(),
+            pool_counters: PoolCounters::default(),
+            stats: StatCounters::default(),
             cache: PriceLevelCache::new(),
+            price_scale: PriceScale::default(),
+            price_floor: 0,
             trade_listener: None,
+            trade_channel: None,
+            trade_tape: None,
+            rate_limiter: None,
+            clock: Arc::new(SystemClock),
+            memory_watermarks: None,
+            memory_pressure_listener: None,
+            memory_pressure: AtomicU8::new(MemoryPressure::Normal as u8),
+            event_listener: None,
+            version: AtomicU64::new(0),
+            writes: WriteTracker::default(),
+            bbo_listener: None,
+            last_bbo: Mutex::new(TopOfBook::default()),
+            special_prices: SpecialPriceSection::default(),
+            dark_pool: DarkPool::default(),
+            short_sale_rule: None,
+            short_sale_restricted: AtomicBool::new(false),
+            session: SessionState::default(),
+            circuit_breaker: CircuitBreaker::default(),
+            quotes: Quotes::default(),
+            signals: Signals::default(),
+            notional: None,
+            kill_switches: KillSwitches::default(),
+            amendments: None,
+            compact: None,
+            #[cfg(feature = "audit")]
+            audit: None,
+            #[cfg(feature = "metrics")]
+            metrics: LatencyMetrics::new(),
+            #[cfg(feature = "parallel")]
+            parallel_snapshot_depth: DEFAULT_PARALLEL_SNAPSHOT_DEPTH,
             _phantom: PhantomData,
         }
     }
 
+    /// Create a new order book whose matching engine sizes its pooled vectors
+    /// according to `pool_config`. An `initial_capacity` above `max_capacity`
+    /// is lowered to it
+    pub fn new_with_config(symbol: &str, mut pool_config: PoolConfig) -> Self {
+        pool_config.initial_capacity = pool_config.initial_capacity.min(pool_config.max_capacity);
+        let mut order_book = Self::new(symbol);
+        order_book.pool_config = pool_config;
+        order_book
+    }
+
+    /// Create an order book whose runs can be replayed exactly.
+    ///
+    /// The book reads the time from `clock`, typically a
+    /// [`ManualClock`](crate::utils::ManualClock), and numbers its
+    /// transactions from a namespace derived from the symbol rather than a
+    /// random one. Applying the same requests in the same order at the same
+    /// clock readings then gives the same orders, trades and snapshots.
+    pub fn new_deterministic(symbol: &str, clock: Arc<dyn Clock>) -> Self {
+        let mut order_book = Self::new(symbol);
+        let namespace = Uuid::new_v5(&Uuid::NAMESPACE_OID, symbol.as_bytes());
+        order_book.transaction_id_generator = UuidGenerator::new(namespace);
+        order_book.clock = clock;
+        order_book
+    }
+
+    /// Sizing of the vectors borrowed from the matching pool
+    pub fn pool_config(&self) -> PoolConfig {
+        self.pool_config
+    }
+
+    /// How often matching found a pooled vector, had to allocate one, or
+    /// outgrew the one it got
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool_counters.snapshot()
+    }
+
+    /// How much of the best price cache a change to the book discards
+    pub fn cache_invalidation(&self) -> CacheInvalidation {
+        self.cache.granularity()
+    }
+
+    /// Choose how much of the best price cache a change to the book discards.
+    /// Price-aware invalidation, the default, keeps the cached best price of
+    /// a side when orders are added or cancelled away from the touch
+    pub fn set_cache_invalidation(&self, granularity: CacheInvalidation) {
+        self.cache.set_granularity(granularity);
+    }
+
     /// Create a new order book for the given symbol with a trade listner
     pub fn with_trade_listener(symbol: &str, trade_listener: TradeListener) -> Self {
-        let namespace = Uuid::new_v4();
+        let mut order_book = Self::new(symbol);
+        order_book.trade_listener = Some(trade_listener);
+        order_book
+    }
 
-        Self {
-            symbol: symbol.to_string(),
-            bids: DashMap::new(),
-            asks: DashMap::new(),
-            order_locations: DashMap::new(),
-            transaction_id_generator: UuidGenerator::new(namespace),
-            last_trade_price: AtomicU64::new(0),
-            has_traded: AtomicBool::new(false),
-            market_close_timestamp: AtomicU64::new(0),
-            has_market_close: AtomicBool::new(false),
-            cache: PriceLevelCache::new(),
-            trade_listener: Some(trade_listener),
-            _phantom: PhantomData,
+    /// Create a new order book for the given symbol whose match results are pushed
+    /// into a bounded lock-free queue of `capacity` entries and handed to `consumer`
+    /// on a dedicated thread, instead of being delivered synchronously like with
+    /// [`with_trade_listener`](Self::with_trade_listener).
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero or the consumer thread cannot be spawned.
+    pub fn with_trade_channel<F>(
+        symbol: &str,
+        capacity: usize,
+        policy: OverflowPolicy,
+        consumer: F,
+    ) -> Self
+    where
+        F: FnMut(MatchResult) + Send + 'static,
+    {
+        let mut order_book = Self::new(symbol);
+        order_book.trade_channel = Some(TradeChannel::new(symbol, capacity, policy, consumer));
+        order_book
+    }
+
+    /// Get the trade channel, if the book was created with one
+    pub fn trade_channel(&self) -> Option<&TradeChannel> {
+        self.trade_channel.as_ref()
+    }
+
+    /// Monitor the book against the given memory watermarks
+    pub fn set_memory_watermarks(&mut self, watermarks: MemoryWatermarks) {
+        self.memory_watermarks = Some(watermarks);
+        self.update_memory_pressure();
+    }
+
+    /// Set the callback invoked when the memory pressure level changes
+    pub fn set_memory_pressure_listener(&mut self, listener: MemoryPressureListener) {
+        self.memory_pressure_listener = Some(listener);
+    }
+
+    /// Current memory pressure level. Always `Normal` without watermarks
+    pub fn memory_pressure(&self) -> MemoryPressure {
+        MemoryPressure::from_u8(self.memory_pressure.load(Ordering::Relaxed))
+    }
+
+    /// Current number of resting orders and levels, with an estimate of the memory they use
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::estimate(
+            self.order_locations.len() as u64,
+            (self.bids.len() + self.asks.len()) as u64,
+        )
+    }
+
+    /// Re-evaluate the memory pressure level, notifying the listener if it changed
+    pub(super) fn update_memory_pressure(&self) {
+        let Some(watermarks) = self.memory_watermarks else {
+            return;
+        };
+
+        let usage = self.memory_usage();
+        let current = watermarks.pressure(&usage);
+        let previous =
+            MemoryPressure::from_u8(self.memory_pressure.swap(current as u8, Ordering::Relaxed));
+        if previous == current {
+            return;
+        }
+
+        trace!(
+            "Order book {}: memory pressure changed from {:?} to {:?} ({:?})",
+            self.symbol, previous, current, usage
+        );
+        if let Some(listener) = &self.memory_pressure_listener {
+            listener(&MemoryPressureEvent {
+                symbol: self.symbol.clone(),
+                previous,
+                current,
+                usage,
+            });
+        }
+    }
+
+    /// Reject orders priced far from the market while memory pressure is critical
+    pub(super) fn check_far_order_under_pressure(
+        &self,
+        price: u64,
+        side: Side,
+    ) -> Result<(), OrderBookError> {
+        let Some(max_distance) = self
+            .memory_watermarks
+            .and_then(|watermarks| watermarks.reject_far_orders_beyond)
+        else {
+            return Ok(());
+        };
+        if self.memory_pressure() != MemoryPressure::Critical {
+            return Ok(());
+        }
+
+        let reference_price = match side {
+            Side::Buy => self.best_bid().or_else(|| self.best_ask()),
+            Side::Sell => self.best_ask().or_else(|| self.best_bid()),
+        };
+        let Some(reference_price) = reference_price else {
+            return Ok(());
+        };
+
+        if price.abs_diff(reference_price) > max_distance {
+            return Err(OrderBookError::MemoryPressure {
+                price,
+                reference_price,
+            });
+        }
+        Ok(())
+    }
+
+    /// Set the listener receiving the events published by the book
+    pub fn set_event_listener(&mut self, listener: EventListener) {
+        self.event_listener = Some(listener);
+    }
+
+    /// Publish an event to the listener, if any
+    pub(super) fn emit_event(&self, event: &OrderBookEvent) {
+        #[cfg(feature = "audit")]
+        self.audit_event(event);
+        if let Some(listener) = &self.event_listener {
+            listener(event);
+        }
+    }
+
+    /// Returns true if published events reach anyone, so building them is
+    /// worth it
+    pub(super) fn publishes_events(&self) -> bool {
+        #[cfg(feature = "audit")]
+        if self.audit.is_some() {
+            return true;
+        }
+        self.event_listener.is_some()
+    }
+
+    /// Publish an `OrderRejected` event for `order_id`, handing the error back to the caller
+    pub(super) fn reject_order(&self, order_id: OrderId, error: OrderBookError) -> OrderBookError {
+        self.stats.record_rejected();
+        // A duplicate of a resting order leaves the status of that order alone
+        if !self.order_locations.contains_key(&order_id) {
+            self.executions.finish_rejected(order_id);
+        }
+        self.release_client_order_id(order_id);
+        self.reentries.remove(&order_id);
+        if !self.publishes_events() {
+            return error;
+        }
+        trace!(
+            "Order book {}: Rejected order {}: {}",
+            self.symbol, order_id, error
+        );
+
+        let event = OrderBookEvent::OrderRejected(OrderReject {
+            order_id,
+            reason: RejectReason::from(&error),
+            error,
+            timestamp: self.now(),
+            config_version: self.config_version(),
+        });
+        self.emit_event(&event);
+        match event {
+            OrderBookEvent::OrderRejected(reject) => reject.error,
+            _ => unreachable!("the event was built as a reject"),
+        }
+    }
+
+    /// Current version of the book, incremented whenever resting orders change
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Record a change to the resting orders. Must be called once the change
+    /// is complete, as it may read the top of book to notify the BBO listener
+    /// and evaluate the registered signals.
+    pub(super) fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+        if self.bbo_listener.is_some() {
+            self.notify_bbo_change();
+        }
+        self.evaluate_signals();
+    }
+
+    /// Read the time from `clock` from now on, instead of the system clock
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The time source of the book
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Current time of the book's clock, in milliseconds since UNIX epoch
+    pub fn now(&self) -> u64 {
+        self.clock.now_millis()
+    }
+
+    /// Orders added, matched, cancelled and rejected, and the trades executed,
+    /// since the book was created or `reset_stats` was last called
+    pub fn stats(&self) -> BookStats {
+        BookStats {
+            notional: self.notional.as_ref().map(NotionalTracker::snapshot),
+            ..self.stats.snapshot()
+        }
+    }
+
+    /// Zero the activity counters and the notional traded
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+        if let Some(notional) = &self.notional {
+            notional.reset();
+        }
+    }
+
+    /// Latency distribution of the add, cancel and match operations recorded so far
+    #[cfg(feature = "metrics")]
+    pub fn metrics_report(&self) -> MetricsReport {
+        MetricsReport {
+            add_order: self.metrics.stats(Operation::Add),
+            cancel_order: self.metrics.stats(Operation::Cancel),
+            match_order: self.metrics.stats(Operation::Match),
+        }
+    }
+
+    /// Discard the latencies recorded so far
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// Number of levels from which snapshots are built in parallel
+    #[cfg(feature = "parallel")]
+    pub fn parallel_snapshot_depth(&self) -> usize {
+        self.parallel_snapshot_depth
+    }
+
+    /// Build snapshots covering at least `depth` levels of a side on the rayon
+    /// thread pool, one level per task. Below it the coordination costs more
+    /// than it saves, see the `Snapshot - Parallel` benchmark for the crossover
+    /// on your hardware. Pass `usize::MAX` to always build them sequentially.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel_snapshot_depth(&mut self, depth: usize) {
+        self.parallel_snapshot_depth = depth;
+    }
+
+    /// Build one entry per price in `prices`, keeping their order and skipping
+    /// prices for which `level` returns `None`
+    fn collect_levels<R, F>(&self, prices: &[u64], level: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(u64) -> Option<R> + Send + Sync,
+    {
+        #[cfg(feature = "parallel")]
+        if prices.len() >= self.parallel_snapshot_depth {
+            use rayon::prelude::*;
+            return prices
+                .par_iter()
+                .filter_map(|&price| level(price))
+                .collect();
+        }
+        prices.iter().filter_map(|&price| level(price)).collect()
+    }
+
+    /// Acceptance sequence number of a resting order.
+    ///
+    /// Every order that rests in the book is numbered from a single counter
+    /// while it holds its price level, so the numbers give a total order over
+    /// orders accepted concurrently on different threads. Within a price level
+    /// orders are matched in exactly this order, whatever their timestamps.
+    /// An order whose price is changed is numbered again, as it loses its
+    /// priority. Returns `None` for orders that are not resting.
+    pub fn order_sequence(&self, order_id: OrderId) -> Option<u64> {
+        self.order_sequences
+            .get(&order_id)
+            .map(|acceptance| acceptance.sequence)
+    }
+
+    /// When a resting order was accepted, in nanoseconds since UNIX epoch by
+    /// the book's clock, or `None` for orders that are not resting.
+    ///
+    /// Unlike order timestamps, which the sender sets in milliseconds,
+    /// acceptance times are unique within the book: an order accepted in the
+    /// same nanosecond as the previous one, or while the clock stands still or
+    /// steps back, is stamped one nanosecond after it. Within a price level
+    /// they increase in queue order. Like the sequence number, the time is
+    /// taken again when a price change re-queues the order.
+    pub fn accepted_at_nanos(&self, order_id: OrderId) -> Option<u64> {
+        self.order_sequences
+            .get(&order_id)
+            .map(|acceptance| acceptance.nanos)
+    }
+
+    /// Last acceptance sequence number handed out, 0 if no order ever rested
+    pub fn last_sequence(&self) -> u64 {
+        self.sequence.load(Ordering::Acquire)
+    }
+
+    /// Hand out the next acceptance sequence number and time. Must be called
+    /// while the price level receiving the order is held.
+    pub(super) fn next_acceptance(&self) -> Acceptance {
+        let sequence = self.sequence.fetch_add(1, Ordering::AcqRel) + 1;
+        let now = self.clock.now_nanos();
+        let previous = self
+            .last_acceptance_nanos
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_else(|last| last);
+        Acceptance {
+            sequence,
+            nanos: now.max(previous + 1),
+        }
+    }
+
+    /// Orders resting at `price_level`, in queue order.
+    ///
+    /// The level lists its orders by their millisecond timestamps, which tie
+    /// for orders sent in the same millisecond, so they are put back in the
+    /// order of their acceptance sequence numbers.
+    pub(super) fn queued_orders(&self, price_level: &PriceLevel) -> Vec<Arc<OrderType<()>>> {
+        let mut orders = price_level.iter_orders();
+        self.sort_by_acceptance(&mut orders);
+        orders
+    }
+
+    /// Put orders of one price level in the order of their acceptance sequence numbers
+    fn sort_by_acceptance(&self, orders: &mut [Arc<OrderType<()>>]) {
+        orders.sort_by_cached_key(|order| {
+            self.order_sequences
+                .get(&order.id())
+                .map_or(u64::MAX, |acceptance| acceptance.sequence)
+        });
+    }
+
+    /// Renumber a resting order that went to the back of its price level
+    pub(super) fn requeue_acceptance(&self, order_id: OrderId) {
+        if let Some(mut acceptance) = self.order_sequences.get_mut(&order_id) {
+            *acceptance = self.next_acceptance();
         }
     }
 
+    /// Record that a resting order keeps its price level off the level's own
+    /// FIFO matching until it leaves the book
+    pub(super) fn mark_irregular(&self, order_id: OrderId, side: Side, price: u64) {
+        if self.irregular_orders.insert(order_id) {
+            match side {
+                Side::Buy => self.bids.add_irregular(price),
+                Side::Sell => self.asks.add_irregular(price),
+            }
+        }
+    }
+
+    /// Drop the tracking kept for an order that no longer rests in the book
+    pub(super) fn forget_order(&self, order_id: OrderId) {
+        let location = self.order_locations.remove(&order_id);
+        if self.irregular_orders.remove(&order_id).is_some()
+            && let Some((_, (price, side))) = location
+        {
+            match side {
+                Side::Buy => self.bids.remove_irregular(price),
+                Side::Sell => self.asks.remove_irregular(price),
+            }
+        }
+        self.order_constraints.remove(&order_id);
+        self.display_sizes.remove(&order_id);
+        self.order_sequences.remove(&order_id);
+        self.executions.remove(order_id);
+        self.unindex_account(order_id);
+    }
+
+    /// Record the final status of an order that stopped working, freeing its
+    /// client order id
+    pub(super) fn finish_order(&self, order_id: OrderId, status: OrderStatus) {
+        self.executions.finish(order_id, status);
+        self.release_client_order_id(order_id);
+    }
+
+    /// Cumulative quantity executed by a resting order, both on entry as an
+    /// aggressor and since then as a maker, or `None` if the order is not resting.
+    ///
+    /// Quantity amendments do not reset it, so together with the quantity left
+    /// it gives the size the order was originally worked for.
+    pub fn executed_so_far(&self, order_id: OrderId) -> Option<u64> {
+        if let Some(state) = self.executions.get(order_id) {
+            return Some(state.executed_quantity);
+        }
+        self.order_locations.contains_key(&order_id).then_some(0)
+    }
+
+    /// Visible quantity a resting iceberg or reserve order was entered with,
+    /// which price amendments restore, or `None` for other orders
+    pub fn display_size(&self, order_id: OrderId) -> Option<u64> {
+        self.display_sizes.get(&order_id).map(|display| *display)
+    }
+
     /// Get the symbol of this order book
     pub fn symbol(&self) -> &str {
         &self.symbol
     }
 
+    /// Get the price scale used to convert decimal prices
+    pub fn price_scale(&self) -> PriceScale {
+        self.price_scale
+    }
+
+    /// Set the price scale used to convert decimal prices in the `*_f64` order entry methods
+    pub fn set_price_scale(&mut self, price_scale: PriceScale) {
+        self.price_scale = price_scale;
+    }
+
+    /// Set the round lot size. Trades for less than it are flagged as odd-lot;
+    /// a size of 0 disables the flag
+    pub fn set_round_lot(&self, round_lot: u64) {
+        self.update_options(|options| options.round_lot = round_lot);
+    }
+
     /// Set the market close timestamp for DAY orders
     pub fn set_market_close_timestamp(&self, timestamp: u64) {
-        self.market_close_timestamp
-            .store(timestamp, Ordering::SeqCst);
-        self.has_market_close.store(true, Ordering::SeqCst);
+        self.update_options(|options| options.market_close_timestamp = Some(timestamp));
         trace!(
             "Order book {}: Set market close timestamp to {}",
             self.symbol, timestamp
@@ -267,31 +969,41 @@ where
 
     /// Clear the market close timestamp
     pub fn clear_market_close_timestamp(&self) {
-        self.has_market_close.store(false, Ordering::SeqCst);
+        self.update_options(|options| options.market_close_timestamp = None);
     }
 
     /// Get the best bid price, if any
     pub fn best_bid(&self) -> Option<u64> {
+        if let Some(storage) = self.compact_storage() {
+            return storage.best_bid();
+        }
         if let Some(cached_bid) = self.cache.get_cached_best_bid() {
             return Some(cached_bid);
         }
 
-        let best_price = self.bids.iter().map(|item| *item.key()).max();
+        let generation = self.cache.generation(Side::Buy);
+        let best_price = self.bids.best();
 
-        self.cache.update_best_prices(best_price, None);
+        self.cache
+            .update_best_price(Side::Buy, generation, best_price);
 
         best_price
     }
 
     /// Get the best ask price, if any
     pub fn best_ask(&self) -> Option<u64> {
+        if let Some(storage) = self.compact_storage() {
+            return storage.best_ask();
+        }
         if let Some(cached_ask) = self.cache.get_cached_best_ask() {
             return Some(cached_ask);
         }
 
-        let best_price = self.asks.iter().map(|item| *item.key()).min();
+        let generation = self.cache.generation(Side::Sell);
+        let best_price = self.asks.best();
 
-        self.cache.update_best_prices(None, best_price);
+        self.cache
+            .update_best_price(Side::Sell, generation, best_price);
 
         best_price
     }
@@ -309,6 +1021,9 @@ where
 
     /// Get the last trade price, if any
     pub fn last_trade_price(&self) -> Option<u64> {
+        if let Some(storage) = self.compact_storage() {
+            return storage.last_trade_price();
+        }
         if self.has_traded.load(Ordering::Relaxed) {
             Some(self.last_trade_price.load(Ordering::Relaxed))
         } else {
@@ -327,7 +1042,9 @@ where
         }
     }
 
-    /// Get all orders at a specific price level
+    /// Get all orders at a specific price level. For levels holding many
+    /// orders see [`iter_orders_at_price`](Self::iter_orders_at_price) and
+    /// [`get_orders_at_price_page`](Self::get_orders_at_price_page)
     pub fn get_orders_at_price(&self, price: u64, side: Side) -> Vec<Arc<OrderType<T>>>
     where
         T: Default,
@@ -336,14 +1053,16 @@ where
             "Order book {}: Getting orders at price {} for side {:?}",
             self.symbol, price, side
         );
+        if let Some(storage) = self.compact_storage() {
+            return Self::get_compact_orders_at_price(&storage, price, side);
+        }
         let price_levels = match side {
             Side::Buy => &self.bids,
             Side::Sell => &self.asks,
         };
 
         if let Some(price_level) = price_levels.get(&price) {
-            price_level
-                .iter_orders()
+            self.queued_orders(&price_level)
                 .into_iter()
                 .map(|order| Arc::new(self.convert_from_unit_type(&order)))
                 .collect()
@@ -358,6 +1077,9 @@ where
         T: Default,
     {
         trace!("Order book {}: Getting all orders", self.symbol);
+        if let Some(storage) = self.compact_storage() {
+            return Self::get_all_compact_orders(&storage);
+        }
         let mut result = Vec::new();
 
         // Get all bid orders
@@ -390,6 +1112,9 @@ where
     where
         T: Default,
     {
+        if let Some(storage) = self.compact_storage() {
+            return Self::get_compact_order(&storage, order_id);
+        }
         // Get the order location without locking
         if let Some(location) = self.order_locations.get(&order_id) {
             let (price, side) = *location;
@@ -413,18 +1138,78 @@ where
         None
     }
 
+    /// Get the execution constraints of a resting order.
+    /// Orders placed without constraints (or unknown ids) report the default, unconstrained value.
+    pub fn get_order_constraints(&self, order_id: OrderId) -> OrderConstraints {
+        self.order_constraints
+            .get(&order_id)
+            .map(|constraints| *constraints)
+            .unwrap_or_default()
+    }
+
     /// Match a market order against the book
     pub fn match_market_order(
         &self,
         order_id: OrderId,
         quantity: u64,
         side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        self.match_market_order_as(order_id, quantity, side, None)
+    }
+
+    /// Match a market order entered for `account_id` against the book
+    pub(super) fn match_market_order_as(
+        &self,
+        order_id: OrderId,
+        quantity: u64,
+        side: Side,
+        account_id: Option<&str>,
     ) -> Result<MatchResult, OrderBookError> {
         trace!(
             "Order book {}: Matching market order {} for {} at side {:?}",
             self.symbol, order_id, quantity, side
         );
-        OrderBook::<T>::match_order(self, order_id, side, quantity, None)
+        // Begun before the kill switch is checked, so throwing it waits for us
+        let _write = self.writes.begin();
+        self.check_kill_switch(account_id)
+            .map_err(|error| self.reject_order(order_id, error))?;
+        // Admitted: a final status recorded under a finished id no longer applies
+        self.executions.reopen(order_id);
+        self.advance_session();
+        let state = self.trading_state();
+        if state != TradingState::Open {
+            return Err(self.reject_order(order_id, OrderBookError::InvalidTradingState { state }));
+        }
+        if let Some(storage) = self.compact_storage() {
+            let result = self.match_compact_order(storage, order_id, side, quantity, None);
+            if result.remaining_quantity == quantity {
+                return Err(self.reject_order(
+                    order_id,
+                    OrderBookError::InsufficientLiquidity {
+                        side,
+                        requested: quantity,
+                        available: 0,
+                    },
+                ));
+            }
+            return Ok(result);
+        }
+        // Market orders never rest, so a depth limit leaves their remainder unfilled
+        let options = self.options();
+        let limit_price = self.depth_capped_limit(side, None, &options);
+        let result = self
+            .match_order_under(order_id, side, quantity, limit_price, &options, account_id)
+            .map_err(|error| self.reject_order(order_id, error))?;
+        // Whatever the book could not fill is cancelled
+        self.finish_order(
+            order_id,
+            if result.remaining_quantity == 0 {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::Cancelled
+            },
+        );
+        Ok(result)
     }
 
     /// Attempts to match a limit order in the order book.
@@ -474,43 +1259,78 @@ where
 
     /// Create a snapshot of the current order book state
     pub fn create_snapshot(&self, depth: usize) -> OrderBookSnapshot {
-        // Get all bid prices and sort them in descending order
-        let mut bid_prices: Vec<u64> = self.bids.iter().map(|item| *item.key()).collect();
-        bid_prices.sort_by(|a, b| b.cmp(a)); // Descending order
-        bid_prices.truncate(depth);
-
-        // Get all ask prices and sort them in ascending order
-        let mut ask_prices: Vec<u64> = self.asks.iter().map(|item| *item.key()).collect();
-        ask_prices.sort(); // Ascending order
-        ask_prices.truncate(depth);
-
-        let mut bid_levels = Vec::with_capacity(bid_prices.len());
-        let mut ask_levels = Vec::with_capacity(ask_prices.len());
-
-        // Create snapshots for each bid level
-        for price in bid_prices {
-            if let Some(price_level) = self.bids.get(&price) {
-                bid_levels.push(price_level.snapshot());
-            }
+        if let Some(storage) = self.compact_storage() {
+            return self.create_compact_snapshot(&storage, depth);
         }
+        // Best prices first: bids descending, asks ascending
+        let bid_prices = self.bids.best_prices(depth);
+        let ask_prices = self.asks.best_prices(depth);
 
-        // Create snapshots for each ask level
-        for price in ask_prices {
-            if let Some(price_level) = self.asks.get(&price) {
-                ask_levels.push(price_level.snapshot());
-            }
-        }
+        let snapshot_level = |levels: &BookSide, price| {
+            levels.get(&price).map(|price_level| {
+                let mut snapshot = price_level.snapshot();
+                self.sort_by_acceptance(&mut snapshot.orders);
+                snapshot
+            })
+        };
+        let bid_levels =
+            self.collect_levels(&bid_prices, |price| snapshot_level(&self.bids, price));
+        let ask_levels =
+            self.collect_levels(&ask_prices, |price| snapshot_level(&self.asks, price));
 
         OrderBookSnapshot {
             symbol: self.symbol.clone(),
-            timestamp: current_time_millis(),
+            timestamp: self.now(),
             bids: bid_levels,
             asks: ask_levels,
         }
     }
 
+    /// Create a market-by-order snapshot of the `depth` best levels of each
+    /// side, listing every individual order in priority order
+    pub fn create_l3_snapshot(&self, depth: usize) -> OrderBookL3Snapshot {
+        let bid_prices = self.bids.best_prices(depth);
+        let ask_prices = self.asks.best_prices(depth);
+
+        OrderBookL3Snapshot {
+            symbol: self.symbol.clone(),
+            timestamp: self.now(),
+            bids: self.collect_levels(&bid_prices, |price| {
+                self.l3_level(&self.bids, Side::Buy, price)
+            }),
+            asks: self.collect_levels(&ask_prices, |price| {
+                self.l3_level(&self.asks, Side::Sell, price)
+            }),
+        }
+    }
+
+    fn l3_level(&self, levels: &BookSide, side: Side, price: u64) -> Option<L3Level> {
+        let level = levels.get(&price)?;
+        let orders = self.queued_orders(&level);
+        Some(L3Level {
+            price,
+            orders: orders
+                .iter()
+                .map(|order| L3Order {
+                    id: order.id(),
+                    side,
+                    price,
+                    visible_quantity: order.visible_quantity(),
+                    hidden_quantity: order.hidden_quantity(),
+                    executed_quantity: self.executions.executed(order.id()),
+                    timestamp: order.timestamp(),
+                    sequence: self.order_sequence(order.id()),
+                    accepted_at_nanos: self.accepted_at_nanos(order.id()),
+                })
+                .collect(),
+        })
+    }
+
     /// Get the total volume at each price level
     pub fn get_volume_by_price(&self) -> (HashMap<u64, u64>, HashMap<u64, u64>) {
+        if let Some(storage) = self.compact_storage() {
+            return storage.get_volume_by_price();
+        }
         let mut bid_volumes = HashMap::new();
         let mut ask_volumes = HashMap::new();
 
@@ -530,4 +1350,42 @@ where
 
         (bid_volumes, ask_volumes)
     }
+
+    /// Liquidity resting within `bps` basis points of the mid price, as
+    /// `(bid_quantity, bid_notional, ask_quantity, ask_notional)`.
+    ///
+    /// Quantities include hidden quantity and notionals are `price * quantity`
+    /// summed over the levels inside the band. Returns zeros when either side
+    /// is empty, since there is no mid price to measure from.
+    pub fn liquidity_within_bps(&self, bps: u32) -> (u64, u64, u64, u64) {
+        let Some(mid) = self.mid_price() else {
+            return (0, 0, 0, 0);
+        };
+        let band = mid * f64::from(bps) / 10_000.0;
+        let (lower, upper) = (mid - band, mid + band);
+
+        // Level totals, read from the compact storage if the book has one
+        let (bid_levels, ask_levels) = self.get_volume_by_price();
+        let aggregate = |levels: &HashMap<u64, u64>, in_band: &dyn Fn(f64) -> bool| {
+            levels
+                .iter()
+                .filter(|(price, _)| in_band(**price as f64))
+                .fold(
+                    (0u64, 0u64),
+                    |(quantity, notional), (price, level_quantity)| {
+                        (
+                            quantity + level_quantity,
+                            notional.saturating_add(price.saturating_mul(*level_quantity)),
+                        )
+                    },
+                )
+        };
+        let (bid_quantity, bid_notional) = aggregate(&bid_levels, &|price| price >= lower);
+        let (ask_quantity, ask_notional) = aggregate(&ask_levels, &|price| price <= upper);
+        trace!(
+            "Order book {}: liquidity within {} bps of {}: bids {}, asks {}",
+            self.symbol, bps, mid, bid_quantity, ask_quantity
+        );
+        (bid_quantity, bid_notional, ask_quantity, ask_notional)
+    }
 }
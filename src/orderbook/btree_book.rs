@@ -0,0 +1,383 @@
+//! Price-time matching over `BTreeMap` price levels, shared by the
+//! single-threaded books, each keeping its resting orders its own way
+
+use super::validation::{ValidationIssue, ValidationReport};
+use pricelevel::{MatchResult, OrderId, Side, Transaction, UuidGenerator};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// What became of a resting order after an execution against it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Execution {
+    /// Quantity is left on display: the order keeps its place
+    InPlace,
+    /// The display was refreshed from hidden quantity: the order goes to the
+    /// back of its level
+    Refreshed,
+    /// Nothing is left: the order leaves the book
+    Filled,
+}
+
+/// How a book built on [`BTreeBook`] keeps its resting orders.
+///
+/// Levels queue one `Entry` per resting order: the order itself, or a handle
+/// to where the storage keeps it.
+pub(super) trait OrderStorage {
+    /// What a level queues for a resting order
+    type Entry;
+    /// A resting order as it leaves the storage
+    type Order;
+
+    /// Id of the order behind `entry`, `None` if the storage let go of it
+    fn order_id(&self, entry: &Self::Entry) -> Option<OrderId>;
+
+    /// Visible and hidden quantity left on the order behind `entry`
+    fn quantity(&self, entry: &Self::Entry) -> u64;
+
+    /// Execute up to `quantity` against the displayed quantity of the order
+    /// behind `entry`, returning the quantity executed and what became of
+    /// the order. Nothing changes if no quantity is displayed
+    fn execute(&mut self, entry: &mut Self::Entry, quantity: u64) -> (u64, Execution);
+
+    /// Let go of an order that left the book, returning it
+    fn release(&mut self, entry: Self::Entry) -> Option<Self::Order>;
+}
+
+/// Bid and ask price levels in `BTreeMap`s, each a FIFO queue of storage
+/// entries, matched in price-time priority.
+///
+/// Nothing is locked or atomic: changes take `&mut self`, and the books built
+/// on it are driven from a single thread.
+pub(super) struct BTreeBook<S: OrderStorage> {
+    storage: S,
+    bids: BTreeMap<u64, VecDeque<S::Entry>>,
+    asks: BTreeMap<u64, VecDeque<S::Entry>>,
+    order_locations: HashMap<OrderId, (u64, Side)>,
+    transaction_id_generator: UuidGenerator,
+    last_trade_price: Option<u64>,
+}
+
+impl<S: OrderStorage> BTreeBook<S> {
+    /// Create an empty book keeping its orders in `storage`, with room for
+    /// `orders` resting orders in its index, and numbering its transactions
+    /// from `namespace`
+    pub(super) fn new(storage: S, orders: usize, namespace: Uuid) -> Self {
+        Self {
+            storage,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            order_locations: HashMap::with_capacity(orders),
+            transaction_id_generator: UuidGenerator::new(namespace),
+            last_trade_price: None,
+        }
+    }
+
+    pub(super) fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    pub(super) fn storage_mut(&mut self) -> &mut S {
+        &mut self.storage
+    }
+
+    /// Number of resting orders
+    pub(super) fn len(&self) -> usize {
+        self.order_locations.len()
+    }
+
+    /// Price and side of a resting order
+    pub(super) fn location(&self, order_id: OrderId) -> Option<(u64, Side)> {
+        self.order_locations.get(&order_id).copied()
+    }
+
+    pub(super) fn best_bid(&self) -> Option<u64> {
+        self.bids.keys().next_back().copied()
+    }
+
+    pub(super) fn best_ask(&self) -> Option<u64> {
+        self.asks.keys().next().copied()
+    }
+
+    pub(super) fn last_trade_price(&self) -> Option<u64> {
+        self.last_trade_price
+    }
+
+    /// The best opposite price an order on `side` at `price` would trade
+    /// against, if any
+    pub(super) fn crossed_price(&self, price: u64, side: Side) -> Option<u64> {
+        match side {
+            Side::Buy => self.best_ask().filter(|&ask| price >= ask),
+            Side::Sell => self.best_bid().filter(|&bid| price <= bid),
+        }
+    }
+
+    /// The levels of one side, keyed by price
+    pub(super) fn levels(&self, side: Side) -> &BTreeMap<u64, VecDeque<S::Entry>> {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    /// The entry of a resting order
+    pub(super) fn entry(&self, order_id: OrderId) -> Option<&S::Entry> {
+        let (price, side) = self.location(order_id)?;
+        self.levels(side)
+            .get(&price)?
+            .iter()
+            .find(|entry| self.storage.order_id(entry) == Some(order_id))
+    }
+
+    /// The entry of a resting order, to change it in place
+    pub(super) fn entry_mut(&mut self, order_id: OrderId) -> Option<&mut S::Entry> {
+        let (price, side) = self.location(order_id)?;
+        let storage = &self.storage;
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        levels
+            .get_mut(&price)?
+            .iter_mut()
+            .find(|entry| storage.order_id(entry) == Some(order_id))
+    }
+
+    /// Queue `entry` at the back of the level at `price`
+    pub(super) fn rest(&mut self, order_id: OrderId, price: u64, side: Side, entry: S::Entry) {
+        self.order_locations.insert(order_id, (price, side));
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        levels.entry(price).or_default().push_back(entry);
+    }
+
+    /// Take a resting order out of the book
+    pub(super) fn remove(&mut self, order_id: OrderId) -> Option<S::Order> {
+        let (price, side) = self.order_locations.remove(&order_id)?;
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let queue = levels.get_mut(&price)?;
+        let entry = queue
+            .iter()
+            .position(|entry| self.storage.order_id(entry) == Some(order_id))
+            .and_then(|position| queue.remove(position));
+        if queue.is_empty() {
+            levels.remove(&price);
+        }
+        self.storage.release(entry?)
+    }
+
+    /// Take a whole price level out of the book, returning its orders in
+    /// queue order
+    pub(super) fn remove_level(&mut self, price: u64, side: Side) -> Vec<S::Order> {
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let Some(queue) = levels.remove(&price) else {
+            return Vec::new();
+        };
+        queue
+            .into_iter()
+            .filter_map(|entry| {
+                if let Some(order_id) = self.storage.order_id(&entry) {
+                    self.order_locations.remove(&order_id);
+                }
+                self.storage.release(entry)
+            })
+            .collect()
+    }
+
+    /// Quantity an order on `side` could execute right now within
+    /// `limit_price`, counted up to `quantity`
+    pub(super) fn available(&self, side: Side, quantity: u64, limit_price: Option<u64>) -> u64 {
+        let levels: Box<dyn Iterator<Item = (&u64, &VecDeque<S::Entry>)>> = match side {
+            Side::Buy => Box::new(self.asks.iter()),
+            Side::Sell => Box::new(self.bids.iter().rev()),
+        };
+        let mut available = 0u64;
+        for (&price, queue) in levels {
+            if !crosses(side, price, limit_price) {
+                break;
+            }
+            for entry in queue {
+                available = available.saturating_add(self.storage.quantity(entry));
+                if available >= quantity {
+                    return quantity;
+                }
+            }
+        }
+        available
+    }
+
+    /// Total quantity at each price level, for bids and asks
+    pub(super) fn volumes(&self) -> (HashMap<u64, u64>, HashMap<u64, u64>) {
+        let volumes = |levels: &BTreeMap<u64, VecDeque<S::Entry>>| {
+            levels
+                .iter()
+                .map(|(&price, queue)| {
+                    let volume = queue.iter().map(|entry| self.storage.quantity(entry));
+                    (price, volume.sum())
+                })
+                .collect()
+        };
+        (volumes(&self.bids), volumes(&self.asks))
+    }
+
+    /// Broken invariants between the levels and the location index: empty
+    /// levels, orders indexed at a level that does not hold them or held by a
+    /// level they are not indexed at, and a crossed book
+    pub(super) fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut resting = HashSet::new();
+        for side in [Side::Buy, Side::Sell] {
+            for (&price, queue) in self.levels(side) {
+                report.levels_checked += 1;
+                report.orders_checked += queue.len();
+                if queue.is_empty() {
+                    report
+                        .issues
+                        .push(ValidationIssue::EmptyLevel { price, side });
+                }
+                for order_id in queue
+                    .iter()
+                    .filter_map(|entry| self.storage.order_id(entry))
+                {
+                    resting.insert(order_id);
+                    match self.location(order_id) {
+                        None => report.issues.push(ValidationIssue::UnindexedOrder {
+                            order_id,
+                            price,
+                            side,
+                        }),
+                        Some(indexed) if indexed != (price, side) => {
+                            report.issues.push(ValidationIssue::WrongLocation {
+                                order_id,
+                                indexed,
+                                actual: (price, side),
+                            })
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        for (&order_id, &(price, side)) in &self.order_locations {
+            if !resting.contains(&order_id) {
+                report.issues.push(ValidationIssue::MissingOrder {
+                    order_id,
+                    price,
+                    side,
+                });
+            }
+        }
+        if let (Some(best_bid), Some(best_ask)) = (self.best_bid(), self.best_ask())
+            && best_bid >= best_ask
+        {
+            report
+                .issues
+                .push(ValidationIssue::CrossedBook { best_bid, best_ask });
+        }
+        report
+    }
+
+    /// Match an incoming order against the opposite side, best price first and
+    /// in queue order within a price. Orders refreshed from hidden quantity
+    /// go to the back of their level and keep trading there
+    pub(super) fn match_order(
+        &mut self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+    ) -> MatchResult {
+        let mut match_result = MatchResult::new(order_id, quantity);
+        let mut remaining = quantity;
+        let levels = match side {
+            Side::Buy => &mut self.asks,
+            Side::Sell => &mut self.bids,
+        };
+
+        while remaining > 0 {
+            let best = match side {
+                Side::Buy => levels.first_entry(),
+                Side::Sell => levels.last_entry(),
+            };
+            let Some(mut level) = best else {
+                break;
+            };
+            let price = *level.key();
+            if !crosses(side, price, limit_price) {
+                break;
+            }
+
+            let queue = level.get_mut();
+            let mut stalled = false;
+            let mut traded = false;
+            while remaining > 0 {
+                let Some(maker) = queue.front_mut() else {
+                    break;
+                };
+                let Some(maker_id) = self.storage.order_id(maker) else {
+                    // Entry of an order the storage let go of
+                    queue.pop_front();
+                    continue;
+                };
+                let (executed, execution) = self.storage.execute(maker, remaining);
+                if executed == 0 {
+                    // Nothing displayed to trade against, leave the level as is
+                    stalled = true;
+                    break;
+                }
+                match_result.add_transaction(Transaction::new(
+                    self.transaction_id_generator.next(),
+                    order_id,
+                    maker_id,
+                    price,
+                    executed,
+                    side,
+                ));
+                remaining -= executed;
+                traded = true;
+                match execution {
+                    Execution::InPlace => {}
+                    Execution::Refreshed => {
+                        if let Some(maker) = queue.pop_front() {
+                            queue.push_back(maker);
+                        }
+                    }
+                    Execution::Filled => {
+                        if let Some(maker) = queue.pop_front() {
+                            self.storage.release(maker);
+                        }
+                        self.order_locations.remove(&maker_id);
+                        match_result.add_filled_order_id(maker_id);
+                    }
+                }
+            }
+            if traded {
+                self.last_trade_price = Some(price);
+            }
+            if queue.is_empty() {
+                level.remove();
+            }
+            if stalled {
+                break;
+            }
+        }
+
+        match_result
+    }
+}
+
+/// Whether an order on `side` limited to `limit_price` trades at `price`
+fn crosses(side: Side, price: u64, limit_price: Option<u64>) -> bool {
+    match (side, limit_price) {
+        (_, None) => true,
+        (Side::Buy, Some(limit)) => price <= limit,
+        (Side::Sell, Some(limit)) => price >= limit,
+    }
+}
@@ -0,0 +1,205 @@
+//! A small DSL for setting up order books with resting orders
+
+use super::book::{OrderBook, TradeListener};
+use super::error::OrderBookError;
+use super::trade_channel::TradeChannel;
+use crate::utils::{Clock, SystemClock};
+use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+use std::sync::Arc;
+
+/// Builds an order book pre-populated with resting orders.
+///
+/// Orders get sequential ids (`OrderId::from_u64(1)`, `2`, ...) in the order
+/// they are declared, so scenarios can refer to them without keeping handles
+/// around. They are added to the book in that same order, which means
+/// declaring crossing orders will make them trade.
+///
+/// ```
+/// use orderbook_rs::{BookBuilder, OrderBook};
+/// use pricelevel::Side;
+///
+/// let book: OrderBook = BookBuilder::new("BTC/USD")
+///     .bids([(100, 10), (99, 5)])
+///     .asks([(101, 7)])
+///     .iceberg(Side::Sell, 102, 5, 20)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(book.best_bid(), Some(100));
+/// assert_eq!(book.best_ask(), Some(101));
+/// ```
+pub struct BookBuilder<T = ()> {
+    symbol: String,
+    orders: Vec<OrderType<T>>,
+    next_id: u64,
+    time_in_force: TimeInForce,
+    trade_listener: Option<TradeListener>,
+    trade_channel: Option<TradeChannel>,
+    clock: Option<Arc<dyn Clock>>,
+    compact_storage: bool,
+}
+
+impl<T> BookBuilder<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Start building a book for `symbol`
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            orders: Vec::new(),
+            next_id: 1,
+            time_in_force: TimeInForce::Gtc,
+            trade_listener: None,
+            trade_channel: None,
+            clock: None,
+            compact_storage: false,
+        }
+    }
+
+    /// Number the following orders starting at `id`
+    pub fn starting_id(mut self, id: u64) -> Self {
+        self.next_id = id;
+        self
+    }
+
+    /// Time in force for the following orders (`Gtc` by default)
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    /// Install a trade listener on the built book
+    pub fn trade_listener(mut self, listener: TradeListener) -> Self {
+        self.trade_listener = Some(listener);
+        self
+    }
+
+    /// Hand the match results of the built book to `channel`, see
+    /// [`OrderBook::with_trade_channel`]
+    pub fn trade_channel(mut self, channel: TradeChannel) -> Self {
+        self.trade_channel = Some(channel);
+        self
+    }
+
+    /// Give the built book `clock` as its time source. Orders declared after
+    /// this call are stamped with it
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Keep the built book's resting orders in compact storage, see
+    /// [`OrderBook::with_compact_storage`]
+    pub fn compact_storage(mut self) -> Self {
+        self.compact_storage = true;
+        self
+    }
+
+    fn now(&self) -> u64 {
+        match &self.clock {
+            Some(clock) => clock.now_millis(),
+            None => SystemClock.now_millis(),
+        }
+    }
+
+    fn next_id(&mut self) -> OrderId {
+        let id = OrderId::from_u64(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// One limit buy order per `(price, quantity)` pair
+    pub fn bids(self, levels: impl IntoIterator<Item = (u64, u64)>) -> Self {
+        levels.into_iter().fold(self, |builder, (price, quantity)| {
+            builder.bid(price, quantity)
+        })
+    }
+
+    /// One limit sell order per `(price, quantity)` pair
+    pub fn asks(self, levels: impl IntoIterator<Item = (u64, u64)>) -> Self {
+        levels.into_iter().fold(self, |builder, (price, quantity)| {
+            builder.ask(price, quantity)
+        })
+    }
+
+    /// A limit buy order
+    pub fn bid(self, price: u64, quantity: u64) -> Self {
+        self.limit(Side::Buy, price, quantity)
+    }
+
+    /// A limit sell order
+    pub fn ask(self, price: u64, quantity: u64) -> Self {
+        self.limit(Side::Sell, price, quantity)
+    }
+
+    /// A limit order
+    pub fn limit(mut self, side: Side, price: u64, quantity: u64) -> Self {
+        let order = OrderType::Standard {
+            id: self.next_id(),
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force: self.time_in_force,
+            extra_fields: T::default(),
+        };
+        self.order(order)
+    }
+
+    /// An iceberg order showing `visible` and hiding `hidden`
+    pub fn iceberg(mut self, side: Side, price: u64, visible: u64, hidden: u64) -> Self {
+        let order = OrderType::IcebergOrder {
+            id: self.next_id(),
+            price,
+            visible_quantity: visible,
+            hidden_quantity: hidden,
+            side,
+            timestamp: self.now(),
+            time_in_force: self.time_in_force,
+            extra_fields: T::default(),
+        };
+        self.order(order)
+    }
+
+    /// A post-only order
+    pub fn post_only(mut self, side: Side, price: u64, quantity: u64) -> Self {
+        let order = OrderType::PostOnly {
+            id: self.next_id(),
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force: self.time_in_force,
+            extra_fields: T::default(),
+        };
+        self.order(order)
+    }
+
+    /// Any order, with its own id. Sequential numbering is not affected.
+    pub fn order(mut self, order: OrderType<T>) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    /// Create the book and add every declared order to it
+    ///
+    /// # Errors
+    /// Returns the first error returned while adding an order.
+    pub fn build(self) -> Result<OrderBook<T>, OrderBookError> {
+        let mut book = if self.compact_storage {
+            OrderBook::with_compact_storage(&self.symbol)
+        } else {
+            OrderBook::new(&self.symbol)
+        };
+        book.trade_listener = self.trade_listener;
+        book.trade_channel = self.trade_channel;
+        if let Some(clock) = self.clock {
+            book.set_clock(clock);
+        }
+        for order in self.orders {
+            book.add_order(order)?;
+        }
+        Ok(book)
+    }
+}
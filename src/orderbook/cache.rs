@@ -4,58 +4,197 @@
    Date: 15/7/25
 ******************************************************************************/
 
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use super::sync::Mutex;
+use super::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use pricelevel::Side;
+use serde::{Deserialize, Serialize};
+
+/// How much of the best price cache a change to the book discards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CacheInvalidation {
+    /// Any change discards both cached best prices
+    Wholesale,
+    /// A change discards the cached best price of its own side only
+    PerSide,
+    /// A change discards the cached best price of its side only if it can
+    /// move it: a level added at a better price, or the best level removed
+    #[default]
+    PriceAware,
+}
+
+impl CacheInvalidation {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => CacheInvalidation::Wholesale,
+            1 => CacheInvalidation::PerSide,
+            _ => CacheInvalidation::PriceAware,
+        }
+    }
+}
+
+/// Cached best price of one side.
+///
+/// Every invalidation bumps the generation, and a price computed under an
+/// older generation is not marked valid, so a computation racing with a
+/// change to the book cannot leave a stale price in the cache. Prices are
+/// published one at a time: two computations under different generations
+/// could otherwise interleave their writes and pair a valid flag with the
+/// other's stale price.
+struct SideCache {
+    price: AtomicU64,
+    valid: AtomicBool,
+    generation: AtomicU64,
+    publishing: Mutex<()>,
+}
+
+impl SideCache {
+    fn new() -> Self {
+        Self {
+            price: AtomicU64::new(0),
+            valid: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            publishing: Mutex::new(()),
+        }
+    }
+
+    fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.valid.store(false, Ordering::SeqCst);
+    }
+
+    /// The cached price, `Some(None)` if the side is cached as empty
+    fn get(&self) -> Option<Option<u64>> {
+        if !self.valid.load(Ordering::SeqCst) {
+            return None;
+        }
+        let price = self.price.load(Ordering::SeqCst);
+        Some((price > 0).then_some(price))
+    }
+
+    fn update(&self, generation: u64, best: Option<u64>) {
+        // Caching is optional, a computation finding another one publishing skips it
+        let Ok(_publishing) = self.publishing.try_lock() else {
+            return;
+        };
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        self.price.store(best.unwrap_or(0), Ordering::SeqCst);
+        self.valid.store(true, Ordering::SeqCst);
+        // An invalidation that slipped in since the check wins. Reading the
+        // generation with a read-modify-write sees the latest bump, or makes
+        // that bump synchronize with this store, without relying on the total
+        // order of sequentially consistent accesses
+        if self.generation.fetch_add(0, Ordering::SeqCst) != generation {
+            self.valid.store(false, Ordering::SeqCst);
+        }
+    }
+}
 
 pub struct PriceLevelCache {
-    best_bid_price: AtomicU64,
-    best_ask_price: AtomicU64,
-    cache_valid: AtomicBool,
+    bid: SideCache,
+    ask: SideCache,
+    granularity: AtomicU8,
 }
 
 impl PriceLevelCache {
     pub fn new() -> Self {
         Self {
-            best_bid_price: AtomicU64::new(0),
-            best_ask_price: AtomicU64::new(0),
-            cache_valid: AtomicBool::new(false),
+            bid: SideCache::new(),
+            ask: SideCache::new(),
+            granularity: AtomicU8::new(CacheInvalidation::default() as u8),
+        }
+    }
+
+    fn side(&self, side: Side) -> &SideCache {
+        match side {
+            Side::Buy => &self.bid,
+            Side::Sell => &self.ask,
         }
     }
 
+    pub fn granularity(&self) -> CacheInvalidation {
+        CacheInvalidation::from_u8(self.granularity.load(Ordering::Relaxed))
+    }
+
+    pub fn set_granularity(&self, granularity: CacheInvalidation) {
+        self.granularity.store(granularity as u8, Ordering::Relaxed);
+        self.invalidate();
+    }
+
+    /// Discard both cached best prices
     pub fn invalidate(&self) {
-        self.cache_valid.store(false, Ordering::Relaxed);
+        self.bid.invalidate();
+        self.ask.invalidate();
     }
 
-    pub fn get_cached_best_bid(&self) -> Option<u64> {
-        if self.cache_valid.load(Ordering::Relaxed) {
-            let price = self.best_bid_price.load(Ordering::Relaxed);
-            if price > 0 { Some(price) } else { None }
-        } else {
-            None
+    /// Discard the cached best price of `side`, or both under wholesale invalidation
+    pub fn invalidate_side(&self, side: Side) {
+        match self.granularity() {
+            CacheInvalidation::Wholesale => self.invalidate(),
+            _ => self.side(side).invalidate(),
         }
     }
 
-    pub fn get_cached_best_ask(&self) -> Option<u64> {
-        if self.cache_valid.load(Ordering::Relaxed) {
-            let price = self.best_ask_price.load(Ordering::Relaxed);
-            if price > 0 { Some(price) } else { None }
-        } else {
-            None
-        }
+    /// A level was added at `price`, or may have been
+    pub fn level_added(&self, side: Side, price: u64) {
+        let unaffected = match self.side(side).get() {
+            Some(Some(best)) => match side {
+                Side::Buy => price <= best,
+                Side::Sell => price >= best,
+            },
+            _ => false,
+        };
+        self.invalidate_unless(side, unaffected);
     }
 
-    pub fn update_best_prices(&self, best_bid: Option<u64>, best_ask: Option<u64>) {
-        if let Some(bid) = best_bid {
-            self.best_bid_price.store(bid, Ordering::Relaxed);
-        } else {
-            self.best_bid_price.store(0, Ordering::Relaxed);
+    /// The level at `price` was removed
+    pub fn level_removed(&self, side: Side, price: u64) {
+        let unaffected = matches!(self.side(side).get(), Some(Some(best)) if best != price);
+        self.invalidate_unless(side, unaffected);
+    }
+
+    /// The levels at `prices` were removed by one operation, such as a sweep
+    /// through several levels. Discards the cached best price at most once
+    pub fn levels_removed(&self, side: Side, prices: &[u64]) {
+        if prices.is_empty() {
+            return;
         }
+        let unaffected = matches!(
+            self.side(side).get(),
+            Some(Some(best)) if !prices.contains(&best)
+        );
+        self.invalidate_unless(side, unaffected);
+    }
 
-        if let Some(ask) = best_ask {
-            self.best_ask_price.store(ask, Ordering::Relaxed);
-        } else {
-            self.best_ask_price.store(0, Ordering::Relaxed);
+    fn invalidate_unless(&self, side: Side, unaffected: bool) {
+        match self.granularity() {
+            CacheInvalidation::PriceAware if unaffected => {}
+            _ => self.invalidate_side(side),
         }
+    }
+
+    /// Generation of the cached best price of `side`, to pass to
+    /// [`update_best_price`](Self::update_best_price) once it is computed
+    pub fn generation(&self, side: Side) -> u64 {
+        self.side(side).generation.load(Ordering::SeqCst)
+    }
+
+    pub fn get_cached_best(&self, side: Side) -> Option<u64> {
+        self.side(side).get().flatten()
+    }
+
+    pub fn get_cached_best_bid(&self) -> Option<u64> {
+        self.get_cached_best(Side::Buy)
+    }
+
+    pub fn get_cached_best_ask(&self) -> Option<u64> {
+        self.get_cached_best(Side::Sell)
+    }
 
-        self.cache_valid.store(true, Ordering::Relaxed);
+    /// Cache the best price of `side` computed under `generation`, unless it
+    /// was invalidated in the meantime
+    pub fn update_best_price(&self, side: Side, generation: u64, best: Option<u64>) {
+        self.side(side).update(generation, best);
     }
 }
@@ -0,0 +1,203 @@
+//! Caps on the orders resting in the book, per price level and book-wide.
+//!
+//! In long-running simulations where cancels lag adds, resting orders pile
+//! up without bound. [`RestingCaps`] bound them, and either reject orders
+//! that would rest beyond a cap or make room by cancelling old ones.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use pricelevel::{OrderId, Side};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::trace;
+
+/// What becomes of an order that would rest beyond a cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CapAction {
+    /// The order is rejected, or its remainder cancelled if it traded on entry
+    #[default]
+    Reject,
+    /// Resting orders are cancelled to make room, oldest first: at the
+    /// order's level for a level cap, and at the level furthest from the
+    /// touch for the book-wide cap
+    EvictOldest,
+}
+
+/// One of the caps of [`RestingCaps`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RestingCap {
+    /// Orders resting at one price level
+    OrdersPerLevel,
+    /// Quantity resting at one price level, hidden quantity included
+    QuantityPerLevel,
+    /// Orders resting in the whole book
+    RestingOrders,
+}
+
+impl fmt::Display for RestingCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestingCap::OrdersPerLevel => write!(f, "orders per level"),
+            RestingCap::QuantityPerLevel => write!(f, "quantity per level"),
+            RestingCap::RestingOrders => write!(f, "resting orders"),
+        }
+    }
+}
+
+/// Bounds on the orders resting in the book. Unset caps are unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestingCaps {
+    /// Most orders resting at one price level
+    pub max_orders_per_level: Option<usize>,
+    /// Most quantity resting at one price level
+    pub max_quantity_per_level: Option<u64>,
+    /// Most orders resting in the whole book
+    pub max_resting_orders: Option<usize>,
+    /// What becomes of an order that would rest beyond a cap
+    pub action: CapAction,
+}
+
+impl RestingCaps {
+    /// Cap the orders resting at one price level
+    pub fn with_max_orders_per_level(mut self, max_orders: usize) -> Self {
+        self.max_orders_per_level = Some(max_orders);
+        self
+    }
+
+    /// Cap the quantity resting at one price level
+    pub fn with_max_quantity_per_level(mut self, max_quantity: u64) -> Self {
+        self.max_quantity_per_level = Some(max_quantity);
+        self
+    }
+
+    /// Cap the orders resting in the whole book
+    pub fn with_max_resting_orders(mut self, max_orders: usize) -> Self {
+        self.max_resting_orders = Some(max_orders);
+        self
+    }
+
+    /// Choose what becomes of an order that would rest beyond a cap
+    pub fn with_action(mut self, action: CapAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Bound the orders resting in the book, or lift every bound with `None`.
+    /// Returns the new configuration version. Orders already resting beyond a
+    /// new cap stay, and only orders resting afterwards are checked
+    pub fn set_resting_caps(&self, caps: Option<RestingCaps>) -> u64 {
+        self.update_options(|options| options.resting_caps = caps)
+    }
+
+    /// Make sure `quantity` more can rest at `price` on `side` within `caps`,
+    /// evicting old orders if the caps allow it.
+    ///
+    /// Caps are checked as each order comes to rest, so orders resting at the
+    /// same time from other threads may briefly take the book past a cap.
+    pub(super) fn make_room(
+        &self,
+        caps: &RestingCaps,
+        side: Side,
+        price: u64,
+        quantity: u64,
+    ) -> Result<(), OrderBookError> {
+        let exceeded = |cap, limit| OrderBookError::RestingCapExceeded { cap, limit };
+        let evict = caps.action == CapAction::EvictOldest;
+
+        if let Some(max_orders) = caps.max_orders_per_level {
+            let (count, _) = self.level_load(side, price);
+            if count >= max_orders {
+                if !evict || max_orders == 0 {
+                    return Err(exceeded(RestingCap::OrdersPerLevel, max_orders as u64));
+                }
+                self.evict_at_level(side, price, |count, _| count >= max_orders);
+            }
+        }
+
+        if let Some(max_quantity) = caps.max_quantity_per_level {
+            let (_, resting) = self.level_load(side, price);
+            if resting.saturating_add(quantity) > max_quantity {
+                if !evict || quantity > max_quantity {
+                    return Err(exceeded(RestingCap::QuantityPerLevel, max_quantity));
+                }
+                self.evict_at_level(side, price, |_, resting| {
+                    resting.saturating_add(quantity) > max_quantity
+                });
+            }
+        }
+
+        if let Some(max_orders) = caps.max_resting_orders {
+            while self.order_locations.len() >= max_orders {
+                if !evict {
+                    return Err(exceeded(RestingCap::RestingOrders, max_orders as u64));
+                }
+                let furthest = self
+                    .furthest_level(side)
+                    .or_else(|| self.furthest_level(side.opposite()));
+                let Some((furthest_side, furthest_price)) = furthest else {
+                    return Err(exceeded(RestingCap::RestingOrders, max_orders as u64));
+                };
+                if !self.evict_oldest(furthest_side, furthest_price) {
+                    return Err(exceeded(RestingCap::RestingOrders, max_orders as u64));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Order count and total quantity resting at a level
+    fn level_load(&self, side: Side, price: u64) -> (usize, u64) {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.get(&price).map_or((0, 0), |level| {
+            (level.order_count(), level.total_quantity())
+        })
+    }
+
+    /// The level furthest from the touch on `side`
+    fn furthest_level(&self, side: Side) -> Option<(Side, u64)> {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.worst().map(|price| (side, price))
+    }
+
+    /// Cancel the oldest orders at a level while `over(order_count, quantity)`
+    /// holds for what rests there
+    fn evict_at_level(&self, side: Side, price: u64, over: impl Fn(usize, u64) -> bool) {
+        loop {
+            let (count, resting) = self.level_load(side, price);
+            if count == 0 || !over(count, resting) || !self.evict_oldest(side, price) {
+                return;
+            }
+        }
+    }
+
+    /// Cancel the order at the front of a level, returning false if there was
+    /// none to cancel
+    fn evict_oldest(&self, side: Side, price: u64) -> bool {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let oldest: Option<OrderId> = levels
+            .get(&price)
+            .and_then(|level| self.queued_orders(&level).first().map(|order| order.id()));
+        let Some(order_id) = oldest else {
+            return false;
+        };
+        trace!(
+            "Order book {}: Evicting order {} at {} to stay within the resting caps",
+            self.symbol, order_id, price
+        );
+        matches!(self.cancel_order(order_id), Ok(Some(_)))
+    }
+}
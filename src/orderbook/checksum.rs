@@ -0,0 +1,165 @@
+//! CRC32 checksums of the top of the book, in the layouts exchanges publish
+//! alongside their depth feeds.
+//!
+//! A client applying incremental updates compares its own checksum with the one
+//! the source publishes and resubscribes on a mismatch, without comparing the
+//! books level by level. Prices and quantities are the book's integer values,
+//! so both ends must agree on the tick and lot sizes.
+
+use super::book::OrderBook;
+use super::snapshot::OrderBookSnapshot;
+use pricelevel::{PriceLevelSnapshot, Side};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fmt::Write;
+
+/// Layout of the string a checksum is computed over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChecksumFormat {
+    /// Bid and ask levels alternate, best first, each as `price:quantity`, all
+    /// joined with `:` (`bid1:qty1:ask1:qty1:bid2:...`). When one side runs
+    /// out the other continues alone. This is the OKX layout; OKX compares the
+    /// value as a signed integer, `checksum as i32`.
+    #[default]
+    Interleaved,
+    /// The ask levels then the bid levels, best first, each price followed by
+    /// its quantity with no separator. This is the Kraken layout, whose
+    /// decimal points and leading zeros integer values do not have.
+    Concatenated,
+}
+
+/// CRC-32 (IEEE 802.3, as zlib computes it) lookup table
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// CRC-32 of `bytes`, as zlib's `crc32` computes it
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Checksum of the `(price, quantity)` levels of each side, best first,
+/// laid out as `format` says
+pub fn levels_checksum(bids: &[(u64, u64)], asks: &[(u64, u64)], format: ChecksumFormat) -> u32 {
+    let mut text = String::with_capacity(24 * (bids.len() + asks.len()));
+    match format {
+        ChecksumFormat::Interleaved => {
+            for index in 0..bids.len().max(asks.len()) {
+                for (price, quantity) in [bids.get(index), asks.get(index)].into_iter().flatten() {
+                    if !text.is_empty() {
+                        text.push(':');
+                    }
+                    // Writing to a String cannot fail
+                    let _ = write!(text, "{price}:{quantity}");
+                }
+            }
+        }
+        ChecksumFormat::Concatenated => {
+            for (price, quantity) in asks.iter().chain(bids) {
+                let _ = write!(text, "{price}{quantity}");
+            }
+        }
+    }
+    crc32(text.as_bytes())
+}
+
+/// Visible quantity of the `depth` best levels of a side with anything
+/// visible, best first
+fn best_levels(levels: &[PriceLevelSnapshot], side: Side, depth: usize) -> Vec<(u64, u64)> {
+    let mut levels: Vec<(u64, u64)> = levels
+        .iter()
+        .filter(|level| level.visible_quantity > 0)
+        .map(|level| (level.price, level.visible_quantity))
+        .collect();
+    match side {
+        Side::Buy => levels.sort_unstable_by_key(|level| Reverse(level.0)),
+        Side::Sell => levels.sort_unstable_by_key(|level| level.0),
+    }
+    levels.truncate(depth);
+    levels
+}
+
+impl OrderBookSnapshot {
+    /// Checksum of the `depth` best levels of each side of the snapshot, see
+    /// [`OrderBook::checksum_with`]
+    pub fn checksum(&self, depth: usize, format: ChecksumFormat) -> u32 {
+        levels_checksum(
+            &best_levels(&self.bids, Side::Buy, depth),
+            &best_levels(&self.asks, Side::Sell, depth),
+            format,
+        )
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Checksum of the `depth` best levels of each side in the
+    /// [`Interleaved`](ChecksumFormat::Interleaved) layout
+    pub fn checksum(&self, depth: usize) -> u32 {
+        self.checksum_with(depth, ChecksumFormat::Interleaved)
+    }
+
+    /// Checksum of the `depth` best levels of each side, over the visible
+    /// quantity of each level as depth feeds publish it. Levels with nothing
+    /// visible, such as a level of fully hidden reserve, are left out as feeds
+    /// leave them out.
+    ///
+    /// Like a snapshot, the checksum is only consistent while no other thread
+    /// modifies the book.
+    pub fn checksum_with(&self, depth: usize, format: ChecksumFormat) -> u32 {
+        levels_checksum(
+            &self.visible_levels(Side::Buy, depth),
+            &self.visible_levels(Side::Sell, depth),
+            format,
+        )
+    }
+
+    /// Visible quantity of the `depth` best levels of a side with anything
+    /// visible, best first
+    fn visible_levels(&self, side: Side, depth: usize) -> Vec<(u64, u64)> {
+        if let Some(storage) = self.compact_storage() {
+            return storage.depth(side, depth);
+        }
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        // Levels showing nothing are skipped, so look deeper until enough show
+        let mut wanted = depth;
+        loop {
+            let prices = levels.best_prices(wanted);
+            let visible: Vec<(u64, u64)> = prices
+                .iter()
+                .filter_map(|price| {
+                    let quantity = levels.get(price)?.visible_quantity();
+                    (quantity > 0).then_some((*price, quantity))
+                })
+                .take(depth)
+                .collect();
+            if visible.len() == depth || prices.len() < wanted {
+                return visible;
+            }
+            wanted = wanted.saturating_mul(2);
+        }
+    }
+}
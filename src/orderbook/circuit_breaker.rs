@@ -0,0 +1,156 @@
+//! Flash-crash protection: a circuit breaker halting trading when aggressive
+//! orders sweep too much of one side of the book in a short time.
+//!
+//! The quantity executed against each side is kept over a rolling window.
+//! After every match the [`SweepGuard`] compares it with what is still
+//! resting on that side, and once the swept share of the side, that is the
+//! quantity executed over the quantity executed plus the quantity left,
+//! reaches the configured fraction the book enters
+//! [`TradingState::Halted`](super::session::TradingState::Halted) and a
+//! `TradingHalted` event is published. Trading resumes only when
+//! [`OrderBook::resume_trading`] is called.
+
+use super::book::OrderBook;
+use super::events::OrderBookEvent;
+use super::options::OrderBookOptions;
+use pricelevel::Side;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::trace;
+
+/// Most of one side of the book that may be swept within a time window
+/// before trading is halted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SweepGuard {
+    /// Share of a side, in basis points, whose execution within the window
+    /// halts trading. 10 000 halts only once a side is swept out entirely
+    pub max_swept_bps: u32,
+    /// Length of the rolling window, in milliseconds on the book's clock
+    pub window_ms: u64,
+}
+
+impl SweepGuard {
+    /// Halt trading once `max_swept_bps` of a side trade within `window_ms`
+    pub fn new(max_swept_bps: u32, window_ms: u64) -> Self {
+        Self {
+            max_swept_bps,
+            window_ms,
+        }
+    }
+
+    /// Returns true if executing `swept` out of `swept + resting` reaches
+    /// the maximum share
+    fn is_tripped_by(&self, swept: u64, resting: u64) -> bool {
+        let total = u128::from(swept) + u128::from(resting);
+        swept > 0 && u128::from(swept) * 10_000 >= total * u128::from(self.max_swept_bps)
+    }
+}
+
+/// Why a book halted trading, as published with the `TradingHalted` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingHalt {
+    /// Side of the book that was swept
+    pub side: Side,
+    /// Quantity executed against the side within the window
+    pub swept_quantity: u64,
+    /// Quantity left resting on the side when the breaker tripped
+    pub resting_quantity: u64,
+    /// Length of the window the quantity was executed in, in milliseconds
+    pub window_ms: u64,
+    /// When the breaker tripped, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+/// State of the circuit breaker of a book
+#[derive(Debug, Default)]
+pub(super) struct CircuitBreaker {
+    halted: AtomicBool,
+    /// Executions against the bids and the asks within the window, as
+    /// timestamp and quantity, oldest first
+    sweeps: Mutex<[VecDeque<(u64, u64)>; 2]>,
+}
+
+impl CircuitBreaker {
+    fn clear(&self) {
+        let mut sweeps = self
+            .sweeps
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        sweeps.iter_mut().for_each(VecDeque::clear);
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Returns true while trading is halted by the circuit breaker
+    pub fn is_trading_halted(&self) -> bool {
+        self.circuit_breaker.halted.load(Ordering::Acquire)
+    }
+
+    /// Lift a halt, returning false if trading was not halted. The executions
+    /// that tripped the breaker are forgotten, so the window starts afresh
+    pub fn resume_trading(&self) -> bool {
+        self.circuit_breaker.clear();
+        let resumed = self.circuit_breaker.halted.swap(false, Ordering::AcqRel);
+        if resumed {
+            trace!("Order book {}: Trading resumed", self.symbol);
+        }
+        resumed
+    }
+
+    /// Count `quantity` executed against `maker_side` towards the sweep guard,
+    /// halting trading if it trips
+    pub(super) fn record_sweep(&self, maker_side: Side, quantity: u64, options: &OrderBookOptions) {
+        let Some(guard) = options.sweep_guard else {
+            return;
+        };
+        let now = self.now();
+        let swept = {
+            let mut sweeps = self
+                .circuit_breaker
+                .sweeps
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let window = &mut sweeps[usize::from(maker_side == Side::Sell)];
+            window.push_back((now, quantity));
+            let start = now.saturating_sub(guard.window_ms);
+            while window
+                .front()
+                .is_some_and(|(timestamp, _)| *timestamp < start)
+            {
+                window.pop_front();
+            }
+            window.iter().map(|(_, quantity)| quantity).sum::<u64>()
+        };
+
+        let levels = match maker_side {
+            Side::Buy => self.iter_bids(),
+            Side::Sell => self.iter_asks(),
+        };
+        let resting = levels.map(|(_, level)| level.total_quantity()).sum();
+        if !guard.is_tripped_by(swept, resting)
+            || self.circuit_breaker.halted.swap(true, Ordering::AcqRel)
+        {
+            return;
+        }
+        trace!(
+            "Order book {}: Trading halted after {} of {} on the {:?} side traded within {}ms",
+            self.symbol,
+            swept,
+            swept + resting,
+            maker_side,
+            guard.window_ms
+        );
+        self.emit_event(&OrderBookEvent::TradingHalted(TradingHalt {
+            side: maker_side,
+            swept_quantity: swept,
+            resting_quantity: resting,
+            window_ms: guard.window_ms,
+            timestamp: now,
+        }));
+    }
+}
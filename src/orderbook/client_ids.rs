@@ -0,0 +1,89 @@
+//! Client order ids: the identifier the sender gives an order, as the `ClOrdID`
+//! of order entry protocols, mapped to the id the book knows it by
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use dashmap::mapref::entry::Entry;
+use pricelevel::{OrderId, OrderType};
+use std::sync::Arc;
+use tracing::trace;
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Add an order under the client order id `client_order_id`, automatically
+    /// matching it if it's aggressive.
+    ///
+    /// A client order id identifies one working order at a time: the order is
+    /// rejected with `DuplicateClientOrderId` while another order using the id
+    /// rests in the book. Once the order is filled, cancelled or expired the id
+    /// is free again. Price and quantity amendments keep it. An order entered
+    /// under the id of a resting order is handled as by
+    /// [`add_order`](Self::add_order), before its client order id is reserved.
+    pub fn add_order_with_client_id(
+        &self,
+        order: OrderType<T>,
+        client_order_id: &str,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let order_id = order.id();
+        if self.has_compact_storage() {
+            return Err(self.reject_order(order_id, self.compact_unsupported("client order ids")));
+        }
+        // Settled first, so a rejected duplicate cannot take over the client
+        // order id of the order resting under its id
+        self.admit_order_id(order_id, &self.options().options)
+            .map_err(|error| self.reject_order(order_id, error))?;
+        // Reserved before the order is added, so concurrent submissions of one
+        // id cannot both get in
+        let reserved = match self.client_orders.entry(client_order_id.to_string()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(order_id);
+                true
+            }
+        };
+        if !reserved {
+            trace!(
+                "Order book {}: Client order id {} of order {} is in use",
+                self.symbol, client_order_id, order_id
+            );
+            return Err(self.reject_order(
+                order_id,
+                OrderBookError::DuplicateClientOrderId {
+                    client_order_id: client_order_id.to_string(),
+                },
+            ));
+        }
+        self.order_client_ids
+            .insert(order_id, client_order_id.to_string());
+        // Rejected, filled or cancelled on entry, the order releases the id itself
+        self.add_order(order)
+    }
+
+    /// Id of the working order added under `client_order_id`, if any
+    pub fn find_by_client_id(&self, client_order_id: &str) -> Option<OrderId> {
+        self.client_orders
+            .get(client_order_id)
+            .map(|order_id| *order_id)
+    }
+
+    /// Client order id of a working order, if it was added with one
+    pub fn client_order_id(&self, order_id: OrderId) -> Option<String> {
+        self.order_client_ids
+            .get(&order_id)
+            .map(|client_order_id| client_order_id.clone())
+    }
+
+    /// Free the client order id of an order that is no longer working. An order
+    /// still resting under the id keeps it
+    pub(super) fn release_client_order_id(&self, order_id: OrderId) {
+        if self.order_client_ids.is_empty() || self.order_locations.contains_key(&order_id) {
+            return;
+        }
+        if let Some((_, client_order_id)) = self.order_client_ids.remove(&order_id) {
+            self.client_orders
+                .remove_if(&client_order_id, |_, owner| *owner == order_id);
+        }
+    }
+}
@@ -0,0 +1,554 @@
+//! Compact storage of resting orders, keeping only the essential fields of
+//! each order, for very large simulations
+
+use super::book::OrderBook;
+use super::btree_book::{BTreeBook, Execution, OrderStorage};
+use super::error::OrderBookError;
+use super::private::order_to_unit_type;
+use super::snapshot::OrderBookSnapshot;
+use super::validation::ValidationReport;
+use pricelevel::{MatchResult, OrderId, OrderType, PriceLevelSnapshot, Side, TimeInForce};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::trace;
+use uuid::Uuid;
+
+/// A resting order reduced to what price-time matching needs.
+///
+/// The order id is interned: `handle` stands for it within its book, which
+/// resolves it with [`CompactOrderBook::order_id`]. Handles of orders that
+/// left the book are given to later orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactOrder {
+    /// Handle of the order id in its book
+    pub handle: u32,
+    /// Limit price
+    pub price: u64,
+    /// Quantity left to execute
+    pub quantity: u64,
+    /// Acceptance sequence of the order in its book, giving its time priority
+    pub sequence: u64,
+}
+
+/// Interned ids of the orders resting in a compact book
+#[derive(Debug, Default)]
+struct OrderIds {
+    /// Id of each handle, `None` for handles free to be given out again
+    ids: Vec<Option<OrderId>>,
+    free_handles: Vec<u32>,
+}
+
+impl OrderIds {
+    fn with_capacity(orders: usize) -> Self {
+        Self {
+            ids: Vec::with_capacity(orders),
+            free_handles: Vec::new(),
+        }
+    }
+
+    fn get(&self, handle: u32) -> Option<OrderId> {
+        self.ids.get(handle as usize).copied().flatten()
+    }
+
+    /// Whether every handle is in use
+    fn is_full(&self) -> bool {
+        self.free_handles.is_empty() && self.ids.len() > u32::MAX as usize
+    }
+
+    /// Give `order_id` a handle, reusing a free one if there is any. The
+    /// caller checked that a handle is available
+    fn intern(&mut self, order_id: OrderId) -> u32 {
+        match self.free_handles.pop() {
+            Some(handle) => {
+                self.ids[handle as usize] = Some(order_id);
+                handle
+            }
+            None => {
+                self.ids.push(Some(order_id));
+                (self.ids.len() - 1) as u32
+            }
+        }
+    }
+}
+
+impl OrderStorage for OrderIds {
+    type Entry = CompactOrder;
+    type Order = CompactOrder;
+
+    fn order_id(&self, order: &CompactOrder) -> Option<OrderId> {
+        self.get(order.handle)
+    }
+
+    fn quantity(&self, order: &CompactOrder) -> u64 {
+        order.quantity
+    }
+
+    fn execute(&mut self, order: &mut CompactOrder, quantity: u64) -> (u64, Execution) {
+        let executed = quantity.min(order.quantity);
+        order.quantity -= executed;
+        if order.quantity == 0 {
+            (executed, Execution::Filled)
+        } else {
+            (executed, Execution::InPlace)
+        }
+    }
+
+    fn release(&mut self, order: CompactOrder) -> Option<CompactOrder> {
+        self.ids[order.handle as usize] = None;
+        self.free_handles.push(order.handle);
+        Some(order)
+    }
+}
+
+/// A book storing each resting order as a [`CompactOrder`] in a per-level FIFO
+/// queue, for simulations with millions of orders on modest hardware. An
+/// [`OrderBook`] created with
+/// [`with_compact_storage`](OrderBook::with_compact_storage) keeps its orders
+/// in one.
+///
+/// A `CompactOrder` takes 32 bytes inline, its 32-byte id being kept once in
+/// the book's id table, against 112 bytes for an
+/// [`OrderType`](pricelevel::OrderType) in a price level plus the side tables
+/// of [`OrderBook`]. It matches on the same `BTreeMap` core as
+/// [`DeterministicOrderBook`](super::DeterministicOrderBook). The saving comes
+/// at the price of these features, which the compact book does not offer:
+///
+/// - only good-till-cancelled limit orders and market orders: no iceberg,
+///   reserve, post-only, pegged or trailing orders and no time in force;
+/// - no per-order timestamps or extra fields. Time priority is given by the
+///   acceptance sequence alone;
+/// - no execution constraints, trade listeners, events or metrics;
+/// - at most `u32::MAX` orders resting at once;
+/// - methods take `&mut self`, so the book is driven from a single thread.
+pub struct CompactOrderBook {
+    symbol: String,
+    book: BTreeBook<OrderIds>,
+    next_sequence: u64,
+}
+
+impl CompactOrderBook {
+    /// Create a new compact book for the given symbol
+    pub fn new(symbol: &str) -> Self {
+        Self::with_capacity(symbol, 0)
+    }
+
+    /// Create a new compact book with room for `orders` resting orders in its index
+    pub fn with_capacity(symbol: &str, orders: usize) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            book: BTreeBook::new(OrderIds::with_capacity(orders), orders, Uuid::new_v4()),
+            next_sequence: 0,
+        }
+    }
+
+    /// Get the symbol of this order book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Number of resting orders
+    pub fn order_count(&self) -> usize {
+        self.book.len()
+    }
+
+    /// Get the best bid price, if any
+    pub fn best_bid(&self) -> Option<u64> {
+        self.book.best_bid()
+    }
+
+    /// Get the best ask price, if any
+    pub fn best_ask(&self) -> Option<u64> {
+        self.book.best_ask()
+    }
+
+    /// Get the spread (best ask - best bid)
+    pub fn spread(&self) -> Option<u64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some(ask.saturating_sub(bid)),
+            _ => None,
+        }
+    }
+
+    /// Get the last trade price, if any
+    pub fn last_trade_price(&self) -> Option<u64> {
+        self.book.last_trade_price()
+    }
+
+    /// Id of the resting order holding `handle`
+    pub fn order_id(&self, handle: u32) -> Option<OrderId> {
+        self.book.storage().get(handle)
+    }
+
+    /// Side a resting order is on
+    pub fn order_side(&self, order_id: OrderId) -> Option<Side> {
+        self.book.location(order_id).map(|(_, side)| side)
+    }
+
+    /// Get a resting order by its id
+    pub fn get_order(&self, order_id: OrderId) -> Option<CompactOrder> {
+        self.book.entry(order_id).copied()
+    }
+
+    /// Get all orders at a specific price level, in priority order
+    pub fn get_orders_at_price(&self, price: u64, side: Side) -> Vec<CompactOrder> {
+        self.book
+            .levels(side)
+            .get(&price)
+            .map(|queue| queue.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the total quantity at each price level, for bids and asks
+    pub fn get_volume_by_price(&self) -> (HashMap<u64, u64>, HashMap<u64, u64>) {
+        self.book.volumes()
+    }
+
+    /// Price and total quantity of the `levels` best levels of a side, best first
+    pub fn depth(&self, side: Side, levels: usize) -> Vec<(u64, u64)> {
+        let level = |(&price, queue): (&u64, &VecDeque<CompactOrder>)| {
+            (price, queue.iter().map(|order| order.quantity).sum())
+        };
+        match side {
+            Side::Buy => self
+                .book
+                .levels(side)
+                .iter()
+                .rev()
+                .take(levels)
+                .map(level)
+                .collect(),
+            Side::Sell => self
+                .book
+                .levels(side)
+                .iter()
+                .take(levels)
+                .map(level)
+                .collect(),
+        }
+    }
+
+    /// Quantity an order on `side` could execute right now within
+    /// `limit_price`, counted up to `quantity`
+    pub fn peek_match(&self, side: Side, quantity: u64, limit_price: Option<u64>) -> u64 {
+        self.book.available(side, quantity, limit_price)
+    }
+
+    /// Check the levels against the index of resting orders
+    pub fn validate(&self) -> ValidationReport {
+        self.book.validate()
+    }
+
+    /// Add a limit order. The part that crosses the book is matched at once and
+    /// the remainder rests at `price`.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the quantity is zero, an
+    /// order with the same id is already resting or every handle is in use.
+    pub fn add_limit_order(
+        &mut self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        if quantity == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Order quantity must be positive".to_string(),
+            });
+        }
+        if self.book.location(id).is_some() {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Order {id} is already resting"),
+            });
+        }
+        if self.book.storage().is_full() {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Order {id} cannot rest: every order handle is in use"),
+            });
+        }
+        trace!(
+            "Compact order book {}: Adding order {} at price {}",
+            self.symbol, id, price
+        );
+
+        let match_result = self.book.match_order(id, side, quantity, Some(price));
+        if match_result.remaining_quantity > 0 {
+            let order = CompactOrder {
+                handle: self.book.storage_mut().intern(id),
+                price,
+                quantity: match_result.remaining_quantity,
+                sequence: self.next_sequence,
+            };
+            self.next_sequence += 1;
+            self.book.rest(id, price, side, order);
+        }
+        Ok(match_result)
+    }
+
+    /// Submit a market order, executing against the best prices first
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InsufficientLiquidity` if the opposite side is empty.
+    pub fn submit_market_order(
+        &mut self,
+        id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        let match_result = self.book.match_order(id, side, quantity, None);
+        if match_result.remaining_quantity == quantity {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: quantity,
+                available: 0,
+            });
+        }
+        Ok(match_result)
+    }
+
+    /// Cancel a resting order, returning it if it was found
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Option<CompactOrder> {
+        self.book.remove(order_id)
+    }
+
+    /// Match an incoming order against the opposite side, best price first and
+    /// in sequence order within a price
+    pub fn match_order(
+        &mut self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+    ) -> MatchResult {
+        self.book.match_order(order_id, side, quantity, limit_price)
+    }
+}
+
+/// Convert a resting compact order of `side` to a standard, good-till-cancelled
+/// order. Compact orders keep no timestamp, so it is zero
+fn standard_order<T: Default>(
+    storage: &CompactOrderBook,
+    order: &CompactOrder,
+    side: Side,
+) -> Option<OrderType<T>> {
+    Some(OrderType::Standard {
+        id: storage.order_id(order.handle)?,
+        price: order.price,
+        quantity: order.quantity,
+        side,
+        timestamp: 0,
+        time_in_force: TimeInForce::Gtc,
+        extra_fields: T::default(),
+    })
+}
+
+impl<T> OrderBook<T>
+where
+    T: Default + Clone + Send + Sync + 'static,
+{
+    /// Create a new order book for the given symbol that keeps its resting
+    /// orders in a [`CompactOrderBook`] rather than in price levels.
+    ///
+    /// Adding, cancelling (one by one, by price range, side or all at once)
+    /// and looking up orders, matching limit and market orders, seeding,
+    /// snapshots, validation and the best prices, last trade price, depth and
+    /// volume at each price all go through the compact storage, and trades
+    /// are still reported to the trade listener and the trade channel.
+    ///
+    /// Everything the compact book does not keep is not available: only
+    /// good-till-cancelled standard orders are accepted, orders read back
+    /// have a zero timestamp, and events and statistics are not published.
+    /// Entry points that would need what it does not keep return
+    /// `OrderBookError::InvalidOperation`: order updates, execution
+    /// constraints, accounts, client order ids and quotes. The level
+    /// iterators, order pages and L3 snapshots read the price levels the book
+    /// does not use, and come back empty.
+    pub fn with_compact_storage(symbol: &str) -> Self {
+        let mut order_book = Self::new(symbol);
+        order_book.compact = Some(Mutex::new(CompactOrderBook::new(symbol)));
+        order_book
+    }
+
+    /// Whether the book keeps its resting orders in compact storage
+    pub fn has_compact_storage(&self) -> bool {
+        self.compact.is_some()
+    }
+
+    /// Lock the compact storage, if the book was created with it
+    pub(super) fn compact_storage(&self) -> Option<MutexGuard<'_, CompactOrderBook>> {
+        self.compact.as_ref().map(|compact| {
+            compact
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+        })
+    }
+
+    /// Add `order` to the compact storage, matching the part that crosses.
+    /// The storage is unlocked before trades are reported
+    pub(super) fn add_compact_order(
+        &self,
+        mut storage: MutexGuard<'_, CompactOrderBook>,
+        order: OrderType<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            time_in_force: TimeInForce::Gtc,
+            ..
+        } = order
+        else {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!(
+                    "Order {} cannot be added: compact storage only holds good-till-cancelled standard orders",
+                    order.id()
+                ),
+            });
+        };
+        let match_result = storage.add_limit_order(id, price, quantity, side)?;
+        drop(storage);
+        self.report_compact_trades(&match_result);
+        Ok(Arc::new(order))
+    }
+
+    /// Match an incoming order against the compact storage, which is
+    /// unlocked before trades are reported
+    pub(super) fn match_compact_order(
+        &self,
+        mut storage: MutexGuard<'_, CompactOrderBook>,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+    ) -> MatchResult {
+        let match_result = storage.match_order(order_id, side, quantity, limit_price);
+        drop(storage);
+        self.report_compact_trades(&match_result);
+        match_result
+    }
+
+    /// Take a resting order out of the compact storage
+    pub(super) fn cancel_compact_order(
+        storage: &mut CompactOrderBook,
+        order_id: OrderId,
+    ) -> Option<Arc<OrderType<T>>> {
+        let side = storage.order_side(order_id)?;
+        let order = storage.get_order(order_id)?;
+        let converted = standard_order(storage, &order, side).map(Arc::new);
+        storage.cancel_order(order_id);
+        converted
+    }
+
+    /// Get a resting order of the compact storage by its id
+    pub(super) fn get_compact_order(
+        storage: &CompactOrderBook,
+        order_id: OrderId,
+    ) -> Option<Arc<OrderType<T>>> {
+        let side = storage.order_side(order_id)?;
+        let order = storage.get_order(order_id)?;
+        standard_order(storage, &order, side).map(Arc::new)
+    }
+
+    /// Get the orders resting in the compact storage at a price, in priority order
+    pub(super) fn get_compact_orders_at_price(
+        storage: &CompactOrderBook,
+        price: u64,
+        side: Side,
+    ) -> Vec<Arc<OrderType<T>>> {
+        storage
+            .get_orders_at_price(price, side)
+            .iter()
+            .filter_map(|order| standard_order(storage, order, side))
+            .map(Arc::new)
+            .collect()
+    }
+
+    /// Error for `feature`, which needs what the compact storage does not keep
+    pub(super) fn compact_unsupported(&self, feature: &str) -> OrderBookError {
+        OrderBookError::InvalidOperation {
+            message: format!(
+                "Order book {}: compact storage does not support {feature}",
+                self.symbol
+            ),
+        }
+    }
+
+    /// Snapshot of the `depth` best levels of each side of the compact storage
+    pub(super) fn create_compact_snapshot(
+        &self,
+        storage: &CompactOrderBook,
+        depth: usize,
+    ) -> OrderBookSnapshot {
+        let levels = |side| {
+            storage
+                .depth(side, depth)
+                .into_iter()
+                .map(|(price, quantity)| {
+                    let mut snapshot = PriceLevelSnapshot::new(price);
+                    snapshot.visible_quantity = quantity;
+                    snapshot.orders = Self::get_compact_orders_at_price(storage, price, side)
+                        .iter()
+                        .map(|order| Arc::new(order_to_unit_type(order)))
+                        .collect();
+                    snapshot.order_count = snapshot.orders.len();
+                    snapshot
+                })
+                .collect()
+        };
+        OrderBookSnapshot {
+            symbol: self.symbol.clone(),
+            timestamp: self.now(),
+            bids: levels(Side::Buy),
+            asks: levels(Side::Sell),
+        }
+    }
+
+    /// Every order resting in the compact storage, bids from best to worst
+    /// followed by asks from best to worst
+    pub(super) fn get_all_compact_orders(storage: &CompactOrderBook) -> Vec<Arc<OrderType<T>>> {
+        [Side::Buy, Side::Sell]
+            .into_iter()
+            .flat_map(|side| {
+                storage
+                    .depth(side, usize::MAX)
+                    .into_iter()
+                    .flat_map(move |(price, _)| {
+                        Self::get_compact_orders_at_price(storage, price, side)
+                    })
+            })
+            .collect()
+    }
+
+    /// Take the levels of one side of the compact storage whose price
+    /// satisfies `selected` out of it, returning their orders level by level,
+    /// best price first, in queue order within a level
+    pub(super) fn cancel_compact_levels(
+        storage: &mut CompactOrderBook,
+        side: Side,
+        selected: impl Fn(u64) -> bool,
+    ) -> Vec<Arc<OrderType<T>>> {
+        let mut cancelled = Vec::new();
+        for (price, _) in storage.depth(side, usize::MAX) {
+            if !selected(price) {
+                continue;
+            }
+            cancelled.extend(Self::get_compact_orders_at_price(storage, price, side));
+            storage.book.remove_level(price, side);
+        }
+        cancelled
+    }
+
+    fn report_compact_trades(&self, match_result: &MatchResult) {
+        if match_result.transactions.is_empty() {
+            return;
+        }
+        if let Some(ref listener) = self.trade_listener {
+            listener(match_result);
+        }
+        if let Some(ref channel) = self.trade_channel {
+            channel.publish(match_result.clone());
+        }
+    }
+}
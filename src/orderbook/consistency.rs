@@ -0,0 +1,125 @@
+//! Snapshots that represent a single point in the book's history.
+//!
+//! A snapshot reads one level at a time while writers keep working on the
+//! others, so under load it can show a change half applied: an order moved by
+//! an amendment missing from both prices, or a sweep that emptied one level
+//! but not yet the next. Every change to the resting orders is bracketed by a
+//! [`WriteTracker`], and a consistent read is retried until it starts with no
+//! change in progress and none begins or ends before it finishes.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::manager::VersionedSnapshot;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::trace;
+
+/// Number of times a consistent read is attempted before giving up
+pub const CONSISTENT_READ_ATTEMPTS: usize = 1_024;
+
+/// Counts the changes to the resting orders begun and finished
+#[derive(Debug, Default)]
+pub(super) struct WriteTracker {
+    started: AtomicU64,
+    finished: AtomicU64,
+}
+
+/// A change in progress, finished when dropped
+pub(super) struct WriteGuard<'a> {
+    tracker: &'a WriteTracker,
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.tracker.finished.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl WriteTracker {
+    /// Mark the start of a change, finished when the guard is dropped. Changes
+    /// may nest, as an amendment cancelling and re-adding an order does
+    pub(super) fn begin(&self) -> WriteGuard<'_> {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        WriteGuard { tracker: self }
+    }
+
+    /// Number of changes finished, or `None` while one is in progress
+    fn quiescent(&self) -> Option<u64> {
+        // Finished first: a change starting in between shows as in progress
+        let finished = self.finished.load(Ordering::SeqCst);
+        (self.started.load(Ordering::SeqCst) == finished).then_some(finished)
+    }
+
+    /// Wait, yielding, for a moment with no change in progress, so every
+    /// change begun before the call has finished. Returns false if there was
+    /// none within [`CONSISTENT_READ_ATTEMPTS`] attempts, as when the caller
+    /// itself runs inside a change
+    pub(super) fn fence(&self) -> bool {
+        for _ in 0..CONSISTENT_READ_ATTEMPTS {
+            if self.quiescent().is_some() {
+                return true;
+            }
+            std::thread::yield_now();
+        }
+        false
+    }
+
+    /// Returns true if no change began or finished since `quiescent`
+    /// returned `mark`
+    fn unchanged_since(&self, mark: u64) -> bool {
+        self.started.load(Ordering::SeqCst) == mark && self.finished.load(Ordering::SeqCst) == mark
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Run `read` against the book until it sees no change in progress,
+    /// returning its result with the book version it reflects.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the book kept changing
+    /// for [`CONSISTENT_READ_ATTEMPTS`] attempts.
+    pub fn read_consistent<R>(
+        &self,
+        read: impl Fn(&Self) -> R,
+    ) -> Result<(u64, R), OrderBookError> {
+        for attempt in 1..=CONSISTENT_READ_ATTEMPTS {
+            if let Some(mark) = self.writes.quiescent() {
+                let version = self.version();
+                let result = read(self);
+                if self.writes.unchanged_since(mark) {
+                    return Ok((version, result));
+                }
+            }
+            trace!(
+                "Order book {}: Consistent read attempt {} overlapped a change",
+                self.symbol, attempt
+            );
+            std::thread::yield_now();
+        }
+        Err(OrderBookError::InvalidOperation {
+            message: format!(
+                "Order book {} kept changing while being snapshotted",
+                self.symbol
+            ),
+        })
+    }
+
+    /// Create a snapshot of the `depth` best levels of each side that reflects
+    /// a single point between changes to the book, together with the book
+    /// version at that point.
+    ///
+    /// Unlike [`create_snapshot`](Self::create_snapshot), which may observe a
+    /// change half applied, the snapshot is retaken until no change overlaps
+    /// it, so a steady stream of writes can make it wait or fail. Called from
+    /// a listener while the book is notifying it of a change, it always fails.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the book kept changing
+    /// for [`CONSISTENT_READ_ATTEMPTS`] attempts.
+    pub fn consistent_snapshot(&self, depth: usize) -> Result<VersionedSnapshot, OrderBookError> {
+        self.read_consistent(|book| book.create_snapshot(depth))
+            .map(|(version, snapshot)| VersionedSnapshot { version, snapshot })
+    }
+}
@@ -0,0 +1,96 @@
+//! Execution constraints that the order book enforces on top of the order type semantics
+
+use super::session::AuctionTimeInForce;
+use serde::{Deserialize, Serialize};
+
+/// Execution constraints attached to an individual order.
+///
+/// The underlying `OrderType` has no room for venue-specific execution
+/// instructions, so the book keeps them in a side table keyed by order id
+/// and consults it while matching.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderConstraints {
+    /// All-or-none: the order may rest in the book, but it can only ever be
+    /// executed in full. Unlike fill-or-kill it is not cancelled when it
+    /// cannot be filled immediately.
+    pub all_or_none: bool,
+
+    /// Minimum execution quantity: the order only trades when at least this
+    /// much can be executed in a single match. Once less than this is left,
+    /// the remainder can only be executed in full.
+    pub min_quantity: Option<u64>,
+
+    /// Short sale: the order sells what the seller does not own, and must
+    /// pass the book's short sale rule whenever it is entered or repriced.
+    #[serde(default)]
+    pub short_sale: bool,
+
+    /// Auction the order is restricted to: it is only accepted while that
+    /// auction is called, and expires when the call ends.
+    #[serde(default)]
+    pub auction: Option<AuctionTimeInForce>,
+}
+
+impl OrderConstraints {
+    /// Constraints for an all-or-none order
+    pub fn all_or_none() -> Self {
+        Self {
+            all_or_none: true,
+            ..Self::default()
+        }
+    }
+
+    /// Constraints for an order with a minimum execution quantity
+    pub fn min_quantity(min_quantity: u64) -> Self {
+        Self {
+            min_quantity: Some(min_quantity),
+            ..Self::default()
+        }
+    }
+
+    /// Constraints for a short sale with no execution constraint
+    pub fn short_sale() -> Self {
+        Self {
+            short_sale: true,
+            ..Self::default()
+        }
+    }
+
+    /// Constraints for an order restricted to an auction
+    pub fn auction(auction: AuctionTimeInForce) -> Self {
+        Self {
+            auction: Some(auction),
+            ..Self::default()
+        }
+    }
+
+    /// The same constraints, marked as a short sale
+    pub fn as_short_sale(mut self) -> Self {
+        self.short_sale = true;
+        self
+    }
+
+    /// Returns true if no constraint is set, in which case the book does not track them
+    pub fn is_unconstrained(&self) -> bool {
+        !self.all_or_none
+            && self.min_quantity.is_none()
+            && !self.short_sale
+            && self.auction.is_none()
+    }
+
+    /// The smallest quantity a single match must execute for an order with
+    /// `order_quantity` left
+    pub fn required_execution(&self, order_quantity: u64) -> u64 {
+        if self.all_or_none {
+            return order_quantity;
+        }
+        self.min_quantity
+            .map_or(0, |min_quantity| min_quantity.min(order_quantity))
+    }
+
+    /// Returns true if an order with these constraints and `order_quantity` left
+    /// may trade against an opposite order offering `available` units
+    pub fn allows_execution(&self, order_quantity: u64, available: u64) -> bool {
+        available.min(order_quantity) >= self.required_execution(order_quantity)
+    }
+}
@@ -0,0 +1,488 @@
+//! Contingent orders: groups of orders whose legs cancel or activate each
+//! other as they trade, driven by the book's event stream.
+//!
+//! Three kinds of group are supported:
+//!
+//! - **One-cancels-other**: two orders working at once. The first fill of
+//!   either cancels the other, which the filled leg then outlives if it was
+//!   only partly filled. Cancelling or expiring a leg cancels the other too.
+//! - **Bracket**: an entry order with a profit target and a protective stop
+//!   held back until the entry executes in full. The target then rests in
+//!   the book and the stop is watched by the group: a trade at or through
+//!   its stop price sends it as a market order and cancels the target, while
+//!   fills of the target reduce the stop by as much. An entry leaving the
+//!   book before it is filled in full ends the group without activating it.
+//! - **If-done**: a parent order and a child held back until the parent has
+//!   executed as much as its [`ChildActivation`] asks for, when the child is
+//!   entered and the group ends. A parent leaving the book first drops the
+//!   child.
+//!
+//! A [`ContingentOrders`] reacts to the events of the book it is
+//! [attached](ContingentOrders::attach) to, from whichever thread publishes
+//! them, and calls back into the book to cancel or enter legs. A leg the book
+//! refuses ends its group.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::{EventListener, OrderBookEvent};
+use super::fills::FillNotification;
+use pricelevel::{OrderId, OrderType, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use tracing::trace;
+
+/// Protective stop of a bracket, sent as a market order once triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StopLeg {
+    /// Id the market order is sent under
+    pub order_id: OrderId,
+    /// Side of the stop, opposite to the entry
+    pub side: Side,
+    /// A trade at or below this price triggers a sell stop, at or above it a buy stop
+    pub stop_price: u64,
+    /// Quantity sent once triggered, reduced by the fills of the target
+    pub quantity: u64,
+}
+
+impl StopLeg {
+    fn is_triggered_by(&self, trade_price: u64) -> bool {
+        match self.side {
+            Side::Sell => trade_price <= self.stop_price,
+            Side::Buy => trade_price >= self.stop_price,
+        }
+    }
+}
+
+/// How much of an if-done parent must execute before its child is entered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChildActivation {
+    /// The parent's first fill, however small
+    #[default]
+    FirstFill,
+    /// At least this quantity, or the parent's whole quantity if it is smaller
+    ExecutedQuantity(u64),
+    /// The parent's whole quantity
+    Filled,
+}
+
+impl ChildActivation {
+    fn is_reached_by(&self, fill: &FillNotification) -> bool {
+        match self {
+            ChildActivation::FirstFill => true,
+            ChildActivation::ExecutedQuantity(quantity) => {
+                fill.cumulative_quantity >= *quantity || fill.is_complete()
+            }
+            ChildActivation::Filled => fill.is_complete(),
+        }
+    }
+}
+
+enum Group<T> {
+    OneCancelsOther {
+        legs: [OrderId; 2],
+    },
+    /// Entry working, target and stop held back
+    PendingBracket {
+        entry: OrderId,
+        target: OrderType<T>,
+        stop: StopLeg,
+    },
+    /// Target resting, stop watched
+    ActiveBracket {
+        target: OrderId,
+        stop: StopLeg,
+    },
+    /// Parent working, child held back
+    IfDone {
+        parent: OrderId,
+        child: OrderType<T>,
+        activation: ChildActivation,
+    },
+}
+
+impl<T> Group<T> {
+    /// Orders of the group working in the book
+    fn working(&self) -> Vec<OrderId> {
+        match self {
+            Group::OneCancelsOther { legs } => legs.to_vec(),
+            Group::PendingBracket { entry, .. } => vec![*entry],
+            Group::ActiveBracket { target, .. } => vec![*target],
+            Group::IfDone { parent, .. } => vec![*parent],
+        }
+    }
+}
+
+/// What the book is asked to do once the group table is released
+enum Action<T> {
+    Cancel(OrderId),
+    Add(u64, OrderType<T>),
+    SendStop(StopLeg),
+}
+
+struct Groups<T> {
+    next_id: u64,
+    groups: HashMap<u64, Group<T>>,
+    /// Group of each working leg
+    legs: HashMap<OrderId, u64>,
+}
+
+impl<T> Groups<T> {
+    fn open(&mut self, group: Group<T>) -> u64 {
+        self.next_id += 1;
+        for order_id in group.working() {
+            self.legs.insert(order_id, self.next_id);
+        }
+        self.groups.insert(self.next_id, group);
+        self.next_id
+    }
+
+    fn close(&mut self, group_id: u64) -> Option<Group<T>> {
+        let group = self.groups.remove(&group_id)?;
+        for order_id in group.working() {
+            self.legs.remove(&order_id);
+        }
+        Some(group)
+    }
+}
+
+/// Manages the one-cancels-other and bracket groups of a book. See the
+/// [module docs](self)
+pub struct ContingentOrders<T = ()>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    book: Weak<OrderBook<T>>,
+    groups: Mutex<Groups<T>>,
+}
+
+impl<T> ContingentOrders<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Attach a manager to `book`, returning the shared book and the manager.
+    ///
+    /// The manager listens to the book's events after the listener already
+    /// set on it, if any, which keeps receiving every event.
+    pub fn attach(mut book: OrderBook<T>) -> (Arc<OrderBook<T>>, Arc<Self>) {
+        let previous = book.event_listener.take();
+        let mut manager = None;
+        let book = Arc::new_cyclic(|weak| {
+            let contingent = Arc::new(Self {
+                book: weak.clone(),
+                groups: Mutex::new(Groups {
+                    next_id: 0,
+                    groups: HashMap::new(),
+                    legs: HashMap::new(),
+                }),
+            });
+            let listening = Arc::clone(&contingent);
+            let listener: EventListener = Arc::new(move |event| {
+                if let Some(previous) = &previous {
+                    previous(event);
+                }
+                listening.on_event(event);
+            });
+            book.set_event_listener(listener);
+            manager = Some(contingent);
+            book
+        });
+        (book, manager.expect("set while the book was built"))
+    }
+
+    /// Enter two orders of which the first to fill cancels the other,
+    /// returning the id of the group.
+    ///
+    /// # Errors
+    /// Returns the error of [`OrderBook::add_order`] for either leg, after
+    /// cancelling the first if the second is refused.
+    pub fn submit_one_cancels_other(
+        &self,
+        first: OrderType<T>,
+        second: OrderType<T>,
+    ) -> Result<u64, OrderBookError> {
+        let book = self.book()?;
+        let (first_id, second_id) = (first.id(), second.id());
+        // Opened before the legs are entered so fills on entry are seen
+        let group_id = self.lock().open(Group::OneCancelsOther {
+            legs: [first_id, second_id],
+        });
+        if let Err(error) = book.add_order(first) {
+            self.lock().close(group_id);
+            return Err(error);
+        }
+        // The first leg may have filled on entry, closing the group
+        if !self.is_working(group_id) {
+            return Ok(group_id);
+        }
+        if let Err(error) = book.add_order(second) {
+            self.cancel_group(group_id);
+            return Err(error);
+        }
+        trace!(
+            "Order book {}: OCO group {} of {} and {}",
+            book.symbol(),
+            group_id,
+            first_id,
+            second_id
+        );
+        Ok(group_id)
+    }
+
+    /// Enter `entry`, holding `target` and `stop` back until it is filled in
+    /// full, and return the id of the group.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the target or the stop
+    /// is on the side of the entry, and otherwise the error of
+    /// [`OrderBook::add_order`] for the entry.
+    pub fn submit_bracket(
+        &self,
+        entry: OrderType<T>,
+        target: OrderType<T>,
+        stop: StopLeg,
+    ) -> Result<u64, OrderBookError> {
+        let book = self.book()?;
+        let exit_side = entry.side().opposite();
+        if target.side() != exit_side || stop.side != exit_side {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!(
+                    "Target and stop of bracket entry {} must be {:?} orders",
+                    entry.id(),
+                    exit_side
+                ),
+            });
+        }
+        let entry_id = entry.id();
+        let group_id = self.lock().open(Group::PendingBracket {
+            entry: entry_id,
+            target,
+            stop,
+        });
+        if let Err(error) = book.add_order(entry) {
+            self.lock().close(group_id);
+            return Err(error);
+        }
+        trace!(
+            "Order book {}: Bracket group {} entered with {}",
+            book.symbol(),
+            group_id,
+            entry_id
+        );
+        Ok(group_id)
+    }
+
+    /// Enter `parent`, holding `child` back until the parent has executed as
+    /// much as `activation` asks for, and return the id of the group.
+    ///
+    /// The child is entered from the listener of the fill that activates it,
+    /// or right away if the parent gets that far on entry.
+    ///
+    /// # Errors
+    /// Returns the error of [`OrderBook::add_order`] for the parent.
+    pub fn submit_if_done(
+        &self,
+        parent: OrderType<T>,
+        child: OrderType<T>,
+        activation: ChildActivation,
+    ) -> Result<u64, OrderBookError> {
+        let book = self.book()?;
+        let parent_id = parent.id();
+        let group_id = self.lock().open(Group::IfDone {
+            parent: parent_id,
+            child,
+            activation,
+        });
+        if let Err(error) = book.add_order(parent) {
+            self.lock().close(group_id);
+            return Err(error);
+        }
+        trace!(
+            "Order book {}: If-done group {} entered with {}",
+            book.symbol(),
+            group_id,
+            parent_id
+        );
+        Ok(group_id)
+    }
+
+    /// Cancel the working legs of a group and drop the legs held back,
+    /// returning false if the group already ended
+    pub fn cancel_group(&self, group_id: u64) -> bool {
+        let Some(group) = self.lock().close(group_id) else {
+            return false;
+        };
+        let actions = group.working().into_iter().map(Action::Cancel).collect();
+        self.apply(actions);
+        true
+    }
+
+    /// Returns true until the group ends
+    pub fn is_working(&self, group_id: u64) -> bool {
+        self.lock().groups.contains_key(&group_id)
+    }
+
+    /// Orders of a working group resting in the book: both legs of a
+    /// one-cancels-other group, the entry or the target of a bracket and the
+    /// parent of an if-done group
+    pub fn working_orders(&self, group_id: u64) -> Option<Vec<OrderId>> {
+        self.lock().groups.get(&group_id).map(Group::working)
+    }
+
+    /// The stop of a bracket, once its entry has filled and until it is
+    /// triggered or the bracket ends
+    pub fn active_stop(&self, group_id: u64) -> Option<StopLeg> {
+        match self.lock().groups.get(&group_id)? {
+            Group::ActiveBracket { stop, .. } => Some(*stop),
+            _ => None,
+        }
+    }
+
+    /// React to an event of the book. Called by the listener installed by
+    /// [`attach`](Self::attach)
+    pub fn on_event(&self, event: &OrderBookEvent) {
+        let actions = {
+            let mut groups = self.lock();
+            match event {
+                OrderBookEvent::OrderFilled(fill) => {
+                    // Each trade is published for both sides; look at it once
+                    let mut actions = if fill.is_maker {
+                        trigger_stops(&mut groups, fill.price)
+                    } else {
+                        Vec::new()
+                    };
+                    actions.extend(leg_filled(&mut groups, fill));
+                    actions
+                }
+                OrderBookEvent::OrderCancelled(cancelled) => {
+                    leg_gone(&mut groups, cancelled.order_id)
+                }
+                OrderBookEvent::OrderExpired(expired) => leg_gone(&mut groups, expired.order_id),
+                _ => Vec::new(),
+            }
+        };
+        // Applied without the table locked, as the book publishes what they do
+        self.apply(actions);
+    }
+
+    fn apply(&self, actions: Vec<Action<T>>) {
+        let Some(book) = self.book.upgrade() else {
+            return;
+        };
+        for action in actions {
+            let outcome = match action {
+                Action::Cancel(order_id) => book.cancel_order(order_id).map(|_| ()),
+                Action::Add(group_id, order) => {
+                    book.add_order(order).map(|_| ()).inspect_err(|_| {
+                        self.lock().close(group_id);
+                    })
+                }
+                Action::SendStop(stop) => book
+                    .submit_market_order(stop.order_id, stop.quantity, stop.side)
+                    .map(|_| ()),
+            };
+            if let Err(error) = outcome {
+                trace!(
+                    "Order book {}: Contingent leg refused: {}",
+                    book.symbol(),
+                    error
+                );
+            }
+        }
+    }
+
+    fn book(&self) -> Result<Arc<OrderBook<T>>, OrderBookError> {
+        self.book
+            .upgrade()
+            .ok_or_else(|| OrderBookError::InvalidOperation {
+                message: "Contingent orders outlived their book".to_string(),
+            })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Groups<T>> {
+        self.groups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Close the brackets whose stop a trade at `trade_price` triggers
+fn trigger_stops<T>(groups: &mut Groups<T>, trade_price: u64) -> Vec<Action<T>> {
+    let triggered: Vec<u64> = groups
+        .groups
+        .iter()
+        .filter(|(_, group)| {
+            matches!(group, Group::ActiveBracket { stop, .. } if stop.is_triggered_by(trade_price))
+        })
+        .map(|(group_id, _)| *group_id)
+        .collect();
+    let mut actions = Vec::new();
+    for group_id in triggered {
+        if let Some(Group::ActiveBracket { target, stop }) = groups.close(group_id) {
+            actions.push(Action::Cancel(target));
+            actions.push(Action::SendStop(stop));
+        }
+    }
+    actions
+}
+
+fn leg_filled<T: Clone>(groups: &mut Groups<T>, fill: &FillNotification) -> Vec<Action<T>> {
+    let (order_id, complete) = (fill.order_id, fill.is_complete());
+    let Some(&group_id) = groups.legs.get(&order_id) else {
+        return Vec::new();
+    };
+    match groups.groups.get_mut(&group_id) {
+        Some(Group::OneCancelsOther { legs }) => {
+            let other = if legs[0] == order_id {
+                legs[1]
+            } else {
+                legs[0]
+            };
+            groups.close(group_id);
+            vec![Action::Cancel(other)]
+        }
+        Some(Group::PendingBracket { .. }) if complete => {
+            let Some(Group::PendingBracket { target, stop, .. }) = groups.close(group_id) else {
+                return Vec::new();
+            };
+            let target_id = target.id();
+            let group = Group::ActiveBracket {
+                target: target_id,
+                stop,
+            };
+            // Keeps its id, so the caller can follow the group through activation
+            groups.legs.insert(target_id, group_id);
+            groups.groups.insert(group_id, group);
+            vec![Action::Add(group_id, target)]
+        }
+        Some(Group::ActiveBracket { stop, .. }) => {
+            stop.quantity = stop.quantity.saturating_sub(fill.quantity);
+            if complete || stop.quantity == 0 {
+                groups.close(group_id);
+            }
+            Vec::new()
+        }
+        Some(Group::IfDone { activation, .. }) if activation.is_reached_by(fill) => {
+            let Some(Group::IfDone { child, .. }) = groups.close(group_id) else {
+                return Vec::new();
+            };
+            vec![Action::Add(group_id, child)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// A leg left the book without filling in full
+fn leg_gone<T>(groups: &mut Groups<T>, order_id: OrderId) -> Vec<Action<T>> {
+    let Some(&group_id) = groups.legs.get(&order_id) else {
+        return Vec::new();
+    };
+    match groups.close(group_id) {
+        Some(Group::OneCancelsOther { legs }) => legs
+            .into_iter()
+            .filter(|leg| *leg != order_id)
+            .map(Action::Cancel)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
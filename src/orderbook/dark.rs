@@ -0,0 +1,457 @@
+//! Non-displayed matching segment: a dark pool attached to the book whose
+//! orders are never shown and trade at the midpoint of the lit book.
+//!
+//! Dark orders cross each other as they arrive, in arrival order, and can
+//! carry a minimum execution quantity (MEQ) below which they do not trade and
+//! a limit beyond which the midpoint is not acceptable to them. With
+//! [`DarkMatching::BeforeLit`] incoming lit limit orders also trade with the
+//! dark orders at the midpoint before they reach the lit book, as a source of
+//! price improvement.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use super::fills::FillNotification;
+use super::options::VersionedOptions;
+use super::pegs::{MidpointPeg, SubTickHandling};
+use super::trade::{TradeCondition, TradeReport};
+use pricelevel::{MatchResult, OrderId, Side, Transaction, UuidGenerator};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tracing::trace;
+
+/// When the dark segment trades relative to the lit book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DarkMatching {
+    /// Dark orders only trade with each other, alongside the lit book
+    #[default]
+    Alongside,
+    /// Dark orders also trade with incoming lit limit orders, which sweep the
+    /// dark segment at the midpoint before matching the lit book
+    BeforeLit,
+}
+
+/// An order resting in the dark segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DarkOrder {
+    /// Id of the order
+    pub id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Quantity still to be executed
+    pub quantity: u64,
+    /// Quantity executed so far
+    pub executed_quantity: u64,
+    /// Smallest quantity a single execution of the order may be for, 0 for no
+    /// minimum. Once less than it is left, the remainder may execute in full
+    pub min_quantity: u64,
+    /// Highest price a buy or lowest price a sell accepts the midpoint at;
+    /// any midpoint when unset
+    pub limit_price: Option<u64>,
+    /// When the order was accepted (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+impl DarkOrder {
+    /// Returns true if the order accepts trading at `price`
+    fn accepts(&self, price: u64) -> bool {
+        match (self.limit_price, self.side) {
+            (None, _) => true,
+            (Some(limit), Side::Buy) => price <= limit,
+            (Some(limit), Side::Sell) => price >= limit,
+        }
+    }
+
+    /// Smallest execution the order takes part in right now
+    fn min_execution(&self) -> u64 {
+        self.min_quantity.min(self.quantity)
+    }
+}
+
+/// Outcome of adding an order to the dark segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DarkMatch {
+    /// Trades of the order on arrival, in execution order
+    pub trades: Vec<Transaction>,
+    /// What is left of the order resting in the segment, `None` once it is
+    /// fully executed
+    pub resting: Option<DarkOrder>,
+}
+
+/// A trade against a resting dark order, with the maker's state after it
+struct DarkFill {
+    transaction: Transaction,
+    maker_executed: u64,
+    maker_remaining: u64,
+}
+
+/// Dark orders in arrival order
+#[derive(Default)]
+pub(super) struct DarkPool {
+    orders: Mutex<Vec<DarkOrder>>,
+}
+
+impl DarkPool {
+    fn orders(&self) -> std::sync::MutexGuard<'_, Vec<DarkOrder>> {
+        self.orders
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Execute `taker` against the `resting` orders of the other side at `price`,
+/// in arrival order, skipping makers whose limit or minimum execution quantity
+/// the trade would not satisfy. Filled makers leave `resting`.
+fn cross(
+    taker: &mut DarkOrder,
+    resting: &mut Vec<DarkOrder>,
+    price: u64,
+    transaction_id_generator: &UuidGenerator,
+) -> Vec<DarkFill> {
+    let mut fills = Vec::new();
+    if !taker.accepts(price) {
+        return fills;
+    }
+    for maker in resting.iter_mut() {
+        if taker.quantity == 0 {
+            break;
+        }
+        if maker.side == taker.side || maker.quantity == 0 || !maker.accepts(price) {
+            continue;
+        }
+        let quantity = taker.quantity.min(maker.quantity);
+        if quantity < taker.min_execution() || quantity < maker.min_execution() {
+            continue;
+        }
+        taker.quantity -= quantity;
+        taker.executed_quantity += quantity;
+        maker.quantity -= quantity;
+        maker.executed_quantity += quantity;
+        fills.push(DarkFill {
+            transaction: Transaction::new(
+                transaction_id_generator.next(),
+                taker.id,
+                maker.id,
+                price,
+                quantity,
+                taker.side,
+            ),
+            maker_executed: maker.executed_quantity,
+            maker_remaining: maker.quantity,
+        });
+    }
+    resting.retain(|maker| maker.quantity > 0);
+    fills
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// When the dark segment trades relative to the lit book
+    pub fn dark_matching(&self) -> DarkMatching {
+        self.options().options.dark_matching
+    }
+
+    /// Change when the dark segment trades relative to the lit book, returning
+    /// the new configuration version
+    pub fn set_dark_matching(&self, dark_matching: DarkMatching) -> u64 {
+        self.update_options(|options| options.dark_matching = dark_matching)
+    }
+
+    /// Add a non-displayed order to the dark segment, trading it right away
+    /// against the dark orders of the other side at the lit midpoint.
+    ///
+    /// A midpoint between two price units is rounded with the
+    /// [midpoint peg rounding](Self::midpoint_peg), the resting order acting
+    /// as the maker. Makers are visited in arrival
+    /// order and skipped when the trade would be below either order's minimum
+    /// execution quantity or beyond either limit. Nothing trades while the lit
+    /// book is one-sided, locked or crossed, or during an auction call; the
+    /// order then rests until a later arrival or
+    /// [`cross_dark_pool`](Self::cross_dark_pool) executes it.
+    ///
+    /// Dark trades are flagged with [`TradeCondition::DarkCross`], published to
+    /// the trade listener and as fill events, and do not move the last trade
+    /// price.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the quantity is zero, the
+    /// minimum execution quantity exceeds it or the id is already used by a
+    /// dark order, and `OrderBookError::InvalidTradingState` while the book
+    /// does not accept orders.
+    pub fn add_dark_order(
+        &self,
+        id: OrderId,
+        side: Side,
+        quantity: u64,
+        min_quantity: u64,
+        limit_price: Option<u64>,
+    ) -> Result<DarkMatch, OrderBookError> {
+        let _write = self.writes.begin();
+        let state = self
+            .check_order_entry()
+            .map_err(|error| self.reject_order(id, error))?;
+        let options = self.options();
+        let mut orders = self.dark_pool.orders();
+        let error = if quantity == 0 {
+            Some("Dark order quantity must be positive".to_string())
+        } else if min_quantity > quantity {
+            Some("Dark order minimum quantity exceeds its quantity".to_string())
+        } else if orders.iter().any(|order| order.id == id) {
+            Some(format!("Dark order {id} already exists"))
+        } else {
+            None
+        };
+        if let Some(message) = error {
+            drop(orders);
+            return Err(self.reject_order(id, OrderBookError::InvalidOperation { message }));
+        }
+
+        let mut order = DarkOrder {
+            id,
+            side,
+            quantity,
+            executed_quantity: 0,
+            min_quantity,
+            limit_price,
+            timestamp: self.now(),
+        };
+        trace!(
+            "Order book {}: Adding dark order {} {} {} with minimum {}",
+            self.symbol, id, side, quantity, min_quantity
+        );
+        let fills = match self.dark_price(side.opposite(), &options) {
+            Some(price) if !state.is_auction_call() => cross(
+                &mut order,
+                &mut orders,
+                price,
+                &self.transaction_id_generator,
+            ),
+            _ => Vec::new(),
+        };
+        let resting = (order.quantity > 0).then(|| {
+            orders.push(order);
+            order
+        });
+        drop(orders);
+
+        let arrival = DarkOrder {
+            quantity,
+            executed_quantity: 0,
+            ..order
+        };
+        let trades = self.publish_dark_fills(&arrival, fills, &options);
+        Ok(DarkMatch { trades, resting })
+    }
+
+    /// Remove an order from the dark segment
+    pub fn cancel_dark_order(&self, id: OrderId) -> Option<DarkOrder> {
+        let mut orders = self.dark_pool.orders();
+        let position = orders.iter().position(|order| order.id == id)?;
+        Some(orders.remove(position))
+    }
+
+    /// Remove every order from the dark segment, returning them in arrival order
+    pub(super) fn cancel_all_dark_orders(&self) -> Vec<DarkOrder> {
+        std::mem::take(&mut *self.dark_pool.orders())
+    }
+
+    /// Orders resting in the dark segment, in arrival order
+    pub fn dark_orders(&self) -> Vec<DarkOrder> {
+        self.dark_pool.orders().clone()
+    }
+
+    /// Cross the resting dark orders with each other at the current lit
+    /// midpoint, returning the trades.
+    ///
+    /// Dark orders that could not trade when they arrived, because the lit
+    /// book was one-sided or the midpoint was beyond a limit, may trade once
+    /// the lit book moves. Each order is crossed in arrival order against the
+    /// orders that arrived before it, as if it arrived again now.
+    pub fn cross_dark_pool(&self) -> Vec<Transaction> {
+        if !self
+            .check_order_entry()
+            .is_ok_and(|state| !state.is_auction_call())
+        {
+            return Vec::new();
+        }
+        let options = self.options();
+        let (Some(buy_maker_price), Some(sell_maker_price)) = (
+            self.dark_price(Side::Buy, &options),
+            self.dark_price(Side::Sell, &options),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut orders = self.dark_pool.orders();
+        let arrivals = std::mem::take(&mut *orders);
+        let mut crossings = Vec::new();
+        for mut order in arrivals {
+            let price = match order.side {
+                Side::Buy => sell_maker_price,
+                Side::Sell => buy_maker_price,
+            };
+            let before = order;
+            let fills = cross(
+                &mut order,
+                &mut orders,
+                price,
+                &self.transaction_id_generator,
+            );
+            if !fills.is_empty() {
+                crossings.push((before, fills));
+            }
+            if order.quantity > 0 {
+                orders.push(order);
+            }
+        }
+        drop(orders);
+
+        crossings
+            .into_iter()
+            .flat_map(|(taker, fills)| self.publish_dark_fills(&taker, fills, &options))
+            .collect()
+    }
+
+    /// Trade an incoming lit limit order against the dark segment at the
+    /// midpoint before it matches the lit book, returning the executed
+    /// quantity and value.
+    pub(super) fn sweep_dark_pool(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: u64,
+        options: &VersionedOptions,
+    ) -> (u64, u128) {
+        let Some(price) = self.dark_price(side.opposite(), options) else {
+            return (0, 0);
+        };
+        let mut orders = self.dark_pool.orders();
+        if orders.is_empty() {
+            return (0, 0);
+        }
+        let mut taker = DarkOrder {
+            id: order_id,
+            side,
+            quantity,
+            executed_quantity: 0,
+            min_quantity: 0,
+            limit_price: Some(limit_price),
+            timestamp: self.now(),
+        };
+        let arrival = taker;
+        let fills = cross(
+            &mut taker,
+            &mut orders,
+            price,
+            &self.transaction_id_generator,
+        );
+        drop(orders);
+
+        let trades = self.publish_dark_fills(&arrival, fills, options);
+        let value = trades
+            .iter()
+            .map(|trade| u128::from(trade.price) * u128::from(trade.quantity))
+            .sum();
+        (taker.executed_quantity, value)
+    }
+
+    /// Price dark orders trade at against a resting order on `maker_side`: the
+    /// lit midpoint rounded to a whole price unit, or `None` without a
+    /// two-sided uncrossed lit market
+    fn dark_price(&self, maker_side: Side, options: &VersionedOptions) -> Option<u64> {
+        let rounding = options.options.midpoint_peg.rounding;
+        MidpointPeg::new(rounding, SubTickHandling::AllowSubTick).price(
+            maker_side,
+            self.best_bid()?,
+            self.best_ask()?,
+            1,
+        )
+    }
+
+    /// Flag, charge and publish the trades of one dark taker, given as it was
+    /// before trading, returning them
+    fn publish_dark_fills(
+        &self,
+        taker: &DarkOrder,
+        fills: Vec<DarkFill>,
+        options: &VersionedOptions,
+    ) -> Vec<Transaction> {
+        if fills.is_empty() {
+            return Vec::new();
+        }
+        let taker_id = taker.id;
+        let fee_schedule = options.options.fee_schedule;
+        let mut result = MatchResult::new(taker_id, taker.quantity);
+        let mut reports = Vec::with_capacity(fills.len());
+        let mut notifications = Vec::with_capacity(fills.len() * 2);
+        let mut taker_executed = taker.executed_quantity;
+        let mut taker_remaining = taker.quantity;
+        for fill in &fills {
+            let transaction = fill.transaction;
+            let fees = fee_schedule.map(|fee_schedule| fee_schedule.fees_for(&transaction));
+            result.add_transaction(transaction);
+            taker_executed += transaction.quantity;
+            taker_remaining -= transaction.quantity;
+            if self.publishes_events() {
+                reports.push(TradeReport {
+                    transaction,
+                    conditions: TradeCondition::DarkCross.into(),
+                    fees,
+                });
+                notifications.push(FillNotification {
+                    order_id: taker_id,
+                    transaction_id: transaction.transaction_id,
+                    side: transaction.taker_side,
+                    price: transaction.price,
+                    quantity: transaction.quantity,
+                    cumulative_quantity: taker_executed,
+                    remaining_quantity: taker_remaining,
+                    is_maker: false,
+                    fee: fees.map(|fees| fees.taker_fee),
+                    timestamp: transaction.timestamp,
+                });
+                notifications.push(FillNotification {
+                    order_id: transaction.maker_order_id,
+                    transaction_id: transaction.transaction_id,
+                    side: transaction.taker_side.opposite(),
+                    price: transaction.price,
+                    quantity: transaction.quantity,
+                    cumulative_quantity: fill.maker_executed,
+                    remaining_quantity: fill.maker_remaining,
+                    is_maker: true,
+                    fee: fees.map(|fees| fees.maker_fee),
+                    timestamp: transaction.timestamp,
+                });
+            }
+        }
+        trace!(
+            "Order book {}: Dark order {} traded {} times at the midpoint",
+            self.symbol,
+            taker_id,
+            fills.len()
+        );
+        result.remaining_quantity = taker_remaining;
+        result.is_complete = taker_remaining == 0;
+        let trades = result.transactions.as_vec().to_vec();
+        self.stats.record_match(
+            trades.len(),
+            trades.iter().map(|trade| trade.quantity).sum(),
+        );
+        if let Some(ref listener) = self.trade_listener {
+            listener(&result);
+        }
+        if let Some(ref channel) = self.trade_channel {
+            channel.publish(result);
+        }
+        for report in reports {
+            self.emit_event(&OrderBookEvent::TradeExecuted(report));
+        }
+        for notification in notifications {
+            self.emit_event(&OrderBookEvent::OrderFilled(notification));
+        }
+        trades
+    }
+}
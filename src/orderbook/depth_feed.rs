@@ -0,0 +1,211 @@
+//! Top-N depth updates conflated to at most one per interval, for GUIs and
+//! consumers on slow links that cannot keep up with every change.
+//!
+//! A [`ConflatedDepth`] is polled, by the caller or by a thread started with
+//! [`ConflatedDepth::spawn`]. Each poll at least one interval after the last
+//! update compares the top of the book with what was last published, and
+//! publishes one update carrying the new levels and the net change of every
+//! level, however many changes the book went through in between.
+
+use super::book::OrderBook;
+use super::snapshot::{OrderBookSnapshot, SnapshotDiff};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::trace;
+
+/// One conflated depth update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthUpdate {
+    /// Number of the update, from 1, with no gaps
+    pub sequence: u64,
+    /// Version of the book the levels were read at
+    pub version: u64,
+    /// Changes to the book merged into this update since the previous one,
+    /// or since the book was created for the first update
+    pub conflated_changes: u64,
+    /// The `depth` best levels of each side
+    pub snapshot: OrderBookSnapshot,
+    /// Net change of each level since the previous update, or against an
+    /// empty book for the first one
+    pub changes: SnapshotDiff,
+}
+
+/// Receives the updates of a [`ConflatedDepth`]
+pub type DepthListener = Arc<dyn Fn(&DepthUpdate) + Send + Sync>;
+
+/// What was last published
+struct Published {
+    sequence: u64,
+    version: u64,
+    /// Time of the last update, in milliseconds on the book's clock
+    at: Option<u64>,
+    snapshot: OrderBookSnapshot,
+}
+
+/// Publishes the top `depth` levels of a book at most once per interval,
+/// merging the changes made in between. See the [module docs](self)
+pub struct ConflatedDepth<T = ()>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    book: Arc<OrderBook<T>>,
+    depth: usize,
+    interval: Duration,
+    listener: DepthListener,
+    published: Mutex<Published>,
+}
+
+impl<T> ConflatedDepth<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Publish the `depth` best levels of `book` to `listener`, at most once
+    /// per `interval` as measured by the book's clock
+    pub fn new(
+        book: Arc<OrderBook<T>>,
+        depth: usize,
+        interval: Duration,
+        listener: DepthListener,
+    ) -> Self {
+        let empty = OrderBookSnapshot {
+            symbol: book.symbol().to_string(),
+            timestamp: 0,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        };
+        Self {
+            book,
+            depth,
+            interval,
+            listener,
+            published: Mutex::new(Published {
+                sequence: 0,
+                version: 0,
+                at: None,
+                snapshot: empty,
+            }),
+        }
+    }
+
+    /// The book being published
+    pub fn book(&self) -> &Arc<OrderBook<T>> {
+        &self.book
+    }
+
+    /// Number of updates published so far
+    pub fn published_count(&self) -> u64 {
+        self.lock().sequence
+    }
+
+    /// Publish an update if an interval has passed since the last one and
+    /// the top `depth` levels changed, returning it.
+    ///
+    /// Changes deeper in the book move the version on without publishing
+    /// anything. Nothing is published either if the book kept changing too
+    /// fast to be read consistently; the next poll tries again.
+    pub fn poll(&self) -> Option<DepthUpdate> {
+        let mut published = self.lock();
+        if let Some(at) = published.at {
+            let interval = u64::try_from(self.interval.as_millis()).unwrap_or(u64::MAX);
+            if self.book.now() < at.saturating_add(interval) {
+                return None;
+            }
+        }
+        self.publish(&mut published)
+    }
+
+    /// Publish an update now if the top `depth` levels changed, whether or
+    /// not an interval has passed, as when a consumer is about to disconnect
+    pub fn flush(&self) -> Option<DepthUpdate> {
+        let mut published = self.lock();
+        self.publish(&mut published)
+    }
+
+    fn publish(&self, published: &mut Published) -> Option<DepthUpdate> {
+        if published.at.is_some() && self.book.version() == published.version {
+            return None;
+        }
+        let current = self.book.consistent_snapshot(self.depth).ok()?;
+        let changes = published.snapshot.diff(&current.snapshot);
+        let conflated_changes = current.version - published.version;
+        published.version = current.version;
+        if changes.is_empty() && published.at.is_some() {
+            return None;
+        }
+
+        published.sequence += 1;
+        published.at = Some(self.book.now());
+        published.snapshot = current.snapshot.clone();
+        let update = DepthUpdate {
+            sequence: published.sequence,
+            version: current.version,
+            conflated_changes,
+            snapshot: current.snapshot,
+            changes,
+        };
+        trace!(
+            "Order book {}: Depth update {} merging {} changes",
+            self.book.symbol(),
+            update.sequence,
+            update.conflated_changes
+        );
+        (self.listener)(&update);
+        Some(update)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Published> {
+        self.published
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Poll `publisher` once per interval on a dedicated thread until the
+    /// returned handle is dropped. Unavailable on `wasm32`, which cannot
+    /// spawn threads; poll from the host's timer there instead.
+    ///
+    /// # Panics
+    /// Panics if the thread cannot be spawned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(publisher: Arc<Self>) -> DepthFeedThread {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let name = format!("{}-depth", publisher.book.symbol());
+        let interval = publisher.interval.max(Duration::from_millis(1));
+        let thread = thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Acquire) {
+                    publisher.poll();
+                    thread::park_timeout(interval);
+                }
+            })
+            .expect("failed to spawn depth feed thread");
+        DepthFeedThread {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// The thread polling a [`ConflatedDepth`], stopped and joined on drop
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DepthFeedThread {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for DepthFeedThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,599 @@
+//! Single-threaded order book with reproducible matching for backtests
+
+use super::book::{TradeListener, order_from_unit_type};
+use super::btree_book::{BTreeBook, Execution, OrderStorage};
+use super::error::OrderBookError;
+use super::modifications::OrderQuantity;
+use super::private::order_to_unit_type;
+use super::snapshot::OrderBookSnapshot;
+use pricelevel::{
+    MatchResult, OrderId, OrderType, OrderUpdate, PriceLevelSnapshot, Side, TimeInForce,
+};
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tracing::trace;
+use uuid::Uuid;
+
+/// Resting orders kept whole in their level queues, with the display size
+/// iceberg and reserve orders replenish to
+#[derive(Default)]
+struct UnitOrders {
+    display_sizes: HashMap<OrderId, u64>,
+}
+
+impl OrderStorage for UnitOrders {
+    type Entry = OrderType<()>;
+    type Order = OrderType<()>;
+
+    fn order_id(&self, order: &OrderType<()>) -> Option<OrderId> {
+        Some(order.id())
+    }
+
+    fn quantity(&self, order: &OrderType<()>) -> u64 {
+        order.total_quantity()
+    }
+
+    fn execute(&mut self, order: &mut OrderType<()>, quantity: u64) -> (u64, Execution) {
+        let executed = quantity.min(order.visible_quantity());
+        if executed == 0 {
+            return (0, Execution::InPlace);
+        }
+        consume(order, executed);
+        if order.visible_quantity() > 0 {
+            return (executed, Execution::InPlace);
+        }
+        let display = self.display_sizes.get(&order.id()).copied();
+        if replenish(order, display.unwrap_or(0)) {
+            (executed, Execution::Refreshed)
+        } else {
+            (executed, Execution::Filled)
+        }
+    }
+
+    fn release(&mut self, order: OrderType<()>) -> Option<OrderType<()>> {
+        self.display_sizes.remove(&order.id());
+        Some(order)
+    }
+}
+
+/// An order book with the same API as [`OrderBook`](super::OrderBook) built on
+/// plain `BTreeMap` levels holding FIFO queues instead of lock-free structures.
+///
+/// Every input that makes a run differ from the next is pinned down:
+///
+/// - time is a logical clock set with [`set_time`](Self::set_time) rather than
+///   the wall clock, and stamps new orders, transactions, snapshots and expiry
+///   checks;
+/// - transaction ids come from a namespace derived from the symbol, so the same
+///   sequence of operations produces the same ids;
+/// - levels are always visited in price order and orders in arrival order.
+///
+/// Feeding the same operations to two books therefore yields identical match
+/// results and snapshots, which makes backtests and golden-file tests
+/// reproducible. Methods take `&mut self` and the book is meant to be driven
+/// from a single thread. Execution constraints (all-or-none, minimum quantity)
+/// are not supported.
+pub struct DeterministicOrderBook<T = ()> {
+    symbol: String,
+    book: BTreeBook<UnitOrders>,
+    current_time: u64,
+    market_close_timestamp: Option<u64>,
+    trade_listener: Option<TradeListener>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> DeterministicOrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create a new book for the given symbol, with the clock at zero
+    pub fn new(symbol: &str) -> Self {
+        let namespace = Uuid::new_v5(&Uuid::NAMESPACE_OID, symbol.as_bytes());
+        Self {
+            symbol: symbol.to_string(),
+            book: BTreeBook::new(UnitOrders::default(), 0, namespace),
+            current_time: 0,
+            market_close_timestamp: None,
+            trade_listener: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create a new book for the given symbol with a trade listener
+    pub fn with_trade_listener(symbol: &str, trade_listener: TradeListener) -> Self {
+        let mut order_book = Self::new(symbol);
+        order_book.trade_listener = Some(trade_listener);
+        order_book
+    }
+
+    /// Get the symbol of this order book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Current value of the logical clock
+    pub fn current_time(&self) -> u64 {
+        self.current_time
+    }
+
+    /// Move the logical clock to `timestamp`
+    pub fn set_time(&mut self, timestamp: u64) {
+        self.current_time = timestamp;
+    }
+
+    /// Set the market close timestamp for DAY orders
+    pub fn set_market_close_timestamp(&mut self, timestamp: u64) {
+        self.market_close_timestamp = Some(timestamp);
+    }
+
+    /// Clear the market close timestamp
+    pub fn clear_market_close_timestamp(&mut self) {
+        self.market_close_timestamp = None;
+    }
+
+    /// Get the best bid price, if any
+    pub fn best_bid(&self) -> Option<u64> {
+        self.book.best_bid()
+    }
+
+    /// Get the best ask price, if any
+    pub fn best_ask(&self) -> Option<u64> {
+        self.book.best_ask()
+    }
+
+    /// Get the mid price (average of best bid and best ask)
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid as f64 + ask as f64) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Get the last trade price, if any
+    pub fn last_trade_price(&self) -> Option<u64> {
+        self.book.last_trade_price()
+    }
+
+    /// Get the spread (best ask - best bid)
+    pub fn spread(&self) -> Option<u64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some(ask.saturating_sub(bid)),
+            _ => None,
+        }
+    }
+
+    /// Get all orders at a specific price level, in priority order
+    pub fn get_orders_at_price(&self, price: u64, side: Side) -> Vec<Arc<OrderType<T>>> {
+        self.book
+            .levels(side)
+            .get(&price)
+            .map(|queue| {
+                queue
+                    .iter()
+                    .map(|order| Arc::new(order_from_unit_type(order)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get all orders, bids from best to worst followed by asks from best to worst
+    pub fn get_all_orders(&self) -> Vec<Arc<OrderType<T>>> {
+        self.book
+            .levels(Side::Buy)
+            .values()
+            .rev()
+            .chain(self.book.levels(Side::Sell).values())
+            .flatten()
+            .map(|order| Arc::new(order_from_unit_type(order)))
+            .collect()
+    }
+
+    /// Get an order by ID
+    pub fn get_order(&self, order_id: OrderId) -> Option<Arc<OrderType<T>>> {
+        self.book
+            .entry(order_id)
+            .map(|order| Arc::new(order_from_unit_type(order)))
+    }
+
+    /// Get the total volume at each price level
+    pub fn get_volume_by_price(&self) -> (HashMap<u64, u64>, HashMap<u64, u64>) {
+        self.book.volumes()
+    }
+
+    /// Create a snapshot of the current order book state, stamped with the logical clock
+    pub fn create_snapshot(&self, depth: usize) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: self.symbol.clone(),
+            timestamp: self.current_time,
+            bids: self
+                .book
+                .levels(Side::Buy)
+                .iter()
+                .rev()
+                .take(depth)
+                .map(|(price, queue)| level_snapshot(*price, queue))
+                .collect(),
+            asks: self
+                .book
+                .levels(Side::Sell)
+                .iter()
+                .take(depth)
+                .map(|(price, queue)| level_snapshot(*price, queue))
+                .collect(),
+        }
+    }
+
+    /// Add a limit order to the book, stamped with the logical clock
+    pub fn add_limit_order(
+        &mut self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order(OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp: self.current_time,
+            time_in_force,
+            extra_fields: extra_fields.unwrap_or_default(),
+        })
+    }
+
+    /// Add an iceberg order to the book, stamped with the logical clock
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_iceberg_order(
+        &mut self,
+        id: OrderId,
+        price: u64,
+        visible_quantity: u64,
+        hidden_quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order(OrderType::IcebergOrder {
+            id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            timestamp: self.current_time,
+            time_in_force,
+            extra_fields: extra_fields.unwrap_or_default(),
+        })
+    }
+
+    /// Add a post-only order to the book, stamped with the logical clock
+    pub fn add_post_only_order(
+        &mut self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order(OrderType::PostOnly {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp: self.current_time,
+            time_in_force,
+            extra_fields: extra_fields.unwrap_or_default(),
+        })
+    }
+
+    /// Add a new order to the book, automatically matching it if it's aggressive
+    pub fn add_order(&mut self, order: OrderType<T>) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        trace!(
+            "Deterministic order book {}: Adding order {} at price {}",
+            self.symbol,
+            order.id(),
+            order.price()
+        );
+
+        let market_close = self.market_close_timestamp;
+        if order
+            .time_in_force()
+            .is_expired(self.current_time, market_close)
+        {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Order has already expired".to_string(),
+            });
+        }
+
+        let (price, side) = (order.price(), order.side());
+        if order.is_post_only()
+            && let Some(opposite_price) = self.book.crossed_price(price, side)
+        {
+            return Err(OrderBookError::PriceCrossing {
+                price,
+                side,
+                opposite_price,
+            });
+        }
+
+        let total_quantity = order.total_quantity();
+        if order.is_fill_or_kill() {
+            let available = self.peek_match(side, total_quantity, Some(price));
+            if available < total_quantity {
+                return Err(OrderBookError::InsufficientLiquidity {
+                    side,
+                    requested: total_quantity,
+                    available,
+                });
+            }
+        }
+
+        let match_result = self.match_order(order.id(), side, total_quantity, Some(price))?;
+        if !match_result.transactions.is_empty()
+            && let Some(listener) = self.trade_listener
+        {
+            listener(&match_result);
+        }
+
+        let remaining = match_result.remaining_quantity;
+        if remaining == 0 {
+            return Ok(Arc::new(order));
+        }
+        if order.is_immediate() {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: total_quantity,
+                available: total_quantity - remaining,
+            });
+        }
+
+        let mut unit_order = order_to_unit_type(&order);
+        let display_sizes = &mut self.book.storage_mut().display_sizes;
+        if unit_order.hidden_quantity() > 0 {
+            display_sizes.insert(unit_order.id(), unit_order.visible_quantity());
+        }
+        consume(&mut unit_order, total_quantity - remaining);
+        if unit_order.visible_quantity() == 0 {
+            let display = display_sizes.get(&unit_order.id()).copied();
+            replenish(&mut unit_order, display.unwrap_or(0));
+        }
+        let resting = order_from_unit_type(&unit_order);
+        self.book.rest(unit_order.id(), price, side, unit_order);
+        Ok(Arc::new(resting))
+    }
+
+    /// Cancel an order by ID
+    pub fn cancel_order(
+        &mut self,
+        order_id: OrderId,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        Ok(self
+            .book
+            .remove(order_id)
+            .map(|order| Arc::new(order_from_unit_type(&order))))
+    }
+
+    /// Update an order's price and/or quantity.
+    ///
+    /// Changing only the quantity keeps the order's place in its queue. Any
+    /// other change cancels the order and adds it again, matching it if it
+    /// now crosses and queueing it behind the orders already at its price.
+    /// Returns the updated order, or `None` if it is not resting.
+    pub fn update_order(
+        &mut self,
+        update: OrderUpdate,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        trace!(
+            "Deterministic order book {}: Updating order {:?}",
+            self.symbol, update
+        );
+        match update {
+            OrderUpdate::Cancel { order_id } => self.cancel_order(order_id),
+            OrderUpdate::UpdateQuantity {
+                order_id,
+                new_quantity,
+            } => self.update_quantity(order_id, new_quantity),
+            OrderUpdate::UpdatePrice {
+                order_id,
+                new_price,
+            } => {
+                if self
+                    .book
+                    .location(order_id)
+                    .is_some_and(|(price, _)| price == new_price)
+                {
+                    return Err(OrderBookError::InvalidOperation {
+                        message: "Cannot update price to the same value".to_string(),
+                    });
+                }
+                self.readd_order(order_id, |order| reprice(order, new_price, None))
+            }
+            OrderUpdate::UpdatePriceAndQuantity {
+                order_id,
+                new_price,
+                new_quantity,
+            } => self.readd_order(order_id, |order| {
+                reprice(order, new_price, None);
+                order.set_quantity(new_quantity);
+            }),
+            OrderUpdate::Replace {
+                order_id,
+                price,
+                quantity,
+                side,
+            } => self.readd_order(order_id, |order| {
+                reprice(order, price, Some(side));
+                order.set_quantity(quantity);
+            }),
+        }
+    }
+
+    fn update_quantity(
+        &mut self,
+        order_id: OrderId,
+        new_quantity: u64,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        if new_quantity == 0 {
+            return self.cancel_order(order_id);
+        }
+        Ok(self.book.entry_mut(order_id).map(|order| {
+            order.set_quantity(new_quantity);
+            Arc::new(order_from_unit_type(order))
+        }))
+    }
+
+    /// Cancel an order and add it again once `modify` has changed it
+    fn readd_order(
+        &mut self,
+        order_id: OrderId,
+        modify: impl FnOnce(&mut OrderType<T>),
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        let Some(cancelled) = self.cancel_order(order_id)? else {
+            return Ok(None);
+        };
+        let mut order = Arc::unwrap_or_clone(cancelled);
+        modify(&mut order);
+        self.add_order(order).map(Some)
+    }
+
+    /// Match an incoming order against the opposite side, best price first and
+    /// in arrival order within a price. Iceberg and reserve orders show their
+    /// visible quantity, then replenish from their hidden quantity and go to the
+    /// back of their level.
+    pub fn match_order(
+        &mut self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+    ) -> Result<MatchResult, OrderBookError> {
+        let mut match_result = self.book.match_order(order_id, side, quantity, limit_price);
+        for transaction in &mut match_result.transactions.transactions {
+            transaction.timestamp = self.current_time;
+        }
+
+        if limit_price.is_none() && match_result.remaining_quantity == quantity {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: quantity,
+                available: 0,
+            });
+        }
+        Ok(match_result)
+    }
+
+    /// Match a market order against the book
+    pub fn match_market_order(
+        &mut self,
+        order_id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        self.match_order(order_id, side, quantity, None)
+    }
+
+    /// Submit a simple market order
+    pub fn submit_market_order(
+        &mut self,
+        id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        self.match_market_order(id, quantity, side)
+    }
+
+    /// Quantity an order on `side` could execute right now, without changing the book
+    pub fn peek_match(&self, side: Side, quantity: u64, price_limit: Option<u64>) -> u64 {
+        self.book.available(side, quantity, price_limit)
+    }
+
+    /// Check if there would be a price crossing
+    pub fn will_cross_market(&self, price: u64, side: Side) -> bool {
+        self.book.crossed_price(price, side).is_some()
+    }
+}
+
+/// Move an order to `new_price`, and to `new_side` if given
+fn reprice<T>(order: &mut OrderType<T>, new_price: u64, new_side: Option<Side>) {
+    match order {
+        OrderType::Standard { price, side, .. }
+        | OrderType::IcebergOrder { price, side, .. }
+        | OrderType::PostOnly { price, side, .. }
+        | OrderType::TrailingStop { price, side, .. }
+        | OrderType::PeggedOrder { price, side, .. }
+        | OrderType::MarketToLimit { price, side, .. }
+        | OrderType::ReserveOrder { price, side, .. } => {
+            *price = new_price;
+            if let Some(new_side) = new_side {
+                *side = new_side;
+            }
+        }
+    }
+}
+
+fn level_snapshot(price: u64, queue: &VecDeque<OrderType<()>>) -> PriceLevelSnapshot {
+    let mut snapshot = PriceLevelSnapshot::new(price);
+    for order in queue {
+        snapshot.visible_quantity += order.visible_quantity();
+        snapshot.hidden_quantity += order.hidden_quantity();
+        snapshot.order_count += 1;
+        snapshot.orders.push(Arc::new(*order));
+    }
+    snapshot
+}
+
+/// Take `quantity` out of an order, from its visible quantity first
+fn consume(order: &mut OrderType<()>, quantity: u64) {
+    match order {
+        OrderType::IcebergOrder {
+            visible_quantity,
+            hidden_quantity,
+            ..
+        }
+        | OrderType::ReserveOrder {
+            visible_quantity,
+            hidden_quantity,
+            ..
+        } => {
+            let from_visible = quantity.min(*visible_quantity);
+            *visible_quantity -= from_visible;
+            *hidden_quantity = hidden_quantity.saturating_sub(quantity - from_visible);
+        }
+        OrderType::Standard { quantity: q, .. }
+        | OrderType::PostOnly { quantity: q, .. }
+        | OrderType::TrailingStop { quantity: q, .. }
+        | OrderType::PeggedOrder { quantity: q, .. }
+        | OrderType::MarketToLimit { quantity: q, .. } => *q = q.saturating_sub(quantity),
+    }
+}
+
+/// Refill an exhausted display from the hidden quantity, up to `display` (or the
+/// replenish amount of a reserve order). Returns false if nothing is left to show.
+fn replenish(order: &mut OrderType<()>, display: u64) -> bool {
+    match order {
+        OrderType::IcebergOrder {
+            visible_quantity,
+            hidden_quantity,
+            ..
+        } => {
+            *visible_quantity = display.min(*hidden_quantity);
+            *hidden_quantity -= *visible_quantity;
+            *visible_quantity > 0
+        }
+        OrderType::ReserveOrder {
+            visible_quantity,
+            hidden_quantity,
+            replenish_amount,
+            ..
+        } => {
+            *visible_quantity = replenish_amount.unwrap_or(display).min(*hidden_quantity);
+            *hidden_quantity -= *visible_quantity;
+            *visible_quantity > 0
+        }
+        _ => false,
+    }
+}
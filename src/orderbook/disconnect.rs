@@ -0,0 +1,137 @@
+//! Cancel-on-disconnect: orders entered through a [`Session`] are cancelled
+//! when the session ends, as a gateway does when a client's connection drops
+//! so that no order is left working without anyone watching it.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use pricelevel::{OrderId, OrderType};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::trace;
+
+/// Owns the orders entered through it and cancels those still resting when
+/// dropped or when [`cancel_all`](Self::cancel_all) is called.
+///
+/// Orders that fill or are cancelled by other means are forgotten lazily.
+/// Orders entered and cancelled through one session are handled one at a
+/// time. Order ids are expected to be unique per sender: an id reused by another
+/// sender after the session's order left the book is treated as the session's
+/// until the session notices the order is gone.
+pub struct Session<T = ()>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    book: Arc<OrderBook<T>>,
+    order_ids: Mutex<HashSet<OrderId>>,
+}
+
+impl<T> Session<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Open a session entering orders into `book`
+    pub fn new(book: Arc<OrderBook<T>>) -> Self {
+        Self {
+            book,
+            order_ids: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The book orders are entered into
+    pub fn book(&self) -> &Arc<OrderBook<T>> {
+        &self.book
+    }
+
+    /// Add an order owned by the session, automatically matching it if it's
+    /// aggressive. Whatever part of it rests is cancelled with the session.
+    ///
+    /// # Errors
+    /// Returns the error of [`OrderBook::add_order`].
+    pub fn add_order(&self, order: OrderType<T>) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let order_id = order.id();
+        // Held until the order is owned, so a concurrent cancel_all either
+        // finds it or runs before it rests
+        let mut order_ids = self.lock();
+        let added = self.book.add_order(order)?;
+        if self.book.get_order(order_id).is_some() {
+            order_ids.insert(order_id);
+        }
+        Ok(added)
+    }
+
+    /// Take ownership of an order already resting in the book, returning false
+    /// if it is not resting
+    pub fn track(&self, order_id: OrderId) -> bool {
+        if self.book.get_order(order_id).is_none() {
+            return false;
+        }
+        self.lock().insert(order_id);
+        true
+    }
+
+    /// Give up ownership of an order, leaving it working after the session
+    /// ends. Returns false if the session did not own it
+    pub fn release(&self, order_id: OrderId) -> bool {
+        self.lock().remove(&order_id)
+    }
+
+    /// Ids of the session's orders still resting, in acceptance order
+    pub fn order_ids(&self) -> Vec<OrderId> {
+        let mut order_ids = self.lock();
+        order_ids.retain(|order_id| self.book.get_order(*order_id).is_some());
+        self.in_acceptance_order(&order_ids)
+    }
+
+    /// Cancel every resting order of the session, returning the cancelled
+    /// orders in acceptance order. The session stays open for new orders.
+    ///
+    /// A price level refusing a cancel is reported as a `PriceLevelFault`
+    /// event and the order stays in the book, no longer owned by the session.
+    pub fn cancel_all(&self) -> Vec<Arc<OrderType<T>>> {
+        let mut owned = self.lock();
+        let order_ids = self.in_acceptance_order(&owned);
+        owned.clear();
+        trace!(
+            "Order book {}: Session cancelling {} orders",
+            self.book.symbol(),
+            order_ids.len()
+        );
+
+        let mut cancelled = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            match self.book.cancel_order(order_id) {
+                Ok(Some(order)) => cancelled.push(order),
+                Ok(None) => {}
+                Err(error) => self
+                    .book
+                    .emit_event(&OrderBookEvent::PriceLevelFault(error)),
+            }
+        }
+        cancelled
+    }
+
+    fn in_acceptance_order(&self, order_ids: &HashSet<OrderId>) -> Vec<OrderId> {
+        let mut resting: Vec<(u64, OrderId)> = order_ids
+            .iter()
+            .filter_map(|order_id| Some((self.book.order_sequence(*order_id)?, *order_id)))
+            .collect();
+        resting.sort_unstable_by_key(|(sequence, _)| *sequence);
+        resting.into_iter().map(|(_, order_id)| order_id).collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<OrderId>> {
+        self.order_ids
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> Drop for Session<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    fn drop(&mut self) {
+        self.cancel_all();
+    }
+}
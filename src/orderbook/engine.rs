@@ -0,0 +1,409 @@
+//! Single-writer engine: one thread owns an unsynchronized book and applies
+//! commands from a bounded queue in the order they were queued.
+//!
+//! [`EngineLoop`] drives a [`DeterministicOrderBook`], which needs no locks
+//! because nothing else touches it, and [`EngineHandle`]s queue work for it
+//! from any number of threads. Fire-and-forget [`Command`]s never wait for
+//! the engine; the blocking methods of the handle mirror the `OrderBook` API.
+
+use super::book::OrderBook;
+use super::deterministic::DeterministicOrderBook;
+use super::error::OrderBookError;
+use super::snapshot::OrderBookSnapshot;
+use crate::utils::{Clock, SystemClock};
+use crossbeam_queue::ArrayQueue;
+use pricelevel::{MatchResult, OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, OnceLock};
+use std::thread::{self, JoinHandle, Thread};
+use std::time::Duration;
+use tracing::trace;
+
+/// How long the engine sleeps between checks when it was not woken up explicitly
+const ENGINE_PARK_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// An operation on the book, applied by the engine in queue order
+#[derive(Debug, Clone)]
+pub enum Command<T> {
+    /// Add an order, matching it if it crosses
+    Add(OrderType<T>),
+    /// Cancel a resting order
+    Cancel(OrderId),
+    /// Change the price or quantity of a resting order
+    Update(OrderUpdate),
+    /// Execute a market order
+    MarketOrder {
+        /// Id of the market order
+        order_id: OrderId,
+        /// Quantity to execute
+        quantity: u64,
+        /// Side of the market order
+        side: Side,
+    },
+}
+
+/// What applying a [`Command`] produced
+#[derive(Debug, Clone)]
+pub enum CommandOutcome<T> {
+    /// The order added, cancelled or updated, `None` if it was not resting
+    Order(Option<Arc<OrderType<T>>>),
+    /// The trades of a market order
+    Match(MatchResult),
+}
+
+impl<T> Command<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Apply the command to `book`
+    pub fn apply(
+        self,
+        book: &mut DeterministicOrderBook<T>,
+    ) -> Result<CommandOutcome<T>, OrderBookError> {
+        match self {
+            Command::Add(order) => book
+                .add_order(order)
+                .map(|order| CommandOutcome::Order(Some(order))),
+            Command::Cancel(order_id) => book.cancel_order(order_id).map(CommandOutcome::Order),
+            Command::Update(update) => book.update_order(update).map(CommandOutcome::Order),
+            Command::MarketOrder {
+                order_id,
+                quantity,
+                side,
+            } => book
+                .submit_market_order(order_id, quantity, side)
+                .map(CommandOutcome::Match),
+        }
+    }
+
+    /// Apply the command to a shared `book`
+    pub fn apply_to_book(self, book: &OrderBook<T>) -> Result<CommandOutcome<T>, OrderBookError> {
+        match self {
+            Command::Add(order) => book
+                .add_order(order)
+                .map(|order| CommandOutcome::Order(Some(order))),
+            Command::Cancel(order_id) => book.cancel_order(order_id).map(CommandOutcome::Order),
+            Command::Update(update) => book.update_order(update).map(CommandOutcome::Order),
+            Command::MarketOrder {
+                order_id,
+                quantity,
+                side,
+            } => book
+                .submit_market_order(order_id, quantity, side)
+                .map(CommandOutcome::Match),
+        }
+    }
+}
+
+type Call<T> = Box<dyn FnOnce(&mut DeterministicOrderBook<T>) + Send>;
+
+enum Request<T> {
+    /// A command whose outcome nobody waits for
+    Command(Command<T>),
+    /// Work whose result is sent back to a waiting handle
+    Call(Call<T>),
+    /// Apply what is already queued, then stop
+    Stop,
+}
+
+/// State shared between the engine and its handles
+struct Shared<T> {
+    queue: ArrayQueue<Request<T>>,
+    engine: OnceLock<Thread>,
+    stopped: AtomicBool,
+    applied: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl<T> Shared<T> {
+    fn wake_engine(&self) {
+        if let Some(engine) = self.engine.get() {
+            engine.unpark();
+        }
+    }
+}
+
+fn stopped() -> OrderBookError {
+    OrderBookError::InvalidOperation {
+        message: "the engine loop has stopped".to_string(),
+    }
+}
+
+/// The single writer of a book, applying queued requests one at a time.
+///
+/// Before each request the book's logical clock is set from the engine's
+/// [`Clock`], so orders and trades are stamped with the time they were
+/// applied. Run it on a dedicated thread with [`spawn`](Self::spawn) or
+/// [`run`](Self::run), or poll it from an existing loop with
+/// [`run_once`](Self::run_once).
+pub struct EngineLoop<T> {
+    book: DeterministicOrderBook<T>,
+    clock: Arc<dyn Clock>,
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> EngineLoop<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create an engine owning `book`, queueing up to `capacity` requests,
+    /// and the first handle to it
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn new(book: DeterministicOrderBook<T>, capacity: usize) -> (Self, EngineHandle<T>) {
+        let shared = Arc::new(Shared {
+            queue: ArrayQueue::new(capacity),
+            engine: OnceLock::new(),
+            stopped: AtomicBool::new(false),
+            applied: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        });
+        let handle = EngineHandle {
+            shared: Arc::clone(&shared),
+        };
+        let engine = Self {
+            book,
+            clock: Arc::new(SystemClock),
+            shared,
+        };
+        (engine, handle)
+    }
+
+    /// Stamp the book from `clock` instead of the system clock
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The book, as left by the requests applied so far
+    pub fn book(&self) -> &DeterministicOrderBook<T> {
+        &self.book
+    }
+
+    /// Apply every request queued so far. Returns the number applied, and
+    /// stops early at a stop request
+    pub fn run_once(&mut self) -> usize {
+        let mut applied = 0;
+        while let Some(request) = self.shared.queue.pop() {
+            self.book.set_time(self.clock.now_millis());
+            match request {
+                Request::Command(command) => {
+                    let counter = match command.apply(&mut self.book) {
+                        Ok(_) => &self.shared.applied,
+                        Err(_) => &self.shared.failed,
+                    };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+                Request::Call(call) => call(&mut self.book),
+                Request::Stop => {
+                    self.shared.stopped.store(true, Ordering::Release);
+                    break;
+                }
+            }
+            applied += 1;
+        }
+        applied
+    }
+
+    /// Apply requests on the current thread until a handle calls
+    /// [`shutdown`](EngineHandle::shutdown) or every handle is dropped, then
+    /// return the book. Requests queued before the stop are applied first
+    pub fn run(mut self) -> DeterministicOrderBook<T> {
+        // Only the first engine of a queue is ever woken
+        let _ = self.shared.engine.set(thread::current());
+        loop {
+            self.run_once();
+            if self.shared.stopped.load(Ordering::Acquire) {
+                break;
+            }
+            if self.shared.queue.is_empty() && Arc::strong_count(&self.shared) == 1 {
+                break;
+            }
+            thread::park_timeout(ENGINE_PARK_TIMEOUT);
+        }
+        // Handles calling after the stop get an error instead of waiting forever
+        while let Some(request) = self.shared.queue.pop() {
+            drop(request);
+        }
+        trace!("Engine loop for {} stopped", self.book.symbol());
+        self.book
+    }
+
+    /// Run the engine on a dedicated thread named after the symbol. Joining
+    /// the thread returns the book once the engine stops
+    ///
+    /// # Panics
+    /// Panics if the thread cannot be spawned.
+    pub fn spawn(self) -> JoinHandle<DeterministicOrderBook<T>> {
+        thread::Builder::new()
+            .name(format!("{}-engine", self.book.symbol()))
+            .spawn(move || self.run())
+            .expect("failed to spawn engine loop thread")
+    }
+}
+
+/// Queues requests for an [`EngineLoop`]. Clone it to submit from several threads.
+///
+/// The blocking methods wait until the engine has applied the request, so
+/// they never return while the engine is not running.
+pub struct EngineHandle<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for EngineHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for EngineHandle<T> {
+    fn drop(&mut self) {
+        // Let the engine notice when the last handle is gone
+        self.shared.wake_engine();
+    }
+}
+
+impl<T> EngineHandle<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    fn enqueue(&self, request: Request<T>) -> Result<(), OrderBookError> {
+        let mut pending = request;
+        loop {
+            if self.shared.stopped.load(Ordering::Acquire) {
+                return Err(stopped());
+            }
+            match self.shared.queue.push(pending) {
+                Ok(()) => {
+                    if self.shared.stopped.load(Ordering::Acquire) {
+                        // The engine stopped in the meantime and will never
+                        // pop it. Dropping what is left fails every waiter
+                        while let Some(request) = self.shared.queue.pop() {
+                            drop(request);
+                        }
+                        return Err(stopped());
+                    }
+                    self.shared.wake_engine();
+                    return Ok(());
+                }
+                Err(rejected) => {
+                    // Back-pressure: wait for the engine to free a slot
+                    pending = rejected;
+                    self.shared.wake_engine();
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+
+    /// Queue `command` without waiting for it to be applied. Its outcome is
+    /// only counted, see [`applied_count`](Self::applied_count)
+    pub fn submit(&self, command: Command<T>) -> Result<(), OrderBookError> {
+        self.enqueue(Request::Command(command))
+    }
+
+    /// Run `call` on the engine with exclusive access to the book and wait for its result
+    pub fn call<R, F>(&self, call: F) -> Result<R, OrderBookError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut DeterministicOrderBook<T>) -> R + Send + 'static,
+    {
+        let (reply, response) = mpsc::sync_channel(1);
+        self.enqueue(Request::Call(Box::new(move |book| {
+            // The caller may have stopped waiting
+            let _ = reply.send(call(book));
+        })))?;
+        response.recv().map_err(|_| stopped())
+    }
+
+    /// Apply `command` and wait for its outcome
+    pub fn execute(&self, command: Command<T>) -> Result<CommandOutcome<T>, OrderBookError> {
+        self.call(move |book| command.apply(book))?
+    }
+
+    /// Add a limit order and wait until it has been matched and rested
+    pub fn add_limit_order(
+        &self,
+        order_id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.call(move |book| {
+            book.add_limit_order(order_id, price, quantity, side, time_in_force, extra_fields)
+        })?
+    }
+
+    /// Add an order of any type and wait until it has been matched and rested
+    pub fn add_order(&self, order: OrderType<T>) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.call(move |book| book.add_order(order))?
+    }
+
+    /// Cancel a resting order, returning it, or `None` if it was not resting
+    pub fn cancel_order(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        self.call(move |book| book.cancel_order(order_id))?
+    }
+
+    /// Update the price or quantity of a resting order
+    pub fn update_order(
+        &self,
+        update: OrderUpdate,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        self.call(move |book| book.update_order(update))?
+    }
+
+    /// Execute a market order and wait for its trades
+    pub fn submit_market_order(
+        &self,
+        order_id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<MatchResult, OrderBookError> {
+        self.call(move |book| book.submit_market_order(order_id, quantity, side))?
+    }
+
+    /// Highest bid price once the requests queued before are applied
+    pub fn best_bid(&self) -> Result<Option<u64>, OrderBookError> {
+        self.call(|book| book.best_bid())
+    }
+
+    /// Lowest ask price once the requests queued before are applied
+    pub fn best_ask(&self) -> Result<Option<u64>, OrderBookError> {
+        self.call(|book| book.best_ask())
+    }
+
+    /// Snapshot of the top `depth` levels of each side once the requests
+    /// queued before are applied
+    pub fn create_snapshot(&self, depth: usize) -> Result<OrderBookSnapshot, OrderBookError> {
+        self.call(move |book| book.create_snapshot(depth))
+    }
+
+    /// Number of submitted commands applied successfully
+    pub fn applied_count(&self) -> u64 {
+        self.shared.applied.load(Ordering::Relaxed)
+    }
+
+    /// Number of submitted commands the book rejected
+    pub fn failed_count(&self) -> u64 {
+        self.shared.failed.load(Ordering::Relaxed)
+    }
+
+    /// Number of requests waiting for the engine
+    pub fn pending(&self) -> usize {
+        self.shared.queue.len()
+    }
+
+    /// Ask the engine to stop once the requests queued so far are applied.
+    /// Requests from any handle fail from then on
+    pub fn shutdown(&self) -> Result<(), OrderBookError> {
+        self.enqueue(Request::Stop)
+    }
+}
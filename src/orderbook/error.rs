@@ -1,14 +1,51 @@
 //! Order book error types
 
-use pricelevel::{PriceLevelError, Side};
+use super::caps::RestingCap;
+use super::session::TradingState;
+use pricelevel::{OrderId, PriceLevelError, Side};
 use std::fmt;
 
+/// Operations the book applies to the orders of a price level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LevelOperation {
+    /// Remove an order from the level
+    Cancel,
+    /// Change the quantity of an order in place
+    UpdateQuantity,
+    /// Apply a fill to a resting maker
+    Fill,
+}
+
+impl fmt::Display for LevelOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelOperation::Cancel => write!(f, "cancel"),
+            LevelOperation::UpdateQuantity => write!(f, "update the quantity of"),
+            LevelOperation::Fill => write!(f, "fill"),
+        }
+    }
+}
+
 /// Errors that can occur within the OrderBook
 #[derive(Debug)]
 pub enum OrderBookError {
     /// Error from underlying price level operations
     PriceLevelError(PriceLevelError),
 
+    /// A price level refused an operation the book applied to one of its orders
+    PriceLevelOperation {
+        /// The operation that failed
+        operation: LevelOperation,
+        /// Order the operation targeted
+        order_id: OrderId,
+        /// Side of the price level
+        side: Side,
+        /// Price of the level
+        price: u64,
+        /// Error returned by the price level
+        source: PriceLevelError,
+    },
+
     /// Order not found in the book
     OrderNotFound(String),
 
@@ -40,12 +77,92 @@ pub enum OrderBookError {
         /// Description of the error
         message: String,
     },
+
+    /// Decimal price that cannot be converted to an integer book price
+    InvalidPrice {
+        /// The decimal price that was supplied
+        price: f64,
+        /// Why the conversion failed
+        reason: String,
+    },
+
+    /// Order rejected because memory pressure is critical and it is priced far from the market
+    MemoryPressure {
+        /// Price of the rejected order
+        price: u64,
+        /// Best price the distance was measured from
+        reference_price: u64,
+    },
+
+    /// Order rejected because its rate limit bucket is empty
+    RateLimited {
+        /// Account whose bucket is empty, `None` for the book-wide bucket
+        account_id: Option<String>,
+    },
+
+    /// Order entry refused in the current phase of the trading day
+    InvalidTradingState {
+        /// Trading state the book was in
+        state: TradingState,
+    },
+
+    /// Order rejected because an order with the same id is resting
+    DuplicateOrderId {
+        /// The id in use
+        order_id: OrderId,
+    },
+
+    /// Order rejected because a working order already uses its client order id
+    DuplicateClientOrderId {
+        /// The client order id in use
+        client_order_id: String,
+    },
+
+    /// Short sale refused by the book's short sale rule
+    ShortSaleRestricted {
+        /// Price of the short sale
+        price: u64,
+        /// Why the rule refused it
+        message: String,
+    },
+
+    /// Order rejected because resting it would exceed a cap on resting orders
+    RestingCapExceeded {
+        /// The cap that would be exceeded
+        cap: RestingCap,
+        /// Value of the cap
+        limit: u64,
+    },
+
+    /// Order rejected because a kill switch covers it
+    KillSwitchActive {
+        /// Account whose switch is thrown, `None` for the book's switch
+        account_id: Option<String>,
+    },
+
+    /// Data that could not be serialized or deserialized
+    SerializationError {
+        /// Description of the error
+        message: String,
+    },
 }
 
 impl fmt::Display for OrderBookError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             OrderBookError::PriceLevelError(err) => write!(f, "Price level error: {err}"),
+            OrderBookError::PriceLevelOperation {
+                operation,
+                order_id,
+                side,
+                price,
+                source,
+            } => {
+                write!(
+                    f,
+                    "Price level {side} {price} failed to {operation} order {order_id}: {source}"
+                )
+            }
             OrderBookError::OrderNotFound(id) => write!(f, "Order not found: {id}"),
             OrderBookError::InvalidPriceLevel(price) => write!(f, "Invalid price level: {price}"),
             OrderBookError::PriceCrossing {
@@ -71,11 +188,57 @@ impl fmt::Display for OrderBookError {
             OrderBookError::InvalidOperation { message } => {
                 write!(f, "Invalid operation: {message}")
             }
+            OrderBookError::InvalidPrice { price, reason } => {
+                write!(f, "Invalid price {price}: {reason}")
+            }
+            OrderBookError::MemoryPressure {
+                price,
+                reference_price,
+            } => {
+                write!(
+                    f,
+                    "Memory pressure critical: order at {price} is too far from {reference_price}"
+                )
+            }
+            OrderBookError::RateLimited { account_id } => match account_id {
+                Some(account_id) => write!(f, "Rate limit exceeded for account {account_id}"),
+                None => write!(f, "Rate limit exceeded"),
+            },
+            OrderBookError::InvalidTradingState { state } => {
+                write!(f, "Order entry not allowed while the book is in {state}")
+            }
+            OrderBookError::DuplicateOrderId { order_id } => {
+                write!(f, "Order {order_id} is already resting")
+            }
+            OrderBookError::DuplicateClientOrderId { client_order_id } => {
+                write!(f, "Client order id {client_order_id} is already in use")
+            }
+            OrderBookError::ShortSaleRestricted { price, message } => {
+                write!(f, "Short sale at {price} restricted: {message}")
+            }
+            OrderBookError::RestingCapExceeded { cap, limit } => {
+                write!(f, "Resting the order would exceed the cap of {limit} {cap}")
+            }
+            OrderBookError::KillSwitchActive { account_id } => match account_id {
+                Some(account_id) => write!(f, "Kill switch active for account {account_id}"),
+                None => write!(f, "Kill switch active"),
+            },
+            OrderBookError::SerializationError { message } => {
+                write!(f, "Serialization error: {message}")
+            }
         }
     }
 }
 
-impl std::error::Error for OrderBookError {}
+impl std::error::Error for OrderBookError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OrderBookError::PriceLevelError(source)
+            | OrderBookError::PriceLevelOperation { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 impl From<PriceLevelError> for OrderBookError {
     fn from(err: PriceLevelError) -> Self {
@@ -0,0 +1,234 @@
+//! Events published by the order book to an optional listener
+
+use super::circuit_breaker::TradingHalt;
+use super::error::OrderBookError;
+use super::expiry::ExpiredOrder;
+use super::fills::FillNotification;
+use super::options::VersionedOptions;
+use super::quotes::PulledQuotes;
+use super::session::SessionTransition;
+use super::trade::TradeReport;
+use pricelevel::{OrderId, Side};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Machine-readable reason an order was rejected, with a stable numeric code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// The underlying price level refused the operation
+    PriceLevel,
+    /// The referenced order does not exist
+    UnknownOrder,
+    /// The price is invalid or off the tick grid
+    InvalidPrice,
+    /// A post-only order would have crossed the market
+    WouldCrossMarket,
+    /// Not enough liquidity to satisfy an immediate or constrained order
+    InsufficientLiquidity,
+    /// The operation is not allowed for this order or book state
+    InvalidOperation,
+    /// Rejected to protect the book under critical memory pressure
+    MemoryPressure,
+    /// The sender exceeded its order rate limit
+    RateLimited,
+    /// Order entry is not allowed in the current trading state
+    InvalidTradingState,
+    /// A working order already uses the client order id
+    DuplicateClientOrderId,
+    /// A short sale refused by the short sale rule
+    ShortSaleRestricted,
+    /// Resting the order would exceed a cap on resting orders
+    RestingCapExceeded,
+    /// An order with the same id is resting
+    DuplicateOrderId,
+    /// A kill switch covers the order's account or the whole book
+    KillSwitch,
+}
+
+impl RejectReason {
+    /// Stable numeric code for this reason
+    pub fn code(&self) -> u16 {
+        match self {
+            RejectReason::PriceLevel => 1,
+            RejectReason::UnknownOrder => 2,
+            RejectReason::InvalidPrice => 3,
+            RejectReason::WouldCrossMarket => 4,
+            RejectReason::InsufficientLiquidity => 5,
+            RejectReason::InvalidOperation => 6,
+            RejectReason::MemoryPressure => 7,
+            RejectReason::RateLimited => 8,
+            RejectReason::InvalidTradingState => 9,
+            RejectReason::DuplicateClientOrderId => 10,
+            RejectReason::ShortSaleRestricted => 11,
+            RejectReason::RestingCapExceeded => 12,
+            RejectReason::DuplicateOrderId => 13,
+            RejectReason::KillSwitch => 14,
+        }
+    }
+}
+
+impl From<&OrderBookError> for RejectReason {
+    fn from(error: &OrderBookError) -> Self {
+        match error {
+            OrderBookError::PriceLevelError(_) | OrderBookError::PriceLevelOperation { .. } => {
+                RejectReason::PriceLevel
+            }
+            OrderBookError::OrderNotFound(_) => RejectReason::UnknownOrder,
+            OrderBookError::InvalidPriceLevel(_) | OrderBookError::InvalidPrice { .. } => {
+                RejectReason::InvalidPrice
+            }
+            OrderBookError::PriceCrossing { .. } => RejectReason::WouldCrossMarket,
+            OrderBookError::InsufficientLiquidity { .. } => RejectReason::InsufficientLiquidity,
+            OrderBookError::InvalidOperation { .. } | OrderBookError::SerializationError { .. } => {
+                RejectReason::InvalidOperation
+            }
+            OrderBookError::MemoryPressure { .. } => RejectReason::MemoryPressure,
+            OrderBookError::RateLimited { .. } => RejectReason::RateLimited,
+            OrderBookError::InvalidTradingState { .. } => RejectReason::InvalidTradingState,
+            OrderBookError::DuplicateClientOrderId { .. } => RejectReason::DuplicateClientOrderId,
+            OrderBookError::ShortSaleRestricted { .. } => RejectReason::ShortSaleRestricted,
+            OrderBookError::RestingCapExceeded { .. } => RejectReason::RestingCapExceeded,
+            OrderBookError::DuplicateOrderId { .. } => RejectReason::DuplicateOrderId,
+            OrderBookError::KillSwitchActive { .. } => RejectReason::KillSwitch,
+        }
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// An order that the book refused to accept
+#[derive(Debug)]
+pub struct OrderReject {
+    /// Id of the rejected order
+    pub order_id: OrderId,
+    /// Machine-readable reason
+    pub reason: RejectReason,
+    /// The error returned to the caller
+    pub error: OrderBookError,
+    /// When the order was rejected (milliseconds since epoch)
+    pub timestamp: u64,
+    /// Version of the options in force when the order was rejected
+    pub config_version: u64,
+}
+
+/// An order that came to rest in the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedOrder {
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Price the order rests at
+    pub price: u64,
+    /// Quantity resting, visible and hidden
+    pub quantity: u64,
+    /// Quantity the order executed on entry, before it came to rest
+    pub executed_quantity: u64,
+    /// Acceptance sequence number, see `OrderBook::order_sequence`
+    pub sequence: u64,
+    /// When the book accepted the order (nanoseconds since epoch), see
+    /// `OrderBook::accepted_at_nanos`
+    pub accepted_at_nanos: u64,
+}
+
+/// A resting order removed from the book by a cancel request, individually or
+/// as part of a mass cancel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelledOrder {
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Price of the order
+    pub price: u64,
+    /// Quantity that was still resting, visible and hidden
+    pub quantity: u64,
+    /// Quantity the order executed before it was cancelled
+    pub executed_quantity: u64,
+    /// When the order was cancelled (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+/// A price level that appeared in or disappeared from the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelChange {
+    /// Side of the level
+    pub side: Side,
+    /// Price of the level
+    pub price: u64,
+}
+
+/// Events published by an order book
+#[derive(Debug)]
+pub enum OrderBookEvent {
+    /// An order came to rest in the book. Published again when a price
+    /// amendment re-queues it
+    OrderAccepted(AcceptedOrder),
+    /// An incoming order was rejected
+    OrderRejected(OrderReject),
+    /// New options were installed under a new configuration version
+    OptionsChanged(VersionedOptions),
+    /// A trade executed, with its conditions and fees. Published for every
+    /// trade once the match is complete, before the fills of its two sides
+    TradeExecuted(TradeReport),
+    /// An order was filled in full or in part. Published for both sides of
+    /// every trade once the match is complete
+    OrderFilled(FillNotification),
+    /// A resting order was removed because its time in force ran out
+    OrderExpired(ExpiredOrder),
+    /// A resting order was cancelled. Amendments that cancel and re-add an
+    /// order internally do not publish it
+    OrderCancelled(CancelledOrder),
+    /// A price level refused an operation that could not report the failure to
+    /// a caller, such as a fill applied while matching. Always carries an
+    /// `OrderBookError::PriceLevelOperation`.
+    PriceLevelFault(OrderBookError),
+    /// The session schedule moved the book to another phase of the trading day
+    TradingStateChanged(SessionTransition),
+    /// The circuit breaker halted trading after one side of the book was
+    /// swept too fast
+    TradingHalted(TradingHalt),
+    /// The quote protection pulled every quote of an owner
+    QuotesPulled(PulledQuotes),
+    /// The first order at a price created its level. Published before the
+    /// event of the order that created it
+    LevelAdded(LevelChange),
+    /// A level lost its last order to a fill, cancel or expiry and was
+    /// removed, before the events of the orders that emptied it are
+    /// published. Not published when the book is cleared
+    LevelRemoved(LevelChange),
+}
+
+impl OrderBookEvent {
+    /// Price the event is about, as stored in the book: the order's or level's
+    /// price, the fill price or an auction's uncrossing price
+    pub fn price(&self) -> Option<u64> {
+        match self {
+            OrderBookEvent::OrderAccepted(accepted) => Some(accepted.price),
+            OrderBookEvent::TradeExecuted(report) => Some(report.transaction.price),
+            OrderBookEvent::OrderFilled(fill) => Some(fill.price),
+            OrderBookEvent::OrderExpired(expired) => Some(expired.price),
+            OrderBookEvent::OrderCancelled(cancelled) => Some(cancelled.price),
+            OrderBookEvent::LevelAdded(change) | OrderBookEvent::LevelRemoved(change) => {
+                Some(change.price)
+            }
+            OrderBookEvent::TradingStateChanged(transition) => transition
+                .auction
+                .as_ref()
+                .map(|auction| auction.equilibrium.price),
+            OrderBookEvent::OrderRejected(_)
+            | OrderBookEvent::OptionsChanged(_)
+            | OrderBookEvent::PriceLevelFault(_)
+            | OrderBookEvent::TradingHalted(_)
+            | OrderBookEvent::QuotesPulled(_) => None,
+        }
+    }
+}
+
+/// Callback receiving every event published by a book
+pub type EventListener = Arc<dyn Fn(&OrderBookEvent) + Send + Sync>;
@@ -0,0 +1,167 @@
+//! Execution state of resting orders: original size, quantity executed and
+//! average price, and the final status of orders that left the book
+
+use super::book::OrderBook;
+use super::execution_report::OrderStatus;
+use super::memory::{StructureMemory, map_memory};
+use crossbeam_queue::ArrayQueue;
+use dashmap::DashMap;
+use pricelevel::OrderId;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of finished orders whose final status [`OrderBook::order_status`]
+/// remembers
+pub const FINISHED_ORDERS_RETAINED: usize = 65_536;
+
+/// How much of an order has executed, and at what prices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionState {
+    /// Total quantity of the order when it was accepted
+    pub original_quantity: u64,
+    /// Quantity executed so far, on entry as an aggressor and since then as a maker
+    pub executed_quantity: u64,
+    /// Sum of price times quantity over every execution
+    pub executed_value: u128,
+}
+
+impl ExecutionState {
+    /// Volume-weighted average price of the executions, or `None` before the first one
+    pub fn average_price(&self) -> Option<f64> {
+        (self.executed_quantity > 0)
+            .then(|| self.executed_value as f64 / self.executed_quantity as f64)
+    }
+}
+
+/// Execution state of every resting order, kept until the order is filled or
+/// cancelled, then the final status of the most recent finished orders
+#[derive(Default)]
+pub(super) struct ExecutionTracker {
+    states: DashMap<OrderId, ExecutionState>,
+    /// Final status of finished orders, with the sequence it was recorded under
+    finished: DashMap<OrderId, (u64, OrderStatus)>,
+    /// Recording order of `finished`, oldest first, to drop the oldest statuses.
+    /// Allocated when the first order finishes. Boxed, as the queue is
+    /// cache-line aligned and would otherwise raise the alignment of every book
+    finished_order: OnceLock<Box<ArrayQueue<(u64, OrderId)>>>,
+    finished_sequence: AtomicU64,
+}
+
+impl ExecutionTracker {
+    /// Start tracking an order that is coming to rest
+    pub(super) fn open(&self, order_id: OrderId, state: ExecutionState) {
+        self.states.insert(order_id, state);
+    }
+
+    /// Record an execution of a resting order
+    pub(super) fn record_fill(&self, order_id: OrderId, price: u64, quantity: u64) {
+        let mut state = self.states.entry(order_id).or_default();
+        state.executed_quantity += quantity;
+        state.executed_value += u128::from(price) * u128::from(quantity);
+    }
+
+    pub(super) fn get(&self, order_id: OrderId) -> Option<ExecutionState> {
+        self.states.get(&order_id).map(|state| *state)
+    }
+
+    /// Quantity executed by an order, 0 if it is not tracked
+    pub(super) fn executed(&self, order_id: OrderId) -> u64 {
+        self.states
+            .get(&order_id)
+            .map_or(0, |state| state.executed_quantity)
+    }
+
+    pub(super) fn remove(&self, order_id: OrderId) {
+        self.states.remove(&order_id);
+    }
+
+    pub(super) fn clear(&self) {
+        self.states.clear();
+    }
+
+    /// Record the final status of an order that left the book or never rested
+    pub(super) fn finish(&self, order_id: OrderId, status: OrderStatus) {
+        let sequence = self.finished_sequence.fetch_add(1, Ordering::Relaxed);
+        self.finished.insert(order_id, (sequence, status));
+        let finished_order = self
+            .finished_order
+            .get_or_init(|| Box::new(ArrayQueue::new(FINISHED_ORDERS_RETAINED)));
+        if let Some((oldest, oldest_id)) = finished_order.force_push((sequence, order_id)) {
+            // Only if the order was not finished again since
+            self.finished
+                .remove_if(&oldest_id, |_, (sequence, _)| *sequence == oldest);
+        }
+    }
+
+    /// Record that an order was rejected, unless its operation already
+    /// recorded an outcome, such as the cancelled remainder of a partly
+    /// filled IOC order
+    pub(super) fn finish_rejected(&self, order_id: OrderId) {
+        if !self.finished.contains_key(&order_id) {
+            self.finish(order_id, OrderStatus::Rejected);
+        }
+    }
+
+    /// Forget the final status recorded under an id that is being submitted again
+    pub(super) fn reopen(&self, order_id: OrderId) {
+        self.finished.remove(&order_id);
+    }
+
+    /// Release the spare capacity of the execution states and final statuses.
+    /// The queue of finished orders keeps its fixed capacity
+    pub(super) fn shrink_to_fit(&self) {
+        self.states.shrink_to_fit();
+        self.finished.shrink_to_fit();
+    }
+
+    /// Entries and approximate bytes of the execution states and final statuses
+    pub(super) fn memory(&self) -> StructureMemory {
+        // Each slot of the queue also holds its stamp
+        let queue_bytes = self.finished_order.get().map_or(0, |queue| {
+            (queue.capacity() * (size_of::<(u64, OrderId)>() + size_of::<usize>())) as u64
+        });
+        let states = map_memory(&self.states);
+        let finished = map_memory(&self.finished);
+        StructureMemory {
+            entries: states.entries + finished.entries,
+            bytes: states.bytes + finished.bytes + queue_bytes,
+        }
+    }
+
+    pub(super) fn finished_status(&self, order_id: OrderId) -> Option<OrderStatus> {
+        self.finished.get(&order_id).map(|entry| entry.1)
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Execution state of a resting order, or `None` once it was filled or
+    /// cancelled, or if it never rested
+    pub fn execution_state(&self, order_id: OrderId) -> Option<ExecutionState> {
+        self.executions.get(order_id)
+    }
+
+    /// Status of an order: `New` or `PartiallyFilled` while it rests, its final
+    /// status once it left the book or if it never rested, and `Unknown` for
+    /// ids the book never saw.
+    ///
+    /// Final statuses are kept for the last [`FINISHED_ORDERS_RETAINED`]
+    /// finished orders, older ones report `Unknown`. Orders removed by
+    /// [`clear`](Self::clear) report `Unknown` as well.
+    pub fn order_status(&self, order_id: OrderId) -> OrderStatus {
+        if self.order_locations.contains_key(&order_id) {
+            return if self.executions.executed(order_id) > 0 {
+                OrderStatus::PartiallyFilled
+            } else {
+                OrderStatus::New
+            };
+        }
+        self.executions
+            .finished_status(order_id)
+            .unwrap_or(OrderStatus::Unknown)
+    }
+}
@@ -0,0 +1,281 @@
+//! Execution reports, one uniform record per acceptance, fill, cancel, reject
+//! and expiry.
+//!
+//! An [`ExecutionReport`] carries the fields an order management system keys
+//! its order state on, whatever event produced it, and is what a FIX gateway
+//! maps to an `ExecutionReport <8>` message. Reports are derived from the
+//! book's events, so [`execution_report_listener`] is all it takes to receive
+//! them.
+
+use super::events::{AcceptedOrder, CancelledOrder, EventListener, OrderBookEvent, OrderReject};
+use super::expiry::ExpiredOrder;
+use super::fills::FillNotification;
+use pricelevel::{OrderId, Side};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// What happened to the order, as in FIX `ExecType <150>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExecType {
+    /// The order came to rest in the book
+    New,
+    /// The order traded
+    Trade,
+    /// The order was cancelled
+    Cancelled,
+    /// The order was refused by the book
+    Rejected,
+    /// The order's time in force ran out
+    Expired,
+}
+
+impl ExecType {
+    /// Value of the FIX `ExecType <150>` field
+    pub fn fix_value(&self) -> char {
+        match self {
+            ExecType::New => '0',
+            ExecType::Trade => 'F',
+            ExecType::Cancelled => '4',
+            ExecType::Rejected => '8',
+            ExecType::Expired => 'C',
+        }
+    }
+}
+
+impl fmt::Display for ExecType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// State of an order, as in FIX `OrdStatus <39>`. Execution reports carry the
+/// state once their event is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// The order rests in the book and nothing has executed yet
+    New,
+    /// Part of the order executed and the rest is still working
+    PartiallyFilled,
+    /// The whole order executed
+    Filled,
+    /// The order was cancelled, possibly after partial fills
+    Cancelled,
+    /// The order expired, possibly after partial fills
+    Expired,
+    /// The order was never accepted
+    Rejected,
+    /// The book has no record of the order
+    Unknown,
+}
+
+impl OrderStatus {
+    /// Value of the FIX `OrdStatus <39>` field. FIX has no unknown status and
+    /// answers status requests about unknown orders as rejected
+    pub fn fix_value(&self) -> char {
+        match self {
+            OrderStatus::New => '0',
+            OrderStatus::PartiallyFilled => '1',
+            OrderStatus::Filled => '2',
+            OrderStatus::Cancelled => '4',
+            OrderStatus::Expired => 'C',
+            OrderStatus::Rejected | OrderStatus::Unknown => '8',
+        }
+    }
+
+    /// Returns true if the order can no longer trade
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Cancelled
+                | OrderStatus::Expired
+                | OrderStatus::Rejected
+        )
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// One execution report about one order.
+///
+/// The quantities follow FIX: `last_*` describe the trade that produced the
+/// report and are zero for other reports, `leaves_quantity` is what is still
+/// working and `cumulative_quantity` what has executed so far, so a cancel
+/// after partial fills reports the executed part in `cumulative_quantity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Id of this report. Derived from the event, so reporting the same event
+    /// twice gives the same id and receivers can drop the duplicate
+    pub exec_id: Uuid,
+    /// What happened to the order
+    pub exec_type: ExecType,
+    /// Side of the order, unknown for rejects
+    pub side: Option<Side>,
+    /// Quantity of the trade, zero unless `exec_type` is `Trade`
+    pub last_quantity: u64,
+    /// Price of the trade, zero unless `exec_type` is `Trade`
+    pub last_price: u64,
+    /// Quantity still working after the event
+    pub leaves_quantity: u64,
+    /// Quantity executed so far
+    pub cumulative_quantity: u64,
+    /// State of the order after the event
+    pub status: OrderStatus,
+    /// Id of the trade, for trade reports
+    pub trade_id: Option<Uuid>,
+    /// When the event happened (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+impl ExecutionReport {
+    /// Id for a report that is not about a trade, from what identifies its event
+    fn derive_exec_id(order_id: OrderId, exec_type: ExecType, timestamp: u64) -> Uuid {
+        let name = format!("{order_id}:{}:{timestamp}", exec_type.fix_value());
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes())
+    }
+
+    /// Report an order coming to rest. An order that traded on entry is
+    /// already partially filled
+    pub fn from_accepted(accepted: &AcceptedOrder) -> Self {
+        let timestamp = accepted.accepted_at_nanos / 1_000_000;
+        Self {
+            order_id: accepted.order_id,
+            exec_id: Self::derive_exec_id(
+                accepted.order_id,
+                ExecType::New,
+                accepted.accepted_at_nanos,
+            ),
+            exec_type: ExecType::New,
+            side: Some(accepted.side),
+            last_quantity: 0,
+            last_price: 0,
+            leaves_quantity: accepted.quantity,
+            cumulative_quantity: accepted.executed_quantity,
+            status: if accepted.executed_quantity > 0 {
+                OrderStatus::PartiallyFilled
+            } else {
+                OrderStatus::New
+            },
+            trade_id: None,
+            timestamp,
+        }
+    }
+
+    /// Report a fill. The maker and taker reports of one trade get distinct ids
+    pub fn from_fill(fill: &FillNotification) -> Self {
+        let role: &[u8] = if fill.is_maker { b"maker" } else { b"taker" };
+        Self {
+            order_id: fill.order_id,
+            exec_id: Uuid::new_v5(&fill.transaction_id, role),
+            exec_type: ExecType::Trade,
+            side: Some(fill.side),
+            last_quantity: fill.quantity,
+            last_price: fill.price,
+            leaves_quantity: fill.remaining_quantity,
+            cumulative_quantity: fill.cumulative_quantity,
+            status: if fill.is_complete() {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            },
+            trade_id: Some(fill.transaction_id),
+            timestamp: fill.timestamp,
+        }
+    }
+
+    /// Report a cancel
+    pub fn from_cancel(cancel: &CancelledOrder) -> Self {
+        Self {
+            order_id: cancel.order_id,
+            exec_id: Self::derive_exec_id(cancel.order_id, ExecType::Cancelled, cancel.timestamp),
+            exec_type: ExecType::Cancelled,
+            side: Some(cancel.side),
+            last_quantity: 0,
+            last_price: 0,
+            leaves_quantity: 0,
+            cumulative_quantity: cancel.executed_quantity,
+            status: OrderStatus::Cancelled,
+            trade_id: None,
+            timestamp: cancel.timestamp,
+        }
+    }
+
+    /// Report a reject. Rejected orders never rested, so nothing executed
+    pub fn from_reject(reject: &OrderReject) -> Self {
+        Self {
+            order_id: reject.order_id,
+            exec_id: Self::derive_exec_id(reject.order_id, ExecType::Rejected, reject.timestamp),
+            exec_type: ExecType::Rejected,
+            side: None,
+            last_quantity: 0,
+            last_price: 0,
+            leaves_quantity: 0,
+            cumulative_quantity: 0,
+            status: OrderStatus::Rejected,
+            trade_id: None,
+            timestamp: reject.timestamp,
+        }
+    }
+
+    /// Report an expiry
+    pub fn from_expired(expired: &ExpiredOrder) -> Self {
+        Self {
+            order_id: expired.order_id,
+            exec_id: Self::derive_exec_id(expired.order_id, ExecType::Expired, expired.timestamp),
+            exec_type: ExecType::Expired,
+            side: Some(expired.side),
+            last_quantity: 0,
+            last_price: 0,
+            leaves_quantity: 0,
+            cumulative_quantity: expired.executed_quantity,
+            status: OrderStatus::Expired,
+            trade_id: None,
+            timestamp: expired.timestamp,
+        }
+    }
+}
+
+impl OrderBookEvent {
+    /// The execution report for this event, or `None` for events that are not
+    /// about a single order
+    pub fn execution_report(&self) -> Option<ExecutionReport> {
+        match self {
+            OrderBookEvent::OrderAccepted(accepted) => {
+                Some(ExecutionReport::from_accepted(accepted))
+            }
+            OrderBookEvent::OrderFilled(fill) => Some(ExecutionReport::from_fill(fill)),
+            OrderBookEvent::OrderCancelled(cancel) => Some(ExecutionReport::from_cancel(cancel)),
+            OrderBookEvent::OrderRejected(reject) => Some(ExecutionReport::from_reject(reject)),
+            OrderBookEvent::OrderExpired(expired) => Some(ExecutionReport::from_expired(expired)),
+            OrderBookEvent::LevelAdded(_)
+            | OrderBookEvent::LevelRemoved(_)
+            | OrderBookEvent::OptionsChanged(_)
+            | OrderBookEvent::PriceLevelFault(_)
+            | OrderBookEvent::QuotesPulled(_)
+            | OrderBookEvent::TradingHalted(_)
+            | OrderBookEvent::TradingStateChanged(_)
+            | OrderBookEvent::TradeExecuted(_) => None,
+        }
+    }
+}
+
+/// Event listener passing `on_report` the execution report of every event that
+/// has one, for [`OrderBook::set_event_listener`](crate::orderbook::OrderBook::set_event_listener)
+pub fn execution_report_listener<F>(on_report: F) -> EventListener
+where
+    F: Fn(&ExecutionReport) + Send + Sync + 'static,
+{
+    Arc::new(move |event: &OrderBookEvent| {
+        if let Some(report) = event.execution_report() {
+            on_report(&report);
+        }
+    })
+}
@@ -0,0 +1,180 @@
+//! Removal of expired GTD, DAY and auction orders, with a log of what was removed
+
+use super::book::OrderBook;
+use super::events::OrderBookEvent;
+use super::execution_report::OrderStatus;
+use super::session::{AuctionTimeInForce, TradingState};
+use pricelevel::{OrderId, Side, TimeInForce};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tracing::trace;
+
+/// Number of expired orders kept for [`OrderBook::expired_orders_since`]
+pub const EXPIRED_ORDERS_RETAINED: usize = 65_536;
+
+/// A resting order removed from the book because its time in force ran out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpiredOrder {
+    /// Position of the order in the book's expiry log, starting at 1
+    pub sequence: u64,
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Price of the order
+    pub price: u64,
+    /// Quantity that was still resting, visible and hidden
+    pub quantity: u64,
+    /// Quantity the order executed before it expired
+    #[serde(default)]
+    pub executed_quantity: u64,
+    /// Time in force of the order
+    pub time_in_force: TimeInForce,
+    /// Auction the order was restricted to, if that is what expired it
+    #[serde(default)]
+    pub auction: Option<AuctionTimeInForce>,
+    /// When the order was removed (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+#[derive(Default)]
+struct ExpiryLogState {
+    entries: VecDeque<ExpiredOrder>,
+    last_sequence: u64,
+}
+
+/// The most recent expired orders, numbered in removal order
+#[derive(Default)]
+pub(super) struct ExpiryLog {
+    state: Mutex<ExpiryLogState>,
+}
+
+impl ExpiryLog {
+    fn state(&self) -> std::sync::MutexGuard<'_, ExpiryLogState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Remove every resting order whose time in force has run out by the
+    /// book's clock, see
+    /// [`expire_orders_at`](Self::expire_orders_at)
+    pub fn expire_orders(&self) -> Vec<ExpiredOrder> {
+        self.expire_orders_at(self.now())
+    }
+
+    /// Remove every resting GTD order whose expiry is at or before `now`, and
+    /// every DAY order once `now` reaches the market close.
+    ///
+    /// Each removed order is published as an `OrderExpired` event and recorded
+    /// in the expiry log read by [`expired_orders_since`](Self::expired_orders_since).
+    pub fn expire_orders_at(&self, now: u64) -> Vec<ExpiredOrder> {
+        let market_close = self.options().options.market_close_timestamp;
+        // Collect first: cancelling takes the level entries the iteration holds
+        let candidates: Vec<OrderId> = self
+            .bids
+            .iter()
+            .chain(self.asks.iter())
+            .flat_map(|level| self.queued_orders(&level))
+            .filter(|order| order.time_in_force().is_expired(now, market_close))
+            .map(|order| order.id())
+            .collect();
+
+        candidates
+            .into_iter()
+            .filter_map(|order_id| self.expire_order(order_id, None, now))
+            .collect()
+    }
+
+    /// Remove the resting orders restricted to the auction called in
+    /// `leaving`, as the book leaves it. Published and logged as
+    /// [`expire_orders_at`](Self::expire_orders_at) does
+    pub(super) fn expire_auction_orders(
+        &self,
+        leaving: TradingState,
+        now: u64,
+    ) -> Vec<ExpiredOrder> {
+        let mut candidates: Vec<(u64, OrderId, AuctionTimeInForce)> = self
+            .order_constraints
+            .iter()
+            .filter_map(|entry| {
+                let auction = entry
+                    .value()
+                    .auction
+                    .filter(|auction| auction.is_for(leaving))?;
+                let sequence = self.order_sequence(*entry.key())?;
+                Some((sequence, *entry.key(), auction))
+            })
+            .collect();
+        candidates.sort_unstable_by_key(|(sequence, _, _)| *sequence);
+        candidates
+            .into_iter()
+            .filter_map(|(_, order_id, auction)| self.expire_order(order_id, Some(auction), now))
+            .collect()
+    }
+
+    fn expire_order(
+        &self,
+        order_id: OrderId,
+        auction: Option<AuctionTimeInForce>,
+        now: u64,
+    ) -> Option<ExpiredOrder> {
+        let (order, executed_quantity) = match self.remove_resting_order(order_id) {
+            Ok(removed) => removed?,
+            Err(error) => {
+                self.emit_event(&OrderBookEvent::PriceLevelFault(error));
+                return None;
+            }
+        };
+        self.finish_order(order_id, OrderStatus::Expired);
+        let mut log = self.expiry_log.state();
+        log.last_sequence += 1;
+        let entry = ExpiredOrder {
+            sequence: log.last_sequence,
+            order_id,
+            side: order.side(),
+            price: order.price(),
+            quantity: order.visible_quantity() + order.hidden_quantity(),
+            executed_quantity,
+            time_in_force: order.time_in_force(),
+            auction,
+            timestamp: now,
+        };
+        if log.entries.len() == EXPIRED_ORDERS_RETAINED {
+            log.entries.pop_front();
+        }
+        log.entries.push_back(entry);
+        drop(log);
+
+        trace!(
+            "Order book {}: Expired order {} ({})",
+            self.symbol,
+            order_id,
+            auction.map_or_else(
+                || entry.time_in_force.to_string(),
+                |auction| auction.to_string()
+            )
+        );
+        self.emit_event(&OrderBookEvent::OrderExpired(entry));
+        Some(entry)
+    }
+
+    /// Orders removed by expiry after the one with log sequence `sequence`, oldest first.
+    ///
+    /// Pass 0 to get every retained entry, then the last sequence seen to get
+    /// only what expired since. Only the latest [`EXPIRED_ORDERS_RETAINED`]
+    /// entries are kept.
+    pub fn expired_orders_since(&self, sequence: u64) -> Vec<ExpiredOrder> {
+        let log = self.expiry_log.state();
+        let skip = log
+            .entries
+            .partition_point(|entry| entry.sequence <= sequence);
+        log.entries.iter().skip(skip).copied().collect()
+    }
+}
@@ -0,0 +1,313 @@
+//! Export of trades and snapshots to CSV and, with the `parquet` feature, to
+//! Parquet, for analysis in pandas, Polars or a spreadsheet.
+//!
+//! Both formats use the same columns. Trades get one row each, and snapshots
+//! one row per price level in long format, so that a file of periodic
+//! snapshots loads as a single table.
+
+use super::snapshot::OrderBookSnapshot;
+use super::tape::TradeTape;
+use pricelevel::{PriceLevelSnapshot, Side, Transaction};
+use std::io::{self, Write};
+
+/// Columns of the trade CSV
+pub const TRADE_CSV_HEADER: &str =
+    "timestamp,transaction_id,price,quantity,taker_side,taker_order_id,maker_order_id";
+
+/// Columns of the snapshot CSV
+pub const SNAPSHOT_CSV_HEADER: &str =
+    "timestamp,symbol,side,level,price,visible_quantity,hidden_quantity,order_count";
+
+/// Quote a CSV field if it holds a separator, quote or line break
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+/// Levels of a snapshot as `(side, level, snapshot)`, bids then asks, best first
+fn snapshot_rows(
+    snapshot: &OrderBookSnapshot,
+) -> impl Iterator<Item = (Side, usize, &PriceLevelSnapshot)> {
+    let bids = snapshot
+        .bids
+        .iter()
+        .enumerate()
+        .map(|(level, price_level)| (Side::Buy, level, price_level));
+    let asks = snapshot
+        .asks
+        .iter()
+        .enumerate()
+        .map(|(level, price_level)| (Side::Sell, level, price_level));
+    bids.chain(asks)
+}
+
+/// Streams trades to CSV, one row per trade, under a header written on creation
+pub struct TradeCsvWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TradeCsvWriter<W> {
+    /// Start a trade CSV on `writer`, writing the header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{TRADE_CSV_HEADER}")?;
+        Ok(Self { writer })
+    }
+
+    /// Append one trade
+    pub fn write_trade(&mut self, trade: &Transaction) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{}",
+            trade.timestamp,
+            trade.transaction_id,
+            trade.price,
+            trade.quantity,
+            side_name(trade.taker_side),
+            csv_field(&trade.taker_order_id.to_string()),
+            csv_field(&trade.maker_order_id.to_string()),
+        )
+    }
+
+    /// Append trades in order, such as the transactions of a match result
+    pub fn write_trades(&mut self, trades: &[Transaction]) -> io::Result<()> {
+        trades.iter().try_for_each(|trade| self.write_trade(trade))
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Streams snapshots to CSV, one row per price level, under a header written
+/// on creation. Levels are numbered from 0 at the best price of each side
+pub struct SnapshotCsvWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SnapshotCsvWriter<W> {
+    /// Start a snapshot CSV on `writer`, writing the header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{SNAPSHOT_CSV_HEADER}")?;
+        Ok(Self { writer })
+    }
+
+    /// Append the levels of one snapshot
+    pub fn write_snapshot(&mut self, snapshot: &OrderBookSnapshot) -> io::Result<()> {
+        let symbol = csv_field(&snapshot.symbol);
+        for (side, level, price_level) in snapshot_rows(snapshot) {
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{}",
+                snapshot.timestamp,
+                symbol,
+                side_name(side),
+                level,
+                price_level.price,
+                price_level.visible_quantity,
+                price_level.hidden_quantity,
+                price_level.order_count,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl TradeTape {
+    /// Write the trades on the tape to `writer` as CSV, oldest first
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut csv = TradeCsvWriter::new(writer)?;
+        csv.write_trades(&self.trades())?;
+        csv.flush()
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use self::parquet_export::{SnapshotParquetWriter, TradeParquetWriter};
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use super::{OrderBookSnapshot, TradeTape, side_name, snapshot_rows};
+    use crate::orderbook::error::OrderBookError;
+    use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+    use pricelevel::Transaction;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    fn serialization_error(error: impl std::fmt::Display) -> OrderBookError {
+        OrderBookError::SerializationError {
+            message: error.to_string(),
+        }
+    }
+
+    fn u64_column(values: impl Iterator<Item = u64>) -> ArrayRef {
+        Arc::new(values.collect::<UInt64Array>())
+    }
+
+    fn string_column<S: AsRef<str>>(values: impl Iterator<Item = S>) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(values))
+    }
+
+    fn open<W: Write + Send>(
+        writer: W,
+        schema: &SchemaRef,
+    ) -> Result<ArrowWriter<W>, OrderBookError> {
+        ArrowWriter::try_new(writer, Arc::clone(schema), None).map_err(serialization_error)
+    }
+
+    fn write_batch<W: Write + Send>(
+        writer: &mut ArrowWriter<W>,
+        schema: &SchemaRef,
+        columns: Vec<ArrayRef>,
+    ) -> Result<(), OrderBookError> {
+        let batch =
+            RecordBatch::try_new(Arc::clone(schema), columns).map_err(serialization_error)?;
+        writer.write(&batch).map_err(serialization_error)
+    }
+
+    /// Streams trades to a Parquet file with the columns of the trade CSV.
+    ///
+    /// The file is only readable once [`finish`](Self::finish) has written its footer.
+    pub struct TradeParquetWriter<W: Write + Send> {
+        writer: ArrowWriter<W>,
+        schema: SchemaRef,
+    }
+
+    impl<W: Write + Send> TradeParquetWriter<W> {
+        /// Start a trade Parquet file on `writer`
+        pub fn new(writer: W) -> Result<Self, OrderBookError> {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("timestamp", DataType::UInt64, false),
+                Field::new("transaction_id", DataType::Utf8, false),
+                Field::new("price", DataType::UInt64, false),
+                Field::new("quantity", DataType::UInt64, false),
+                Field::new("taker_side", DataType::Utf8, false),
+                Field::new("taker_order_id", DataType::Utf8, false),
+                Field::new("maker_order_id", DataType::Utf8, false),
+            ]));
+            Ok(Self {
+                writer: open(writer, &schema)?,
+                schema,
+            })
+        }
+
+        /// Append trades in order
+        pub fn write_trades(&mut self, trades: &[Transaction]) -> Result<(), OrderBookError> {
+            if trades.is_empty() {
+                return Ok(());
+            }
+            let columns = vec![
+                u64_column(trades.iter().map(|trade| trade.timestamp)),
+                string_column(trades.iter().map(|trade| trade.transaction_id.to_string())),
+                u64_column(trades.iter().map(|trade| trade.price)),
+                u64_column(trades.iter().map(|trade| trade.quantity)),
+                string_column(trades.iter().map(|trade| side_name(trade.taker_side))),
+                string_column(trades.iter().map(|trade| trade.taker_order_id.to_string())),
+                string_column(trades.iter().map(|trade| trade.maker_order_id.to_string())),
+            ];
+            write_batch(&mut self.writer, &self.schema, columns)
+        }
+
+        /// Write the footer and return the underlying writer
+        pub fn finish(self) -> Result<W, OrderBookError> {
+            self.writer.into_inner().map_err(serialization_error)
+        }
+    }
+
+    /// Streams snapshots to a Parquet file with the columns of the snapshot CSV.
+    ///
+    /// The file is only readable once [`finish`](Self::finish) has written its footer.
+    pub struct SnapshotParquetWriter<W: Write + Send> {
+        writer: ArrowWriter<W>,
+        schema: SchemaRef,
+    }
+
+    impl<W: Write + Send> SnapshotParquetWriter<W> {
+        /// Start a snapshot Parquet file on `writer`
+        pub fn new(writer: W) -> Result<Self, OrderBookError> {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("timestamp", DataType::UInt64, false),
+                Field::new("symbol", DataType::Utf8, false),
+                Field::new("side", DataType::Utf8, false),
+                Field::new("level", DataType::UInt32, false),
+                Field::new("price", DataType::UInt64, false),
+                Field::new("visible_quantity", DataType::UInt64, false),
+                Field::new("hidden_quantity", DataType::UInt64, false),
+                Field::new("order_count", DataType::UInt64, false),
+            ]));
+            Ok(Self {
+                writer: open(writer, &schema)?,
+                schema,
+            })
+        }
+
+        /// Append the levels of one snapshot
+        pub fn write_snapshot(
+            &mut self,
+            snapshot: &OrderBookSnapshot,
+        ) -> Result<(), OrderBookError> {
+            let rows: Vec<_> = snapshot_rows(snapshot).collect();
+            if rows.is_empty() {
+                return Ok(());
+            }
+            let columns = vec![
+                u64_column(rows.iter().map(|_| snapshot.timestamp)),
+                string_column(rows.iter().map(|_| snapshot.symbol.as_str())),
+                string_column(rows.iter().map(|(side, _, _)| side_name(*side))),
+                Arc::new(
+                    rows.iter()
+                        .map(|(_, level, _)| *level as u32)
+                        .collect::<UInt32Array>(),
+                ),
+                u64_column(rows.iter().map(|(_, _, level)| level.price)),
+                u64_column(rows.iter().map(|(_, _, level)| level.visible_quantity)),
+                u64_column(rows.iter().map(|(_, _, level)| level.hidden_quantity)),
+                u64_column(rows.iter().map(|(_, _, level)| level.order_count as u64)),
+            ];
+            write_batch(&mut self.writer, &self.schema, columns)
+        }
+
+        /// Write the footer and return the underlying writer
+        pub fn finish(self) -> Result<W, OrderBookError> {
+            self.writer.into_inner().map_err(serialization_error)
+        }
+    }
+
+    impl TradeTape {
+        /// Write the trades on the tape to `writer` as a Parquet file, oldest first
+        pub fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<W, OrderBookError> {
+            let mut parquet = TradeParquetWriter::new(writer)?;
+            parquet.write_trades(&self.trades())?;
+            parquet.finish()
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Fair, bounded modification of resting orders at contended price levels.
+//!
+//! The shard locks behind a level are not fair: a writer only gets one once
+//! no reader holds it, and competing writers barge past each other, so at a
+//! hot level an unlucky update can lose the race indefinitely. Quantity
+//! updates first take a ticket for their level and are admitted strictly in
+//! ticket order, so an update waits for at most the updates that arrived at
+//! its level before it. The order may move to another price while the update
+//! waits; its location is then read again, a bounded number of times.
+
+use super::book::OrderBook;
+use super::error::{LevelOperation, OrderBookError};
+use super::execution_report::OrderStatus;
+use pricelevel::{OrderId, OrderType, OrderUpdate, Side};
+use std::sync::Arc;
+use tracing::trace;
+
+/// Times an update looks the order up again after it moved to another level
+const MAX_UPDATE_ATTEMPTS: usize = 8;
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Change the quantity of a resting order in place, keeping its priority
+    /// unless the level decides otherwise.
+    ///
+    /// Updates to the same level are applied in the order they arrived. If the
+    /// order is moved to another level while the update waits, the update
+    /// follows it, up to [`MAX_UPDATE_ATTEMPTS`] times.
+    ///
+    /// # Errors
+    /// Returns the error of the level update, and
+    /// `OrderBookError::InvalidOperation` if the order kept moving.
+    pub(super) fn update_resting_quantity(
+        &self,
+        order_id: OrderId,
+        new_quantity: u64,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        let _write = self.writes.begin();
+        for attempt in 1..=MAX_UPDATE_ATTEMPTS {
+            let Some((price, side)) = self.order_locations.get(&order_id).map(|val| *val) else {
+                return Ok(None);
+            };
+            let price_levels = match side {
+                Side::Buy => &self.bids,
+                Side::Sell => &self.asks,
+            };
+
+            let (updated, is_empty) = {
+                let _ticket = self.level_tickets.lock(side, price);
+                if self.order_locations.get(&order_id).map(|val| *val) != Some((price, side)) {
+                    trace!(
+                        "Order book {}: Order {} moved while updating its quantity, attempt {}",
+                        self.symbol, order_id, attempt
+                    );
+                    continue;
+                }
+                // Hold the level exclusively while modifying it
+                let Some(price_level) = price_levels.get_mut(&price) else {
+                    continue;
+                };
+                let updated = self.apply_level_update(
+                    &price_level,
+                    side,
+                    LevelOperation::UpdateQuantity,
+                    order_id,
+                    OrderUpdate::UpdateQuantity {
+                        order_id,
+                        new_quantity,
+                    },
+                );
+                (updated, price_level.order_count() == 0)
+            };
+            let result = updated?.map(|order| Arc::new(self.convert_from_unit_type(&order)));
+
+            // If the price level is now empty, remove it
+            if is_empty {
+                self.remove_level_if_empty(side, price);
+                self.forget_order(order_id);
+                self.finish_order(order_id, OrderStatus::Cancelled);
+            }
+
+            if result.is_some() || is_empty {
+                self.bump_version();
+            }
+            return Ok(result);
+        }
+
+        Err(OrderBookError::InvalidOperation {
+            message: format!(
+                "Order {order_id} kept moving while its quantity was updated, gave up after {MAX_UPDATE_ATTEMPTS} attempts"
+            ),
+        })
+    }
+}
@@ -0,0 +1,231 @@
+//! A read-only replica of a book, kept up to date from a sequenced
+//! market-by-order feed so market data can be served away from the engine.
+
+use super::FeedMessage;
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::error::OrderBookError;
+use crate::orderbook::snapshot::OrderBookL3Snapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::trace;
+
+/// Default number of messages a follower holds back while waiting for a gap to be filled
+pub const DEFAULT_MAX_PENDING: usize = 10_000;
+
+/// A feed message and its position in the feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencedFeedMessage {
+    /// Sequence number of the message, one more than the message before it
+    pub sequence: u64,
+    /// The change to the book
+    pub message: FeedMessage,
+}
+
+/// Whether a follower's book matches the feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FollowerStatus {
+    /// Every message up to the last sequence was applied
+    Synced,
+    /// The message with sequence `expected` was never received. Later
+    /// messages are held back until it arrives or the book is resynced
+    Gap {
+        /// Sequence number of the missing message
+        expected: u64,
+    },
+    /// The message with sequence `sequence` could not be applied, so the
+    /// book no longer matches the source and must be resynced
+    Diverged {
+        /// Sequence number of the failed message
+        sequence: u64,
+    },
+}
+
+/// Maintains a copy of a book from a sequenced feed of [`FeedMessage`]s.
+///
+/// The follower owns the feed side: one thread hands it messages with
+/// [`apply`](Self::apply), while any number of readers query the shared
+/// [`book`](Self::book). Messages must arrive with consecutive sequence
+/// numbers; a jump leaves the follower in [`FollowerStatus::Gap`] and holds
+/// the later messages back. Once the missing message arrives the held ones
+/// are applied; otherwise [`resync`](Self::resync) restores the book from a
+/// market-by-order snapshot and replays whatever was held back after it.
+///
+/// The replica carries displayed quantities only, as a feed reports them.
+pub struct FollowerBook<T = ()> {
+    book: Arc<OrderBook<T>>,
+    last_sequence: u64,
+    status: FollowerStatus,
+    pending: BTreeMap<u64, FeedMessage>,
+    max_pending: usize,
+}
+
+impl<T> FollowerBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create a follower with an empty book for `symbol`, expecting the feed
+    /// to start at sequence 1
+    pub fn new(symbol: &str) -> Self {
+        Self::from_sequence(symbol, 0)
+    }
+
+    /// Create a follower with an empty book for `symbol` that joins the feed
+    /// after sequence `last_sequence`. Unless the source book was empty at
+    /// that point, [`resync`](Self::resync) it before serving reads
+    pub fn from_sequence(symbol: &str, last_sequence: u64) -> Self {
+        Self {
+            book: Arc::new(OrderBook::new(symbol)),
+            last_sequence,
+            status: FollowerStatus::Synced,
+            pending: BTreeMap::new(),
+            max_pending: DEFAULT_MAX_PENDING,
+        }
+    }
+
+    /// Hold back at most `max_pending` messages while in a gap. Further
+    /// messages are dropped and must be recovered by a resync
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// The replicated book, to be cloned by readers on other threads
+    pub fn book(&self) -> &Arc<OrderBook<T>> {
+        &self.book
+    }
+
+    /// Sequence number of the last message applied to the book
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Whether the book matches the feed
+    pub fn status(&self) -> FollowerStatus {
+        self.status
+    }
+
+    /// Returns true if the book must be restored from a snapshot, or wait
+    /// for a missing message, before it matches the feed again
+    pub fn needs_resync(&self) -> bool {
+        self.status != FollowerStatus::Synced
+    }
+
+    /// Number of messages held back waiting for a gap to be filled
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Apply the next message of the feed, returning the status afterwards.
+    ///
+    /// Messages at or below the last applied sequence are duplicates and
+    /// ignored. A message past the next expected one is held back.
+    ///
+    /// # Errors
+    /// Returns the error of a message the book rejects, see
+    /// [`OrderBook::apply_feed_message`]. The follower is then
+    /// [`FollowerStatus::Diverged`] until resynced.
+    pub fn apply(
+        &mut self,
+        message: SequencedFeedMessage,
+    ) -> Result<FollowerStatus, OrderBookError> {
+        if message.sequence <= self.last_sequence {
+            trace!(
+                "Follower {}: Ignoring duplicate message {}",
+                self.book.symbol(),
+                message.sequence
+            );
+            return Ok(self.status);
+        }
+        self.hold(message);
+        if matches!(self.status, FollowerStatus::Diverged { .. }) {
+            return Ok(self.status);
+        }
+        self.drain_pending()
+    }
+
+    /// Restore the book from `snapshot`, taken at feed sequence `sequence`,
+    /// then apply the held back messages that follow it. Returns the status
+    /// afterwards, still a gap if messages after the snapshot are missing.
+    ///
+    /// The snapshot should cover every level, e.g. `create_l3_snapshot(usize::MAX)`
+    /// on the source book.
+    ///
+    /// # Errors
+    /// Returns the error of an order or held back message the book rejects.
+    /// The follower is then [`FollowerStatus::Diverged`].
+    pub fn resync(
+        &mut self,
+        snapshot: &OrderBookL3Snapshot,
+        sequence: u64,
+    ) -> Result<FollowerStatus, OrderBookError> {
+        trace!(
+            "Follower {}: Resyncing from a snapshot at sequence {}",
+            self.book.symbol(),
+            sequence
+        );
+        self.book.clear();
+        self.last_sequence = sequence;
+        self.pending = self.pending.split_off(&(sequence + 1));
+        self.status = FollowerStatus::Synced;
+
+        for order in snapshot.orders() {
+            let add = FeedMessage::Add {
+                order_id: order.id,
+                side: order.side,
+                price: order.price,
+                quantity: order.visible_quantity,
+            };
+            if let Err(error) = self.book.apply_feed_message(&add) {
+                self.status = FollowerStatus::Diverged { sequence };
+                return Err(error);
+            }
+        }
+        self.drain_pending()
+    }
+
+    /// Hold a message back until every message before it was applied
+    fn hold(&mut self, message: SequencedFeedMessage) {
+        if self.pending.len() >= self.max_pending && !self.pending.contains_key(&message.sequence) {
+            trace!(
+                "Follower {}: Dropping message {}, too many held back",
+                self.book.symbol(),
+                message.sequence
+            );
+            return;
+        }
+        self.pending.insert(message.sequence, message.message);
+    }
+
+    /// Apply the held back messages that follow the last applied one
+    fn drain_pending(&mut self) -> Result<FollowerStatus, OrderBookError> {
+        let mut next = self.last_sequence + 1;
+        while let Some(message) = self.pending.remove(&next) {
+            if let Err(error) = self.book.apply_feed_message(&message) {
+                trace!(
+                    "Follower {}: Message {} diverged: {}",
+                    self.book.symbol(),
+                    next,
+                    error
+                );
+                self.status = FollowerStatus::Diverged { sequence: next };
+                return Err(error);
+            }
+            self.last_sequence = next;
+            next += 1;
+        }
+
+        self.status = if self.pending.is_empty() {
+            FollowerStatus::Synced
+        } else {
+            trace!(
+                "Follower {}: Gap at sequence {}, holding {} messages",
+                self.book.symbol(),
+                next,
+                self.pending.len()
+            );
+            FollowerStatus::Gap { expected: next }
+        };
+        Ok(self.status)
+    }
+}
@@ -0,0 +1,321 @@
+//! Parser for the order messages of NASDAQ TotalView-ITCH 5.0.
+//!
+//! Only the messages that change resting orders are decoded: add (`A`, `F`),
+//! executed (`E`, `C`), cancel (`X`), delete (`D`) and replace (`U`), plus the
+//! stock directory (`R`) used to find the locate code of a symbol. Every other
+//! message type is skipped. Prices keep the four implied decimals of the
+//! protocol and order reference numbers become `OrderId::from_u64`.
+
+use super::FeedMessage;
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::error::OrderBookError;
+use pricelevel::{OrderId, Side};
+use tracing::trace;
+
+/// A decoded ITCH message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItchMessage {
+    /// Stock directory entry assigning a locate code to a symbol
+    StockDirectory {
+        /// Locate code of the symbol for the session
+        stock_locate: u16,
+        /// Nanoseconds since midnight
+        timestamp: u64,
+        /// Symbol, without the padding spaces
+        stock: String,
+    },
+    /// A change to the resting orders of one symbol
+    Order {
+        /// Locate code of the symbol
+        stock_locate: u16,
+        /// Nanoseconds since midnight
+        timestamp: u64,
+        /// Symbol, only carried by add messages
+        stock: Option<String>,
+        /// The change, ready to apply to a book
+        message: FeedMessage,
+    },
+}
+
+impl ItchMessage {
+    /// Locate code of the symbol the message is about
+    pub fn stock_locate(&self) -> u16 {
+        match self {
+            ItchMessage::StockDirectory { stock_locate, .. }
+            | ItchMessage::Order { stock_locate, .. } => *stock_locate,
+        }
+    }
+
+    /// Nanoseconds since midnight
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            ItchMessage::StockDirectory { timestamp, .. }
+            | ItchMessage::Order { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+fn malformed(message: String) -> OrderBookError {
+    OrderBookError::SerializationError { message }
+}
+
+/// Big-endian field reader over one message
+struct Fields<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Fields<'_> {
+    fn take(&mut self, len: usize) -> &[u8] {
+        let field = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        field
+    }
+
+    fn uint(&mut self, len: usize) -> u64 {
+        self.take(len)
+            .iter()
+            .fold(0, |value, byte| (value << 8) | u64::from(*byte))
+    }
+
+    fn side(&mut self) -> Result<Side, OrderBookError> {
+        match self.take(1)[0] {
+            b'B' => Ok(Side::Buy),
+            b'S' => Ok(Side::Sell),
+            other => Err(malformed(format!(
+                "invalid ITCH buy/sell indicator {:?}",
+                other as char
+            ))),
+        }
+    }
+
+    fn stock(&mut self) -> String {
+        String::from_utf8_lossy(self.take(8)).trim_end().to_string()
+    }
+}
+
+/// Length of each decoded message type, type byte included
+fn message_len(message_type: u8) -> Option<usize> {
+    match message_type {
+        b'R' => Some(39),
+        b'A' => Some(36),
+        b'F' => Some(40),
+        b'E' => Some(31),
+        b'C' => Some(36),
+        b'X' => Some(23),
+        b'D' => Some(19),
+        b'U' => Some(35),
+        _ => None,
+    }
+}
+
+/// Decode one ITCH message, without its length prefix.
+///
+/// Returns `Ok(None)` for message types that do not change resting orders.
+///
+/// # Errors
+/// Returns `OrderBookError::SerializationError` if the message is empty,
+/// shorter than its type requires or has an invalid side.
+pub fn parse_message(bytes: &[u8]) -> Result<Option<ItchMessage>, OrderBookError> {
+    let Some(&message_type) = bytes.first() else {
+        return Err(malformed("empty ITCH message".to_string()));
+    };
+    let Some(len) = message_len(message_type) else {
+        return Ok(None);
+    };
+    if bytes.len() < len {
+        return Err(malformed(format!(
+            "ITCH message {:?} has {} bytes, expected {}",
+            message_type as char,
+            bytes.len(),
+            len
+        )));
+    }
+
+    let mut fields = Fields { bytes, offset: 1 };
+    let stock_locate = fields.uint(2) as u16;
+    let _tracking_number = fields.uint(2);
+    let timestamp = fields.uint(6);
+    let order = |stock, message| {
+        Some(ItchMessage::Order {
+            stock_locate,
+            timestamp,
+            stock,
+            message,
+        })
+    };
+
+    let message = match message_type {
+        b'R' => Some(ItchMessage::StockDirectory {
+            stock_locate,
+            timestamp,
+            stock: fields.stock(),
+        }),
+        b'A' | b'F' => {
+            let order_id = OrderId::from_u64(fields.uint(8));
+            let side = fields.side()?;
+            let quantity = fields.uint(4);
+            let stock = fields.stock();
+            let price = fields.uint(4);
+            order(
+                Some(stock),
+                FeedMessage::Add {
+                    order_id,
+                    side,
+                    price,
+                    quantity,
+                },
+            )
+        }
+        b'E' | b'C' => {
+            let order_id = OrderId::from_u64(fields.uint(8));
+            let quantity = fields.uint(4);
+            let _match_number = fields.uint(8);
+            let price = if message_type == b'C' {
+                let _printable = fields.uint(1);
+                Some(fields.uint(4))
+            } else {
+                None
+            };
+            order(
+                None,
+                FeedMessage::Execute {
+                    order_id,
+                    quantity,
+                    price,
+                },
+            )
+        }
+        b'X' => {
+            let order_id = OrderId::from_u64(fields.uint(8));
+            let quantity = fields.uint(4);
+            order(None, FeedMessage::Cancel { order_id, quantity })
+        }
+        b'D' => {
+            let order_id = OrderId::from_u64(fields.uint(8));
+            order(None, FeedMessage::Delete { order_id })
+        }
+        b'U' => {
+            let order_id = OrderId::from_u64(fields.uint(8));
+            let new_order_id = OrderId::from_u64(fields.uint(8));
+            let quantity = fields.uint(4);
+            let price = fields.uint(4);
+            order(
+                None,
+                FeedMessage::Replace {
+                    order_id,
+                    new_order_id,
+                    price,
+                    quantity,
+                },
+            )
+        }
+        _ => unreachable!("message types without a length are skipped"),
+    };
+    Ok(message)
+}
+
+/// Iterator over the messages of an ITCH recording in the length-prefixed
+/// framing of NASDAQ's binary files and MoldUDP64 payloads: each message is
+/// preceded by its length as a big-endian `u16`.
+///
+/// Skipped message types are not yielded. Iteration stops after the first
+/// error, such as a truncated frame at the end of the data.
+pub struct ItchReader<'a> {
+    data: &'a [u8],
+    failed: bool,
+}
+
+impl<'a> ItchReader<'a> {
+    /// Read the messages framed in `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            failed: false,
+        }
+    }
+
+    fn fail(&mut self, message: String) -> Option<Result<ItchMessage, OrderBookError>> {
+        self.failed = true;
+        Some(Err(malformed(message)))
+    }
+}
+
+impl Iterator for ItchReader<'_> {
+    type Item = Result<ItchMessage, OrderBookError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed && !self.data.is_empty() {
+            if self.data.len() < 2 {
+                return self.fail("truncated ITCH length prefix".to_string());
+            }
+            let len = usize::from(u16::from_be_bytes([self.data[0], self.data[1]]));
+            let Some(frame) = self.data.get(2..2 + len) else {
+                return self.fail(format!(
+                    "truncated ITCH frame: {} bytes announced, {} left",
+                    len,
+                    self.data.len() - 2
+                ));
+            };
+            self.data = &self.data[2 + len..];
+            match parse_message(frame) {
+                Ok(Some(message)) => return Some(Ok(message)),
+                Ok(None) => continue,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Rebuild the book of `stock` from an ITCH recording in length-prefixed
+    /// framing, returning the number of order messages applied.
+    ///
+    /// The symbol's locate code is taken from the stock directory, or from
+    /// the first add message naming the symbol when the recording has no
+    /// directory. Messages for other symbols are ignored.
+    ///
+    /// # Errors
+    /// Returns the first parse error, or the first error applying a message,
+    /// see [`apply_feed_message`](Self::apply_feed_message).
+    pub fn replay_itch(&self, data: &[u8], stock: &str) -> Result<usize, OrderBookError> {
+        let mut stock_locate = None;
+        let mut applied = 0;
+        for message in ItchReader::new(data) {
+            match message? {
+                ItchMessage::StockDirectory {
+                    stock_locate: locate,
+                    stock: name,
+                    ..
+                } if name == stock => stock_locate = Some(locate),
+                ItchMessage::StockDirectory { .. } => {}
+                ItchMessage::Order {
+                    stock_locate: locate,
+                    stock: name,
+                    message,
+                    ..
+                } => {
+                    if stock_locate.is_none() && name.as_deref() == Some(stock) {
+                        stock_locate = Some(locate);
+                    }
+                    if stock_locate == Some(locate) {
+                        self.apply_feed_message(&message)?;
+                        applied += 1;
+                    }
+                }
+            }
+        }
+        trace!(
+            "Order book {}: Replayed {} ITCH messages for {}",
+            self.symbol, applied, stock
+        );
+        Ok(applied)
+    }
+}
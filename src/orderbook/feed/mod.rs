@@ -0,0 +1,273 @@
+//! Rebuild an order book from a market-by-order feed, such as a NASDAQ ITCH recording.
+//!
+//! Venue formats are parsed into [`FeedMessage`]s, which
+//! [`OrderBook::apply_feed_message`] applies to the book exactly as the venue
+//! reports them: added orders rest without matching, and executions reduce
+//! the resting orders they name.
+
+pub mod follower;
+#[cfg(feature = "itch")]
+pub mod itch;
+
+use super::book::OrderBook;
+use super::error::{LevelOperation, OrderBookError};
+use super::execution::ExecutionState;
+use super::execution_report::OrderStatus;
+use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tracing::trace;
+
+/// A change to the resting orders of a venue, as published on its market-by-order feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedMessage {
+    /// An order came to rest
+    Add {
+        /// Id of the order
+        order_id: OrderId,
+        /// Side of the order
+        side: Side,
+        /// Limit price
+        price: u64,
+        /// Displayed quantity
+        quantity: u64,
+    },
+    /// Part or all of a resting order traded
+    Execute {
+        /// Id of the resting order
+        order_id: OrderId,
+        /// Quantity traded
+        quantity: u64,
+        /// Price of the trade when it differs from the order's price
+        price: Option<u64>,
+    },
+    /// Part of a resting order was cancelled
+    Cancel {
+        /// Id of the resting order
+        order_id: OrderId,
+        /// Quantity removed
+        quantity: u64,
+    },
+    /// A resting order was removed
+    Delete {
+        /// Id of the resting order
+        order_id: OrderId,
+    },
+    /// A resting order was replaced by a new one on the same side, losing its priority
+    Replace {
+        /// Id of the replaced order
+        order_id: OrderId,
+        /// Id of the new order
+        new_order_id: OrderId,
+        /// Price of the new order
+        price: u64,
+        /// Quantity of the new order
+        quantity: u64,
+    },
+}
+
+impl FeedMessage {
+    /// Id of the resting order the message refers to
+    pub fn order_id(&self) -> OrderId {
+        match self {
+            FeedMessage::Add { order_id, .. }
+            | FeedMessage::Execute { order_id, .. }
+            | FeedMessage::Cancel { order_id, .. }
+            | FeedMessage::Delete { order_id }
+            | FeedMessage::Replace { order_id, .. } => *order_id,
+        }
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Apply a feed message to the book.
+    ///
+    /// The feed is trusted: added orders rest even if they cross the other
+    /// side, as venues report during auctions, and nothing is matched.
+    /// Executions update the last trade price and the execution state of the
+    /// resting order.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::OrderNotFound` if the message refers to an
+    /// order that is not resting, and `OrderBookError::InvalidOperation` if an
+    /// added order has no quantity or its id is already resting.
+    pub fn apply_feed_message(&self, message: &FeedMessage) -> Result<(), OrderBookError> {
+        trace!(
+            "Order book {}: Applying feed message {:?}",
+            self.symbol, message
+        );
+        match *message {
+            FeedMessage::Add {
+                order_id,
+                side,
+                price,
+                quantity,
+            } => self.rest_feed_order(order_id, side, price, quantity),
+            FeedMessage::Execute {
+                order_id,
+                quantity,
+                price,
+            } => {
+                let (order_price, _) = self.reduce_feed_order(order_id, quantity, true)?;
+                self.last_trade_price
+                    .store(price.unwrap_or(order_price), Ordering::Relaxed);
+                self.has_traded.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            FeedMessage::Cancel { order_id, quantity } => {
+                self.reduce_feed_order(order_id, quantity, false)?;
+                Ok(())
+            }
+            FeedMessage::Delete { order_id } => {
+                self.reduce_feed_order(order_id, u64::MAX, false)?;
+                Ok(())
+            }
+            FeedMessage::Replace {
+                order_id,
+                new_order_id,
+                price,
+                quantity,
+            } => {
+                let (_, side) = self.reduce_feed_order(order_id, u64::MAX, false)?;
+                self.rest_feed_order(new_order_id, side, price, quantity)
+            }
+        }
+    }
+
+    /// Apply feed messages in order, stopping at the first one that fails.
+    /// Returns the number of messages applied
+    ///
+    /// # Errors
+    /// Returns the error of the failed message, see [`apply_feed_message`](Self::apply_feed_message).
+    pub fn apply_feed<'a>(
+        &self,
+        messages: impl IntoIterator<Item = &'a FeedMessage>,
+    ) -> Result<usize, OrderBookError> {
+        let mut applied = 0;
+        for message in messages {
+            self.apply_feed_message(message)?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// Rest an order at the back of its level without matching it
+    fn rest_feed_order(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        price: u64,
+        quantity: u64,
+    ) -> Result<(), OrderBookError> {
+        if quantity == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Feed order {order_id} has no quantity"),
+            });
+        }
+        if self.order_locations.contains_key(&order_id) {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Feed order {order_id} is already resting"),
+            });
+        }
+
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let (price_level, created) = price_levels.get_or_insert(price);
+        let acceptance = self.next_acceptance();
+        price_level.add_order(OrderType::Standard {
+            id: order_id,
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        });
+        self.order_locations.insert(order_id, (price, side));
+        self.order_sequences.insert(order_id, acceptance);
+        self.executions.open(
+            order_id,
+            ExecutionState {
+                original_quantity: quantity,
+                ..ExecutionState::default()
+            },
+        );
+        drop(price_level);
+        self.cache.level_added(side, price);
+        self.bump_version();
+        self.update_memory_pressure();
+        if created {
+            self.publish_level_added(side, price);
+        }
+        Ok(())
+    }
+
+    /// Take `quantity` off a resting order, removing it once nothing is left.
+    /// Returns the price and side the order rested at
+    fn reduce_feed_order(
+        &self,
+        order_id: OrderId,
+        quantity: u64,
+        executed: bool,
+    ) -> Result<(u64, Side), OrderBookError> {
+        let not_found = || OrderBookError::OrderNotFound(order_id.to_string());
+        let (price, side) = self
+            .order_locations
+            .get(&order_id)
+            .map(|location| *location)
+            .ok_or_else(not_found)?;
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+
+        let (removed, is_empty) = {
+            let price_level = price_levels.get_mut(&price).ok_or_else(not_found)?;
+            let resting = price_level
+                .iter_orders()
+                .into_iter()
+                .find(|order| order.id() == order_id)
+                .ok_or_else(not_found)?;
+            let left = resting.visible_quantity().saturating_sub(quantity);
+            let (operation, update) = if left == 0 {
+                (LevelOperation::Cancel, OrderUpdate::Cancel { order_id })
+            } else {
+                (
+                    LevelOperation::UpdateQuantity,
+                    OrderUpdate::UpdateQuantity {
+                        order_id,
+                        new_quantity: left,
+                    },
+                )
+            };
+            self.apply_level_update(&price_level, side, operation, order_id, update)?;
+            if executed {
+                let traded = resting.visible_quantity() - left;
+                self.executions.record_fill(order_id, price, traded);
+            }
+            (left == 0, price_level.order_count() == 0)
+        };
+
+        if removed {
+            self.forget_order(order_id);
+            if executed {
+                self.finish_order(order_id, OrderStatus::Filled);
+            } else {
+                self.finish_order(order_id, OrderStatus::Cancelled);
+                self.stats.record_cancelled(1);
+            }
+        }
+        if is_empty {
+            self.remove_level_if_empty(side, price);
+        }
+        self.bump_version();
+        self.update_memory_pressure();
+        Ok((price, side))
+    }
+}
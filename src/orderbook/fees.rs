@@ -0,0 +1,88 @@
+//! Maker/taker fees charged on every trade
+
+use super::book::OrderBook;
+use pricelevel::Transaction;
+use serde::{Deserialize, Serialize};
+
+/// Basis points in one unit
+const BPS_PER_UNIT: i128 = 10_000;
+
+/// Fee rates applied to the notional (price times quantity) of each trade.
+///
+/// A negative rate is a rebate paid to that side. Fees are expressed in the
+/// same units as the notional, i.e. integer price units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Rate charged to the resting order, in basis points
+    pub maker_bps: i32,
+    /// Rate charged to the incoming order, in basis points
+    pub taker_bps: i32,
+    /// Smallest fee charged on a trade; rebates are not affected
+    pub min_fee: u64,
+}
+
+impl FeeSchedule {
+    /// A schedule charging `maker_bps` to makers and `taker_bps` to takers, with no minimum
+    pub fn new(maker_bps: i32, taker_bps: i32) -> Self {
+        Self {
+            maker_bps,
+            taker_bps,
+            min_fee: 0,
+        }
+    }
+
+    /// Returns a copy of the schedule with a minimum fee per trade
+    pub fn with_min_fee(mut self, min_fee: u64) -> Self {
+        self.min_fee = min_fee;
+        self
+    }
+
+    /// Fees of both sides of a trade
+    pub fn fees_for(&self, transaction: &Transaction) -> TradeFees {
+        let notional = i128::from(transaction.price) * i128::from(transaction.quantity);
+        TradeFees {
+            maker_fee: self.fee(notional, self.maker_bps),
+            taker_fee: self.fee(notional, self.taker_bps),
+        }
+    }
+
+    /// Fees are rounded up in the venue's favour and rebates rounded down
+    fn fee(&self, notional: i128, bps: i32) -> i64 {
+        let scaled = notional * i128::from(bps);
+        let fee = if scaled > 0 {
+            (scaled + BPS_PER_UNIT - 1) / BPS_PER_UNIT
+        } else {
+            scaled / BPS_PER_UNIT
+        };
+        let fee = if bps > 0 {
+            fee.max(i128::from(self.min_fee))
+        } else {
+            fee
+        };
+        fee.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+}
+
+/// Fees charged on one trade. Negative amounts are rebates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeFees {
+    /// Fee charged to the maker
+    pub maker_fee: i64,
+    /// Fee charged to the taker
+    pub taker_fee: i64,
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Charge fees on every trade from now on, or stop charging them with `None`.
+    ///
+    /// The schedule is part of the book options, so a match uses the schedule
+    /// in force when it started for all of its trades. The fees are published
+    /// with the `TradeExecuted` event of each trade and the `OrderFilled`
+    /// events of its two sides.
+    pub fn set_fee_schedule(&self, fee_schedule: Option<FeeSchedule>) {
+        self.update_options(|options| options.fee_schedule = fee_schedule);
+    }
+}
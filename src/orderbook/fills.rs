@@ -0,0 +1,123 @@
+//! Per-order fill notifications published to the event listener
+
+use super::book::OrderBook;
+use super::fees::FeeSchedule;
+use pricelevel::{OrderId, PriceLevel, Side, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A fill of one order, as seen by the owner of that order.
+///
+/// Every trade produces two notifications, one for the taker and one for the
+/// maker, each carrying the execution state of its own order right after the
+/// fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillNotification {
+    /// Id of the filled order
+    pub order_id: OrderId,
+    /// Id of the trade
+    pub transaction_id: Uuid,
+    /// Side of the filled order
+    pub side: Side,
+    /// Price of the fill
+    pub price: u64,
+    /// Quantity of this fill
+    pub quantity: u64,
+    /// Quantity executed by the order so far, this fill included
+    pub cumulative_quantity: u64,
+    /// Quantity of the order left to execute after this fill
+    pub remaining_quantity: u64,
+    /// True if the order was resting and provided the liquidity
+    pub is_maker: bool,
+    /// Fee charged to this order on the fill (negative for a rebate), if the
+    /// book had a fee schedule
+    pub fee: Option<i64>,
+    /// When the fill happened (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+impl FillNotification {
+    /// Returns true once the order has nothing left to execute
+    pub fn is_complete(&self) -> bool {
+        self.remaining_quantity == 0
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Build the notifications of the trades a taker just made at one level,
+    /// charged under `fee_schedule`.
+    ///
+    /// Must run after the level was matched and the makers' executed
+    /// quantities were updated, while the level is still held. Since the level
+    /// and the execution tracker only show the state after the last trade, the
+    /// state after each trade is derived by walking the trades backwards.
+    pub(super) fn level_fill_notifications(
+        &self,
+        price_level: &PriceLevel,
+        transactions: &[Transaction],
+        taker_quantity: u64,
+        taker_remaining: u64,
+        fee_schedule: Option<FeeSchedule>,
+    ) -> Vec<FillNotification> {
+        // Quantity each maker has left now, 0 once it was filled and left the level
+        let mut maker_remaining: HashMap<OrderId, u64> = transactions
+            .iter()
+            .map(|transaction| (transaction.maker_order_id, 0))
+            .collect();
+        for order in price_level.iter_orders() {
+            if let Some(remaining) = maker_remaining.get_mut(&order.id()) {
+                *remaining = order.visible_quantity() + order.hidden_quantity();
+            }
+        }
+        let mut maker_executed: HashMap<OrderId, u64> = maker_remaining
+            .keys()
+            .map(|order_id| (*order_id, self.executions.executed(*order_id)))
+            .collect();
+
+        let mut notifications = Vec::with_capacity(transactions.len() * 2);
+        let mut taker_after = taker_remaining;
+        for transaction in transactions.iter().rev() {
+            let remaining = maker_remaining
+                .get_mut(&transaction.maker_order_id)
+                .expect("every maker was collected");
+            let executed = maker_executed
+                .get_mut(&transaction.maker_order_id)
+                .expect("every maker was collected");
+            let fees = fee_schedule.map(|fee_schedule| fee_schedule.fees_for(transaction));
+            notifications.push(FillNotification {
+                order_id: transaction.maker_order_id,
+                transaction_id: transaction.transaction_id,
+                side: transaction.taker_side.opposite(),
+                price: transaction.price,
+                quantity: transaction.quantity,
+                cumulative_quantity: *executed,
+                remaining_quantity: *remaining,
+                is_maker: true,
+                fee: fees.map(|fees| fees.maker_fee),
+                timestamp: transaction.timestamp,
+            });
+            notifications.push(FillNotification {
+                order_id: transaction.taker_order_id,
+                transaction_id: transaction.transaction_id,
+                side: transaction.taker_side,
+                price: transaction.price,
+                quantity: transaction.quantity,
+                cumulative_quantity: taker_quantity - taker_after,
+                remaining_quantity: taker_after,
+                is_maker: false,
+                fee: fees.map(|fees| fees.taker_fee),
+                timestamp: transaction.timestamp,
+            });
+            *remaining += transaction.quantity;
+            *executed = executed.saturating_sub(transaction.quantity);
+            taker_after += transaction.quantity;
+        }
+        // Walked backwards, and the taker comes first within a trade
+        notifications.reverse();
+        notifications
+    }
+}
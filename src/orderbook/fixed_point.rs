@@ -0,0 +1,190 @@
+//! Fixed-point prices and quantities carrying their number of decimal places
+//! in their type, converted exactly to and from decimal text and, with the
+//! `decimal` feature, `rust_decimal::Decimal`
+
+use super::error::OrderBookError;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Error for a decimal value that does not fit a fixed-point type
+fn conversion_error(kind: &str, value: &str, decimals: u32, reason: &str) -> OrderBookError {
+    OrderBookError::InvalidOperation {
+        message: format!("Cannot represent {kind} {value} with {decimals} decimals: {reason}"),
+    }
+}
+
+/// Parse non-negative decimal text into a count of `10^-decimals` units,
+/// rejecting digits beyond the last decimal place rather than rounding them
+fn parse_units(kind: &str, text: &str, decimals: u32) -> Result<u64, OrderBookError> {
+    let error = |reason: &str| conversion_error(kind, text, decimals, reason);
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(error("no digits"));
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(error("not a non-negative decimal number"));
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(error("too many decimal places"));
+    }
+
+    let scale = 10u64
+        .checked_pow(decimals)
+        .ok_or_else(|| error("too many decimals for a u64"))?;
+    let whole_units = if whole.is_empty() {
+        0
+    } else {
+        whole
+            .parse::<u64>()
+            .ok()
+            .and_then(|whole| whole.checked_mul(scale))
+            .ok_or_else(|| error("out of range"))?
+    };
+    let fraction_units = if fraction.is_empty() {
+        0
+    } else {
+        // Right-padded to `decimals` digits, so "5" with 2 decimals is 50
+        fraction.parse::<u64>().map_err(|_| error("out of range"))?
+            * 10u64.pow(decimals - fraction.len() as u32)
+    };
+    whole_units
+        .checked_add(fraction_units)
+        .ok_or_else(|| error("out of range"))
+}
+
+/// Write a count of `10^-decimals` units as decimal text
+fn format_units(f: &mut fmt::Formatter<'_>, units: u64, decimals: u32) -> fmt::Result {
+    if decimals == 0 {
+        return write!(f, "{units}");
+    }
+    let scale = 10u128.pow(decimals);
+    let units = u128::from(units);
+    write!(
+        f,
+        "{}.{:0width$}",
+        units / scale,
+        units % scale,
+        width = decimals as usize
+    )
+}
+
+macro_rules! fixed_point {
+    ($(#[$meta:meta])* $name:ident, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name<const DECIMALS: u32>(u64);
+
+        impl<const DECIMALS: u32> $name<DECIMALS> {
+            /// Number of raw units in one whole unit, `10^DECIMALS`
+            pub const SCALE: u64 = 10u64.pow(DECIMALS);
+
+            /// Wrap a raw count of `10^-DECIMALS` units, as stored in the book
+            pub const fn from_raw(raw: u64) -> Self {
+                Self(raw)
+            }
+
+            /// The raw count of `10^-DECIMALS` units, as stored in the book
+            pub const fn raw(&self) -> u64 {
+                self.0
+            }
+
+            /// A whole number of units, `None` if it overflows
+            pub fn from_units(units: u64) -> Option<Self> {
+                units.checked_mul(Self::SCALE).map(Self)
+            }
+
+            /// The value as a float, for display or analytics only
+            pub fn to_f64(&self) -> f64 {
+                self.0 as f64 / Self::SCALE as f64
+            }
+
+            /// Exact decimal value
+            #[cfg(feature = "decimal")]
+            pub fn to_decimal(&self) -> Decimal {
+                Decimal::from_i128_with_scale(i128::from(self.0), DECIMALS)
+            }
+        }
+
+        impl<const DECIMALS: u32> From<$name<DECIMALS>> for u64 {
+            fn from(value: $name<DECIMALS>) -> u64 {
+                value.0
+            }
+        }
+
+        impl<const DECIMALS: u32> fmt::Display for $name<DECIMALS> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                format_units(f, self.0, DECIMALS)
+            }
+        }
+
+        impl<const DECIMALS: u32> FromStr for $name<DECIMALS> {
+            type Err = OrderBookError;
+
+            /// Parse decimal text such as `"101.25"`. Text with more decimal
+            /// places than `DECIMALS`, other than trailing zeros, is rejected
+            fn from_str(text: &str) -> Result<Self, Self::Err> {
+                parse_units($kind, text, DECIMALS).map(Self)
+            }
+        }
+
+        #[cfg(feature = "decimal")]
+        impl<const DECIMALS: u32> TryFrom<Decimal> for $name<DECIMALS> {
+            type Error = OrderBookError;
+
+            /// Convert exactly; negative values, values with more decimal
+            /// places than `DECIMALS` and values out of range are rejected
+            fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+                let error = |reason: &str| conversion_error($kind, &value.to_string(), DECIMALS, reason);
+                if value.is_sign_negative() && !value.is_zero() {
+                    return Err(error("negative"));
+                }
+                let scaled = value
+                    .checked_mul(Decimal::from(Self::SCALE))
+                    .ok_or_else(|| error("out of range"))?;
+                if !scaled.fract().is_zero() {
+                    return Err(error("too many decimal places"));
+                }
+                u64::try_from(scaled.trunc())
+                    .map(Self)
+                    .map_err(|_| error("out of range"))
+            }
+        }
+
+        #[cfg(feature = "decimal")]
+        impl<const DECIMALS: u32> From<$name<DECIMALS>> for Decimal {
+            fn from(value: $name<DECIMALS>) -> Decimal {
+                value.to_decimal()
+            }
+        }
+    };
+}
+
+fixed_point!(
+    /// A price with `DECIMALS` decimal places, stored as the integer number of
+    /// `10^-DECIMALS` units the book works with.
+    ///
+    /// `Price::<2>::from_str("101.25")` is the book price `10125`.
+    Price,
+    "price"
+);
+
+fixed_point!(
+    /// A quantity with `DECIMALS` decimal places, stored as the integer number
+    /// of `10^-DECIMALS` units the book works with.
+    ///
+    /// `Qty::<8>::from_str("0.5")` is the book quantity `50_000_000`, half a
+    /// coin counted in satoshis.
+    Qty,
+    "quantity"
+);
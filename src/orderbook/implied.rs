@@ -0,0 +1,197 @@
+//! Implied liquidity for a calendar spread between two outright books
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use pricelevel::{MatchResult, OrderId, Side};
+use std::sync::{Arc, Mutex};
+use tracing::trace;
+
+/// Best implied price of one side of the spread and the quantity available at it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImpliedQuote {
+    /// Spread price, front minus back. Negative when the back leg is dearer
+    pub price: i64,
+    /// Spread quantity executable at that price
+    pub quantity: u64,
+    /// Price of the front leg
+    pub front_price: u64,
+    /// Price of the back leg
+    pub back_price: u64,
+}
+
+/// Both sides of the implied spread market
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImpliedSpreadQuote {
+    /// Implied bid: sell the front at its best bid and buy the back at its best ask
+    pub bid: Option<ImpliedQuote>,
+    /// Implied ask: buy the front at its best ask and sell the back at its best bid
+    pub ask: Option<ImpliedQuote>,
+}
+
+/// Outcome of executing the spread against the outright books
+#[derive(Debug, Clone)]
+pub struct ImpliedExecution {
+    /// Side of the spread that was executed
+    pub side: Side,
+    /// Spread price of the execution
+    pub price: i64,
+    /// Spread quantity executed on both legs
+    pub quantity: u64,
+    /// Trades of the front leg
+    pub front: MatchResult,
+    /// Trades of the back leg
+    pub back: MatchResult,
+}
+
+impl ImpliedExecution {
+    /// Quantity the front leg executed beyond the back leg. Non-zero only if
+    /// the back book changed between the two legs, leaving that much unhedged
+    pub fn unhedged_quantity(&self) -> u64 {
+        let front = self.front.transactions.as_vec().iter().map(|t| t.quantity);
+        let back = self.back.transactions.as_vec().iter().map(|t| t.quantity);
+        front.sum::<u64>().saturating_sub(back.sum())
+    }
+}
+
+/// Derives the implied market of a calendar spread (front minus back) from two
+/// outright books, and executes the spread by trading both legs.
+///
+/// Only the best level of each outright contributes, so a quote always has a
+/// single price per leg. The engine does not maintain a book of its own for
+/// the spread, and implied-in prices (outrights implied from spread orders)
+/// are not derived.
+///
+/// Executions made through one engine are serialized, and each leg only trades
+/// what was found executable on both legs just before. Other users of the
+/// outright books may still trade between the two legs, in which case the
+/// shortfall of the back leg is reported by [`ImpliedExecution::unhedged_quantity`]
+/// rather than undone.
+pub struct ImpliedMatchingEngine<T = ()> {
+    front: Arc<OrderBook<T>>,
+    back: Arc<OrderBook<T>>,
+    execution: Mutex<()>,
+}
+
+impl<T> ImpliedMatchingEngine<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create an engine for the spread `front - back`
+    pub fn new(front: Arc<OrderBook<T>>, back: Arc<OrderBook<T>>) -> Self {
+        Self {
+            front,
+            back,
+            execution: Mutex::new(()),
+        }
+    }
+
+    /// Book of the front leg
+    pub fn front(&self) -> &Arc<OrderBook<T>> {
+        &self.front
+    }
+
+    /// Book of the back leg
+    pub fn back(&self) -> &Arc<OrderBook<T>> {
+        &self.back
+    }
+
+    /// The implied spread market right now
+    pub fn implied_quote(&self) -> ImpliedSpreadQuote {
+        ImpliedSpreadQuote {
+            bid: self.implied(Side::Sell),
+            ask: self.implied(Side::Buy),
+        }
+    }
+
+    /// Buy (`Side::Buy`) or sell (`Side::Sell`) up to `quantity` of the spread
+    /// at the best implied price, if that price is no worse than `limit_price`.
+    ///
+    /// Buying the spread buys the front and sells the back; selling it does
+    /// the opposite. Only the best level of each leg is traded, so less than
+    /// `quantity` may execute.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InsufficientLiquidity` if there is no implied
+    /// liquidity on that side, or none within the limit price.
+    pub fn execute_spread(
+        &self,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<i64>,
+    ) -> Result<ImpliedExecution, OrderBookError> {
+        let _serialized = self
+            .execution
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let no_liquidity = OrderBookError::InsufficientLiquidity {
+            side,
+            requested: quantity,
+            available: 0,
+        };
+
+        let Some(quote) = self.implied(side) else {
+            return Err(no_liquidity);
+        };
+        let within_limit = limit_price.is_none_or(|limit| match side {
+            Side::Buy => quote.price <= limit,
+            Side::Sell => quote.price >= limit,
+        });
+        if !within_limit {
+            return Err(no_liquidity);
+        }
+
+        let quantity = quantity.min(quote.quantity);
+        trace!(
+            "Implied spread {}-{}: Executing {} {} at {}",
+            self.front.symbol(),
+            self.back.symbol(),
+            side,
+            quantity,
+            quote.price
+        );
+        let front =
+            self.front
+                .match_order(OrderId::new(), side, quantity, Some(quote.front_price))?;
+        let front_executed = quantity - front.remaining_quantity;
+        let back = if front_executed > 0 {
+            self.back.match_order(
+                OrderId::new(),
+                side.opposite(),
+                front_executed,
+                Some(quote.back_price),
+            )?
+        } else {
+            MatchResult::new(OrderId::new(), 0)
+        };
+        let back_executed = front_executed - back.remaining_quantity;
+
+        Ok(ImpliedExecution {
+            side,
+            price: quote.price,
+            quantity: back_executed,
+            front,
+            back,
+        })
+    }
+
+    /// Best implied quote for an aggressor buying (`Side::Buy`) or selling the spread
+    fn implied(&self, side: Side) -> Option<ImpliedQuote> {
+        let (front_price, back_price) = match side {
+            Side::Buy => (self.front.best_ask()?, self.back.best_bid()?),
+            Side::Sell => (self.front.best_bid()?, self.back.best_ask()?),
+        };
+        let front_quantity =
+            self.front
+                .peek_match_with_constraints(side, u64::MAX, Some(front_price));
+        let back_quantity =
+            self.back
+                .peek_match_with_constraints(side.opposite(), u64::MAX, Some(back_price));
+        let quantity = front_quantity.min(back_quantity);
+        (quantity > 0).then(|| ImpliedQuote {
+            price: front_price as i64 - back_price as i64,
+            quantity,
+            front_price,
+            back_price,
+        })
+    }
+}
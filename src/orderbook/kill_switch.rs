@@ -0,0 +1,186 @@
+//! Kill switch: stop an account, or the whole book, from trading at once.
+//!
+//! Throwing the switch makes the book refuse new orders within its scope with
+//! `OrderBookError::KillSwitchActive` and then cancels the resting orders the
+//! scope covers, reporting them to the caller. The book's switch also empties
+//! the dark segment and the special price section. The switch stays thrown until
+//! [`OrderBook::release_kill_switch`] is called.
+
+use super::book::OrderBook;
+use super::dark::DarkOrder;
+use super::error::OrderBookError;
+use super::special::SpecialPriceOrder;
+use pricelevel::OrderType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::trace;
+
+/// Orders a kill switch applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KillScope {
+    /// Every order of the book, with or without an account
+    Book,
+    /// Orders entered for one account
+    Account(String),
+}
+
+impl fmt::Display for KillScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillScope::Book => write!(f, "the whole book"),
+            KillScope::Account(account_id) => write!(f, "account {account_id}"),
+        }
+    }
+}
+
+/// What throwing a kill switch did
+#[derive(Debug, Clone)]
+pub struct KillReport<T> {
+    /// Scope of the switch
+    pub scope: KillScope,
+    /// Resting orders cancelled, in the order they were cancelled
+    pub cancelled: Vec<Arc<OrderType<T>>>,
+    /// Orders removed from the dark segment, in arrival order. Dark orders
+    /// have no account, so only the book's switch removes them
+    pub dark_cancelled: Vec<DarkOrder>,
+    /// Orders removed from the special price section, in arrival order. Like
+    /// dark orders, only the book's switch removes them
+    pub special_price_cancelled: Vec<SpecialPriceOrder>,
+    /// When the switch was thrown, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+/// Kill switches thrown on a book
+#[derive(Debug, Default)]
+pub(super) struct KillSwitches {
+    book: AtomicBool,
+    /// Set while any account is killed, so order entry skips the lock otherwise
+    any_account: AtomicBool,
+    accounts: RwLock<HashSet<String>>,
+}
+
+impl KillSwitches {
+    fn covers(&self, account_id: Option<&str>) -> bool {
+        // Sequentially consistent with the write tracker, so a writer either
+        // sees the switch thrown or is waited for by the one throwing it
+        if self.book.load(Ordering::SeqCst) {
+            return true;
+        }
+        let Some(account_id) = account_id else {
+            return false;
+        };
+        self.any_account.load(Ordering::SeqCst)
+            && self
+                .accounts
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .contains(account_id)
+    }
+
+    /// Throw or release the switch of `scope`, returning false if it already
+    /// was in that position
+    fn set(&self, scope: &KillScope, thrown: bool) -> bool {
+        match scope {
+            KillScope::Book => self.book.swap(thrown, Ordering::SeqCst) != thrown,
+            KillScope::Account(account_id) => {
+                let mut accounts = self
+                    .accounts
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let changed = if thrown {
+                    accounts.insert(account_id.clone())
+                } else {
+                    accounts.remove(account_id)
+                };
+                self.any_account
+                    .store(!accounts.is_empty(), Ordering::SeqCst);
+                changed
+            }
+        }
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Refuse new orders within `scope` from now on and cancel its resting
+    /// orders, returning them.
+    ///
+    /// Changes to the book in progress when the switch is thrown are waited
+    /// for before sweeping, so an order already past its entry checks is
+    /// cancelled once it rests. Thrown from within such a change, as by a
+    /// listener, the switch cannot wait for it and the order may come to rest
+    /// after the sweep; throwing the switch again sweeps the scope again.
+    pub fn kill_switch(&self, scope: KillScope) -> KillReport<T> {
+        self.kill_switches.set(&scope, true);
+        if !self.writes.fence() {
+            trace!(
+                "Order book {}: Kill switch for {} swept with a change in progress",
+                self.symbol, scope
+            );
+        }
+        let timestamp = self.now();
+        let (cancelled, dark_cancelled, special_price_cancelled) = match &scope {
+            KillScope::Book => (
+                self.cancel_all(),
+                self.cancel_all_dark_orders(),
+                self.cancel_all_special_price_orders(),
+            ),
+            KillScope::Account(account_id) => (
+                self.cancel_all_for_account(account_id),
+                Vec::new(),
+                Vec::new(),
+            ),
+        };
+        trace!(
+            "Order book {}: Kill switch thrown for {}, cancelling {} orders",
+            self.symbol,
+            scope,
+            cancelled.len() + dark_cancelled.len() + special_price_cancelled.len()
+        );
+        KillReport {
+            scope,
+            cancelled,
+            dark_cancelled,
+            special_price_cancelled,
+            timestamp,
+        }
+    }
+
+    /// Accept orders within `scope` again, returning false if its switch was
+    /// not thrown. Releasing the book's switch leaves account switches thrown
+    pub fn release_kill_switch(&self, scope: &KillScope) -> bool {
+        let released = self.kill_switches.set(scope, false);
+        if released {
+            trace!(
+                "Order book {}: Kill switch released for {}",
+                self.symbol, scope
+            );
+        }
+        released
+    }
+
+    /// Returns true if a kill switch refuses orders entered for `account_id`,
+    /// or for no account with `None`
+    pub fn is_killed(&self, account_id: Option<&str>) -> bool {
+        self.kill_switches.covers(account_id)
+    }
+
+    /// Refuse an order entered for `account_id` if a kill switch covers it
+    pub(super) fn check_kill_switch(&self, account_id: Option<&str>) -> Result<(), OrderBookError> {
+        if !self.kill_switches.covers(account_id) {
+            return Ok(());
+        }
+        Err(OrderBookError::KillSwitchActive {
+            account_id: if self.kill_switches.book.load(Ordering::Acquire) {
+                None
+            } else {
+                account_id.map(str::to_string)
+            },
+        })
+    }
+}
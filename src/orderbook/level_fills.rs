@@ -0,0 +1,83 @@
+//! Per-level totals of a match: executed quantity and notional at each price
+//! an incoming order traded at.
+//!
+//! [`MatchResult`] lists every fill, so a sweep through deep levels leaves
+//! consumers summing thousands of transactions back into a handful of
+//! prices. [`OrderBook::match_order_aggregated`] sums them as it walks each
+//! level and returns the totals with the result, so nothing is kept in the
+//! book once the match returns.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::matching::MatchContext;
+use pricelevel::{MatchResult, OrderId, Side, Transaction};
+use serde::{Deserialize, Serialize};
+
+/// What an incoming order executed at one price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelFill {
+    /// Price the trades were made at
+    pub price: u64,
+    /// Quantity executed at this price
+    pub quantity: u64,
+    /// Price times quantity, in integer price units
+    pub notional: u128,
+    /// Number of trades at this price
+    pub trades: usize,
+}
+
+impl LevelFill {
+    /// Totals of trades made at a single price
+    pub(super) fn from_transactions(price: u64, transactions: &[Transaction]) -> Self {
+        let quantity = transactions
+            .iter()
+            .map(|transaction| transaction.quantity)
+            .sum();
+        Self {
+            price,
+            quantity,
+            notional: u128::from(price) * u128::from(quantity),
+            trades: transactions.len(),
+        }
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Match an incoming order like [`match_order`](Self::match_order), also
+    /// returning what it executed at each price level, best price first.
+    /// The totals are empty if the match made no trade
+    pub fn match_order_aggregated(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+    ) -> Result<(MatchResult, Vec<LevelFill>), OrderBookError> {
+        if let Some(storage) = self.compact_storage() {
+            let match_result =
+                self.match_compact_order(storage, order_id, side, quantity, limit_price);
+            let level_fills = match_result
+                .transactions
+                .as_vec()
+                .chunk_by(|a, b| a.price == b.price)
+                .map(|trades| LevelFill::from_transactions(trades[0].price, trades))
+                .collect();
+            return Ok((match_result, level_fills));
+        }
+        let context = MatchContext {
+            aggregate_levels: true,
+            ..MatchContext::default()
+        };
+        self.match_order_at(
+            order_id,
+            side,
+            quantity,
+            limit_price,
+            &self.options(),
+            context,
+        )
+    }
+}
@@ -0,0 +1,190 @@
+//! Ordered iteration over the price levels of a book, summarized one at a time,
+//! and over the orders of one level, page by page or one order at a time
+
+use super::book::{OrderBook, order_from_unit_type};
+use super::side::BookSide;
+use pricelevel::{OrderType, PriceLevel, Side};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::vec;
+
+/// Aggregate state of one price level, without its orders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelSummary {
+    /// Quantity displayed at the level
+    pub visible_quantity: u64,
+    /// Quantity resting at the level but not displayed, e.g. iceberg reserves
+    pub hidden_quantity: u64,
+    /// Number of orders resting at the level
+    pub order_count: usize,
+}
+
+impl LevelSummary {
+    pub(super) fn of(price_level: &PriceLevel) -> Self {
+        Self {
+            visible_quantity: price_level.visible_quantity(),
+            hidden_quantity: price_level.hidden_quantity(),
+            order_count: price_level.order_count(),
+        }
+    }
+
+    /// Visible and hidden quantity of the level
+    pub fn total_quantity(&self) -> u64 {
+        self.visible_quantity + self.hidden_quantity
+    }
+}
+
+/// Iterator over `(price, summary)` of the levels of one side, best price first.
+///
+/// The prices are captured when the iterator is created and each level is
+/// summarized only when it is reached, so stopping early costs nothing for
+/// the remaining levels. A level emptied in the meantime is skipped, and
+/// levels created in the meantime are not visited.
+pub struct LevelIter<'a> {
+    levels: &'a BookSide,
+    prices: vec::IntoIter<u64>,
+}
+
+impl Iterator for LevelIter<'_> {
+    type Item = (u64, LevelSummary);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.prices.find_map(|price| {
+            self.levels
+                .get(&price)
+                .map(|price_level| (price, LevelSummary::of(&price_level)))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.prices.len()))
+    }
+}
+
+/// Iterator over the orders resting at one price level, in priority order.
+///
+/// The level's queue is captured when the iterator is created, sharing the
+/// orders rather than copying them, and each order is converted only when it
+/// is reached. Orders added or removed in the meantime are not reflected.
+pub struct OrderIter<T> {
+    orders: vec::IntoIter<Arc<OrderType<()>>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Default> Iterator for OrderIter<T> {
+    type Item = Arc<OrderType<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.orders
+            .next()
+            .map(|order| Arc::new(order_from_unit_type(&order)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.orders.size_hint()
+    }
+}
+
+impl<T: Default> ExactSizeIterator for OrderIter<T> {}
+
+/// A page of the orders resting at one price level, in priority order
+#[derive(Debug, Clone)]
+pub struct OrderPage<T> {
+    /// Orders of the page
+    pub orders: Vec<Arc<OrderType<T>>>,
+    /// Cursor to pass for the next page, or `None` if this page is the last.
+    /// It is the acceptance sequence of the page's last order, so it stays
+    /// valid after that order leaves the book
+    pub next_cursor: Option<u64>,
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Orders at `price` on `side`, in priority order, converted one at a time
+    /// as the iterator is advanced. Empty if there is no level at the price
+    pub fn iter_orders_at_price(&self, price: u64, side: Side) -> OrderIter<T> {
+        if let Some(storage) = self.compact_storage() {
+            return OrderIter {
+                orders: OrderBook::<()>::get_compact_orders_at_price(&storage, price, side)
+                    .into_iter(),
+                _phantom: PhantomData,
+            };
+        }
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let orders = price_levels
+            .get(&price)
+            .map(|price_level| self.queued_orders(&price_level))
+            .unwrap_or_default();
+        OrderIter {
+            orders: orders.into_iter(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Up to `limit` orders at `price` on `side`, in priority order, starting
+    /// after the order whose acceptance sequence is `cursor`, or at the front
+    /// of the queue for `None`.
+    ///
+    /// Pass the returned `next_cursor` to get the following page. Orders are
+    /// queued by acceptance sequence, so paging goes on where it left off
+    /// even if the orders already returned have since been filled or
+    /// cancelled. Orders that lose their priority to a price change re-join
+    /// at the back of the queue and may be returned again.
+    pub fn get_orders_at_price_page(
+        &self,
+        price: u64,
+        side: Side,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> OrderPage<T> {
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let queue = price_levels
+            .get(&price)
+            .map(|price_level| self.queued_orders(&price_level))
+            .unwrap_or_default();
+        // Orders without a sequence are leaving the book
+        let mut remaining = queue
+            .into_iter()
+            .filter_map(|order| Some((self.order_sequence(order.id())?, order)))
+            .skip_while(|(sequence, _)| cursor.is_some_and(|cursor| *sequence <= cursor));
+        let page: Vec<(u64, Arc<OrderType<()>>)> = remaining.by_ref().take(limit).collect();
+        let next_cursor = match (page.last(), remaining.next()) {
+            (Some((sequence, _)), Some(_)) => Some(*sequence),
+            _ => None,
+        };
+        OrderPage {
+            orders: page
+                .iter()
+                .map(|(_, order)| Arc::new(order_from_unit_type(order)))
+                .collect(),
+            next_cursor,
+        }
+    }
+
+    /// Bid levels from the highest price down, summarized lazily
+    pub fn iter_bids(&self) -> LevelIter<'_> {
+        let prices = self.bids.best_prices(usize::MAX);
+        LevelIter {
+            levels: &self.bids,
+            prices: prices.into_iter(),
+        }
+    }
+
+    /// Ask levels from the lowest price up, summarized lazily
+    pub fn iter_asks(&self) -> LevelIter<'_> {
+        let prices = self.asks.best_prices(usize::MAX);
+        LevelIter {
+            levels: &self.asks,
+            prices: prices.into_iter(),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Management of several order books, one per symbol
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::registry::SymbolRegistry;
+use super::shard::{ShardExecutor, SharedBooks};
+use super::snapshot::OrderBookSnapshot;
+use crate::utils::current_time_millis;
+use dashmap::DashMap;
+use pricelevel::OrderType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use tracing::trace;
+
+/// A snapshot of one book together with the version it was taken at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedSnapshot {
+    /// Version of the book when the snapshot was taken
+    pub version: u64,
+    /// The snapshot itself
+    pub snapshot: OrderBookSnapshot,
+}
+
+/// Snapshots of several books captured at the same coordination point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiBookSnapshot {
+    /// Timestamp of the coordination point (milliseconds since epoch)
+    pub timestamp: u64,
+    /// Snapshots keyed by symbol
+    pub books: BTreeMap<String, VersionedSnapshot>,
+}
+
+impl MultiBookSnapshot {
+    /// Get the snapshot of a symbol, if it was captured
+    pub fn get(&self, symbol: &str) -> Option<&OrderBookSnapshot> {
+        self.books.get(symbol).map(|book| &book.snapshot)
+    }
+}
+
+/// Holds one order book per symbol.
+///
+/// Changes made through [`with_book`](Self::with_book) run concurrently with
+/// each other but never overlap with [`snapshot_all`](Self::snapshot_all), which
+/// gives snapshots across symbols a single point in time they all reflect.
+///
+/// Books created by the manager take their price scale and round lot from
+/// the reference data of their symbol in the manager's [`SymbolRegistry`],
+/// and orders entered through [`add_order`](Self::add_order) are checked
+/// against it.
+///
+/// [`start_executor`](Self::start_executor) moves matching onto one thread
+/// per shard of symbols instead of the calling threads.
+pub struct OrderBookManager<T = ()> {
+    books: SharedBooks<T>,
+    registry: Arc<SymbolRegistry>,
+    /// Shared by writers going through the manager, held exclusively while snapshotting
+    coordination: Arc<RwLock<()>>,
+}
+
+impl<T> Default for OrderBookManager<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OrderBookManager<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create an empty manager
+    pub fn new() -> Self {
+        Self::with_registry(Arc::new(SymbolRegistry::new()))
+    }
+
+    /// Create an empty manager taking reference data from `registry`, which
+    /// may be shared with other managers
+    pub fn with_registry(registry: Arc<SymbolRegistry>) -> Self {
+        Self {
+            books: Arc::new(DashMap::new()),
+            registry,
+            coordination: Arc::new(RwLock::new(())),
+        }
+    }
+
+    /// The reference data consulted by this manager
+    pub fn registry(&self) -> &Arc<SymbolRegistry> {
+        &self.registry
+    }
+
+    /// Create a book for `symbol`, or return the existing one.
+    ///
+    /// A new book gets the price scale and round lot of the symbol's
+    /// reference data, if it has any, and tracks its notional in the
+    /// symbol's currency.
+    pub fn add_book(&self, symbol: &str) -> Arc<OrderBook<T>> {
+        self.books
+            .entry(symbol.to_string())
+            .or_insert_with(|| {
+                let mut book = OrderBook::new(symbol);
+                if let Some(info) = self.registry.get(symbol) {
+                    book.set_price_scale(info.price_scale());
+                    book.set_round_lot(info.lot_size);
+                    book.track_notional(&info);
+                }
+                Arc::new(book)
+            })
+            .clone()
+    }
+
+    /// Add `order` to the book for `symbol` once it passes the symbol's
+    /// reference data checks, never concurrently with `snapshot_all`
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `symbol` is not managed,
+    /// the errors of [`SymbolRegistry::validate`], and those of
+    /// [`OrderBook::add_order`].
+    pub fn add_order(
+        &self,
+        symbol: &str,
+        order: OrderType<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let quantity = order.visible_quantity() + order.hidden_quantity();
+        self.registry.validate(symbol, order.price(), quantity)?;
+        self.with_book(symbol, |book| book.add_order(order))
+            .unwrap_or_else(|| {
+                Err(OrderBookError::InvalidOperation {
+                    message: format!("No order book for symbol {symbol}"),
+                })
+            })
+    }
+
+    /// Notional of `quantity` of `symbol` at the integer `price`, in integer
+    /// price units scaled by the contract multiplier. `None` without
+    /// reference data for the symbol.
+    pub fn notional(&self, symbol: &str, price: u64, quantity: u64) -> Option<u128> {
+        self.registry
+            .get(symbol)
+            .map(|info| info.notional(price, quantity))
+    }
+
+    /// Register an existing book under its own symbol, replacing any previous one
+    pub fn insert_book(&self, book: Arc<OrderBook<T>>) -> Option<Arc<OrderBook<T>>> {
+        self.books.insert(book.symbol().to_string(), book)
+    }
+
+    /// Get the book for `symbol`
+    pub fn get_book(&self, symbol: &str) -> Option<Arc<OrderBook<T>>> {
+        self.books.get(symbol).map(|book| Arc::clone(&book))
+    }
+
+    /// Remove the book for `symbol`, returning it
+    pub fn remove_book(&self, symbol: &str) -> Option<Arc<OrderBook<T>>> {
+        self.books.remove(symbol).map(|(_, book)| book)
+    }
+
+    /// All managed symbols, sorted
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.books.iter().map(|book| book.key().clone()).collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Number of managed books
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    /// Returns true if no book is managed
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// Run `f` against the book for `symbol`, never concurrently with `snapshot_all`.
+    /// Returns `None` if there is no such book.
+    pub fn with_book<R>(&self, symbol: &str, f: impl FnOnce(&OrderBook<T>) -> R) -> Option<R> {
+        let book = self.get_book(symbol)?;
+        let _guard = self
+            .coordination
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Some(f(&book))
+    }
+
+    /// Start an executor running the work of each managed symbol on one of
+    /// `shards` dedicated threads, chosen by hashing the symbol, each
+    /// queueing up to `capacity` requests. Books added later are reachable
+    /// through it as well.
+    ///
+    /// # Panics
+    /// Panics if `shards` or `capacity` is zero, or a thread cannot be spawned.
+    pub fn start_executor(&self, shards: usize, capacity: usize) -> ShardExecutor<T> {
+        ShardExecutor::start(
+            Arc::clone(&self.books),
+            Arc::clone(&self.coordination),
+            shards,
+            capacity,
+        )
+    }
+
+    /// Capture snapshots of `symbols` (all books if empty) up to `depth` levels
+    /// that reflect a single point in time.
+    ///
+    /// Writers going through [`with_book`](Self::with_book) are held off while the
+    /// snapshots are taken. Each book is snapshotted with
+    /// [`OrderBook::consistent_snapshot`], so changes made directly on a book
+    /// are detected and the book is read again.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if a symbol is not managed or a
+    /// book keeps changing while being read.
+    pub fn snapshot_all(
+        &self,
+        symbols: &[&str],
+        depth: usize,
+    ) -> Result<MultiBookSnapshot, OrderBookError> {
+        let selected: Vec<String> = if symbols.is_empty() {
+            self.symbols()
+        } else {
+            symbols.iter().map(|symbol| symbol.to_string()).collect()
+        };
+        let books = selected
+            .into_iter()
+            .map(|symbol| match self.get_book(&symbol) {
+                Some(book) => Ok((symbol, book)),
+                None => Err(OrderBookError::InvalidOperation {
+                    message: format!("No order book for symbol {symbol}"),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let _guard = self
+            .coordination
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let timestamp = current_time_millis();
+
+        let mut snapshots = BTreeMap::new();
+        for (symbol, book) in books {
+            let snapshot = book.consistent_snapshot(depth)?;
+            snapshots.insert(symbol, snapshot);
+        }
+        trace!("Captured consistent snapshots of {} books", snapshots.len());
+
+        Ok(MultiBookSnapshot {
+            timestamp,
+            books: snapshots,
+        })
+    }
+}
@@ -1,9 +1,58 @@
 //! Contains the core matching engine logic for the order book.
 
+use crate::orderbook::error::LevelOperation;
+use crate::orderbook::events::OrderBookEvent;
+use crate::orderbook::execution_report::OrderStatus;
+use crate::orderbook::level_fills::LevelFill;
+use crate::orderbook::memory::StructureMemory;
+#[cfg(feature = "metrics")]
+use crate::orderbook::metrics::Operation;
+use crate::orderbook::modifications::OrderQuantity;
+use crate::orderbook::options::{ReserveRefresh, VersionedOptions};
 use crate::orderbook::pool::MatchingPool;
+use crate::orderbook::trade::{TradeCondition, TradeConditions, TradeReport};
 use crate::{OrderBook, OrderBookError};
-use pricelevel::{MatchResult, OrderId, Side};
+use pricelevel::{MatchResult, OrderId, OrderType, OrderUpdate, PriceLevel, Side, Transaction};
+use std::collections::VecDeque;
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use tracing::trace;
+
+/// How the trades of a match execute, beyond what the incoming order says
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct MatchContext<'a> {
+    /// Price every trade executes at instead of the maker's, as in an auction
+    pub(super) execution_price: Option<u64>,
+    /// Account the incoming order was entered for, to flag trades against
+    /// the account's own resting orders
+    pub(super) account_id: Option<&'a str>,
+    /// Sum the trades made at each price level into a [`LevelFill`]
+    pub(super) aggregate_levels: bool,
+}
+
+/// A maker left with quantity after a fill, as it rests again
+pub(super) enum Resting {
+    /// Kept in its place in its level's queue
+    InPlace(OrderType<()>),
+    /// Refreshed from its hidden quantity and put at the back of its level's queue
+    Back(OrderType<()>),
+}
+
+// Use static memory pool for better performance
+thread_local! {
+    static MATCHING_POOL: MatchingPool = MatchingPool::new();
+}
+
+/// Release the vectors pooled for matching on the current thread
+pub(super) fn clear_matching_pool() {
+    MATCHING_POOL.with(MatchingPool::clear);
+}
+
+/// Vectors pooled for matching on the current thread and the bytes they take
+pub(super) fn matching_pool_memory() -> StructureMemory {
+    let (entries, bytes) = MATCHING_POOL.with(MatchingPool::retained);
+    StructureMemory { entries, bytes }
+}
 
 impl<T> OrderBook<T>
 where
@@ -17,9 +66,87 @@ where
         quantity: u64,
         limit_price: Option<u64>,
     ) -> Result<MatchResult, OrderBookError> {
-        self.cache.invalidate();
+        if let Some(storage) = self.compact_storage() {
+            return Ok(self.match_compact_order(storage, order_id, side, quantity, limit_price));
+        }
+        self.match_order_under(order_id, side, quantity, limit_price, &self.options(), None)
+    }
+
+    /// Tighten the limit price of an aggressive order on `side` so it cannot
+    /// sweep more levels than the maximum match depth of `options` allows.
+    /// The deepest reachable level is the last one swept
+    pub(super) fn depth_capped_limit(
+        &self,
+        side: Side,
+        limit_price: Option<u64>,
+        options: &VersionedOptions,
+    ) -> Option<u64> {
+        let Some(depth) = options.options.max_match_depth else {
+            return limit_price;
+        };
+        let match_side = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
+        let Some(deepest) = match_side.nth_best_price(depth.max_levels.max(1)) else {
+            return limit_price;
+        };
+        Some(match (side, limit_price) {
+            (_, None) => deepest,
+            (Side::Buy, Some(limit)) => limit.min(deepest),
+            (Side::Sell, Some(limit)) => limit.max(deepest),
+        })
+    }
+
+    /// Hand the vectors of a match result back to the current thread's
+    /// matching pool once done with it, so later matches on this thread build
+    /// their results without allocating
+    pub fn recycle_match_result(&self, result: MatchResult) {
+        let buffers = (result.transactions.into_vec(), result.filled_order_ids);
+        MATCHING_POOL.with(|pool| pool.return_result_buffers(buffers, &self.pool_config));
+    }
+
+    /// Match an incoming order entered for `account_id` under the given
+    /// options, which the caller read when its operation started
+    pub(super) fn match_order_under(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+        options: &VersionedOptions,
+        account_id: Option<&str>,
+    ) -> Result<MatchResult, OrderBookError> {
+        let context = MatchContext {
+            account_id,
+            ..MatchContext::default()
+        };
+        self.match_order_at(order_id, side, quantity, limit_price, options, context)
+            .map(|(match_result, _)| match_result)
+    }
+
+    /// Match an incoming order in the given context, executing every trade at
+    /// its execution price instead of the maker's price when it has one, as an
+    /// auction does. The per-level totals are only collected if the context
+    /// asks for them
+    pub(super) fn match_order_at(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        quantity: u64,
+        limit_price: Option<u64>,
+        options: &VersionedOptions,
+        context: MatchContext<'_>,
+    ) -> Result<(MatchResult, Vec<LevelFill>), OrderBookError> {
+        let execution_price = context.execution_price;
+        #[cfg(feature = "metrics")]
+        let _timer = self.metrics.timer(Operation::Match);
+        let _write = self.writes.begin();
         let mut match_result = MatchResult::new(order_id, quantity);
         let mut remaining_quantity = quantity;
+        let mut fills = Vec::new();
+        let mut reports = Vec::new();
+        let mut level_fills = Vec::new();
 
         // Choose the appropriate side for matching
         let match_side = match side {
@@ -37,34 +164,28 @@ where
                 });
             }
             match_result.remaining_quantity = remaining_quantity;
-            return Ok(match_result);
-        }
-
-        // Use static memory pool for better performance
-        thread_local! {
-            static MATCHING_POOL: MatchingPool = MatchingPool::new();
+            return Ok((match_result, level_fills));
         }
 
         // Get reusable vectors from pool
-        let (mut filled_orders, mut empty_price_levels, mut sorted_prices) =
-            MATCHING_POOL.with(|pool| {
-                let filled = pool.get_filled_orders_vec();
-                let empty = pool.get_price_vec();
-                let prices = pool.get_price_vec();
-                (filled, empty, prices)
-            });
-
-        // Collect and sort prices efficiently
-        sorted_prices.extend(match_side.iter().map(|item| *item.key()));
-
-        if side == Side::Buy {
-            sorted_prices.sort_unstable(); // Ascending for asks
-        } else {
-            sorted_prices.sort_unstable_by(|a, b| b.cmp(a)); // Descending for bids
-        }
+        let (mut filled_orders, mut empty_price_levels) = MATCHING_POOL.with(|pool| {
+            let filled = pool.get_filled_orders_vec(&self.pool_config, &self.pool_counters);
+            let empty = pool.get_price_vec(&self.pool_config, &self.pool_counters);
+            (filled, empty)
+        });
+        let checked_out_capacities = (filled_orders.capacity(), empty_price_levels.capacity());
+        // Build the result in recycled buffers, if the caller handed any back
+        (
+            match_result.transactions.transactions,
+            match_result.filled_order_ids,
+        ) = MATCHING_POOL.with(MatchingPool::get_result_buffers);
+
+        // Walk the levels best price first through the price index. Emptied
+        // levels are only removed after the walk, so the cursor stays valid
+        let mut cursor = None;
+        while let Some(price) = match_side.next_after(cursor) {
+            cursor = Some(price);
 
-        // Process each price level
-        for &price in &sorted_prices {
             // Check price limit constraint early
             if let Some(limit) = limit_price {
                 match side {
@@ -80,26 +201,99 @@ where
                 None => continue,
             };
 
-            // Perform the match at this price level
-            let price_level_match = {
+            // Perform the match at this price level. A level whose queue holds its
+            // orders in acceptance order, none of them constrained, iceberg or
+            // reserve, stays on the price level's own FIFO matching.
+            let mut price_level_match = {
                 let price_level = &mut *price_level_entry;
-                price_level.match_order(
-                    remaining_quantity,
-                    order_id,
-                    &self.transaction_id_generator,
-                )
+                if match_side.is_fifo(price) {
+                    let level_match = price_level.match_order(
+                        remaining_quantity,
+                        order_id,
+                        &self.transaction_id_generator,
+                    );
+                    // The level sends a partially filled maker to the back of its
+                    // queue, though the maker keeps its priority
+                    if let Some(last) = level_match.transactions.as_vec().last()
+                        && !level_match.filled_order_ids.contains(&last.maker_order_id)
+                        && price_level.order_count() > 1
+                    {
+                        self.mark_irregular(last.maker_order_id, side.opposite(), price);
+                    }
+                    level_match
+                } else {
+                    self.match_level_with_constraints(
+                        price_level,
+                        order_id,
+                        side,
+                        remaining_quantity,
+                        options,
+                    )
+                }
             };
 
+            // The level stamps trades with the system time; they take the
+            // book's clock, like the orders they execute
+            let timestamp = self.now();
+            for transaction in &mut price_level_match.transactions.transactions {
+                transaction.timestamp = timestamp;
+                if let Some(execution_price) = execution_price {
+                    transaction.price = execution_price;
+                }
+            }
+
             // Process transactions if any occurred
             if !price_level_match.transactions.as_vec().is_empty() {
                 // Update last trade price atomically
-                self.last_trade_price.store(price, Ordering::Relaxed);
+                self.last_trade_price
+                    .store(execution_price.unwrap_or(price), Ordering::Relaxed);
                 self.has_traded.store(true, Ordering::Relaxed);
+                let mut is_opening_trade = !self.session_traded.swap(true, Ordering::Relaxed);
+                let round_lot = options.options.round_lot;
+                let fee_schedule = options.options.fee_schedule;
 
                 // Add transactions to result
                 for transaction in price_level_match.transactions.as_vec() {
+                    if self.publishes_events() {
+                        let conditions = self.trade_conditions(
+                            transaction,
+                            std::mem::take(&mut is_opening_trade),
+                            round_lot,
+                            context,
+                        );
+                        reports.push(TradeReport {
+                            transaction: *transaction,
+                            conditions,
+                            fees: fee_schedule
+                                .map(|fee_schedule| fee_schedule.fees_for(transaction)),
+                        });
+                    }
+                    self.executions.record_fill(
+                        transaction.maker_order_id,
+                        transaction.price,
+                        transaction.quantity,
+                    );
                     match_result.add_transaction(*transaction);
                 }
+                if let Some(tape) = &self.trade_tape {
+                    tape.record(price_level_match.transactions.as_vec());
+                }
+                if context.aggregate_levels {
+                    level_fills.push(LevelFill::from_transactions(
+                        execution_price.unwrap_or(price),
+                        price_level_match.transactions.as_vec(),
+                    ));
+                }
+
+                if self.publishes_events() {
+                    fills.extend(self.level_fill_notifications(
+                        &price_level_entry,
+                        price_level_match.transactions.as_vec(),
+                        quantity,
+                        price_level_match.remaining_quantity,
+                        fee_schedule,
+                    ));
+                }
             }
 
             // Collect filled orders for batch removal
@@ -110,6 +304,8 @@ where
 
             // Update remaining quantity
             remaining_quantity = price_level_match.remaining_quantity;
+            // Copied into the match result, the level's own result builds a later one
+            self.recycle_match_result(price_level_match);
 
             // Check if price level is empty and mark for removal
             if price_level_entry.order_count() == 0 {
@@ -125,23 +321,60 @@ where
             }
         }
 
-        // Batch remove empty price levels
-        for price in &empty_price_levels {
-            match_side.remove(price);
-        }
+        // Batch remove empty price levels, invalidating the cache once for the sweep
+        self.remove_levels_if_empty(side.opposite(), &empty_price_levels);
+
+        // Counted while the filled makers are still indexed by account
+        self.record_trade_notional(match_result.transactions.as_vec());
 
         // Batch remove filled orders from tracking
         for order_id in &filled_orders {
-            self.order_locations.remove(order_id);
+            self.forget_order(*order_id);
+            self.finish_order(*order_id, OrderStatus::Filled);
         }
 
         // Return vectors to pool for reuse
         MATCHING_POOL.with(|pool| {
-            pool.return_filled_orders_vec(filled_orders);
-            pool.return_price_vec(empty_price_levels);
-            pool.return_price_vec(sorted_prices);
+            pool.return_filled_orders_vec(
+                filled_orders,
+                checked_out_capacities.0,
+                &self.pool_config,
+                &self.pool_counters,
+            );
+            pool.return_price_vec(
+                empty_price_levels,
+                checked_out_capacities.1,
+                &self.pool_config,
+                &self.pool_counters,
+            );
         });
 
+        let trades = match_result.transactions.as_vec();
+        if !trades.is_empty() {
+            self.stats.record_match(
+                trades.len(),
+                trades.iter().map(|trade| trade.quantity).sum(),
+            );
+            self.bump_version();
+        }
+        self.update_memory_pressure();
+
+        // Published once the levels are released, so listeners may read the book
+        for report in reports {
+            self.emit_event(&OrderBookEvent::TradeExecuted(report));
+        }
+        for fill in fills {
+            self.emit_event(&OrderBookEvent::OrderFilled(fill));
+        }
+        // Auction uncrosses trade at one price by design and are not sweeps
+        let executed = quantity - remaining_quantity;
+        if executed > 0 && execution_price.is_none() {
+            self.record_sweep(side.opposite(), executed, &options.options);
+        }
+        if executed > 0 {
+            self.record_quote_fills(match_result.transactions.as_vec(), &options.options);
+        }
+
         // Check for insufficient liquidity in market orders
         if limit_price.is_none() && remaining_quantity == quantity {
             return Err(OrderBookError::InsufficientLiquidity {
@@ -155,40 +388,333 @@ where
         match_result.remaining_quantity = remaining_quantity;
         match_result.is_complete = remaining_quantity == 0;
 
-        Ok(match_result)
+        Ok((match_result, level_fills))
     }
 
-    /// Optimized peek match with memory pooling
-    pub fn peek_match(&self, side: Side, quantity: u64, price_limit: Option<u64>) -> u64 {
-        let price_levels = match side {
+    /// Conditions of a trade made by matching an incoming order in `context`
+    fn trade_conditions(
+        &self,
+        transaction: &Transaction,
+        is_opening_trade: bool,
+        round_lot: u64,
+        context: MatchContext<'_>,
+    ) -> TradeConditions {
+        let mut conditions = TradeConditions::none();
+        if is_opening_trade {
+            conditions.insert(TradeCondition::OpeningTrade);
+        }
+        if context.execution_price.is_some() {
+            conditions.insert(TradeCondition::AuctionCross);
+        }
+        if let Some(account_id) = context.account_id
+            && self
+                .order_accounts
+                .get(&transaction.maker_order_id)
+                .is_some_and(|maker_account| *maker_account == account_id)
+        {
+            conditions.insert(TradeCondition::InternalCross);
+        }
+        if transaction.quantity < round_lot {
+            conditions.insert(TradeCondition::OddLot);
+        }
+        conditions
+    }
+
+    /// Matches against a single price level order by order, in acceptance
+    /// order, skipping makers whose execution constraints cannot be satisfied
+    /// by the remaining quantity and refreshing iceberg and reserve makers as
+    /// they trade.
+    ///
+    /// The returned `MatchResult` has the same shape as `PriceLevel::match_order`:
+    /// the transactions executed at this level, the makers that were fully
+    /// filled and the taker quantity left over.
+    fn match_level_with_constraints(
+        &self,
+        price_level: &PriceLevel,
+        taker_order_id: OrderId,
+        taker_side: Side,
+        quantity: u64,
+        options: &VersionedOptions,
+    ) -> MatchResult {
+        let mut level_match = MatchResult::new(taker_order_id, quantity);
+        let mut remaining_quantity = quantity;
+
+        // The level is sorted once, then kept sorted from one match to the next
+        // while nothing else changes it. Makers stay in the walk as they rest
+        // again, in their place or at the back
+        let maker_side = match taker_side {
             Side::Buy => &self.asks,
             Side::Sell => &self.bids,
         };
+        let mut queue = VecDeque::from(
+            maker_side
+                .take_queue(price_level.price())
+                .unwrap_or_else(|| self.queued_orders(price_level)),
+        );
+        let mut index = 0;
+        while remaining_quantity > 0
+            && let Some(maker) = queue.get(index).cloned()
+        {
+            let maker_id = maker.id();
+            if let Some(constraints) = self.order_constraints.get(&maker_id)
+                && !constraints.allows_execution(maker.total_quantity(), remaining_quantity)
+            {
+                // Leave the maker in place, keeping its priority, and keep walking the queue
+                index += 1;
+                continue;
+            }
 
-        if price_levels.is_empty() {
-            return 0;
+            let (consumed, fully_filled, remaining, resting) = match self.fill_resting_order(
+                price_level,
+                taker_side.opposite(),
+                &maker,
+                remaining_quantity,
+                options,
+            ) {
+                Ok((0, ..)) => {
+                    index += 1;
+                    continue;
+                }
+                Ok(fill) => fill,
+                // Other fills of this match may already have executed, so the failure
+                // cannot be returned: report it and leave the maker untouched
+                Err(error) => {
+                    trace!(
+                        "Order book {}: Failed to apply fill to maker {}, skipping it: {}",
+                        self.symbol, maker_id, error
+                    );
+                    self.emit_event(&OrderBookEvent::PriceLevelFault(error));
+                    index += 1;
+                    continue;
+                }
+            };
+
+            if fully_filled {
+                level_match.add_filled_order_id(maker_id);
+            }
+            level_match.add_transaction(Transaction::new(
+                self.transaction_id_generator.next(),
+                taker_order_id,
+                maker_id,
+                price_level.price(),
+                consumed,
+                taker_side,
+            ));
+            remaining_quantity = remaining;
+            match resting {
+                Some(Resting::InPlace(order)) => queue[index] = Arc::new(order),
+                Some(Resting::Back(order)) => {
+                    queue.remove(index);
+                    queue.push_back(Arc::new(order));
+                }
+                None => {
+                    queue.remove(index);
+                }
+            }
         }
+        maker_side.keep_queue(price_level.price(), Vec::from(queue));
+
+        level_match.remaining_quantity = remaining_quantity;
+        level_match.is_complete = remaining_quantity == 0;
+        level_match
+    }
+
+    /// Executes up to `quantity` against the resting `maker` of `price_level`,
+    /// returning the quantity consumed, whether the maker was filled in full,
+    /// the quantity left over and the maker as it rests again if it was not.
+    ///
+    /// A maker partially filled from its visible quantity keeps its priority;
+    /// one refreshed from its hidden quantity goes to the back of the queue,
+    /// unless it is a reserve order and `options`, those of the whole match,
+    /// keep its place. A reserve order that auto-replenishes is refreshed as
+    /// soon as its visible quantity falls below its replenish threshold.
+    pub(super) fn fill_resting_order(
+        &self,
+        price_level: &PriceLevel,
+        maker_side: Side,
+        maker: &OrderType<()>,
+        quantity: u64,
+        options: &VersionedOptions,
+    ) -> Result<(u64, bool, u64, Option<Resting>), OrderBookError> {
+        let maker_id = maker.id();
+        let (consumed, updated_maker, hidden_reduced, remaining) = match maker {
+            // Replenished below by the book's own rules rather than the level's
+            OrderType::ReserveOrder {
+                auto_replenish: true,
+                ..
+            } => match_visible_slice(maker, quantity),
+            _ => maker.match_against(quantity),
+        };
+        if consumed == 0 {
+            return Ok((0, false, quantity, None));
+        }
+
+        let fully_filled = updated_maker.is_none();
+        let (updated_maker, refreshed) = match updated_maker {
+            Some(updated) if hidden_reduced == 0 => {
+                match replenish_reserve(&updated, self.display_size(maker_id)) {
+                    Some(replenished) => (Some(replenished), true),
+                    None => (Some(updated), false),
+                }
+            }
+            updated => (updated, hidden_reduced > 0),
+        };
+        let fill = |update| {
+            self.apply_level_update(
+                price_level,
+                maker_side,
+                LevelOperation::Fill,
+                maker_id,
+                update,
+            )
+        };
+        let resting = match updated_maker {
+            // Partially filled from its visible quantity, or a reserve order refreshed
+            // in its place: updated in place to keep priority
+            Some(updated)
+                if !refreshed
+                    || (matches!(updated, OrderType::ReserveOrder { .. })
+                        && options.options.reserve_refresh == ReserveRefresh::KeepPlace) =>
+            {
+                self.update_in_place(price_level, maker_side, updated)?;
+                Some(Resting::InPlace(updated))
+            }
+            // Refreshed from hidden quantity: the refreshed slice goes to the back of the
+            // queue. Its old entry stays ahead in the level's own queue
+            Some(updated) => {
+                fill(OrderUpdate::Cancel { order_id: maker_id })?;
+                price_level.add_order(updated);
+                self.requeue_acceptance(maker_id);
+                if price_level.order_count() > 1 {
+                    self.mark_irregular(maker_id, maker_side, price_level.price());
+                }
+                Some(Resting::Back(updated))
+            }
+            None => {
+                fill(OrderUpdate::Cancel { order_id: maker_id })?;
+                None
+            }
+        };
+        Ok((consumed, fully_filled, remaining, resting))
+    }
+
+    /// Replace a resting order of `price_level` by its updated self, keeping
+    /// its place in the queue.
+    ///
+    /// The level updates the quantity of standard, iceberg and post-only
+    /// orders in place. It cannot resize other order types, so those are
+    /// taken out and put back the way the level's own quantity update does
+    /// it: the order's first entry in the level queue stays where it was and
+    /// still hands out the order, and its acceptance sequence is unchanged.
+    fn update_in_place(
+        &self,
+        price_level: &PriceLevel,
+        side: Side,
+        order: OrderType<()>,
+    ) -> Result<(), OrderBookError> {
+        let order_id = order.id();
+        if reducible_in_place(&order) {
+            self.apply_level_update(
+                price_level,
+                side,
+                LevelOperation::Fill,
+                order_id,
+                OrderUpdate::UpdateQuantity {
+                    order_id,
+                    new_quantity: order.visible_quantity(),
+                },
+            )?;
+        } else {
+            self.apply_level_update(
+                price_level,
+                side,
+                LevelOperation::Fill,
+                order_id,
+                OrderUpdate::Cancel { order_id },
+            )?;
+            price_level.add_order(order);
+        }
+        Ok(())
+    }
+
+    /// Computes how much of `quantity` could be executed right now, honouring the
+    /// execution constraints of resting makers.
+    ///
+    /// Equivalent to `peek_match` when no resting order carries constraints.
+    pub fn peek_match_with_constraints(
+        &self,
+        side: Side,
+        quantity: u64,
+        price_limit: Option<u64>,
+    ) -> u64 {
+        if self.order_constraints.is_empty() {
+            return self.peek_match(side, quantity, price_limit);
+        }
+
+        let price_levels = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
 
         let mut matched_quantity = 0u64;
+        let mut cursor = None;
+        while let Some(price) = price_levels.next_after(cursor) {
+            cursor = Some(price);
+            if matched_quantity >= quantity {
+                break;
+            }
+
+            if let Some(limit) = price_limit {
+                match side {
+                    Side::Buy if price > limit => break,
+                    Side::Sell if price < limit => break,
+                    _ => {}
+                }
+            }
 
-        // Use thread-local pool for price vector
-        thread_local! {
-            static PEEK_POOL: MatchingPool = MatchingPool::new();
+            if let Some(price_level) = price_levels.get(&price) {
+                for maker in self.queued_orders(&price_level) {
+                    let needed_quantity = quantity - matched_quantity;
+                    if needed_quantity == 0 {
+                        break;
+                    }
+
+                    let maker_quantity = maker.total_quantity();
+                    if let Some(constraints) = self.order_constraints.get(&maker.id())
+                        && !constraints.allows_execution(maker_quantity, needed_quantity)
+                    {
+                        continue;
+                    }
+
+                    matched_quantity += needed_quantity.min(maker_quantity);
+                }
+            }
         }
 
-        let mut sorted_prices = PEEK_POOL.with(|pool| pool.get_price_vec());
+        matched_quantity
+    }
 
-        // Collect and sort prices
-        sorted_prices.extend(price_levels.iter().map(|r| *r.key()));
+    /// Optimized peek match with memory pooling
+    pub fn peek_match(&self, side: Side, quantity: u64, price_limit: Option<u64>) -> u64 {
+        if let Some(storage) = self.compact_storage() {
+            return storage.peek_match(side, quantity, price_limit);
+        }
+        let price_levels = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
 
-        if side == Side::Buy {
-            sorted_prices.sort_unstable(); // Ascending for asks
-        } else {
-            sorted_prices.sort_unstable_by(|a, b| b.cmp(a)); // Descending for bids
+        if price_levels.is_empty() {
+            return 0;
         }
 
-        // Process each price level
-        for &price in &sorted_prices {
+        let mut matched_quantity = 0u64;
+
+        // Process each price level, best price first
+        let mut cursor = None;
+        while let Some(price) = price_levels.next_after(cursor) {
+            cursor = Some(price);
+
             // Early termination when we have enough quantity
             if matched_quantity >= quantity {
                 break;
@@ -197,8 +723,8 @@ where
             // Check price limit
             if let Some(limit) = price_limit {
                 match side {
-                    Side::Buy if price > limit => continue,
-                    Side::Sell if price < limit => continue,
+                    Side::Buy if price > limit => break,
+                    Side::Sell if price < limit => break,
                     _ => {}
                 }
             }
@@ -212,12 +738,63 @@ where
             }
         }
 
-        // Return vector to pool
-        PEEK_POOL.with(|pool| pool.return_price_vec(sorted_prices));
-
         matched_quantity
     }
 
+    /// Turn a market-to-limit order into the limit order it becomes on entry,
+    /// priced at the best opposite level, so it executes against that level
+    /// only and rests any remainder there. Other orders are returned as they are.
+    ///
+    /// # Errors
+    /// Returns `InsufficientLiquidity` if the opposite side is empty, and
+    /// `InvalidTradingState` during an auction call, where there is no level
+    /// to execute against.
+    pub(super) fn price_market_to_limit(
+        &self,
+        order: OrderType<T>,
+    ) -> Result<OrderType<T>, OrderBookError> {
+        let OrderType::MarketToLimit {
+            id,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            extra_fields,
+            ..
+        } = order
+        else {
+            return Ok(order);
+        };
+        let state = self.trading_state();
+        if state.is_auction_call() {
+            return Err(OrderBookError::InvalidTradingState { state });
+        }
+        let best_opposite = match side {
+            Side::Buy => self.best_ask(),
+            Side::Sell => self.best_bid(),
+        };
+        let Some(price) = best_opposite else {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: quantity,
+                available: 0,
+            });
+        };
+        trace!(
+            "Order book {}: Market-to-limit order {} priced at {}",
+            self.symbol, id, price
+        );
+        Ok(OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            extra_fields,
+        })
+    }
+
     /// Batch operation for multiple order matches (additional optimization)
     pub fn match_orders_batch(
         &self,
@@ -233,3 +810,75 @@ where
         results
     }
 }
+
+/// Whether a price level quantity update applies to `order`. The level leaves
+/// order types other than these unchanged
+fn reducible_in_place(order: &OrderType<()>) -> bool {
+    matches!(
+        order,
+        OrderType::Standard { .. } | OrderType::IcebergOrder { .. } | OrderType::PostOnly { .. }
+    )
+}
+
+/// Match `quantity` against the visible quantity of a reserve order only,
+/// leaving its hidden quantity untouched. `None` once both are used up
+fn match_visible_slice(
+    order: &OrderType<()>,
+    quantity: u64,
+) -> (u64, Option<OrderType<()>>, u64, u64) {
+    let mut updated = *order;
+    let OrderType::ReserveOrder {
+        visible_quantity,
+        hidden_quantity,
+        ..
+    } = &mut updated
+    else {
+        return order.match_against(quantity);
+    };
+    let consumed = (*visible_quantity).min(quantity);
+    *visible_quantity -= consumed;
+    let exhausted = *visible_quantity == 0 && *hidden_quantity == 0;
+    (
+        consumed,
+        (!exhausted).then_some(updated),
+        0,
+        quantity - consumed,
+    )
+}
+
+/// A reserve order that auto-replenishes and traded below its replenish
+/// threshold, topped up from its reserve by its replenish amount, or back to
+/// `display_size` without one. `None` if the order is not to be replenished.
+fn replenish_reserve(order: &OrderType<()>, display_size: Option<u64>) -> Option<OrderType<()>> {
+    let OrderType::ReserveOrder {
+        visible_quantity,
+        hidden_quantity,
+        replenish_threshold,
+        replenish_amount,
+        auto_replenish: true,
+        ..
+    } = order
+    else {
+        return None;
+    };
+    if *hidden_quantity == 0 || *visible_quantity >= *replenish_threshold {
+        return None;
+    }
+    let top_up = replenish_amount
+        .unwrap_or_else(|| display_size.unwrap_or(0).saturating_sub(*visible_quantity))
+        .min(*hidden_quantity);
+    if top_up == 0 {
+        return None;
+    }
+    let mut replenished = *order;
+    if let OrderType::ReserveOrder {
+        visible_quantity,
+        hidden_quantity,
+        ..
+    } = &mut replenished
+    {
+        *visible_quantity += top_up;
+        *hidden_quantity -= top_up;
+    }
+    Some(replenished)
+}
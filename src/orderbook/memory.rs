@@ -0,0 +1,199 @@
+//! Approximate memory used by the structures of a book, broken down so
+//! capacity planning can see which structure grows with what.
+//!
+//! Estimates are made from the allocated capacity of each map and the size of
+//! its entries, not measured from the allocator. Heap data behind an entry,
+//! such as the text of account names and client order ids, is not counted.
+
+use super::book::OrderBook;
+use super::matching::{clear_matching_pool, matching_pool_memory};
+use super::side::BookSide;
+use dashmap::DashMap;
+use pricelevel::{OrderType, PriceLevel, Side};
+use serde::{Deserialize, Serialize};
+use std::hash::{BuildHasher, Hash};
+use std::mem::size_of;
+use std::sync::Arc;
+use tracing::trace;
+
+/// Entries held by one structure and the bytes they take
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructureMemory {
+    /// Number of entries
+    pub entries: usize,
+    /// Approximate bytes allocated, spare capacity included
+    pub bytes: u64,
+}
+
+impl StructureMemory {
+    fn add(self, other: StructureMemory) -> StructureMemory {
+        StructureMemory {
+            entries: self.entries + other.entries,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// Price levels and orders of one side of the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SideMemory {
+    /// Number of price levels
+    pub levels: usize,
+    /// Number of resting orders
+    pub orders: usize,
+    /// Approximate bytes taken by the levels, their orders, the level map and
+    /// the sorted price index
+    pub bytes: u64,
+}
+
+/// Approximate memory used by a book, see [`OrderBook::memory_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryStats {
+    /// Bid levels and their orders
+    pub bids: SideMemory,
+    /// Ask levels and their orders
+    pub asks: SideMemory,
+    /// Index from order id to the price and side it rests at
+    pub order_locations: StructureMemory,
+    /// Per-order tables of resting orders besides their location: constraints,
+    /// display sizes, acceptance sequences, accounts and client order ids
+    pub order_tables: StructureMemory,
+    /// Execution state of resting orders and final status of finished ones
+    pub executions: StructureMemory,
+    /// Vectors pooled for matching on the calling thread. The pool is shared
+    /// by every book matched on that thread
+    pub matching_pool: StructureMemory,
+    /// Best price cache
+    pub cache: StructureMemory,
+}
+
+impl MemoryStats {
+    /// Approximate bytes used by every structure together
+    pub fn total_bytes(&self) -> u64 {
+        self.bids.bytes
+            + self.asks.bytes
+            + self.order_locations.bytes
+            + self.order_tables.bytes
+            + self.executions.bytes
+            + self.matching_pool.bytes
+            + self.cache.bytes
+    }
+}
+
+/// Entries of a map and the bytes its table allocated: each slot holds a key
+/// and value plus one control byte
+pub(super) fn map_memory<K, V, S>(map: &DashMap<K, V, S>) -> StructureMemory
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    StructureMemory {
+        entries: map.len(),
+        bytes: (map.capacity() * (size_of::<(K, V)>() + 1)) as u64,
+    }
+}
+
+/// Levels, orders and approximate bytes of one side
+fn side_memory(levels: &BookSide) -> SideMemory {
+    let orders: usize = levels.iter().map(|level| level.order_count()).sum();
+    let level_count = levels.len();
+    // Levels and orders sit behind an `Arc`, with its two reference counts
+    let arc_overhead = 2 * size_of::<usize>();
+    let map_bytes = levels.capacity() * (size_of::<(u64, Arc<PriceLevel>)>() + 1);
+    // A skip list node holds the price, its reference count and height, and
+    // two tower pointers on average
+    let index_bytes = level_count * (size_of::<u64>() + 3 * size_of::<usize>());
+    let bytes = map_bytes
+        + index_bytes
+        + level_count * (size_of::<PriceLevel>() + arc_overhead)
+        + orders * (size_of::<OrderType<()>>() + arc_overhead);
+    SideMemory {
+        levels: level_count,
+        orders,
+        bytes: bytes as u64,
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Approximate memory used by the book, per structure.
+    ///
+    /// Counting the orders of each side visits every price level, so this is
+    /// meant for monitoring rather than the order entry path. For a cheap
+    /// estimate from the order and level counts alone, see
+    /// [`memory_usage`](Self::memory_usage).
+    pub fn memory_stats(&self) -> MemoryStats {
+        let order_tables = [
+            map_memory(&self.order_constraints),
+            map_memory(&self.display_sizes),
+            map_memory(&self.order_sequences),
+            map_memory(&self.order_accounts),
+            map_memory(&self.account_orders),
+            map_memory(&self.client_orders),
+            map_memory(&self.order_client_ids),
+        ]
+        .into_iter()
+        .fold(StructureMemory::default(), StructureMemory::add);
+
+        MemoryStats {
+            bids: side_memory(&self.bids),
+            asks: side_memory(&self.asks),
+            order_locations: map_memory(&self.order_locations),
+            order_tables,
+            executions: self.executions.memory(),
+            matching_pool: matching_pool_memory(),
+            cache: StructureMemory {
+                entries: 2,
+                bytes: size_of_val(&self.cache) as u64,
+            },
+        }
+    }
+
+    /// Reclaim memory a long run left allocated but unused, returning the
+    /// approximate bytes released as [`memory_stats`](Self::memory_stats)
+    /// counts them.
+    ///
+    /// Price levels left without orders are removed, every per-order and
+    /// per-trade map and the level maps give back their spare capacity, and
+    /// the calling thread's matching pool is emptied. Resting orders and their priority are untouched.
+    ///
+    /// Shrinking a map locks each of its shards in turn and rehashes it, so
+    /// this is meant for quiet periods rather than while orders stream in.
+    pub fn compact(&self) -> u64 {
+        let before = self.memory_stats().total_bytes();
+        let _write = self.writes.begin();
+        let mut levels_removed = 0;
+        for (side, levels) in [(Side::Buy, &self.bids), (Side::Sell, &self.asks)] {
+            let removed = levels.compact();
+            levels_removed += removed.len();
+            self.cache.levels_removed(side, &removed);
+            self.publish_levels_removed(side, &removed);
+        }
+        self.order_locations.shrink_to_fit();
+        self.order_constraints.shrink_to_fit();
+        self.display_sizes.shrink_to_fit();
+        self.order_sequences.shrink_to_fit();
+        self.order_accounts.shrink_to_fit();
+        self.account_orders
+            .iter_mut()
+            .for_each(|mut orders| orders.shrink_to_fit());
+        self.account_orders.shrink_to_fit();
+        self.client_orders.shrink_to_fit();
+        self.order_client_ids.shrink_to_fit();
+        self.executions.shrink_to_fit();
+        clear_matching_pool();
+        if levels_removed > 0 {
+            self.bump_version();
+        }
+        self.update_memory_pressure();
+
+        let released = before.saturating_sub(self.memory_stats().total_bytes());
+        trace!(
+            "Order book {}: Compacted, removing {} empty levels and releasing about {} bytes",
+            self.symbol, levels_removed, released
+        );
+        released
+    }
+}
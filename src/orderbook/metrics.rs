@@ -0,0 +1,149 @@
+//! Per-operation latency histograms, recorded when the `metrics` feature is enabled
+
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+/// Highest latency tracked, in nanoseconds. Slower operations are clamped to it.
+const MAX_TRACKED_NS: u64 = 60_000_000_000;
+
+/// Precision of the histograms, in significant decimal digits
+const SIGNIFICANT_DIGITS: u8 = 3;
+
+/// An operation whose latency is recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Operation {
+    /// Adding an order, including any matching it triggers
+    Add,
+    /// Cancelling an order
+    Cancel,
+    /// Matching an incoming order against the book
+    Match,
+}
+
+/// Latency histograms of the operations of one book
+pub(super) struct LatencyMetrics {
+    add: Mutex<Histogram<u64>>,
+    cancel: Mutex<Histogram<u64>>,
+    matching: Mutex<Histogram<u64>>,
+}
+
+impl LatencyMetrics {
+    pub(super) fn new() -> Self {
+        let histogram = || {
+            Mutex::new(
+                Histogram::new_with_bounds(1, MAX_TRACKED_NS, SIGNIFICANT_DIGITS)
+                    .expect("valid histogram bounds"),
+            )
+        };
+        Self {
+            add: histogram(),
+            cancel: histogram(),
+            matching: histogram(),
+        }
+    }
+
+    fn histogram(&self, operation: Operation) -> &Mutex<Histogram<u64>> {
+        match operation {
+            Operation::Add => &self.add,
+            Operation::Cancel => &self.cancel,
+            Operation::Match => &self.matching,
+        }
+    }
+
+    /// Start timing an operation; the latency is recorded when the timer is dropped
+    pub(super) fn timer(&self, operation: Operation) -> LatencyTimer<'_> {
+        LatencyTimer {
+            metrics: self,
+            operation,
+            started: Instant::now(),
+        }
+    }
+
+    pub(super) fn record(&self, operation: Operation, latency_ns: u64) {
+        self.histogram(operation)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .saturating_record(latency_ns.max(1));
+    }
+
+    pub(super) fn stats(&self, operation: Operation) -> LatencyStats {
+        let histogram = self
+            .histogram(operation)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if histogram.is_empty() {
+            return LatencyStats::default();
+        }
+        LatencyStats {
+            count: histogram.len(),
+            min_ns: histogram.min(),
+            mean_ns: histogram.mean(),
+            p50_ns: histogram.value_at_quantile(0.5),
+            p90_ns: histogram.value_at_quantile(0.9),
+            p99_ns: histogram.value_at_quantile(0.99),
+            p999_ns: histogram.value_at_quantile(0.999),
+            max_ns: histogram.max(),
+        }
+    }
+
+    pub(super) fn reset(&self) {
+        for operation in [Operation::Add, Operation::Cancel, Operation::Match] {
+            self.histogram(operation)
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .reset();
+        }
+    }
+}
+
+/// Records the time elapsed since its creation when dropped
+pub(super) struct LatencyTimer<'a> {
+    metrics: &'a LatencyMetrics,
+    operation: Operation,
+    started: Instant,
+}
+
+impl Drop for LatencyTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed().as_nanos();
+        self.metrics
+            .record(self.operation, u64::try_from(elapsed).unwrap_or(u64::MAX));
+    }
+}
+
+/// Latency distribution of one operation, in nanoseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Number of recorded operations
+    pub count: u64,
+    /// Fastest recorded operation
+    pub min_ns: u64,
+    /// Mean latency
+    pub mean_ns: f64,
+    /// Median latency
+    pub p50_ns: u64,
+    /// 90th percentile
+    pub p90_ns: u64,
+    /// 99th percentile
+    pub p99_ns: u64,
+    /// 99.9th percentile
+    pub p999_ns: u64,
+    /// Slowest recorded operation
+    pub max_ns: u64,
+}
+
+/// Latencies recorded by a book, returned by `OrderBook::metrics_report`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsReport {
+    /// Adding orders, including the matching they trigger
+    pub add_order: LatencyStats,
+    /// Cancelling orders
+    pub cancel_order: LatencyStats,
+    /// Matching incoming orders
+    pub match_order: LatencyStats,
+}
@@ -1,18 +1,180 @@
 //! OrderBook implementation for managing multiple price levels and order matching.
 
+pub mod accounts;
+pub mod amendments;
+pub mod analytics;
+#[cfg(feature = "anonymize")]
+pub mod anonymize;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "async_api")]
+pub mod async_api;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod bbo;
+#[cfg(feature = "binary")]
+mod binary;
 pub mod book;
+mod btree_book;
+pub mod builder;
+pub mod caps;
+pub mod checksum;
+pub mod client_ids;
 pub mod error;
+pub mod events;
+pub mod execution;
+pub mod execution_report;
+pub mod expiry;
+pub mod export;
+mod fairness;
+pub mod feed;
+pub mod fees;
+pub mod fills;
+pub mod fixed_point;
+pub mod implied;
+pub mod kill_switch;
+pub mod level_fills;
+pub mod levels;
 pub mod matching;
 
 mod cache;
+pub mod circuit_breaker;
+pub mod compact;
+pub mod consistency;
+pub mod constraints;
+pub mod contingent;
+pub mod dark;
+pub mod depth_feed;
+pub mod deterministic;
+pub mod disconnect;
+pub mod engine;
+pub mod manager;
+pub mod memory;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 /// Contains the core logic for modifying the order book state, such as adding, canceling, or updating orders.
 pub mod modifications;
+pub mod notional;
 pub mod operations;
+pub mod options;
+pub mod pegs;
 mod pool;
+pub mod price_scale;
 mod private;
+pub mod quotes;
+pub mod rate_limit;
+pub mod registry;
+pub mod replay;
+pub mod session;
+pub mod shard;
+pub mod short_sale;
+mod side;
+pub mod signals;
+pub mod signed;
 pub mod snapshot;
+pub mod special;
+pub mod stats;
+mod sync;
+pub mod tape;
+pub mod testing;
 mod tests;
+mod ticket;
+pub mod trade;
+pub mod trade_channel;
+pub mod validation;
+pub mod views;
+pub mod watermarks;
 
+pub use amendments::Amendment;
+#[cfg(feature = "anonymize")]
+pub use anonymize::Anonymizer;
+#[cfg(feature = "arena")]
+pub use arena::{ArenaOrderBook, OrderArena, OrderHandle};
+#[cfg(feature = "async_api")]
+pub use async_api::{AsyncOrderBook, OrderAck};
+#[cfg(feature = "audit")]
+pub use audit::{AuditCheckpoint, encode_event, events_hash};
+pub use bbo::{BboChange, BboListener, TopOfBook};
+#[cfg(feature = "parallel")]
+pub use book::DEFAULT_PARALLEL_SNAPSHOT_DEPTH;
 pub use book::OrderBook;
-pub use error::OrderBookError;
-pub use snapshot::OrderBookSnapshot;
+pub use builder::BookBuilder;
+pub use cache::CacheInvalidation;
+pub use caps::{CapAction, RestingCap, RestingCaps};
+pub use checksum::{ChecksumFormat, crc32, levels_checksum};
+pub use circuit_breaker::{SweepGuard, TradingHalt};
+pub use compact::{CompactOrder, CompactOrderBook};
+pub use consistency::CONSISTENT_READ_ATTEMPTS;
+pub use constraints::OrderConstraints;
+pub use contingent::{ChildActivation, ContingentOrders, StopLeg};
+pub use dark::{DarkMatch, DarkMatching, DarkOrder};
+#[cfg(not(target_arch = "wasm32"))]
+pub use depth_feed::DepthFeedThread;
+pub use depth_feed::{ConflatedDepth, DepthListener, DepthUpdate};
+pub use deterministic::DeterministicOrderBook;
+pub use disconnect::Session;
+pub use engine::{Command, CommandOutcome, EngineHandle, EngineLoop};
+pub use error::{LevelOperation, OrderBookError};
+pub use events::{
+    AcceptedOrder, CancelledOrder, EventListener, LevelChange, OrderBookEvent, OrderReject,
+    RejectReason,
+};
+pub use execution::{ExecutionState, FINISHED_ORDERS_RETAINED};
+pub use execution_report::{ExecType, ExecutionReport, OrderStatus, execution_report_listener};
+pub use expiry::{EXPIRED_ORDERS_RETAINED, ExpiredOrder};
+pub use export::{SNAPSHOT_CSV_HEADER, SnapshotCsvWriter, TRADE_CSV_HEADER, TradeCsvWriter};
+#[cfg(feature = "parquet")]
+pub use export::{SnapshotParquetWriter, TradeParquetWriter};
+pub use feed::FeedMessage;
+pub use feed::follower::{FollowerBook, FollowerStatus, SequencedFeedMessage};
+#[cfg(feature = "itch")]
+pub use feed::itch::{ItchMessage, ItchReader};
+pub use fees::{FeeSchedule, TradeFees};
+pub use fills::FillNotification;
+pub use fixed_point::{Price, Qty};
+pub use implied::{ImpliedExecution, ImpliedMatchingEngine, ImpliedQuote, ImpliedSpreadQuote};
+pub use kill_switch::{KillReport, KillScope};
+pub use level_fills::LevelFill;
+pub use levels::{LevelIter, LevelSummary, OrderIter, OrderPage};
+pub use manager::{MultiBookSnapshot, OrderBookManager, VersionedSnapshot};
+pub use memory::{MemoryStats, SideMemory, StructureMemory};
+#[cfg(feature = "metrics")]
+pub use metrics::{LatencyStats, MetricsReport};
+pub use notional::{AccountNotional, NotionalStats};
+pub use options::{
+    DepthLimitAction, DuplicateOrderIdAction, MatchDepthLimit, OrderBookOptions, ReserveRefresh,
+    VersionedOptions,
+};
+pub use pegs::{MidpointPeg, MidpointRounding, RepricedOrder, SubTickHandling};
+pub use pool::{PoolConfig, PoolStats};
+pub use price_scale::{MAX_PRICE_DECIMALS, PriceScale, RoundingMode};
+pub use quotes::{PulledQuotes, Quote, QuoteProtection, QuoteSide};
+pub use rate_limit::{RateLimit, RateLimitScope, RateLimiter};
+pub use registry::{SymbolInfo, SymbolRegistry};
+pub use replay::{ReplayEngine, ReplayOperation, ReplayRecord, ReplayStep, ReplayStop};
+pub use session::{
+    AuctionEquilibrium, AuctionResult, AuctionTimeInForce, SessionSchedule, SessionTransition,
+    TradingState,
+};
+pub use shard::ShardExecutor;
+pub use short_sale::{ShortSaleCheck, ShortSaleReference, ShortSaleRule, short_sale_price_test};
+pub use signals::{DepthTotals, SignalFired, SignalListener, SignalPredicate};
+pub use signed::{SignedLevel, SignedOrderUpdate, SignedSnapshot, SignedTrade};
+pub use snapshot::{
+    L3Level, L3Order, LevelDelta, OrderBookL3Snapshot, OrderBookSnapshot, SnapshotDiff,
+};
+pub use special::{SpecialPriceOrder, SpecialPriceSettlement};
+pub use stats::BookStats;
+pub use tape::{OhlcvBar, TradeTape};
+pub use testing::{
+    BookOp, Divergence, ReferenceBook, ReferenceFill, ReferenceLevel, RunLength, StepOutcome,
+    StressConfig, StressHarness, StressReport, check_against_reference,
+};
+pub use trade::{TradeCondition, TradeConditions, TradeReport};
+pub use trade_channel::{OverflowPolicy, TradeChannel};
+pub use validation::{ValidationIssue, ValidationReport};
+pub use views::{AskView, BidView};
+pub use watermarks::{
+    MemoryPressure, MemoryPressureEvent, MemoryPressureListener, MemoryUsage, MemoryWatermarks,
+    Watermark,
+};
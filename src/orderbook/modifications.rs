@@ -1,9 +1,22 @@
 use crate::orderbook::book::OrderBook;
-use crate::orderbook::error::OrderBookError;
-use pricelevel::{OrderId, OrderType, OrderUpdate, PriceLevel, Side};
+use crate::orderbook::constraints::OrderConstraints;
+use crate::orderbook::dark::DarkMatching;
+use crate::orderbook::error::{LevelOperation, OrderBookError};
+use crate::orderbook::events::{AcceptedOrder, CancelledOrder, OrderBookEvent};
+use crate::orderbook::execution::ExecutionState;
+use crate::orderbook::execution_report::OrderStatus;
+use crate::orderbook::matching::clear_matching_pool;
+#[cfg(feature = "metrics")]
+use crate::orderbook::metrics::Operation;
+use crate::orderbook::options::{DepthLimitAction, DuplicateOrderIdAction, OrderBookOptions};
+use pricelevel::{MatchResult, OrderId, OrderType, OrderUpdate, Side, TimeInForce};
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use tracing::trace;
 
+/// An order taken out of the book, with the quantity it had executed
+type RemovedOrder<T> = (Arc<OrderType<T>>, u64);
+
 /// A trait to abstract quantity access and modification for different order types.
 pub trait OrderQuantity<T = ()> {
     /// Returns the primary quantity used for display or simple matching.
@@ -100,14 +113,76 @@ impl<T> OrderBook<T>
 where
     T: Clone + Send + Sync + Default + 'static,
 {
-    /// Update an order's price and/or quantity
+    /// Update an order's price and/or quantity.
+    ///
+    /// Quantity updates are applied in place and are fair under contention:
+    /// updates to the same level are admitted in the order they arrive, so
+    /// none waits behind more than the updates queued before it. Price
+    /// updates cancel the order and add it again at the new price.
+    ///
+    /// Amendments are recorded in the order's
+    /// [`amendment_history`](Self::amendment_history) when it is enabled.
     pub fn update_order(
         &self,
         update: OrderUpdate,
     ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
-        self.cache.invalidate();
+        if self.has_compact_storage() {
+            return Err(self.compact_unsupported("order updates"));
+        }
+        let amended = match update {
+            _ if self.amendments.is_none() => None,
+            OrderUpdate::Cancel { .. } => None,
+            OrderUpdate::UpdatePrice { order_id, .. }
+            | OrderUpdate::UpdateQuantity { order_id, .. }
+            | OrderUpdate::UpdatePriceAndQuantity { order_id, .. }
+            | OrderUpdate::Replace { order_id, .. } => self.get_order(order_id),
+        };
+        let result = self.apply_order_update(update)?;
+        if let (Some(before), Some(updated)) = (amended, &result) {
+            // A repriced order that crossed in full no longer rests
+            let after = self
+                .get_order(before.id())
+                .unwrap_or_else(|| Arc::clone(updated));
+            self.record_amendment(&before, &after);
+        }
+        Ok(result)
+    }
+
+    fn apply_order_update(
+        &self,
+        update: OrderUpdate,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
         trace!("Order book {}: Updating order {:?}", self.symbol, update);
+        let _write = self.writes.begin();
+        if !matches!(update, OrderUpdate::Cancel { .. }) {
+            self.advance_session();
+            self.check_order_entry()?;
+        }
         match update {
+            OrderUpdate::UpdatePrice {
+                order_id,
+                new_price,
+            } if self.display_sizes.contains_key(&order_id) => {
+                if self
+                    .order_locations
+                    .get(&order_id)
+                    .is_some_and(|location| location.0 == new_price)
+                {
+                    return Err(OrderBookError::InvalidOperation {
+                        message: "Cannot update price to the same value".to_string(),
+                    });
+                }
+                self.amend_reserve_order(order_id, new_price, None)
+            }
+
+            OrderUpdate::UpdatePriceAndQuantity {
+                order_id,
+                new_price,
+                new_quantity,
+            } if self.display_sizes.contains_key(&order_id) => {
+                self.amend_reserve_order(order_id, new_price, Some(new_quantity))
+            }
+
             OrderUpdate::UpdatePrice {
                 order_id,
                 new_price,
@@ -131,25 +206,21 @@ where
                         return Ok(None); // Order not found
                     };
 
+                    // Keep the execution constraints and owner across the cancel/re-add
+                    let constraints = self.get_order_constraints(order_id);
+                    let account_id = self.order_account(order_id);
+
                     // Cancel the original order
-                    self.cancel_order(order_id)?;
+                    self.remove_resting_order(order_id)?;
 
                     // Create a new order with the updated price
                     let mut new_order = original_order;
 
-                    // Update the price based on order type
-                    match &mut new_order {
-                        OrderType::Standard { price, .. } => *price = new_price,
-                        OrderType::IcebergOrder { price, .. } => *price = new_price,
-                        OrderType::PostOnly { price, .. } => *price = new_price,
-                        OrderType::TrailingStop { price, .. } => *price = new_price,
-                        OrderType::PeggedOrder { price, .. } => *price = new_price,
-                        OrderType::MarketToLimit { price, .. } => *price = new_price,
-                        OrderType::ReserveOrder { price, .. } => *price = new_price,
-                    }
+                    set_order_price(&mut new_order, new_price);
 
                     // Add the updated order
-                    let result = self.add_order(new_order)?;
+                    let result =
+                        self.reenter_order(new_order, constraints, account_id.as_deref())?;
                     Ok(Some(result))
                 } else {
                     Ok(None) // Order not found
@@ -159,49 +230,7 @@ where
             OrderUpdate::UpdateQuantity {
                 order_id,
                 new_quantity,
-            } => {
-                // Get order location without locking
-                let location = self.order_locations.get(&order_id).map(|val| *val);
-
-                if let Some((price, side)) = location {
-                    // Get the appropriate price levels map
-                    let price_levels = match side {
-                        Side::Buy => &self.bids,
-                        Side::Sell => &self.asks,
-                    };
-
-                    // Use entry() to safely modify the price level without deadlocks
-                    let mut result = None;
-                    let mut is_empty = false;
-
-                    // Update the order in place within the price level
-                    price_levels.entry(price).and_modify(|price_level| {
-                        let update = OrderUpdate::UpdateQuantity {
-                            order_id,
-                            new_quantity,
-                        };
-
-                        if let Ok(updated_order) = price_level.update_order(update)
-                            && let Some(order) = updated_order
-                        {
-                            result = Some(Arc::new(self.convert_from_unit_type(&order)));
-                        }
-
-                        is_empty = price_level.order_count() == 0;
-                    });
-
-                    // If the price level is now empty, remove it
-                    if is_empty {
-                        price_levels.remove(&price);
-                        self.order_locations.remove(&order_id);
-                    }
-
-                    self.cache.invalidate();
-                    Ok(result)
-                } else {
-                    Ok(None) // Order not found
-                }
-            }
+            } => self.update_resting_quantity(order_id, new_quantity),
 
             OrderUpdate::UpdatePriceAndQuantity {
                 order_id,
@@ -220,28 +249,24 @@ where
                         return Ok(None); // Order not found
                     };
 
+                    // Keep the execution constraints and owner across the cancel/re-add
+                    let constraints = self.get_order_constraints(order_id);
+                    let account_id = self.order_account(order_id);
+
                     // Cancel the original order
-                    self.cancel_order(order_id)?;
+                    self.remove_resting_order(order_id)?;
 
                     // Create a new order with the updated price and quantity
                     let mut new_order = original_order;
 
-                    // Update the price based on order type
-                    match &mut new_order {
-                        OrderType::Standard { price, .. } => *price = new_price,
-                        OrderType::IcebergOrder { price, .. } => *price = new_price,
-                        OrderType::PostOnly { price, .. } => *price = new_price,
-                        OrderType::TrailingStop { price, .. } => *price = new_price,
-                        OrderType::PeggedOrder { price, .. } => *price = new_price,
-                        OrderType::MarketToLimit { price, .. } => *price = new_price,
-                        OrderType::ReserveOrder { price, .. } => *price = new_price,
-                    }
+                    set_order_price(&mut new_order, new_price);
 
                     // Update the quantity using the trait method
                     new_order.set_quantity(new_quantity);
 
                     // Add the updated order
-                    let result = self.add_order(new_order)?;
+                    let result =
+                        self.reenter_order(new_order, constraints, account_id.as_deref())?;
                     Ok(Some(result))
                 } else {
                     Ok(None) // Order not found
@@ -259,28 +284,44 @@ where
                         Side::Sell => &self.asks,
                     };
 
-                    // Use entry() to safely modify the price level
+                    // Hold the level exclusively while modifying it
                     let mut result = None;
                     let mut is_empty = false;
+                    let mut executed_quantity = 0;
 
                     // Get the current order first
                     if let Some(current_order) = self.get_order(order_id) {
                         result = Some(current_order);
 
                         // Remove the order directly from the price level
-                        price_levels.entry(price).and_modify(|price_level| {
+                        let mut cancelled = Ok(None);
+                        if let Some(price_level) = price_levels.get_mut(&price).as_deref() {
                             let cancel_update = OrderUpdate::Cancel { order_id };
-                            let _ = price_level.update_order(cancel_update);
+                            cancelled = self.apply_level_update(
+                                price_level,
+                                side,
+                                LevelOperation::Cancel,
+                                order_id,
+                                cancel_update,
+                            );
                             is_empty = price_level.order_count() == 0;
-                        });
+                        }
+                        cancelled?;
 
                         // Remove from order locations tracking
-                        self.order_locations.remove(&order_id);
+                        executed_quantity = self.executions.executed(order_id);
+                        self.forget_order(order_id);
+                        self.stats.record_cancelled(1);
                     }
 
                     // If price level is empty, remove it
                     if is_empty {
-                        price_levels.remove(&price);
+                        self.remove_level_if_empty(side, price);
+                    }
+                    if let Some(order) = &result {
+                        self.bump_version();
+                        self.finish_order(order_id, OrderStatus::Cancelled);
+                        self.publish_cancel(order, executed_quantity);
                     }
 
                     Ok(result)
@@ -390,11 +431,16 @@ where
                         }
                     }
 
+                    // Keep the execution constraints and owner across the cancel/re-add
+                    let constraints = self.get_order_constraints(order_id);
+                    let account_id = self.order_account(order_id);
+
                     // Cancel the original order
-                    self.cancel_order(order_id)?;
+                    self.remove_resting_order(order_id)?;
 
                     // Add the new order
-                    let result = self.add_order(new_order)?;
+                    let result =
+                        self.reenter_order(new_order, constraints, account_id.as_deref())?;
                     Ok(Some(result))
                 } else {
                     Ok(None) // Original order not found
@@ -403,12 +449,88 @@ where
         }
     }
 
-    /// Cancel an order by ID
+    /// Move a resting iceberg or reserve order to `new_price`, optionally with
+    /// a new total quantity.
+    ///
+    /// The order is cancelled and added again like any price amendment, but
+    /// its quantity is split back into its original display size and a hidden
+    /// remainder, so a partially worked iceberg keeps both its size and its
+    /// hidden quantity. Reserve orders keep their replenishment settings.
+    fn amend_reserve_order(
+        &self,
+        order_id: OrderId,
+        new_price: u64,
+        new_quantity: Option<u64>,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        let Some(original) = self.get_order(order_id) else {
+            return Ok(None);
+        };
+        let display_size = self
+            .display_size(order_id)
+            .unwrap_or_else(|| original.visible_quantity());
+        let total_quantity = new_quantity.unwrap_or_else(|| original.total_quantity());
+        let constraints = self.get_order_constraints(order_id);
+        let account_id = self.order_account(order_id);
+
+        self.remove_resting_order(order_id)?;
+
+        let mut new_order = Arc::unwrap_or_clone(original);
+        if let OrderType::IcebergOrder {
+            price,
+            visible_quantity,
+            hidden_quantity,
+            ..
+        }
+        | OrderType::ReserveOrder {
+            price,
+            visible_quantity,
+            hidden_quantity,
+            ..
+        } = &mut new_order
+        {
+            *price = new_price;
+            *visible_quantity = display_size.min(total_quantity);
+            *hidden_quantity = total_quantity - *visible_quantity;
+        }
+        trace!(
+            "Order book {}: Amending reserve order {} to {} at {} showing {}",
+            self.symbol, order_id, total_quantity, new_price, display_size
+        );
+
+        let result = self.reenter_order(new_order, constraints, account_id.as_deref())?;
+        // Partial execution on re-entry must not shrink the size shown from now on
+        if self.order_locations.contains_key(&order_id) {
+            self.display_sizes.insert(order_id, display_size);
+        }
+        Ok(Some(result))
+    }
+
+    /// Cancel an order by ID, publishing an `OrderCancelled` event if it was
+    /// resting
     pub fn cancel_order(
         &self,
         order_id: OrderId,
     ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
-        self.cache.invalidate();
+        #[cfg(feature = "metrics")]
+        let _timer = self.metrics.timer(Operation::Cancel);
+        if let Some(mut storage) = self.compact_storage() {
+            return Ok(Self::cancel_compact_order(&mut storage, order_id));
+        }
+        let Some((order, executed_quantity)) = self.remove_resting_order(order_id)? else {
+            return Ok(None);
+        };
+        self.finish_order(order_id, OrderStatus::Cancelled);
+        self.publish_cancel(&order, executed_quantity);
+        Ok(Some(order))
+    }
+
+    /// Take a resting order out of the book without publishing anything,
+    /// returning it with the quantity it had executed
+    pub(super) fn remove_resting_order(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<RemovedOrder<T>>, OrderBookError> {
+        let _write = self.writes.begin();
         // First, we find the order's location (price and side) without locking
         let location = self.order_locations.get(&order_id).map(|val| *val);
 
@@ -422,41 +544,422 @@ where
             // Create the update to cancel
             let update = OrderUpdate::Cancel { order_id };
 
-            // Use entry() to safely modify the price level
-            let mut result = None;
+            // Hold the level exclusively while modifying it
+            let mut result = Ok(None);
             let mut empty_level = false;
 
-            price_levels.entry(price).and_modify(|price_level| {
+            if let Some(price_level) = price_levels.get_mut(&price).as_deref() {
                 // Try to cancel the order
-                if let Ok(cancelled) = price_level.update_order(update) {
-                    result = cancelled;
-
-                    // Check if the level became empty
-                    empty_level = price_level.order_count() == 0;
-                }
-            });
+                result = self.apply_level_update(
+                    price_level,
+                    side,
+                    LevelOperation::Cancel,
+                    order_id,
+                    update,
+                );
+
+                // Check if the level became empty
+                empty_level = price_level.order_count() == 0;
+            }
 
-            self.cache.invalidate();
+            let result = result?;
+            let executed_quantity = self.executions.executed(order_id);
             // If we got a result and the order was canceled
             if result.is_some() {
                 // Remove the order from the locations map
-                self.order_locations.remove(&order_id);
+                self.forget_order(order_id);
+                self.stats.record_cancelled(1);
 
                 // If the level became empty, remove it
                 if empty_level {
-                    price_levels.remove(&price);
+                    self.remove_level_if_empty(side, price);
                 }
+                self.bump_version();
+                self.update_memory_pressure();
             }
 
-            Ok(result.map(|order| Arc::new(self.convert_from_unit_type(&order))))
+            Ok(result.map(|order| {
+                (
+                    Arc::new(self.convert_from_unit_type(&order)),
+                    executed_quantity,
+                )
+            }))
         } else {
             Ok(None)
         }
     }
 
-    /// Add a new order to the book, automatically matching it if it's aggressive.
-    pub fn add_order(&self, mut order: OrderType<T>) -> Result<Arc<OrderType<T>>, OrderBookError> {
+    /// Publish an `OrderCancelled` event for an order taken out of the book
+    fn publish_cancel(&self, order: &OrderType<T>, executed_quantity: u64) {
+        if !self.publishes_events() {
+            return;
+        }
+        self.emit_event(&OrderBookEvent::OrderCancelled(CancelledOrder {
+            order_id: order.id(),
+            side: order.side(),
+            price: order.price(),
+            quantity: order.visible_quantity() + order.hidden_quantity(),
+            executed_quantity,
+            timestamp: self.now(),
+        }));
+    }
+
+    /// Cancel every resting order on both sides, returning the cancelled orders
+    pub fn cancel_all(&self) -> Vec<Arc<OrderType<T>>> {
+        let _write = self.writes.begin();
+        if let Some(mut storage) = self.compact_storage() {
+            let mut cancelled = Self::cancel_compact_levels(&mut storage, Side::Buy, |_| true);
+            cancelled.extend(Self::cancel_compact_levels(
+                &mut storage,
+                Side::Sell,
+                |_| true,
+            ));
+            return cancelled;
+        }
+        let mut cancelled = self.cancel_levels(Side::Buy, |_| true);
+        cancelled.extend(self.cancel_levels(Side::Sell, |_| true));
+        self.finish_mass_cancel(cancelled)
+    }
+
+    /// Cancel every resting order on one side, returning the cancelled orders
+    pub fn cancel_side(&self, side: Side) -> Vec<Arc<OrderType<T>>> {
+        let _write = self.writes.begin();
+        if let Some(mut storage) = self.compact_storage() {
+            return Self::cancel_compact_levels(&mut storage, side, |_| true);
+        }
+        let cancelled = self.cancel_levels(side, |_| true);
+        self.finish_mass_cancel(cancelled)
+    }
+
+    /// Cancel every resting order on one side priced between `min_price` and
+    /// `max_price` inclusive, returning the cancelled orders
+    pub fn cancel_price_range(
+        &self,
+        side: Side,
+        min_price: u64,
+        max_price: u64,
+    ) -> Vec<Arc<OrderType<T>>> {
+        let _write = self.writes.begin();
+        if let Some(mut storage) = self.compact_storage() {
+            return Self::cancel_compact_levels(&mut storage, side, |price| {
+                (min_price..=max_price).contains(&price)
+            });
+        }
+        let cancelled = self.cancel_levels(side, |price| (min_price..=max_price).contains(&price));
+        self.finish_mass_cancel(cancelled)
+    }
+
+    /// Remove whole price levels of one side whose price satisfies `selected`,
+    /// forgetting their orders. Orders are returned with the quantity they had
+    /// executed, level by level, best price first, in queue order within a level.
+    fn cancel_levels(&self, side: Side, selected: impl Fn(u64) -> bool) -> Vec<RemovedOrder<T>> {
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let mut cancelled = Vec::new();
+        let mut removed = Vec::new();
+        for price in price_levels
+            .best_prices(usize::MAX)
+            .into_iter()
+            .filter(|price| selected(*price))
+        {
+            let Some((_, price_level)) = price_levels.remove(&price) else {
+                continue;
+            };
+            removed.push(price);
+            for order in self.queued_orders(&price_level) {
+                let executed_quantity = self.executions.executed(order.id());
+                self.forget_order(order.id());
+                cancelled.push((
+                    Arc::new(self.convert_from_unit_type(&order)),
+                    executed_quantity,
+                ));
+            }
+        }
+        self.publish_levels_removed(side, &removed);
+        cancelled
+    }
+
+    /// Bookkeeping done once per mass cancel rather than once per order, then
+    /// an `OrderCancelled` event per order
+    fn finish_mass_cancel(&self, cancelled: Vec<RemovedOrder<T>>) -> Vec<Arc<OrderType<T>>> {
+        if cancelled.is_empty() {
+            return Vec::new();
+        }
+        trace!(
+            "Order book {}: Mass cancelled {} orders",
+            self.symbol,
+            cancelled.len()
+        );
+        self.stats.record_cancelled(cancelled.len());
         self.cache.invalidate();
+        self.bump_version();
+        self.update_memory_pressure();
+        cancelled
+            .into_iter()
+            .map(|(order, executed_quantity)| {
+                self.finish_order(order.id(), OrderStatus::Cancelled);
+                self.publish_cancel(&order, executed_quantity);
+                order
+            })
+            .collect()
+    }
+
+    /// Empty the book: both sides, the per-order tables, the quotes, the best
+    /// price cache and the current thread's matching pool.
+    ///
+    /// Options, the last trade price and the expiry log are kept; the next
+    /// trade is an opening trade again. Operations
+    /// running concurrently with the purge may leave orders behind, so callers
+    /// should stop order entry while clearing.
+    pub fn clear(&self) {
+        trace!("Order book {}: Clearing", self.symbol);
+        let _write = self.writes.begin();
+        self.bids.clear();
+        self.asks.clear();
+        self.order_locations.clear();
+        self.order_constraints.clear();
+        self.display_sizes.clear();
+        self.order_sequences.clear();
+        self.irregular_orders.clear();
+        self.reentries.clear();
+        self.executions.clear();
+        self.order_accounts.clear();
+        self.account_orders.clear();
+        self.session_traded.store(false, Ordering::Relaxed);
+        self.client_orders.clear();
+        self.order_client_ids.clear();
+        self.quotes.clear();
+        self.cache.invalidate();
+        clear_matching_pool();
+        self.bump_version();
+        self.update_memory_pressure();
+    }
+
+    /// Rest one good-till-cancelled standard order per `(price, quantity, side)`
+    /// entry, without matching, and return the ids given to the orders.
+    ///
+    /// Meant to set up books for tests and simulations far faster than adding
+    /// the orders one by one. Entries at the same price queue in the order given.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if a quantity is zero, and
+    /// `OrderBookError::PriceCrossing` if a seeded bid would be at or above a
+    /// seeded or resting ask, or the other way round. Nothing is added then.
+    pub fn seed(&self, levels: &[(u64, u64, Side)]) -> Result<Vec<OrderId>, OrderBookError> {
+        let _write = self.writes.begin();
+        let mut highest_bid = self.best_bid();
+        let mut lowest_ask = self.best_ask();
+        for &(price, quantity, side) in levels {
+            if quantity == 0 {
+                return Err(OrderBookError::InvalidOperation {
+                    message: format!("Seeded order at {price} has no quantity"),
+                });
+            }
+            match side {
+                Side::Buy => highest_bid = highest_bid.max(Some(price)),
+                Side::Sell => lowest_ask = Some(lowest_ask.map_or(price, |ask| ask.min(price))),
+            }
+        }
+        if let (Some(bid), Some(ask)) = (highest_bid, lowest_ask)
+            && bid >= ask
+        {
+            return Err(OrderBookError::PriceCrossing {
+                price: bid,
+                side: Side::Buy,
+                opposite_price: ask,
+            });
+        }
+
+        trace!(
+            "Order book {}: Seeding {} orders",
+            self.symbol,
+            levels.len()
+        );
+        if let Some(mut storage) = self.compact_storage() {
+            return levels
+                .iter()
+                .map(|&(price, quantity, side)| {
+                    let order_id = OrderId::new();
+                    storage.add_limit_order(order_id, price, quantity, side)?;
+                    Ok(order_id)
+                })
+                .collect();
+        }
+        let timestamp = self.now();
+        let mut order_ids = Vec::with_capacity(levels.len());
+        let mut created_levels = Vec::new();
+        for &(price, quantity, side) in levels {
+            let order_id = OrderId::new();
+            let price_levels = match side {
+                Side::Buy => &self.bids,
+                Side::Sell => &self.asks,
+            };
+            let (price_level, created) = price_levels.get_or_insert(price);
+            if created {
+                created_levels.push((side, price));
+            }
+            let acceptance = self.next_acceptance();
+            price_level.add_order(OrderType::Standard {
+                id: order_id,
+                price,
+                quantity,
+                side,
+                timestamp,
+                time_in_force: TimeInForce::Gtc,
+                extra_fields: (),
+            });
+            price_levels.queue_changed(price);
+            self.order_locations.insert(order_id, (price, side));
+            self.order_sequences.insert(order_id, acceptance);
+            self.executions.open(
+                order_id,
+                ExecutionState {
+                    original_quantity: quantity,
+                    ..ExecutionState::default()
+                },
+            );
+            order_ids.push(order_id);
+        }
+
+        self.cache.invalidate();
+        self.bump_version();
+        self.update_memory_pressure();
+        for (side, price) in created_levels {
+            self.publish_level_added(side, price);
+        }
+        Ok(order_ids)
+    }
+
+    /// Add a new order to the book, automatically matching it if it's aggressive.
+    ///
+    /// An order entered under the id of a resting order is rejected with
+    /// `DuplicateOrderId`, or replaces the resting order if the book was set
+    /// to with [`set_duplicate_order_id_action`](Self::set_duplicate_order_id_action).
+    pub fn add_order(&self, order: OrderType<T>) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order_with_constraints(order, OrderConstraints::default())
+    }
+
+    /// Add a new order to the book with additional execution constraints.
+    ///
+    /// An all-or-none order only trades if it can be filled in full right away;
+    /// otherwise it rests untouched (or is rejected if its time in force is
+    /// immediate). Once resting, incoming orders skip it unless they can take
+    /// it entirely.
+    ///
+    /// An order with a minimum execution quantity that crosses the book but
+    /// cannot execute at least that quantity on entry is cancelled. Once
+    /// resting, incoming orders skip it unless they can execute at least that
+    /// quantity against it.
+    ///
+    /// Orders that cannot be accepted are reported to the event listener as
+    /// `OrderRejected` events before the error is returned.
+    pub fn add_order_with_constraints(
+        &self,
+        order: OrderType<T>,
+        constraints: OrderConstraints,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.add_order_as(order, constraints, None)
+    }
+
+    /// Add an order, recording `account_id` as its owner if it rests
+    pub(super) fn add_order_as(
+        &self,
+        order: OrderType<T>,
+        constraints: OrderConstraints,
+        account_id: Option<&str>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        #[cfg(feature = "metrics")]
+        let _timer = self.metrics.timer(Operation::Add);
+        let order_id = order.id();
+        // Begun before the kill switch is checked, so throwing it waits for us
+        let _write = self.writes.begin();
+        self.check_kill_switch(account_id)
+            .map_err(|error| self.reject_order(order_id, error))?;
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter
+                .check(account_id, self.now())
+                .map_err(|error| self.reject_order(order_id, error))?;
+        }
+        // Admitted: a final status recorded under a finished id no longer applies
+        self.executions.reopen(order_id);
+        self.advance_session();
+        if self.has_compact_storage() {
+            let unsupported = if constraints != OrderConstraints::default() {
+                Some("execution constraints")
+            } else {
+                account_id.map(|_| "orders for accounts")
+            };
+            if let Some(feature) = unsupported {
+                return Err(self.reject_order(order_id, self.compact_unsupported(feature)));
+            }
+        }
+        if let Some(storage) = self.compact_storage() {
+            return self
+                .add_compact_order(storage, order)
+                .map_err(|error| self.reject_order(order_id, error));
+        }
+        let added = self
+            .try_add_order(order, constraints, account_id)
+            .map_err(|error| self.reject_order(order_id, error))?;
+        // Left over if the order traded in full or was cancelled on entry
+        self.reentries.remove(&order_id);
+        self.stats.record_added();
+        Ok(added)
+    }
+
+    /// Add an order an amendment took out of the book back in under its id.
+    ///
+    /// The order's old entry can still stand in the queue of the level it
+    /// returns to, ahead of its new place, so the level's own FIFO matching is
+    /// not used for it while other orders rest there.
+    fn reenter_order(
+        &self,
+        order: OrderType<T>,
+        constraints: OrderConstraints,
+        account_id: Option<&str>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        self.reentries.insert(order.id());
+        self.add_order_as(order, constraints, account_id)
+    }
+
+    /// Make way for an order entered under `order_id` while an order with the
+    /// same id rests: the resting order is cancelled if `options` replace
+    /// duplicates, and the new one refused otherwise.
+    ///
+    /// Two new orders submitted concurrently under one unused id are not
+    /// serialized against each other; ids are expected to be unique per sender.
+    pub(super) fn admit_order_id(
+        &self,
+        order_id: OrderId,
+        options: &OrderBookOptions,
+    ) -> Result<(), OrderBookError> {
+        if !self.order_locations.contains_key(&order_id) {
+            return Ok(());
+        }
+        match options.duplicate_order_ids {
+            DuplicateOrderIdAction::Reject => Err(OrderBookError::DuplicateOrderId { order_id }),
+            DuplicateOrderIdAction::Replace => {
+                trace!(
+                    "Order book {}: Replacing resting order {} entered again",
+                    self.symbol, order_id
+                );
+                self.cancel_order(order_id)?;
+                self.reentries.insert(order_id);
+                Ok(())
+            }
+        }
+    }
+
+    fn try_add_order(
+        &self,
+        mut order: OrderType<T>,
+        constraints: OrderConstraints,
+        account_id: Option<&str>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let _write = self.writes.begin();
+        let options = self.options();
 
         trace!(
             "Order book {}: Adding order {} at price {}",
@@ -465,12 +968,40 @@ where
             order.price()
         );
 
-        if self.has_expired(&order) {
+        self.admit_order_id(order.id(), &options.options)?;
+        // Priced before anything reads the price
+        order = self.price_market_to_limit(order)?;
+
+        if self.has_expired_under(&order, &options.options) {
             return Err(OrderBookError::InvalidOperation {
                 message: "Order has already expired".to_string(),
             });
         }
 
+        if constraints.short_sale {
+            self.check_short_sale(
+                order.id(),
+                order.side(),
+                order.price(),
+                order.total_quantity(),
+            )?;
+        }
+
+        // During an auction call orders rest without matching, and immediate
+        // orders have nothing to execute against
+        let state = self.check_order_entry()?;
+        if state.is_auction_call() && order.is_immediate() {
+            return Err(OrderBookError::InvalidTradingState { state });
+        }
+        // Auction orders only while their auction is called
+        if let Some(auction) = constraints.auction
+            && !auction.is_for(state)
+        {
+            return Err(OrderBookError::InvalidTradingState { state });
+        }
+
+        self.check_far_order_under_pressure(order.price(), order.side())?;
+
         if order.is_post_only() && self.will_cross_market(order.price(), order.side()) {
             return Err(OrderBookError::PriceCrossing {
                 price: order.price(),
@@ -483,72 +1014,229 @@ where
             });
         }
 
-        // For FOK orders, first check if the entire quantity can be matched without altering the book.
-        if order.is_fill_or_kill() {
+        // The order may not sweep beyond the maximum match depth
+        let match_limit = self.depth_capped_limit(order.side(), Some(order.price()), &options);
+
+        // For MEQ orders, check that the marketable part can execute at least the minimum quantity.
+        if constraints.min_quantity.is_some() && !state.is_auction_call() {
+            let required = constraints.required_execution(order.total_quantity());
             let potential_match =
-                self.peek_match(order.side(), order.total_quantity(), Some(order.price()));
-            if potential_match < order.total_quantity() {
+                self.peek_match_with_constraints(order.side(), order.total_quantity(), match_limit);
+            if potential_match > 0 && potential_match < required {
                 return Err(OrderBookError::InsufficientLiquidity {
                     side: order.side(),
-                    requested: order.total_quantity(),
+                    requested: required,
                     available: potential_match,
                 });
             }
         }
 
-        self.cache.invalidate();
-        // Attempt to match the order immediately
-        let match_result = self.match_order(
-            order.id(),
-            order.side(),
-            order.total_quantity(), // Use total quantity for matching
-            Some(order.price()),
-        )?;
+        // For FOK and AON orders, first check if the entire quantity can be matched without altering the book.
+        let mut can_match = !state.is_auction_call();
+        if can_match && (order.is_fill_or_kill() || constraints.all_or_none) {
+            let potential_match =
+                self.peek_match_with_constraints(order.side(), order.total_quantity(), match_limit);
+            if potential_match < order.total_quantity() {
+                if order.is_immediate() {
+                    return Err(OrderBookError::InsufficientLiquidity {
+                        side: order.side(),
+                        requested: order.total_quantity(),
+                        available: potential_match,
+                    });
+                }
+                // A resting AON order must not be partially filled on entry
+                can_match = false;
+            }
+        }
 
-        if !match_result.transactions.transactions.is_empty()
-            && let Some(ref listener) = self.trade_listener
+        // The dark segment may improve on the lit prices before the lit book is reached
+        let (dark_quantity, dark_value) = if can_match
+            && options.options.dark_matching == DarkMatching::BeforeLit
+            && !order.is_post_only()
+            && !order.is_fill_or_kill()
+            && !constraints.all_or_none
+            && constraints.min_quantity.is_none()
         {
+            self.sweep_dark_pool(
+                order.id(),
+                order.side(),
+                order.total_quantity(),
+                order.price(),
+                &options,
+            )
+        } else {
+            (0, 0)
+        };
+        let lit_quantity = order.total_quantity() - dark_quantity;
+
+        // Attempt to match the order immediately
+        let match_result = if can_match && lit_quantity > 0 {
+            self.match_order_under(
+                order.id(),
+                order.side(),
+                lit_quantity,
+                match_limit,
+                &options,
+                account_id,
+            )?
+        } else {
+            MatchResult::new(order.id(), lit_quantity)
+        };
+
+        let traded = !match_result.transactions.transactions.is_empty();
+        if traded && let Some(ref listener) = self.trade_listener {
             listener(&match_result) // emit trade events to listener
         }
+        self.record_taker_notional(account_id, match_result.transactions.as_vec());
+        let remaining_quantity = match_result.remaining_quantity;
+        let executed_value: u128 = dark_value
+            + match_result
+                .transactions
+                .as_vec()
+                .iter()
+                .map(|transaction| u128::from(transaction.price) * u128::from(transaction.quantity))
+                .sum::<u128>();
+        // Nothing reads the trades past this point: the trade channel takes
+        // them, or their buffers go back to the matching pool
+        match &self.trade_channel {
+            Some(channel) if traded => {
+                channel.publish(match_result);
+            }
+            _ => self.recycle_match_result(match_result),
+        }
+
+        // A remainder stopped by the match depth would still cross the book
+        if remaining_quantity > 0
+            && !order.is_immediate()
+            && let Some(depth) = options.options.max_match_depth
+            && let Some(deepest) = match_limit.filter(|&limit| limit != order.price())
+            && self.will_cross_market(order.price(), order.side())
+        {
+            match depth.action {
+                DepthLimitAction::Cancel => {
+                    trace!(
+                        "Order book {}: Cancelling {} of order {} beyond the match depth",
+                        self.symbol,
+                        remaining_quantity,
+                        order.id()
+                    );
+                    order.set_quantity(remaining_quantity);
+                    self.finish_order(order.id(), OrderStatus::Cancelled);
+                    return Ok(Arc::new(order));
+                }
+                DepthLimitAction::Rest => set_order_price(&mut order, deepest),
+            }
+        }
 
         // If the order was not fully filled, add the remainder to the book
-        if match_result.remaining_quantity > 0 {
+        if remaining_quantity > 0 {
             if order.is_immediate() {
                 // IOC/FOK orders should not have a resting part.
                 // If FOK, it should have been fully filled or cancelled before this point.
                 // If IOC, this is the remaining part that couldn't be filled, so we just drop it.
+                if remaining_quantity < order.total_quantity() {
+                    self.finish_order(order.id(), OrderStatus::Cancelled);
+                }
                 return Err(OrderBookError::InsufficientLiquidity {
                     side: order.side(),
                     requested: order.quantity(), // Now uses the trait method
-                    available: order
-                        .quantity()
-                        .saturating_sub(match_result.remaining_quantity),
+                    available: order.quantity().saturating_sub(remaining_quantity),
                 });
             }
 
+            let execution = ExecutionState {
+                original_quantity: order.total_quantity(),
+                executed_quantity: order.total_quantity() - remaining_quantity,
+                executed_value,
+            };
+
+            let display_size = matches!(
+                order,
+                OrderType::IcebergOrder { .. } | OrderType::ReserveOrder { .. }
+            )
+            .then(|| order.visible_quantity());
+
             // Update the order with the remaining quantity
             // For iceberg orders, only update if there was actual matching (remaining < total)
-            if match_result.remaining_quantity < order.total_quantity() {
-                order.set_quantity(match_result.remaining_quantity); // Now uses the trait method
+            if remaining_quantity < order.total_quantity() {
+                order.set_quantity(remaining_quantity); // Now uses the trait method
             }
 
             let price = order.price();
             let side = order.side();
 
+            if let Some(caps) = &options.options.resting_caps
+                && let Err(error) = self.make_room(caps, side, price, remaining_quantity)
+            {
+                // A remainder that traded on entry is cancelled rather than rejected
+                if remaining_quantity == execution.original_quantity {
+                    return Err(error);
+                }
+                trace!(
+                    "Order book {}: Cancelling the remainder of order {}: {}",
+                    self.symbol,
+                    order.id(),
+                    error
+                );
+                self.finish_order(order.id(), OrderStatus::Cancelled);
+                return Ok(Arc::new(order));
+            }
+
             let price_levels = match side {
                 Side::Buy => &self.bids,
                 Side::Sell => &self.asks,
             };
 
-            let price_level = price_levels
-                .entry(price)
-                .or_insert_with(|| Arc::new(PriceLevel::new(price)));
+            let (price_level, created) = price_levels.get_or_insert(price);
 
             // Convert to unit type for PriceLevel compatibility
             let unit_order = self.convert_to_unit_type(&order);
+            // Numbered while the level is held, so the sequence follows queue order
+            let acceptance = self.next_acceptance();
             let unit_order_arc = price_level.add_order(unit_order);
+            price_levels.queue_added(price, &unit_order_arc);
             self.order_locations
                 .insert(unit_order_arc.id(), (price, side));
+            self.order_sequences.insert(unit_order_arc.id(), acceptance);
+            if let Some(display_size) = display_size {
+                self.display_sizes.insert(unit_order_arc.id(), display_size);
+            }
+            self.executions.open(unit_order_arc.id(), execution);
+            if !constraints.is_unconstrained() {
+                self.order_constraints
+                    .insert(unit_order_arc.id(), constraints);
+            }
+            // The level's FIFO matching honours neither constraints nor display
+            // sizes, nor the new place of an order whose old entry is still queued
+            let reentered = self.reentries.remove(&unit_order_arc.id()).is_some();
+            if !constraints.is_unconstrained()
+                || display_size.is_some()
+                || (reentered && price_level.order_count() > 1)
+            {
+                self.mark_irregular(unit_order_arc.id(), side, price);
+            }
+            if let Some(account_id) = account_id {
+                self.index_account(unit_order_arc.id(), account_id);
+            }
+            // Release the level entry before the pressure check reads the maps
+            drop(price_level);
+            self.cache.level_added(side, price);
+            self.bump_version();
+            self.update_memory_pressure();
+            if created {
+                self.publish_level_added(side, price);
+            }
+            if self.publishes_events() {
+                self.emit_event(&OrderBookEvent::OrderAccepted(AcceptedOrder {
+                    order_id: unit_order_arc.id(),
+                    side,
+                    price,
+                    quantity: remaining_quantity,
+                    executed_quantity: execution.executed_quantity,
+                    sequence: acceptance.sequence,
+                    accepted_at_nanos: acceptance.nanos,
+                }));
+            }
 
             // Convert back to generic type for return
             let generic_order = self.convert_from_unit_type(&unit_order_arc);
@@ -557,7 +1245,21 @@ where
             // The order was fully matched, create an Arc from the matched result
             // Note: The original order object is consumed, but we can reconstruct its essence if needed.
             // For now, we return a representation of the completed order.
+            self.finish_order(order.id(), OrderStatus::Filled);
             Ok(Arc::new(order))
         }
     }
 }
+
+/// Move an order of any type to `new_price`
+fn set_order_price<T>(order: &mut OrderType<T>, new_price: u64) {
+    match order {
+        OrderType::Standard { price, .. } => *price = new_price,
+        OrderType::IcebergOrder { price, .. } => *price = new_price,
+        OrderType::PostOnly { price, .. } => *price = new_price,
+        OrderType::TrailingStop { price, .. } => *price = new_price,
+        OrderType::PeggedOrder { price, .. } => *price = new_price,
+        OrderType::MarketToLimit { price, .. } => *price = new_price,
+        OrderType::ReserveOrder { price, .. } => *price = new_price,
+    }
+}
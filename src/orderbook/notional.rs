@@ -0,0 +1,156 @@
+//! Notional traded by a book, by aggressor side and by account, in the
+//! currency of the symbol's reference data.
+//!
+//! Tracking is off unless [`OrderBook::track_notional`] is called, which the
+//! [`OrderBookManager`](super::manager::OrderBookManager) does for the books
+//! it creates for symbols of its registry. The totals are reported by
+//! [`OrderBook::stats`] and zeroed with the other counters.
+
+use super::book::OrderBook;
+use super::registry::SymbolInfo;
+use pricelevel::{Side, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use tracing::trace;
+
+/// Notional an account traded on each side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountNotional {
+    /// Notional of the account's buys
+    pub bought: u128,
+    /// Notional of the account's sells
+    pub sold: u128,
+}
+
+/// Notional traded since tracking started or the statistics were last reset.
+///
+/// Amounts are price times quantity times the contract multiplier, in integer
+/// price units as [`SymbolInfo::notional`] computes them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotionalStats {
+    /// Currency the amounts are in
+    pub currency: String,
+    /// Contract multiplier applied to every trade
+    pub multiplier: u64,
+    /// Notional of trades an incoming buy order executed
+    pub buy_initiated: u128,
+    /// Notional of trades an incoming sell order executed
+    pub sell_initiated: u128,
+    /// Notional per account owning either side of a trade. Orders entered
+    /// without an account are only counted in the side totals
+    pub accounts: BTreeMap<String, AccountNotional>,
+}
+
+impl NotionalStats {
+    /// Notional of every trade
+    pub fn total(&self) -> u128 {
+        self.buy_initiated + self.sell_initiated
+    }
+
+    fn add_for_account(&mut self, account_id: &str, side: Side, notional: u128) {
+        let account = self.accounts.entry(account_id.to_string()).or_default();
+        match side {
+            Side::Buy => account.bought += notional,
+            Side::Sell => account.sold += notional,
+        }
+    }
+}
+
+/// Accumulator behind [`NotionalStats`]
+#[derive(Debug)]
+pub(super) struct NotionalTracker {
+    stats: Mutex<NotionalStats>,
+}
+
+impl NotionalTracker {
+    fn lock(&self) -> MutexGuard<'_, NotionalStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(super) fn snapshot(&self) -> NotionalStats {
+        self.lock().clone()
+    }
+
+    pub(super) fn reset(&self) {
+        let mut stats = self.lock();
+        stats.buy_initiated = 0;
+        stats.sell_initiated = 0;
+        stats.accounts.clear();
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Accumulate the notional traded from now on in the currency and with
+    /// the contract multiplier of `info`, restarting from zero if it was
+    /// already tracked
+    pub fn track_notional(&mut self, info: &SymbolInfo) {
+        trace!(
+            "Order book {}: Tracking notional in {}",
+            self.symbol, info.currency
+        );
+        self.notional = Some(NotionalTracker {
+            stats: Mutex::new(NotionalStats {
+                currency: info.currency.clone(),
+                multiplier: info.multiplier,
+                ..NotionalStats::default()
+            }),
+        });
+    }
+
+    /// Count lit trades towards the side totals and the accounts of their
+    /// makers. Called while the makers are still indexed
+    pub(super) fn record_trade_notional(&self, transactions: &[Transaction]) {
+        let Some(tracker) = &self.notional else {
+            return;
+        };
+        if transactions.is_empty() {
+            return;
+        }
+        let mut stats = tracker.lock();
+        let multiplier = stats.multiplier;
+        for transaction in transactions {
+            let notional = notional_of(transaction, multiplier);
+            match transaction.taker_side {
+                Side::Buy => stats.buy_initiated += notional,
+                Side::Sell => stats.sell_initiated += notional,
+            }
+            if let Some(account_id) = self.order_account(transaction.maker_order_id) {
+                stats.add_for_account(&account_id, transaction.taker_side.opposite(), notional);
+            }
+        }
+    }
+
+    /// Count the trades of an incoming order towards the account it was
+    /// entered for
+    pub(super) fn record_taker_notional(
+        &self,
+        account_id: Option<&str>,
+        transactions: &[Transaction],
+    ) {
+        let (Some(tracker), Some(account_id)) = (&self.notional, account_id) else {
+            return;
+        };
+        if transactions.is_empty() {
+            return;
+        }
+        let mut stats = tracker.lock();
+        let multiplier = stats.multiplier;
+        for transaction in transactions {
+            stats.add_for_account(
+                account_id,
+                transaction.taker_side,
+                notional_of(transaction, multiplier),
+            );
+        }
+    }
+}
+
+fn notional_of(transaction: &Transaction, multiplier: u64) -> u128 {
+    u128::from(transaction.price) * u128::from(transaction.quantity) * u128::from(multiplier)
+}
@@ -1,8 +1,13 @@
 //! Order book operations like adding, modifying and canceling orders
 
 use super::book::OrderBook;
+use super::constraints::OrderConstraints;
 use super::error::OrderBookError;
+use super::price_scale::RoundingMode;
+use super::session::AuctionTimeInForce;
 use pricelevel::{MatchResult, OrderId, OrderType, Side, TimeInForce};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use std::sync::Arc;
 use tracing::trace;
 
@@ -26,7 +31,7 @@ where
             price,
             quantity,
             side,
-            timestamp: crate::utils::current_time_millis(),
+            timestamp: self.now(),
             time_in_force,
             extra_fields,
         };
@@ -56,7 +61,7 @@ where
             visible_quantity,
             hidden_quantity,
             side,
-            timestamp: crate::utils::current_time_millis(),
+            timestamp: self.now(),
             time_in_force,
             extra_fields,
         };
@@ -83,7 +88,7 @@ where
             price,
             quantity,
             side,
-            timestamp: crate::utils::current_time_millis(),
+            timestamp: self.now(),
             time_in_force,
             extra_fields,
         };
@@ -94,6 +99,215 @@ where
         self.add_order(order)
     }
 
+    /// Add an all-or-none limit order to the book.
+    ///
+    /// The order is only executed if it can be filled in full. When that is not
+    /// possible on entry it rests in the book, where incoming orders may only
+    /// trade with it if they can take its whole quantity.
+    pub fn add_all_or_none_order(
+        &self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let extra_fields: T = extra_fields.unwrap_or_default();
+        let order = OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force,
+            extra_fields,
+        };
+        trace!(
+            "Adding all-or-none order {} {} {} {} {}",
+            id, price, quantity, side, time_in_force
+        );
+        self.add_order_with_constraints(order, OrderConstraints::all_or_none())
+    }
+
+    /// Add a limit order with a minimum execution quantity (MEQ).
+    ///
+    /// The order only trades when at least `min_quantity` can be executed in a
+    /// single match. If it crosses the book on entry but less than that is
+    /// available it is cancelled; while resting, smaller incoming orders skip it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_min_quantity_order(
+        &self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        min_quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        if min_quantity == 0 || min_quantity > quantity {
+            return Err(self.reject_order(
+                id,
+                OrderBookError::InvalidOperation {
+                    message: format!(
+                        "Minimum quantity {min_quantity} must be between 1 and the order quantity {quantity}"
+                    ),
+                },
+            ));
+        }
+        let extra_fields: T = extra_fields.unwrap_or_default();
+        let order = OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force,
+            extra_fields,
+        };
+        trace!(
+            "Adding minimum quantity order {} {} {} (min {}) {} {}",
+            id, price, quantity, min_quantity, side, time_in_force
+        );
+        self.add_order_with_constraints(order, OrderConstraints::min_quantity(min_quantity))
+    }
+
+    /// Add a limit order restricted to an auction.
+    ///
+    /// The order is rejected with `InvalidTradingState` unless the book is in
+    /// the call of the auction `auction` names. It rests without matching until
+    /// the uncross, and whatever the auction leaves of it expires as the call
+    /// ends.
+    pub fn add_auction_order(
+        &self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        auction: AuctionTimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let extra_fields: T = extra_fields.unwrap_or_default();
+        let order = OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp: self.now(),
+            time_in_force: TimeInForce::Gtc,
+            extra_fields,
+        };
+        trace!(
+            "Adding auction order {} {} {} {} {}",
+            id, price, quantity, side, auction
+        );
+        self.add_order_with_constraints(order, OrderConstraints::auction(auction))
+    }
+
+    /// Add a limit order priced in decimal units.
+    ///
+    /// The price is converted through the book's `PriceScale` using the given
+    /// rounding mode. Returns the exact integer price the order was placed at
+    /// together with the resulting order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_limit_order_f64(
+        &self,
+        id: OrderId,
+        price: f64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        rounding: RoundingMode,
+        extra_fields: Option<T>,
+    ) -> Result<(u64, Arc<OrderType<T>>), OrderBookError> {
+        let integer_price = self
+            .price_scale
+            .to_integer_price(price, rounding, side == Side::Buy)
+            .map_err(|error| self.reject_order(id, error))?;
+        trace!(
+            "Converted decimal price {} to {} using {:?}",
+            price, integer_price, rounding
+        );
+        let order = self.add_limit_order(
+            id,
+            integer_price,
+            quantity,
+            side,
+            time_in_force,
+            extra_fields,
+        )?;
+        Ok((integer_price, order))
+    }
+
+    /// Add a limit order priced with an exact `Decimal`.
+    ///
+    /// Like [`OrderBook::add_limit_order_f64`], but the price is converted
+    /// without going through floating point.
+    #[cfg(feature = "decimal")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_limit_order_decimal(
+        &self,
+        id: OrderId,
+        price: Decimal,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        rounding: RoundingMode,
+        extra_fields: Option<T>,
+    ) -> Result<(u64, Arc<OrderType<T>>), OrderBookError> {
+        let integer_price = self
+            .price_scale
+            .decimal_to_integer_price(price, rounding, side == Side::Buy)
+            .map_err(|error| self.reject_order(id, error))?;
+        trace!(
+            "Converted decimal price {} to {} using {:?}",
+            price, integer_price, rounding
+        );
+        let order = self.add_limit_order(
+            id,
+            integer_price,
+            quantity,
+            side,
+            time_in_force,
+            extra_fields,
+        )?;
+        Ok((integer_price, order))
+    }
+
+    /// Add a post-only order priced in decimal units.
+    ///
+    /// See [`OrderBook::add_limit_order_f64`] for how the price is converted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_post_only_order_f64(
+        &self,
+        id: OrderId,
+        price: f64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        rounding: RoundingMode,
+        extra_fields: Option<T>,
+    ) -> Result<(u64, Arc<OrderType<T>>), OrderBookError> {
+        let integer_price = self
+            .price_scale
+            .to_integer_price(price, rounding, side == Side::Buy)
+            .map_err(|error| self.reject_order(id, error))?;
+        trace!(
+            "Converted decimal price {} to {} using {:?}",
+            price, integer_price, rounding
+        );
+        let order = self.add_post_only_order(
+            id,
+            integer_price,
+            quantity,
+            side,
+            time_in_force,
+            extra_fields,
+        )?;
+        Ok((integer_price, order))
+    }
+
     /// Submit a simple market order
     pub fn submit_market_order(
         &self,
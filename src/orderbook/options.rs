@@ -0,0 +1,224 @@
+//! Hot-reloadable book options, stamped with a configuration version
+
+use super::book::OrderBook;
+use super::caps::RestingCaps;
+use super::circuit_breaker::SweepGuard;
+use super::dark::DarkMatching;
+use super::events::OrderBookEvent;
+use super::fees::FeeSchedule;
+use super::pegs::MidpointPeg;
+use super::quotes::QuoteProtection;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use tracing::trace;
+
+/// Options of a book that can be changed while it is trading
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBookOptions {
+    /// Round lot size. Trades for less than it are flagged as odd-lot; 0 disables the flag
+    pub round_lot: u64,
+    /// Market close timestamp (milliseconds since epoch) after which DAY orders are expired
+    pub market_close_timestamp: Option<u64>,
+    /// Maker and taker fees charged on every trade; no fees are computed when unset
+    #[serde(default)]
+    pub fee_schedule: Option<FeeSchedule>,
+    /// Most price levels a single aggressive order may sweep; unlimited when unset
+    #[serde(default)]
+    pub max_match_depth: Option<MatchDepthLimit>,
+    /// How midpoint pegs are rounded when repriced
+    #[serde(default)]
+    pub midpoint_peg: MidpointPeg,
+    /// When the dark segment trades relative to the lit book
+    #[serde(default)]
+    pub dark_matching: DarkMatching,
+    /// Bounds on the orders resting in the book; unbounded when unset
+    #[serde(default)]
+    pub resting_caps: Option<RestingCaps>,
+    /// What becomes of an order entered under the id of a resting order
+    #[serde(default)]
+    pub duplicate_order_ids: DuplicateOrderIdAction,
+    /// Where a reserve order goes in its level's queue when the matcher
+    /// refreshes its visible quantity from its reserve
+    #[serde(default)]
+    pub reserve_refresh: ReserveRefresh,
+    /// Halts trading when too much of one side is swept within a time window;
+    /// no halt is ever tripped when unset
+    #[serde(default)]
+    pub sweep_guard: Option<SweepGuard>,
+    /// Pulls the quotes of an owner filled too often or for too much within a
+    /// time window; quotes are never pulled when unset
+    #[serde(default)]
+    pub quote_protection: Option<QuoteProtection>,
+}
+
+/// Where a reserve order goes in its level's queue when the matcher refreshes
+/// its visible quantity from its reserve. Iceberg orders always go to the back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReserveRefresh {
+    /// The refreshed order loses its time priority, as new quantity would
+    #[default]
+    BackOfQueue,
+    /// The refreshed order keeps its place in the queue
+    KeepPlace,
+}
+
+/// What becomes of an order entered under the id of an order already resting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DuplicateOrderIdAction {
+    /// The new order is rejected with `DuplicateOrderId`
+    #[default]
+    Reject,
+    /// The resting order is cancelled and the new one takes its place, as a
+    /// cancel-replace sent without a new id
+    Replace,
+}
+
+/// What becomes of the part of an aggressive limit order that would sweep
+/// beyond the maximum match depth. Market orders never rest, so their
+/// remainder is always left unfilled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthLimitAction {
+    /// The remainder is cancelled
+    #[default]
+    Cancel,
+    /// The remainder rests at the price of the deepest level the order could
+    /// reach, which it emptied, so it does not cross the book
+    Rest,
+}
+
+/// Bound on how many price levels a single aggressive order may sweep, both
+/// as a limit on the work of one match and to model venue protections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchDepthLimit {
+    /// Most levels an order may match against; at least one is always allowed
+    pub max_levels: usize,
+    /// What becomes of a limit order's remainder beyond the last level
+    pub action: DepthLimitAction,
+}
+
+impl MatchDepthLimit {
+    /// Sweep at most `max_levels` levels, cancelling the remainder
+    pub fn new(max_levels: usize) -> Self {
+        Self {
+            max_levels,
+            action: DepthLimitAction::Cancel,
+        }
+    }
+
+    /// Choose what becomes of the remainder
+    pub fn with_action(mut self, action: DepthLimitAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// A set of options together with the configuration version that installed it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedOptions {
+    /// Configuration version, starting at 0 and incremented on every change
+    pub version: u64,
+    /// The options in force under this version
+    pub options: OrderBookOptions,
+}
+
+/// The options in force, swapped as a whole so readers always see a consistent set
+#[derive(Default)]
+pub(super) struct OptionsCell {
+    current: RwLock<Arc<VersionedOptions>>,
+}
+
+impl OptionsCell {
+    /// The options in force right now
+    pub(super) fn load(&self) -> Arc<VersionedOptions> {
+        Arc::clone(
+            &self
+                .current
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Derive new options from the current ones and install them under the next version
+    fn update(&self, change: impl FnOnce(&mut OrderBookOptions)) -> Arc<VersionedOptions> {
+        let mut current = self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut options = current.options.clone();
+        change(&mut options);
+        *current = Arc::new(VersionedOptions {
+            version: current.version + 1,
+            options,
+        });
+        Arc::clone(&current)
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// The options in force and their configuration version.
+    ///
+    /// Every operation reads the options once when it starts and keeps using
+    /// them until it completes, so an order that is being added or matched
+    /// while the options are reloaded runs entirely under the old version,
+    /// and only operations starting afterwards see the new one.
+    pub fn options(&self) -> Arc<VersionedOptions> {
+        self.options.load()
+    }
+
+    /// Version of the options in force
+    pub fn config_version(&self) -> u64 {
+        self.options.load().version
+    }
+
+    /// Replace the options in force, returning the new configuration version.
+    ///
+    /// An `OptionsChanged` event carrying the new version is published to the
+    /// event listener.
+    pub fn reload_options(&self, options: OrderBookOptions) -> u64 {
+        self.update_options(|current| *current = options)
+    }
+
+    /// Limit how many price levels a single aggressive order may sweep, or
+    /// lift the limit with `None`. Returns the new configuration version
+    pub fn set_max_match_depth(&self, limit: Option<MatchDepthLimit>) -> u64 {
+        self.update_options(|options| options.max_match_depth = limit)
+    }
+
+    /// Choose what becomes of an order entered under the id of a resting
+    /// order. Returns the new configuration version
+    pub fn set_duplicate_order_id_action(&self, action: DuplicateOrderIdAction) -> u64 {
+        self.update_options(|options| options.duplicate_order_ids = action)
+    }
+
+    /// Choose where reserve orders go in the queue when the matcher refreshes
+    /// them. Returns the new configuration version
+    pub fn set_reserve_refresh(&self, refresh: ReserveRefresh) -> u64 {
+        self.update_options(|options| options.reserve_refresh = refresh)
+    }
+
+    /// Set or remove the guard halting trading when the book is swept too
+    /// fast. Returns the new configuration version
+    pub fn set_sweep_guard(&self, guard: Option<SweepGuard>) -> u64 {
+        self.update_options(|options| options.sweep_guard = guard)
+    }
+
+    /// Set or remove the protection pulling the quotes of owners filled too
+    /// fast. Returns the new configuration version
+    pub fn set_quote_protection(&self, protection: Option<QuoteProtection>) -> u64 {
+        self.update_options(|options| options.quote_protection = protection)
+    }
+
+    /// Change some of the options in force, returning the new configuration version
+    pub(super) fn update_options(&self, change: impl FnOnce(&mut OrderBookOptions)) -> u64 {
+        let installed = self.options.update(change);
+        trace!(
+            "Order book {}: Installed options version {}: {:?}",
+            self.symbol, installed.version, installed.options
+        );
+        self.emit_event(&OrderBookEvent::OptionsChanged(installed.as_ref().clone()));
+        installed.version
+    }
+}
@@ -0,0 +1,260 @@
+//! Repricing of pegged orders, which follow a reference price instead of
+//! keeping the price they were entered at.
+//!
+//! A pegged order rests at the price it carries until
+//! [`OrderBook::reprice_pegged_orders`] moves it to its reference price plus
+//! its offset. Midpoint pegs rarely land on the tick grid, so how they round
+//! is configured with a [`MidpointPeg`] in the book options.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use pricelevel::{OrderId, OrderType, OrderUpdate, PegReferenceType, Side};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Direction a midpoint between two valid prices is rounded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MidpointRounding {
+    /// In favour of the resting peg: buys round down and sells round up
+    #[default]
+    TowardMaker,
+    /// In favour of whoever trades against the peg: buys round up and sells
+    /// round down
+    TowardTaker,
+    /// To the nearest valid price, and an exact half to the even multiple of
+    /// the increment
+    Bankers,
+}
+
+/// Whether a midpoint peg may rest between ticks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubTickHandling {
+    /// The peg is rounded to a multiple of the tick size
+    #[default]
+    RoundToTick,
+    /// The peg may rest at any whole price unit, as venues that execute
+    /// midpoint orders at sub-tick prices allow
+    AllowSubTick,
+}
+
+/// How midpoint pegs are priced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MidpointPeg {
+    /// Direction the midpoint is rounded in when it is not a valid price
+    pub rounding: MidpointRounding,
+    /// Increment the midpoint is rounded to
+    pub sub_tick: SubTickHandling,
+}
+
+impl MidpointPeg {
+    /// Midpoint pegs rounded as `rounding` says
+    pub fn new(rounding: MidpointRounding, sub_tick: SubTickHandling) -> Self {
+        Self { rounding, sub_tick }
+    }
+
+    /// Price of a midpoint peg on `side` between `best_bid` and `best_ask`, or
+    /// `None` when the market is locked or crossed and has no midpoint.
+    ///
+    /// The price always improves on both touches or sits on the peg's own
+    /// touch: when rounding would reach the opposite touch, the midpoint is
+    /// rounded the other way instead, so the peg never takes liquidity
+    /// without price improvement.
+    pub fn price(&self, side: Side, best_bid: u64, best_ask: u64, tick_size: u64) -> Option<u64> {
+        if best_bid >= best_ask {
+            return None;
+        }
+        let increment = match self.sub_tick {
+            SubTickHandling::RoundToTick => u128::from(tick_size.max(1)),
+            SubTickHandling::AllowSubTick => 1,
+        };
+        // Twice the midpoint, so a half unit stays exact
+        let doubled = u128::from(best_bid) + u128::from(best_ask);
+        let steps = doubled / (2 * increment);
+        let remainder = doubled % (2 * increment);
+        let lower = steps * increment;
+        let price = if remainder == 0 {
+            lower
+        } else {
+            let upper = lower + increment;
+            let rounded = match (self.rounding, side) {
+                (MidpointRounding::TowardMaker, Side::Buy)
+                | (MidpointRounding::TowardTaker, Side::Sell) => lower,
+                (MidpointRounding::TowardMaker, Side::Sell)
+                | (MidpointRounding::TowardTaker, Side::Buy) => upper,
+                (MidpointRounding::Bankers, _) => match remainder.cmp(&increment) {
+                    std::cmp::Ordering::Less => lower,
+                    std::cmp::Ordering::Greater => upper,
+                    std::cmp::Ordering::Equal if steps % 2 == 0 => lower,
+                    std::cmp::Ordering::Equal => upper,
+                },
+            };
+            // The midpoint lies strictly inside the spread, so the other
+            // rounding never reaches the opposite touch
+            match side {
+                Side::Buy if rounded >= u128::from(best_ask) => lower,
+                Side::Sell if rounded <= u128::from(best_bid) => upper,
+                _ => rounded,
+            }
+        };
+        u64::try_from(price).ok()
+    }
+}
+
+/// A pegged order moved by a repricing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepricedOrder {
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Price the order rested at before
+    pub old_price: u64,
+    /// Price the order was moved to
+    pub new_price: u64,
+}
+
+/// Reference prices read once for a whole repricing
+struct References {
+    best_bid: Option<u64>,
+    best_ask: Option<u64>,
+    last_trade: Option<u64>,
+    tick_size: u64,
+    midpoint: MidpointPeg,
+}
+
+impl References {
+    /// Price a pegged order should rest at, or `None` if its reference is
+    /// missing or the offset takes it below zero
+    fn target(
+        &self,
+        side: Side,
+        reference: PegReferenceType,
+        reference_price_offset: i64,
+    ) -> Option<u64> {
+        let reference_price = match reference {
+            PegReferenceType::BestBid => self.best_bid?,
+            PegReferenceType::BestAsk => self.best_ask?,
+            PegReferenceType::LastTrade => self.last_trade?,
+            PegReferenceType::MidPrice => {
+                self.midpoint
+                    .price(side, self.best_bid?, self.best_ask?, self.tick_size)?
+            }
+        };
+        match reference_price.checked_add_signed(reference_price_offset)? {
+            0 => None,
+            price => Some(price),
+        }
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// How midpoint pegs are priced
+    pub fn midpoint_peg(&self) -> MidpointPeg {
+        self.options().options.midpoint_peg
+    }
+
+    /// Change how midpoint pegs are priced, returning the new configuration
+    /// version. Resting pegs keep their price until the next repricing
+    pub fn set_midpoint_peg(&self, midpoint_peg: MidpointPeg) -> u64 {
+        self.update_options(|options| options.midpoint_peg = midpoint_peg)
+    }
+
+    /// Move every resting pegged order to its reference price plus its offset,
+    /// returning the orders that moved.
+    ///
+    /// The best bid and ask a peg follows are those of the orders that are not
+    /// pegged, so pegs never chase each other's prices, and every reference
+    /// is read once before any order moves. A moved order loses its time
+    /// priority and is matched like a new order if its new price crosses the
+    /// book. Orders whose reference is missing, such as a midpoint peg in a
+    /// one-sided or locked market, keep their price.
+    pub fn reprice_pegged_orders(&self) -> Vec<RepricedOrder> {
+        let references = References {
+            best_bid: self.unpegged_touch(Side::Buy),
+            best_ask: self.unpegged_touch(Side::Sell),
+            last_trade: self.last_trade_price(),
+            tick_size: self.price_scale.tick_size,
+            midpoint: self.midpoint_peg(),
+        };
+        // Collect first: repricing takes the level entries the iteration holds
+        let moves: Vec<RepricedOrder> = self
+            .bids
+            .iter()
+            .chain(self.asks.iter())
+            .flat_map(|level| self.queued_orders(&level))
+            .filter_map(|order| match *order {
+                OrderType::PeggedOrder {
+                    id,
+                    price,
+                    side,
+                    reference_price_offset,
+                    reference_price_type,
+                    ..
+                } => references
+                    .target(side, reference_price_type, reference_price_offset)
+                    .filter(|target| *target != price)
+                    .map(|target| RepricedOrder {
+                        order_id: id,
+                        old_price: price,
+                        new_price: target,
+                    }),
+                _ => None,
+            })
+            .collect();
+
+        let mut repriced = Vec::with_capacity(moves.len());
+        for repricing in moves {
+            match self.reprice_order(repricing) {
+                Ok(true) => repriced.push(repricing),
+                // The order traded or was cancelled since it was collected
+                Ok(false) => {}
+                Err(error) => trace!(
+                    "Order book {}: Could not reprice pegged order {}: {}",
+                    self.symbol, repricing.order_id, error
+                ),
+            }
+        }
+        repriced
+    }
+
+    /// Best price of a side among the levels holding an order that is not pegged
+    fn unpegged_touch(&self, side: Side) -> Option<u64> {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let prices = levels.iter().filter_map(|level| {
+            level
+                .iter_orders()
+                .iter()
+                .any(|order| !matches!(**order, OrderType::PeggedOrder { .. }))
+                .then(|| level.price())
+        });
+        match side {
+            Side::Buy => prices.max(),
+            Side::Sell => prices.min(),
+        }
+    }
+
+    /// Move one pegged order, returning false if it no longer rests where it
+    /// was found
+    fn reprice_order(&self, repricing: RepricedOrder) -> Result<bool, OrderBookError> {
+        let still_resting = self
+            .order_locations
+            .get(&repricing.order_id)
+            .is_some_and(|location| location.0 == repricing.old_price);
+        if !still_resting {
+            return Ok(false);
+        }
+        trace!(
+            "Order book {}: Repricing pegged order {} from {} to {}",
+            self.symbol, repricing.order_id, repricing.old_price, repricing.new_price
+        );
+        self.update_order(OrderUpdate::UpdatePrice {
+            order_id: repricing.order_id,
+            new_price: repricing.new_price,
+        })?;
+        Ok(true)
+    }
+}
@@ -1,11 +1,84 @@
-use pricelevel::OrderId;
+use pricelevel::{OrderId, Transaction};
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Sizing of the vectors the matching engine borrows from its pool.
+///
+/// The pooled vectors are kept per thread and shared by every book matching
+/// on that thread; each book sizes the vectors it allocates and trims the
+/// ones it returns according to its own configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Capacity of a vector allocated when the pool is empty
+    pub initial_capacity: usize,
+    /// Largest capacity a vector may keep when returned to the pool. Vectors
+    /// grown beyond it by a large match are shrunk back, releasing the memory
+    pub max_capacity: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            initial_capacity: 32,
+            max_capacity: 4_096,
+        }
+    }
+}
+
+/// How the matching pool of a book has been used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStats {
+    /// Vectors taken from the pool
+    pub hits: u64,
+    /// Vectors allocated because the pool was empty
+    pub misses: u64,
+    /// Vectors that had to reallocate while in use, a sign `initial_capacity` is too small
+    pub growths: u64,
+}
+
+/// Pool usage counters of one book
+#[derive(Debug, Default)]
+pub(super) struct PoolCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    growths: AtomicU64,
+}
+
+impl PoolCounters {
+    pub(super) fn snapshot(&self) -> PoolStats {
+        PoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            growths: self.growths.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_checkout(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Most pairs of match result buffers a pool keeps
+const MAX_POOLED_RESULTS: usize = 16;
+
+/// The transaction and filled order vectors of a recycled `MatchResult`
+type ResultBuffers = (Vec<Transaction>, Vec<OrderId>);
 
 /// A memory pool for reusing vectors to reduce allocations in hot paths.
+///
+/// Besides the working vectors of a match, it keeps the buffers of match
+/// results handed back with `OrderBook::recycle_match_result` and of the
+/// results price levels build for their part of a match, so a match builds
+/// its result without allocating. Result buffers are not counted in
+/// [`PoolStats`].
 #[derive(Debug)]
 pub struct MatchingPool {
     filled_orders_pool: RefCell<Vec<Vec<OrderId>>>,
     price_vec_pool: RefCell<Vec<Vec<u64>>>,
+    result_pool: RefCell<Vec<ResultBuffers>>,
 }
 
 impl MatchingPool {
@@ -14,36 +87,136 @@ impl MatchingPool {
         MatchingPool {
             filled_orders_pool: RefCell::new(Vec::with_capacity(4)),
             price_vec_pool: RefCell::new(Vec::with_capacity(4)),
+            result_pool: RefCell::new(Vec::with_capacity(4)),
         }
     }
 
     /// Retrieves a vector for filled orders from the pool.
-    pub fn get_filled_orders_vec(&self) -> Vec<OrderId> {
-        self.filled_orders_pool
-            .borrow_mut()
-            .pop()
-            .unwrap_or_else(|| Vec::with_capacity(16))
+    pub(super) fn get_filled_orders_vec(
+        &self,
+        config: &PoolConfig,
+        counters: &PoolCounters,
+    ) -> Vec<OrderId> {
+        checkout(&self.filled_orders_pool, config, counters)
     }
 
-    /// Returns a filled orders vector to the pool for reuse.
-    pub fn return_filled_orders_vec(&self, mut vec: Vec<OrderId>) {
-        vec.clear();
-        self.filled_orders_pool.borrow_mut().push(vec);
+    /// Returns a filled orders vector to the pool for reuse. `checked_out_capacity`
+    /// is the capacity it had when it was retrieved.
+    pub(super) fn return_filled_orders_vec(
+        &self,
+        vec: Vec<OrderId>,
+        checked_out_capacity: usize,
+        config: &PoolConfig,
+        counters: &PoolCounters,
+    ) {
+        give_back(
+            &self.filled_orders_pool,
+            vec,
+            checked_out_capacity,
+            config,
+            counters,
+        );
     }
 
     /// Retrieves a vector for prices from the pool.
-    pub fn get_price_vec(&self) -> Vec<u64> {
-        self.price_vec_pool
-            .borrow_mut()
-            .pop()
-            .unwrap_or_else(|| Vec::with_capacity(32))
+    pub(super) fn get_price_vec(&self, config: &PoolConfig, counters: &PoolCounters) -> Vec<u64> {
+        checkout(&self.price_vec_pool, config, counters)
+    }
+
+    /// Returns a price vector to the pool for reuse. `checked_out_capacity` is
+    /// the capacity it had when it was retrieved.
+    pub(super) fn return_price_vec(
+        &self,
+        vec: Vec<u64>,
+        checked_out_capacity: usize,
+        config: &PoolConfig,
+        counters: &PoolCounters,
+    ) {
+        give_back(
+            &self.price_vec_pool,
+            vec,
+            checked_out_capacity,
+            config,
+            counters,
+        );
+    }
+
+    /// Retrieves empty buffers for the transactions and filled orders of a
+    /// match result. Without recycled buffers they are new vectors that have
+    /// not allocated yet.
+    pub(super) fn get_result_buffers(&self) -> ResultBuffers {
+        self.result_pool.borrow_mut().pop().unwrap_or_default()
+    }
+
+    /// Returns the buffers of a match result the caller is done with
+    pub(super) fn return_result_buffers(
+        &self,
+        (mut transactions, mut filled): ResultBuffers,
+        config: &PoolConfig,
+    ) {
+        let mut pool = self.result_pool.borrow_mut();
+        if pool.len() >= MAX_POOLED_RESULTS
+            || (transactions.capacity() == 0 && filled.capacity() == 0)
+        {
+            return;
+        }
+        transactions.clear();
+        transactions.shrink_to(config.max_capacity);
+        filled.clear();
+        filled.shrink_to(config.max_capacity);
+        pool.push((transactions, filled));
+    }
+
+    /// Number of pooled vectors and the bytes their capacity takes, result
+    /// buffers included
+    pub fn retained(&self) -> (usize, u64) {
+        let filled = self.filled_orders_pool.borrow();
+        let prices = self.price_vec_pool.borrow();
+        let results = self.result_pool.borrow();
+        let vectors = filled.len() + prices.len() + 2 * results.len();
+        let bytes = filled
+            .iter()
+            .map(|vec| vec.capacity() * size_of::<OrderId>())
+            .chain(prices.iter().map(|vec| vec.capacity() * size_of::<u64>()))
+            .chain(results.iter().map(|(transactions, filled)| {
+                transactions.capacity() * size_of::<Transaction>()
+                    + filled.capacity() * size_of::<OrderId>()
+            }))
+            .sum::<usize>();
+        (vectors, bytes as u64)
+    }
+
+    /// Drops every pooled vector, releasing its memory.
+    pub fn clear(&self) {
+        self.filled_orders_pool.borrow_mut().clear();
+        self.price_vec_pool.borrow_mut().clear();
+        self.result_pool.borrow_mut().clear();
     }
+}
+
+fn checkout<V>(
+    pool: &RefCell<Vec<Vec<V>>>,
+    config: &PoolConfig,
+    counters: &PoolCounters,
+) -> Vec<V> {
+    let pooled = pool.borrow_mut().pop();
+    counters.record_checkout(pooled.is_some());
+    pooled.unwrap_or_else(|| Vec::with_capacity(config.initial_capacity))
+}
 
-    /// Returns a price vector to the pool for reuse.
-    pub fn return_price_vec(&self, mut vec: Vec<u64>) {
-        vec.clear();
-        self.price_vec_pool.borrow_mut().push(vec);
+fn give_back<V>(
+    pool: &RefCell<Vec<Vec<V>>>,
+    mut vec: Vec<V>,
+    checked_out_capacity: usize,
+    config: &PoolConfig,
+    counters: &PoolCounters,
+) {
+    if vec.capacity() > checked_out_capacity {
+        counters.growths.fetch_add(1, Ordering::Relaxed);
     }
+    vec.clear();
+    vec.shrink_to(config.max_capacity);
+    pool.borrow_mut().push(vec);
 }
 
 impl Default for MatchingPool {
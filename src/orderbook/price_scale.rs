@@ -0,0 +1,208 @@
+//! Conversion between decimal prices and the integer prices stored in the book
+
+use super::error::OrderBookError;
+#[cfg(feature = "decimal")]
+use rust_decimal::{Decimal, RoundingStrategy, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+/// Relative tolerance used to absorb binary floating point noise
+/// (e.g. `100.1 * 100.0 == 10009.999999999998`) before rounding.
+const FLOAT_TOLERANCE: f64 = 1e-9;
+
+/// Most decimal places a price scale can carry: `10^18` is the largest power
+/// of ten that fits in a `u64` integer price
+pub const MAX_PRICE_DECIMALS: u32 = 18;
+
+/// How a decimal price that does not fall on a tick is turned into an integer price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundingMode {
+    /// Round towards the lower tick
+    Down,
+    /// Round towards the upper tick
+    Up,
+    /// Round to the nearest tick, halfway cases away from zero
+    Nearest,
+    /// Round away from the opposite side so the order is never more aggressive
+    /// than requested: down for buys, up for sells
+    Passive,
+    /// Reject prices that are not exactly on a tick
+    Exact,
+}
+
+/// Describes how decimal prices map to the integer prices used by the book.
+///
+/// An integer price is the decimal price multiplied by `10^decimals`, and it
+/// must be a multiple of `tick_size`. The default scale (no decimals, tick of 1)
+/// maps decimal prices to whole numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceScale {
+    /// Number of decimal places carried by integer prices
+    pub decimals: u32,
+    /// Minimum price increment, expressed in integer price units
+    pub tick_size: u64,
+}
+
+impl Default for PriceScale {
+    fn default() -> Self {
+        Self {
+            decimals: 0,
+            tick_size: 1,
+        }
+    }
+}
+
+impl PriceScale {
+    /// Create a new price scale
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `tick_size` is zero or
+    /// `decimals` exceeds [`MAX_PRICE_DECIMALS`].
+    pub fn new(decimals: u32, tick_size: u64) -> Result<Self, OrderBookError> {
+        if tick_size == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Tick size must be greater than zero".to_string(),
+            });
+        }
+        if decimals > MAX_PRICE_DECIMALS {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!(
+                    "A price scale carries at most {MAX_PRICE_DECIMALS} decimals, got {decimals}"
+                ),
+            });
+        }
+        Ok(Self {
+            decimals,
+            tick_size,
+        })
+    }
+
+    /// Multiplier between decimal and integer prices
+    pub fn multiplier(&self) -> f64 {
+        10f64.powi(self.decimals as i32)
+    }
+
+    /// Convert a decimal price into an integer price on the tick grid.
+    ///
+    /// `is_buy` is only consulted for `RoundingMode::Passive`.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if the price is not finite, is
+    /// negative, does not fit in a `u64`, or is off-tick with `RoundingMode::Exact`.
+    pub fn to_integer_price(
+        &self,
+        price: f64,
+        rounding: RoundingMode,
+        is_buy: bool,
+    ) -> Result<u64, OrderBookError> {
+        if !price.is_finite() || price < 0.0 {
+            return Err(OrderBookError::InvalidPrice {
+                price,
+                reason: "price must be a finite, non-negative number".to_string(),
+            });
+        }
+
+        let ticks = snap(price * self.multiplier() / self.tick_size as f64);
+        let rounded = match rounding {
+            RoundingMode::Down => ticks.floor(),
+            RoundingMode::Up => ticks.ceil(),
+            RoundingMode::Nearest => ticks.round(),
+            RoundingMode::Passive if is_buy => ticks.floor(),
+            RoundingMode::Passive => ticks.ceil(),
+            RoundingMode::Exact => {
+                if ticks.fract() != 0.0 {
+                    return Err(OrderBookError::InvalidPrice {
+                        price,
+                        reason: format!(
+                            "price is not a multiple of the tick size {}",
+                            self.tick_size
+                        ),
+                    });
+                }
+                ticks
+            }
+        };
+
+        let integer_price = rounded * self.tick_size as f64;
+        if integer_price >= u64::MAX as f64 {
+            return Err(OrderBookError::InvalidPrice {
+                price,
+                reason: "price does not fit the integer price range".to_string(),
+            });
+        }
+        Ok(integer_price as u64)
+    }
+
+    /// Convert an integer price back into its decimal value
+    pub fn to_decimal_price(&self, price: u64) -> f64 {
+        price as f64 / self.multiplier()
+    }
+
+    /// Convert a `Decimal` price into an integer price on the tick grid.
+    ///
+    /// Same as [`to_integer_price`](Self::to_integer_price) but exact: no
+    /// floating point noise is involved in deciding whether a price is on a tick.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if the price is negative, does
+    /// not fit in a `u64`, or is off-tick with `RoundingMode::Exact`.
+    #[cfg(feature = "decimal")]
+    pub fn decimal_to_integer_price(
+        &self,
+        price: Decimal,
+        rounding: RoundingMode,
+        is_buy: bool,
+    ) -> Result<u64, OrderBookError> {
+        let invalid = |reason: String| OrderBookError::InvalidPrice {
+            price: price.to_f64().unwrap_or(f64::NAN),
+            reason,
+        };
+        if price.is_sign_negative() && !price.is_zero() {
+            return Err(invalid("price must be non-negative".to_string()));
+        }
+
+        let ticks = 10u64
+            .checked_pow(self.decimals)
+            .and_then(|multiplier| price.checked_mul(Decimal::from(multiplier)))
+            .and_then(|scaled| scaled.checked_div(Decimal::from(self.tick_size)))
+            .ok_or_else(|| invalid("price does not fit the integer price range".to_string()))?;
+        let rounded = match rounding {
+            RoundingMode::Down => ticks.floor(),
+            RoundingMode::Up => ticks.ceil(),
+            RoundingMode::Nearest => {
+                ticks.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            }
+            RoundingMode::Passive if is_buy => ticks.floor(),
+            RoundingMode::Passive => ticks.ceil(),
+            RoundingMode::Exact => {
+                if !ticks.fract().is_zero() {
+                    return Err(invalid(format!(
+                        "price is not a multiple of the tick size {}",
+                        self.tick_size
+                    )));
+                }
+                ticks
+            }
+        };
+
+        rounded
+            .to_u64()
+            .and_then(|ticks| ticks.checked_mul(self.tick_size))
+            .ok_or_else(|| invalid("price does not fit the integer price range".to_string()))
+    }
+
+    /// Convert an integer price back into its exact decimal value
+    #[cfg(feature = "decimal")]
+    pub fn to_decimal(&self, price: u64) -> Decimal {
+        Decimal::from_i128_with_scale(i128::from(price), self.decimals)
+    }
+}
+
+/// Snap values that are within floating point noise of an integer onto it
+fn snap(value: f64) -> f64 {
+    let nearest = value.round();
+    if (value - nearest).abs() <= FLOAT_TOLERANCE * nearest.abs().max(1.0) {
+        nearest
+    } else {
+        value
+    }
+}
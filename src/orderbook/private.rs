@@ -1,7 +1,9 @@
-use crate::{OrderBook, OrderBookError, current_time_millis};
-use pricelevel::{OrderType, PriceLevel, Side};
+use crate::orderbook::error::LevelOperation;
+use crate::orderbook::events::{LevelChange, OrderBookEvent};
+use crate::orderbook::options::OrderBookOptions;
+use crate::{OrderBook, OrderBookError};
+use pricelevel::{OrderId, OrderType, OrderUpdate, PriceLevel, Side};
 use std::sync::Arc;
-use std::sync::atomic::Ordering;
 
 impl<T> OrderBook<T>
 where
@@ -9,17 +11,18 @@ where
 {
     /// Check if an order has expired
     pub fn has_expired(&self, order: &OrderType<T>) -> bool {
-        let time_in_force = order.time_in_force();
-        let current_time = current_time_millis();
-
-        // Only check market close timestamp if we have one set
-        let market_close = if self.has_market_close.load(Ordering::Relaxed) {
-            Some(self.market_close_timestamp.load(Ordering::Relaxed))
-        } else {
-            None
-        };
+        self.has_expired_under(order, &self.options().options)
+    }
 
-        time_in_force.is_expired(current_time, market_close)
+    /// Check if an order has expired under the given options
+    pub(super) fn has_expired_under(
+        &self,
+        order: &OrderType<T>,
+        options: &OrderBookOptions,
+    ) -> bool {
+        order
+            .time_in_force()
+            .is_expired(self.now(), options.market_close_timestamp)
     }
 
     /// Check if there would be a price crossing
@@ -44,162 +47,271 @@ where
         };
 
         // Get or create the price level
-        let price_level = book_side
-            .entry(price)
-            .or_insert_with(|| PriceLevel::new(price).into())
-            .value()
-            .clone();
+        let (price_level, created) = book_side.get_or_insert(price);
+        let price_level = price_level.value().clone();
 
         // Convert OrderType<T> to OrderType<()> for compatibility with current PriceLevel API
         let unit_order = self.convert_to_unit_type(&*order);
         let _added_order = price_level.add_order(unit_order);
+        book_side.queue_changed(price);
         // The location is stored as (price, side) for efficient retrieval in cancel_order
         self.order_locations.insert(order_id, (price, side));
+        self.cache.level_added(side, price);
+        if created {
+            self.publish_level_added(side, price);
+        }
 
         Ok(order)
     }
 
+    /// Remove the level at `price` if it has no orders left, discarding the
+    /// cached best price if it was that level
+    pub(super) fn remove_level_if_empty(&self, side: Side, price: u64) {
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        if price_levels.remove_if_empty(price) {
+            self.cache.level_removed(side, price);
+            self.publish_levels_removed(side, &[price]);
+        }
+    }
+
+    /// Remove the levels at `prices` that have no orders left, discarding the
+    /// cached best price once if any of them was the best level
+    pub(super) fn remove_levels_if_empty(&self, side: Side, prices: &[u64]) {
+        let price_levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let removed: Vec<u64> = prices
+            .iter()
+            .copied()
+            .filter(|&price| price_levels.remove_if_empty(price))
+            .collect();
+        self.cache.levels_removed(side, &removed);
+        self.publish_levels_removed(side, &removed);
+    }
+
+    /// Publish a `LevelAdded` event for the level just created at `price`
+    pub(super) fn publish_level_added(&self, side: Side, price: u64) {
+        if self.publishes_events() {
+            self.emit_event(&OrderBookEvent::LevelAdded(LevelChange { side, price }));
+        }
+    }
+
+    /// Publish a `LevelRemoved` event for each level removed at `prices`
+    pub(super) fn publish_levels_removed(&self, side: Side, prices: &[u64]) {
+        if !self.publishes_events() {
+            return;
+        }
+        for &price in prices {
+            self.emit_event(&OrderBookEvent::LevelRemoved(LevelChange { side, price }));
+        }
+    }
+
     /// Convert `OrderType<T>` to OrderType<()> for compatibility with current PriceLevel API
     pub fn convert_to_unit_type(&self, order: &OrderType<T>) -> OrderType<()> {
-        match order {
-            OrderType::Standard {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::Standard {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: (),
-            },
-            OrderType::IcebergOrder {
-                id,
-                price,
-                visible_quantity,
-                hidden_quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::IcebergOrder {
-                id: *id,
-                price: *price,
-                visible_quantity: *visible_quantity,
-                hidden_quantity: *hidden_quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: (),
-            },
-            OrderType::PostOnly {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::PostOnly {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: (),
-            },
-            OrderType::TrailingStop {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                trail_amount,
-                last_reference_price,
-                ..
-            } => OrderType::TrailingStop {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                trail_amount: *trail_amount,
-                last_reference_price: *last_reference_price,
-                extra_fields: (),
-            },
-            OrderType::PeggedOrder {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                reference_price_offset,
-                reference_price_type,
-                ..
-            } => OrderType::PeggedOrder {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                reference_price_offset: *reference_price_offset,
-                reference_price_type: *reference_price_type,
-                extra_fields: (),
-            },
-            OrderType::MarketToLimit {
-                id,
-                price,
-                quantity,
-                side,
-                timestamp,
-                time_in_force,
-                ..
-            } => OrderType::MarketToLimit {
-                id: *id,
-                price: *price,
-                quantity: *quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                extra_fields: (),
-            },
-            OrderType::ReserveOrder {
-                id,
-                price,
-                visible_quantity,
-                hidden_quantity,
-                side,
-                timestamp,
-                time_in_force,
-                replenish_threshold,
-                replenish_amount,
-                auto_replenish,
-                ..
-            } => OrderType::ReserveOrder {
-                id: *id,
-                price: *price,
-                visible_quantity: *visible_quantity,
-                hidden_quantity: *hidden_quantity,
-                side: *side,
-                timestamp: *timestamp,
-                time_in_force: *time_in_force,
-                replenish_threshold: *replenish_threshold,
-                replenish_amount: *replenish_amount,
-                auto_replenish: *auto_replenish,
-                extra_fields: (),
-            },
+        order_to_unit_type(order)
+    }
+
+    /// Apply an update to an order of a price level, turning a failure into an
+    /// `OrderBookError::PriceLevelOperation` naming the operation, order and level
+    pub(super) fn apply_level_update(
+        &self,
+        price_level: &PriceLevel,
+        side: Side,
+        operation: LevelOperation,
+        order_id: OrderId,
+        update: OrderUpdate,
+    ) -> Result<Option<Arc<OrderType<()>>>, OrderBookError> {
+        #[cfg(test)]
+        let result = match self.level_faults.take(operation) {
+            Some(fault) => Err(fault),
+            None => price_level.update_order(update),
+        };
+        #[cfg(not(test))]
+        let result = price_level.update_order(update);
+        match side {
+            Side::Buy => self.bids.queue_changed(price_level.price()),
+            Side::Sell => self.asks.queue_changed(price_level.price()),
         }
+
+        result.map_err(|source| OrderBookError::PriceLevelOperation {
+            operation,
+            order_id,
+            side,
+            price: price_level.price(),
+            source,
+        })
+    }
+}
+
+/// Failures injected into price level updates by tests, each consumed by the
+/// next update performing its operation
+#[cfg(test)]
+#[derive(Default)]
+pub(super) struct LevelFaults {
+    pending: std::sync::Mutex<Vec<(LevelOperation, pricelevel::PriceLevelError)>>,
+}
+
+#[cfg(test)]
+impl LevelFaults {
+    /// Make the next update performing `operation` fail with `error`
+    pub(super) fn inject(&self, operation: LevelOperation, error: pricelevel::PriceLevelError) {
+        self.pending.lock().unwrap().push((operation, error));
+    }
+
+    fn take(&self, operation: LevelOperation) -> Option<pricelevel::PriceLevelError> {
+        let mut pending = self.pending.lock().unwrap();
+        let position = pending
+            .iter()
+            .position(|(pending, _)| *pending == operation)?;
+        Some(pending.remove(position).1)
+    }
+}
+
+/// Convert an `OrderType<T>` into the `OrderType<()>` stored in price levels,
+/// dropping its extra fields
+pub(super) fn order_to_unit_type<T>(order: &OrderType<T>) -> OrderType<()> {
+    match order {
+        OrderType::Standard {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::Standard {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: (),
+        },
+        OrderType::IcebergOrder {
+            id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::IcebergOrder {
+            id: *id,
+            price: *price,
+            visible_quantity: *visible_quantity,
+            hidden_quantity: *hidden_quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: (),
+        },
+        OrderType::PostOnly {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::PostOnly {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: (),
+        },
+        OrderType::TrailingStop {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            trail_amount,
+            last_reference_price,
+            ..
+        } => OrderType::TrailingStop {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            trail_amount: *trail_amount,
+            last_reference_price: *last_reference_price,
+            extra_fields: (),
+        },
+        OrderType::PeggedOrder {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            reference_price_offset,
+            reference_price_type,
+            ..
+        } => OrderType::PeggedOrder {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            reference_price_offset: *reference_price_offset,
+            reference_price_type: *reference_price_type,
+            extra_fields: (),
+        },
+        OrderType::MarketToLimit {
+            id,
+            price,
+            quantity,
+            side,
+            timestamp,
+            time_in_force,
+            ..
+        } => OrderType::MarketToLimit {
+            id: *id,
+            price: *price,
+            quantity: *quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            extra_fields: (),
+        },
+        OrderType::ReserveOrder {
+            id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            timestamp,
+            time_in_force,
+            replenish_threshold,
+            replenish_amount,
+            auto_replenish,
+            ..
+        } => OrderType::ReserveOrder {
+            id: *id,
+            price: *price,
+            visible_quantity: *visible_quantity,
+            hidden_quantity: *hidden_quantity,
+            side: *side,
+            timestamp: *timestamp,
+            time_in_force: *time_in_force,
+            replenish_threshold: *replenish_threshold,
+            replenish_amount: *replenish_amount,
+            auto_replenish: *auto_replenish,
+            extra_fields: (),
+        },
     }
 }
 
@@ -0,0 +1,469 @@
+//! Two-sided quotes for market makers: a bid and an ask entered, replaced and
+//! pulled together under a quote id chosen by the quoting firm.
+//!
+//! Quotes entered for an owner are covered by the book's
+//! [`QuoteProtection`], if set: the fills of each owner's quotes are counted
+//! over a rolling window, and once they reach the configured number or
+//! notional every quote of the owner is pulled and a `QuotesPulled` event
+//! published, so a firm quoting many series is not run over on all of them
+//! before it can react.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use super::options::OrderBookOptions;
+use pricelevel::{OrderId, Side, TimeInForce, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use tracing::trace;
+
+/// One side of a quote, as entered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteSide {
+    /// Id of the post-only order resting for this side
+    pub order_id: OrderId,
+    /// Price of the side
+    pub price: u64,
+    /// Quantity entered, before any fill
+    pub quantity: u64,
+}
+
+/// The orders of a two-sided quote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    /// Id the quote is replaced and pulled under
+    pub quote_id: String,
+    /// Firm the quote belongs to, if entered for one
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Bid, unless quoted with no quantity
+    pub bid: Option<QuoteSide>,
+    /// Ask, unless quoted with no quantity
+    pub ask: Option<QuoteSide>,
+}
+
+impl Quote {
+    fn order_ids(&self) -> impl Iterator<Item = OrderId> {
+        self.bid.iter().chain(&self.ask).map(|side| side.order_id)
+    }
+}
+
+/// Fills of an owner's quotes within a time window that pull all of them.
+/// A threshold of 0 is not checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuoteProtection {
+    /// Number of fills
+    pub max_fills: usize,
+    /// Sum of price times quantity over the fills
+    pub max_notional: u128,
+    /// Length of the rolling window, in milliseconds on the book's clock
+    pub window_ms: u64,
+}
+
+impl QuoteProtection {
+    /// Pull an owner's quotes once `max_fills` fills or `max_notional` of
+    /// notional traded against them within `window_ms`
+    pub fn new(max_fills: usize, max_notional: u128, window_ms: u64) -> Self {
+        Self {
+            max_fills,
+            max_notional,
+            window_ms,
+        }
+    }
+
+    fn is_breached_by(&self, fills: usize, notional: u128) -> bool {
+        (self.max_fills > 0 && fills >= self.max_fills)
+            || (self.max_notional > 0 && notional >= self.max_notional)
+    }
+}
+
+/// The quotes of an owner pulled by the quote protection, as published with
+/// the `QuotesPulled` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PulledQuotes {
+    /// Owner of the quotes
+    pub owner: String,
+    /// Ids of the quotes pulled
+    pub quote_ids: Vec<String>,
+    /// Fills counted within the window, the one breaching the protection included
+    pub fills: usize,
+    /// Notional of those fills
+    pub notional: u128,
+    /// When the quotes were pulled, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default)]
+struct QuoteTable {
+    quotes: HashMap<String, Quote>,
+    /// Owner of each order resting for an owned quote
+    owners: HashMap<OrderId, String>,
+    /// Fills of each owner's quotes within the protection window, as
+    /// timestamp and notional, oldest first
+    fills: HashMap<String, VecDeque<(u64, u128)>>,
+    /// Generation of each quote id, advanced whenever the quote under it is
+    /// replaced or removed
+    generations: HashMap<String, u64>,
+    next_generation: u64,
+}
+
+impl QuoteTable {
+    fn insert(&mut self, quote: Quote) {
+        if let Some(owner) = &quote.owner {
+            for order_id in quote.order_ids() {
+                self.owners.insert(order_id, owner.clone());
+            }
+        }
+        self.quotes.insert(quote.quote_id.clone(), quote);
+    }
+
+    /// Advance the generation of `quote_id`, returning the new one
+    fn claim(&mut self, quote_id: &str) -> u64 {
+        self.next_generation += 1;
+        self.generations
+            .insert(quote_id.to_string(), self.next_generation);
+        self.next_generation
+    }
+
+    fn is_current(&self, quote_id: &str, generation: u64) -> bool {
+        self.generations.get(quote_id) == Some(&generation)
+    }
+
+    fn remove(&mut self, quote_id: &str) -> Option<Quote> {
+        let quote = self.quotes.remove(quote_id)?;
+        self.claim(quote_id);
+        for order_id in quote.order_ids() {
+            self.owners.remove(&order_id);
+        }
+        Some(quote)
+    }
+
+    /// Remove every quote of `owner`, in quote id order
+    fn remove_owned(&mut self, owner: &str) -> Vec<Quote> {
+        let mut quote_ids: Vec<String> = self
+            .quotes
+            .values()
+            .filter(|quote| quote.owner.as_deref() == Some(owner))
+            .map(|quote| quote.quote_id.clone())
+            .collect();
+        quote_ids.sort_unstable();
+        self.fills.remove(owner);
+        quote_ids
+            .iter()
+            .filter_map(|quote_id| self.remove(quote_id))
+            .collect()
+    }
+}
+
+/// Quotes of a book by quote id. Never held while orders are added or
+/// cancelled, as listeners may quote again: a quote being entered claims a
+/// generation of its id and is only recorded if no other entry or cancel of
+/// the id came in between
+#[derive(Debug, Default)]
+pub(super) struct Quotes {
+    table: Mutex<QuoteTable>,
+}
+
+impl Quotes {
+    pub(super) fn clear(&self) {
+        *self.lock() = QuoteTable::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QuoteTable> {
+        self.table
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Enter a two-sided quote under `quote_id`, replacing the one already
+    /// entered under it, and return the new quote.
+    ///
+    /// Each side rests as a good-till-cancelled post-only order under a new
+    /// order id; a side quoted with no quantity is left out, so quoting none
+    /// on both sides pulls the quote. The previous orders are only cancelled
+    /// once the new prices are known to cross neither each other nor the
+    /// book, leaving the previous orders out of the check.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::PriceCrossing` if the bid is at or above the
+    /// ask, or either side would trade against the book, leaving the previous
+    /// quote in place. Returns `OrderBookError::InvalidTradingState` if the
+    /// book is not accepting orders. If the book refuses a side anyway, as
+    /// when another order crossed it in between, the error is returned with
+    /// the quote pulled. Returns `OrderBookError::InvalidOperation` with the
+    /// new sides cancelled if the quote was entered again or cancelled while
+    /// its orders were being added, as a listener may do.
+    pub fn submit_quote(
+        &self,
+        quote_id: &str,
+        bid_price: u64,
+        bid_quantity: u64,
+        ask_price: u64,
+        ask_quantity: u64,
+    ) -> Result<Quote, OrderBookError> {
+        self.enter_quote(
+            None,
+            quote_id,
+            bid_price,
+            bid_quantity,
+            ask_price,
+            ask_quantity,
+        )
+    }
+
+    /// Enter a two-sided quote for `owner`, covered by the quote protection,
+    /// as [`submit_quote`](Self::submit_quote) does.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `quote_id` is already
+    /// used by a quote of another owner or of none, and otherwise the errors
+    /// of `submit_quote`.
+    pub fn submit_quote_for(
+        &self,
+        owner: &str,
+        quote_id: &str,
+        bid_price: u64,
+        bid_quantity: u64,
+        ask_price: u64,
+        ask_quantity: u64,
+    ) -> Result<Quote, OrderBookError> {
+        self.enter_quote(
+            Some(owner),
+            quote_id,
+            bid_price,
+            bid_quantity,
+            ask_price,
+            ask_quantity,
+        )
+    }
+
+    fn enter_quote(
+        &self,
+        owner: Option<&str>,
+        quote_id: &str,
+        bid_price: u64,
+        bid_quantity: u64,
+        ask_price: u64,
+        ask_quantity: u64,
+    ) -> Result<Quote, OrderBookError> {
+        if self.has_compact_storage() {
+            return Err(self.compact_unsupported("quotes"));
+        }
+        self.advance_session();
+        self.check_order_entry()?;
+        let (previous, generation) = {
+            let mut quotes = self.quotes.lock();
+            let previous = quotes.quotes.get(quote_id);
+            if let Some(previous) = previous
+                && previous.owner.as_deref() != owner
+            {
+                return Err(OrderBookError::InvalidOperation {
+                    message: format!("Quote {quote_id} belongs to another owner"),
+                });
+            }
+            let own_bid = previous.and_then(|quote| quote.bid).map(|bid| bid.order_id);
+            let own_ask = previous.and_then(|quote| quote.ask).map(|ask| ask.order_id);
+
+            if bid_quantity > 0 && ask_quantity > 0 && bid_price >= ask_price {
+                return Err(OrderBookError::PriceCrossing {
+                    price: bid_price,
+                    side: Side::Buy,
+                    opposite_price: ask_price,
+                });
+            }
+            if bid_quantity > 0
+                && let Some(best_ask) = self.best_price_besides(Side::Sell, own_ask)
+                && bid_price >= best_ask
+            {
+                return Err(OrderBookError::PriceCrossing {
+                    price: bid_price,
+                    side: Side::Buy,
+                    opposite_price: best_ask,
+                });
+            }
+            if ask_quantity > 0
+                && let Some(best_bid) = self.best_price_besides(Side::Buy, own_bid)
+                && ask_price <= best_bid
+            {
+                return Err(OrderBookError::PriceCrossing {
+                    price: ask_price,
+                    side: Side::Sell,
+                    opposite_price: best_bid,
+                });
+            }
+            (quotes.remove(quote_id), quotes.claim(quote_id))
+        };
+
+        // Orders are cancelled and added without the table locked, as
+        // listeners may quote again
+        let _write = self.writes.begin();
+        if let Some(previous) = previous {
+            for order_id in previous.order_ids() {
+                self.cancel_order(order_id)?;
+            }
+        }
+        let mut quote = Quote {
+            quote_id: quote_id.to_string(),
+            owner: owner.map(str::to_string),
+            bid: None,
+            ask: None,
+        };
+        for (side, price, quantity) in [
+            (Side::Buy, bid_price, bid_quantity),
+            (Side::Sell, ask_price, ask_quantity),
+        ] {
+            if quantity == 0 {
+                continue;
+            }
+            let order_id = OrderId::new();
+            if let Err(error) =
+                self.add_post_only_order(order_id, price, quantity, side, TimeInForce::Gtc, None)
+            {
+                for order_id in quote.order_ids() {
+                    self.cancel_order(order_id)?;
+                }
+                return Err(error);
+            }
+            let entered = Some(QuoteSide {
+                order_id,
+                price,
+                quantity,
+            });
+            match side {
+                Side::Buy => quote.bid = entered,
+                Side::Sell => quote.ask = entered,
+            }
+        }
+
+        let current = {
+            let mut quotes = self.quotes.lock();
+            let current = quotes.is_current(quote_id, generation);
+            if current && (quote.bid.is_some() || quote.ask.is_some()) {
+                quotes.insert(quote.clone());
+            }
+            current
+        };
+        if !current {
+            // Replaced or cancelled meanwhile: the later change stands
+            for order_id in quote.order_ids() {
+                self.cancel_order(order_id)?;
+            }
+            return Err(OrderBookError::InvalidOperation {
+                message: format!("Quote {quote_id} was changed while being entered"),
+            });
+        }
+        trace!(
+            "Order book {}: Quote {} {:?} / {:?}",
+            self.symbol, quote_id, quote.bid, quote.ask
+        );
+        Ok(quote)
+    }
+
+    /// Cancel both sides of the quote entered under `quote_id`, returning it
+    /// as it was entered, or `None` if there is no such quote
+    ///
+    /// # Errors
+    /// Returns the error of [`cancel_order`](Self::cancel_order) for a side.
+    pub fn cancel_quote(&self, quote_id: &str) -> Result<Option<Quote>, OrderBookError> {
+        let Some(quote) = self.quotes.lock().remove(quote_id) else {
+            return Ok(None);
+        };
+        let _write = self.writes.begin();
+        for order_id in quote.order_ids() {
+            self.cancel_order(order_id)?;
+        }
+        Ok(Some(quote))
+    }
+
+    /// The quote entered under `quote_id`, as entered. Sides that have since
+    /// filled or been cancelled by other means are still listed
+    pub fn quote(&self, quote_id: &str) -> Option<Quote> {
+        self.quotes.lock().quotes.get(quote_id).cloned()
+    }
+
+    /// Count the fills of owned quotes among `trades` towards the quote
+    /// protection, pulling the quotes of every owner it trips for
+    pub(super) fn record_quote_fills(&self, trades: &[Transaction], options: &OrderBookOptions) {
+        let Some(protection) = options.quote_protection else {
+            return;
+        };
+        let now = self.now();
+        let mut pulled = Vec::new();
+        {
+            let mut quotes = self.quotes.lock();
+            for trade in trades {
+                let Some(owner) = quotes.owners.get(&trade.maker_order_id).cloned() else {
+                    continue;
+                };
+                let window = quotes.fills.entry(owner.clone()).or_default();
+                window.push_back((now, u128::from(trade.price) * u128::from(trade.quantity)));
+                let start = now.saturating_sub(protection.window_ms);
+                while window
+                    .front()
+                    .is_some_and(|(timestamp, _)| *timestamp < start)
+                {
+                    window.pop_front();
+                }
+                let fills = window.len();
+                let notional = window.iter().map(|(_, notional)| notional).sum();
+                if protection.is_breached_by(fills, notional) {
+                    let removed = quotes.remove_owned(&owner);
+                    let event = PulledQuotes {
+                        owner,
+                        quote_ids: removed.iter().map(|quote| quote.quote_id.clone()).collect(),
+                        fills,
+                        notional,
+                        timestamp: now,
+                    };
+                    pulled.push((event, removed));
+                }
+            }
+        }
+
+        // Cancelled without the table locked, as listeners may quote again
+        for (event, removed) in pulled {
+            trace!(
+                "Order book {}: Pulled {} quotes of {} after {} fills",
+                self.symbol,
+                event.quote_ids.len(),
+                event.owner,
+                event.fills
+            );
+            for order_id in removed.iter().flat_map(Quote::order_ids) {
+                if let Err(error) = self.cancel_order(order_id) {
+                    self.emit_event(&OrderBookEvent::PriceLevelFault(error));
+                }
+            }
+            self.emit_event(&OrderBookEvent::QuotesPulled(event));
+        }
+    }
+
+    /// Best price of `side` not given by `own` alone
+    fn best_price_besides(&self, side: Side, own: Option<OrderId>) -> Option<u64> {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        let own_price = own
+            .and_then(|order_id| self.order_locations.get(&order_id))
+            .map(|location| location.0);
+        let mut cursor = None;
+        while let Some(price) = levels.next_after(cursor) {
+            cursor = Some(price);
+            let only_own = Some(price) == own_price
+                && levels
+                    .get(&price)
+                    .is_some_and(|level| level.order_count() <= 1);
+            if !only_own {
+                return Some(price);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,188 @@
+//! Token bucket throttling of order entry, globally or per account
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tracing::trace;
+
+/// Tokens are counted in thousandths so a bucket refilling at `per_second`
+/// tokens gains exactly `per_second` units per millisecond
+const UNITS_PER_TOKEN: u64 = 1_000;
+
+/// Size and refill rate of a token bucket. Every order takes one token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Orders accepted back to back from a full bucket
+    pub burst: u32,
+    /// Tokens returned to the bucket per second, the sustained order rate
+    pub per_second: u32,
+}
+
+impl RateLimit {
+    /// A bucket holding `burst` tokens and refilling at `per_second`
+    pub fn new(burst: u32, per_second: u32) -> Self {
+        Self { burst, per_second }
+    }
+
+    fn capacity(&self) -> u64 {
+        self.burst as u64 * UNITS_PER_TOKEN
+    }
+}
+
+/// Which orders share a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateLimitScope {
+    /// One bucket for every order entering the book
+    Global,
+    /// One bucket per account. Orders added without an account share a bucket
+    PerAccount,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    units: u64,
+    refilled_at: u64,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: u64) -> Self {
+        Self {
+            units: limit.capacity(),
+            refilled_at: now,
+        }
+    }
+
+    fn try_take(&mut self, limit: &RateLimit, now: u64) -> bool {
+        // A clock going backwards refills nothing
+        let elapsed = now.saturating_sub(self.refilled_at);
+        self.units = self
+            .units
+            .saturating_add(elapsed.saturating_mul(limit.per_second as u64))
+            .min(limit.capacity());
+        self.refilled_at = self.refilled_at.max(now);
+        if self.units < UNITS_PER_TOKEN {
+            return false;
+        }
+        self.units -= UNITS_PER_TOKEN;
+        true
+    }
+}
+
+/// Throttles the orders added to a book, rejecting them with
+/// `OrderBookError::RateLimited` once their bucket is empty.
+///
+/// Buckets start full and refill continuously. Install one with
+/// [`OrderBook::set_rate_limiter`](super::OrderBook::set_rate_limiter).
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    scope: RateLimitScope,
+    shared: Mutex<Option<TokenBucket>>,
+    accounts: DashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// A limiter with one bucket for the whole book
+    pub fn global(limit: RateLimit) -> Self {
+        Self::new(limit, RateLimitScope::Global)
+    }
+
+    /// A limiter with one bucket per account
+    pub fn per_account(limit: RateLimit) -> Self {
+        Self::new(limit, RateLimitScope::PerAccount)
+    }
+
+    fn new(limit: RateLimit, scope: RateLimitScope) -> Self {
+        Self {
+            limit,
+            scope,
+            shared: Mutex::new(None),
+            accounts: DashMap::new(),
+        }
+    }
+
+    /// Size and refill rate of each bucket
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Which orders share a bucket
+    pub fn scope(&self) -> RateLimitScope {
+        self.scope
+    }
+
+    /// Take a token for an order from `account_id` at `now` (milliseconds),
+    /// returning `false` if its bucket is empty
+    pub fn try_acquire(&self, account_id: Option<&str>, now: u64) -> bool {
+        let acquired = match (self.scope, account_id) {
+            (RateLimitScope::PerAccount, Some(account_id)) => {
+                // Look up before inserting so the common case does not copy the id
+                match self.accounts.get_mut(account_id) {
+                    Some(mut bucket) => bucket.try_take(&self.limit, now),
+                    None => self
+                        .accounts
+                        .entry(account_id.to_string())
+                        .or_insert_with(|| TokenBucket::full(&self.limit, now))
+                        .try_take(&self.limit, now),
+                }
+            }
+            _ => {
+                let mut shared = self
+                    .shared
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                shared
+                    .get_or_insert_with(|| TokenBucket::full(&self.limit, now))
+                    .try_take(&self.limit, now)
+            }
+        };
+        if !acquired {
+            trace!("Rate limit exceeded for account {:?}", account_id);
+        }
+        acquired
+    }
+
+    /// Like [`try_acquire`](Self::try_acquire), returning the error the book rejects with
+    pub(super) fn check(&self, account_id: Option<&str>, now: u64) -> Result<(), OrderBookError> {
+        if self.try_acquire(account_id, now) {
+            return Ok(());
+        }
+        Err(OrderBookError::RateLimited {
+            account_id: match self.scope {
+                RateLimitScope::Global => None,
+                RateLimitScope::PerAccount => account_id.map(str::to_string),
+            },
+        })
+    }
+
+    /// Refill every bucket and forget the accounts seen so far
+    pub fn reset(&self) {
+        *self
+            .shared
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        self.accounts.clear();
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Throttle the orders added from now on with `rate_limiter`
+    pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) {
+        self.rate_limiter = Some(rate_limiter);
+    }
+
+    /// Stop throttling order entry
+    pub fn clear_rate_limiter(&mut self) {
+        self.rate_limiter = None;
+    }
+
+    /// The rate limiter installed on the book, if any
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+}
@@ -0,0 +1,177 @@
+//! Reference data shared by the books of a manager: tick and lot sizes,
+//! price precision, currency and contract multiplier per symbol
+
+use super::error::OrderBookError;
+use super::price_scale::PriceScale;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+/// Reference data of one instrument
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    /// Minimum price increment, in integer price units
+    pub tick_size: u64,
+    /// Quantities must be a multiple of it
+    pub lot_size: u64,
+    /// Number of decimal places carried by integer prices
+    pub price_precision: u32,
+    /// Currency prices are quoted in
+    pub currency: String,
+    /// Units of the underlying per unit of quantity
+    pub multiplier: u64,
+}
+
+impl SymbolInfo {
+    /// Reference data with a lot size and multiplier of 1
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `tick_size` is zero or
+    /// `price_precision` exceeds [`MAX_PRICE_DECIMALS`](super::MAX_PRICE_DECIMALS).
+    pub fn new(
+        tick_size: u64,
+        price_precision: u32,
+        currency: &str,
+    ) -> Result<Self, OrderBookError> {
+        PriceScale::new(price_precision, tick_size)?;
+        Ok(Self {
+            tick_size,
+            lot_size: 1,
+            price_precision,
+            currency: currency.to_string(),
+            multiplier: 1,
+        })
+    }
+
+    /// Require quantities to be a multiple of `lot_size`
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `lot_size` is zero.
+    pub fn with_lot_size(mut self, lot_size: u64) -> Result<Self, OrderBookError> {
+        if lot_size == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Lot size must be greater than zero".to_string(),
+            });
+        }
+        self.lot_size = lot_size;
+        Ok(self)
+    }
+
+    /// Set the contract multiplier
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `multiplier` is zero.
+    pub fn with_multiplier(mut self, multiplier: u64) -> Result<Self, OrderBookError> {
+        if multiplier == 0 {
+            return Err(OrderBookError::InvalidOperation {
+                message: "Multiplier must be greater than zero".to_string(),
+            });
+        }
+        self.multiplier = multiplier;
+        Ok(self)
+    }
+
+    /// How decimal prices of this instrument map to integer prices
+    pub fn price_scale(&self) -> PriceScale {
+        PriceScale {
+            decimals: self.price_precision,
+            tick_size: self.tick_size,
+        }
+    }
+
+    /// Check that `price` is on the tick grid and `quantity` a whole number of lots
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPriceLevel` for an off-tick price and
+    /// `OrderBookError::InvalidOperation` for a quantity that is zero or not
+    /// a multiple of the lot size.
+    pub fn validate(&self, price: u64, quantity: u64) -> Result<(), OrderBookError> {
+        if !price.is_multiple_of(self.tick_size) {
+            return Err(OrderBookError::InvalidPriceLevel(price));
+        }
+        if quantity == 0 || !quantity.is_multiple_of(self.lot_size) {
+            return Err(OrderBookError::InvalidOperation {
+                message: format!(
+                    "Quantity {quantity} is not a multiple of the lot size {}",
+                    self.lot_size
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Notional of `quantity` at the integer `price`, in integer price units
+    pub fn notional(&self, price: u64, quantity: u64) -> u128 {
+        u128::from(price) * u128::from(quantity) * u128::from(self.multiplier)
+    }
+
+    /// Notional of `quantity` at the integer `price`, in units of the currency
+    pub fn notional_value(&self, price: u64, quantity: u64) -> f64 {
+        self.notional(price, quantity) as f64 / self.price_scale().multiplier()
+    }
+}
+
+/// Reference data of every instrument, keyed by symbol
+#[derive(Debug, Default)]
+pub struct SymbolRegistry {
+    symbols: DashMap<String, SymbolInfo>,
+}
+
+impl SymbolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store the reference data of `symbol`, returning what it replaced
+    pub fn register(&self, symbol: &str, info: SymbolInfo) -> Option<SymbolInfo> {
+        self.symbols.insert(symbol.to_string(), info)
+    }
+
+    /// Reference data of `symbol`
+    pub fn get(&self, symbol: &str) -> Option<SymbolInfo> {
+        self.symbols.get(symbol).map(|info| info.clone())
+    }
+
+    /// Remove the reference data of `symbol`, returning it
+    pub fn remove(&self, symbol: &str) -> Option<SymbolInfo> {
+        self.symbols.remove(symbol).map(|(_, info)| info)
+    }
+
+    /// True if `symbol` has reference data
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.contains_key(symbol)
+    }
+
+    /// All registered symbols, sorted
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .symbols
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Number of registered symbols
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns true if no symbol is registered
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Check an order of `symbol` against its reference data; symbols without
+    /// reference data accept any order
+    ///
+    /// # Errors
+    /// See [`SymbolInfo::validate`].
+    pub fn validate(&self, symbol: &str, price: u64, quantity: u64) -> Result<(), OrderBookError> {
+        match self.symbols.get(symbol) {
+            Some(info) => info.validate(price, quantity),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Rebuild an order book from a log of operations, with stepping and breakpoints
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tracing::trace;
+
+/// An operation applied to a book, as captured in an operation log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayOperation {
+    /// Add a standard limit order
+    AddLimit {
+        /// Id of the order
+        order_id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Quantity of the order
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+        /// Time in force of the order
+        time_in_force: TimeInForce,
+    },
+    /// Add an iceberg order
+    AddIceberg {
+        /// Id of the order
+        order_id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Displayed quantity
+        visible_quantity: u64,
+        /// Hidden quantity
+        hidden_quantity: u64,
+        /// Side of the order
+        side: Side,
+        /// Time in force of the order
+        time_in_force: TimeInForce,
+    },
+    /// Add a post-only order
+    AddPostOnly {
+        /// Id of the order
+        order_id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Quantity of the order
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+        /// Time in force of the order
+        time_in_force: TimeInForce,
+    },
+    /// Submit a market order
+    Market {
+        /// Id of the order
+        order_id: OrderId,
+        /// Quantity of the order
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+    },
+    /// Cancel a resting order
+    Cancel {
+        /// Id of the order
+        order_id: OrderId,
+    },
+    /// Change the quantity of a resting order
+    UpdateQuantity {
+        /// Id of the order
+        order_id: OrderId,
+        /// New quantity
+        new_quantity: u64,
+    },
+    /// Move a resting order to a new price
+    UpdatePrice {
+        /// Id of the order
+        order_id: OrderId,
+        /// New price
+        new_price: u64,
+    },
+}
+
+/// An entry of an operation log: an operation and the sequence number it was logged under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayRecord {
+    /// Sequence number of the entry in the log
+    pub sequence: u64,
+    /// The logged operation
+    pub operation: ReplayOperation,
+}
+
+impl ReplayRecord {
+    /// Parse a log holding one JSON-encoded record per line. Blank lines are skipped.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` naming the first line that
+    /// is not a valid record.
+    pub fn parse_json_lines(input: &str) -> Result<Vec<ReplayRecord>, OrderBookError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|error| OrderBookError::SerializationError {
+                    message: format!("line {}: {error}", index + 1),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Why [`ReplayEngine::run`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayStop {
+    /// The next record has a breakpoint on its sequence number and was not applied yet
+    Breakpoint(u64),
+    /// Every record was applied
+    Finished,
+}
+
+/// Outcome of applying one record
+#[derive(Debug)]
+pub struct ReplayStep {
+    /// Sequence number of the applied record
+    pub sequence: u64,
+    /// What the book returned for the operation. A failure is part of the
+    /// history being replayed and does not stop the replay.
+    pub result: Result<(), OrderBookError>,
+}
+
+/// Rebuilds an [`OrderBook`] by applying a log of operations in order, for
+/// instance to reproduce the state of a production book around an incident.
+///
+/// The replay can advance one record at a time with [`step`](Self::step), up
+/// to a sequence number with [`run_until`](Self::run_until), or until the next
+/// breakpoint with [`run`](Self::run). The book can be inspected between calls.
+pub struct ReplayEngine<T = ()> {
+    book: OrderBook<T>,
+    records: Vec<ReplayRecord>,
+    position: usize,
+    breakpoints: BTreeSet<u64>,
+    /// Position at which `run` last stopped on a breakpoint, so resuming applies it
+    paused_at: Option<usize>,
+    failed: Vec<u64>,
+}
+
+impl<T> ReplayEngine<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create an engine replaying `records` into a new book for `symbol`
+    pub fn new(symbol: &str, records: Vec<ReplayRecord>) -> Self {
+        Self::with_book(OrderBook::new(symbol), records)
+    }
+
+    /// Create an engine replaying `records` into an existing book, for instance
+    /// one restored from a snapshot taken before the first record
+    pub fn with_book(book: OrderBook<T>, records: Vec<ReplayRecord>) -> Self {
+        Self {
+            book,
+            records,
+            position: 0,
+            breakpoints: BTreeSet::new(),
+            paused_at: None,
+            failed: Vec::new(),
+        }
+    }
+
+    /// Create an engine replaying a log of JSON lines, see
+    /// [`ReplayRecord::parse_json_lines`]
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::SerializationError` if the log cannot be parsed.
+    pub fn from_json_lines(symbol: &str, input: &str) -> Result<Self, OrderBookError> {
+        Ok(Self::new(symbol, ReplayRecord::parse_json_lines(input)?))
+    }
+
+    /// The book in its current state
+    pub fn book(&self) -> &OrderBook<T> {
+        &self.book
+    }
+
+    /// Consume the engine, returning the book in its current state
+    pub fn into_book(self) -> OrderBook<T> {
+        self.book
+    }
+
+    /// Sequence number of the next record to apply, or `None` once all were applied
+    pub fn next_sequence(&self) -> Option<u64> {
+        self.records
+            .get(self.position)
+            .map(|record| record.sequence)
+    }
+
+    /// Number of records applied so far
+    pub fn applied(&self) -> usize {
+        self.position
+    }
+
+    /// Returns true once every record was applied
+    pub fn is_finished(&self) -> bool {
+        self.position == self.records.len()
+    }
+
+    /// Sequence numbers of the applied records whose operation failed
+    pub fn failed_sequences(&self) -> &[u64] {
+        &self.failed
+    }
+
+    /// Stop [`run`](Self::run) before applying the record with this sequence number
+    pub fn add_breakpoint(&mut self, sequence: u64) {
+        self.breakpoints.insert(sequence);
+    }
+
+    /// Remove a breakpoint, returning true if it was set
+    pub fn remove_breakpoint(&mut self, sequence: u64) -> bool {
+        self.breakpoints.remove(&sequence)
+    }
+
+    /// Remove every breakpoint
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Apply the next record, returning `None` once all were applied
+    pub fn step(&mut self) -> Option<ReplayStep> {
+        let record = *self.records.get(self.position)?;
+        self.position += 1;
+        self.paused_at = None;
+
+        trace!(
+            "Replay of {}: Applying record {}: {:?}",
+            self.book.symbol(),
+            record.sequence,
+            record.operation
+        );
+        let result = apply(&self.book, record.operation);
+        if result.is_err() {
+            self.failed.push(record.sequence);
+        }
+        Some(ReplayStep {
+            sequence: record.sequence,
+            result,
+        })
+    }
+
+    /// Apply records until the next one has a breakpoint or the log is exhausted.
+    /// Calling it again after a breakpoint applies that record and carries on.
+    pub fn run(&mut self) -> ReplayStop {
+        while let Some(sequence) = self.next_sequence() {
+            if self.breakpoints.contains(&sequence) && self.paused_at != Some(self.position) {
+                self.paused_at = Some(self.position);
+                return ReplayStop::Breakpoint(sequence);
+            }
+            self.step();
+        }
+        ReplayStop::Finished
+    }
+
+    /// Apply every record up to and including the one with sequence number
+    /// `sequence`, ignoring breakpoints. Returns the number of records applied.
+    pub fn run_until(&mut self, sequence: u64) -> usize {
+        let start = self.position;
+        while self.next_sequence().is_some_and(|next| next <= sequence) {
+            self.step();
+        }
+        self.position - start
+    }
+}
+
+/// Apply a logged operation to a book
+fn apply<T>(book: &OrderBook<T>, operation: ReplayOperation) -> Result<(), OrderBookError>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    match operation {
+        ReplayOperation::AddLimit {
+            order_id,
+            price,
+            quantity,
+            side,
+            time_in_force,
+        } => book
+            .add_limit_order(order_id, price, quantity, side, time_in_force, None)
+            .map(|_| ()),
+        ReplayOperation::AddIceberg {
+            order_id,
+            price,
+            visible_quantity,
+            hidden_quantity,
+            side,
+            time_in_force,
+        } => book
+            .add_iceberg_order(
+                order_id,
+                price,
+                visible_quantity,
+                hidden_quantity,
+                side,
+                time_in_force,
+                None,
+            )
+            .map(|_| ()),
+        ReplayOperation::AddPostOnly {
+            order_id,
+            price,
+            quantity,
+            side,
+            time_in_force,
+        } => book
+            .add_post_only_order(order_id, price, quantity, side, time_in_force, None)
+            .map(|_| ()),
+        ReplayOperation::Market {
+            order_id,
+            quantity,
+            side,
+        } => book
+            .submit_market_order(order_id, quantity, side)
+            .map(|_| ()),
+        ReplayOperation::Cancel { order_id } => {
+            require_found(book.cancel_order(order_id)?, order_id)
+        }
+        ReplayOperation::UpdateQuantity {
+            order_id,
+            new_quantity,
+        } => require_found(
+            book.update_order(OrderUpdate::UpdateQuantity {
+                order_id,
+                new_quantity,
+            })?,
+            order_id,
+        ),
+        ReplayOperation::UpdatePrice {
+            order_id,
+            new_price,
+        } => require_found(
+            book.update_order(OrderUpdate::UpdatePrice {
+                order_id,
+                new_price,
+            })?,
+            order_id,
+        ),
+    }
+}
+
+/// Turn the `None` returned for an order that is not resting into an error
+fn require_found<O>(found: Option<O>, order_id: OrderId) -> Result<(), OrderBookError> {
+    found
+        .map(|_| ())
+        .ok_or_else(|| OrderBookError::OrderNotFound(order_id.to_string()))
+}
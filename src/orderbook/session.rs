@@ -0,0 +1,556 @@
+//! Trading sessions: a schedule moving the book through pre-open, continuous
+//! trading and close, with an auction uncrossing the book at the open and at
+//! the close
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use super::execution_report::OrderStatus;
+use super::expiry::ExpiredOrder;
+use super::matching::MatchContext;
+use pricelevel::{OrderId, Side, Transaction};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, RwLock};
+use tracing::trace;
+
+/// Phase of the trading day a book is in, in the order the phases follow each other
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum TradingState {
+    /// Before the pre-open: new orders are rejected
+    Closed,
+    /// Orders rest without matching until the opening auction
+    PreOpen,
+    /// Continuous trading: orders match as they arrive
+    #[default]
+    Open,
+    /// Orders rest without matching until the closing auction
+    PreClose,
+    /// After the close: new orders are rejected
+    PostClose,
+    /// Trading stopped by a circuit breaker until resumed: new orders are
+    /// rejected, cancels are still accepted. Never a phase of the schedule,
+    /// which carries on underneath
+    Halted,
+}
+
+impl TradingState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => TradingState::Closed,
+            1 => TradingState::PreOpen,
+            2 => TradingState::Open,
+            3 => TradingState::PreClose,
+            _ => TradingState::PostClose,
+        }
+    }
+
+    /// True while orders are collected for an auction instead of matched
+    pub fn is_auction_call(&self) -> bool {
+        matches!(self, TradingState::PreOpen | TradingState::PreClose)
+    }
+
+    /// True if new orders and amendments are accepted
+    pub fn accepts_orders(&self) -> bool {
+        !matches!(
+            self,
+            TradingState::Closed | TradingState::PostClose | TradingState::Halted
+        )
+    }
+}
+
+impl fmt::Display for TradingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Time in force restricting an order to an auction.
+///
+/// The order is only accepted while its auction is being called, takes part
+/// in the uncross, and expires with whatever the auction left unexecuted
+/// instead of carrying on into the next phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuctionTimeInForce {
+    /// Good for the next auction, opening or closing
+    GoodForAuction,
+    /// At the open: only for the opening auction
+    AtTheOpen,
+    /// At the close: only for the closing auction
+    AtTheClose,
+}
+
+impl AuctionTimeInForce {
+    /// Returns true if the order belongs to the auction called in `state`,
+    /// in which case it is accepted then and expires on leaving it
+    pub fn is_for(&self, state: TradingState) -> bool {
+        match self {
+            AuctionTimeInForce::GoodForAuction => state.is_auction_call(),
+            AuctionTimeInForce::AtTheOpen => state == TradingState::PreOpen,
+            AuctionTimeInForce::AtTheClose => state == TradingState::PreClose,
+        }
+    }
+}
+
+impl fmt::Display for AuctionTimeInForce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// When the phases of one trading day start (milliseconds since epoch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSchedule {
+    /// Start of the pre-open, when orders start being collected for the opening auction
+    pub pre_open: u64,
+    /// Opening auction, followed by continuous trading
+    pub open: u64,
+    /// Start of the call for the closing auction; without it continuous
+    /// trading runs until the close
+    pub pre_close: Option<u64>,
+    /// Close: the closing auction runs and DAY orders expire
+    pub close: u64,
+}
+
+impl SessionSchedule {
+    /// A day without a closing auction call
+    pub fn new(pre_open: u64, open: u64, close: u64) -> Self {
+        Self {
+            pre_open,
+            open,
+            pre_close: None,
+            close,
+        }
+    }
+
+    /// Collect orders for the closing auction from `pre_close` until the close
+    pub fn with_pre_close(mut self, pre_close: u64) -> Self {
+        self.pre_close = Some(pre_close);
+        self
+    }
+
+    /// The phase the day is in at `now`
+    pub fn state_at(&self, now: u64) -> TradingState {
+        if now < self.pre_open {
+            TradingState::Closed
+        } else if now < self.open {
+            TradingState::PreOpen
+        } else if now < self.pre_close.unwrap_or(self.close) {
+            TradingState::Open
+        } else if now < self.close {
+            TradingState::PreClose
+        } else {
+            TradingState::PostClose
+        }
+    }
+}
+
+/// Price at which the resting bids and asks would uncross, and what would trade there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionEquilibrium {
+    /// Price every auction trade executes at
+    pub price: u64,
+    /// Quantity that trades at that price
+    pub volume: u64,
+    /// Bid quantity at or above the price
+    pub bid_quantity: u64,
+    /// Ask quantity at or below the price
+    pub ask_quantity: u64,
+}
+
+impl AuctionEquilibrium {
+    /// Quantity left unmatched on the heavier side
+    pub fn imbalance(&self) -> u64 {
+        self.bid_quantity.abs_diff(self.ask_quantity)
+    }
+
+    /// True if this candidate is a better uncrossing price than `other`: more
+    /// volume, then a smaller imbalance, then closer to the reference price
+    fn beats(&self, other: &Self, reference_price: Option<u64>) -> bool {
+        if self.volume != other.volume {
+            return self.volume > other.volume;
+        }
+        if self.imbalance() != other.imbalance() {
+            return self.imbalance() < other.imbalance();
+        }
+        reference_price.is_some_and(|reference| {
+            self.price.abs_diff(reference) < other.price.abs_diff(reference)
+        })
+    }
+}
+
+/// Trades executed by an auction uncross
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuctionResult {
+    /// The uncrossing price and volume
+    pub equilibrium: AuctionEquilibrium,
+    /// Every trade of the auction, all at the uncrossing price and flagged
+    /// [`TradeCondition::AuctionCross`]
+    pub trades: Vec<Transaction>,
+}
+
+/// A change of trading state applied by the session schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTransition {
+    /// State before the change
+    pub from: TradingState,
+    /// State after the change
+    pub to: TradingState,
+    /// When the change was applied (milliseconds since epoch)
+    pub timestamp: u64,
+    /// The auction run on leaving an auction call, if anything crossed
+    pub auction: Option<AuctionResult>,
+    /// Auction orders left unexecuted on leaving their auction call, then
+    /// DAY orders expired at the close
+    pub expired: Vec<ExpiredOrder>,
+}
+
+/// Trading state of a book and the schedule driving it
+pub(super) struct SessionState {
+    state: AtomicU8,
+    schedule: RwLock<Option<SessionSchedule>>,
+    /// Held while transitions are applied, so each auction runs once
+    transition: Mutex<()>,
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            state: AtomicU8::new(TradingState::default() as u8),
+            schedule: RwLock::new(None),
+            transition: Mutex::new(()),
+        }
+    }
+}
+
+impl SessionState {
+    fn load(&self) -> TradingState {
+        TradingState::from_u8(self.state.load(Ordering::Acquire))
+    }
+
+    fn store(&self, state: TradingState) {
+        self.state.store(state as u8, Ordering::Release);
+    }
+
+    fn schedule(&self) -> Option<SessionSchedule> {
+        *self
+            .schedule
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_schedule(&self, schedule: Option<SessionSchedule>) {
+        *self
+            .schedule
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = schedule;
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// The phase of the trading day the book is in, or `Halted` while a
+    /// circuit breaker has stopped trading
+    pub fn trading_state(&self) -> TradingState {
+        if self.is_trading_halted() {
+            TradingState::Halted
+        } else {
+            self.session.load()
+        }
+    }
+
+    /// The schedule driving the trading state, if any
+    pub fn session_schedule(&self) -> Option<SessionSchedule> {
+        self.session.schedule()
+    }
+
+    /// Drive the trading state from `schedule`.
+    ///
+    /// The close becomes the market close timestamp at which DAY orders
+    /// expire. The book moves to the phase the schedule gives for the current
+    /// time on its next operation, or right away through
+    /// [`advance_session`](Self::advance_session).
+    pub fn set_session_schedule(&self, schedule: SessionSchedule) {
+        self.session.set_schedule(Some(schedule));
+        self.set_market_close_timestamp(schedule.close);
+        trace!(
+            "Order book {}: Set session schedule {:?}",
+            self.symbol, schedule
+        );
+    }
+
+    /// Stop driving the trading state from a schedule, leaving the book in
+    /// continuous trading. Orders restricted to an auction being called
+    /// expire without it running.
+    pub fn clear_session_schedule(&self) {
+        let _transition = self
+            .session
+            .transition
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.session.set_schedule(None);
+        let left = self.session.load();
+        self.session.store(TradingState::Open);
+        self.expire_auction_orders(left, self.now());
+    }
+
+    /// Apply every transition the session schedule calls for by the book's
+    /// clock, see [`advance_session_at`](Self::advance_session_at)
+    pub fn advance_session(&self) -> Vec<SessionTransition> {
+        if self.session.schedule().is_none() {
+            return Vec::new();
+        }
+        self.advance_session_at(self.now())
+    }
+
+    /// Move the book to the phase the session schedule gives for `now`,
+    /// passing through every phase in between.
+    ///
+    /// Leaving the pre-open runs the opening auction and leaving the pre-close
+    /// the closing auction, after which the orders restricted to that auction
+    /// expire; reaching the close expires DAY orders. Each
+    /// transition is published as a `TradingStateChanged` event. A schedule
+    /// placing `now` in an earlier phase, such as the next day's, moves the
+    /// book there directly.
+    ///
+    /// Called on every order entry, so a scheduled book changes phase without
+    /// a timer as long as orders arrive. A transition already being applied
+    /// by another thread is left to finish, and nothing is returned.
+    pub fn advance_session_at(&self, now: u64) -> Vec<SessionTransition> {
+        let Some(schedule) = self.session.schedule() else {
+            return Vec::new();
+        };
+        let target = schedule.state_at(now);
+        if self.session.load() == target {
+            return Vec::new();
+        }
+        // A listener adding orders from an auction event must not wait for itself
+        let Ok(_transition) = self.session.transition.try_lock() else {
+            return Vec::new();
+        };
+
+        let mut transitions = Vec::new();
+        let mut current = self.session.load();
+        if target < current {
+            // Leaving a call without running its auction still ends its orders
+            let expired = self.expire_auction_orders(current, now);
+            transitions.push(self.enter_state(current, target, now, None, expired));
+            return transitions;
+        }
+        while current < target {
+            let next = match current {
+                TradingState::Closed => TradingState::PreOpen,
+                TradingState::PreOpen => TradingState::Open,
+                TradingState::Open if schedule.pre_close.is_some() => TradingState::PreClose,
+                _ => TradingState::PostClose,
+            };
+            let auction = if current.is_auction_call() {
+                self.uncross()
+            } else {
+                None
+            };
+            let mut expired = self.expire_auction_orders(current, now);
+            if next == TradingState::PostClose {
+                expired.extend(self.expire_orders_at(now));
+            }
+            transitions.push(self.enter_state(current, next, now, auction, expired));
+            current = next;
+        }
+        transitions
+    }
+
+    fn enter_state(
+        &self,
+        from: TradingState,
+        to: TradingState,
+        timestamp: u64,
+        auction: Option<AuctionResult>,
+        expired: Vec<ExpiredOrder>,
+    ) -> SessionTransition {
+        self.session.store(to);
+        // The next trading day starts, so its first trade opens it
+        if to <= TradingState::PreOpen || to < from {
+            self.session_traded.store(false, Ordering::Relaxed);
+        }
+        trace!(
+            "Order book {}: Trading state {} -> {}",
+            self.symbol, from, to
+        );
+        let transition = SessionTransition {
+            from,
+            to,
+            timestamp,
+            auction,
+            expired,
+        };
+        self.emit_event(&OrderBookEvent::TradingStateChanged(transition.clone()));
+        transition
+    }
+
+    /// Reject order entry unless the trading state accepts new orders and
+    /// the book's kill switch is not thrown
+    pub(super) fn check_order_entry(&self) -> Result<TradingState, OrderBookError> {
+        self.check_kill_switch(None)?;
+        let state = self.trading_state();
+        if state.accepts_orders() {
+            Ok(state)
+        } else {
+            Err(OrderBookError::InvalidTradingState { state })
+        }
+    }
+
+    /// The price an auction would uncross the book at right now, if the best
+    /// bid is at or above the best ask.
+    ///
+    /// Among the level prices inside the crossed range, the one trading the
+    /// most volume wins, then the one leaving the smallest imbalance, then
+    /// the one closest to the last trade price, then the lowest.
+    pub fn auction_equilibrium(&self) -> Option<AuctionEquilibrium> {
+        let bids: Vec<(u64, u64)> = self
+            .iter_bids()
+            .map(|(price, level)| (price, level.total_quantity()))
+            .collect();
+        let asks: Vec<(u64, u64)> = self
+            .iter_asks()
+            .map(|(price, level)| (price, level.total_quantity()))
+            .collect();
+        let (best_bid, best_ask) = (bids.first()?.0, asks.first()?.0);
+        if best_bid < best_ask {
+            return None;
+        }
+
+        let mut prices: Vec<u64> = bids
+            .iter()
+            .chain(&asks)
+            .map(|(price, _)| *price)
+            .filter(|price| (best_ask..=best_bid).contains(price))
+            .collect();
+        prices.sort_unstable();
+        prices.dedup();
+
+        let reference_price = self.last_trade_price();
+        let mut best: Option<AuctionEquilibrium> = None;
+        for price in prices {
+            let bid_quantity = bids
+                .iter()
+                .take_while(|(level_price, _)| *level_price >= price)
+                .map(|(_, quantity)| quantity)
+                .sum();
+            let ask_quantity = asks
+                .iter()
+                .take_while(|(level_price, _)| *level_price <= price)
+                .map(|(_, quantity)| quantity)
+                .sum();
+            let candidate = AuctionEquilibrium {
+                price,
+                volume: u64::min(bid_quantity, ask_quantity),
+                bid_quantity,
+                ask_quantity,
+            };
+            if best.is_none_or(|best| candidate.beats(&best, reference_price)) {
+                best = Some(candidate);
+            }
+        }
+        best.filter(|equilibrium| equilibrium.volume > 0)
+    }
+
+    /// Match the crossed part of the book at the auction equilibrium price.
+    ///
+    /// Bids at or above the price take the asks at or below it in price-time
+    /// priority, every trade executing at that price. Partially filled bids
+    /// keep their place in the queue.
+    fn uncross(&self) -> Option<AuctionResult> {
+        let equilibrium = self.auction_equilibrium()?;
+        let price = equilibrium.price;
+        let options = self.options();
+
+        let takers: Vec<(u64, OrderId)> = self
+            .bids
+            .best_prices(usize::MAX)
+            .into_iter()
+            .take_while(|level_price| *level_price >= price)
+            .flat_map(|level_price| {
+                self.bids
+                    .get(&level_price)
+                    .map(|level| self.queued_orders(&level))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |order| (level_price, order.id()))
+            })
+            .collect();
+
+        let mut trades = Vec::new();
+        for (level_price, order_id) in takers {
+            let Some(order) = self
+                .bids
+                .get(&level_price)
+                .and_then(|level| level.iter_orders().into_iter().find(|o| o.id() == order_id))
+            else {
+                continue;
+            };
+            let quantity = order.visible_quantity() + order.hidden_quantity();
+            let account_id = self.order_account(order_id);
+            let context = MatchContext {
+                execution_price: Some(price),
+                account_id: account_id.as_deref(),
+                aggregate_levels: false,
+            };
+            let Ok((match_result, _)) = self.match_order_at(
+                order_id,
+                Side::Buy,
+                quantity,
+                Some(price),
+                &options,
+                context,
+            ) else {
+                continue;
+            };
+            let executed = match_result.executed_quantity();
+            if executed == 0 {
+                // The asks at or below the price are exhausted
+                break;
+            }
+
+            let filled = self.bids.get(&level_price).map(|level| {
+                self.fill_resting_order(&level, Side::Buy, &order, executed, &options)
+            });
+            match filled {
+                Some(Ok((_, fully_filled, ..))) => {
+                    self.executions.record_fill(order_id, price, executed);
+                    if fully_filled {
+                        self.forget_order(order_id);
+                        self.finish_order(order_id, OrderStatus::Filled);
+                        self.remove_level_if_empty(Side::Buy, level_price);
+                    }
+                }
+                Some(Err(error)) => self.emit_event(&OrderBookEvent::PriceLevelFault(error)),
+                None => {}
+            }
+
+            trades.extend_from_slice(match_result.transactions.as_vec());
+            if let Some(ref listener) = self.trade_listener {
+                listener(&match_result)
+            }
+            if let Some(ref channel) = self.trade_channel {
+                channel.publish(match_result);
+            }
+        }
+
+        trace!(
+            "Order book {}: Auction uncrossed {} at {} in {} trades",
+            self.symbol,
+            equilibrium.volume,
+            price,
+            trades.len()
+        );
+        Some(AuctionResult {
+            equilibrium,
+            trades,
+        })
+    }
+}
@@ -0,0 +1,276 @@
+//! Shard-per-symbol executor: every symbol of a manager is matched on one
+//! dedicated thread, chosen by hashing the symbol, so a book's data stays in
+//! the caches of the core running that thread.
+
+use super::book::OrderBook;
+use super::engine::{Command, CommandOutcome};
+use super::error::OrderBookError;
+use crossbeam_queue::ArrayQueue;
+use dashmap::DashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::trace;
+
+/// How long a shard sleeps between checks when it was not woken up explicitly
+const SHARD_PARK_TIMEOUT: Duration = Duration::from_millis(1);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The books of a manager, shared with its executors
+pub(super) type SharedBooks<T> = Arc<DashMap<String, Arc<OrderBook<T>>>>;
+
+/// Queue and counters of one shard, shared between its thread and the executor
+struct ShardQueue {
+    queue: ArrayQueue<Job>,
+    stopped: AtomicBool,
+    applied: AtomicU64,
+    failed: AtomicU64,
+}
+
+struct Shard {
+    queue: Arc<ShardQueue>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Shard {
+    fn spawn(index: usize, capacity: usize) -> Self {
+        let queue = Arc::new(ShardQueue {
+            queue: ArrayQueue::new(capacity),
+            stopped: AtomicBool::new(false),
+            applied: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        });
+        let worker = Arc::clone(&queue);
+        let thread = thread::Builder::new()
+            .name(format!("orderbook-shard-{index}"))
+            .spawn(move || {
+                loop {
+                    while let Some(job) = worker.queue.pop() {
+                        job();
+                    }
+                    // Jobs queued before the stop was seen are applied first
+                    if worker.stopped.load(Ordering::Acquire) && worker.queue.is_empty() {
+                        break;
+                    }
+                    thread::park_timeout(SHARD_PARK_TIMEOUT);
+                }
+                trace!("Order book shard {} stopped", index);
+            })
+            .expect("failed to spawn order book shard thread");
+        Self {
+            queue,
+            thread: Some(thread),
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(thread) = &self.thread {
+            thread.thread().unpark();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.queue.stopped.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+fn stopped() -> OrderBookError {
+    OrderBookError::InvalidOperation {
+        message: "the shard executor has stopped".to_string(),
+    }
+}
+
+/// Runs the work of each symbol of an [`OrderBookManager`](super::OrderBookManager)
+/// on the thread of its shard, created with
+/// [`OrderBookManager::start_executor`](super::OrderBookManager::start_executor).
+///
+/// A symbol always maps to the same shard, and each shard applies its queue
+/// in order, so requests for one symbol never run concurrently and are
+/// applied in the order they were queued. Like
+/// [`with_book`](super::OrderBookManager::with_book), work never overlaps
+/// with the manager's `snapshot_all`.
+///
+/// Dropping the executor, or calling [`shutdown`](Self::shutdown), applies
+/// what is queued and stops the shard threads.
+pub struct ShardExecutor<T = ()> {
+    books: SharedBooks<T>,
+    coordination: Arc<RwLock<()>>,
+    shards: Vec<Shard>,
+}
+
+impl<T> ShardExecutor<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Spawn `shards` threads, each queueing up to `capacity` requests
+    ///
+    /// # Panics
+    /// Panics if `shards` or `capacity` is zero, or a thread cannot be spawned.
+    pub(super) fn start(
+        books: SharedBooks<T>,
+        coordination: Arc<RwLock<()>>,
+        shards: usize,
+        capacity: usize,
+    ) -> Self {
+        assert!(shards > 0, "a shard executor needs at least one shard");
+        trace!("Starting order book executor with {} shards", shards);
+        Self {
+            books,
+            coordination,
+            shards: (0..shards)
+                .map(|index| Shard::spawn(index, capacity))
+                .collect(),
+        }
+    }
+
+    /// Number of shard threads
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Index of the shard `symbol` is matched on
+    pub fn shard_of(&self, symbol: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        symbol.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Queue `command` for the book of `symbol` without waiting for it to be
+    /// applied. Its outcome is only counted, see [`applied_count`](Self::applied_count)
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `symbol` is not managed
+    /// or the executor has stopped.
+    pub fn submit(&self, symbol: &str, command: Command<T>) -> Result<(), OrderBookError> {
+        let book = self.book(symbol)?;
+        let shard = &self.shards[self.shard_of(symbol)];
+        let queue = Arc::clone(&shard.queue);
+        let coordination = Arc::clone(&self.coordination);
+        self.enqueue(
+            shard,
+            Box::new(move || {
+                let _guard = coordination
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let counter = match command.apply_to_book(&book) {
+                    Ok(_) => &queue.applied,
+                    Err(_) => &queue.failed,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+            }),
+        )
+    }
+
+    /// Run `f` against the book of `symbol` on its shard and wait for the result
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if `symbol` is not managed
+    /// or the executor has stopped.
+    pub fn call<R, F>(&self, symbol: &str, f: F) -> Result<R, OrderBookError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&OrderBook<T>) -> R + Send + 'static,
+    {
+        let book = self.book(symbol)?;
+        let coordination = Arc::clone(&self.coordination);
+        let (reply, response) = mpsc::sync_channel(1);
+        self.enqueue(
+            &self.shards[self.shard_of(symbol)],
+            Box::new(move || {
+                let _guard = coordination
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                // The caller may have stopped waiting
+                let _ = reply.send(f(&book));
+            }),
+        )?;
+        response.recv().map_err(|_| stopped())
+    }
+
+    /// Apply `command` to the book of `symbol` on its shard and wait for its outcome
+    ///
+    /// # Errors
+    /// Returns the errors of [`call`](Self::call) and of the command itself.
+    pub fn execute(
+        &self,
+        symbol: &str,
+        command: Command<T>,
+    ) -> Result<CommandOutcome<T>, OrderBookError> {
+        self.call(symbol, move |book| command.apply_to_book(book))?
+    }
+
+    /// Number of submitted commands applied successfully, over every shard
+    pub fn applied_count(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.queue.applied.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Number of submitted commands a book rejected, over every shard
+    pub fn failed_count(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.queue.failed.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Number of requests waiting in each shard's queue
+    pub fn pending(&self) -> Vec<usize> {
+        self.shards
+            .iter()
+            .map(|shard| shard.queue.queue.len())
+            .collect()
+    }
+
+    /// Apply what is queued, then stop every shard thread and wait for them
+    pub fn shutdown(self) {
+        drop(self);
+    }
+
+    fn book(&self, symbol: &str) -> Result<Arc<OrderBook<T>>, OrderBookError> {
+        self.books
+            .get(symbol)
+            .map(|book| Arc::clone(&book))
+            .ok_or_else(|| OrderBookError::InvalidOperation {
+                message: format!("No order book for symbol {symbol}"),
+            })
+    }
+
+    fn enqueue(&self, shard: &Shard, job: Job) -> Result<(), OrderBookError> {
+        let mut pending = job;
+        loop {
+            if shard.queue.stopped.load(Ordering::Acquire) {
+                return Err(stopped());
+            }
+            match shard.queue.queue.push(pending) {
+                Ok(()) => {
+                    shard.wake();
+                    return Ok(());
+                }
+                Err(rejected) => {
+                    // Back-pressure: wait for the shard to free a slot
+                    pending = rejected;
+                    shard.wake();
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for ShardExecutor<T> {
+    fn drop(&mut self) {
+        for shard in &mut self.shards {
+            shard.stop();
+        }
+    }
+}
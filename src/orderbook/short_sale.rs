@@ -0,0 +1,126 @@
+//! Short sale orders and the price tests a short sale restriction applies to
+//! them, such as the uptick rule or SEC Rule 201's alternative uptick rule.
+//!
+//! An order is marked as a short sale through its
+//! [`OrderConstraints`](super::constraints::OrderConstraints). Each short sale
+//! is passed to the book's [`ShortSaleRule`] on entry and on every price
+//! amendment, together with whether a restriction (a circuit condition such as
+//! a 10% decline) is in force, and is rejected if the rule refuses it.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use pricelevel::{OrderId, Side};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tracing::trace;
+
+/// A short sale being entered, as a [`ShortSaleRule`] sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortSaleCheck {
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Side of the order, a sell unless the rule is applied to buys
+    pub side: Side,
+    /// Limit price of the order
+    pub price: u64,
+    /// Quantity of the order
+    pub quantity: u64,
+    /// Whether a short sale restriction is in force on the book
+    pub restricted: bool,
+    /// Price of the last trade, if any
+    pub last_trade_price: Option<u64>,
+    /// Best bid, if any
+    pub best_bid: Option<u64>,
+}
+
+/// Decides whether a short sale may be entered, returning why not otherwise
+pub type ShortSaleRule = Arc<dyn Fn(&ShortSaleCheck) -> Result<(), String> + Send + Sync>;
+
+/// Reference price a restricted short sale must be priced above
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShortSaleReference {
+    /// The last trade price, as the classic uptick rule
+    LastTrade,
+    /// The best bid, as Rule 201's alternative uptick rule
+    BestBid,
+}
+
+/// Rule refusing, while a restriction is in force, any short sale priced at
+/// or below `reference`. Short sales are unrestricted otherwise, and while
+/// the reference price is unknown
+pub fn short_sale_price_test(reference: ShortSaleReference) -> ShortSaleRule {
+    Arc::new(move |check: &ShortSaleCheck| {
+        if !check.restricted {
+            return Ok(());
+        }
+        let reference_price = match reference {
+            ShortSaleReference::LastTrade => check.last_trade_price,
+            ShortSaleReference::BestBid => check.best_bid,
+        };
+        match reference_price {
+            Some(reference_price) if check.price <= reference_price => Err(format!(
+                "Short sale at {} must be priced above {reference_price}",
+                check.price
+            )),
+            _ => Ok(()),
+        }
+    })
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Set the rule short sales are checked against. Without a rule short
+    /// sales are accepted like any other order
+    pub fn set_short_sale_rule(&mut self, rule: ShortSaleRule) {
+        self.short_sale_rule = Some(rule);
+    }
+
+    /// Put a short sale restriction in force or lift it, as a circuit
+    /// condition triggers or expires
+    pub fn set_short_sale_restriction(&self, restricted: bool) {
+        trace!(
+            "Order book {}: Short sale restriction {}",
+            self.symbol,
+            if restricted { "in force" } else { "lifted" }
+        );
+        self.short_sale_restricted
+            .store(restricted, Ordering::Relaxed);
+    }
+
+    /// Returns true while a short sale restriction is in force
+    pub fn is_short_sale_restricted(&self) -> bool {
+        self.short_sale_restricted.load(Ordering::Relaxed)
+    }
+
+    /// Check a short sale being entered against the short sale rule
+    pub(super) fn check_short_sale(
+        &self,
+        order_id: OrderId,
+        side: Side,
+        price: u64,
+        quantity: u64,
+    ) -> Result<(), OrderBookError> {
+        let Some(rule) = &self.short_sale_rule else {
+            return Ok(());
+        };
+        let check = ShortSaleCheck {
+            order_id,
+            side,
+            price,
+            quantity,
+            restricted: self.is_short_sale_restricted(),
+            last_trade_price: self.last_trade_price(),
+            best_bid: self.best_bid(),
+        };
+        rule(&check).map_err(|message| {
+            trace!(
+                "Order book {}: Short sale {} refused: {}",
+                self.symbol, order_id, message
+            );
+            OrderBookError::ShortSaleRestricted { price, message }
+        })
+    }
+}
@@ -0,0 +1,280 @@
+//! One side of the book: its price levels and a sorted index of their prices
+
+use crossbeam_skiplist::SkipSet;
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::multiple::RefMulti;
+use dashmap::mapref::one::{Ref, RefMut};
+use pricelevel::{OrderType, PriceLevel, Side};
+use std::ops::Bound;
+use std::sync::Arc;
+
+/// The price levels of one side, keyed by price, with their prices also kept
+/// in a sorted index.
+///
+/// Levels live in a `DashMap` so orders at different prices can be worked on
+/// concurrently, while the index, a lock-free skip list, answers best price
+/// and ordered traversal in O(log n) without scanning the keys or serializing
+/// writers at different prices. A level and its index entry are only added or
+/// removed while the map shard holding that price is locked, so every price in
+/// the map is in the index once the change returns; the index itself takes no
+/// lock.
+pub(super) struct BookSide {
+    side: Side,
+    levels: DashMap<u64, Arc<PriceLevel>>,
+    /// Boxed, as the skip list is cache-line aligned and would otherwise raise
+    /// the alignment of every book, which some allocators cannot honour
+    prices: Box<SkipSet<u64>>,
+    /// The levels that the level's own FIFO matching cannot take, see
+    /// [`BookSide::is_fifo`]. Prices without irregular orders are absent
+    irregular_levels: DashMap<u64, IrregularLevel>,
+}
+
+/// A price level matched by the book rather than by its own FIFO matching
+#[derive(Default)]
+struct IrregularLevel {
+    /// Number of its orders that the level's FIFO matching cannot take
+    orders: usize,
+    /// Its orders in acceptance order as the last match left them, dropped
+    /// whenever the level changes outside a match
+    queue: Option<Vec<Arc<OrderType<()>>>>,
+}
+
+impl BookSide {
+    pub(super) fn new(side: Side) -> Self {
+        Self {
+            side,
+            levels: DashMap::new(),
+            prices: Box::new(SkipSet::new()),
+            irregular_levels: DashMap::new(),
+        }
+    }
+
+    pub(super) fn get(&self, price: &u64) -> Option<Ref<'_, u64, Arc<PriceLevel>>> {
+        self.levels.get(price)
+    }
+
+    /// The level at `price`, locked exclusively until the guard is dropped
+    pub(super) fn get_mut(&self, price: &u64) -> Option<RefMut<'_, u64, Arc<PriceLevel>>> {
+        self.levels.get_mut(price)
+    }
+
+    #[cfg(test)]
+    pub(super) fn contains_key(&self, price: &u64) -> bool {
+        self.levels.contains_key(price)
+    }
+
+    /// Number of price levels
+    pub(super) fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Number of levels the map can hold without reallocating
+    pub(super) fn capacity(&self) -> usize {
+        self.levels.capacity()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// All levels, in no particular order. Use the index methods for ordered access
+    pub(super) fn iter(&self) -> impl Iterator<Item = RefMulti<'_, u64, Arc<PriceLevel>>> {
+        self.levels.iter()
+    }
+
+    /// The level at `price`, created empty if there is none, locked
+    /// exclusively until the guard is dropped, and whether it was created
+    pub(super) fn get_or_insert(&self, price: u64) -> (RefMut<'_, u64, Arc<PriceLevel>>, bool) {
+        match self.levels.entry(price) {
+            Entry::Occupied(entry) => (entry.into_ref(), false),
+            Entry::Vacant(entry) => {
+                self.prices.insert(price);
+                (entry.insert(Arc::new(PriceLevel::new(price))), true)
+            }
+        }
+    }
+
+    /// Put a level at `price`, returning the one it replaced
+    #[cfg(test)]
+    pub(super) fn insert(
+        &self,
+        price: u64,
+        price_level: Arc<PriceLevel>,
+    ) -> Option<Arc<PriceLevel>> {
+        match self.levels.entry(price) {
+            Entry::Occupied(mut entry) => {
+                self.queue_changed(price);
+                Some(entry.insert(price_level))
+            }
+            Entry::Vacant(entry) => {
+                self.prices.insert(price);
+                entry.insert(price_level);
+                None
+            }
+        }
+    }
+
+    /// Remove the level at `price` with its orders
+    pub(super) fn remove(&self, price: &u64) -> Option<(u64, Arc<PriceLevel>)> {
+        match self.levels.entry(*price) {
+            Entry::Occupied(entry) => {
+                self.prices.remove(price);
+                self.queue_changed(*price);
+                Some(entry.remove_entry())
+            }
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    /// Remove the level at `price` if it has no orders left. Checked under the
+    /// level's lock, so an order added since the level emptied keeps it alive
+    pub(super) fn remove_if_empty(&self, price: u64) -> bool {
+        match self.levels.entry(price) {
+            Entry::Occupied(entry) if entry.get().order_count() == 0 => {
+                self.prices.remove(&price);
+                self.queue_changed(price);
+                entry.remove();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove every level
+    pub(super) fn clear(&self) {
+        self.levels.retain(|price, _| {
+            self.prices.remove(price);
+            false
+        });
+        self.irregular_levels.clear();
+    }
+
+    /// Whether the level at `price` can be matched by its own FIFO matching:
+    /// none of its orders has execution constraints or a display size, and
+    /// the level's queue holds its orders in acceptance order
+    pub(super) fn is_fifo(&self, price: u64) -> bool {
+        !self.irregular_levels.contains_key(&price)
+    }
+
+    /// Count one more order at `price` that keeps the level off its FIFO matching
+    pub(super) fn add_irregular(&self, price: u64) {
+        self.irregular_levels.entry(price).or_default().orders += 1;
+    }
+
+    /// Count one order less at `price` that keeps the level off its FIFO matching
+    pub(super) fn remove_irregular(&self, price: u64) {
+        if let Entry::Occupied(mut entry) = self.irregular_levels.entry(price) {
+            entry.get_mut().orders -= 1;
+            if entry.get().orders == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Take the orders of the irregular level at `price` in acceptance order,
+    /// as the last match left them, if the level did not change since
+    pub(super) fn take_queue(&self, price: u64) -> Option<Vec<Arc<OrderType<()>>>> {
+        self.irregular_levels.get_mut(&price)?.queue.take()
+    }
+
+    /// Keep the orders of the irregular level at `price` in acceptance order
+    /// for its next match. Must be called while the level is held
+    pub(super) fn keep_queue(&self, price: u64, queue: Vec<Arc<OrderType<()>>>) {
+        if let Some(mut level) = self.irregular_levels.get_mut(&price) {
+            level.queue = Some(queue);
+        }
+    }
+
+    /// Append an order just accepted at `price`, and so last in acceptance
+    /// order, to the queue kept for the level. Must be called while the level
+    /// is held
+    pub(super) fn queue_added(&self, price: u64, order: &Arc<OrderType<()>>) {
+        if let Some(mut level) = self.irregular_levels.get_mut(&price)
+            && let Some(queue) = &mut level.queue
+        {
+            queue.push(Arc::clone(order));
+        }
+    }
+
+    /// Drop the queue kept for the level at `price` after a change to its
+    /// orders. Must be called while the level is held
+    pub(super) fn queue_changed(&self, price: u64) {
+        if let Some(mut level) = self.irregular_levels.get_mut(&price) {
+            level.queue = None;
+        }
+    }
+
+    /// Remove the levels left without orders and release the spare capacity of
+    /// the level map, returning the prices removed
+    pub(super) fn compact(&self) -> Vec<u64> {
+        let mut removed = Vec::new();
+        self.levels.retain(|price, level| {
+            if level.order_count() > 0 {
+                return true;
+            }
+            self.prices.remove(price);
+            self.queue_changed(*price);
+            removed.push(*price);
+            false
+        });
+        self.levels.shrink_to_fit();
+        removed
+    }
+
+    /// Best price: the highest bid or the lowest ask
+    pub(super) fn best(&self) -> Option<u64> {
+        let entry = match self.side {
+            Side::Buy => self.prices.back(),
+            Side::Sell => self.prices.front(),
+        };
+        entry.map(|entry| *entry.value())
+    }
+
+    /// Worst price: the lowest bid or the highest ask
+    pub(super) fn worst(&self) -> Option<u64> {
+        let entry = match self.side {
+            Side::Buy => self.prices.front(),
+            Side::Sell => self.prices.back(),
+        };
+        entry.map(|entry| *entry.value())
+    }
+
+    /// The best price strictly worse than `after`, or the best price if `after`
+    /// is `None`. Walks the side best price first, one lookup per step
+    pub(super) fn next_after(&self, after: Option<u64>) -> Option<u64> {
+        let Some(after) = after else {
+            return self.best();
+        };
+        let entry = match self.side {
+            Side::Buy => self.prices.upper_bound(Bound::Excluded(&after)),
+            Side::Sell => self.prices.lower_bound(Bound::Excluded(&after)),
+        };
+        entry.map(|entry| *entry.value())
+    }
+
+    /// Up to `depth` prices, best first
+    pub(super) fn best_prices(&self, depth: usize) -> Vec<u64> {
+        let prices = self.prices.iter().map(|entry| *entry.value());
+        match self.side {
+            Side::Buy => prices.rev().take(depth).collect(),
+            Side::Sell => prices.take(depth).collect(),
+        }
+    }
+
+    /// The `n`th best price, counting from 1, if the side has that many levels.
+    /// The index keeps no ranks, so this walks the `n` best prices: O(n)
+    pub(super) fn nth_best_price(&self, n: usize) -> Option<u64> {
+        let index = n.checked_sub(1)?;
+        let mut prices = self.prices.iter().map(|entry| *entry.value());
+        match self.side {
+            Side::Buy => prices.rev().nth(index),
+            Side::Sell => prices.nth(index),
+        }
+    }
+
+    /// Every price in the index, lowest first, for consistency checks
+    pub(super) fn indexed_prices(&self) -> Vec<u64> {
+        self.prices.iter().map(|entry| *entry.value()).collect()
+    }
+}
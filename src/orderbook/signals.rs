@@ -0,0 +1,243 @@
+//! Signals evaluated inside the book: predicates over the quantity resting
+//! near the top of each side, with a callback run whenever one starts to hold.
+//!
+//! Registered signals are evaluated after every change to the resting orders.
+//! The visible quantity of each side is summed once per change, down to the
+//! deepest level any signal looks at, and each predicate reads its own depth
+//! from those running totals. A signal fires when its predicate becomes true
+//! and is re-armed once it turns false again, so a condition that keeps
+//! holding while the book changes is reported once.
+
+use super::book::OrderBook;
+use super::side::BookSide;
+use pricelevel::Side;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::trace;
+
+/// Visible quantity resting at the top levels of each side, as a signal sees it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepthTotals {
+    /// Number of levels of each side summed
+    pub levels: usize,
+    /// Visible quantity of the best `levels` bid levels
+    pub bid_quantity: u64,
+    /// Visible quantity of the best `levels` ask levels
+    pub ask_quantity: u64,
+}
+
+impl DepthTotals {
+    /// Bid quantity over ask quantity, or `None` without ask quantity
+    pub fn bid_ask_ratio(&self) -> Option<f64> {
+        (self.ask_quantity > 0).then(|| self.bid_quantity as f64 / self.ask_quantity as f64)
+    }
+
+    /// `(bids - asks) / (bids + asks)`, from -1 to 1, or `None` if both sides
+    /// are empty. See [`OrderBook::volume_imbalance`]
+    pub fn imbalance(&self) -> Option<f64> {
+        let total = self.bid_quantity as f64 + self.ask_quantity as f64;
+        (total > 0.0).then(|| (self.bid_quantity as f64 - self.ask_quantity as f64) / total)
+    }
+}
+
+/// Emitted when the predicate of a signal starts to hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalFired {
+    /// Symbol of the book
+    pub symbol: String,
+    /// Id returned when the signal was registered
+    pub signal_id: u64,
+    /// Totals the predicate held for
+    pub depth: DepthTotals,
+    /// Version of the book the predicate was evaluated on
+    pub version: u64,
+    /// When the signal fired, in milliseconds on the book's clock
+    pub timestamp: u64,
+}
+
+/// Condition evaluated on the totals of the levels a signal looks at
+pub type SignalPredicate = Arc<dyn Fn(&DepthTotals) -> bool + Send + Sync>;
+
+/// Callback invoked when a signal fires
+pub type SignalListener = Arc<dyn Fn(&SignalFired) + Send + Sync>;
+
+struct Signal {
+    id: u64,
+    levels: usize,
+    predicate: SignalPredicate,
+    listener: SignalListener,
+    /// The predicate held at the last evaluation
+    holding: bool,
+}
+
+#[derive(Default)]
+struct SignalTable {
+    next_id: u64,
+    signals: Vec<Signal>,
+}
+
+/// Signals registered on a book
+#[derive(Default)]
+pub(super) struct Signals {
+    /// Set while any signal is registered, so changes skip the table otherwise
+    active: AtomicBool,
+    table: Mutex<SignalTable>,
+}
+
+impl Signals {
+    fn lock(&self) -> MutexGuard<'_, SignalTable> {
+        self.table
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Register a signal over the best `levels` levels of each side, calling
+    /// `listener` each time `predicate` starts to hold, and return its id.
+    ///
+    /// The predicate is evaluated right away, without notifying, so a
+    /// condition already holding fires only after it has turned false and
+    /// true again. Listeners run on the thread that changed the book, once
+    /// the change is complete, and may read the book.
+    pub fn register_signal(
+        &self,
+        levels: usize,
+        predicate: SignalPredicate,
+        listener: SignalListener,
+    ) -> u64 {
+        let holding = predicate(&self.depth_totals(levels));
+        let mut table = self.signals.lock();
+        table.next_id += 1;
+        let id = table.next_id;
+        table.signals.push(Signal {
+            id,
+            levels,
+            predicate,
+            listener,
+            holding,
+        });
+        self.signals.active.store(true, Ordering::Release);
+        trace!(
+            "Order book {}: Signal {} registered over {} levels",
+            self.symbol, id, levels
+        );
+        id
+    }
+
+    /// Remove a signal, returning false if there was none with this id
+    pub fn remove_signal(&self, signal_id: u64) -> bool {
+        let mut table = self.signals.lock();
+        let before = table.signals.len();
+        table.signals.retain(|signal| signal.id != signal_id);
+        self.signals
+            .active
+            .store(!table.signals.is_empty(), Ordering::Release);
+        table.signals.len() < before
+    }
+
+    /// Visible quantity of the best `levels` levels of each side
+    pub fn depth_totals(&self, levels: usize) -> DepthTotals {
+        if let Some(storage) = self.compact_storage() {
+            let total = |side| {
+                storage
+                    .depth(side, levels)
+                    .iter()
+                    .map(|(_, quantity)| quantity)
+                    .sum()
+            };
+            return DepthTotals {
+                levels,
+                bid_quantity: total(Side::Buy),
+                ask_quantity: total(Side::Sell),
+            };
+        }
+        let totals = |side: &BookSide| -> u64 {
+            side.best_prices(levels)
+                .into_iter()
+                .filter_map(|price| side.get(&price))
+                .map(|price_level| price_level.visible_quantity())
+                .sum()
+        };
+        DepthTotals {
+            levels,
+            bid_quantity: totals(&self.bids),
+            ask_quantity: totals(&self.asks),
+        }
+    }
+
+    /// Evaluate the registered signals after a change, notifying those that
+    /// started to hold
+    pub(super) fn evaluate_signals(&self) {
+        if !self.signals.active.load(Ordering::Acquire) {
+            return;
+        }
+        let fired = {
+            let mut table = self.signals.lock();
+            let deepest = table
+                .signals
+                .iter()
+                .map(|signal| signal.levels)
+                .max()
+                .unwrap_or(0);
+            let bids = running_totals(&self.bids, deepest);
+            let asks = running_totals(&self.asks, deepest);
+            let version = self.version();
+            let mut fired = Vec::new();
+            for signal in &mut table.signals {
+                let depth = DepthTotals {
+                    levels: signal.levels,
+                    bid_quantity: total_of(&bids, signal.levels),
+                    ask_quantity: total_of(&asks, signal.levels),
+                };
+                let holding = (signal.predicate)(&depth);
+                if holding && !signal.holding {
+                    fired.push((
+                        Arc::clone(&signal.listener),
+                        SignalFired {
+                            symbol: self.symbol.clone(),
+                            signal_id: signal.id,
+                            depth,
+                            version,
+                            timestamp: self.now(),
+                        },
+                    ));
+                }
+                signal.holding = holding;
+            }
+            fired
+        };
+        // Called with the table released, so listeners may register or remove signals
+        for (listener, signal) in fired {
+            trace!(
+                "Order book {}: Signal {} fired on {:?}",
+                self.symbol, signal.signal_id, signal.depth
+            );
+            listener(&signal);
+        }
+    }
+}
+
+/// Cumulative visible quantity of the best `depth` levels of a side
+fn running_totals(side: &BookSide, depth: usize) -> Vec<u64> {
+    side.best_prices(depth)
+        .into_iter()
+        .filter_map(|price| side.get(&price))
+        .scan(0u64, |total, price_level| {
+            *total += price_level.visible_quantity();
+            Some(*total)
+        })
+        .collect()
+}
+
+/// Quantity of the best `levels` levels given the running totals of a side
+fn total_of(running_totals: &[u64], levels: usize) -> u64 {
+    levels
+        .min(running_totals.len())
+        .checked_sub(1)
+        .map_or(0, |last| running_totals[last])
+}
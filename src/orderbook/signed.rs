@@ -0,0 +1,345 @@
+//! Signed prices, for instruments such as futures spreads that can trade
+//! below zero.
+//!
+//! The book keeps storing `u64` prices: a signed price is stored as its
+//! distance from the book's price floor. The mapping preserves order, so
+//! matching, the best price cache and every price comparison behave the same
+//! for signed prices as for unsigned ones. With the default floor of 0 the
+//! mapping is the identity.
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use pricelevel::{
+    MatchResult, OrderId, OrderType, OrderUpdate, PriceLevelSnapshot, Side, TimeInForce,
+    Transaction,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::trace;
+use uuid::Uuid;
+
+/// A price level of a [`SignedSnapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedLevel {
+    /// Signed price of the level
+    pub price: i64,
+    /// Quantity shown at the level
+    pub visible_quantity: u64,
+    /// Quantity hidden at the level
+    pub hidden_quantity: u64,
+    /// Number of orders at the level
+    pub order_count: usize,
+}
+
+/// A snapshot of the book's levels with signed prices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedSnapshot {
+    /// The symbol or identifier of the book
+    pub symbol: String,
+    /// Timestamp when the snapshot was created (milliseconds since epoch)
+    pub timestamp: u64,
+    /// Bid levels, best first
+    pub bids: Vec<SignedLevel>,
+    /// Ask levels, best first
+    pub asks: Vec<SignedLevel>,
+}
+
+/// A trade with its signed price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTrade {
+    /// Unique transaction id
+    pub transaction_id: Uuid,
+    /// Id of the aggressive order
+    pub taker_order_id: OrderId,
+    /// Id of the resting order
+    pub maker_order_id: OrderId,
+    /// Signed price the trade executed at
+    pub price: i64,
+    /// Quantity traded
+    pub quantity: u64,
+    /// Side of the aggressive order
+    pub taker_side: Side,
+    /// When the trade occurred (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Signed price stored in the book as price 0; orders are priced above it
+    pub fn price_floor(&self) -> i64 {
+        self.price_floor
+    }
+
+    /// Set the signed price the book stores as price 0, e.g. `-10_000` for a
+    /// spread that may trade above -100.00 with two decimals. Orders must be
+    /// priced above the floor, since a stored price of 0 reads as no price.
+    ///
+    /// Prices already stored would change meaning, so the floor can only be
+    /// set while no order rests in the book.
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the book holds orders.
+    pub fn set_price_floor(&mut self, floor: i64) -> Result<(), OrderBookError> {
+        if !self.bids.is_empty() || !self.asks.is_empty() {
+            return Err(OrderBookError::InvalidOperation {
+                message: "The price floor can only be changed while the book is empty".to_string(),
+            });
+        }
+        self.price_floor = floor;
+        trace!("Order book {}: Set price floor to {}", self.symbol, floor);
+        Ok(())
+    }
+
+    /// The price stored in the book for the signed `price`
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if `price` is not above the floor.
+    pub fn to_book_price(&self, price: i64) -> Result<u64, OrderBookError> {
+        let book_price = i128::from(price) - i128::from(self.price_floor);
+        if book_price <= 0 {
+            return Err(OrderBookError::InvalidPrice {
+                price: price as f64,
+                reason: format!("price must be above the price floor {}", self.price_floor),
+            });
+        }
+        Ok(book_price as u64)
+    }
+
+    /// The signed price of a price stored in the book, or `None` if it is
+    /// beyond the range of `i64`
+    pub fn to_signed_price(&self, book_price: u64) -> Option<i64> {
+        i64::try_from(i128::from(book_price) + i128::from(self.price_floor)).ok()
+    }
+
+    /// The signed price of a price stored in the book, for orders it holds
+    fn signed(&self, book_price: u64) -> Result<i64, OrderBookError> {
+        self.to_signed_price(book_price)
+            .ok_or_else(|| OrderBookError::InvalidPrice {
+                price: book_price as f64,
+                reason: format!(
+                    "price is beyond the signed range above the price floor {}",
+                    self.price_floor
+                ),
+            })
+    }
+
+    /// Add a limit order at a signed price, returning the order with its
+    /// price as stored in the book
+    pub fn add_limit_order_signed(
+        &self,
+        id: OrderId,
+        price: i64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+        extra_fields: Option<T>,
+    ) -> Result<Arc<OrderType<T>>, OrderBookError> {
+        let book_price = self
+            .to_book_price(price)
+            .map_err(|error| self.reject_order(id, error))?;
+        self.add_limit_order(id, book_price, quantity, side, time_in_force, extra_fields)
+    }
+
+    /// Apply `update` with its price given as a signed price. Quantity-only
+    /// updates and cancels carry no price and are applied as they are
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if the new price is not above
+    /// the floor, and otherwise the errors of
+    /// [`update_order`](Self::update_order).
+    pub fn update_order_signed(
+        &self,
+        update: SignedOrderUpdate,
+    ) -> Result<Option<Arc<OrderType<T>>>, OrderBookError> {
+        let update = match update {
+            SignedOrderUpdate::UpdatePrice {
+                order_id,
+                new_price,
+            } => OrderUpdate::UpdatePrice {
+                order_id,
+                new_price: self.to_book_price(new_price)?,
+            },
+            SignedOrderUpdate::UpdateQuantity {
+                order_id,
+                new_quantity,
+            } => OrderUpdate::UpdateQuantity {
+                order_id,
+                new_quantity,
+            },
+            SignedOrderUpdate::UpdatePriceAndQuantity {
+                order_id,
+                new_price,
+                new_quantity,
+            } => OrderUpdate::UpdatePriceAndQuantity {
+                order_id,
+                new_price: self.to_book_price(new_price)?,
+                new_quantity,
+            },
+            SignedOrderUpdate::Cancel { order_id } => OrderUpdate::Cancel { order_id },
+            SignedOrderUpdate::Replace {
+                order_id,
+                price,
+                quantity,
+                side,
+            } => OrderUpdate::Replace {
+                order_id,
+                price: self.to_book_price(price)?,
+                quantity,
+                side,
+            },
+        };
+        self.update_order(update)
+    }
+
+    /// Match a limit order with a signed limit price, see
+    /// [`match_limit_order`](Self::match_limit_order)
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if the limit is not above the
+    /// floor, and otherwise the errors of `match_limit_order`.
+    pub fn match_limit_order_signed(
+        &self,
+        order_id: OrderId,
+        quantity: u64,
+        side: Side,
+        limit_price: i64,
+    ) -> Result<MatchResult, OrderBookError> {
+        let limit_price = self.to_book_price(limit_price)?;
+        self.match_limit_order(order_id, quantity, side, limit_price)
+    }
+
+    /// Best bid as a signed price
+    pub fn best_bid_signed(&self) -> Option<i64> {
+        self.best_bid()
+            .and_then(|price| self.to_signed_price(price))
+    }
+
+    /// Best ask as a signed price
+    pub fn best_ask_signed(&self) -> Option<i64> {
+        self.best_ask()
+            .and_then(|price| self.to_signed_price(price))
+    }
+
+    /// Last trade price as a signed price
+    pub fn last_trade_price_signed(&self) -> Option<i64> {
+        self.last_trade_price()
+            .and_then(|price| self.to_signed_price(price))
+    }
+
+    /// A snapshot of up to `depth` levels per side with signed prices, see
+    /// [`create_snapshot`](Self::create_snapshot)
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if a level's price is beyond
+    /// the range of `i64`.
+    pub fn create_snapshot_signed(&self, depth: usize) -> Result<SignedSnapshot, OrderBookError> {
+        let snapshot = self.create_snapshot(depth);
+        let levels = |levels: &[PriceLevelSnapshot]| {
+            levels
+                .iter()
+                .map(|level| {
+                    Ok(SignedLevel {
+                        price: self.signed(level.price)?,
+                        visible_quantity: level.visible_quantity,
+                        hidden_quantity: level.hidden_quantity,
+                        order_count: level.order_count,
+                    })
+                })
+                .collect::<Result<Vec<_>, OrderBookError>>()
+        };
+        Ok(SignedSnapshot {
+            bids: levels(&snapshot.bids)?,
+            asks: levels(&snapshot.asks)?,
+            symbol: snapshot.symbol,
+            timestamp: snapshot.timestamp,
+        })
+    }
+
+    /// The trades of `match_result` with signed prices
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if a trade's price is beyond
+    /// the range of `i64`.
+    pub fn trades_signed(
+        &self,
+        match_result: &MatchResult,
+    ) -> Result<Vec<SignedTrade>, OrderBookError> {
+        match_result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|trade| self.trade_signed(trade))
+            .collect()
+    }
+
+    /// `trade` with its signed price
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidPrice` if its price is beyond the
+    /// range of `i64`.
+    pub fn trade_signed(&self, trade: &Transaction) -> Result<SignedTrade, OrderBookError> {
+        Ok(SignedTrade {
+            transaction_id: trade.transaction_id,
+            taker_order_id: trade.taker_order_id,
+            maker_order_id: trade.maker_order_id,
+            price: self.signed(trade.price)?,
+            quantity: trade.quantity,
+            taker_side: trade.taker_side,
+            timestamp: trade.timestamp,
+        })
+    }
+
+    /// The signed price `event` is about, for events carrying a price, see
+    /// [`OrderBookEvent::price`]
+    pub fn event_price_signed(&self, event: &OrderBookEvent) -> Option<i64> {
+        event.price().and_then(|price| self.to_signed_price(price))
+    }
+}
+
+/// An [`OrderUpdate`] with signed prices, see
+/// [`OrderBook::update_order_signed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignedOrderUpdate {
+    /// Move an order to a new price
+    UpdatePrice {
+        /// Id of the order
+        order_id: OrderId,
+        /// New signed price
+        new_price: i64,
+    },
+    /// Change the quantity of an order
+    UpdateQuantity {
+        /// Id of the order
+        order_id: OrderId,
+        /// New quantity
+        new_quantity: u64,
+    },
+    /// Change both the price and the quantity of an order
+    UpdatePriceAndQuantity {
+        /// Id of the order
+        order_id: OrderId,
+        /// New signed price
+        new_price: i64,
+        /// New quantity
+        new_quantity: u64,
+    },
+    /// Cancel an order
+    Cancel {
+        /// Id of the order
+        order_id: OrderId,
+    },
+    /// Cancel an order and enter a new one in its place
+    Replace {
+        /// Id of the order
+        order_id: OrderId,
+        /// Signed price of the replacement
+        price: i64,
+        /// Quantity of the replacement
+        quantity: u64,
+        /// Side of the replacement
+        side: Side,
+    },
+}
@@ -1,7 +1,8 @@
 //! Order book snapshot for market data
 
-use pricelevel::PriceLevelSnapshot;
+use pricelevel::{OrderId, PriceLevelSnapshot, Side};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use tracing::trace;
 
 /// A snapshot of the order book state at a specific point in time
@@ -102,4 +103,232 @@ impl OrderBookSnapshot {
         trace!("total_ask_value: {:?}", value);
         value
     }
+
+    /// Levels that differ between this snapshot and `other`, with the change
+    /// in quantity and order count needed to turn this one into `other`.
+    ///
+    /// Levels are matched by side and price. Each list is ordered bids first,
+    /// best price first within a side.
+    pub fn diff(&self, other: &OrderBookSnapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+        for (side, before, after) in [
+            (Side::Buy, &self.bids, &other.bids),
+            (Side::Sell, &self.asks, &other.asks),
+        ] {
+            let before = levels_by_price(before);
+            let after = levels_by_price(after);
+            let mut prices: Vec<u64> = before.keys().chain(after.keys()).copied().collect();
+            prices.sort_unstable();
+            prices.dedup();
+            if side == Side::Buy {
+                prices.reverse();
+            }
+
+            for price in prices {
+                let old = before.get(&price).copied();
+                let new = after.get(&price).copied();
+                let delta = LevelDelta {
+                    side,
+                    price,
+                    visible_delta: quantity_delta(
+                        old.map(|level| level.0),
+                        new.map(|level| level.0),
+                    ),
+                    hidden_delta: quantity_delta(
+                        old.map(|level| level.1),
+                        new.map(|level| level.1),
+                    ),
+                    order_count_delta: quantity_delta(
+                        old.map(|level| level.2 as u64),
+                        new.map(|level| level.2 as u64),
+                    ),
+                };
+                match (old, new) {
+                    (None, Some(_)) => diff.added.push(delta),
+                    (Some(_), None) => diff.removed.push(delta),
+                    (Some(old), Some(new)) if old != new => diff.changed.push(delta),
+                    _ => {}
+                }
+            }
+        }
+        trace!(
+            "diff: {} added, {} removed, {} changed levels",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        );
+        diff
+    }
+}
+
+/// `(visible, hidden, order count)` of each level, keyed by price. Levels
+/// listed twice at the same price are merged
+fn levels_by_price(levels: &[PriceLevelSnapshot]) -> BTreeMap<u64, (u64, u64, usize)> {
+    let mut by_price = BTreeMap::new();
+    for level in levels {
+        let entry = by_price.entry(level.price).or_insert((0, 0, 0));
+        entry.0 += level.visible_quantity;
+        entry.1 += level.hidden_quantity;
+        entry.2 += level.order_count;
+    }
+    by_price
+}
+
+/// Signed change from `before` to `after`, a missing level counting as 0
+fn quantity_delta(before: Option<u64>, after: Option<u64>) -> i64 {
+    let delta = i128::from(after.unwrap_or(0)) - i128::from(before.unwrap_or(0));
+    delta.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+/// Change of one price level between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelDelta {
+    /// Side of the level
+    pub side: Side,
+    /// Price of the level
+    pub price: u64,
+    /// Change in visible quantity
+    pub visible_delta: i64,
+    /// Change in hidden quantity
+    pub hidden_delta: i64,
+    /// Change in the number of resting orders
+    pub order_count_delta: i64,
+}
+
+impl LevelDelta {
+    /// Change in visible and hidden quantity together
+    pub fn total_delta(&self) -> i64 {
+        self.visible_delta.saturating_add(self.hidden_delta)
+    }
+}
+
+/// Price levels that differ between two snapshots, see [`OrderBookSnapshot::diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Levels only present in the other snapshot
+    pub added: Vec<LevelDelta>,
+    /// Levels only present in this snapshot
+    pub removed: Vec<LevelDelta>,
+    /// Levels present in both with a different quantity or order count
+    pub changed: Vec<LevelDelta>,
+}
+
+impl SnapshotDiff {
+    /// True if both snapshots hold the same levels
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Every differing level: added, then removed, then changed
+    pub fn iter(&self) -> impl Iterator<Item = &LevelDelta> {
+        self.added.iter().chain(&self.removed).chain(&self.changed)
+    }
+}
+
+/// An individual resting order in a market-by-order snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L3Order {
+    /// Id of the order
+    pub id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Price of the order
+    pub price: u64,
+    /// Displayed quantity left to execute
+    pub visible_quantity: u64,
+    /// Hidden quantity left to execute, for iceberg and reserve orders
+    pub hidden_quantity: u64,
+    /// Quantity the order has executed so far, see `OrderBook::executed_so_far`
+    pub executed_quantity: u64,
+    /// When the order was created (milliseconds since epoch)
+    pub timestamp: u64,
+    /// Acceptance sequence number, if the book assigned one
+    pub sequence: Option<u64>,
+    /// When the book accepted the order (nanoseconds since epoch), see
+    /// `OrderBook::accepted_at_nanos`
+    #[serde(default)]
+    pub accepted_at_nanos: Option<u64>,
+}
+
+impl L3Order {
+    /// Total quantity left to execute, visible and hidden
+    pub fn remaining_quantity(&self) -> u64 {
+        self.visible_quantity + self.hidden_quantity
+    }
+}
+
+/// A price level of a market-by-order snapshot, with its orders in priority order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L3Level {
+    /// Price of the level
+    pub price: u64,
+    /// Orders resting at the level, first in queue first
+    pub orders: Vec<L3Order>,
+}
+
+impl L3Level {
+    /// Visible quantity of the level
+    pub fn visible_quantity(&self) -> u64 {
+        self.orders.iter().map(|order| order.visible_quantity).sum()
+    }
+
+    /// Total quantity of the level, visible and hidden
+    pub fn total_quantity(&self) -> u64 {
+        self.orders.iter().map(L3Order::remaining_quantity).sum()
+    }
+}
+
+/// A market-by-order (L3) snapshot listing every resting order, for
+/// market-by-order feeds and exact replays
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBookL3Snapshot {
+    /// The symbol or identifier for this order book
+    pub symbol: String,
+
+    /// Timestamp when the snapshot was created (milliseconds since epoch)
+    pub timestamp: u64,
+
+    /// Bid levels, best (highest) price first
+    pub bids: Vec<L3Level>,
+
+    /// Ask levels, best (lowest) price first
+    pub asks: Vec<L3Level>,
+}
+
+impl OrderBookL3Snapshot {
+    /// Get the best bid price and visible quantity
+    pub fn best_bid(&self) -> Option<(u64, u64)> {
+        self.bids
+            .first()
+            .map(|level| (level.price, level.visible_quantity()))
+    }
+
+    /// Get the best ask price and visible quantity
+    pub fn best_ask(&self) -> Option<(u64, u64)> {
+        self.asks
+            .first()
+            .map(|level| (level.price, level.visible_quantity()))
+    }
+
+    /// Number of orders in the snapshot
+    pub fn order_count(&self) -> usize {
+        self.bids
+            .iter()
+            .chain(&self.asks)
+            .map(|level| level.orders.len())
+            .sum()
+    }
+
+    /// Every order of the snapshot, bids first, each side in priority order
+    pub fn orders(&self) -> impl Iterator<Item = &L3Order> {
+        self.bids
+            .iter()
+            .chain(&self.asks)
+            .flat_map(|level| level.orders.iter())
+    }
+
+    /// Find an order by its id
+    pub fn get_order(&self, order_id: OrderId) -> Option<&L3Order> {
+        self.orders().find(|order| order.id == order_id)
+    }
 }
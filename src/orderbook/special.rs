@@ -0,0 +1,223 @@
+//! Special price section for orders priced off a settlement price known only at the end of the day
+
+use super::book::OrderBook;
+use super::error::OrderBookError;
+use super::events::OrderBookEvent;
+use super::trade::{TradeCondition, TradeReport};
+use pricelevel::{OrderId, Side, Transaction, UuidGenerator};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tracing::trace;
+
+/// An order in the special price section, such as a trade-at-settlement order.
+///
+/// Instead of a price it carries an offset from the settlement price, which is
+/// only computed at the end of the day. Special price orders never interact
+/// with the regular book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecialPriceOrder {
+    /// Id of the order
+    pub id: OrderId,
+    /// Side of the order
+    pub side: Side,
+    /// Offset from the settlement price, in price units
+    pub offset: i64,
+    /// Quantity still to be executed
+    pub quantity: u64,
+    /// When the order was accepted (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+impl SpecialPriceOrder {
+    /// Price of the order for the given settlement price, or `None` if it would be negative
+    pub fn price_at(&self, settlement_price: u64) -> Option<u64> {
+        settlement_price.checked_add_signed(self.offset)
+    }
+}
+
+/// Outcome of matching the special price section at a settlement price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpecialPriceSettlement {
+    /// Settlement price the orders were priced off
+    pub settlement_price: u64,
+    /// Trades between special price orders, in execution order
+    pub trades: Vec<Transaction>,
+    /// Orders left (partially) unexecuted, with their remaining quantity.
+    /// They expire with the settlement.
+    pub expired: Vec<SpecialPriceOrder>,
+}
+
+/// Orders waiting for the settlement price, in arrival order
+#[derive(Default)]
+pub(super) struct SpecialPriceSection {
+    orders: Mutex<Vec<SpecialPriceOrder>>,
+}
+
+impl SpecialPriceSection {
+    fn orders(&self) -> std::sync::MutexGuard<'_, Vec<SpecialPriceOrder>> {
+        self.orders
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Match special price orders priced at `settlement_price`.
+///
+/// Buys are visited from the highest price and sells from the lowest, in
+/// arrival order within a price. Each trade executes at the price of whichever
+/// order arrived first, which acts as the maker.
+fn settle(
+    orders: Vec<SpecialPriceOrder>,
+    settlement_price: u64,
+    transaction_id_generator: &UuidGenerator,
+) -> SpecialPriceSettlement {
+    let mut expired = Vec::new();
+    let mut buys = Vec::new();
+    let mut sells = Vec::new();
+    for (arrival, order) in orders.into_iter().enumerate() {
+        match order.price_at(settlement_price) {
+            Some(price) if order.side == Side::Buy => buys.push((price, arrival, order)),
+            Some(price) => sells.push((price, arrival, order)),
+            None => expired.push(order),
+        }
+    }
+    buys.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    sells.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut trades = Vec::new();
+    let (mut b, mut s) = (0, 0);
+    while b < buys.len() && s < sells.len() && buys[b].0 >= sells[s].0 {
+        let (buy_price, buy_arrival, buy) = buys[b];
+        let (sell_price, sell_arrival, sell) = sells[s];
+        let quantity = buy.quantity.min(sell.quantity);
+        let (maker, taker, price) = if buy_arrival < sell_arrival {
+            (buy, sell, buy_price)
+        } else {
+            (sell, buy, sell_price)
+        };
+        trades.push(Transaction::new(
+            transaction_id_generator.next(),
+            taker.id,
+            maker.id,
+            price,
+            quantity,
+            taker.side,
+        ));
+
+        buys[b].2.quantity -= quantity;
+        sells[s].2.quantity -= quantity;
+        if buys[b].2.quantity == 0 {
+            b += 1;
+        }
+        if sells[s].2.quantity == 0 {
+            s += 1;
+        }
+    }
+    expired.extend(
+        buys[b..]
+            .iter()
+            .chain(&sells[s..])
+            .map(|(_, _, order)| *order),
+    );
+
+    SpecialPriceSettlement {
+        settlement_price,
+        trades,
+        expired,
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Add an order to the special price section, to be priced at `offset` from
+    /// the settlement price and matched by
+    /// [`settle_special_prices`](Self::settle_special_prices).
+    ///
+    /// # Errors
+    /// Returns `OrderBookError::InvalidOperation` if the quantity is zero or the
+    /// id is already used by a special price order, and
+    /// `OrderBookError::KillSwitchActive` while the book's kill switch is thrown.
+    pub fn add_special_price_order(
+        &self,
+        id: OrderId,
+        side: Side,
+        offset: i64,
+        quantity: u64,
+    ) -> Result<SpecialPriceOrder, OrderBookError> {
+        let _write = self.writes.begin();
+        self.check_kill_switch(None)
+            .map_err(|error| self.reject_order(id, error))?;
+        let mut orders = self.special_prices.orders();
+        let error = if quantity == 0 {
+            Some("Special price order quantity must be positive".to_string())
+        } else if orders.iter().any(|order| order.id == id) {
+            Some(format!("Special price order {id} already exists"))
+        } else {
+            None
+        };
+        if let Some(message) = error {
+            drop(orders);
+            return Err(self.reject_order(id, OrderBookError::InvalidOperation { message }));
+        }
+
+        let order = SpecialPriceOrder {
+            id,
+            side,
+            offset,
+            quantity,
+            timestamp: self.now(),
+        };
+        trace!(
+            "Order book {}: Adding special price order {} {} {} at offset {}",
+            self.symbol, id, side, quantity, offset
+        );
+        orders.push(order);
+        Ok(order)
+    }
+
+    /// Remove an order from the special price section
+    pub fn cancel_special_price_order(&self, id: OrderId) -> Option<SpecialPriceOrder> {
+        let mut orders = self.special_prices.orders();
+        let position = orders.iter().position(|order| order.id == id)?;
+        Some(orders.remove(position))
+    }
+
+    /// Remove every order from the special price section, returning them in
+    /// arrival order
+    pub(super) fn cancel_all_special_price_orders(&self) -> Vec<SpecialPriceOrder> {
+        std::mem::take(&mut *self.special_prices.orders())
+    }
+
+    /// Orders waiting in the special price section, in arrival order
+    pub fn special_price_orders(&self) -> Vec<SpecialPriceOrder> {
+        self.special_prices.orders().clone()
+    }
+
+    /// Price the special price section at `settlement_price` and match it.
+    ///
+    /// Orders are matched only against each other, never against the regular
+    /// book, and the section is emptied: whatever does not execute expires.
+    /// The trades are published as `TradeExecuted` events flagged with
+    /// [`TradeCondition::TradeAtSettlement`] and do not move the last trade price.
+    pub fn settle_special_prices(&self, settlement_price: u64) -> SpecialPriceSettlement {
+        let orders = std::mem::take(&mut *self.special_prices.orders());
+        let settlement = settle(orders, settlement_price, &self.transaction_id_generator);
+        for trade in &settlement.trades {
+            self.emit_event(&OrderBookEvent::TradeExecuted(TradeReport {
+                transaction: *trade,
+                conditions: TradeCondition::TradeAtSettlement.into(),
+                fees: None,
+            }));
+        }
+        trace!(
+            "Order book {}: Settled special prices at {}: {} trades, {} expired",
+            self.symbol,
+            settlement_price,
+            settlement.trades.len(),
+            settlement.expired.len()
+        );
+        settlement
+    }
+}
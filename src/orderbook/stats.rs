@@ -0,0 +1,87 @@
+//! Activity counters kept by the book itself
+
+use super::notional::NotionalStats;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Activity of a book since it was created or its statistics were last reset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookStats {
+    /// Orders accepted by `add_order` and its variants, whether they rested or traded
+    pub orders_added: u64,
+    /// Incoming orders that traded at least once, limit and market orders alike
+    pub orders_matched: u64,
+    /// Resting orders cancelled, one by one or by a mass cancel
+    pub orders_cancelled: u64,
+    /// Orders the book refused
+    pub orders_rejected: u64,
+    /// Quantity traded, summed over every trade
+    pub traded_volume: u64,
+    /// Number of trades, one per maker an incoming order executed against
+    pub trades: u64,
+    /// Notional traded, if the book tracks it
+    #[serde(default)]
+    pub notional: Option<NotionalStats>,
+}
+
+/// Counters behind [`BookStats`]. Each one is updated on its own, so a
+/// snapshot taken while operations run may mix their before and after states
+#[derive(Debug, Default)]
+pub(super) struct StatCounters {
+    orders_added: AtomicU64,
+    orders_matched: AtomicU64,
+    orders_cancelled: AtomicU64,
+    orders_rejected: AtomicU64,
+    traded_volume: AtomicU64,
+    trades: AtomicU64,
+}
+
+impl StatCounters {
+    pub(super) fn snapshot(&self) -> BookStats {
+        BookStats {
+            orders_added: self.orders_added.load(Ordering::Relaxed),
+            orders_matched: self.orders_matched.load(Ordering::Relaxed),
+            orders_cancelled: self.orders_cancelled.load(Ordering::Relaxed),
+            orders_rejected: self.orders_rejected.load(Ordering::Relaxed),
+            traded_volume: self.traded_volume.load(Ordering::Relaxed),
+            trades: self.trades.load(Ordering::Relaxed),
+            notional: None,
+        }
+    }
+
+    pub(super) fn reset(&self) {
+        for counter in [
+            &self.orders_added,
+            &self.orders_matched,
+            &self.orders_cancelled,
+            &self.orders_rejected,
+            &self.traded_volume,
+            &self.trades,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn record_added(&self) {
+        self.orders_added.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_cancelled(&self, count: usize) {
+        self.orders_cancelled
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_rejected(&self) {
+        self.orders_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An incoming order executed `trades` trades for `volume` in total
+    pub(super) fn record_match(&self, trades: usize, volume: u64) {
+        if trades == 0 {
+            return;
+        }
+        self.orders_matched.fetch_add(1, Ordering::Relaxed);
+        self.trades.fetch_add(trades as u64, Ordering::Relaxed);
+        self.traded_volume.fetch_add(volume, Ordering::Relaxed);
+    }
+}
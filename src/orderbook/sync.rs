@@ -0,0 +1,10 @@
+//! Synchronization primitives of the lock-free parts of the book.
+//!
+//! The best price cache and the level ticket locks import their primitives from
+//! here rather than from `std`, and the loom test suite compiles the same
+//! sources against a `sync` module of loom's model-checked types instead.
+
+pub(crate) use std::hint;
+pub(crate) use std::sync::Mutex;
+pub(crate) use std::sync::atomic;
+pub(crate) use std::thread;
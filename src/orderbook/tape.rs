@@ -0,0 +1,189 @@
+//! Trade tape: the recent trades of a book, aggregated into OHLCV bars and VWAP
+
+use super::book::OrderBook;
+use pricelevel::Transaction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Open, high, low, close and volume of the trades within one time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OhlcvBar {
+    /// Start of the window (milliseconds since epoch, inclusive)
+    pub start: u64,
+    /// End of the window (milliseconds since epoch, exclusive)
+    pub end: u64,
+    /// Price of the first trade
+    pub open: u64,
+    /// Highest trade price
+    pub high: u64,
+    /// Lowest trade price
+    pub low: u64,
+    /// Price of the last trade
+    pub close: u64,
+    /// Quantity traded
+    pub volume: u64,
+    /// Sum of price times quantity over the trades
+    pub notional: u128,
+    /// Number of trades
+    pub trade_count: u64,
+}
+
+impl OhlcvBar {
+    fn open_with(start: u64, end: u64, transaction: &Transaction) -> Self {
+        let mut bar = Self {
+            start,
+            end,
+            open: transaction.price,
+            high: transaction.price,
+            low: transaction.price,
+            close: transaction.price,
+            volume: 0,
+            notional: 0,
+            trade_count: 0,
+        };
+        bar.add(transaction);
+        bar
+    }
+
+    fn add(&mut self, transaction: &Transaction) {
+        self.high = self.high.max(transaction.price);
+        self.low = self.low.min(transaction.price);
+        self.close = transaction.price;
+        self.volume += transaction.quantity;
+        self.notional += u128::from(transaction.price) * u128::from(transaction.quantity);
+        self.trade_count += 1;
+    }
+
+    /// Volume-weighted average price of the trades in the bar
+    pub fn vwap(&self) -> f64 {
+        self.notional as f64 / self.volume as f64
+    }
+}
+
+/// Records the trades of a book as they are matched and aggregates them on demand.
+///
+/// Trades older than the retention period, measured from the latest trade,
+/// are discarded, so the retention must cover the longest window queried.
+/// The tape is shared behind an `Arc`, so a backtest or strategy can keep
+/// reading it while the book trades.
+pub struct TradeTape {
+    retention_ms: u64,
+    trades: Mutex<VecDeque<Transaction>>,
+}
+
+impl TradeTape {
+    /// Create a tape keeping the trades of the last `retention`
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention_ms: retention.as_millis() as u64,
+            trades: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Append trades to the tape, in the order they were made.
+    ///
+    /// Books record their trades automatically once the tape is attached;
+    /// this is public so trades from other sources, e.g. a replay, can be fed
+    /// to a tape directly.
+    pub fn record(&self, transactions: &[Transaction]) {
+        let Some(latest) = transactions.iter().map(|t| t.timestamp).max() else {
+            return;
+        };
+        let mut trades = self.lock();
+        trades.extend(transactions.iter().copied());
+        let cutoff = latest.saturating_sub(self.retention_ms);
+        while trades.front().is_some_and(|trade| trade.timestamp < cutoff) {
+            trades.pop_front();
+        }
+    }
+
+    /// Trades currently on the tape, oldest first
+    pub fn trades(&self) -> Vec<Transaction> {
+        self.lock().iter().copied().collect()
+    }
+
+    /// Number of trades currently on the tape
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no trade is on the tape
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Bars of `interval` (e.g. 1s, 1m or 5m) covering every trade on the tape,
+    /// oldest first.
+    ///
+    /// Bars are aligned on multiples of the interval since the epoch, and
+    /// intervals without trades produce no bar.
+    ///
+    /// # Panics
+    /// Panics if `interval` is shorter than a millisecond.
+    pub fn bars(&self, interval: Duration) -> Vec<OhlcvBar> {
+        let interval_ms = Self::interval_ms(interval);
+        // Trades matched concurrently may be recorded slightly out of time order
+        let mut bars: BTreeMap<u64, OhlcvBar> = BTreeMap::new();
+        for trade in self.lock().iter() {
+            let start = trade.timestamp - trade.timestamp % interval_ms;
+            bars.entry(start)
+                .and_modify(|bar| bar.add(trade))
+                .or_insert_with(|| OhlcvBar::open_with(start, start + interval_ms, trade));
+        }
+        bars.into_values().collect()
+    }
+
+    /// A single bar over the trades of the trailing `window` ending at `now`
+    /// (milliseconds since epoch, exclusive), or `None` if there were none
+    ///
+    /// # Panics
+    /// Panics if `window` is shorter than a millisecond.
+    pub fn rolling_bar(&self, window: Duration, now: u64) -> Option<OhlcvBar> {
+        let start = now.saturating_sub(Self::interval_ms(window));
+        let trades = self.lock();
+        let mut in_window = trades
+            .iter()
+            .filter(|trade| trade.timestamp >= start && trade.timestamp < now);
+        let mut bar = OhlcvBar::open_with(start, now, in_window.next()?);
+        in_window.for_each(|trade| bar.add(trade));
+        Some(bar)
+    }
+
+    /// Volume-weighted average price over the trailing `window` ending at
+    /// `now`, or `None` if nothing traded in it
+    ///
+    /// # Panics
+    /// Panics if `window` is shorter than a millisecond.
+    pub fn vwap(&self, window: Duration, now: u64) -> Option<f64> {
+        self.rolling_bar(window, now).map(|bar| bar.vwap())
+    }
+
+    fn interval_ms(interval: Duration) -> u64 {
+        let interval_ms = interval.as_millis() as u64;
+        assert!(interval_ms > 0, "bar interval must be at least 1ms");
+        interval_ms
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Transaction>> {
+        self.trades
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Record every trade of the book on `tape` from now on
+    pub fn set_trade_tape(&mut self, tape: Arc<TradeTape>) {
+        self.trade_tape = Some(tape);
+    }
+
+    /// The trade tape attached to the book, if any
+    pub fn trade_tape(&self) -> Option<&Arc<TradeTape>> {
+        self.trade_tape.as_ref()
+    }
+}
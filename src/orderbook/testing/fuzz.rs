@@ -0,0 +1,242 @@
+//! Operations a fuzzer can interleave on an order book
+
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::error::OrderBookError;
+use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+/// One call into the public [`OrderBook`] API, in a form fuzzers can generate.
+///
+/// With the `arbitrary` feature, `Op` implements `arbitrary::Arbitrary` and
+/// draws ids, prices and quantities from small ranges, so generated sequences
+/// keep hitting the same orders and levels instead of scattering.
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    /// Add a standard limit order
+    AddLimit {
+        /// Id of the order
+        id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Quantity
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+        /// Time in force
+        time_in_force: TimeInForce,
+    },
+    /// Add an iceberg order
+    AddIceberg {
+        /// Id of the order
+        id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Displayed quantity
+        visible: u64,
+        /// Hidden reserve
+        hidden: u64,
+        /// Side of the order
+        side: Side,
+    },
+    /// Add a post-only order
+    AddPostOnly {
+        /// Id of the order
+        id: OrderId,
+        /// Limit price
+        price: u64,
+        /// Quantity
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+    },
+    /// Submit a market order
+    Market {
+        /// Id of the order
+        id: OrderId,
+        /// Quantity
+        quantity: u64,
+        /// Side of the order
+        side: Side,
+    },
+    /// Match against the book without adding an order, up to an optional limit
+    Match {
+        /// Id of the incoming order
+        id: OrderId,
+        /// Quantity
+        quantity: u64,
+        /// Side of the incoming order
+        side: Side,
+        /// Worst acceptable price
+        limit_price: Option<u64>,
+    },
+    /// Cancel an order
+    Cancel {
+        /// Id of the order
+        id: OrderId,
+    },
+    /// Apply an [`OrderUpdate`]
+    Update(OrderUpdate),
+}
+
+/// Apply `op` to `book`, returning the error the book reported if any.
+///
+/// Errors are part of normal operation (unknown ids, crossing post-only
+/// orders, missing liquidity), so a fuzz target ignores them and looks for
+/// panics and for invariants that [`OrderBook::validate`] reports broken.
+pub fn apply_op<T>(book: &OrderBook<T>, op: Op) -> Result<(), OrderBookError>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    match op {
+        Op::AddLimit {
+            id,
+            price,
+            quantity,
+            side,
+            time_in_force,
+        } => book
+            .add_limit_order(id, price, quantity, side, time_in_force, None)
+            .map(drop),
+        Op::AddIceberg {
+            id,
+            price,
+            visible,
+            hidden,
+            side,
+        } => book
+            .add_iceberg_order(id, price, visible, hidden, side, TimeInForce::Gtc, None)
+            .map(drop),
+        Op::AddPostOnly {
+            id,
+            price,
+            quantity,
+            side,
+        } => book
+            .add_post_only_order(id, price, quantity, side, TimeInForce::Gtc, None)
+            .map(drop),
+        Op::Market { id, quantity, side } => book
+            .submit_market_order(id, quantity, side)
+            .map(|result| book.recycle_match_result(result)),
+        Op::Match {
+            id,
+            quantity,
+            side,
+            limit_price,
+        } => book
+            .match_order(id, side, quantity, limit_price)
+            .map(|result| book.recycle_match_result(result)),
+        Op::Cancel { id } => book.cancel_order(id).map(drop),
+        Op::Update(update) => book.update_order(update).map(drop),
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod generate {
+    use super::Op;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+    // Narrow ranges make generated operations collide on ids and price levels
+    const MAX_ID: u64 = 64;
+    const MAX_PRICE: u64 = 32;
+    const MAX_QUANTITY: u64 = 100;
+
+    fn id(u: &mut Unstructured<'_>) -> Result<OrderId> {
+        Ok(OrderId::from_u64(u.int_in_range(1..=MAX_ID)?))
+    }
+
+    fn price(u: &mut Unstructured<'_>) -> Result<u64> {
+        u.int_in_range(1..=MAX_PRICE)
+    }
+
+    // Zero is included to exercise the rejection paths
+    fn quantity(u: &mut Unstructured<'_>) -> Result<u64> {
+        u.int_in_range(0..=MAX_QUANTITY)
+    }
+
+    fn side(u: &mut Unstructured<'_>) -> Result<Side> {
+        Ok(if bool::arbitrary(u)? {
+            Side::Buy
+        } else {
+            Side::Sell
+        })
+    }
+
+    fn time_in_force(u: &mut Unstructured<'_>) -> Result<TimeInForce> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => TimeInForce::Ioc,
+            1 => TimeInForce::Fok,
+            2 => TimeInForce::Day,
+            _ => TimeInForce::Gtc,
+        })
+    }
+
+    fn update(u: &mut Unstructured<'_>) -> Result<OrderUpdate> {
+        let order_id = id(u)?;
+        Ok(match u.int_in_range(0..=4)? {
+            0 => OrderUpdate::UpdatePrice {
+                order_id,
+                new_price: price(u)?,
+            },
+            1 => OrderUpdate::UpdateQuantity {
+                order_id,
+                new_quantity: quantity(u)?,
+            },
+            2 => OrderUpdate::UpdatePriceAndQuantity {
+                order_id,
+                new_price: price(u)?,
+                new_quantity: quantity(u)?,
+            },
+            3 => OrderUpdate::Cancel { order_id },
+            _ => OrderUpdate::Replace {
+                order_id,
+                price: price(u)?,
+                quantity: quantity(u)?,
+                side: side(u)?,
+            },
+        })
+    }
+
+    impl<'a> Arbitrary<'a> for Op {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(match u.int_in_range(0..=6)? {
+                0 => Op::AddLimit {
+                    id: id(u)?,
+                    price: price(u)?,
+                    quantity: quantity(u)?,
+                    side: side(u)?,
+                    time_in_force: time_in_force(u)?,
+                },
+                1 => Op::AddIceberg {
+                    id: id(u)?,
+                    price: price(u)?,
+                    visible: quantity(u)?,
+                    hidden: quantity(u)?,
+                    side: side(u)?,
+                },
+                2 => Op::AddPostOnly {
+                    id: id(u)?,
+                    price: price(u)?,
+                    quantity: quantity(u)?,
+                    side: side(u)?,
+                },
+                3 => Op::Market {
+                    id: id(u)?,
+                    quantity: quantity(u)?,
+                    side: side(u)?,
+                },
+                4 => Op::Match {
+                    id: id(u)?,
+                    quantity: quantity(u)?,
+                    side: side(u)?,
+                    limit_price: if bool::arbitrary(u)? {
+                        Some(price(u)?)
+                    } else {
+                        None
+                    },
+                },
+                5 => Op::Cancel { id: id(u)? },
+                _ => Op::Update(update(u)?),
+            })
+        }
+    }
+}
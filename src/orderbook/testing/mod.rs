@@ -0,0 +1,23 @@
+//! Test support for users and contributors who extend the book.
+//!
+//! [`StressHarness`] runs reproducible multi-threaded soak tests and checks the
+//! invariants of the book they leave behind. [`ReferenceBook`] is a deliberately
+//! simple single-threaded model of price-time matching, and
+//! [`check_against_reference`] replays a sequence of [`BookOp`]s on both it and
+//! an [`OrderBook`](crate::orderbook::OrderBook) to report the first step where
+//! they disagree. With the `proptest` feature, [`strategies`] generates such
+//! sequences. [`apply_op`] drives the book from an [`Op`], which fuzzers can
+//! generate with the `arbitrary` feature; see the `fuzz` directory.
+
+pub mod fuzz;
+pub mod reference;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod stress;
+
+pub use fuzz::{Op, apply_op};
+pub use reference::{
+    BookOp, Divergence, ReferenceBook, ReferenceFill, ReferenceLevel, StepOutcome,
+    check_against_reference,
+};
+pub use stress::{RunLength, StressConfig, StressHarness, StressReport};
@@ -0,0 +1,452 @@
+//! Reference model of price-time matching and a checker against the real book
+
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::error::OrderBookError;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+/// One input of a sequence applied to both [`ReferenceBook`] and [`OrderBook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookOp {
+    /// Submit a standard limit order
+    Limit {
+        /// Id of the order, unique within the sequence
+        id: OrderId,
+        /// Side of the order
+        side: Side,
+        /// Limit price
+        price: u64,
+        /// Quantity, at least 1
+        quantity: u64,
+        /// `Gtc`, `Ioc` or `Fok`
+        time_in_force: TimeInForce,
+    },
+    /// Submit a market order
+    Market {
+        /// Id of the order, unique within the sequence
+        id: OrderId,
+        /// Side of the order
+        side: Side,
+        /// Quantity, at least 1
+        quantity: u64,
+    },
+    /// Cancel a resting order, which may no longer exist
+    Cancel {
+        /// Id of the order to cancel
+        id: OrderId,
+    },
+}
+
+/// A fill against a resting order of the [`ReferenceBook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferenceFill {
+    /// Id of the resting order
+    pub maker_order_id: OrderId,
+    /// Price of the resting order
+    pub price: u64,
+    /// Quantity executed
+    pub quantity: u64,
+}
+
+/// A price level with its resting orders in time priority
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceLevel {
+    /// Side of the level
+    pub side: Side,
+    /// Price of the level
+    pub price: u64,
+    /// Id and remaining quantity of each resting order, oldest first
+    pub orders: Vec<(OrderId, u64)>,
+}
+
+/// Price-time priority book built on `BTreeMap`s of FIFO queues.
+///
+/// The model supports standard limit orders (GTC, IOC and FOK), market orders
+/// and cancels, with no validation, fees, self-trade prevention or special
+/// order types. It mirrors how [`OrderBook`] reports outcomes: market orders
+/// that execute nothing and IOC or FOK orders that do not fill completely are
+/// errors, although an IOC order keeps the fills it got.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceBook {
+    bids: BTreeMap<u64, VecDeque<(OrderId, u64)>>,
+    asks: BTreeMap<u64, VecDeque<(OrderId, u64)>>,
+    locations: HashMap<OrderId, (Side, u64)>,
+}
+
+impl ReferenceBook {
+    /// Create an empty book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highest bid price
+    pub fn best_bid(&self) -> Option<u64> {
+        self.bids.keys().next_back().copied()
+    }
+
+    /// Lowest ask price
+    pub fn best_ask(&self) -> Option<u64> {
+        self.asks.keys().next().copied()
+    }
+
+    /// Number of resting orders
+    pub fn order_count(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Every level, bids from best to worst followed by asks from best to worst
+    pub fn levels(&self) -> Vec<ReferenceLevel> {
+        let level =
+            |side: Side, (price, orders): (&u64, &VecDeque<(OrderId, u64)>)| ReferenceLevel {
+                side,
+                price: *price,
+                orders: orders.iter().copied().collect(),
+            };
+        self.bids
+            .iter()
+            .rev()
+            .map(|entry| level(Side::Buy, entry))
+            .chain(self.asks.iter().map(|entry| level(Side::Sell, entry)))
+            .collect()
+    }
+
+    /// Match a limit order and rest what is left of a GTC order
+    pub fn add_limit_order(
+        &mut self,
+        id: OrderId,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+    ) -> Result<Vec<ReferenceFill>, OrderBookError> {
+        if time_in_force == TimeInForce::Fok {
+            let available = self.available(side, quantity, price);
+            if available < quantity {
+                return Err(OrderBookError::InsufficientLiquidity {
+                    side,
+                    requested: quantity,
+                    available,
+                });
+            }
+        }
+
+        let (fills, remaining) = self.sweep(side, quantity, Some(price));
+        if remaining > 0 {
+            if time_in_force.is_immediate() {
+                return Err(OrderBookError::InsufficientLiquidity {
+                    side,
+                    requested: quantity,
+                    available: quantity - remaining,
+                });
+            }
+            self.side_mut(side)
+                .entry(price)
+                .or_default()
+                .push_back((id, remaining));
+            self.locations.insert(id, (side, price));
+        }
+        Ok(fills)
+    }
+
+    /// Match a market order, failing if nothing executes
+    pub fn submit_market_order(
+        &mut self,
+        _id: OrderId,
+        quantity: u64,
+        side: Side,
+    ) -> Result<Vec<ReferenceFill>, OrderBookError> {
+        let (fills, remaining) = self.sweep(side, quantity, None);
+        if remaining == quantity {
+            return Err(OrderBookError::InsufficientLiquidity {
+                side,
+                requested: quantity,
+                available: 0,
+            });
+        }
+        Ok(fills)
+    }
+
+    /// Remove a resting order and return its remaining quantity
+    pub fn cancel_order(&mut self, id: OrderId) -> Option<u64> {
+        let (side, price) = self.locations.remove(&id)?;
+        let book_side = self.side_mut(side);
+        let level = book_side.get_mut(&price)?;
+        let position = level.iter().position(|(order_id, _)| *order_id == id)?;
+        let (_, remaining) = level.remove(position)?;
+        if level.is_empty() {
+            book_side.remove(&price);
+        }
+        Some(remaining)
+    }
+
+    /// Apply one operation and report whether the book accepted it
+    pub fn apply(&mut self, op: &BookOp) -> StepOutcome {
+        match *op {
+            BookOp::Limit {
+                id,
+                side,
+                price,
+                quantity,
+                time_in_force,
+            } => StepOutcome {
+                accepted: self
+                    .add_limit_order(id, price, quantity, side, time_in_force)
+                    .is_ok(),
+                fills: None,
+            },
+            BookOp::Market { id, side, quantity } => {
+                let result = self.submit_market_order(id, quantity, side);
+                StepOutcome {
+                    accepted: result.is_ok(),
+                    fills: result.ok(),
+                }
+            }
+            BookOp::Cancel { id } => StepOutcome {
+                accepted: self.cancel_order(id).is_some(),
+                fills: None,
+            },
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u64, VecDeque<(OrderId, u64)>> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    /// Prices of the side an order of `side` matches against, best first
+    fn opposite_prices(&self, side: Side) -> Vec<u64> {
+        match side {
+            Side::Buy => self.asks.keys().copied().collect(),
+            Side::Sell => self.bids.keys().rev().copied().collect(),
+        }
+    }
+
+    fn crosses(side: Side, price: u64, limit: Option<u64>) -> bool {
+        match (side, limit) {
+            (_, None) => true,
+            (Side::Buy, Some(limit)) => price <= limit,
+            (Side::Sell, Some(limit)) => price >= limit,
+        }
+    }
+
+    /// Quantity an order of `side` could execute up to `limit`, capped at `quantity`
+    fn available(&self, side: Side, quantity: u64, limit: u64) -> u64 {
+        let opposite = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
+        let mut available = 0u64;
+        for price in self.opposite_prices(side) {
+            if !Self::crosses(side, price, Some(limit)) || available >= quantity {
+                break;
+            }
+            available += opposite[&price].iter().map(|(_, qty)| qty).sum::<u64>();
+        }
+        available.min(quantity)
+    }
+
+    fn sweep(
+        &mut self,
+        side: Side,
+        quantity: u64,
+        limit: Option<u64>,
+    ) -> (Vec<ReferenceFill>, u64) {
+        let mut fills = Vec::new();
+        let mut remaining = quantity;
+        for price in self.opposite_prices(side) {
+            if remaining == 0 || !Self::crosses(side, price, limit) {
+                break;
+            }
+            self.fill_level(side.opposite(), price, &mut remaining, &mut fills);
+        }
+        (fills, remaining)
+    }
+
+    /// Fill resting orders at `price` oldest first, dropping the level once empty
+    fn fill_level(
+        &mut self,
+        side: Side,
+        price: u64,
+        remaining: &mut u64,
+        fills: &mut Vec<ReferenceFill>,
+    ) {
+        let book_side = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let Some(level) = book_side.get_mut(&price) else {
+            return;
+        };
+        while *remaining > 0 {
+            let Some((maker_order_id, resting)) = level.front_mut() else {
+                break;
+            };
+            let executed = (*remaining).min(*resting);
+            *resting -= executed;
+            *remaining -= executed;
+            fills.push(ReferenceFill {
+                maker_order_id: *maker_order_id,
+                price,
+                quantity: executed,
+            });
+            if *resting == 0 {
+                let maker_order_id = *maker_order_id;
+                level.pop_front();
+                self.locations.remove(&maker_order_id);
+            }
+        }
+        if level.is_empty() {
+            book_side.remove(&price);
+        }
+    }
+}
+
+/// What one step of a sequence did on a book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepOutcome {
+    /// Whether the book accepted the operation
+    pub accepted: bool,
+    /// Fills of an accepted market order, the only operation that reports them
+    pub fills: Option<Vec<ReferenceFill>>,
+}
+
+/// First step where [`OrderBook`] and [`ReferenceBook`] disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the operation in the sequence
+    pub step: usize,
+    /// The operation applied at that step
+    pub op: BookOp,
+    /// Outcome on the reference model
+    pub expected: StepOutcome,
+    /// Outcome on the order book
+    pub actual: StepOutcome,
+    /// Levels of the reference model after the step
+    pub expected_levels: Vec<ReferenceLevel>,
+    /// Levels of the order book after the step
+    pub actual_levels: Vec<ReferenceLevel>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {} ({:?}) diverged: ", self.step, self.op)?;
+        if self.expected != self.actual {
+            write!(f, "expected {:?}, got {:?}", self.expected, self.actual)
+        } else {
+            write!(
+                f,
+                "expected levels {:?}, got {:?}",
+                self.expected_levels, self.actual_levels
+            )
+        }
+    }
+}
+
+/// Replay `ops` on `book` and on a fresh [`ReferenceBook`], comparing the outcome
+/// of every step and the full depth of both books after it.
+///
+/// `book` should start empty and use default options, or the differences its
+/// configuration introduces will be reported as divergences.
+pub fn check_against_reference<T>(
+    book: &OrderBook<T>,
+    ops: &[BookOp],
+) -> Result<(), Box<Divergence>>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    let mut reference = ReferenceBook::new();
+    for (step, op) in ops.iter().enumerate() {
+        let expected = reference.apply(op);
+        let actual = apply_to_book(book, op);
+        let expected_levels = reference.levels();
+        let actual_levels = book_levels(book);
+        if expected != actual || expected_levels != actual_levels {
+            return Err(Box::new(Divergence {
+                step,
+                op: *op,
+                expected,
+                actual,
+                expected_levels,
+                actual_levels,
+            }));
+        }
+    }
+    Ok(())
+}
+
+fn apply_to_book<T>(book: &OrderBook<T>, op: &BookOp) -> StepOutcome
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    match *op {
+        BookOp::Limit {
+            id,
+            side,
+            price,
+            quantity,
+            time_in_force,
+        } => StepOutcome {
+            accepted: book
+                .add_limit_order(id, price, quantity, side, time_in_force, None)
+                .is_ok(),
+            fills: None,
+        },
+        BookOp::Market { id, side, quantity } => match book.submit_market_order(id, quantity, side)
+        {
+            Ok(result) => StepOutcome {
+                accepted: true,
+                fills: Some(
+                    result
+                        .transactions
+                        .as_vec()
+                        .iter()
+                        .map(|transaction| ReferenceFill {
+                            maker_order_id: transaction.maker_order_id,
+                            price: transaction.price,
+                            quantity: transaction.quantity,
+                        })
+                        .collect(),
+                ),
+            },
+            Err(_) => StepOutcome {
+                accepted: false,
+                fills: None,
+            },
+        },
+        BookOp::Cancel { id } => StepOutcome {
+            accepted: matches!(book.cancel_order(id), Ok(Some(_))),
+            fills: None,
+        },
+    }
+}
+
+/// Depth of `book` in the shape of [`ReferenceBook::levels`]
+fn book_levels<T>(book: &OrderBook<T>) -> Vec<ReferenceLevel>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    let snapshot = book.create_snapshot(usize::MAX);
+    let level = |side: Side, price: u64| ReferenceLevel {
+        side,
+        price,
+        orders: book
+            .get_orders_at_price(price, side)
+            .iter()
+            .map(|order| {
+                (
+                    order.id(),
+                    order.visible_quantity() + order.hidden_quantity(),
+                )
+            })
+            .collect(),
+    };
+    snapshot
+        .bids
+        .iter()
+        .map(|bid| level(Side::Buy, bid.price))
+        .chain(snapshot.asks.iter().map(|ask| level(Side::Sell, ask.price)))
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! Proptest strategies generating sequences of [`BookOp`]s
+
+use super::reference::BookOp;
+use pricelevel::{OrderId, Side, TimeInForce};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use std::ops::RangeInclusive;
+
+/// Shape of an operation before ids are assigned to the sequence
+#[derive(Debug, Clone)]
+enum OpShape {
+    Limit(Side, u64, u64, TimeInForce),
+    Market(Side, u64),
+    Cancel(Index),
+}
+
+/// Either side with equal probability
+pub fn side() -> impl Strategy<Value = Side> {
+    prop_oneof![Just(Side::Buy), Just(Side::Sell)]
+}
+
+/// Mostly GTC with some IOC and FOK, so the book keeps resting liquidity
+pub fn time_in_force() -> impl Strategy<Value = TimeInForce> {
+    prop_oneof![
+        6 => Just(TimeInForce::Gtc),
+        2 => Just(TimeInForce::Ioc),
+        2 => Just(TimeInForce::Fok),
+    ]
+}
+
+fn op_shape(prices: RangeInclusive<u64>, max_quantity: u64) -> impl Strategy<Value = OpShape> {
+    let quantities = 1..=max_quantity.max(1);
+    prop_oneof![
+        6 => (side(), prices, quantities.clone(), time_in_force())
+            .prop_map(|(side, price, quantity, tif)| OpShape::Limit(side, price, quantity, tif)),
+        2 => (side(), quantities).prop_map(|(side, quantity)| OpShape::Market(side, quantity)),
+        2 => any::<Index>().prop_map(OpShape::Cancel),
+    ]
+}
+
+/// Sequences of up to `max_len` operations with limit prices in `prices` and
+/// quantities up to `max_quantity`.
+///
+/// Order ids are unique within a sequence and cancels target an id submitted
+/// earlier in it, which may since have been filled or cancelled. A narrow price
+/// range makes orders cross often.
+pub fn op_sequence_in(
+    prices: RangeInclusive<u64>,
+    max_quantity: u64,
+    max_len: usize,
+) -> impl Strategy<Value = Vec<BookOp>> {
+    prop::collection::vec(op_shape(prices, max_quantity), 0..=max_len).prop_map(|shapes| {
+        let mut ops = Vec::with_capacity(shapes.len());
+        let mut submitted: Vec<OrderId> = Vec::new();
+        for (step, shape) in shapes.into_iter().enumerate() {
+            let id = OrderId::from_u64(step as u64 + 1);
+            let op = match shape {
+                OpShape::Limit(side, price, quantity, time_in_force) => {
+                    submitted.push(id);
+                    BookOp::Limit {
+                        id,
+                        side,
+                        price,
+                        quantity,
+                        time_in_force,
+                    }
+                }
+                OpShape::Market(side, quantity) => BookOp::Market { id, side, quantity },
+                OpShape::Cancel(index) if !submitted.is_empty() => BookOp::Cancel {
+                    id: *index.get(&submitted),
+                },
+                OpShape::Cancel(_) => BookOp::Cancel { id },
+            };
+            ops.push(op);
+        }
+        ops
+    })
+}
+
+/// Sequences of up to `max_len` operations around a price of 100
+pub fn op_sequence(max_len: usize) -> impl Strategy<Value = Vec<BookOp>> {
+    op_sequence_in(95..=105, 20, max_len)
+}
@@ -0,0 +1,516 @@
+//! Multi-threaded stress harness for soak tests against an order book
+
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::validation::ValidationReport;
+use pricelevel::{OrderId, Side, TimeInForce};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::trace;
+
+/// Most order ids kept around for cancellers; older ids are dropped first
+const CANCEL_QUEUE_CAPACITY: usize = 4096;
+
+/// How long each worker thread of a [`StressHarness`] keeps running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunLength {
+    /// Every worker performs exactly this many operations
+    Operations(u64),
+    /// Workers keep going until this much wall-clock time has passed
+    Time(Duration),
+}
+
+/// Parameters of a [`StressHarness`] run.
+///
+/// Each worker thread draws its actions from a pseudo-random generator seeded
+/// with `seed` and its thread index, picking a maker, taker or canceller action
+/// with probability proportional to the configured weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StressConfig {
+    /// Number of worker threads
+    pub threads: usize,
+    /// Relative weight of maker actions (limit, post-only, iceberg and IOC orders)
+    pub maker_weight: u32,
+    /// Relative weight of taker actions (market orders)
+    pub taker_weight: u32,
+    /// Relative weight of canceller actions (cancelling a previously added order)
+    pub canceller_weight: u32,
+    /// How long the workers run
+    pub run_length: RunLength,
+    /// Seed of the per-thread generators
+    pub seed: u64,
+    /// Price around which bids and asks are placed
+    pub mid_price: u64,
+    /// Distance between two generated price levels
+    pub tick_size: u64,
+    /// Number of price levels generated on each side of the mid price
+    pub price_levels: u64,
+    /// Largest quantity of a generated order, at least 1
+    pub max_quantity: u64,
+    /// Number of orders rested on each side before the workers start
+    pub preload_per_side: usize,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            maker_weight: 5,
+            taker_weight: 3,
+            canceller_weight: 2,
+            run_length: RunLength::Operations(10_000),
+            seed: 0,
+            mid_price: 10_000,
+            tick_size: 10,
+            price_levels: 20,
+            max_quantity: 20,
+            preload_per_side: 100,
+        }
+    }
+}
+
+impl StressConfig {
+    /// Set the number of worker threads
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Set the relative weights of maker, taker and canceller actions
+    pub fn with_ratios(mut self, maker: u32, taker: u32, canceller: u32) -> Self {
+        self.maker_weight = maker;
+        self.taker_weight = taker;
+        self.canceller_weight = canceller;
+        self
+    }
+
+    /// Set how long the workers run
+    pub fn with_run_length(mut self, run_length: RunLength) -> Self {
+        self.run_length = run_length;
+        self
+    }
+
+    /// Set the seed of the per-thread generators
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the mid price, tick size and number of levels per side
+    pub fn with_prices(mut self, mid_price: u64, tick_size: u64, price_levels: u64) -> Self {
+        self.mid_price = mid_price;
+        self.tick_size = tick_size;
+        self.price_levels = price_levels;
+        self
+    }
+
+    /// Set the largest quantity of a generated order
+    pub fn with_max_quantity(mut self, max_quantity: u64) -> Self {
+        self.max_quantity = max_quantity;
+        self
+    }
+
+    /// Set the number of orders rested on each side before the run
+    pub fn with_preload(mut self, preload_per_side: usize) -> Self {
+        self.preload_per_side = preload_per_side;
+        self
+    }
+
+    fn total_weight(&self) -> u64 {
+        u64::from(self.maker_weight)
+            + u64::from(self.taker_weight)
+            + u64::from(self.canceller_weight)
+    }
+}
+
+/// Counters and final state of a [`StressHarness`] run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StressReport {
+    /// Orders accepted by the book, whether they rested or executed
+    pub orders_added: u64,
+    /// Orders the book rejected
+    pub orders_rejected: u64,
+    /// Market orders submitted
+    pub market_orders: u64,
+    /// Market orders that executed at least partially
+    pub market_orders_filled: u64,
+    /// Cancel requests sent
+    pub cancels_attempted: u64,
+    /// Cancel requests that removed a resting order
+    pub orders_cancelled: u64,
+    /// Wall-clock time the workers ran for
+    pub elapsed: Duration,
+    /// Best bid once every worker finished
+    pub best_bid: Option<u64>,
+    /// Best ask once every worker finished
+    pub best_ask: Option<u64>,
+    /// Invariant check of the book once every worker finished
+    pub validation: ValidationReport,
+}
+
+impl StressReport {
+    /// Total number of actions the workers performed
+    pub fn total_operations(&self) -> u64 {
+        self.orders_added + self.orders_rejected + self.market_orders + self.cancels_attempted
+    }
+
+    /// Actions performed per second of wall-clock time
+    pub fn operations_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.total_operations() as f64 / seconds
+    }
+
+    /// Returns true if the final book passed validation and is not crossed
+    pub fn is_valid(&self) -> bool {
+        let crossed =
+            matches!((self.best_bid, self.best_ask), (Some(bid), Some(ask)) if bid >= ask);
+        self.validation.is_valid() && !crossed
+    }
+
+    /// Panic with the list of broken invariants unless [`is_valid`](Self::is_valid) holds
+    pub fn assert_invariants(&self) {
+        assert!(self.is_valid(), "stress run left an invalid book: {self}");
+    }
+
+    fn merge(&mut self, other: &StressReport) {
+        self.orders_added += other.orders_added;
+        self.orders_rejected += other.orders_rejected;
+        self.market_orders += other.market_orders;
+        self.market_orders_filled += other.market_orders_filled;
+        self.cancels_attempted += other.cancels_attempted;
+        self.orders_cancelled += other.orders_cancelled;
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} operations in {:?} ({} added, {} rejected, {}/{} market orders filled, {}/{} cancels), best bid {:?}, best ask {:?}; {}",
+            self.total_operations(),
+            self.elapsed,
+            self.orders_added,
+            self.orders_rejected,
+            self.market_orders_filled,
+            self.market_orders,
+            self.orders_cancelled,
+            self.cancels_attempted,
+            self.best_bid,
+            self.best_ask,
+            self.validation
+        )
+    }
+}
+
+/// Splitmix64 generator, small and good enough to drive reproducible workloads
+struct StressRng(u64);
+
+impl StressRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound`, `bound` must be positive
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+type ExtraFieldsFn<T> = dyn Fn(OrderId) -> T + Send + Sync;
+
+/// Drives a shared [`OrderBook`] from several threads with a configurable mix of
+/// makers, takers and cancellers, then checks the invariants of the final book.
+///
+/// Every worker owns a generator seeded from [`StressConfig::seed`] and its
+/// thread index and draws order ids from its own range, so the sequence of
+/// actions each worker attempts is the same from run to run. With a single
+/// thread and [`RunLength::Operations`] the whole run is reproducible; with more
+/// threads only the interleaving varies. Extra fields of generated orders come
+/// from [`with_extra_fields`](Self::with_extra_fields), or `T::default()`.
+pub struct StressHarness<T> {
+    config: StressConfig,
+    extra_fields: Option<Arc<ExtraFieldsFn<T>>>,
+}
+
+impl<T> StressHarness<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Create a harness running the given configuration
+    pub fn new(config: StressConfig) -> Self {
+        Self {
+            config,
+            extra_fields: None,
+        }
+    }
+
+    /// Build the extra fields of every generated order from its id
+    pub fn with_extra_fields<F>(mut self, extra_fields: F) -> Self
+    where
+        F: Fn(OrderId) -> T + Send + Sync + 'static,
+    {
+        self.extra_fields = Some(Arc::new(extra_fields));
+        self
+    }
+
+    /// The configuration this harness runs
+    pub fn config(&self) -> &StressConfig {
+        &self.config
+    }
+
+    /// Preload the book, run every worker to completion and validate the result.
+    ///
+    /// Individual rejections are expected under load (post-only orders that
+    /// would cross, market orders on an empty side) and are only counted.
+    pub fn run(&self, book: &Arc<OrderBook<T>>) -> StressReport {
+        let config = &self.config;
+        assert!(config.threads > 0, "stress run needs at least one thread");
+        assert!(
+            config.total_weight() > 0,
+            "stress run needs a positive ratio"
+        );
+
+        let cancel_queue = Arc::new(Mutex::new(VecDeque::new()));
+        let mut report = StressReport::default();
+        self.preload(book, &cancel_queue, &mut report);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let barrier = Arc::new(Barrier::new(config.threads + 1));
+        let handles: Vec<_> = (0..config.threads)
+            .map(|index| {
+                let worker = Worker {
+                    index,
+                    config: config.clone(),
+                    extra_fields: self.extra_fields.clone(),
+                    book: Arc::clone(book),
+                    cancel_queue: Arc::clone(&cancel_queue),
+                    rng: StressRng::new(
+                        config.seed ^ (index as u64).wrapping_mul(0xA24B_AED4_963E_E407),
+                    ),
+                    next_id: 0,
+                    report: StressReport::default(),
+                };
+                let running = Arc::clone(&running);
+                let barrier = Arc::clone(&barrier);
+                thread::Builder::new()
+                    .name(format!("orderbook-stress-{index}"))
+                    .spawn(move || worker.run(&barrier, &running))
+                    .expect("failed to spawn stress worker")
+            })
+            .collect();
+
+        barrier.wait();
+        let start = Instant::now();
+        if let RunLength::Time(duration) = config.run_length {
+            thread::sleep(duration);
+            running.store(false, Ordering::Relaxed);
+        }
+        for handle in handles {
+            let worker_report = handle.join().expect("stress worker panicked");
+            report.merge(&worker_report);
+        }
+        report.elapsed = start.elapsed();
+
+        report.best_bid = book.best_bid();
+        report.best_ask = book.best_ask();
+        report.validation = book.validate();
+        trace!("Stress run on {} finished: {}", book.symbol(), report);
+        report
+    }
+
+    fn preload(
+        &self,
+        book: &OrderBook<T>,
+        cancel_queue: &Mutex<VecDeque<OrderId>>,
+        report: &mut StressReport,
+    ) {
+        let config = &self.config;
+        let mut rng = StressRng::new(config.seed);
+        // The range after the last worker's is reserved for preloaded orders
+        let mut next_id = 0;
+        for i in 0..config.preload_per_side * 2 {
+            let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+            let id = worker_order_id(config.threads, &mut next_id);
+            let price = passive_price(config, side, rng.below(config.price_levels.max(1)));
+            let quantity = 1 + rng.below(config.max_quantity.max(1));
+            let extra = self.extra_fields.as_ref().map(|f| f(id));
+            match book.add_limit_order(id, price, quantity, side, TimeInForce::Gtc, extra) {
+                Ok(_) => {
+                    report.orders_added += 1;
+                    push_cancel_candidate(cancel_queue, id);
+                }
+                Err(_) => report.orders_rejected += 1,
+            }
+        }
+    }
+}
+
+/// Order ids of worker `index` occupy their own range so workers never collide
+fn worker_order_id(index: usize, next_id: &mut u64) -> OrderId {
+    *next_id += 1;
+    OrderId::from_u64(((index as u64 + 1) << 40) | *next_id)
+}
+
+/// Price `level` ticks away from the mid price on the passive side of `side`
+fn passive_price(config: &StressConfig, side: Side, level: u64) -> u64 {
+    let offset = (level + 1) * config.tick_size;
+    match side {
+        Side::Buy => config.mid_price.saturating_sub(offset).max(1),
+        Side::Sell => config.mid_price.saturating_add(offset),
+    }
+}
+
+fn push_cancel_candidate(cancel_queue: &Mutex<VecDeque<OrderId>>, id: OrderId) {
+    if let Ok(mut queue) = cancel_queue.lock() {
+        queue.push_back(id);
+        if queue.len() > CANCEL_QUEUE_CAPACITY {
+            queue.pop_front();
+        }
+    }
+}
+
+struct Worker<T> {
+    index: usize,
+    config: StressConfig,
+    extra_fields: Option<Arc<ExtraFieldsFn<T>>>,
+    book: Arc<OrderBook<T>>,
+    cancel_queue: Arc<Mutex<VecDeque<OrderId>>>,
+    rng: StressRng,
+    next_id: u64,
+    report: StressReport,
+}
+
+impl<T> Worker<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    fn run(mut self, barrier: &Barrier, running: &AtomicBool) -> StressReport {
+        barrier.wait();
+        match self.config.run_length {
+            RunLength::Operations(operations) => {
+                for _ in 0..operations {
+                    self.step();
+                }
+            }
+            RunLength::Time(_) => {
+                while running.load(Ordering::Relaxed) {
+                    self.step();
+                }
+            }
+        }
+        self.report
+    }
+
+    fn step(&mut self) {
+        let roll = self.rng.below(self.config.total_weight());
+        let maker = u64::from(self.config.maker_weight);
+        let taker = u64::from(self.config.taker_weight);
+        if roll < maker {
+            self.make();
+        } else if roll < maker + taker {
+            self.take();
+        } else {
+            self.cancel();
+        }
+    }
+
+    fn next_order_id(&mut self) -> OrderId {
+        worker_order_id(self.index, &mut self.next_id)
+    }
+
+    fn extra(&self, id: OrderId) -> Option<T> {
+        self.extra_fields.as_ref().map(|f| f(id))
+    }
+
+    fn make(&mut self) {
+        let id = self.next_order_id();
+        let side = if self.rng.below(2) == 0 {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let level = self.rng.below(self.config.price_levels.max(1));
+        let quantity = 1 + self.rng.below(self.config.max_quantity.max(1));
+        let price = passive_price(&self.config, side, level);
+        let extra = self.extra(id);
+
+        let result = match self.rng.below(4) {
+            0 => self
+                .book
+                .add_limit_order(id, price, quantity, side, TimeInForce::Gtc, extra),
+            1 => self
+                .book
+                .add_post_only_order(id, price, quantity, side, TimeInForce::Gtc, extra),
+            2 => {
+                let visible = quantity.div_ceil(4);
+                self.book.add_iceberg_order(
+                    id,
+                    price,
+                    visible,
+                    quantity - visible,
+                    side,
+                    TimeInForce::Gtc,
+                    extra,
+                )
+            }
+            _ => {
+                // Aggressive IOC priced through the mid price on the other side
+                let cross = passive_price(&self.config, side.opposite(), level);
+                self.book
+                    .add_limit_order(id, cross, quantity, side, TimeInForce::Ioc, extra)
+            }
+        };
+
+        match result {
+            Ok(_) => {
+                self.report.orders_added += 1;
+                push_cancel_candidate(&self.cancel_queue, id);
+            }
+            Err(_) => self.report.orders_rejected += 1,
+        }
+    }
+
+    fn take(&mut self) {
+        let id = self.next_order_id();
+        let side = if self.rng.below(2) == 0 {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let quantity = 1 + self.rng.below(self.config.max_quantity.max(1));
+        self.report.market_orders += 1;
+        if let Ok(result) = self.book.submit_market_order(id, quantity, side)
+            && result.executed_quantity() > 0
+        {
+            self.report.market_orders_filled += 1;
+        }
+    }
+
+    fn cancel(&mut self) {
+        let candidate = self
+            .cancel_queue
+            .lock()
+            .ok()
+            .and_then(|mut queue| queue.pop_front());
+        // Without a candidate the cancel targets an id that was never sent
+        let id = candidate.unwrap_or_else(|| self.next_order_id());
+        self.report.cancels_attempted += 1;
+        if let Ok(Some(_)) = self.book.cancel_order(id) {
+            self.report.orders_cancelled += 1;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Unit tests for the per-account index of resting orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+
+    fn ids(orders: &[std::sync::Arc<OrderType<()>>]) -> Vec<OrderId> {
+        orders.iter().map(|order| order.id()).collect()
+    }
+
+    #[test]
+    fn test_orders_are_listed_per_account_in_acceptance_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_for_account(limit(1, 100, 10, Side::Buy), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(2, 110, 10, Side::Sell), "bob")
+            .unwrap();
+        book.add_order_for_account(limit(3, 99, 10, Side::Buy), "alice")
+            .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(4),
+            98,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ids(&book.orders_for_account("alice")),
+            vec![OrderId::from_u64(1), OrderId::from_u64(3)]
+        );
+        assert_eq!(
+            ids(&book.orders_for_account("bob")),
+            vec![OrderId::from_u64(2)]
+        );
+        assert!(book.orders_for_account("carol").is_empty());
+        assert_eq!(
+            book.order_account(OrderId::from_u64(1)).as_deref(),
+            Some("alice")
+        );
+        assert_eq!(book.order_account(OrderId::from_u64(4)), None);
+    }
+
+    #[test]
+    fn test_filled_and_cancelled_orders_leave_the_index() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_for_account(limit(1, 100, 10, Side::Sell), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(2, 101, 10, Side::Sell), "alice")
+            .unwrap();
+
+        book.submit_market_order(OrderId::from_u64(3), 10, Side::Buy)
+            .unwrap();
+        assert_eq!(
+            ids(&book.orders_for_account("alice")),
+            vec![OrderId::from_u64(2)]
+        );
+
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+        assert!(book.orders_for_account("alice").is_empty());
+        assert!(book.account_orders.is_empty());
+        assert!(book.order_accounts.is_empty());
+    }
+
+    #[test]
+    fn test_fully_matched_taker_is_not_indexed() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_order_for_account(limit(2, 100, 10, Side::Buy), "alice")
+            .unwrap();
+
+        assert!(book.orders_for_account("alice").is_empty());
+
+        // A partially matched taker rests with its owner
+        book.add_limit_order(
+            OrderId::from_u64(3),
+            100,
+            4,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_order_for_account(limit(4, 100, 10, Side::Buy), "alice")
+            .unwrap();
+        let resting = book.orders_for_account("alice");
+        assert_eq!(ids(&resting), vec![OrderId::from_u64(4)]);
+        assert_eq!(resting[0].visible_quantity(), 6);
+    }
+
+    #[test]
+    fn test_amendments_keep_the_owner() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let id = OrderId::from_u64(1);
+        book.add_order_for_account(limit(1, 100, 10, Side::Buy), "alice")
+            .unwrap();
+
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: id,
+            new_price: 101,
+        })
+        .unwrap();
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: id,
+            new_quantity: 5,
+        })
+        .unwrap();
+
+        let orders = book.orders_for_account("alice");
+        assert_eq!(ids(&orders), vec![id]);
+        assert_eq!(orders[0].price(), 101);
+        assert_eq!(orders[0].visible_quantity(), 5);
+    }
+
+    #[test]
+    fn test_cancel_all_for_account() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_for_account(limit(1, 100, 10, Side::Buy), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(2, 110, 10, Side::Sell), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(3, 99, 10, Side::Buy), "bob")
+            .unwrap();
+
+        assert_eq!(
+            ids(&book.cancel_all_for_account("alice")),
+            vec![OrderId::from_u64(1), OrderId::from_u64(2)]
+        );
+
+        assert!(book.orders_for_account("alice").is_empty());
+        assert_eq!(book.best_bid(), Some(99));
+        assert_eq!(book.best_ask(), None);
+        assert!(book.cancel_all_for_account("alice").is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+//! Unit tests for the amendment history of orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, OrderUpdate, Side};
+
+    fn reprice(book: &OrderBook, id: u64, new_price: u64) {
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(id),
+            new_price,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_price_and_quantity_changes_are_recorded_in_order() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.enable_amendment_history(8);
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        book.add_order(limit(2, 90, 10, Side::Buy)).unwrap();
+
+        reprice(&book, 1, 101);
+        reprice(&book, 2, 91);
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: OrderId::from_u64(1),
+            new_quantity: 4,
+        })
+        .unwrap();
+
+        let history = book.amendment_history(OrderId::from_u64(1));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].sequence, 1);
+        assert_eq!((history[0].old_price, history[0].new_price), (100, 101));
+        assert_eq!((history[0].old_quantity, history[0].new_quantity), (10, 10));
+        assert_eq!(history[1].sequence, 3);
+        assert_eq!((history[1].old_price, history[1].new_price), (101, 101));
+        assert_eq!((history[1].old_quantity, history[1].new_quantity), (10, 4));
+
+        let history = book.amendment_history(OrderId::from_u64(2));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sequence, 2);
+    }
+
+    #[test]
+    fn test_history_keeps_the_latest_amendments_past_the_order() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.enable_amendment_history(2);
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        reprice(&book, 1, 101);
+        reprice(&book, 1, 102);
+        reprice(&book, 1, 103);
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+
+        let history = book.amendment_history(OrderId::from_u64(1));
+        let prices: Vec<u64> = history
+            .iter()
+            .map(|amendment| amendment.new_price)
+            .collect();
+        assert_eq!(prices, vec![102, 103]);
+        assert_eq!(history[1].sequence, 3);
+
+        assert_eq!(book.remove_amendment_history(OrderId::from_u64(1)), history);
+        assert!(book.amendment_history(OrderId::from_u64(1)).is_empty());
+    }
+
+    #[test]
+    fn test_nothing_is_recorded_unless_enabled_or_applied() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        reprice(&book, 1, 101);
+        assert!(book.amendment_history(OrderId::from_u64(1)).is_empty());
+
+        book.enable_amendment_history(8);
+        assert!(
+            book.update_order(OrderUpdate::UpdatePrice {
+                order_id: OrderId::from_u64(1),
+                new_price: 101,
+            })
+            .is_err()
+        );
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(99),
+            new_price: 101,
+        })
+        .unwrap();
+        assert!(book.amendment_history(OrderId::from_u64(1)).is_empty());
+        assert!(book.amendment_history(OrderId::from_u64(99)).is_empty());
+    }
+}
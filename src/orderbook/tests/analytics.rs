@@ -0,0 +1,94 @@
+//! Unit tests for the microstructure analytics.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn book_with(levels: &[(u64, u64, Side)]) -> OrderBook<()> {
+        let book = OrderBook::new("TEST_SYMBOL");
+        for (price, quantity, side) in levels {
+            book.add_limit_order(
+                OrderId::new(),
+                *price,
+                *quantity,
+                *side,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_volume_imbalance_over_top_levels() {
+        let book = book_with(&[
+            (100, 30, Side::Buy),
+            (99, 50, Side::Buy),
+            (101, 10, Side::Sell),
+            (102, 10, Side::Sell),
+        ]);
+
+        assert_eq!(book.volume_imbalance(1), Some(0.5));
+        assert_eq!(book.volume_imbalance(2), Some(0.6));
+        assert_eq!(book.volume_imbalance(0), None);
+        assert_eq!(OrderBook::<()>::new("EMPTY").volume_imbalance(5), None);
+    }
+
+    #[test]
+    fn test_hidden_quantity_is_not_counted() {
+        let book = book_with(&[(101, 10, Side::Sell)]);
+        book.add_iceberg_order(
+            OrderId::new(),
+            100,
+            10,
+            90,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(book.volume_imbalance(1), Some(0.0));
+    }
+
+    #[test]
+    fn test_weighted_mid_price_leans_toward_thin_side() {
+        let book = book_with(&[(100, 30, Side::Buy), (110, 10, Side::Sell)]);
+
+        // (100 * 10 + 110 * 30) / 40
+        assert_eq!(book.weighted_mid_price(), Some(107.5));
+        assert_eq!(book.mid_price(), Some(105.0));
+        assert_eq!(
+            book_with(&[(100, 30, Side::Buy)]).weighted_mid_price(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_order_flow_imbalance() {
+        let book = book_with(&[(100, 10, Side::Buy), (105, 10, Side::Sell)]);
+        let previous = book.create_snapshot(1);
+        assert_eq!(book.order_flow_imbalance_since(&previous), 0);
+
+        // Bid quantity added at the same price
+        book.add_limit_order(OrderId::new(), 100, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        assert_eq!(book.order_flow_imbalance_since(&previous), 5);
+
+        // A higher bid counts its whole quantity, and part of the ask traded away
+        let previous = book.create_snapshot(1);
+        book.add_limit_order(OrderId::new(), 101, 7, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        book.submit_market_order(OrderId::new(), 4, Side::Buy)
+            .unwrap();
+        assert_eq!(book.order_flow_imbalance_since(&previous), 7 + 4);
+
+        // Ask quantity added at a lower price is selling pressure
+        let previous = book.create_snapshot(1);
+        book.add_limit_order(OrderId::new(), 104, 3, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        assert_eq!(book.order_flow_imbalance_since(&previous), -3);
+    }
+}
@@ -0,0 +1,131 @@
+//! Unit tests for the order flow anonymizer of the `anonymize` feature.
+
+#[cfg(all(test, feature = "anonymize"))]
+mod tests {
+    use crate::orderbook::anonymize::Anonymizer;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::replay::{ReplayOperation, ReplayRecord};
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce, Transaction};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_pseudonyms_are_consistent_and_keyed() {
+        let anonymizer = Anonymizer::new(b"secret");
+        let id = OrderId::from_u64(42);
+
+        assert_eq!(anonymizer.order_id(id), anonymizer.order_id(id));
+        assert_ne!(anonymizer.order_id(id), id);
+        assert_ne!(
+            anonymizer.order_id(id),
+            anonymizer.order_id(OrderId::from_u64(43))
+        );
+        assert_ne!(
+            anonymizer.order_id(id),
+            Anonymizer::new(b"other").order_id(id)
+        );
+        // The same value is mapped differently depending on what it identifies
+        assert_ne!(
+            anonymizer.transaction_id(Uuid::from_bytes(id.as_bytes())),
+            Uuid::from_bytes(anonymizer.order_id(id).as_bytes())
+        );
+
+        let account = anonymizer.account_id("ACC-001");
+        assert!(account.starts_with("acct-"));
+        assert_eq!(account, anonymizer.account_id("ACC-001"));
+        assert_ne!(account, anonymizer.account_id("ACC-002"));
+        assert!(!anonymizer.client_tag("desk-7").contains("desk"));
+    }
+
+    #[test]
+    fn test_timestamp_jitter_is_bounded_and_repeatable() {
+        let salt = Uuid::from_u128(7);
+        assert_eq!(Anonymizer::new(b"secret").timestamp(1_000, salt), 1_000);
+
+        let anonymizer = Anonymizer::new(b"secret").with_timestamp_jitter(50);
+        let mut moved = 0;
+        for timestamp in 10_000..10_200 {
+            let jittered = anonymizer.timestamp(timestamp, salt);
+            assert!(jittered.abs_diff(timestamp) <= 50);
+            assert_eq!(jittered, anonymizer.timestamp(timestamp, salt));
+            if jittered != timestamp {
+                moved += 1;
+            }
+        }
+        assert!(moved > 100);
+        // Timestamps close to zero do not wrap around
+        assert!(anonymizer.timestamp(10, salt) <= 60);
+    }
+
+    #[test]
+    fn test_transactions_keep_links_between_orders() {
+        let anonymizer = Anonymizer::new(b"secret");
+        let maker = OrderId::from_u64(1);
+        let trade = Transaction::new(
+            Uuid::from_u128(99),
+            OrderId::from_u64(2),
+            maker,
+            100,
+            5,
+            Side::Buy,
+        );
+
+        let anonymized = anonymizer.transaction(&trade);
+        assert_eq!(anonymized.maker_order_id, anonymizer.order_id(maker));
+        assert_ne!(anonymized.transaction_id, trade.transaction_id);
+        assert_eq!(
+            (anonymized.price, anonymized.quantity, anonymized.taker_side),
+            (100, 5, Side::Buy)
+        );
+    }
+
+    #[test]
+    fn test_orders_snapshots_and_logs() {
+        let anonymizer = Anonymizer::new(b"secret");
+        let order = OrderType::Standard {
+            id: OrderId::from_u64(1),
+            price: 100,
+            quantity: 10,
+            side: Side::Sell,
+            timestamp: 123,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: "client notes".to_string(),
+        };
+        let anonymized: OrderType<()> = anonymizer.order(&order);
+        assert_eq!(anonymized.id(), anonymizer.order_id(OrderId::from_u64(1)));
+        assert_eq!(anonymized.price(), 100);
+        assert_eq!(anonymized.timestamp(), 123);
+
+        let book: crate::OrderBook = BookBuilder::new("TEST")
+            .bid(100, 10)
+            .ask(101, 5)
+            .build()
+            .unwrap();
+        let snapshot = book.create_l3_snapshot(10);
+        let anonymized = anonymizer.l3_snapshot(&snapshot);
+        assert_eq!(anonymized.order_count(), 2);
+        assert!(snapshot.get_order(OrderId::from_u64(1)).is_some());
+        assert!(anonymized.get_order(OrderId::from_u64(1)).is_none());
+        assert_eq!(
+            anonymized
+                .get_order(anonymizer.order_id(OrderId::from_u64(1)))
+                .map(|order| order.remaining_quantity()),
+            Some(10)
+        );
+
+        let record = ReplayRecord {
+            sequence: 3,
+            operation: ReplayOperation::Cancel {
+                order_id: OrderId::from_u64(1),
+            },
+        };
+        assert_eq!(
+            anonymizer.replay_record(&record),
+            ReplayRecord {
+                sequence: 3,
+                operation: ReplayOperation::Cancel {
+                    order_id: anonymizer.order_id(OrderId::from_u64(1)),
+                },
+            }
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Unit tests for the order arena and the book built on it.
+
+#[cfg(all(test, feature = "arena"))]
+mod tests {
+    use crate::orderbook::arena::{ArenaOrderBook, OrderArena};
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+
+    fn limit_with(
+        id: u64,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        time_in_force: TimeInForce,
+    ) -> OrderType<()> {
+        OrderType::Standard {
+            id: OrderId::from_u64(id),
+            price,
+            quantity,
+            side,
+            timestamp: 0,
+            time_in_force,
+            extra_fields: (),
+        }
+    }
+
+    fn iceberg(id: u64, price: u64, visible: u64, hidden: u64, side: Side) -> OrderType<()> {
+        OrderType::IcebergOrder {
+            id: OrderId::from_u64(id),
+            price,
+            visible_quantity: visible,
+            hidden_quantity: hidden,
+            side,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        }
+    }
+
+    #[test]
+    fn test_arena_reuses_freed_slots() {
+        let mut arena = OrderArena::with_capacity(4);
+        let first = arena.insert(limit(1, 100, 10, Side::Buy));
+        let second = arena.insert(limit(2, 100, 10, Side::Buy));
+        assert_eq!(arena.len(), 2);
+
+        assert_eq!(
+            arena.remove(first).map(|order| order.id()),
+            Some(OrderId::from_u64(1))
+        );
+        assert!(arena.get(first).is_none());
+        assert!(arena.remove(first).is_none());
+
+        let third = arena.insert(limit(3, 100, 10, Side::Buy));
+        // The freed slot is reused, but the stale handle does not reach the new order
+        assert!(arena.get(first).is_none());
+        assert_eq!(
+            arena.get(third).map(|order| order.id()),
+            Some(OrderId::from_u64(3))
+        );
+        assert_eq!(
+            arena.get(second).map(|order| order.id()),
+            Some(OrderId::from_u64(2))
+        );
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.capacity(), 4);
+
+        arena.clear();
+        assert!(arena.is_empty());
+        assert!(arena.get(second).is_none());
+        assert!(arena.get(third).is_none());
+    }
+
+    #[test]
+    fn test_arena_book_matches_in_price_time_priority() {
+        let mut book: ArenaOrderBook = ArenaOrderBook::new("TEST");
+        book.add_order(limit(1, 101, 10, Side::Sell)).unwrap();
+        book.add_order(limit(2, 100, 10, Side::Sell)).unwrap();
+        book.add_order(limit(3, 100, 10, Side::Sell)).unwrap();
+        assert_eq!(book.best_ask(), Some(100));
+
+        let result = book.add_order(limit(4, 101, 25, Side::Buy)).unwrap();
+        let makers: Vec<_> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|trade| (trade.maker_order_id, trade.price, trade.quantity))
+            .collect();
+        assert_eq!(
+            makers,
+            vec![
+                (OrderId::from_u64(2), 100, 10),
+                (OrderId::from_u64(3), 100, 10),
+                (OrderId::from_u64(1), 101, 5),
+            ]
+        );
+        assert_eq!(result.remaining_quantity, 0);
+        assert_eq!(book.last_trade_price(), Some(101));
+        assert_eq!(book.order_count(), 1);
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .map(|order| order.visible_quantity()),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_arena_book_rests_remainder_and_cancels() {
+        let mut book: ArenaOrderBook = ArenaOrderBook::with_capacity("TEST", 16);
+        book.add_order(limit(1, 100, 10, Side::Sell)).unwrap();
+        let result = book.add_order(limit(2, 100, 30, Side::Buy)).unwrap();
+        assert_eq!(result.remaining_quantity, 20);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), None);
+
+        let cancelled = book.cancel_order(OrderId::from_u64(2)).unwrap();
+        assert_eq!(cancelled.visible_quantity(), 20);
+        assert_eq!(book.best_bid(), None);
+        assert!(book.cancel_order(OrderId::from_u64(2)).is_none());
+        assert_eq!(book.order_count(), 0);
+        assert_eq!(book.arena_capacity(), 16);
+    }
+
+    #[test]
+    fn test_arena_book_refreshes_iceberg_to_back_of_level() {
+        let mut book: ArenaOrderBook = ArenaOrderBook::new("TEST");
+        book.add_order(iceberg(1, 100, 10, 20, Side::Sell)).unwrap();
+        book.add_order(limit(2, 100, 10, Side::Sell)).unwrap();
+
+        book.add_order(limit(3, 100, 10, Side::Buy)).unwrap();
+        let queue: Vec<_> = book
+            .get_orders_at_price(100, Side::Sell)
+            .iter()
+            .map(|order| order.id())
+            .collect();
+        assert_eq!(queue, vec![OrderId::from_u64(2), OrderId::from_u64(1)]);
+        let refreshed = book.get_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(refreshed.visible_quantity(), 10);
+        assert_eq!(refreshed.hidden_quantity(), 10);
+    }
+
+    #[test]
+    fn test_arena_book_immediate_and_post_only_orders() {
+        let mut book: ArenaOrderBook = ArenaOrderBook::new("TEST");
+        book.add_order(limit(1, 100, 10, Side::Sell)).unwrap();
+
+        let fok = book.add_order(limit_with(2, 100, 20, Side::Buy, TimeInForce::Fok));
+        assert!(matches!(
+            fok,
+            Err(OrderBookError::InsufficientLiquidity { available: 10, .. })
+        ));
+        assert_eq!(book.order_count(), 1);
+
+        let ioc = book
+            .add_order(limit_with(3, 100, 15, Side::Buy, TimeInForce::Ioc))
+            .unwrap();
+        assert_eq!(ioc.remaining_quantity, 5);
+        assert_eq!(book.order_count(), 0);
+
+        book.add_order(limit(4, 100, 10, Side::Sell)).unwrap();
+        let post_only = OrderType::PostOnly {
+            id: OrderId::from_u64(5),
+            price: 100,
+            quantity: 10,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        };
+        assert!(matches!(
+            book.add_order(post_only),
+            Err(OrderBookError::PriceCrossing {
+                opposite_price: 100,
+                ..
+            })
+        ));
+        assert!(matches!(
+            book.add_order(limit(4, 99, 10, Side::Buy)),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_arena_book_market_order() {
+        let mut book: ArenaOrderBook = ArenaOrderBook::new("TEST");
+        assert!(matches!(
+            book.submit_market_order(OrderId::from_u64(1), 10, Side::Buy),
+            Err(OrderBookError::InsufficientLiquidity { .. })
+        ));
+        book.add_order(limit(2, 100, 10, Side::Sell)).unwrap();
+        book.add_order(limit(3, 102, 10, Side::Sell)).unwrap();
+        let result = book
+            .submit_market_order(OrderId::from_u64(4), 15, Side::Buy)
+            .unwrap();
+        assert_eq!(result.executed_quantity(), 15);
+        assert_eq!(result.filled_order_ids, vec![OrderId::from_u64(2)]);
+        assert_eq!(book.best_ask(), Some(102));
+    }
+}
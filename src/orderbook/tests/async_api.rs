@@ -0,0 +1,198 @@
+//! Unit tests for the tokio front end of the order book.
+
+#[cfg(all(test, feature = "async_api"))]
+mod tests {
+    use crate::orderbook::async_api::AsyncOrderBook;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::OrderBookEvent;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_limit_order_ack_reports_entry_fills() {
+        block_on(async {
+            let book = AsyncOrderBook::spawn(OrderBook::<()>::new("TEST"), 16);
+            let ack = book
+                .add_limit_order(
+                    OrderId::from_u64(1),
+                    100,
+                    10,
+                    Side::Sell,
+                    TimeInForce::Gtc,
+                    None,
+                )
+                .await
+                .unwrap();
+            assert!(ack.fills.is_empty());
+            assert!(ack.order.is_some());
+
+            let ack = book
+                .add_limit_order(
+                    OrderId::from_u64(2),
+                    100,
+                    4,
+                    Side::Buy,
+                    TimeInForce::Gtc,
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(ack.order_id, OrderId::from_u64(2));
+            assert_eq!(ack.fills.len(), 1);
+            assert!(!ack.fills[0].is_maker);
+            assert_eq!(ack.executed_quantity(), 4);
+            assert_eq!(book.best_ask().await.unwrap(), Some(100));
+        });
+    }
+
+    #[test]
+    fn test_market_order_and_fill_subscription() {
+        block_on(async {
+            let book = AsyncOrderBook::spawn(OrderBook::<()>::new("TEST"), 16);
+            let mut fills = book.subscribe_fills();
+            book.add_limit_order(
+                OrderId::from_u64(1),
+                100,
+                5,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .await
+            .unwrap();
+
+            let ack = book
+                .submit_market_order(OrderId::from_u64(2), 5, Side::Buy)
+                .await
+                .unwrap();
+            assert!(ack.order.is_none());
+            assert_eq!(ack.executed_quantity(), 5);
+
+            let first = fills.recv().await.unwrap();
+            let second = fills.recv().await.unwrap();
+            let maker = if first.is_maker { first } else { second };
+            assert_eq!(maker.order_id, OrderId::from_u64(1));
+            assert_eq!(maker.remaining_quantity, 0);
+        });
+    }
+
+    #[test]
+    fn test_errors_and_cancel() {
+        block_on(async {
+            let book = AsyncOrderBook::spawn(OrderBook::<()>::new("TEST"), 16);
+            assert!(
+                book.submit_market_order(OrderId::from_u64(1), 5, Side::Buy)
+                    .await
+                    .is_err()
+            );
+            book.add_limit_order(
+                OrderId::from_u64(2),
+                99,
+                5,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .await
+            .unwrap();
+            assert!(
+                book.cancel_order(OrderId::from_u64(2))
+                    .await
+                    .unwrap()
+                    .is_some()
+            );
+            assert!(
+                book.cancel_order(OrderId::from_u64(2))
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+            assert!(book.snapshot(5).await.unwrap().bids.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_concurrent_handles_are_serialized() {
+        block_on(async {
+            let book = AsyncOrderBook::spawn(OrderBook::<()>::new("TEST"), 4);
+            let tasks: Vec<_> = (0..20)
+                .map(|i| {
+                    let book = book.clone();
+                    tokio::spawn(async move {
+                        book.add_limit_order(
+                            OrderId::from_u64(i),
+                            100 + i,
+                            1,
+                            Side::Sell,
+                            TimeInForce::Gtc,
+                            None,
+                        )
+                        .await
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap().unwrap();
+            }
+            let count = book
+                .execute(|book| book.get_all_orders().len())
+                .await
+                .unwrap();
+            assert_eq!(count, 20);
+        });
+    }
+
+    #[test]
+    fn test_shutdown_returns_book_with_listener() {
+        block_on(async {
+            let events = Arc::new(AtomicUsize::new(0));
+            let counted = Arc::clone(&events);
+            let mut inner = OrderBook::<()>::new("TEST");
+            inner.set_event_listener(Arc::new(move |event| {
+                if matches!(event, OrderBookEvent::OrderRejected(_)) {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                }
+            }));
+
+            let book = AsyncOrderBook::spawn(inner, 16);
+            let other = book.clone();
+            assert!(
+                book.submit_market_order(OrderId::from_u64(1), 5, Side::Sell)
+                    .await
+                    .is_err()
+            );
+            assert_eq!(events.load(Ordering::SeqCst), 1);
+            book.add_limit_order(
+                OrderId::from_u64(2),
+                100,
+                5,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .await
+            .unwrap();
+
+            let inner = book.shutdown().await.unwrap();
+            assert_eq!(inner.best_bid(), Some(100));
+            assert!(other.best_bid().await.is_err());
+            assert!(other.shutdown().await.is_err());
+
+            assert!(
+                inner
+                    .submit_market_order(OrderId::from_u64(3), 5, Side::Buy)
+                    .is_err()
+            );
+            assert_eq!(events.load(Ordering::SeqCst), 2);
+        });
+    }
+}
@@ -0,0 +1,64 @@
+//! Unit tests for the event hash chain of the `audit` feature.
+
+#[cfg(all(test, feature = "audit"))]
+mod tests {
+    use crate::orderbook::audit::{AuditCheckpoint, encode_event, events_hash};
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_trail_is_empty_until_enabled() {
+        let book: OrderBook = OrderBook::new("TEST");
+        rest(&book, 1, 100, 10, Side::Buy);
+        assert!(book.audit_trail().is_empty());
+        assert_eq!(book.audit_sequence(), None);
+    }
+
+    #[test]
+    fn test_checkpoints_chain_and_match_the_book() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.enable_audit_trail(3);
+        // Level and order events are published without a listener
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 101, 10, Side::Sell);
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+
+        let trail = book.audit_trail();
+        assert_eq!(book.audit_sequence(), Some(6));
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail[0].sequence, 3);
+        assert_eq!(trail[1].sequence, 6);
+        assert_eq!(trail[1].checksum, book.checksum(usize::MAX));
+        assert_eq!(trail[1].version, book.version());
+        assert!(AuditCheckpoint::verify_chain(&trail));
+
+        let mut tampered = trail.clone();
+        tampered[0].checksum ^= 1;
+        assert!(!AuditCheckpoint::verify_chain(&tampered));
+    }
+
+    #[test]
+    fn test_events_hash_recomputes_from_the_event_log() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        let log: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        book.set_event_listener(Arc::new(move |event| {
+            sink.lock().unwrap().push(encode_event(event));
+        }));
+        book.enable_audit_trail(2);
+        rest(&book, 1, 100, 10, Side::Sell);
+        rest(&book, 2, 100, 4, Side::Buy);
+
+        let trail = book.audit_trail();
+        let log = log.lock().unwrap();
+        assert_eq!(trail.len(), log.len() / 2);
+        for (checkpoint, events) in trail.iter().zip(log.chunks(2)) {
+            assert_eq!(
+                checkpoint.events_hash,
+                events_hash(events.iter().map(Vec::as_slice))
+            );
+        }
+    }
+}
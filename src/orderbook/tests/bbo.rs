@@ -0,0 +1,106 @@
+//! Unit tests for top-of-book change notifications.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::bbo::{BboChange, TopOfBook};
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, OrderUpdate, Side};
+    use std::sync::{Arc, Mutex};
+
+    fn watched_book() -> (OrderBook, Arc<Mutex<Vec<BboChange>>>) {
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&changes);
+        book.on_bbo_change(Arc::new(move |change: &BboChange| {
+            sink.lock().unwrap().push(change.clone());
+        }));
+        (book, changes)
+    }
+
+    fn tob(bid: Option<(u64, u64)>, ask: Option<(u64, u64)>) -> TopOfBook {
+        TopOfBook {
+            bid_price: bid.map(|(price, _)| price),
+            bid_quantity: bid.map_or(0, |(_, quantity)| quantity),
+            ask_price: ask.map(|(price, _)| price),
+            ask_quantity: ask.map_or(0, |(_, quantity)| quantity),
+        }
+    }
+
+    fn currents(changes: &Mutex<Vec<BboChange>>) -> Vec<TopOfBook> {
+        changes
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|change| change.current)
+            .collect()
+    }
+
+    #[test]
+    fn test_fires_on_price_and_size_changes_only() {
+        let (book, changes) = watched_book();
+
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 99, 10, Side::Buy);
+        rest(&book, 3, 105, 5, Side::Sell);
+        rest(&book, 4, 100, 7, Side::Buy);
+        assert_eq!(
+            currents(&changes),
+            vec![
+                tob(Some((100, 10)), None),
+                tob(Some((100, 10)), Some((105, 5))),
+                tob(Some((100, 17)), Some((105, 5))),
+            ]
+        );
+
+        // Deeper levels do not move the top of book
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+        rest(&book, 5, 110, 5, Side::Sell);
+        assert!(currents(&changes).is_empty());
+
+        book.submit_market_order(OrderId::from_u64(6), 12, Side::Sell)
+            .unwrap();
+        book.cancel_order(OrderId::from_u64(4)).unwrap();
+        book.update_order(OrderUpdate::Cancel {
+            order_id: OrderId::from_u64(3),
+        })
+        .unwrap();
+        assert_eq!(
+            currents(&changes),
+            vec![
+                tob(Some((100, 5)), Some((105, 5))),
+                tob(None, Some((105, 5))),
+                tob(None, Some((110, 5))),
+            ]
+        );
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_change_carries_previous_state() {
+        let (book, changes) = watched_book();
+        rest(&book, 1, 100, 10, Side::Sell);
+        rest(&book, 2, 98, 10, Side::Sell);
+
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].symbol, "TEST_SYMBOL");
+        assert_eq!(changes[1].previous, tob(None, Some((100, 10))));
+        assert_eq!(changes[1].current, tob(None, Some((98, 10))));
+    }
+
+    #[test]
+    fn test_listener_starts_from_current_top_of_book() {
+        let mut book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&changes);
+        book.on_bbo_change(Arc::new(move |change: &BboChange| {
+            sink.lock().unwrap().push(change.clone());
+        }));
+
+        rest(&book, 2, 90, 10, Side::Buy);
+        assert!(changes.lock().unwrap().is_empty());
+        assert_eq!(book.top_of_book(), tob(Some((100, 10)), None));
+    }
+}
@@ -0,0 +1,58 @@
+//! Unit tests for the binary snapshot encoding of the `binary` feature.
+
+#[cfg(all(test, feature = "binary"))]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::snapshot::{OrderBookL3Snapshot, OrderBookSnapshot};
+    use pricelevel::Side;
+
+    fn book() -> OrderBook {
+        let mut builder = BookBuilder::new("TEST_SYMBOL").iceberg(Side::Sell, 200, 5, 20);
+        for level in 0..50 {
+            builder = builder.bid(100 - level, 10 + level).ask(150 + level, 7);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let snapshot = book().create_snapshot(usize::MAX);
+        let bytes = snapshot.to_bytes().unwrap();
+        let decoded = OrderBookSnapshot::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.symbol, snapshot.symbol);
+        assert_eq!(decoded.timestamp, snapshot.timestamp);
+        assert_eq!(decoded.bids.len(), 50);
+        assert_eq!(decoded.asks.len(), 51);
+        assert_eq!(decoded.best_bid(), snapshot.best_bid());
+        assert_eq!(decoded.total_ask_volume(), snapshot.total_ask_volume());
+        assert!(bytes.len() < serde_json::to_vec(&snapshot).unwrap().len());
+    }
+
+    #[test]
+    fn test_l3_snapshot_roundtrip() {
+        let snapshot = book().create_l3_snapshot(usize::MAX);
+        let bytes = snapshot.to_bytes().unwrap();
+        assert_eq!(OrderBookL3Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+        assert!(bytes.len() * 2 < serde_json::to_vec(&snapshot).unwrap().len());
+    }
+
+    #[test]
+    fn test_invalid_bytes_are_rejected() {
+        let bytes = book().create_l3_snapshot(5).to_bytes().unwrap();
+        assert!(matches!(
+            OrderBookL3Snapshot::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(OrderBookError::SerializationError { .. })
+        ));
+
+        let mut padded = bytes.clone();
+        padded.push(0);
+        let error = OrderBookL3Snapshot::from_bytes(&padded).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Serialization error: 1 trailing bytes after snapshot"
+        );
+    }
+}
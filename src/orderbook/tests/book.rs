@@ -1,13 +1,10 @@
 #[cfg(test)]
 mod tests {
+    use crate::orderbook::tests::helpers::create_order_id;
     use crate::{OrderBook, OrderBookError};
-    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+    use pricelevel::{OrderType, Side, TimeInForce};
 
     // Helper function to create a unique order ID
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     // Helper to create a standard limit order
     fn create_standard_order(price: u64, quantity: u64, side: Side) -> OrderType<()> {
         OrderType::Standard {
@@ -570,13 +567,10 @@ mod tests {
 #[cfg(test)]
 mod test_orderbook_book {
     use crate::OrderBook;
-    use pricelevel::{OrderId, Side, TimeInForce};
+    use crate::orderbook::tests::helpers::create_order_id;
+    use pricelevel::{Side, TimeInForce};
 
     // Helper function to create a unique order ID
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     #[test]
     fn test_market_close_timestamp() {
         let book: OrderBook<()> = OrderBook::new("TEST");
@@ -817,11 +811,8 @@ mod test_orderbook_book {
 #[cfg(test)]
 mod test_book_remaining {
     use crate::OrderBook;
-    use pricelevel::{OrderId, Side, TimeInForce};
-
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
+    use crate::orderbook::tests::helpers::create_order_id;
+    use pricelevel::{Side, TimeInForce};
 
     #[test]
     fn test_symbol_accessor() {
@@ -836,36 +827,23 @@ mod test_book_remaining {
         let book: OrderBook<()> = OrderBook::new("TEST");
 
         // Initially, market close is not set
-        assert!(
-            !book
-                .has_market_close
-                .load(std::sync::atomic::Ordering::Relaxed)
-        );
+        assert_eq!(book.options().options.market_close_timestamp, None);
 
         // Set market close timestamp
         let timestamp = 12345678;
         book.set_market_close_timestamp(timestamp);
 
         // Verify it was set correctly
-        assert!(
-            book.has_market_close
-                .load(std::sync::atomic::Ordering::Relaxed)
-        );
         assert_eq!(
-            book.market_close_timestamp
-                .load(std::sync::atomic::Ordering::Relaxed),
-            timestamp
+            book.options().options.market_close_timestamp,
+            Some(timestamp)
         );
 
         // Clear market close timestamp
         book.clear_market_close_timestamp();
 
         // Verify it was cleared
-        assert!(
-            !book
-                .has_market_close
-                .load(std::sync::atomic::Ordering::Relaxed)
-        );
+        assert_eq!(book.options().options.market_close_timestamp, None);
     }
 
     #[test]
@@ -984,13 +962,10 @@ mod test_book_remaining {
 
 #[cfg(test)]
 mod test_book_specific {
-    use crate::OrderBook;
+    use crate::orderbook::tests::helpers::create_order_id;
+    use crate::{BookBuilder, OrderBook};
     use pricelevel::{OrderId, Side, TimeInForce};
 
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     #[test]
     fn test_get_orders_at_price() {
         let book: OrderBook<()> = OrderBook::new("TEST");
@@ -1068,4 +1043,29 @@ mod test_book_specific {
             _ => panic!("Expected InsufficientLiquidity error"),
         }
     }
+
+    #[test]
+    fn test_liquidity_within_bps() {
+        let book: OrderBook<()> = BookBuilder::new("TEST")
+            .bids([(9_990, 10), (9_950, 20), (9_900, 30)])
+            .asks([(10_010, 5), (10_050, 15), (10_200, 25)])
+            .iceberg(Side::Sell, 10_010, 1, 4)
+            .build()
+            .unwrap();
+
+        // Mid is 10_000, so 50 bps covers 9_950..=10_050
+        assert_eq!(
+            book.liquidity_within_bps(50),
+            (30, 9_990 * 10 + 9_950 * 20, 25, 10_010 * 10 + 10_050 * 15)
+        );
+        assert_eq!(book.liquidity_within_bps(10), (10, 99_900, 10, 100_100));
+        assert_eq!(book.liquidity_within_bps(0), (0, 0, 0, 0));
+        assert_eq!(book.liquidity_within_bps(10_000).0, 60);
+    }
+
+    #[test]
+    fn test_liquidity_within_bps_one_sided() {
+        let book: OrderBook<()> = BookBuilder::new("TEST").bid(100, 10).build().unwrap();
+        assert_eq!(book.liquidity_within_bps(100), (0, 0, 0, 0));
+    }
 }
@@ -0,0 +1,109 @@
+//! Unit tests for the order book builder.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::OrderBookError;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_builds_levels_with_sequential_ids() {
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .bids([(100, 10), (99, 5)])
+            .asks([(101, 7), (102, 3)])
+            .build()
+            .unwrap();
+
+        assert_eq!(book.symbol(), "TEST_SYMBOL");
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+        for (id, price) in [(1, 100), (2, 99), (3, 101), (4, 102)] {
+            let order = book.get_order(OrderId::from_u64(id)).unwrap();
+            assert_eq!(order.price(), price);
+        }
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_special_orders_and_settings() {
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .starting_id(10)
+            .time_in_force(TimeInForce::Day)
+            .iceberg(Side::Sell, 105, 5, 20)
+            .post_only(Side::Buy, 95, 8)
+            .order(OrderType::Standard {
+                id: OrderId::from_u64(99),
+                price: 90,
+                quantity: 1,
+                side: Side::Buy,
+                timestamp: 0,
+                time_in_force: TimeInForce::Gtc,
+                extra_fields: (),
+            })
+            .bid(94, 2)
+            .build()
+            .unwrap();
+
+        let iceberg = book.get_order(OrderId::from_u64(10)).unwrap();
+        assert_eq!(iceberg.visible_quantity(), 5);
+        assert_eq!(iceberg.hidden_quantity(), 20);
+        assert_eq!(iceberg.time_in_force(), TimeInForce::Day);
+        assert!(matches!(
+            *book.get_order(OrderId::from_u64(11)).unwrap(),
+            OrderType::PostOnly { .. }
+        ));
+        assert_eq!(book.get_order(OrderId::from_u64(99)).unwrap().price(), 90);
+        assert_eq!(book.get_order(OrderId::from_u64(12)).unwrap().price(), 94);
+    }
+
+    #[test]
+    fn test_crossing_orders_trade() {
+        static TRADES: AtomicUsize = AtomicUsize::new(0);
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .trade_listener(|_| {
+                TRADES.fetch_add(1, Ordering::SeqCst);
+            })
+            .ask(100, 10)
+            .bid(100, 4)
+            .build()
+            .unwrap();
+
+        assert_eq!(TRADES.load(Ordering::SeqCst), 1);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .visible_quantity(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_build_fails_on_rejected_order() {
+        let result: Result<OrderBook, _> = BookBuilder::new("TEST_SYMBOL")
+            .ask(100, 10)
+            .post_only(Side::Buy, 100, 5)
+            .build();
+
+        assert!(matches!(result, Err(OrderBookError::PriceCrossing { .. })));
+    }
+
+    #[test]
+    fn test_clock_stamps_orders_and_is_installed() {
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .clock(Arc::new(ManualClock::new(42)))
+            .bid(100, 10)
+            .build()
+            .unwrap();
+
+        assert_eq!(book.now(), 42);
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1)).unwrap().timestamp(),
+            42
+        );
+    }
+}
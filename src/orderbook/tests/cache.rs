@@ -0,0 +1,139 @@
+//! Unit tests for best price cache invalidation.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::cache::CacheInvalidation;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn add(book: &OrderBook<()>, id: u64, price: u64, side: Side) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            10,
+            side,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    fn add_quantity(book: &OrderBook<()>, id: u64, price: u64, quantity: u64, side: Side) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            side,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    fn populated_book() -> OrderBook<()> {
+        let book = OrderBook::new("TEST_SYMBOL");
+        add(&book, 1, 100, Side::Buy);
+        add(&book, 2, 99, Side::Buy);
+        add(&book, 3, 101, Side::Sell);
+        add(&book, 4, 102, Side::Sell);
+        // Populate the cache
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+        book
+    }
+
+    #[test]
+    fn test_changes_away_from_the_touch_keep_the_cache() {
+        let book = populated_book();
+
+        add(&book, 5, 98, Side::Buy);
+        book.cancel_order(OrderId::from_u64(4)).unwrap();
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+
+        assert_eq!(book.cache.get_cached_best_bid(), Some(100));
+        assert_eq!(book.cache.get_cached_best_ask(), Some(101));
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_changes_at_the_touch_invalidate_their_side_only() {
+        let book = populated_book();
+
+        add(&book, 5, 100, Side::Buy);
+        assert_eq!(book.cache.get_cached_best_bid(), Some(100));
+
+        add(&book, 6, 100, Side::Buy);
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        assert_eq!(book.cache.get_cached_best_bid(), Some(100));
+        assert_eq!(book.cache.get_cached_best_ask(), None);
+        assert_eq!(book.best_ask(), Some(102));
+
+        add(&book, 7, 101, Side::Buy);
+        assert_eq!(book.cache.get_cached_best_bid(), None);
+        assert_eq!(book.cache.get_cached_best_ask(), Some(102));
+        assert_eq!(book.best_bid(), Some(101));
+    }
+
+    #[test]
+    fn test_matching_invalidates_only_when_a_level_is_consumed() {
+        let book = populated_book();
+
+        book.submit_market_order(OrderId::from_u64(5), 4, Side::Buy)
+            .unwrap();
+        assert_eq!(book.cache.get_cached_best_ask(), Some(101));
+
+        book.submit_market_order(OrderId::from_u64(6), 6, Side::Buy)
+            .unwrap();
+        assert_eq!(book.cache.get_cached_best_ask(), None);
+        assert_eq!(book.cache.get_cached_best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(102));
+    }
+
+    #[test]
+    fn test_sweep_invalidates_each_side_once() {
+        let book = populated_book();
+        let ask_generation = book.cache.generation(Side::Sell);
+        let bid_generation = book.cache.generation(Side::Buy);
+
+        // Consumes both ask levels and rests the remainder as the new best bid
+        add_quantity(&book, 5, 102, 25, Side::Buy);
+        assert_eq!(book.cache.generation(Side::Sell), ask_generation + 1);
+        assert_eq!(book.cache.generation(Side::Buy), bid_generation + 1);
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.best_bid(), Some(102));
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_coarser_granularities() {
+        let book = populated_book();
+        assert_eq!(book.cache_invalidation(), CacheInvalidation::PriceAware);
+
+        book.set_cache_invalidation(CacheInvalidation::PerSide);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+        add(&book, 5, 98, Side::Buy);
+        assert_eq!(book.cache.get_cached_best_bid(), None);
+        assert_eq!(book.cache.get_cached_best_ask(), Some(101));
+
+        book.set_cache_invalidation(CacheInvalidation::Wholesale);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+        add(&book, 6, 97, Side::Buy);
+        assert_eq!(book.cache.get_cached_best_bid(), None);
+        assert_eq!(book.cache.get_cached_best_ask(), None);
+    }
+
+    #[test]
+    fn test_price_computed_before_an_invalidation_is_not_cached() {
+        let book = populated_book();
+        book.cache.invalidate();
+        let generation = book.cache.generation(Side::Buy);
+
+        book.cache.level_added(Side::Buy, 105);
+        book.cache
+            .update_best_price(Side::Buy, generation, Some(100));
+
+        assert_eq!(book.cache.get_cached_best_bid(), None);
+    }
+}
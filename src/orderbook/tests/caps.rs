@@ -0,0 +1,161 @@
+//! Unit tests for the caps on resting orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::caps::{CapAction, RestingCap, RestingCaps};
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::execution_report::OrderStatus;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn add(
+        book: &OrderBook<()>,
+        id: u64,
+        price: u64,
+        quantity: u64,
+        side: Side,
+    ) -> Result<(), OrderBookError> {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            side,
+            TimeInForce::Gtc,
+            None,
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_orders_per_level_cap_rejects() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_resting_caps(Some(RestingCaps::default().with_max_orders_per_level(2)));
+        add(&book, 1, 100, 10, Side::Buy).unwrap();
+        add(&book, 2, 100, 10, Side::Buy).unwrap();
+
+        let result = add(&book, 3, 100, 10, Side::Buy);
+        assert!(matches!(
+            result,
+            Err(OrderBookError::RestingCapExceeded {
+                cap: RestingCap::OrdersPerLevel,
+                limit: 2
+            })
+        ));
+        assert_eq!(
+            book.order_status(OrderId::from_u64(3)),
+            OrderStatus::Rejected
+        );
+        // Other levels are not affected
+        add(&book, 4, 99, 10, Side::Buy).unwrap();
+    }
+
+    #[test]
+    fn test_orders_per_level_cap_evicts_the_oldest() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_resting_caps(Some(
+            RestingCaps::default()
+                .with_max_orders_per_level(2)
+                .with_action(CapAction::EvictOldest),
+        ));
+        add(&book, 1, 100, 10, Side::Buy).unwrap();
+        add(&book, 2, 100, 10, Side::Buy).unwrap();
+        add(&book, 3, 100, 10, Side::Buy).unwrap();
+
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert_eq!(
+            book.order_status(OrderId::from_u64(1)),
+            OrderStatus::Cancelled
+        );
+        assert!(book.get_order(OrderId::from_u64(2)).is_some());
+        assert!(book.get_order(OrderId::from_u64(3)).is_some());
+    }
+
+    #[test]
+    fn test_quantity_per_level_cap() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_resting_caps(Some(RestingCaps::default().with_max_quantity_per_level(25)));
+        add(&book, 1, 100, 10, Side::Sell).unwrap();
+        add(&book, 2, 100, 10, Side::Sell).unwrap();
+        assert!(add(&book, 3, 100, 10, Side::Sell).is_err());
+        add(&book, 4, 100, 5, Side::Sell).unwrap();
+
+        book.set_resting_caps(Some(
+            RestingCaps::default()
+                .with_max_quantity_per_level(25)
+                .with_action(CapAction::EvictOldest),
+        ));
+        add(&book, 5, 100, 15, Side::Sell).unwrap();
+        // Orders 1 and 2 made room for 15
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert!(book.get_order(OrderId::from_u64(2)).is_none());
+        assert!(book.get_order(OrderId::from_u64(4)).is_some());
+        // An order larger than the cap can never fit
+        assert!(add(&book, 6, 100, 30, Side::Sell).is_err());
+        assert!(book.get_order(OrderId::from_u64(4)).is_some());
+    }
+
+    #[test]
+    fn test_resting_orders_cap() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_resting_caps(Some(RestingCaps::default().with_max_resting_orders(3)));
+        add(&book, 1, 100, 10, Side::Buy).unwrap();
+        add(&book, 2, 99, 10, Side::Buy).unwrap();
+        add(&book, 3, 110, 10, Side::Sell).unwrap();
+        assert!(matches!(
+            add(&book, 4, 98, 10, Side::Buy),
+            Err(OrderBookError::RestingCapExceeded {
+                cap: RestingCap::RestingOrders,
+                ..
+            })
+        ));
+
+        // Aggressive orders that do not rest are not capped
+        add(&book, 5, 110, 10, Side::Buy).unwrap();
+        add(&book, 6, 111, 10, Side::Sell).unwrap();
+    }
+
+    #[test]
+    fn test_resting_orders_cap_evicts_furthest_from_the_touch() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_resting_caps(Some(
+            RestingCaps::default()
+                .with_max_resting_orders(3)
+                .with_action(CapAction::EvictOldest),
+        ));
+        add(&book, 1, 100, 10, Side::Buy).unwrap();
+        add(&book, 2, 98, 10, Side::Buy).unwrap();
+        add(&book, 3, 110, 10, Side::Sell).unwrap();
+        add(&book, 4, 101, 10, Side::Buy).unwrap();
+
+        assert!(book.get_order(OrderId::from_u64(2)).is_none());
+        assert_eq!(book.best_bid(), Some(101));
+        assert!(book.get_order(OrderId::from_u64(3)).is_some());
+    }
+
+    #[test]
+    fn test_remainder_beyond_the_cap_is_cancelled() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        add(&book, 1, 100, 5, Side::Buy).unwrap();
+        add(&book, 2, 110, 5, Side::Sell).unwrap();
+        add(&book, 3, 111, 5, Side::Sell).unwrap();
+        book.set_resting_caps(Some(RestingCaps::default().with_max_resting_orders(2)));
+
+        // Trades 5 at 100, then finds the book full
+        let order = book
+            .add_limit_order(
+                OrderId::from_u64(4),
+                100,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        assert_eq!(order.visible_quantity(), 5);
+        assert!(book.get_order(OrderId::from_u64(4)).is_none());
+        assert_eq!(
+            book.order_status(OrderId::from_u64(4)),
+            OrderStatus::Cancelled
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Unit tests for the top of book checksum.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::checksum::{ChecksumFormat, crc32, levels_checksum};
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn sample_book() -> OrderBook<()> {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 99, 20, Side::Buy);
+        rest(&book, 3, 98, 30, Side::Buy);
+        rest(&book, 4, 101, 5, Side::Sell);
+        rest(&book, 5, 102, 15, Side::Sell);
+        book
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_interleaved_layout() {
+        let bids = [(100, 10), (99, 20), (98, 30)];
+        let asks = [(101, 5)];
+        assert_eq!(
+            levels_checksum(&bids, &asks, ChecksumFormat::Interleaved),
+            crc32(b"100:10:101:5:99:20:98:30")
+        );
+    }
+
+    #[test]
+    fn test_concatenated_layout() {
+        let bids = [(100, 10), (99, 20)];
+        let asks = [(101, 5), (102, 15)];
+        assert_eq!(
+            levels_checksum(&bids, &asks, ChecksumFormat::Concatenated),
+            crc32(b"101510215100109920")
+        );
+    }
+
+    #[test]
+    fn test_book_checksum_covers_the_top_levels() {
+        let book = sample_book();
+        assert_eq!(book.checksum(2), crc32(b"100:10:101:5:99:20:102:15"));
+        assert_eq!(
+            book.checksum_with(10, ChecksumFormat::Concatenated),
+            crc32(b"1015102151001099209830")
+        );
+        assert_eq!(book.checksum(0), crc32(b""));
+    }
+
+    #[test]
+    fn test_book_and_snapshot_agree() {
+        let book = sample_book();
+        let snapshot = book.create_snapshot(usize::MAX);
+        for depth in [1, 2, 5] {
+            for format in [ChecksumFormat::Interleaved, ChecksumFormat::Concatenated] {
+                assert_eq!(
+                    book.checksum_with(depth, format),
+                    snapshot.checksum(depth, format)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum_follows_the_book() {
+        let book = sample_book();
+        let before = book.checksum(5);
+        rest(&book, 6, 99, 1, Side::Buy);
+        assert_ne!(book.checksum(5), before);
+
+        book.cancel_order(OrderId::from_u64(6)).unwrap();
+        assert_eq!(book.checksum(5), before);
+    }
+
+    #[test]
+    fn test_levels_showing_nothing_are_skipped() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 99, 20, Side::Buy);
+        rest(&book, 2, 98, 30, Side::Buy);
+        rest(&book, 3, 102, 15, Side::Sell);
+        // Fully hidden levels at the top of each side
+        book.add_iceberg_order(
+            OrderId::from_u64(4),
+            100,
+            0,
+            50,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_iceberg_order(
+            OrderId::from_u64(5),
+            101,
+            0,
+            50,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(book.checksum(2), crc32(b"99:20:102:15:98:30"));
+        assert_eq!(
+            book.create_snapshot(usize::MAX)
+                .checksum(2, ChecksumFormat::Interleaved),
+            book.checksum(2)
+        );
+    }
+}
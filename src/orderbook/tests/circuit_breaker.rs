@@ -0,0 +1,135 @@
+//! Unit tests for the sweep guard halting trading.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::circuit_breaker::{SweepGuard, TradingHalt};
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::session::TradingState;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    type Halts = Arc<Mutex<Vec<TradingHalt>>>;
+
+    /// A book with 10 offered at 100 and 10 at 101, halting once half of a
+    /// side trades within a second
+    fn setup() -> (OrderBook, Arc<ManualClock>, Halts) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let halts: Halts = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&halts);
+        let mut book = OrderBook::new("TEST");
+        book.set_clock(clock.clone());
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::TradingHalted(halt) = event {
+                recorded.lock().unwrap().push(*halt);
+            }
+        }));
+        book.set_sweep_guard(Some(SweepGuard::new(5_000, 1_000)));
+        add(&book, 1, 100, 10, Side::Sell).unwrap();
+        add(&book, 2, 101, 10, Side::Sell).unwrap();
+        (book, clock, halts)
+    }
+
+    fn add(
+        book: &OrderBook,
+        id: u64,
+        price: u64,
+        quantity: u64,
+        side: Side,
+    ) -> Result<(), OrderBookError> {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            side,
+            TimeInForce::Gtc,
+            None,
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_sweeping_half_a_side_halts_trading() {
+        let (book, _clock, halts) = setup();
+        add(&book, 3, 100, 6, Side::Buy).unwrap();
+        assert!(!book.is_trading_halted());
+
+        add(&book, 4, 101, 5, Side::Buy).unwrap();
+        assert!(book.is_trading_halted());
+        assert_eq!(book.trading_state(), TradingState::Halted);
+        assert_eq!(
+            *halts.lock().unwrap(),
+            vec![TradingHalt {
+                side: Side::Sell,
+                swept_quantity: 11,
+                resting_quantity: 9,
+                window_ms: 1_000,
+                timestamp: 1_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_halted_book_rejects_orders_but_accepts_cancels() {
+        let (book, _clock, halts) = setup();
+        add(&book, 3, 101, 11, Side::Buy).unwrap();
+        assert!(book.is_trading_halted());
+
+        assert!(matches!(
+            add(&book, 4, 90, 5, Side::Buy),
+            Err(OrderBookError::InvalidTradingState {
+                state: TradingState::Halted
+            })
+        ));
+        assert!(matches!(
+            book.submit_market_order(OrderId::from_u64(5), 1, Side::Buy),
+            Err(OrderBookError::InvalidTradingState { .. })
+        ));
+        assert!(book.cancel_order(OrderId::from_u64(2)).unwrap().is_some());
+        assert_eq!(book.best_ask(), None);
+        // Halting is published once, however much more trades
+        assert_eq!(halts.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_executions_leave_the_window() {
+        let (book, clock, halts) = setup();
+        add(&book, 3, 100, 6, Side::Buy).unwrap();
+        clock.advance(1_001);
+
+        // 5 of the 14 left trade in the new window
+        add(&book, 4, 101, 5, Side::Buy).unwrap();
+        assert!(!book.is_trading_halted());
+        assert!(halts.lock().unwrap().is_empty());
+
+        // Within the window of the last trade, 9 of 14 now have
+        add(&book, 5, 101, 4, Side::Buy).unwrap();
+        assert!(book.is_trading_halted());
+        let halt = halts.lock().unwrap()[0];
+        assert_eq!((halt.swept_quantity, halt.resting_quantity), (9, 5));
+    }
+
+    #[test]
+    fn test_resume_trading_starts_a_new_window() {
+        let (book, _clock, _halts) = setup();
+        add(&book, 3, 101, 11, Side::Buy).unwrap();
+        assert!(book.resume_trading());
+        assert!(!book.resume_trading());
+        assert_eq!(book.trading_state(), TradingState::Open);
+
+        // Without the executions before the halt, 4 of 9 stay under half
+        add(&book, 4, 101, 4, Side::Buy).unwrap();
+        assert!(!book.is_trading_halted());
+    }
+
+    #[test]
+    fn test_book_without_guard_never_halts() {
+        let (book, _clock, halts) = setup();
+        book.set_sweep_guard(None);
+        add(&book, 3, 101, 20, Side::Buy).unwrap();
+        assert!(!book.is_trading_halted());
+        assert!(halts.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Unit tests for the client order id index.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::{OrderBookEvent, RejectReason};
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_resting_order_is_found_by_client_id() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Buy), "abc-1")
+            .unwrap();
+
+        assert_eq!(book.find_by_client_id("abc-1"), Some(OrderId::from_u64(1)));
+        assert_eq!(
+            book.client_order_id(OrderId::from_u64(1)).as_deref(),
+            Some("abc-1")
+        );
+        assert_eq!(book.find_by_client_id("abc-2"), None);
+    }
+
+    #[test]
+    fn test_duplicate_client_id_is_rejected() {
+        let rejects = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&rejects);
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderRejected(reject) = event {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push((reject.order_id, reject.reason));
+            }
+        }));
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Buy), "abc-1")
+            .unwrap();
+
+        let result = book.add_order_with_client_id(limit(2, 99, 10, Side::Buy), "abc-1");
+        assert!(matches!(
+            result,
+            Err(OrderBookError::DuplicateClientOrderId { ref client_order_id })
+                if client_order_id == "abc-1"
+        ));
+        assert!(book.get_order(OrderId::from_u64(2)).is_none());
+        assert_eq!(book.find_by_client_id("abc-1"), Some(OrderId::from_u64(1)));
+        assert_eq!(
+            *rejects.lock().unwrap(),
+            vec![(OrderId::from_u64(2), RejectReason::DuplicateClientOrderId)]
+        );
+    }
+
+    #[test]
+    fn test_client_id_is_freed_when_the_order_stops_working() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Buy), "cancelled")
+            .unwrap();
+        book.add_order_with_client_id(limit(2, 110, 5, Side::Sell), "filled")
+            .unwrap();
+
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        book.submit_market_order(OrderId::from_u64(3), 5, Side::Buy)
+            .unwrap();
+
+        assert_eq!(book.find_by_client_id("cancelled"), None);
+        assert_eq!(book.find_by_client_id("filled"), None);
+        assert_eq!(book.client_order_id(OrderId::from_u64(2)), None);
+
+        book.add_order_with_client_id(limit(4, 100, 10, Side::Buy), "cancelled")
+            .unwrap();
+        assert_eq!(
+            book.find_by_client_id("cancelled"),
+            Some(OrderId::from_u64(4))
+        );
+    }
+
+    #[test]
+    fn test_orders_that_never_rest_do_not_keep_the_id() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            5,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        // Filled on entry
+        book.add_order_with_client_id(limit(2, 100, 3, Side::Buy), "taker")
+            .unwrap();
+        assert_eq!(book.find_by_client_id("taker"), None);
+
+        // Rejected: a post-only order crossing the book
+        let post_only = OrderType::PostOnly {
+            id: OrderId::from_u64(3),
+            price: 100,
+            quantity: 1,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        };
+        assert!(book.add_order_with_client_id(post_only, "post").is_err());
+        assert_eq!(book.find_by_client_id("post"), None);
+    }
+
+    #[test]
+    fn test_amendments_keep_the_client_id() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let order_id = OrderId::from_u64(1);
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Buy), "abc-1")
+            .unwrap();
+
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id,
+            new_price: 101,
+        })
+        .unwrap();
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id,
+            new_quantity: 4,
+        })
+        .unwrap();
+
+        assert_eq!(book.find_by_client_id("abc-1"), Some(order_id));
+    }
+
+    #[test]
+    fn test_clear_forgets_client_ids() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Buy), "abc-1")
+            .unwrap();
+        book.clear();
+        assert_eq!(book.find_by_client_id("abc-1"), None);
+    }
+}
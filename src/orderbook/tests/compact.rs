@@ -0,0 +1,323 @@
+//! Unit tests for the compact order book.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::BookBuilder;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::compact::{CompactOrder, CompactOrderBook};
+    use crate::orderbook::constraints::OrderConstraints;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::trade_channel::{OverflowPolicy, TradeChannel};
+    use pricelevel::{OrderId, OrderType, OrderUpdate, PriceLevelSnapshot, Side, TimeInForce};
+    use std::mem::size_of;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn id(n: u64) -> OrderId {
+        OrderId::from_u64(n)
+    }
+
+    #[test]
+    fn test_compact_order_is_smaller_than_order_type() {
+        assert_eq!(size_of::<CompactOrder>(), 32);
+        assert!(size_of::<CompactOrder>() * 2 < size_of::<OrderType<()>>());
+    }
+
+    #[test]
+    fn test_orders_rest_with_increasing_sequence() {
+        let mut book = CompactOrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(id(1), 100, 10, Side::Buy).unwrap();
+        book.add_limit_order(id(2), 100, 5, Side::Buy).unwrap();
+        book.add_limit_order(id(3), 105, 7, Side::Sell).unwrap();
+
+        assert_eq!(book.order_count(), 3);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(105));
+        assert_eq!(book.spread(), Some(5));
+        assert_eq!(
+            book.get_orders_at_price(100, Side::Buy),
+            vec![
+                CompactOrder {
+                    handle: 0,
+                    price: 100,
+                    quantity: 10,
+                    sequence: 0,
+                },
+                CompactOrder {
+                    handle: 1,
+                    price: 100,
+                    quantity: 5,
+                    sequence: 1,
+                },
+            ]
+        );
+        assert_eq!(book.order_id(1), Some(id(2)));
+        let (bids, asks) = book.get_volume_by_price();
+        assert_eq!(bids.get(&100), Some(&15));
+        assert_eq!(asks.get(&105), Some(&7));
+    }
+
+    #[test]
+    fn test_crossing_limit_order_matches_in_price_time_priority() {
+        let mut book = CompactOrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(id(1), 101, 5, Side::Sell).unwrap();
+        book.add_limit_order(id(2), 100, 5, Side::Sell).unwrap();
+        book.add_limit_order(id(3), 100, 5, Side::Sell).unwrap();
+
+        let result = book.add_limit_order(id(4), 100, 8, Side::Buy).unwrap();
+        let fills: Vec<_> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|transaction| (transaction.maker_order_id, transaction.quantity))
+            .collect();
+        assert_eq!(fills, vec![(id(2), 5), (id(3), 3)]);
+        assert_eq!(result.filled_order_ids, vec![id(2)]);
+        assert_eq!(result.remaining_quantity, 0);
+        assert_eq!(book.get_order(id(3)).map(|order| order.quantity), Some(2));
+        assert!(book.get_order(id(4)).is_none());
+        assert_eq!(book.last_trade_price(), Some(100));
+
+        // The remainder of a partially matched order rests at its limit
+        let result = book.add_limit_order(id(5), 101, 10, Side::Buy).unwrap();
+        assert_eq!(result.remaining_quantity, 3);
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.best_bid(), Some(101));
+        assert_eq!(book.get_order(id(5)).map(|order| order.quantity), Some(3));
+    }
+
+    #[test]
+    fn test_market_orders_and_cancels() {
+        let mut book = CompactOrderBook::new("TEST_SYMBOL");
+        assert!(matches!(
+            book.submit_market_order(id(1), 5, Side::Buy),
+            Err(OrderBookError::InsufficientLiquidity { .. })
+        ));
+
+        book.add_limit_order(id(2), 100, 5, Side::Buy).unwrap();
+        book.add_limit_order(id(3), 99, 5, Side::Buy).unwrap();
+        let result = book.submit_market_order(id(4), 7, Side::Sell).unwrap();
+        assert_eq!(result.executed_quantity(), 7);
+        assert_eq!(book.best_bid(), Some(99));
+
+        let cancelled = book.cancel_order(id(3)).unwrap();
+        assert_eq!(cancelled.quantity, 3);
+        assert!(book.cancel_order(id(3)).is_none());
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.order_count(), 0);
+    }
+
+    #[test]
+    fn test_invalid_orders_are_rejected() {
+        let mut book = CompactOrderBook::with_capacity("TEST_SYMBOL", 16);
+        assert!(book.add_limit_order(id(1), 100, 0, Side::Buy).is_err());
+        book.add_limit_order(id(1), 100, 5, Side::Buy).unwrap();
+        assert!(book.add_limit_order(id(1), 100, 5, Side::Buy).is_err());
+        assert_eq!(book.order_count(), 1);
+    }
+
+    #[test]
+    fn test_handles_of_departed_orders_are_reused() {
+        let mut book = CompactOrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(id(1), 100, 5, Side::Buy).unwrap();
+        book.add_limit_order(id(2), 100, 5, Side::Buy).unwrap();
+        book.cancel_order(id(1));
+        assert_eq!(book.order_id(0), None);
+
+        book.add_limit_order(id(3), 99, 5, Side::Buy).unwrap();
+        assert_eq!(book.get_order(id(3)).map(|order| order.handle), Some(0));
+        assert_eq!(book.order_id(0), Some(id(3)));
+
+        // A filled maker gives its handle back too
+        book.submit_market_order(id(4), 5, Side::Sell).unwrap();
+        book.add_limit_order(id(5), 98, 5, Side::Buy).unwrap();
+        assert_eq!(book.get_order(id(5)).map(|order| order.handle), Some(1));
+    }
+
+    #[test]
+    fn test_order_book_with_compact_storage() {
+        let book: OrderBook = OrderBook::with_compact_storage("TEST_SYMBOL");
+        assert!(book.has_compact_storage());
+        book.add_limit_order(id(1), 101, 5, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(id(2), 100, 5, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(id(3), 99, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(99));
+        assert_eq!(book.best_ask(), Some(100));
+        assert_eq!(book.spread(), Some(1));
+        assert_eq!(
+            book.get_order(id(2)).map(|order| order.visible_quantity()),
+            Some(5)
+        );
+
+        // Only good-till-cancelled standard orders fit the compact storage
+        assert!(
+            book.add_limit_order(id(4), 99, 5, Side::Buy, TimeInForce::Ioc, None)
+                .is_err()
+        );
+        assert!(
+            book.add_post_only_order(id(5), 98, 5, Side::Buy, TimeInForce::Gtc, None)
+                .is_err()
+        );
+
+        let result = book.submit_market_order(id(6), 7, Side::Buy).unwrap();
+        assert_eq!(result.executed_quantity(), 7);
+        assert_eq!(book.last_trade_price(), Some(101));
+        assert_eq!(
+            book.get_orders_at_price(101, Side::Sell)
+                .iter()
+                .map(|order| (order.id(), order.visible_quantity()))
+                .collect::<Vec<_>>(),
+            vec![(id(1), 3)]
+        );
+
+        let cancelled = book.cancel_order(id(3)).unwrap().unwrap();
+        assert_eq!(cancelled.id(), id(3));
+        assert_eq!(book.best_bid(), None);
+        let (bids, asks) = book.get_volume_by_price();
+        assert!(bids.is_empty());
+        assert_eq!(asks.get(&101), Some(&3));
+    }
+
+    #[test]
+    fn test_builder_creates_compact_storage() {
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .compact_storage()
+            .bid(100, 5)
+            .ask(101, 5)
+            .build()
+            .unwrap();
+        assert!(book.has_compact_storage());
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+    }
+
+    fn compact_book() -> OrderBook {
+        BookBuilder::new("TEST_SYMBOL")
+            .compact_storage()
+            .bids([(100, 5), (99, 7)])
+            .asks([(101, 3), (101, 4), (103, 6)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_order_book_reads_compact_levels() {
+        let book = compact_book();
+        let snapshot = book.create_snapshot(10);
+        let levels = |levels: &[PriceLevelSnapshot]| {
+            levels
+                .iter()
+                .map(|level| (level.price, level.visible_quantity, level.order_count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(levels(&snapshot.bids), vec![(100, 5, 1), (99, 7, 1)]);
+        assert_eq!(levels(&snapshot.asks), vec![(101, 7, 2), (103, 6, 1)]);
+        assert_eq!(snapshot.asks[0].orders[1].id(), id(4));
+
+        let top = book.top_of_book();
+        assert_eq!((top.bid_price, top.bid_quantity), (Some(100), 5));
+        assert_eq!((top.ask_price, top.ask_quantity), (Some(101), 7));
+        assert_eq!(book.depth_totals(1).ask_quantity, 7);
+        assert_eq!(book.peek_match(Side::Buy, 20, Some(101)), 7);
+        assert_eq!(book.liquidity_within_bps(1_000), (12, 1193, 13, 1325));
+        assert_eq!(book.get_all_orders().len(), 5);
+        assert_eq!(book.iter_orders_at_price(101, Side::Sell).count(), 2);
+
+        let report = book.validate();
+        assert!(report.is_valid());
+        assert_eq!((report.levels_checked, report.orders_checked), (4, 5));
+
+        let plain: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .bids([(100, 5), (99, 7)])
+            .asks([(101, 3), (101, 4), (103, 6)])
+            .build()
+            .unwrap();
+        assert_eq!(book.checksum(10), plain.checksum(10));
+    }
+
+    #[test]
+    fn test_order_book_mass_cancels_compact_orders() {
+        let book = compact_book();
+        let cancelled = book.cancel_price_range(Side::Sell, 101, 102);
+        assert_eq!(
+            cancelled.iter().map(|order| order.id()).collect::<Vec<_>>(),
+            vec![id(3), id(4)]
+        );
+        assert_eq!(book.best_ask(), Some(103));
+
+        assert_eq!(book.cancel_side(Side::Buy).len(), 2);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.cancel_all().len(), 1);
+        assert_eq!(book.create_snapshot(10).asks.len(), 0);
+        assert!(book.validate().is_valid());
+
+        let ids = book
+            .seed(&[(100, 5, Side::Buy), (101, 5, Side::Sell)])
+            .unwrap();
+        assert_eq!(book.get_order(ids[1]).unwrap().price(), 101);
+        assert_eq!(book.spread(), Some(1));
+    }
+
+    #[test]
+    fn test_order_book_rejects_what_compact_storage_does_not_keep() {
+        let book = compact_book();
+        let invalid = |result: Result<_, OrderBookError>| {
+            matches!(result, Err(OrderBookError::InvalidOperation { .. }))
+        };
+        assert!(invalid(
+            book.update_order(OrderUpdate::UpdateQuantity {
+                order_id: id(1),
+                new_quantity: 2,
+            })
+            .map(|_| ())
+        ));
+        let order = |n| OrderType::Standard {
+            id: id(n),
+            price: 98,
+            quantity: 5,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        };
+        let all_or_none = OrderConstraints {
+            all_or_none: true,
+            ..OrderConstraints::default()
+        };
+        assert!(invalid(
+            book.add_order_with_constraints(order(10), all_or_none)
+                .map(|_| ())
+        ));
+        assert!(invalid(
+            book.add_order_for_account(order(11), "ACME").map(|_| ())
+        ));
+        assert!(invalid(
+            book.add_order_with_client_id(order(12), "C-1").map(|_| ())
+        ));
+        assert!(invalid(book.submit_quote("Q", 98, 5, 104, 5).map(|_| ())));
+        assert_eq!(book.get_volume_by_price().0.len(), 2);
+        assert_eq!(book.get_order(id(1)).unwrap().visible_quantity(), 5);
+    }
+
+    #[test]
+    fn test_compact_trades_reach_the_trade_channel() {
+        let (sender, receiver) = mpsc::channel();
+        let channel = TradeChannel::new("TEST_SYMBOL", 4, OverflowPolicy::Park, move |result| {
+            let _ = sender.send(result);
+        });
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .compact_storage()
+            .trade_channel(channel)
+            .asks([(101, 3)])
+            .build()
+            .unwrap();
+        book.submit_market_order(id(2), 2, Side::Buy).unwrap();
+
+        let result = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(result.executed_quantity(), 2);
+        assert_eq!(result.transactions.as_vec()[0].maker_order_id, id(1));
+    }
+}
@@ -0,0 +1,95 @@
+//! Unit tests for snapshots consistent with a single point in the book's history.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_consistent_snapshot_reports_version() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let versioned = book.consistent_snapshot(5).unwrap();
+        assert_eq!(versioned.version, book.version());
+        assert_eq!(versioned.snapshot.bids.len(), 1);
+        assert_eq!(versioned.snapshot.bids[0].price, 100);
+    }
+
+    #[test]
+    fn test_read_consistent_runs_arbitrary_reads() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        for (id, price) in [(1, 100), (2, 101)] {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        let (version, touch) = book
+            .read_consistent(|book| (book.best_bid(), book.best_ask()))
+            .unwrap();
+        assert_eq!(version, book.version());
+        assert_eq!(touch, (None, Some(100)));
+    }
+
+    #[test]
+    fn test_consistent_snapshot_never_sees_a_move_half_applied() {
+        let book: Arc<OrderBook<()>> = Arc::new(OrderBook::new("TEST"));
+        let order_id = OrderId::from_u64(1);
+        book.add_limit_order(order_id, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        // The writer moves the only bid back and forth between two prices:
+        // every point between moves shows it at exactly one of them
+        let done = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let book = Arc::clone(&book);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                for round in 0..2_000u64 {
+                    book.update_order(OrderUpdate::UpdatePrice {
+                        order_id,
+                        new_price: if round % 2 == 0 { 101 } else { 100 },
+                    })
+                    .unwrap();
+                    thread::yield_now();
+                }
+                done.store(true, Ordering::Release);
+            })
+        };
+
+        while !done.load(Ordering::Acquire) {
+            if let Ok(versioned) = book.consistent_snapshot(10) {
+                let bid_quantity: u64 = versioned
+                    .snapshot
+                    .bids
+                    .iter()
+                    .map(|level| level.visible_quantity)
+                    .sum();
+                assert_eq!(bid_quantity, 10);
+                assert_eq!(versioned.snapshot.bids.len(), 1);
+            }
+        }
+        writer.join().unwrap();
+
+        let last = book.consistent_snapshot(10).unwrap();
+        assert_eq!(last.snapshot.bids.len(), 1);
+        assert_eq!(last.version, book.version());
+    }
+}
@@ -0,0 +1,396 @@
+//! Unit tests for order execution constraints such as all-or-none and minimum quantity.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::constraints::OrderConstraints;
+    use crate::orderbook::error::OrderBookError;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+    fn setup_book() -> OrderBook<()> {
+        OrderBook::new("TEST_SYMBOL")
+    }
+
+    #[test]
+    fn test_aon_maker_skipped_when_taker_too_small() {
+        let book = setup_book();
+        let aon_id = OrderId::from_u64(1);
+        let regular_id = OrderId::from_u64(2);
+
+        book.add_all_or_none_order(aon_id, 100, 50, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(regular_id, 100, 20, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(3), Side::Buy, 20, None)
+            .unwrap();
+
+        assert!(result.is_complete);
+        assert_eq!(result.transactions.as_vec().len(), 1);
+        assert_eq!(result.transactions.as_vec()[0].maker_order_id, regular_id);
+        assert_eq!(result.filled_order_ids, vec![regular_id]);
+
+        // The AON order is untouched and still resting
+        let aon = book.get_order(aon_id).unwrap();
+        assert_eq!(aon.visible_quantity(), 50);
+        assert!(book.get_order_constraints(aon_id).all_or_none);
+        assert!(book.get_order(regular_id).is_none());
+    }
+
+    #[test]
+    fn test_aon_maker_filled_in_full() {
+        let book = setup_book();
+        let aon_id = OrderId::from_u64(1);
+        book.add_all_or_none_order(aon_id, 100, 50, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(2), Side::Buy, 60, None)
+            .unwrap();
+
+        assert_eq!(result.executed_quantity(), 50);
+        assert_eq!(result.remaining_quantity, 10);
+        assert_eq!(result.filled_order_ids, vec![aon_id]);
+        assert!(book.get_order(aon_id).is_none());
+        assert!(book.order_constraints.is_empty());
+        assert_eq!(book.best_ask(), None);
+    }
+
+    #[test]
+    fn test_aon_maker_skipped_across_levels() {
+        let book = setup_book();
+        let aon_id = OrderId::from_u64(1);
+        book.add_all_or_none_order(aon_id, 100, 50, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            101,
+            30,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(3), Side::Buy, 30, None)
+            .unwrap();
+
+        assert!(result.is_complete);
+        assert_eq!(result.transactions.as_vec()[0].price, 101);
+        assert_eq!(book.best_ask(), Some(100));
+    }
+
+    #[test]
+    fn test_aon_taker_rests_when_not_fully_fillable() {
+        let book = setup_book();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            20,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let aon_id = OrderId::from_u64(2);
+        let resting = book
+            .add_all_or_none_order(aon_id, 100, 50, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        // Nothing traded: both orders are still in the book
+        assert_eq!(resting.visible_quantity(), 50);
+        assert_eq!(book.get_order(aon_id).unwrap().visible_quantity(), 50);
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .visible_quantity(),
+            20
+        );
+        assert!(book.last_trade_price().is_none());
+    }
+
+    #[test]
+    fn test_aon_taker_executes_when_fully_fillable() {
+        let book = setup_book();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            30,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            101,
+            30,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let aon_id = OrderId::from_u64(3);
+        book.add_all_or_none_order(aon_id, 101, 50, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        assert!(book.get_order(aon_id).is_none());
+        assert_eq!(book.best_ask(), Some(101));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(2))
+                .unwrap()
+                .visible_quantity(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_aon_immediate_taker_rejected() {
+        let book = setup_book();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            20,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let result = book.add_all_or_none_order(
+            OrderId::from_u64(2),
+            100,
+            50,
+            Side::Buy,
+            TimeInForce::Ioc,
+            None,
+        );
+
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InsufficientLiquidity {
+                requested: 50,
+                available: 20,
+                ..
+            })
+        ));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .visible_quantity(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_fok_does_not_count_unreachable_aon_liquidity() {
+        let book = setup_book();
+        book.add_all_or_none_order(
+            OrderId::from_u64(1),
+            100,
+            50,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(book.peek_match(Side::Buy, 30, Some(100)), 30);
+        assert_eq!(
+            book.peek_match_with_constraints(Side::Buy, 30, Some(100)),
+            10
+        );
+
+        let result = book.add_limit_order(
+            OrderId::from_u64(3),
+            100,
+            30,
+            Side::Buy,
+            TimeInForce::Fok,
+            None,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cancel_removes_constraints() {
+        let book = setup_book();
+        let aon_id = OrderId::from_u64(1);
+        book.add_all_or_none_order(aon_id, 100, 50, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        assert!(!book.order_constraints.is_empty());
+
+        book.cancel_order(aon_id).unwrap();
+        assert!(book.order_constraints.is_empty());
+        assert_eq!(
+            book.get_order_constraints(aon_id),
+            OrderConstraints::default()
+        );
+    }
+
+    #[test]
+    fn test_price_update_keeps_constraints() {
+        let book = setup_book();
+        let aon_id = OrderId::from_u64(1);
+        book.add_all_or_none_order(aon_id, 100, 50, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: aon_id,
+            new_price: 99,
+        })
+        .unwrap();
+
+        assert_eq!(book.best_bid(), Some(99));
+        assert!(book.get_order_constraints(aon_id).all_or_none);
+    }
+
+    #[test]
+    fn test_meq_maker_skipped_when_taker_below_minimum() {
+        let book = setup_book();
+        let meq_id = OrderId::from_u64(1);
+        let regular_id = OrderId::from_u64(2);
+
+        book.add_min_quantity_order(meq_id, 100, 100, 40, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(regular_id, 100, 50, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(3), Side::Buy, 30, None)
+            .unwrap();
+
+        assert!(result.is_complete);
+        assert_eq!(result.transactions.as_vec().len(), 1);
+        assert_eq!(result.transactions.as_vec()[0].maker_order_id, regular_id);
+        assert_eq!(book.get_order(meq_id).unwrap().visible_quantity(), 100);
+        assert_eq!(book.get_order(regular_id).unwrap().visible_quantity(), 20);
+    }
+
+    #[test]
+    fn test_meq_maker_partially_filled_at_minimum() {
+        let book = setup_book();
+        let meq_id = OrderId::from_u64(1);
+        book.add_min_quantity_order(meq_id, 100, 100, 40, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(2), Side::Buy, 70, None)
+            .unwrap();
+        assert_eq!(result.executed_quantity(), 70);
+        assert_eq!(book.get_order(meq_id).unwrap().visible_quantity(), 30);
+
+        // Less than the minimum is left, so the remainder only trades in full
+        let result = book.match_order(OrderId::from_u64(3), Side::Buy, 20, None);
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InsufficientLiquidity { available: 0, .. })
+        ));
+        assert_eq!(book.get_order(meq_id).unwrap().visible_quantity(), 30);
+        let result = book
+            .match_order(OrderId::from_u64(4), Side::Buy, 30, None)
+            .unwrap();
+        assert_eq!(result.filled_order_ids, vec![meq_id]);
+        assert!(book.order_constraints.is_empty());
+    }
+
+    #[test]
+    fn test_meq_taker_cancelled_below_minimum() {
+        let book = setup_book();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            20,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let meq_id = OrderId::from_u64(2);
+        let result =
+            book.add_min_quantity_order(meq_id, 100, 100, 30, Side::Buy, TimeInForce::Gtc, None);
+
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InsufficientLiquidity {
+                requested: 30,
+                available: 20,
+                ..
+            })
+        ));
+        assert!(book.get_order(meq_id).is_none());
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .visible_quantity(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_meq_taker_executes_and_rests_remainder() {
+        let book = setup_book();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            40,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let meq_id = OrderId::from_u64(2);
+        book.add_min_quantity_order(meq_id, 100, 100, 30, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.get_order(meq_id).unwrap().visible_quantity(), 60);
+        assert_eq!(book.get_order_constraints(meq_id).min_quantity, Some(30));
+    }
+
+    #[test]
+    fn test_meq_rejects_invalid_minimum() {
+        let book = setup_book();
+        for min_quantity in [0, 101] {
+            let result = book.add_min_quantity_order(
+                OrderId::from_u64(1),
+                100,
+                100,
+                min_quantity,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            );
+            assert!(matches!(
+                result,
+                Err(OrderBookError::InvalidOperation { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_constraints_allows_execution() {
+        let meq = OrderConstraints::min_quantity(40);
+        assert!(!meq.is_unconstrained());
+        assert!(meq.allows_execution(100, 40));
+        assert!(!meq.allows_execution(100, 39));
+        assert!(meq.allows_execution(30, 30));
+        assert!(!meq.allows_execution(30, 29));
+        assert!(OrderConstraints::default().allows_execution(100, 1));
+    }
+}
@@ -0,0 +1,272 @@
+//! Unit tests for one-cancels-other and bracket groups.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::contingent::{ChildActivation, ContingentOrders, StopLeg};
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, Side};
+    use std::sync::{Arc, Mutex};
+
+    fn setup() -> (Arc<OrderBook>, Arc<ContingentOrders>) {
+        ContingentOrders::attach(OrderBook::new("TEST"))
+    }
+
+    fn stop(id: u64, stop_price: u64, quantity: u64) -> StopLeg {
+        StopLeg {
+            order_id: OrderId::from_u64(id),
+            side: Side::Sell,
+            stop_price,
+            quantity,
+        }
+    }
+
+    fn resting(book: &OrderBook, id: u64) -> bool {
+        book.get_order(OrderId::from_u64(id)).is_some()
+    }
+
+    #[test]
+    fn test_fill_of_one_leg_cancels_the_other() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_one_cancels_other(limit(1, 110, 10, Side::Sell), limit(2, 90, 10, Side::Sell))
+            .unwrap();
+        assert_eq!(
+            contingent.working_orders(group),
+            Some(vec![OrderId::from_u64(1), OrderId::from_u64(2)])
+        );
+
+        // A partial fill is enough, and the filled leg keeps working alone
+        book.add_order(limit(3, 90, 4, Side::Buy)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(!resting(&book, 1));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(2))
+                .map(|order| order.visible_quantity()),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn test_cancelling_one_leg_cancels_the_other() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_one_cancels_other(limit(1, 110, 10, Side::Sell), limit(2, 90, 10, Side::Buy))
+            .unwrap();
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(!resting(&book, 1));
+    }
+
+    #[test]
+    fn test_leg_filling_on_entry_keeps_the_other_out() {
+        let (book, contingent) = setup();
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        let group = contingent
+            .submit_one_cancels_other(limit(2, 100, 5, Side::Sell), limit(3, 120, 5, Side::Sell))
+            .unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(!resting(&book, 3));
+        assert_eq!(book.best_ask(), None);
+    }
+
+    #[test]
+    fn test_refused_second_leg_cancels_the_first() {
+        let (book, contingent) = setup();
+        let result = contingent
+            .submit_one_cancels_other(limit(1, 110, 10, Side::Sell), limit(1, 120, 10, Side::Sell));
+        assert!(matches!(
+            result,
+            Err(OrderBookError::DuplicateOrderId { .. })
+        ));
+        assert!(!resting(&book, 1));
+    }
+
+    #[test]
+    fn test_bracket_activates_once_the_entry_fills() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_bracket(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                stop(3, 95, 10),
+            )
+            .unwrap();
+        assert!(!resting(&book, 2));
+
+        book.add_order(limit(4, 100, 6, Side::Sell)).unwrap();
+        assert_eq!(contingent.active_stop(group), None);
+        assert!(!resting(&book, 2));
+
+        book.add_order(limit(5, 100, 4, Side::Sell)).unwrap();
+        assert!(resting(&book, 2));
+        assert_eq!(contingent.active_stop(group), Some(stop(3, 95, 10)));
+        assert_eq!(
+            contingent.working_orders(group),
+            Some(vec![OrderId::from_u64(2)])
+        );
+
+        // Target fills reduce the stop, and completing it ends the bracket
+        book.add_order(limit(6, 110, 4, Side::Buy)).unwrap();
+        assert_eq!(
+            contingent.active_stop(group).map(|stop| stop.quantity),
+            Some(6)
+        );
+        book.add_order(limit(7, 110, 6, Side::Buy)).unwrap();
+        assert!(!contingent.is_working(group));
+    }
+
+    #[test]
+    fn test_trade_through_stop_sends_it_and_cancels_target() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_bracket(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                stop(3, 95, 10),
+            )
+            .unwrap();
+        book.add_order(limit(4, 100, 10, Side::Sell)).unwrap();
+        book.add_order(limit(5, 94, 20, Side::Buy)).unwrap();
+        book.add_order(limit(6, 95, 1, Side::Buy)).unwrap();
+
+        assert!(contingent.is_working(group));
+
+        // Trading at the stop price triggers it
+        book.add_order(limit(7, 95, 1, Side::Sell)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(!resting(&book, 2));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(5))
+                .map(|order| order.visible_quantity()),
+            Some(10)
+        );
+        assert_eq!(book.last_trade_price(), Some(94));
+    }
+
+    #[test]
+    fn test_entry_cancelled_before_filling_ends_the_bracket() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_bracket(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                stop(3, 95, 10),
+            )
+            .unwrap();
+        assert!(contingent.cancel_group(group));
+        assert!(!contingent.cancel_group(group));
+        assert!(!resting(&book, 1));
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_bracket_legs_must_exit_the_entry() {
+        let (_book, contingent) = setup();
+        let result = contingent.submit_bracket(
+            limit(1, 100, 10, Side::Buy),
+            limit(2, 110, 10, Side::Buy),
+            stop(3, 95, 10),
+        );
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_existing_listener_keeps_receiving_events() {
+        let cancelled = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&cancelled);
+        let mut book = OrderBook::new("TEST");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderCancelled(cancel) = event {
+                recorded.lock().unwrap().push(cancel.order_id);
+            }
+        }));
+        let (book, contingent) = ContingentOrders::attach(book);
+        contingent
+            .submit_one_cancels_other(limit(1, 110, 10, Side::Sell), limit(2, 90, 10, Side::Buy))
+            .unwrap();
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(
+            *cancelled.lock().unwrap(),
+            vec![OrderId::from_u64(1), OrderId::from_u64(2)]
+        );
+    }
+
+    #[test]
+    fn test_if_done_child_enters_on_first_fill() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_if_done(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                ChildActivation::FirstFill,
+            )
+            .unwrap();
+        assert_eq!(
+            contingent.working_orders(group),
+            Some(vec![OrderId::from_u64(1)])
+        );
+        assert!(!resting(&book, 2));
+
+        book.add_order(limit(3, 100, 1, Side::Sell)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(resting(&book, 1));
+        assert_eq!(book.best_ask(), Some(110));
+    }
+
+    #[test]
+    fn test_if_done_child_waits_for_the_threshold() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_if_done(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                ChildActivation::ExecutedQuantity(5),
+            )
+            .unwrap();
+        book.add_order(limit(3, 100, 4, Side::Sell)).unwrap();
+        assert!(contingent.is_working(group));
+        assert!(!resting(&book, 2));
+
+        book.add_order(limit(4, 100, 1, Side::Sell)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(resting(&book, 2));
+    }
+
+    #[test]
+    fn test_if_done_parent_filling_on_entry_enters_the_child() {
+        let (book, contingent) = setup();
+        book.add_order(limit(1, 100, 10, Side::Sell)).unwrap();
+        let group = contingent
+            .submit_if_done(
+                limit(2, 100, 10, Side::Buy),
+                limit(3, 110, 10, Side::Sell),
+                ChildActivation::Filled,
+            )
+            .unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(resting(&book, 3));
+    }
+
+    #[test]
+    fn test_if_done_child_is_dropped_with_its_parent() {
+        let (book, contingent) = setup();
+        let group = contingent
+            .submit_if_done(
+                limit(1, 100, 10, Side::Buy),
+                limit(2, 110, 10, Side::Sell),
+                ChildActivation::Filled,
+            )
+            .unwrap();
+        book.add_order(limit(3, 100, 4, Side::Sell)).unwrap();
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert!(!contingent.is_working(group));
+        assert!(!resting(&book, 2));
+        assert_eq!(book.best_ask(), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Unit tests for the non-displayed matching segment.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::dark::DarkMatching;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::tests::helpers::{record_trades, rest};
+    use crate::orderbook::trade::TradeCondition;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    /// A lit market of 100 bid, 110 offered
+    fn lit_book() -> OrderBook<()> {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 50, Side::Buy);
+        rest(&book, 2, 110, 50, Side::Sell);
+        book
+    }
+
+    #[test]
+    fn test_dark_orders_cross_at_the_midpoint() {
+        let mut book = lit_book();
+        let reports = record_trades(&mut book);
+        let first = book
+            .add_dark_order(OrderId::from_u64(10), Side::Buy, 30, 0, None)
+            .unwrap();
+        assert!(first.trades.is_empty());
+        assert_eq!(first.resting.unwrap().quantity, 30);
+
+        let second = book
+            .add_dark_order(OrderId::from_u64(11), Side::Sell, 20, 0, None)
+            .unwrap();
+        assert_eq!(second.trades.len(), 1);
+        let trade = second.trades[0];
+        assert_eq!(trade.price, 105);
+        assert_eq!(trade.quantity, 20);
+        assert_eq!(trade.maker_order_id, OrderId::from_u64(10));
+        assert_eq!(trade.taker_order_id, OrderId::from_u64(11));
+        assert!(second.resting.is_none());
+        let report = reports.lock().unwrap()[0];
+        assert_eq!(report.transaction, trade);
+        assert!(report.conditions.contains(TradeCondition::DarkCross));
+
+        let resting = book.dark_orders();
+        assert_eq!(resting.len(), 1);
+        assert_eq!(resting[0].quantity, 10);
+        assert_eq!(resting[0].executed_quantity, 20);
+        // The lit book is untouched and shows nothing of the dark orders
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(110));
+        assert_eq!(book.last_trade_price(), None);
+    }
+
+    #[test]
+    fn test_sub_unit_midpoint_favours_the_resting_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 50, Side::Buy);
+        rest(&book, 2, 101, 50, Side::Sell);
+        book.add_dark_order(OrderId::from_u64(10), Side::Sell, 10, 0, None)
+            .unwrap();
+        let matched = book
+            .add_dark_order(OrderId::from_u64(11), Side::Buy, 10, 0, None)
+            .unwrap();
+        assert_eq!(matched.trades[0].price, 101);
+    }
+
+    #[test]
+    fn test_minimum_execution_quantity() {
+        let book = lit_book();
+        book.add_dark_order(OrderId::from_u64(10), Side::Buy, 100, 50, None)
+            .unwrap();
+
+        // Too small for the resting order's minimum
+        let small = book
+            .add_dark_order(OrderId::from_u64(11), Side::Sell, 30, 0, None)
+            .unwrap();
+        assert!(small.trades.is_empty());
+
+        // The newcomer skips the small order and trades with the large one
+        let large = book
+            .add_dark_order(OrderId::from_u64(12), Side::Sell, 60, 0, None)
+            .unwrap();
+        assert_eq!(large.trades.len(), 1);
+        assert_eq!(large.trades[0].quantity, 60);
+
+        // 40 left, below the minimum of 50, can still execute in full
+        let rest_of_it = book
+            .add_dark_order(OrderId::from_u64(13), Side::Sell, 40, 0, None)
+            .unwrap();
+        assert_eq!(rest_of_it.trades.len(), 1);
+        assert_eq!(rest_of_it.trades[0].quantity, 40);
+        assert_eq!(
+            book.dark_orders()
+                .iter()
+                .map(|order| order.id)
+                .collect::<Vec<_>>(),
+            vec![OrderId::from_u64(11)]
+        );
+    }
+
+    #[test]
+    fn test_limit_price_guards_the_midpoint() {
+        let book = lit_book();
+        book.add_dark_order(OrderId::from_u64(10), Side::Sell, 10, 0, Some(106))
+            .unwrap();
+        let matched = book
+            .add_dark_order(OrderId::from_u64(11), Side::Buy, 10, 0, None)
+            .unwrap();
+        assert!(matched.trades.is_empty());
+
+        // Once the lit market moves up, the midpoint is acceptable
+        rest(&book, 3, 104, 10, Side::Buy);
+        let trades = book.cross_dark_pool();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, 107);
+        assert_eq!(trades[0].taker_order_id, OrderId::from_u64(11));
+        assert!(book.dark_orders().is_empty());
+    }
+
+    #[test]
+    fn test_one_sided_lit_book_blocks_dark_crossing() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 50, Side::Buy);
+        book.add_dark_order(OrderId::from_u64(10), Side::Buy, 10, 0, None)
+            .unwrap();
+        let matched = book
+            .add_dark_order(OrderId::from_u64(11), Side::Sell, 10, 0, None)
+            .unwrap();
+        assert!(matched.trades.is_empty());
+        assert_eq!(book.dark_orders().len(), 2);
+        assert!(book.cross_dark_pool().is_empty());
+
+        rest(&book, 2, 110, 50, Side::Sell);
+        assert_eq!(book.cross_dark_pool().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_dark_orders_are_rejected() {
+        let book = lit_book();
+        assert!(
+            book.add_dark_order(OrderId::from_u64(10), Side::Buy, 0, 0, None)
+                .is_err()
+        );
+        assert!(
+            book.add_dark_order(OrderId::from_u64(10), Side::Buy, 10, 20, None)
+                .is_err()
+        );
+        book.add_dark_order(OrderId::from_u64(10), Side::Buy, 10, 0, None)
+            .unwrap();
+        assert!(
+            book.add_dark_order(OrderId::from_u64(10), Side::Buy, 10, 0, None)
+                .is_err()
+        );
+
+        let cancelled = book.cancel_dark_order(OrderId::from_u64(10)).unwrap();
+        assert_eq!(cancelled.quantity, 10);
+        assert!(book.cancel_dark_order(OrderId::from_u64(10)).is_none());
+    }
+
+    #[test]
+    fn test_lit_orders_skip_the_dark_segment_alongside() {
+        let book = lit_book();
+        book.add_dark_order(OrderId::from_u64(10), Side::Sell, 10, 0, None)
+            .unwrap();
+        rest(&book, 3, 110, 10, Side::Buy);
+        assert_eq!(book.dark_orders()[0].quantity, 10);
+        assert_eq!(book.last_trade_price(), Some(110));
+    }
+
+    #[test]
+    fn test_lit_orders_sweep_the_dark_segment_first() {
+        let trades = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&trades);
+        let mut book = lit_book();
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event
+                && !fill.is_maker
+            {
+                recorded.lock().unwrap().push((fill.price, fill.quantity));
+            }
+        }));
+        book.set_dark_matching(DarkMatching::BeforeLit);
+        book.add_dark_order(OrderId::from_u64(10), Side::Sell, 10, 0, None)
+            .unwrap();
+
+        // 10 at the midpoint from the dark segment, 5 at the lit offer
+        rest(&book, 3, 110, 15, Side::Buy);
+        assert_eq!(*trades.lock().unwrap(), vec![(105, 10), (110, 5)]);
+        assert!(book.dark_orders().is_empty());
+        assert_eq!(book.best_ask(), Some(110));
+        assert!(book.get_order(OrderId::from_u64(3)).is_none());
+    }
+
+    #[test]
+    fn test_lit_sweep_respects_the_limit() {
+        let book = lit_book();
+        book.set_dark_matching(DarkMatching::BeforeLit);
+        book.add_dark_order(OrderId::from_u64(10), Side::Sell, 10, 0, None)
+            .unwrap();
+
+        // A bid below the midpoint does not reach the dark offer and rests
+        rest(&book, 3, 102, 10, Side::Buy);
+        assert_eq!(book.dark_orders()[0].quantity, 10);
+        assert!(book.get_order(OrderId::from_u64(3)).is_some());
+
+        // Post-only orders never take dark liquidity either
+        book.add_post_only_order(
+            OrderId::from_u64(4),
+            108,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        assert_eq!(book.dark_orders()[0].quantity, 10);
+    }
+
+    #[test]
+    fn test_dark_fills_are_published() {
+        let fills = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&fills);
+        let mut book = lit_book();
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event {
+                recorded.lock().unwrap().push((
+                    fill.order_id,
+                    fill.is_maker,
+                    fill.cumulative_quantity,
+                    fill.remaining_quantity,
+                ));
+            }
+        }));
+        book.add_dark_order(OrderId::from_u64(10), Side::Buy, 30, 0, None)
+            .unwrap();
+        book.add_dark_order(OrderId::from_u64(11), Side::Sell, 20, 0, None)
+            .unwrap();
+
+        assert_eq!(
+            *fills.lock().unwrap(),
+            vec![
+                (OrderId::from_u64(11), false, 20, 0),
+                (OrderId::from_u64(10), true, 20, 10),
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Unit tests for the conflated top-N depth publisher.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::depth_feed::{ConflatedDepth, DepthUpdate};
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    type Received = Arc<Mutex<Vec<DepthUpdate>>>;
+
+    fn setup(depth: usize) -> (Arc<ManualClock>, ConflatedDepth, Received) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.set_clock(clock.clone());
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let publisher = ConflatedDepth::new(
+            Arc::new(book),
+            depth,
+            Duration::from_millis(10),
+            Arc::new(move |update: &DepthUpdate| sink.lock().unwrap().push(update.clone())),
+        );
+        (clock, publisher, received)
+    }
+
+    fn add(publisher: &ConflatedDepth, id: u64, price: u64, quantity: u64, side: Side) {
+        publisher
+            .book()
+            .add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                quantity,
+                side,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_first_poll_publishes_initial_image() {
+        let (_clock, publisher, received) = setup(5);
+        add(&publisher, 1, 100, 10, Side::Buy);
+        add(&publisher, 2, 101, 10, Side::Sell);
+
+        let update = publisher.poll().unwrap();
+        assert_eq!(update.sequence, 1);
+        assert_eq!(update.conflated_changes, 2);
+        assert_eq!(update.changes.added.len(), 2);
+        assert_eq!(update.snapshot.best_bid(), Some((100, 10)));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Nothing changed since
+        assert!(publisher.flush().is_none());
+    }
+
+    #[test]
+    fn test_changes_within_an_interval_are_merged() {
+        let (clock, publisher, received) = setup(5);
+        add(&publisher, 1, 100, 10, Side::Buy);
+        publisher.poll().unwrap();
+
+        add(&publisher, 2, 100, 5, Side::Buy);
+        add(&publisher, 3, 99, 7, Side::Buy);
+        publisher.book().cancel_order(OrderId::from_u64(3)).unwrap();
+        clock.advance(5);
+        assert!(publisher.poll().is_none());
+
+        clock.advance(5);
+        let update = publisher.poll().unwrap();
+        assert_eq!(update.sequence, 2);
+        assert_eq!(update.conflated_changes, 3);
+        // The level added and cancelled in between leaves no trace
+        assert!(update.changes.added.is_empty());
+        assert!(update.changes.removed.is_empty());
+        assert_eq!(update.changes.changed.len(), 1);
+        assert_eq!(update.changes.changed[0].visible_delta, 5);
+        assert_eq!(update.snapshot.best_bid(), Some((100, 15)));
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert_eq!(publisher.published_count(), 2);
+    }
+
+    #[test]
+    fn test_changes_below_depth_are_not_published() {
+        let (clock, publisher, _received) = setup(1);
+        add(&publisher, 1, 100, 10, Side::Buy);
+        publisher.poll().unwrap();
+
+        add(&publisher, 2, 90, 10, Side::Buy);
+        clock.advance(10);
+        assert!(publisher.poll().is_none());
+
+        add(&publisher, 3, 100, 10, Side::Buy);
+        let update = publisher.poll().unwrap();
+        assert_eq!(update.sequence, 2);
+        assert_eq!(update.conflated_changes, 1);
+        assert_eq!(update.snapshot.bids.len(), 1);
+    }
+
+    #[test]
+    fn test_flush_ignores_the_interval() {
+        let (_clock, publisher, _received) = setup(5);
+        publisher.poll().unwrap();
+        add(&publisher, 1, 100, 10, Side::Sell);
+        assert!(publisher.poll().is_none());
+
+        let update = publisher.flush().unwrap();
+        assert_eq!(update.changes.added.len(), 1);
+        assert_eq!(update.snapshot.best_ask(), Some((100, 10)));
+    }
+
+    #[test]
+    fn test_spawned_thread_publishes_until_dropped() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let publisher = Arc::new(ConflatedDepth::new(
+            Arc::clone(&book),
+            5,
+            Duration::from_millis(1),
+            Arc::new(move |update: &DepthUpdate| sink.lock().unwrap().push(update.clone())),
+        ));
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let feed = ConflatedDepth::spawn(Arc::clone(&publisher));
+        for _ in 0..1_000 {
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        drop(feed);
+
+        let received = received.lock().unwrap();
+        assert!(!received.is_empty());
+        assert_eq!(received[0].snapshot.best_bid(), Some((100, 10)));
+    }
+}
@@ -0,0 +1,384 @@
+//! Unit tests for the deterministic order book and for order books created
+//! with `OrderBook::new_deterministic`.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::OrderBookError;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::deterministic::DeterministicOrderBook;
+    use crate::orderbook::modifications::OrderQuantity;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::Arc;
+
+    /// Runs a fixed scenario and returns everything it observed, serialized
+    fn run_scenario() -> String {
+        let mut book: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        let mut log = Vec::new();
+
+        for (i, (price, side)) in [
+            (100, Side::Sell),
+            (100, Side::Sell),
+            (101, Side::Sell),
+            (98, Side::Buy),
+            (99, Side::Buy),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            book.set_time(i as u64 * 10);
+            book.add_limit_order(
+                OrderId::from_u64(i as u64),
+                price,
+                10,
+                side,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book.add_iceberg_order(
+            OrderId::from_u64(5),
+            101,
+            2,
+            8,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        book.set_time(100);
+        let result = book
+            .submit_market_order(OrderId::from_u64(6), 25, Side::Buy)
+            .unwrap();
+        log.push(format!("{result:?}"));
+        book.set_time(110);
+        let result = book
+            .match_order(OrderId::from_u64(7), Side::Sell, 12, Some(98))
+            .unwrap();
+        log.push(format!("{result:?}"));
+        log.push(serde_json::to_string(&book.create_snapshot(10)).unwrap());
+        log.join("\n")
+    }
+
+    #[test]
+    fn test_runs_are_identical() {
+        let first = run_scenario();
+        assert_eq!(first, run_scenario());
+        assert!(first.contains("\"timestamp\":110"));
+    }
+
+    #[test]
+    fn test_price_time_priority() {
+        let mut book: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        for id in 0..3 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100 + id / 2,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+
+        let result = book
+            .match_order(OrderId::from_u64(10), Side::Buy, 25, None)
+            .unwrap();
+        let makers: Vec<(OrderId, u64, u64)> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|t| (t.maker_order_id, t.price, t.quantity))
+            .collect();
+        assert_eq!(
+            makers,
+            vec![
+                (OrderId::from_u64(0), 100, 10),
+                (OrderId::from_u64(1), 100, 10),
+                (OrderId::from_u64(2), 101, 5),
+            ]
+        );
+        assert_eq!(
+            result.filled_order_ids,
+            vec![OrderId::from_u64(0), OrderId::from_u64(1)]
+        );
+        assert_eq!(book.best_ask(), Some(101));
+        assert_eq!(book.last_trade_price(), Some(101));
+        assert!(book.get_order(OrderId::from_u64(0)).is_none());
+    }
+
+    #[test]
+    fn test_iceberg_replenishes_to_back_of_level() {
+        let mut book: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        book.add_iceberg_order(
+            OrderId::from_u64(1),
+            100,
+            5,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            100,
+            4,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let result = book
+            .match_order(OrderId::from_u64(3), Side::Buy, 12, None)
+            .unwrap();
+        let fills: Vec<(OrderId, u64)> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|t| (t.maker_order_id, t.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (OrderId::from_u64(1), 5),
+                (OrderId::from_u64(2), 4),
+                (OrderId::from_u64(1), 3),
+            ]
+        );
+
+        let iceberg = book.get_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(iceberg.visible_quantity(), 2);
+        assert_eq!(iceberg.hidden_quantity(), 5);
+    }
+
+    #[test]
+    fn test_matches_concurrent_book() {
+        let mut deterministic: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        let concurrent: OrderBook = OrderBook::new("TEST_SYMBOL");
+        for (id, price, side) in [
+            (1, 100, Side::Sell),
+            (2, 102, Side::Sell),
+            (3, 97, Side::Buy),
+            (4, 95, Side::Buy),
+            (5, 101, Side::Buy),
+            (6, 96, Side::Sell),
+        ] {
+            let a = deterministic.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                10,
+                side,
+                TimeInForce::Gtc,
+                None,
+            );
+            let b = concurrent.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                10,
+                side,
+                TimeInForce::Gtc,
+                None,
+            );
+            assert_eq!(a.is_ok(), b.is_ok());
+        }
+
+        assert_eq!(deterministic.best_bid(), concurrent.best_bid());
+        assert_eq!(deterministic.best_ask(), concurrent.best_ask());
+        assert_eq!(
+            deterministic.get_volume_by_price(),
+            concurrent.get_volume_by_price()
+        );
+        assert_eq!(
+            deterministic.last_trade_price(),
+            concurrent.last_trade_price()
+        );
+    }
+
+    #[test]
+    fn test_rejections_and_expiry() {
+        let mut book: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let post_only = book.add_post_only_order(
+            OrderId::from_u64(2),
+            100,
+            5,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        );
+        assert!(matches!(
+            post_only,
+            Err(OrderBookError::PriceCrossing { .. })
+        ));
+
+        let fok = book.add_limit_order(
+            OrderId::from_u64(3),
+            100,
+            15,
+            Side::Buy,
+            TimeInForce::Fok,
+            None,
+        );
+        assert!(matches!(
+            fok,
+            Err(OrderBookError::InsufficientLiquidity { available: 10, .. })
+        ));
+        assert_eq!(book.get_order(OrderId::from_u64(1)).unwrap().quantity(), 10);
+
+        book.set_time(500);
+        let expired = book.add_limit_order(
+            OrderId::from_u64(4),
+            90,
+            5,
+            Side::Buy,
+            TimeInForce::Gtd(400),
+            None,
+        );
+        assert!(matches!(
+            expired,
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+
+        assert!(book.cancel_order(OrderId::from_u64(1)).unwrap().is_some());
+        assert!(book.cancel_order(OrderId::from_u64(1)).unwrap().is_none());
+        assert_eq!(book.best_ask(), None);
+        assert!(matches!(
+            book.submit_market_order(OrderId::from_u64(5), 1, Side::Buy),
+            Err(OrderBookError::InsufficientLiquidity { available: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_quantity_updates_keep_queue_position() {
+        let mut book: DeterministicOrderBook = DeterministicOrderBook::new("TEST_SYMBOL");
+        for id in 1..=2 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+
+        let updated = book
+            .update_order(OrderUpdate::UpdateQuantity {
+                order_id: OrderId::from_u64(1),
+                new_quantity: 15,
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.quantity(), 15);
+        let queue: Vec<OrderId> = book
+            .get_orders_at_price(100, Side::Sell)
+            .iter()
+            .map(|order| order.id())
+            .collect();
+        assert_eq!(queue, vec![OrderId::from_u64(1), OrderId::from_u64(2)]);
+
+        let moved = book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(1),
+            new_price: 100,
+        });
+        assert!(matches!(
+            moved,
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(2),
+            new_price: 101,
+        })
+        .unwrap();
+        assert_eq!(book.get_volume_by_price().1.get(&101), Some(&10));
+
+        assert!(
+            book.update_order(OrderUpdate::UpdateQuantity {
+                order_id: OrderId::from_u64(1),
+                new_quantity: 0,
+            })
+            .unwrap()
+            .is_some()
+        );
+        assert_eq!(book.best_ask(), Some(101));
+    }
+
+    fn clocked_book() -> (OrderBook, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(0));
+        let book = OrderBook::new_deterministic("TEST_SYMBOL", clock.clone());
+        (book, clock)
+    }
+
+    /// Runs the same scenario on an `OrderBook` reading a manual clock
+    fn run_clocked_scenario() -> String {
+        let (book, clock) = clocked_book();
+        let mut log = Vec::new();
+
+        for (i, (price, side)) in [
+            (100, Side::Sell),
+            (100, Side::Sell),
+            (101, Side::Sell),
+            (98, Side::Buy),
+            (99, Side::Buy),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            clock.set(i as u64 * 10);
+            book.add_limit_order(
+                OrderId::from_u64(i as u64),
+                price,
+                10,
+                side,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book.add_iceberg_order(
+            OrderId::from_u64(5),
+            101,
+            2,
+            8,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        clock.set(100);
+        let result = book
+            .submit_market_order(OrderId::from_u64(6), 25, Side::Buy)
+            .unwrap();
+        log.push(format!("{result:?}"));
+        clock.set(110);
+        let result = book
+            .match_order(OrderId::from_u64(7), Side::Sell, 12, Some(98))
+            .unwrap();
+        log.push(format!("{result:?}"));
+        log.push(serde_json::to_string(&book.create_snapshot(10)).unwrap());
+        log.join("\n")
+    }
+
+    #[test]
+    fn test_clocked_runs_are_identical() {
+        let first = run_clocked_scenario();
+        assert_eq!(first, run_clocked_scenario());
+        assert!(first.contains("\"timestamp\":110"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Unit tests for cancel-on-disconnect sessions.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::disconnect::Session;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, Side};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_dropping_session_cancels_its_orders() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        let session = Session::new(Arc::clone(&book));
+        session.add_order(limit(2, 99, 10, Side::Buy)).unwrap();
+        session.add_order(limit(3, 101, 10, Side::Sell)).unwrap();
+        assert_eq!(
+            session.order_ids(),
+            vec![OrderId::from_u64(2), OrderId::from_u64(3)]
+        );
+
+        drop(session);
+        assert!(book.get_order(OrderId::from_u64(2)).is_none());
+        assert!(book.get_order(OrderId::from_u64(3)).is_none());
+        // Orders entered outside the session are left alone
+        assert!(book.get_order(OrderId::from_u64(1)).is_some());
+    }
+
+    #[test]
+    fn test_cancel_all_skips_orders_gone_from_the_book() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        let session = Session::new(Arc::clone(&book));
+        session.add_order(limit(1, 100, 10, Side::Sell)).unwrap();
+        session.add_order(limit(2, 101, 10, Side::Sell)).unwrap();
+        session.add_order(limit(3, 102, 10, Side::Sell)).unwrap();
+        book.add_order(limit(4, 100, 10, Side::Buy)).unwrap();
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+
+        let cancelled: Vec<_> = session
+            .cancel_all()
+            .iter()
+            .map(|order| order.id())
+            .collect();
+        assert_eq!(cancelled, vec![OrderId::from_u64(3)]);
+        assert!(session.order_ids().is_empty());
+        assert_eq!(book.best_ask(), None);
+
+        // The session stays open
+        session.add_order(limit(5, 105, 10, Side::Sell)).unwrap();
+        assert_eq!(session.order_ids(), vec![OrderId::from_u64(5)]);
+    }
+
+    #[test]
+    fn test_filled_and_rejected_orders_are_not_owned() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        book.add_order(limit(1, 100, 10, Side::Sell)).unwrap();
+        let session = Session::new(Arc::clone(&book));
+
+        session.add_order(limit(2, 100, 10, Side::Buy)).unwrap();
+        book.add_order(limit(3, 90, 10, Side::Buy)).unwrap();
+        assert!(session.add_order(limit(3, 90, 10, Side::Buy)).is_err());
+        assert!(session.order_ids().is_empty());
+
+        drop(session);
+        assert!(book.get_order(OrderId::from_u64(3)).is_some());
+    }
+
+    #[test]
+    fn test_tracked_orders_are_cancelled_and_released_orders_kept() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        let session = Session::new(Arc::clone(&book));
+        assert!(session.track(OrderId::from_u64(1)));
+        assert!(!session.track(OrderId::from_u64(9)));
+        session.add_order(limit(2, 99, 10, Side::Buy)).unwrap();
+        assert!(session.release(OrderId::from_u64(2)));
+        assert!(!session.release(OrderId::from_u64(2)));
+
+        drop(session);
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert!(book.get_order(OrderId::from_u64(2)).is_some());
+    }
+}
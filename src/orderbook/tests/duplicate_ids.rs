@@ -0,0 +1,95 @@
+//! Unit tests for orders entered under the id of a resting order.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::{OrderBookEvent, RejectReason};
+    use crate::orderbook::execution_report::OrderStatus;
+    use crate::orderbook::options::DuplicateOrderIdAction;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, Side};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_duplicate_order_id_is_rejected() {
+        let rejects = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&rejects);
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.set_event_listener(Arc::new(move |event: &OrderBookEvent| {
+            if let OrderBookEvent::OrderRejected(reject) = event {
+                recorded.lock().unwrap().push(reject.reason);
+            }
+        }));
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+
+        let result = book.add_order(limit(1, 101, 20, Side::Buy));
+        assert!(matches!(
+            result,
+            Err(OrderBookError::DuplicateOrderId { order_id }) if order_id == OrderId::from_u64(1)
+        ));
+        assert_eq!(
+            *rejects.lock().unwrap(),
+            vec![RejectReason::DuplicateOrderId]
+        );
+
+        // The resting order is untouched
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.get_orders_at_price(100, Side::Buy).len(), 1);
+        assert!(book.get_orders_at_price(101, Side::Buy).is_empty());
+        assert_eq!(book.order_status(OrderId::from_u64(1)), OrderStatus::New);
+
+        // And can still be cancelled, after which the id can be used again
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        book.add_order(limit(1, 101, 20, Side::Buy)).unwrap();
+        assert_eq!(book.best_bid(), Some(101));
+    }
+
+    #[test]
+    fn test_duplicate_order_id_replaces_resting_order() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.set_duplicate_order_id_action(DuplicateOrderIdAction::Replace);
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        book.add_order(limit(2, 100, 10, Side::Buy)).unwrap();
+
+        book.add_order(limit(1, 99, 20, Side::Buy)).unwrap();
+        let at_100 = book.get_orders_at_price(100, Side::Buy);
+        assert_eq!(at_100.len(), 1);
+        assert_eq!(at_100[0].id(), OrderId::from_u64(2));
+        let at_99 = book.get_orders_at_price(99, Side::Buy);
+        assert_eq!(at_99.len(), 1);
+        assert_eq!(at_99[0].visible_quantity(), 20);
+
+        // The replacement is cancelled as a whole, leaving nothing behind
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert!(book.get_orders_at_price(99, Side::Buy).is_empty());
+        assert!(book.cancel_order(OrderId::from_u64(1)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_duplicate_order_id_keeps_client_order_id() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order_with_client_id(limit(1, 100, 10, Side::Sell), "abc-1")
+            .unwrap();
+
+        let result = book.add_order_with_client_id(limit(1, 101, 10, Side::Sell), "abc-2");
+        assert!(matches!(
+            result,
+            Err(OrderBookError::DuplicateOrderId { .. })
+        ));
+        assert_eq!(
+            book.client_order_id(OrderId::from_u64(1)).as_deref(),
+            Some("abc-1")
+        );
+        assert_eq!(book.find_by_client_id("abc-1"), Some(OrderId::from_u64(1)));
+        assert_eq!(book.find_by_client_id("abc-2"), None);
+
+        // Replaced, the order takes the new client order id and frees the old one
+        book.set_duplicate_order_id_action(DuplicateOrderIdAction::Replace);
+        book.add_order_with_client_id(limit(1, 101, 10, Side::Sell), "abc-2")
+            .unwrap();
+        assert_eq!(book.find_by_client_id("abc-1"), None);
+        assert_eq!(book.find_by_client_id("abc-2"), Some(OrderId::from_u64(1)));
+        assert_eq!(book.best_ask(), Some(101));
+    }
+}
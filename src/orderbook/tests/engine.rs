@@ -0,0 +1,202 @@
+//! Unit tests for the single-writer engine loop.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::deterministic::DeterministicOrderBook;
+    use crate::orderbook::engine::{Command, CommandOutcome, EngineLoop};
+    use crate::orderbook::tests::helpers::limit;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_run_once_applies_commands_in_order() {
+        let (mut engine, handle) = EngineLoop::new(DeterministicOrderBook::<()>::new("TEST"), 8);
+        handle
+            .submit(Command::Add(limit(1, 100, 10, Side::Sell)))
+            .unwrap();
+        handle
+            .submit(Command::Add(limit(2, 101, 10, Side::Sell)))
+            .unwrap();
+        handle
+            .submit(Command::Cancel(OrderId::from_u64(1)))
+            .unwrap();
+        handle
+            .submit(Command::MarketOrder {
+                order_id: OrderId::from_u64(3),
+                quantity: 50,
+                side: Side::Buy,
+            })
+            .unwrap();
+        handle
+            .submit(Command::Cancel(OrderId::from_u64(2)))
+            .unwrap();
+        assert_eq!(handle.pending(), 5);
+
+        assert_eq!(engine.run_once(), 5);
+        assert_eq!(handle.pending(), 0);
+        assert_eq!(engine.book().best_ask(), None);
+        assert_eq!(engine.book().last_trade_price(), Some(101));
+        assert_eq!(handle.applied_count(), 5);
+        assert_eq!(handle.failed_count(), 0);
+    }
+
+    #[test]
+    fn test_failed_commands_are_counted() {
+        let (mut engine, handle) = EngineLoop::new(DeterministicOrderBook::<()>::new("TEST"), 8);
+        handle
+            .submit(Command::MarketOrder {
+                order_id: OrderId::from_u64(1),
+                quantity: 5,
+                side: Side::Sell,
+            })
+            .unwrap();
+        engine.run_once();
+        assert_eq!(handle.applied_count(), 0);
+        assert_eq!(handle.failed_count(), 1);
+    }
+
+    #[test]
+    fn test_engine_stamps_with_its_clock() {
+        let (mut engine, handle) = EngineLoop::new(DeterministicOrderBook::<()>::new("TEST"), 8);
+        let clock = Arc::new(ManualClock::new(5_000));
+        engine.set_clock(clock.clone());
+        handle
+            .submit(Command::Add(limit(1, 100, 10, Side::Buy)))
+            .unwrap();
+        engine.run_once();
+        assert_eq!(engine.book().current_time(), 5_000);
+        let snapshot = engine.book().create_snapshot(1);
+        assert_eq!(snapshot.timestamp, 5_000);
+
+        clock.advance(250);
+        handle
+            .submit(Command::Cancel(OrderId::from_u64(1)))
+            .unwrap();
+        engine.run_once();
+        assert_eq!(engine.book().current_time(), 5_250);
+    }
+
+    #[test]
+    fn test_handles_block_until_applied() {
+        let (engine, handle) = EngineLoop::new(DeterministicOrderBook::<()>::new("TEST"), 4);
+        let worker = engine.spawn();
+
+        let producers: Vec<_> = (0..4)
+            .map(|producer| {
+                let handle = handle.clone();
+                thread::spawn(move || {
+                    for i in 0..25 {
+                        let id = producer * 100 + i;
+                        handle
+                            .add_limit_order(
+                                OrderId::from_u64(id),
+                                1_000 + id,
+                                1,
+                                Side::Sell,
+                                TimeInForce::Gtc,
+                                None,
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(handle.best_ask().unwrap(), Some(1_000));
+        let trades = handle
+            .submit_market_order(OrderId::from_u64(999), 3, Side::Buy)
+            .unwrap();
+        assert_eq!(trades.transactions.as_vec().len(), 3);
+        let updated = handle
+            .update_order(OrderUpdate::UpdatePrice {
+                order_id: OrderId::from_u64(3),
+                new_price: 900,
+            })
+            .unwrap();
+        assert_eq!(updated.unwrap().price(), 900);
+        assert_eq!(handle.create_snapshot(1).unwrap().asks[0].price, 900);
+
+        handle.shutdown().unwrap();
+        let book = worker.join().unwrap();
+        assert_eq!(book.get_all_orders().len(), 97);
+        assert!(handle.best_bid().is_err());
+        assert!(
+            handle
+                .submit(Command::Cancel(OrderId::from_u64(1)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_engine_stops_when_handles_are_dropped() {
+        let (engine, handle) = EngineLoop::new(DeterministicOrderBook::<()>::new("TEST"), 4);
+        let worker = engine.spawn();
+        let outcome = handle
+            .execute(Command::Add(limit(1, 100, 10, Side::Buy)))
+            .unwrap();
+        assert!(matches!(outcome, CommandOutcome::Order(Some(_))));
+        drop(handle);
+        assert_eq!(worker.join().unwrap().best_bid(), Some(100));
+    }
+
+    #[test]
+    fn test_deterministic_update_order() {
+        let mut book = DeterministicOrderBook::<()>::new("TEST");
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        book.add_order(limit(2, 100, 10, Side::Buy)).unwrap();
+
+        // Reducing keeps the place at the front of the level
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: OrderId::from_u64(1),
+            new_quantity: 4,
+        })
+        .unwrap();
+        let queue = book.get_orders_at_price(100, Side::Buy);
+        assert_eq!(queue[0].id(), OrderId::from_u64(1));
+        assert_eq!(queue[0].visible_quantity(), 4);
+
+        // Increasing keeps it too
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: OrderId::from_u64(1),
+            new_quantity: 20,
+        })
+        .unwrap();
+        let queue = book.get_orders_at_price(100, Side::Buy);
+        assert_eq!(queue[0].id(), OrderId::from_u64(1));
+        let trades = book
+            .submit_market_order(OrderId::from_u64(3), 1, Side::Sell)
+            .unwrap();
+        assert_eq!(
+            trades.transactions.as_vec()[0].maker_order_id,
+            OrderId::from_u64(1)
+        );
+
+        assert!(
+            book.update_order(OrderUpdate::UpdatePrice {
+                order_id: OrderId::from_u64(2),
+                new_price: 100,
+            })
+            .is_err()
+        );
+        book.update_order(OrderUpdate::Replace {
+            order_id: OrderId::from_u64(2),
+            price: 105,
+            quantity: 3,
+            side: Side::Sell,
+        })
+        .unwrap();
+        assert_eq!(book.best_ask(), Some(105));
+        assert!(
+            book.update_order(OrderUpdate::Cancel {
+                order_id: OrderId::from_u64(9),
+            })
+            .unwrap()
+            .is_none()
+        );
+    }
+}
@@ -58,6 +58,51 @@ mod tests {
         assert_eq!(format!("{err}"), format!("Invalid operation: {}", message));
     }
 
+    #[test]
+    fn test_display_invalid_price() {
+        let err = OrderBookError::InvalidPrice {
+            price: 100.12,
+            reason: "price is not a multiple of the tick size 5".to_string(),
+        };
+        assert_eq!(
+            format!("{err}"),
+            "Invalid price 100.12: price is not a multiple of the tick size 5"
+        );
+    }
+
+    #[test]
+    fn test_display_memory_pressure() {
+        let err = OrderBookError::MemoryPressure {
+            price: 500,
+            reference_price: 1000,
+        };
+        assert_eq!(
+            format!("{err}"),
+            "Memory pressure critical: order at 500 is too far from 1000"
+        );
+    }
+
+    #[test]
+    fn test_display_rate_limited() {
+        let err = OrderBookError::RateLimited {
+            account_id: Some("ACC-1".to_string()),
+        };
+        assert_eq!(format!("{err}"), "Rate limit exceeded for account ACC-1");
+        let err = OrderBookError::RateLimited { account_id: None };
+        assert_eq!(format!("{err}"), "Rate limit exceeded");
+    }
+
+    #[test]
+    fn test_display_serialization_error() {
+        let err = OrderBookError::SerializationError {
+            message: "line 3: unexpected end of input".to_string(),
+        };
+        assert_eq!(
+            format!("{err}"),
+            "Serialization error: line 3: unexpected end of input"
+        );
+    }
+
     #[test]
     fn test_from_price_level_error() {
         let price_level_error = PriceLevelError::InvalidFormat;
@@ -0,0 +1,147 @@
+//! Unit tests for events published by the order book.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::{OrderBookEvent, RejectReason};
+    use crate::orderbook::price_scale::RoundingMode;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    type Rejects = Arc<Mutex<Vec<(OrderId, RejectReason, String)>>>;
+
+    fn book_with_recorder() -> (OrderBook<()>, Rejects) {
+        let rejects: Rejects = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&rejects);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| match event {
+            OrderBookEvent::OrderRejected(reject) => {
+                recorded.lock().unwrap().push((
+                    reject.order_id,
+                    reject.reason,
+                    reject.error.to_string(),
+                ));
+            }
+            OrderBookEvent::LevelAdded(_)
+            | OrderBookEvent::LevelRemoved(_)
+            | OrderBookEvent::OptionsChanged(_)
+            | OrderBookEvent::OrderAccepted(_)
+            | OrderBookEvent::OrderExpired(_)
+            | OrderBookEvent::OrderCancelled(_)
+            | OrderBookEvent::OrderFilled(_)
+            | OrderBookEvent::PriceLevelFault(_)
+            | OrderBookEvent::QuotesPulled(_)
+            | OrderBookEvent::TradeExecuted(_)
+            | OrderBookEvent::TradingHalted(_)
+            | OrderBookEvent::TradingStateChanged(_) => {}
+        }));
+        (book, rejects)
+    }
+
+    #[test]
+    fn test_reject_reason_codes_are_unique() {
+        let reasons = [
+            RejectReason::PriceLevel,
+            RejectReason::UnknownOrder,
+            RejectReason::InvalidPrice,
+            RejectReason::WouldCrossMarket,
+            RejectReason::InsufficientLiquidity,
+            RejectReason::InvalidOperation,
+            RejectReason::MemoryPressure,
+            RejectReason::RateLimited,
+            RejectReason::InvalidTradingState,
+            RejectReason::DuplicateClientOrderId,
+            RejectReason::ShortSaleRestricted,
+            RejectReason::RestingCapExceeded,
+            RejectReason::DuplicateOrderId,
+            RejectReason::KillSwitch,
+        ];
+        let mut codes: Vec<u16> = reasons.iter().map(RejectReason::code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), reasons.len());
+    }
+
+    #[test]
+    fn test_post_only_reject_emits_event() {
+        let (book, rejects) = book_with_recorder();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let result = book.add_post_only_order(
+            OrderId::from_u64(2),
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        );
+
+        // The caller still gets the full error
+        assert!(matches!(result, Err(OrderBookError::PriceCrossing { .. })));
+        let rejects = rejects.lock().unwrap();
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0].0, OrderId::from_u64(2));
+        assert_eq!(rejects[0].1, RejectReason::WouldCrossMarket);
+        assert!(rejects[0].2.starts_with("Price crossing"));
+    }
+
+    #[test]
+    fn test_market_and_price_rejects_emit_events() {
+        let (book, rejects) = book_with_recorder();
+
+        assert!(
+            book.submit_market_order(OrderId::from_u64(1), 10, Side::Buy)
+                .is_err()
+        );
+        assert!(
+            book.add_limit_order_f64(
+                OrderId::from_u64(2),
+                -1.0,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                RoundingMode::Down,
+                None,
+            )
+            .is_err()
+        );
+
+        let reasons: Vec<RejectReason> = rejects
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|reject| reject.1)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                RejectReason::InsufficientLiquidity,
+                RejectReason::InvalidPrice
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accepted_orders_emit_no_reject() {
+        let (book, rejects) = book_with_recorder();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        assert!(rejects.lock().unwrap().is_empty());
+    }
+}
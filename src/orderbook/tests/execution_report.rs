@@ -0,0 +1,236 @@
+//! Unit tests for execution reports and the events they are built from.
+
+#[cfg(test)]
+mod tests {
+    use crate::current_time_millis;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::execution_report::{
+        ExecType, ExecutionReport, OrderStatus, execution_report_listener,
+    };
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    type Reports = Arc<Mutex<Vec<ExecutionReport>>>;
+
+    fn book_with_reports() -> (OrderBook<()>, Reports) {
+        let reports: Reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&reports);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(execution_report_listener(move |report| {
+            recorded.lock().unwrap().push(*report);
+        }));
+        (book, reports)
+    }
+
+    #[test]
+    fn test_trade_reports_for_maker_and_taker() {
+        let (book, reports) = book_with_reports();
+        let maker = rest(&book, 1, 100, 10, Side::Sell);
+        reports.lock().unwrap().clear();
+        let taker = OrderId::from_u64(2);
+        book.submit_market_order(taker, 4, Side::Buy).unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        let maker_report = reports.iter().find(|r| r.order_id == maker).unwrap();
+        let taker_report = reports.iter().find(|r| r.order_id == taker).unwrap();
+
+        assert_eq!(maker_report.exec_type, ExecType::Trade);
+        assert_eq!(maker_report.side, Some(Side::Sell));
+        assert_eq!(maker_report.last_quantity, 4);
+        assert_eq!(maker_report.last_price, 100);
+        assert_eq!(maker_report.leaves_quantity, 6);
+        assert_eq!(maker_report.cumulative_quantity, 4);
+        assert_eq!(maker_report.status, OrderStatus::PartiallyFilled);
+
+        assert_eq!(taker_report.side, Some(Side::Buy));
+        assert_eq!(taker_report.leaves_quantity, 0);
+        assert_eq!(taker_report.cumulative_quantity, 4);
+        assert_eq!(taker_report.status, OrderStatus::Filled);
+
+        assert!(maker_report.trade_id.is_some());
+        assert_eq!(maker_report.trade_id, taker_report.trade_id);
+        assert_ne!(maker_report.exec_id, taker_report.exec_id);
+    }
+
+    #[test]
+    fn test_cancel_report_carries_executed_quantity() {
+        let (book, reports) = book_with_reports();
+        let maker = rest(&book, 1, 100, 10, Side::Sell);
+        book.submit_market_order(OrderId::from_u64(2), 3, Side::Buy)
+            .unwrap();
+        reports.lock().unwrap().clear();
+
+        book.cancel_order(maker).unwrap().unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = reports[0];
+        assert_eq!(report.order_id, maker);
+        assert_eq!(report.exec_type, ExecType::Cancelled);
+        assert_eq!(report.side, Some(Side::Sell));
+        assert_eq!(report.last_quantity, 0);
+        assert_eq!(report.leaves_quantity, 0);
+        assert_eq!(report.cumulative_quantity, 3);
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.trade_id, None);
+    }
+
+    #[test]
+    fn test_cancel_through_update_order_is_reported() {
+        let (book, reports) = book_with_reports();
+        let order_id = rest(&book, 1, 100, 10, Side::Buy);
+        reports.lock().unwrap().clear();
+
+        book.update_order(OrderUpdate::Cancel { order_id })
+            .unwrap()
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].exec_type, ExecType::Cancelled);
+    }
+
+    #[test]
+    fn test_unknown_cancel_is_not_reported() {
+        let (book, reports) = book_with_reports();
+        assert!(book.cancel_order(OrderId::from_u64(9)).unwrap().is_none());
+        assert!(reports.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_amendments_do_not_report_cancels() {
+        let (book, reports) = book_with_reports();
+        let order_id = rest(&book, 1, 100, 10, Side::Buy);
+
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id,
+            new_price: 101,
+        })
+        .unwrap();
+        book.update_order(OrderUpdate::Replace {
+            order_id,
+            price: 102,
+            quantity: 5,
+            side: Side::Buy,
+        })
+        .unwrap();
+
+        assert!(
+            reports
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|report| report.exec_type == ExecType::New)
+        );
+        assert!(book.get_order(order_id).is_some());
+    }
+
+    #[test]
+    fn test_mass_cancel_reports_every_order() {
+        let (book, reports) = book_with_reports();
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 99, 10, Side::Buy);
+        rest(&book, 3, 110, 10, Side::Sell);
+
+        assert_eq!(book.cancel_all().len(), 3);
+
+        let reports = reports.lock().unwrap();
+        let mut ids: Vec<OrderId> = reports
+            .iter()
+            .filter(|r| r.exec_type == ExecType::Cancelled)
+            .map(|r| r.order_id)
+            .collect();
+        ids.sort_by_key(|id| id.to_string());
+        let mut expected: Vec<OrderId> = (1..=3).map(OrderId::from_u64).collect();
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(ids, expected);
+        assert!(
+            reports
+                .iter()
+                .filter(|r| r.exec_type == ExecType::Cancelled)
+                .all(|r| r.status == OrderStatus::Cancelled && r.leaves_quantity == 0)
+        );
+    }
+
+    #[test]
+    fn test_reject_report() {
+        let (book, reports) = book_with_reports();
+        rest(&book, 1, 100, 10, Side::Sell);
+        reports.lock().unwrap().clear();
+        let rejected = OrderId::from_u64(2);
+        assert!(
+            book.add_post_only_order(rejected, 100, 5, Side::Buy, TimeInForce::Gtc, None)
+                .is_err()
+        );
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = reports[0];
+        assert_eq!(report.order_id, rejected);
+        assert_eq!(report.exec_type, ExecType::Rejected);
+        assert_eq!(report.side, None);
+        assert_eq!(report.cumulative_quantity, 0);
+        assert_eq!(report.status, OrderStatus::Rejected);
+    }
+
+    #[test]
+    fn test_expiry_report_carries_executed_quantity() {
+        let (book, reports) = book_with_reports();
+        let now = current_time_millis();
+        let order_id = OrderId::from_u64(1);
+        book.add_limit_order(
+            order_id,
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtd(now + 1_000),
+            None,
+        )
+        .unwrap();
+        book.submit_market_order(OrderId::from_u64(2), 4, Side::Buy)
+            .unwrap();
+        reports.lock().unwrap().clear();
+
+        let expired = book.expire_orders_at(now + 1_000);
+        assert_eq!(expired[0].executed_quantity, 4);
+        assert_eq!(expired[0].quantity, 6);
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].exec_type, ExecType::Expired);
+        assert_eq!(reports[0].cumulative_quantity, 4);
+        assert_eq!(reports[0].status, OrderStatus::Expired);
+    }
+
+    #[test]
+    fn test_exec_ids_are_stable_per_event() {
+        let reports: Arc<Mutex<Vec<_>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&reports);
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let (Some(first), Some(second)) =
+                (event.execution_report(), event.execution_report())
+            {
+                recorded.lock().unwrap().push((first, second));
+            }
+        }));
+        let order_id = rest(&book, 1, 100, 10, Side::Buy);
+        book.cancel_order(order_id).unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|(first, second)| first == second));
+    }
+
+    #[test]
+    fn test_fix_values() {
+        assert_eq!(ExecType::Trade.fix_value(), 'F');
+        assert_eq!(ExecType::Cancelled.fix_value(), '4');
+        assert_eq!(OrderStatus::PartiallyFilled.fix_value(), '1');
+        assert_eq!(OrderStatus::Filled.fix_value(), '2');
+        assert!(OrderStatus::Filled.is_terminal());
+        assert!(!OrderStatus::PartiallyFilled.is_terminal());
+    }
+}
@@ -0,0 +1,187 @@
+//! Unit tests for the expiry sweeper and the expired-order log.
+
+#[cfg(test)]
+mod tests {
+    use crate::current_time_millis;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::expiry::ExpiredOrder;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    fn book_with_recorder() -> (OrderBook<()>, Arc<Mutex<Vec<ExpiredOrder>>>) {
+        let expired = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&expired);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderExpired(order) = event {
+                recorded.lock().unwrap().push(*order);
+            }
+        }));
+        (book, expired)
+    }
+
+    #[test]
+    fn test_gtd_orders_expire_at_their_deadline() {
+        let (book, events) = book_with_recorder();
+        let now = current_time_millis();
+        let early = OrderId::new();
+        let late = OrderId::new();
+        book.add_limit_order(
+            early,
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtd(now + 1_000),
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            late,
+            110,
+            5,
+            Side::Sell,
+            TimeInForce::Gtd(now + 5_000),
+            None,
+        )
+        .unwrap();
+
+        assert!(book.expire_orders_at(now + 999).is_empty());
+
+        let expired = book.expire_orders_at(now + 1_000);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].order_id, early);
+        assert_eq!(expired[0].sequence, 1);
+        assert_eq!(expired[0].side, Side::Buy);
+        assert_eq!(expired[0].price, 100);
+        assert_eq!(expired[0].quantity, 10);
+        assert_eq!(expired[0].timestamp, now + 1_000);
+        assert!(book.get_order(early).is_none());
+        assert!(book.get_order(late).is_some());
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(*events.lock().unwrap(), expired);
+    }
+
+    #[test]
+    fn test_day_orders_expire_at_market_close() {
+        let (book, _) = book_with_recorder();
+        let now = current_time_millis();
+        let day = OrderId::new();
+        let gtc = OrderId::new();
+        book.add_limit_order(day, 100, 10, Side::Buy, TimeInForce::Day, None)
+            .unwrap();
+        book.add_limit_order(gtc, 99, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        assert!(book.expire_orders_at(now).is_empty());
+
+        book.set_market_close_timestamp(now + 60_000);
+        let expired = book.expire_orders_at(now + 60_000);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].order_id, day);
+        assert_eq!(expired[0].time_in_force, TimeInForce::Day);
+        assert_eq!(book.best_bid(), Some(99));
+    }
+
+    #[test]
+    fn test_partially_filled_order_reports_remaining_quantity() {
+        let (book, _) = book_with_recorder();
+        let now = current_time_millis();
+        let resting = OrderId::new();
+        book.add_limit_order(
+            resting,
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtd(now + 1_000),
+            None,
+        )
+        .unwrap();
+        book.submit_market_order(OrderId::new(), 4, Side::Buy)
+            .unwrap();
+
+        let expired = book.expire_orders_at(now + 2_000);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].quantity, 6);
+        assert_eq!(book.executed_so_far(resting), None);
+    }
+
+    #[test]
+    fn test_expired_orders_since_returns_later_entries() {
+        let (book, _) = book_with_recorder();
+        let now = current_time_millis();
+        let ids: Vec<OrderId> = (0..3).map(|_| OrderId::new()).collect();
+        for (offset, id) in ids.iter().enumerate() {
+            let deadline = now + 1_000 * (offset as u64 + 1);
+            book.add_limit_order(*id, 100, 1, Side::Buy, TimeInForce::Gtd(deadline), None)
+                .unwrap();
+        }
+
+        book.expire_orders_at(now + 1_000);
+        book.expire_orders_at(now + 3_000);
+
+        let all = book.expired_orders_since(0);
+        assert_eq!(
+            all.iter().map(|order| order.order_id).collect::<Vec<_>>(),
+            ids
+        );
+        assert_eq!(
+            all.iter().map(|order| order.sequence).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let since = book.expired_orders_since(1);
+        assert_eq!(since.len(), 2);
+        assert_eq!(since[0].order_id, ids[1]);
+        assert!(book.expired_orders_since(3).is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_orders_are_not_reported_as_expired() {
+        let (book, events) = book_with_recorder();
+        let now = current_time_millis();
+        let id = OrderId::new();
+        book.add_limit_order(id, 100, 10, Side::Buy, TimeInForce::Gtd(now + 1_000), None)
+            .unwrap();
+        book.cancel_order(id).unwrap();
+
+        assert!(book.expire_orders_at(now + 2_000).is_empty());
+        assert!(book.expired_orders_since(0).is_empty());
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expiry_follows_the_book_clock() {
+        let (mut book, events) = book_with_recorder();
+        let clock = Arc::new(ManualClock::new(1_000));
+        book.set_clock(clock.clone());
+        let gtd = OrderId::new();
+        let day = OrderId::new();
+        book.add_limit_order(gtd, 100, 10, Side::Buy, TimeInForce::Gtd(1_500), None)
+            .unwrap();
+        book.add_limit_order(day, 99, 10, Side::Buy, TimeInForce::Day, None)
+            .unwrap();
+        book.set_market_close_timestamp(3_000);
+
+        assert!(book.expire_orders().is_empty());
+        clock.advance(500);
+        assert_eq!(book.expire_orders()[0].order_id, gtd);
+        // Orders already past their deadline are refused
+        assert!(
+            book.add_limit_order(
+                OrderId::new(),
+                98,
+                10,
+                Side::Buy,
+                TimeInForce::Gtd(1_200),
+                None
+            )
+            .is_err()
+        );
+        clock.set(3_000);
+        assert_eq!(book.expire_orders()[0].order_id, day);
+        assert_eq!(events.lock().unwrap().len(), 2);
+        assert_eq!(book.now(), 3_000);
+    }
+}
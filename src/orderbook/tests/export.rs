@@ -0,0 +1,112 @@
+//! Unit tests for the CSV and Parquet exporters.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::export::{
+        SNAPSHOT_CSV_HEADER, SnapshotCsvWriter, TRADE_CSV_HEADER, TradeCsvWriter,
+    };
+    use crate::orderbook::tape::TradeTape;
+    use pricelevel::{OrderId, Side, Transaction};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn trade(price: u64, quantity: u64) -> Transaction {
+        Transaction::new(
+            Uuid::nil(),
+            OrderId::from_u64(1),
+            OrderId::from_u64(2),
+            price,
+            quantity,
+            Side::Buy,
+        )
+    }
+
+    #[test]
+    fn test_trade_csv() {
+        let mut csv = TradeCsvWriter::new(Vec::new()).unwrap();
+        csv.write_trades(&[trade(100, 5), trade(101, 7)]).unwrap();
+        let output = String::from_utf8(csv.into_inner().unwrap()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], TRADE_CSV_HEADER);
+        let fields: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(fields.len(), 7);
+        assert_eq!(fields[1], Uuid::nil().to_string());
+        assert_eq!(&fields[2..5], ["101", "7", "buy"]);
+    }
+
+    #[test]
+    fn test_snapshot_csv_has_one_row_per_level() {
+        let book: OrderBook = BookBuilder::new("BTC,USD")
+            .bids([(100, 10), (99, 5)])
+            .asks([(101, 7)])
+            .build()
+            .unwrap();
+        let mut csv = SnapshotCsvWriter::new(Vec::new()).unwrap();
+        csv.write_snapshot(&book.create_snapshot(10)).unwrap();
+        csv.write_snapshot(&book.create_snapshot(1)).unwrap();
+        let output = String::from_utf8(csv.into_inner().unwrap()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], SNAPSHOT_CSV_HEADER);
+        assert_eq!(lines.len(), 1 + 3 + 2);
+        assert!(lines[2].ends_with(",\"BTC,USD\",buy,1,99,5,0,1"));
+        assert!(lines[3].ends_with(",\"BTC,USD\",sell,0,101,7,0,1"));
+    }
+
+    #[test]
+    fn test_tape_to_csv() {
+        let tape = Arc::new(TradeTape::new(Duration::from_secs(60)));
+        let mut book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .asks([(101, 7)])
+            .build()
+            .unwrap();
+        book.set_trade_tape(Arc::clone(&tape));
+        book.submit_market_order(OrderId::from_u64(9), 3, Side::Buy)
+            .unwrap();
+
+        let mut output = Vec::new();
+        tape.write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.lines().nth(1).unwrap().contains(",101,3,buy,"));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_round_trip() {
+        use crate::orderbook::export::{SnapshotParquetWriter, TradeParquetWriter};
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use std::fs::File;
+
+        let path = std::env::temp_dir().join(format!("trades-{}.parquet", std::process::id()));
+        let mut trades = TradeParquetWriter::new(File::create(&path).unwrap()).unwrap();
+        trades.write_trades(&[trade(100, 5)]).unwrap();
+        trades
+            .write_trades(&[trade(101, 7), trade(102, 1)])
+            .unwrap();
+        trades.finish().unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        std::fs::remove_file(&path).unwrap();
+
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .bids([(100, 10), (99, 5)])
+            .asks([(101, 7)])
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("snapshots-{}.parquet", std::process::id()));
+        let mut snapshots = SnapshotParquetWriter::new(File::create(&path).unwrap()).unwrap();
+        snapshots.write_snapshot(&book.create_snapshot(10)).unwrap();
+        snapshots.finish().unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 3);
+        assert_eq!(metadata.schema_descr().num_columns(), 8);
+    }
+}
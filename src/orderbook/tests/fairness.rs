@@ -0,0 +1,117 @@
+//! Unit and stress tests for fair quantity updates at contended levels.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const WRITERS: u64 = 8;
+    const UPDATES_PER_WRITER: u64 = 500;
+
+    fn rest(book: &OrderBook<()>, id: u64, price: u64, quantity: u64) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_quantity_update_follows_a_moved_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        rest(&book, 1, 100, 10);
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(1),
+            new_price: 99,
+        })
+        .unwrap();
+
+        let updated = book
+            .update_order(OrderUpdate::UpdateQuantity {
+                order_id: OrderId::from_u64(1),
+                new_quantity: 4,
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.price(), 99);
+        assert_eq!(updated.visible_quantity(), 4);
+        assert!(
+            book.update_order(OrderUpdate::UpdateQuantity {
+                order_id: OrderId::from_u64(2),
+                new_quantity: 4,
+            })
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_no_writer_starves_at_a_hot_level() {
+        let book = Arc::new(OrderBook::<()>::new("TEST"));
+        for id in 1..=WRITERS {
+            rest(&book, id, 100, 1);
+        }
+        let barrier = Arc::new(Barrier::new(WRITERS as usize + 2));
+        let done = Arc::new(AtomicBool::new(false));
+        let progress: Arc<Vec<AtomicU64>> =
+            Arc::new((0..WRITERS).map(|_| AtomicU64::new(0)).collect());
+
+        // Readers keep the level's shard busy, which alone can starve a writer
+        let reader = {
+            let book = Arc::clone(&book);
+            let barrier = Arc::clone(&barrier);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                barrier.wait();
+                while !done.load(Ordering::Relaxed) {
+                    let _ = book.create_snapshot(1);
+                }
+            })
+        };
+
+        let writers: Vec<_> = (1..=WRITERS)
+            .map(|id| {
+                let book = Arc::clone(&book);
+                let barrier = Arc::clone(&barrier);
+                let progress = Arc::clone(&progress);
+                thread::spawn(move || {
+                    barrier.wait();
+                    for update in 1..=UPDATES_PER_WRITER {
+                        book.update_order(OrderUpdate::UpdateQuantity {
+                            order_id: OrderId::from_u64(id),
+                            new_quantity: 1 + update % 7,
+                        })
+                        .unwrap()
+                        .unwrap();
+                        progress[id as usize - 1].fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect();
+
+        let started = Instant::now();
+        barrier.wait();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        reader.join().unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(60));
+        for count in progress.iter() {
+            assert_eq!(count.load(Ordering::Relaxed), UPDATES_PER_WRITER);
+        }
+        assert_eq!(
+            book.create_snapshot(1).bids[0].order_count,
+            WRITERS as usize
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Fault-injection tests checking that price level failures are surfaced, not swallowed.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::{LevelOperation, OrderBookError};
+    use crate::orderbook::events::{OrderBookEvent, RejectReason};
+    use pricelevel::{OrderId, OrderUpdate, PriceLevelError, Side, TimeInForce};
+    use std::error::Error;
+    use std::sync::{Arc, Mutex};
+
+    type Faults = Arc<Mutex<Vec<String>>>;
+
+    fn book_with_recorder() -> (OrderBook<()>, Faults) {
+        let faults: Faults = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&faults);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::PriceLevelFault(error) = event {
+                recorded.lock().unwrap().push(error.to_string());
+            }
+        }));
+        (book, faults)
+    }
+
+    fn injected() -> PriceLevelError {
+        PriceLevelError::InvalidOperation {
+            message: "injected".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_failed_cancel_is_returned_with_context() {
+        let (book, _) = book_with_recorder();
+        let id = OrderId::from_u64(1);
+        book.add_limit_order(id, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        book.level_faults.inject(LevelOperation::Cancel, injected());
+
+        let error = book.cancel_order(id).unwrap_err();
+        match &error {
+            OrderBookError::PriceLevelOperation {
+                operation,
+                order_id,
+                side,
+                price,
+                source,
+            } => {
+                assert_eq!(*operation, LevelOperation::Cancel);
+                assert_eq!(*order_id, id);
+                assert_eq!(*side, Side::Buy);
+                assert_eq!(*price, 100);
+                assert_eq!(source.to_string(), injected().to_string());
+            }
+            other => panic!("Expected PriceLevelOperation, got {other:?}"),
+        }
+        assert!(error.source().is_some());
+        assert_eq!(RejectReason::from(&error), RejectReason::PriceLevel);
+
+        // The order is still tracked and can be cancelled once the level behaves
+        assert!(book.get_order(id).is_some());
+        assert!(book.cancel_order(id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_failed_quantity_update_is_returned() {
+        let (book, _) = book_with_recorder();
+        let id = OrderId::from_u64(1);
+        book.add_limit_order(id, 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.level_faults
+            .inject(LevelOperation::UpdateQuantity, injected());
+
+        let result = book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: id,
+            new_quantity: 5,
+        });
+        assert!(matches!(
+            result,
+            Err(OrderBookError::PriceLevelOperation {
+                operation: LevelOperation::UpdateQuantity,
+                ..
+            })
+        ));
+        assert_eq!(book.get_order(id).unwrap().visible_quantity(), 10);
+    }
+
+    #[test]
+    fn test_failed_cancel_update_keeps_order_tracked() {
+        let (book, _) = book_with_recorder();
+        let id = OrderId::from_u64(1);
+        book.add_limit_order(id, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        book.level_faults.inject(LevelOperation::Cancel, injected());
+
+        let result = book.update_order(OrderUpdate::Cancel { order_id: id });
+        assert!(result.is_err());
+        assert!(book.get_order(id).is_some());
+        assert!(book.order_locations.contains_key(&id));
+    }
+
+    #[test]
+    fn test_failed_fill_is_reported_as_fault_event() {
+        let (book, faults) = book_with_recorder();
+        let failing = OrderId::from_u64(1);
+        let healthy = OrderId::from_u64(2);
+        book.add_limit_order(failing, 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(healthy, 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        // A constrained order resting at the level routes its matching through
+        // the per-order path
+        book.add_all_or_none_order(
+            OrderId::from_u64(3),
+            100,
+            50,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.level_faults.inject(LevelOperation::Fill, injected());
+
+        let result = book
+            .match_order(OrderId::from_u64(4), Side::Buy, 10, Some(100))
+            .unwrap();
+
+        // The failing maker is skipped and keeps its quantity
+        assert_eq!(result.transactions.as_vec().len(), 1);
+        assert_eq!(result.transactions.as_vec()[0].maker_order_id, healthy);
+        assert_eq!(book.get_order(failing).unwrap().visible_quantity(), 10);
+
+        let faults = faults.lock().unwrap();
+        assert_eq!(faults.len(), 1);
+        assert!(faults[0].contains("failed to fill order"));
+        assert!(faults[0].contains(&failing.to_string()));
+    }
+
+    #[test]
+    fn test_failed_expiry_is_reported_as_fault_event() {
+        let (book, faults) = book_with_recorder();
+        let now = crate::current_time_millis();
+        let id = OrderId::from_u64(1);
+        book.add_limit_order(id, 100, 10, Side::Buy, TimeInForce::Gtd(now + 1_000), None)
+            .unwrap();
+        book.level_faults.inject(LevelOperation::Cancel, injected());
+
+        assert!(book.expire_orders_at(now + 1_000).is_empty());
+        assert_eq!(faults.lock().unwrap().len(), 1);
+        assert!(book.get_order(id).is_some());
+    }
+
+    #[test]
+    fn test_price_level_operation_display() {
+        let error = OrderBookError::PriceLevelOperation {
+            operation: LevelOperation::UpdateQuantity,
+            order_id: OrderId::from_u64(7),
+            side: Side::Sell,
+            price: 105,
+            source: PriceLevelError::InvalidFormat,
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Price level SELL 105 failed to update the quantity of order {}: Invalid format",
+                OrderId::from_u64(7)
+            )
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Unit tests for rebuilding a book from feed messages.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::feed::FeedMessage;
+    use pricelevel::{OrderId, Side};
+
+    fn add(id: u64, side: Side, price: u64, quantity: u64) -> FeedMessage {
+        FeedMessage::Add {
+            order_id: OrderId::from_u64(id),
+            side,
+            price,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_adds_rest_without_matching() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let applied = book
+            .apply_feed(&[
+                add(1, Side::Buy, 100, 10),
+                add(2, Side::Sell, 101, 5),
+                // A crossed auction book is kept as reported
+                add(3, Side::Buy, 102, 7),
+            ])
+            .unwrap();
+
+        assert_eq!(applied, 3);
+        assert_eq!(book.best_bid(), Some(102));
+        assert_eq!(book.best_ask(), Some(101));
+        assert_eq!(book.last_trade_price(), None);
+        assert!(matches!(
+            book.apply_feed_message(&add(1, Side::Buy, 99, 1)),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_executions_and_cancels_reduce_the_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.apply_feed(&[add(1, Side::Sell, 101, 10), add(2, Side::Sell, 101, 4)])
+            .unwrap();
+        let id = OrderId::from_u64(1);
+
+        book.apply_feed_message(&FeedMessage::Execute {
+            order_id: id,
+            quantity: 3,
+            price: None,
+        })
+        .unwrap();
+        assert_eq!(book.last_trade_price(), Some(101));
+        assert_eq!(book.executed_so_far(id), Some(3));
+
+        book.apply_feed_message(&FeedMessage::Cancel {
+            order_id: id,
+            quantity: 2,
+        })
+        .unwrap();
+        assert_eq!(book.get_order(id).unwrap().visible_quantity(), 5);
+        // Reduced orders keep their place in the queue
+        let snapshot = book.create_l3_snapshot(1);
+        assert_eq!(snapshot.asks[0].orders[0].id, id);
+
+        book.apply_feed_message(&FeedMessage::Execute {
+            order_id: id,
+            quantity: 5,
+            price: Some(100),
+        })
+        .unwrap();
+        assert!(book.get_order(id).is_none());
+        assert_eq!(book.last_trade_price(), Some(100));
+        assert_eq!(book.stats().orders_cancelled, 0);
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_delete_and_replace() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.apply_feed(&[add(1, Side::Buy, 100, 10), add(2, Side::Buy, 99, 10)])
+            .unwrap();
+
+        book.apply_feed_message(&FeedMessage::Replace {
+            order_id: OrderId::from_u64(1),
+            new_order_id: OrderId::from_u64(3),
+            price: 98,
+            quantity: 6,
+        })
+        .unwrap();
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert_eq!(book.best_bid(), Some(99));
+        let replaced = book.get_order(OrderId::from_u64(3)).unwrap();
+        assert_eq!((replaced.price(), replaced.side()), (98, Side::Buy));
+
+        book.apply_feed_message(&FeedMessage::Delete {
+            order_id: OrderId::from_u64(2),
+        })
+        .unwrap();
+        assert_eq!(book.best_bid(), Some(98));
+        assert!(matches!(
+            book.apply_feed_message(&FeedMessage::Delete {
+                order_id: OrderId::from_u64(2),
+            }),
+            Err(OrderBookError::OrderNotFound(_))
+        ));
+        assert!(book.validate().is_valid());
+    }
+}
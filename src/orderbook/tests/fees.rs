@@ -0,0 +1,133 @@
+//! Unit tests for maker/taker fees.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::fees::{FeeSchedule, TradeFees};
+    use crate::orderbook::fills::FillNotification;
+    use crate::orderbook::tests::helpers::record_trades;
+    use pricelevel::{OrderId, Side, TimeInForce, Transaction};
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    fn book_with_ask(price: u64, quantity: u64) -> OrderBook<()> {
+        let book = OrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            price,
+            quantity,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book
+    }
+
+    #[test]
+    fn test_fees_are_computed_per_trade() {
+        let mut book = book_with_ask(1_000, 10);
+        let reports = record_trades(&mut book);
+        book.set_fee_schedule(Some(FeeSchedule::new(-2, 5)));
+
+        book.submit_market_order(OrderId::from_u64(2), 10, Side::Buy)
+            .unwrap();
+
+        // Notional 10_000: 5 bps is 5, a 2 bps rebate is -2
+        assert_eq!(
+            reports.lock().unwrap()[0].fees,
+            Some(TradeFees {
+                maker_fee: -2,
+                taker_fee: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_fees_without_schedule() {
+        let mut book = book_with_ask(1_000, 10);
+        let reports = record_trades(&mut book);
+
+        book.submit_market_order(OrderId::from_u64(2), 10, Side::Buy)
+            .unwrap();
+
+        assert_eq!(reports.lock().unwrap()[0].fees, None);
+    }
+
+    #[test]
+    fn test_minimum_fee_and_rounding() {
+        let schedule = FeeSchedule::new(-1, 3);
+        let transaction = Transaction::new(
+            Uuid::nil(),
+            OrderId::from_u64(2),
+            OrderId::from_u64(1),
+            101,
+            10,
+            Side::Buy,
+        );
+
+        // Notional 1_010: the taker fee of 0.303 is raised to the minimum, and
+        // the rebate of 0.101 rounds to nothing rather than being raised
+        assert_eq!(
+            schedule.with_min_fee(4).fees_for(&transaction),
+            TradeFees {
+                maker_fee: 0,
+                taker_fee: 4,
+            }
+        );
+
+        // Notional 1_000_010 at 1 bp is 100.001, rounded up
+        let large = Transaction {
+            price: 100_001,
+            ..transaction
+        };
+        let fees = FeeSchedule::new(1, 1).with_min_fee(4).fees_for(&large);
+        assert_eq!(fees.taker_fee, 101);
+    }
+
+    #[test]
+    fn test_schedule_change_applies_to_later_trades() {
+        let mut book = book_with_ask(1_000, 10);
+        let reports = record_trades(&mut book);
+        book.set_fee_schedule(Some(FeeSchedule::new(0, 10)));
+        book.submit_market_order(OrderId::from_u64(2), 5, Side::Buy)
+            .unwrap();
+        book.set_fee_schedule(None);
+        book.submit_market_order(OrderId::from_u64(3), 5, Side::Buy)
+            .unwrap();
+
+        let fees: Vec<_> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|report| report.fees.map(|fees| fees.taker_fee))
+            .collect();
+        assert_eq!(fees, vec![Some(5), None]);
+        assert_eq!(book.options().options.fee_schedule, None);
+    }
+
+    #[test]
+    fn test_fill_notifications_carry_fees() {
+        let fills: Arc<Mutex<Vec<FillNotification>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&fills);
+        let mut book = book_with_ask(1_000, 10);
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event {
+                recorded.lock().unwrap().push(*fill);
+            }
+        }));
+        book.set_fee_schedule(Some(FeeSchedule::new(-2, 5)));
+
+        book.submit_market_order(OrderId::from_u64(2), 10, Side::Buy)
+            .unwrap();
+
+        let fees: Vec<_> = fills
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|fill| (fill.is_maker, fill.fee))
+            .collect();
+        assert_eq!(fees, vec![(false, Some(5)), (true, Some(-2))]);
+    }
+}
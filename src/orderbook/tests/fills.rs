@@ -0,0 +1,145 @@
+//! Unit tests for per-order fill notifications.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::fills::FillNotification;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    type Fills = Arc<Mutex<Vec<FillNotification>>>;
+
+    fn book_with_recorder() -> (OrderBook<()>, Fills) {
+        let fills: Fills = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&fills);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event {
+                recorded.lock().unwrap().push(*fill);
+            }
+        }));
+        (book, fills)
+    }
+
+    fn fills_of(fills: &Fills, order_id: OrderId) -> Vec<(u64, u64, u64)> {
+        fills
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fill| fill.order_id == order_id)
+            .map(|fill| {
+                (
+                    fill.quantity,
+                    fill.cumulative_quantity,
+                    fill.remaining_quantity,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_taker_and_maker_are_notified_for_each_trade() {
+        let (book, fills) = book_with_recorder();
+        let maker = OrderId::from_u64(1);
+        let taker = OrderId::from_u64(2);
+        book.add_limit_order(maker, 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        book.submit_market_order(taker, 4, Side::Buy).unwrap();
+
+        let recorded = fills.lock().unwrap().clone();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].transaction_id, recorded[1].transaction_id);
+
+        let taker_fill = recorded[0];
+        assert_eq!(taker_fill.order_id, taker);
+        assert!(!taker_fill.is_maker);
+        assert_eq!(taker_fill.side, Side::Buy);
+        assert_eq!(taker_fill.price, 100);
+        assert!(taker_fill.is_complete());
+
+        let maker_fill = recorded[1];
+        assert_eq!(maker_fill.order_id, maker);
+        assert!(maker_fill.is_maker);
+        assert_eq!(maker_fill.side, Side::Sell);
+        assert_eq!(
+            (
+                maker_fill.quantity,
+                maker_fill.cumulative_quantity,
+                maker_fill.remaining_quantity
+            ),
+            (4, 4, 6)
+        );
+    }
+
+    #[test]
+    fn test_cumulative_and_remaining_quantities_across_fills() {
+        let (book, fills) = book_with_recorder();
+        let maker = OrderId::from_u64(1);
+        book.add_limit_order(maker, 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            101,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        book.submit_market_order(OrderId::from_u64(3), 3, Side::Buy)
+            .unwrap();
+        let taker = OrderId::from_u64(4);
+        book.add_limit_order(taker, 101, 12, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        assert_eq!(fills_of(&fills, maker), vec![(3, 3, 7), (7, 10, 0)]);
+        assert_eq!(fills_of(&fills, taker), vec![(7, 7, 5), (5, 12, 0)]);
+        assert_eq!(fills_of(&fills, OrderId::from_u64(2)), vec![(5, 5, 5)]);
+    }
+
+    #[test]
+    fn test_several_makers_at_one_level() {
+        let (book, fills) = book_with_recorder();
+        let first = OrderId::from_u64(1);
+        let second = OrderId::from_u64(2);
+        book.add_limit_order(first, 100, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(second, 100, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let taker = OrderId::from_u64(3);
+        book.submit_market_order(taker, 8, Side::Sell).unwrap();
+
+        assert_eq!(fills_of(&fills, first), vec![(5, 5, 0)]);
+        assert_eq!(fills_of(&fills, second), vec![(3, 3, 2)]);
+        assert_eq!(fills_of(&fills, taker), vec![(5, 5, 3), (3, 8, 0)]);
+    }
+
+    #[test]
+    fn test_no_notifications_without_trades() {
+        let (book, fills) = book_with_recorder();
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            5,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            101,
+            5,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert!(fills.lock().unwrap().is_empty());
+    }
+}
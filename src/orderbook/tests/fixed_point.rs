@@ -0,0 +1,115 @@
+//! Unit tests for the fixed-point price and quantity types.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::fixed_point::{Price, Qty};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let price = Price::<2>::from_str("101.25").unwrap();
+        assert_eq!(price.raw(), 10_125);
+        assert_eq!(price.to_string(), "101.25");
+        assert_eq!(Price::<2>::from_str("101.5").unwrap().raw(), 10_150);
+        assert_eq!(Price::<2>::from_str(".5").unwrap().raw(), 50);
+        assert_eq!(Price::<0>::from_str("42").unwrap().to_string(), "42");
+
+        let quantity = Qty::<8>::from_str("0.5").unwrap();
+        assert_eq!(u64::from(quantity), 50_000_000);
+        assert_eq!(quantity.to_string(), "0.50000000");
+    }
+
+    #[test]
+    fn test_parse_rejects_unrepresentable_text() {
+        assert!(Price::<2>::from_str("101.255").is_err());
+        // Trailing zeros beyond the scale carry no value
+        assert_eq!(Price::<2>::from_str("101.2500").unwrap().raw(), 10_125);
+        assert!(Price::<2>::from_str("-1").is_err());
+        assert!(Price::<2>::from_str("abc").is_err());
+        assert!(Price::<2>::from_str(".").is_err());
+        assert!(Qty::<8>::from_str("1000000000000").is_err());
+    }
+
+    #[test]
+    fn test_units_and_ordering() {
+        assert_eq!(Qty::<3>::from_units(2), Some(Qty::from_raw(2_000)));
+        assert_eq!(Qty::<3>::from_units(u64::MAX), None);
+        assert!(Price::<2>::from_raw(100) < Price::from_raw(101));
+        assert!((Price::<2>::from_raw(10_125).to_f64() - 101.25).abs() < 1e-12);
+        assert_eq!(Price::<4>::SCALE, 10_000);
+    }
+}
+
+#[cfg(all(test, feature = "decimal"))]
+mod decimal_tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::fixed_point::{Price, Qty};
+    use crate::orderbook::price_scale::{PriceScale, RoundingMode};
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_decimal_conversions_are_exact() {
+        let price = Price::<2>::try_from(decimal("101.25")).unwrap();
+        assert_eq!(price.raw(), 10_125);
+        assert_eq!(price.to_decimal(), decimal("101.25"));
+        assert_eq!(Decimal::from(Qty::<8>::from_raw(1)), decimal("0.00000001"));
+
+        assert!(Price::<2>::try_from(decimal("0.001")).is_err());
+        assert!(Price::<2>::try_from(decimal("-1")).is_err());
+        assert!(Qty::<8>::try_from(decimal("1000000000000")).is_err());
+    }
+
+    #[test]
+    fn test_price_scale_converts_decimals() {
+        let scale = PriceScale::new(2, 5).unwrap();
+        assert_eq!(
+            scale
+                .decimal_to_integer_price(decimal("100.10"), RoundingMode::Exact, true)
+                .unwrap(),
+            10_010
+        );
+        assert_eq!(
+            scale
+                .decimal_to_integer_price(decimal("100.12"), RoundingMode::Passive, true)
+                .unwrap(),
+            10_010
+        );
+        assert_eq!(
+            scale
+                .decimal_to_integer_price(decimal("100.12"), RoundingMode::Passive, false)
+                .unwrap(),
+            10_015
+        );
+        assert!(
+            scale
+                .decimal_to_integer_price(decimal("100.12"), RoundingMode::Exact, true)
+                .is_err()
+        );
+        assert_eq!(scale.to_decimal(10_015), decimal("100.15"));
+    }
+
+    #[test]
+    fn test_add_limit_order_decimal() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST");
+        book.set_price_scale(PriceScale::new(2, 1).unwrap());
+        let (price, _) = book
+            .add_limit_order_decimal(
+                OrderId::new(),
+                decimal("100.1"),
+                5,
+                Side::Buy,
+                TimeInForce::Gtc,
+                RoundingMode::Exact,
+                None,
+            )
+            .unwrap();
+        assert_eq!(price, 10_010);
+        assert_eq!(book.best_bid(), Some(10_010));
+    }
+}
@@ -0,0 +1,162 @@
+//! Unit tests for the follower book fed by a sequenced feed.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::feed::FeedMessage;
+    use crate::orderbook::feed::follower::{FollowerBook, FollowerStatus, SequencedFeedMessage};
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn add(sequence: u64, id: u64, side: Side, price: u64, quantity: u64) -> SequencedFeedMessage {
+        SequencedFeedMessage {
+            sequence,
+            message: FeedMessage::Add {
+                order_id: OrderId::from_u64(id),
+                side,
+                price,
+                quantity,
+            },
+        }
+    }
+
+    fn delete(sequence: u64, id: u64) -> SequencedFeedMessage {
+        SequencedFeedMessage {
+            sequence,
+            message: FeedMessage::Delete {
+                order_id: OrderId::from_u64(id),
+            },
+        }
+    }
+
+    #[test]
+    fn test_applies_messages_in_sequence() {
+        let mut follower: FollowerBook = FollowerBook::new("TEST");
+        assert_eq!(
+            follower.apply(add(1, 1, Side::Buy, 100, 10)).unwrap(),
+            FollowerStatus::Synced
+        );
+        follower.apply(add(2, 2, Side::Sell, 101, 5)).unwrap();
+        // Duplicates are ignored
+        follower.apply(add(2, 2, Side::Sell, 101, 5)).unwrap();
+
+        assert_eq!(follower.last_sequence(), 2);
+        assert_eq!(follower.book().best_bid(), Some(100));
+        assert_eq!(follower.book().best_ask(), Some(101));
+    }
+
+    #[test]
+    fn test_gap_holds_messages_until_filled() {
+        let mut follower: FollowerBook = FollowerBook::new("TEST");
+        follower.apply(add(1, 1, Side::Buy, 100, 10)).unwrap();
+
+        let status = follower.apply(add(3, 3, Side::Buy, 99, 10)).unwrap();
+        assert_eq!(status, FollowerStatus::Gap { expected: 2 });
+        assert!(follower.needs_resync());
+        assert_eq!(follower.pending_len(), 1);
+        assert_eq!(follower.book().best_bid(), Some(100));
+
+        // The missing message arrives late, the held one follows it
+        let status = follower.apply(delete(2, 1)).unwrap();
+        assert_eq!(status, FollowerStatus::Synced);
+        assert_eq!(follower.last_sequence(), 3);
+        assert_eq!(follower.book().best_bid(), Some(99));
+    }
+
+    #[test]
+    fn test_resync_from_snapshot_replays_held_messages() {
+        let source: OrderBook<()> = OrderBook::new("TEST");
+        source
+            .add_limit_order(
+                OrderId::from_u64(1),
+                100,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        source
+            .add_limit_order(
+                OrderId::from_u64(2),
+                100,
+                4,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        source
+            .add_limit_order(
+                OrderId::from_u64(3),
+                105,
+                7,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        let snapshot = source.create_l3_snapshot(usize::MAX);
+
+        // Joined late: sequences up to 5 are covered by the snapshot
+        let mut follower: FollowerBook = FollowerBook::new("TEST");
+        follower.apply(add(5, 9, Side::Buy, 50, 1)).unwrap();
+        follower.apply(delete(7, 3)).unwrap();
+        assert_eq!(follower.status(), FollowerStatus::Gap { expected: 1 });
+
+        let status = follower.resync(&snapshot, 6).unwrap();
+        assert_eq!(status, FollowerStatus::Synced);
+        assert_eq!(follower.last_sequence(), 7);
+        assert_eq!(follower.pending_len(), 0);
+
+        let book = follower.book();
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), None);
+        assert!(book.get_order(OrderId::from_u64(9)).is_none());
+        let level = &book.create_l3_snapshot(1).bids[0];
+        let ids: Vec<OrderId> = level.orders.iter().map(|order| order.id).collect();
+        assert_eq!(ids, vec![OrderId::from_u64(1), OrderId::from_u64(2)]);
+    }
+
+    #[test]
+    fn test_failed_message_diverges_until_resync() {
+        let mut follower: FollowerBook = FollowerBook::new("TEST");
+        let result = follower.apply(delete(1, 42));
+        assert!(matches!(result, Err(OrderBookError::OrderNotFound(_))));
+        assert_eq!(follower.status(), FollowerStatus::Diverged { sequence: 1 });
+
+        // Later messages wait for the resync
+        follower.apply(add(2, 1, Side::Sell, 101, 3)).unwrap();
+        assert!(follower.book().best_ask().is_none());
+
+        let empty = OrderBook::<()>::new("TEST").create_l3_snapshot(usize::MAX);
+        assert_eq!(follower.resync(&empty, 1).unwrap(), FollowerStatus::Synced);
+        assert_eq!(follower.book().best_ask(), Some(101));
+    }
+
+    #[test]
+    fn test_pending_is_bounded() {
+        let mut follower: FollowerBook = FollowerBook::new("TEST").with_max_pending(2);
+        for sequence in 2..=5 {
+            follower
+                .apply(add(sequence, sequence, Side::Buy, 100, 1))
+                .unwrap();
+        }
+        assert_eq!(follower.pending_len(), 2);
+    }
+
+    #[test]
+    fn test_readers_share_the_book_across_threads() {
+        let mut follower: FollowerBook = FollowerBook::new("TEST");
+        let book = Arc::clone(follower.book());
+        for sequence in 1..=100 {
+            follower
+                .apply(add(sequence, sequence, Side::Sell, 100 + sequence, 1))
+                .unwrap();
+        }
+        let best = thread::spawn(move || book.best_ask()).join().unwrap();
+        assert_eq!(best, Some(101));
+    }
+}
@@ -0,0 +1,124 @@
+//! Unit tests for the fuzzing operations.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::testing::{Op, apply_op};
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+    fn id(n: u64) -> OrderId {
+        OrderId::from_u64(n)
+    }
+
+    #[test]
+    fn test_apply_op_edge_cases_keep_book_valid() {
+        let book: OrderBook<()> = OrderBook::new("FUZZ");
+        let ops = [
+            Op::AddLimit {
+                id: id(1),
+                price: 10,
+                quantity: 5,
+                side: Side::Buy,
+                time_in_force: TimeInForce::Gtc,
+            },
+            Op::AddIceberg {
+                id: id(2),
+                price: 12,
+                visible: 2,
+                hidden: 6,
+                side: Side::Sell,
+            },
+            // Zero quantity
+            Op::AddLimit {
+                id: id(3),
+                price: 10,
+                quantity: 0,
+                side: Side::Buy,
+                time_in_force: TimeInForce::Gtc,
+            },
+            Op::AddPostOnly {
+                id: id(8),
+                price: 9,
+                quantity: 1,
+                side: Side::Buy,
+            },
+            // Crossing post-only
+            Op::AddPostOnly {
+                id: id(4),
+                price: 12,
+                quantity: 1,
+                side: Side::Buy,
+            },
+            Op::Match {
+                id: id(5),
+                quantity: 3,
+                side: Side::Buy,
+                limit_price: Some(12),
+            },
+            Op::Update(OrderUpdate::UpdatePrice {
+                order_id: id(1),
+                new_price: 13,
+            }),
+            Op::Update(OrderUpdate::Replace {
+                order_id: id(2),
+                price: 8,
+                quantity: 4,
+                side: Side::Sell,
+            }),
+            Op::Update(OrderUpdate::UpdateQuantity {
+                order_id: id(99),
+                new_quantity: 1,
+            }),
+            Op::Market {
+                id: id(6),
+                quantity: 50,
+                side: Side::Sell,
+            },
+            Op::Market {
+                id: id(7),
+                quantity: 1,
+                side: Side::Sell,
+            },
+            Op::Cancel { id: id(2) },
+            Op::Cancel { id: id(2) },
+        ];
+
+        let mut errors = 0;
+        for op in ops {
+            if apply_op(&book, op).is_err() {
+                errors += 1;
+            }
+            let report = book.validate();
+            assert!(report.is_valid(), "after {op:?}: {report}");
+        }
+        assert!(errors > 0);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_sequences_keep_book_valid() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // A small deterministic fuzz run over pseudo-random inputs
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..200 {
+            let bytes: Vec<u8> = (0..512)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect();
+            let mut input = Unstructured::new(&bytes);
+            let ops = Vec::<Op>::arbitrary(&mut input).unwrap();
+
+            let book: OrderBook<()> = OrderBook::new("FUZZ");
+            for op in &ops {
+                let _ = apply_op(&book, *op);
+            }
+            let report = book.validate();
+            assert!(report.is_valid(), "{ops:?}: {report}");
+        }
+    }
+}
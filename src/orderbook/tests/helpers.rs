@@ -0,0 +1,51 @@
+//! Helpers shared by the unit tests of the order book.
+
+use crate::orderbook::book::OrderBook;
+use crate::orderbook::events::OrderBookEvent;
+use crate::orderbook::trade::TradeReport;
+use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+use std::sync::{Arc, Mutex};
+
+/// A new order id, unique across tests
+pub(super) fn create_order_id() -> OrderId {
+    OrderId::new_uuid()
+}
+
+/// A good-till-cancelled standard limit order
+pub(super) fn limit(id: u64, price: u64, quantity: u64, side: Side) -> OrderType<()> {
+    OrderType::Standard {
+        id: OrderId::from_u64(id),
+        price,
+        quantity,
+        side,
+        timestamp: 0,
+        time_in_force: TimeInForce::Gtc,
+        extra_fields: (),
+    }
+}
+
+/// Add a good-till-cancelled limit order to `book`, returning its id
+pub(super) fn rest(
+    book: &OrderBook<()>,
+    id: u64,
+    price: u64,
+    quantity: u64,
+    side: Side,
+) -> OrderId {
+    let order_id = OrderId::from_u64(id);
+    book.add_limit_order(order_id, price, quantity, side, TimeInForce::Gtc, None)
+        .unwrap();
+    order_id
+}
+
+/// Record the trade reports `book` publishes from now on, replacing its event listener
+pub(super) fn record_trades(book: &mut OrderBook<()>) -> Arc<Mutex<Vec<TradeReport>>> {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&reports);
+    book.set_event_listener(Arc::new(move |event| {
+        if let OrderBookEvent::TradeExecuted(report) = event {
+            recorded.lock().unwrap().push(*report);
+        }
+    }));
+    reports
+}
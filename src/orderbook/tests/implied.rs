@@ -0,0 +1,108 @@
+//! Unit tests for implied spread matching across two outright books.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::implied::{ImpliedMatchingEngine, ImpliedQuote};
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::Arc;
+
+    fn engine() -> ImpliedMatchingEngine {
+        let front: OrderBook<()> = BookBuilder::new("FRONT")
+            .bid(100, 10)
+            .bid(99, 50)
+            .ask(102, 5)
+            .build()
+            .unwrap();
+        let back: OrderBook<()> = BookBuilder::new("BACK")
+            .bid(104, 8)
+            .ask(106, 3)
+            .ask(107, 50)
+            .build()
+            .unwrap();
+        ImpliedMatchingEngine::new(Arc::new(front), Arc::new(back))
+    }
+
+    #[test]
+    fn test_implied_quote_from_best_levels() {
+        let engine = engine();
+        let quote = engine.implied_quote();
+
+        // Sell front at 100, buy back at 106
+        assert_eq!(
+            quote.bid,
+            Some(ImpliedQuote {
+                price: -6,
+                quantity: 3,
+                front_price: 100,
+                back_price: 106,
+            })
+        );
+        // Buy front at 102, sell back at 104
+        assert_eq!(
+            quote.ask,
+            Some(ImpliedQuote {
+                price: -2,
+                quantity: 5,
+                front_price: 102,
+                back_price: 104,
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_implied_quote_when_a_leg_is_empty() {
+        let front = Arc::new(OrderBook::<()>::new("FRONT"));
+        let back = Arc::new(OrderBook::<()>::new("BACK"));
+        back.add_limit_order(OrderId::new(), 100, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        let engine = ImpliedMatchingEngine::new(front, back);
+
+        assert_eq!(engine.implied_quote().bid, None);
+        assert_eq!(engine.implied_quote().ask, None);
+        assert!(matches!(
+            engine.execute_spread(Side::Buy, 1, None),
+            Err(OrderBookError::InsufficientLiquidity { .. })
+        ));
+    }
+
+    #[test]
+    fn test_buying_the_spread_trades_both_legs() {
+        let engine = engine();
+
+        let execution = engine.execute_spread(Side::Buy, 4, Some(-2)).unwrap();
+
+        assert_eq!(execution.price, -2);
+        assert_eq!(execution.quantity, 4);
+        assert_eq!(execution.unhedged_quantity(), 0);
+        assert_eq!(execution.front.transactions.as_vec()[0].price, 102);
+        assert_eq!(execution.back.transactions.as_vec()[0].price, 104);
+        assert_eq!(engine.front().get_volume_by_price().1.get(&102), Some(&1));
+        assert_eq!(engine.back().get_volume_by_price().0.get(&104), Some(&4));
+    }
+
+    #[test]
+    fn test_execution_is_capped_by_the_thinner_leg() {
+        let engine = engine();
+
+        let execution = engine.execute_spread(Side::Sell, 10, None).unwrap();
+
+        assert_eq!(execution.quantity, 3);
+        assert_eq!(engine.back().best_ask(), Some(107));
+        assert_eq!(engine.front().get_volume_by_price().0.get(&100), Some(&7));
+        // The next implied bid comes from the next back level
+        assert_eq!(engine.implied_quote().bid.unwrap().price, -7);
+    }
+
+    #[test]
+    fn test_limit_price_is_respected() {
+        let engine = engine();
+
+        assert!(engine.execute_spread(Side::Buy, 1, Some(-3)).is_err());
+        assert!(engine.execute_spread(Side::Sell, 1, Some(-5)).is_err());
+        assert_eq!(engine.front().best_ask(), Some(102));
+        assert_eq!(engine.back().best_ask(), Some(106));
+    }
+}
@@ -0,0 +1,150 @@
+//! Unit tests for the ITCH parser of the `itch` feature.
+
+#[cfg(all(test, feature = "itch"))]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::feed::FeedMessage;
+    use crate::orderbook::feed::itch::{ItchMessage, ItchReader, parse_message};
+    use pricelevel::{OrderId, Side};
+
+    fn header(message_type: u8, locate: u16, timestamp: u64) -> Vec<u8> {
+        let mut bytes = vec![message_type];
+        bytes.extend_from_slice(&locate.to_be_bytes());
+        bytes.extend_from_slice(&0u16.to_be_bytes());
+        bytes.extend_from_slice(&timestamp.to_be_bytes()[2..]);
+        bytes
+    }
+
+    fn stock(name: &str) -> [u8; 8] {
+        let mut padded = [b' '; 8];
+        padded[..name.len()].copy_from_slice(name.as_bytes());
+        padded
+    }
+
+    fn directory(locate: u16, name: &str) -> Vec<u8> {
+        let mut bytes = header(b'R', locate, 0);
+        bytes.extend_from_slice(&stock(name));
+        bytes.resize(39, 0);
+        bytes
+    }
+
+    fn add(locate: u16, reference: u64, side: u8, shares: u32, name: &str, price: u32) -> Vec<u8> {
+        let mut bytes = header(b'A', locate, 1_000);
+        bytes.extend_from_slice(&reference.to_be_bytes());
+        bytes.push(side);
+        bytes.extend_from_slice(&shares.to_be_bytes());
+        bytes.extend_from_slice(&stock(name));
+        bytes.extend_from_slice(&price.to_be_bytes());
+        bytes
+    }
+
+    fn executed(locate: u16, reference: u64, shares: u32) -> Vec<u8> {
+        let mut bytes = header(b'E', locate, 2_000);
+        bytes.extend_from_slice(&reference.to_be_bytes());
+        bytes.extend_from_slice(&shares.to_be_bytes());
+        bytes.extend_from_slice(&7u64.to_be_bytes());
+        bytes
+    }
+
+    fn replace(
+        locate: u16,
+        reference: u64,
+        new_reference: u64,
+        shares: u32,
+        price: u32,
+    ) -> Vec<u8> {
+        let mut bytes = header(b'U', locate, 3_000);
+        bytes.extend_from_slice(&reference.to_be_bytes());
+        bytes.extend_from_slice(&new_reference.to_be_bytes());
+        bytes.extend_from_slice(&shares.to_be_bytes());
+        bytes.extend_from_slice(&price.to_be_bytes());
+        bytes
+    }
+
+    fn delete(locate: u16, reference: u64) -> Vec<u8> {
+        let mut bytes = header(b'D', locate, 4_000);
+        bytes.extend_from_slice(&reference.to_be_bytes());
+        bytes
+    }
+
+    fn framed(messages: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for message in messages {
+            data.extend_from_slice(&(message.len() as u16).to_be_bytes());
+            data.extend_from_slice(message);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_add_message() {
+        let message = parse_message(&add(5, 42, b'S', 300, "AAPL", 1_502_500))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message,
+            ItchMessage::Order {
+                stock_locate: 5,
+                timestamp: 1_000,
+                stock: Some("AAPL".to_string()),
+                message: FeedMessage::Add {
+                    order_id: OrderId::from_u64(42),
+                    side: Side::Sell,
+                    price: 1_502_500,
+                    quantity: 300,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_short_and_skips_unknown_messages() {
+        let mut short = executed(1, 1, 1);
+        short.truncate(20);
+        assert!(matches!(
+            parse_message(&short),
+            Err(OrderBookError::SerializationError { .. })
+        ));
+        assert!(matches!(parse_message(b"S\0\0"), Ok(None)));
+        let mut bad_side = add(1, 1, b'Z', 1, "AAPL", 1);
+        bad_side[19] = b'Z';
+        assert!(parse_message(&bad_side).is_err());
+    }
+
+    #[test]
+    fn test_reader_stops_at_a_truncated_frame() {
+        let mut data = framed(&[delete(1, 1), vec![b'S'; 12]]);
+        data.extend_from_slice(&[0, 30, b'D']);
+        let messages: Vec<_> = ItchReader::new(&data).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].is_ok());
+        assert!(messages[1].is_err());
+    }
+
+    #[test]
+    fn test_replay_rebuilds_one_symbol() {
+        let data = framed(&[
+            directory(1, "MSFT"),
+            directory(2, "AAPL"),
+            add(1, 10, b'B', 100, "MSFT", 4_000_000),
+            add(2, 20, b'B', 100, "AAPL", 1_500_000),
+            add(2, 21, b'S', 200, "AAPL", 1_501_000),
+            executed(2, 21, 50),
+            replace(2, 20, 22, 80, 1_499_000),
+            delete(1, 10),
+        ]);
+        let book: OrderBook<()> = OrderBook::new("AAPL");
+
+        assert_eq!(book.replay_itch(&data, "AAPL").unwrap(), 4);
+        assert_eq!(book.best_bid(), Some(1_499_000));
+        assert_eq!(book.best_ask(), Some(1_501_000));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(21))
+                .unwrap()
+                .visible_quantity(),
+            150
+        );
+        assert!(book.get_order(OrderId::from_u64(10)).is_none());
+    }
+}
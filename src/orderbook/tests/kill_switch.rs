@@ -0,0 +1,196 @@
+//! Unit tests for the kill switch.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::kill_switch::KillScope;
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::thread;
+
+    fn limit(id: u64, price: u64, side: Side) -> OrderType<()> {
+        OrderType::Standard {
+            id: OrderId::from_u64(id),
+            price,
+            quantity: 10,
+            side,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        }
+    }
+
+    fn book_with_accounts() -> OrderBook {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order_for_account(limit(1, 100, Side::Buy), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(2, 99, Side::Buy), "alice")
+            .unwrap();
+        book.add_order_for_account(limit(3, 101, Side::Sell), "bob")
+            .unwrap();
+        book.add_order(limit(4, 102, Side::Sell)).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_account_kill_cancels_and_refuses_its_orders_only() {
+        let book = book_with_accounts();
+
+        let report = book.kill_switch(KillScope::Account("alice".to_string()));
+        assert_eq!(report.scope, KillScope::Account("alice".to_string()));
+        let mut cancelled: Vec<OrderId> = report.cancelled.iter().map(|order| order.id()).collect();
+        cancelled.sort_unstable_by_key(|id| id.to_string());
+        assert_eq!(cancelled, vec![OrderId::from_u64(1), OrderId::from_u64(2)]);
+        assert_eq!(book.get_all_orders().len(), 2);
+
+        assert!(book.is_killed(Some("alice")));
+        assert!(!book.is_killed(Some("bob")));
+        assert!(matches!(
+            book.add_order_for_account(limit(5, 98, Side::Buy), "alice"),
+            Err(OrderBookError::KillSwitchActive {
+                account_id: Some(account_id)
+            }) if account_id == "alice"
+        ));
+        book.add_order_for_account(limit(6, 98, Side::Buy), "bob")
+            .unwrap();
+        book.add_order(limit(7, 97, Side::Buy)).unwrap();
+
+        assert!(book.release_kill_switch(&KillScope::Account("alice".to_string())));
+        assert!(!book.release_kill_switch(&KillScope::Account("alice".to_string())));
+        book.add_order_for_account(limit(5, 98, Side::Buy), "alice")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_book_kill_cancels_everything_and_refuses_all_entry() {
+        let book = book_with_accounts();
+
+        let report = book.kill_switch(KillScope::Book);
+        assert_eq!(report.cancelled.len(), 4);
+        assert_eq!(book.get_all_orders().len(), 0);
+        assert!(book.is_killed(None));
+        assert!(book.is_killed(Some("carol")));
+
+        assert!(matches!(
+            book.add_order(limit(5, 100, Side::Buy)),
+            Err(OrderBookError::KillSwitchActive { account_id: None })
+        ));
+        assert!(matches!(
+            book.add_order_for_account(limit(6, 100, Side::Buy), "bob"),
+            Err(OrderBookError::KillSwitchActive { account_id: None })
+        ));
+        assert!(matches!(
+            book.submit_market_order(OrderId::from_u64(7), 1, Side::Buy),
+            Err(OrderBookError::KillSwitchActive { account_id: None })
+        ));
+
+        assert!(book.release_kill_switch(&KillScope::Book));
+        book.add_order(limit(5, 100, Side::Buy)).unwrap();
+    }
+
+    #[test]
+    fn test_book_release_keeps_account_switches() {
+        let book = book_with_accounts();
+        book.kill_switch(KillScope::Account("bob".to_string()));
+        book.kill_switch(KillScope::Book);
+        book.release_kill_switch(&KillScope::Book);
+
+        assert!(!book.is_killed(None));
+        assert!(book.is_killed(Some("bob")));
+    }
+
+    #[test]
+    fn test_market_orders_are_refused_for_killed_accounts() {
+        let book = book_with_accounts();
+        book.kill_switch(KillScope::Account("bob".to_string()));
+        assert!(matches!(
+            book.submit_market_order_for_account(OrderId::from_u64(10), 5, Side::Buy, "bob"),
+            Err(OrderBookError::KillSwitchActive { .. })
+        ));
+        let result = book
+            .submit_market_order_for_account(OrderId::from_u64(11), 5, Side::Buy, "alice")
+            .unwrap();
+        assert_eq!(result.executed_quantity(), 5);
+    }
+
+    #[test]
+    fn test_book_kill_empties_dark_and_special_price_orders() {
+        let book = book_with_accounts();
+        book.add_dark_order(OrderId::from_u64(20), Side::Buy, 10, 0, Some(90))
+            .unwrap();
+        book.add_special_price_order(OrderId::from_u64(21), Side::Sell, 0, 10)
+            .unwrap();
+
+        // Dark and special price orders have no account
+        let report = book.kill_switch(KillScope::Account("alice".to_string()));
+        assert!(report.dark_cancelled.is_empty());
+        assert!(report.special_price_cancelled.is_empty());
+
+        let report = book.kill_switch(KillScope::Book);
+        assert_eq!(report.dark_cancelled.len(), 1);
+        assert_eq!(report.special_price_cancelled.len(), 1);
+        assert!(book.dark_orders().is_empty());
+        assert!(book.special_price_orders().is_empty());
+        assert!(matches!(
+            book.add_special_price_order(OrderId::from_u64(22), Side::Sell, 0, 10),
+            Err(OrderBookError::KillSwitchActive { account_id: None })
+        ));
+    }
+
+    #[test]
+    fn test_book_kill_leaves_nothing_resting_behind_concurrent_entry() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        let next_id = Arc::new(AtomicU64::new(1));
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let book = Arc::clone(&book);
+                let next_id = Arc::clone(&next_id);
+                thread::spawn(move || {
+                    loop {
+                        let id = next_id.fetch_add(1, Ordering::Relaxed);
+                        let price = 100 + id % 50;
+                        if book.add_order(limit(id, price, Side::Buy)).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+        while next_id.load(Ordering::Relaxed) < 1_000 {
+            thread::yield_now();
+        }
+
+        book.kill_switch(KillScope::Book);
+        assert!(book.get_all_orders().is_empty());
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert!(book.get_all_orders().is_empty());
+    }
+
+    #[test]
+    fn test_kill_switch_thrown_by_a_listener_returns() {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        let shared = Arc::new(std::sync::OnceLock::<std::sync::Weak<OrderBook>>::new());
+        let handle = Arc::clone(&shared);
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(_) = event
+                && let Some(book) = handle.get().and_then(std::sync::Weak::upgrade)
+            {
+                book.kill_switch(KillScope::Book);
+            }
+        }));
+        let book = Arc::new(book);
+        shared.set(Arc::downgrade(&book)).unwrap();
+
+        book.add_order(limit(1, 100, Side::Sell)).unwrap();
+        book.add_order(limit(2, 101, Side::Sell)).unwrap();
+        book.submit_market_order(OrderId::from_u64(3), 5, Side::Buy)
+            .unwrap();
+        assert!(book.is_killed(None));
+        assert!(book.get_all_orders().is_empty());
+    }
+}
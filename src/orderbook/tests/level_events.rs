@@ -0,0 +1,115 @@
+//! Unit tests for the events published as price levels appear and disappear.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::{LevelChange, OrderBookEvent};
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side};
+    use std::sync::{Arc, Mutex};
+
+    type Recorded = Arc<Mutex<Vec<String>>>;
+
+    fn book_with_recorder() -> (OrderBook, Recorded) {
+        let mut book: OrderBook = OrderBook::new("TEST");
+        let recorded: Recorded = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&recorded);
+        book.set_event_listener(Arc::new(move |event| {
+            let entry = match event {
+                OrderBookEvent::LevelAdded(LevelChange { side, price }) => {
+                    format!("added {side:?} {price}")
+                }
+                OrderBookEvent::LevelRemoved(LevelChange { side, price }) => {
+                    format!("removed {side:?} {price}")
+                }
+                OrderBookEvent::OrderAccepted(accepted) => format!("accept {}", accepted.order_id),
+                OrderBookEvent::OrderCancelled(cancel) => format!("cancel {}", cancel.order_id),
+                _ => return,
+            };
+            sink.lock().unwrap().push(entry);
+        }));
+        (book, recorded)
+    }
+
+    fn take(recorded: &Recorded) -> Vec<String> {
+        std::mem::take(&mut *recorded.lock().unwrap())
+    }
+
+    #[test]
+    fn test_only_the_first_order_at_a_price_adds_a_level() {
+        let (book, recorded) = book_with_recorder();
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 100, 5, Side::Buy);
+        rest(&book, 3, 101, 5, Side::Sell);
+
+        assert_eq!(
+            take(&recorded),
+            vec![
+                "added Buy 100".to_string(),
+                format!("accept {}", OrderId::from_u64(1)),
+                format!("accept {}", OrderId::from_u64(2)),
+                "added Sell 101".to_string(),
+                format!("accept {}", OrderId::from_u64(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancelling_the_last_order_removes_the_level() {
+        let (book, recorded) = book_with_recorder();
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 100, 5, Side::Buy);
+        take(&recorded);
+
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(
+            take(&recorded),
+            vec![format!("cancel {}", OrderId::from_u64(1))]
+        );
+
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+        assert_eq!(
+            take(&recorded),
+            vec![
+                "removed Buy 100".to_string(),
+                format!("cancel {}", OrderId::from_u64(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sweep_removes_every_level_it_empties() {
+        let (book, recorded) = book_with_recorder();
+        rest(&book, 1, 100, 5, Side::Sell);
+        rest(&book, 2, 101, 5, Side::Sell);
+        rest(&book, 3, 102, 5, Side::Sell);
+        take(&recorded);
+
+        book.submit_market_order(OrderId::from_u64(4), 12, Side::Buy)
+            .unwrap();
+        assert_eq!(
+            take(&recorded),
+            vec![
+                "removed Sell 100".to_string(),
+                "removed Sell 101".to_string()
+            ]
+        );
+        assert_eq!(book.best_ask(), Some(102));
+    }
+
+    #[test]
+    fn test_mass_cancel_and_seed_report_levels() {
+        let (book, recorded) = book_with_recorder();
+        book.seed(&[(100, 5, Side::Buy), (100, 5, Side::Buy), (99, 5, Side::Buy)])
+            .unwrap();
+        assert_eq!(
+            take(&recorded),
+            vec!["added Buy 100".to_string(), "added Buy 99".to_string()]
+        );
+
+        book.cancel_all();
+        let events = take(&recorded);
+        assert_eq!(&events[..2], ["removed Buy 100", "removed Buy 99"]);
+        assert_eq!(events.len(), 5);
+    }
+}
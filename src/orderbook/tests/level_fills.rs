@@ -0,0 +1,103 @@
+//! Unit tests for the per-level totals of a match.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::level_fills::LevelFill;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, Side};
+
+    fn book_with_asks() -> OrderBook {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order(limit(1, 100, 5, Side::Sell)).unwrap();
+        book.add_order(limit(2, 100, 5, Side::Sell)).unwrap();
+        book.add_order(limit(3, 101, 10, Side::Sell)).unwrap();
+        book.add_order(limit(4, 102, 10, Side::Sell)).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_sweep_is_aggregated_per_level() {
+        let book = book_with_asks();
+
+        let (result, fills) = book
+            .match_order_aggregated(OrderId::from_u64(10), Side::Buy, 22, None)
+            .unwrap();
+        assert_eq!(result.transactions.as_vec().len(), 4);
+
+        assert_eq!(
+            fills,
+            vec![
+                LevelFill {
+                    price: 100,
+                    quantity: 10,
+                    notional: 1_000,
+                    trades: 2,
+                },
+                LevelFill {
+                    price: 101,
+                    quantity: 10,
+                    notional: 1_010,
+                    trades: 1,
+                },
+                LevelFill {
+                    price: 102,
+                    quantity: 2,
+                    notional: 204,
+                    trades: 1,
+                },
+            ]
+        );
+        let executed: u64 = fills.iter().map(|fill| fill.quantity).sum();
+        assert_eq!(executed, 22 - result.remaining_quantity);
+    }
+
+    #[test]
+    fn test_limit_price_stops_the_aggregated_match() {
+        let book = book_with_asks();
+
+        let (result, fills) = book
+            .match_order_aggregated(OrderId::from_u64(10), Side::Buy, 15, Some(100))
+            .unwrap();
+        assert_eq!(result.remaining_quantity, 5);
+        assert_eq!(
+            fills,
+            vec![LevelFill {
+                price: 100,
+                quantity: 10,
+                notional: 1_000,
+                trades: 2,
+            }]
+        );
+
+        let (result, fills) = book
+            .match_order_aggregated(OrderId::from_u64(11), Side::Buy, 5, Some(100))
+            .unwrap();
+        assert!(result.transactions.as_vec().is_empty());
+        assert!(fills.is_empty());
+    }
+
+    #[test]
+    fn test_aggregated_match_starts_from_the_book_left_by_earlier_matches() {
+        let book = book_with_asks();
+
+        let result = book
+            .match_order(OrderId::from_u64(10), Side::Buy, 12, None)
+            .unwrap();
+        let (_, fills) = book
+            .match_order_aggregated(OrderId::from_u64(11), Side::Buy, 8, None)
+            .unwrap();
+
+        // The second match starts where the first one left the book
+        assert_eq!(result.transactions.as_vec().len(), 3);
+        assert_eq!(
+            fills,
+            vec![LevelFill {
+                price: 101,
+                quantity: 8,
+                notional: 808,
+                trades: 1,
+            }]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Unit tests for ordered price level iteration.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::levels::LevelSummary;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn populated_book() -> OrderBook<()> {
+        let book = OrderBook::new("TEST_SYMBOL");
+        for (price, side) in [
+            (99, Side::Buy),
+            (101, Side::Buy),
+            (100, Side::Buy),
+            (101, Side::Buy),
+            (105, Side::Sell),
+            (103, Side::Sell),
+            (104, Side::Sell),
+        ] {
+            book.add_limit_order(OrderId::new(), price, 10, side, TimeInForce::Gtc, None)
+                .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_bids_descend_and_asks_ascend() {
+        let book = populated_book();
+
+        let bids: Vec<u64> = book.iter_bids().map(|(price, _)| price).collect();
+        let asks: Vec<u64> = book.iter_asks().map(|(price, _)| price).collect();
+
+        assert_eq!(bids, vec![101, 100, 99]);
+        assert_eq!(asks, vec![103, 104, 105]);
+    }
+
+    #[test]
+    fn test_summaries_match_the_snapshot() {
+        let book = populated_book();
+        book.add_iceberg_order(
+            OrderId::new(),
+            104,
+            5,
+            20,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let (price, best_bid) = book.iter_bids().next().unwrap();
+        assert_eq!(price, 101);
+        assert_eq!(
+            best_bid,
+            LevelSummary {
+                visible_quantity: 20,
+                hidden_quantity: 0,
+                order_count: 2,
+            }
+        );
+
+        let snapshot = book.create_snapshot(usize::MAX);
+        for ((price, summary), level) in book.iter_asks().zip(&snapshot.asks) {
+            assert_eq!(price, level.price);
+            assert_eq!(summary.visible_quantity, level.visible_quantity);
+            assert_eq!(summary.total_quantity(), level.total_quantity());
+            assert_eq!(summary.order_count, level.order_count);
+        }
+    }
+
+    #[test]
+    fn test_levels_emptied_while_iterating_are_skipped() {
+        let book = OrderBook::<()>::new("TEST_SYMBOL");
+        let first = OrderId::new();
+        book.add_limit_order(first, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_limit_order(OrderId::new(), 99, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let bids = book.iter_bids();
+        assert_eq!(bids.size_hint(), (0, Some(2)));
+        book.cancel_order(first).unwrap();
+
+        assert_eq!(bids.map(|(price, _)| price).collect::<Vec<_>>(), vec![99]);
+        assert_eq!(OrderBook::<()>::new("EMPTY").iter_asks().next(), None);
+    }
+
+    fn queued_book(count: u64) -> (OrderBook<()>, Vec<OrderId>) {
+        let book = OrderBook::new("TEST_SYMBOL");
+        let ids: Vec<OrderId> = (1..=count).map(OrderId::from_u64).collect();
+        for (quantity, id) in (1..).zip(&ids) {
+            book.add_limit_order(*id, 100, quantity, Side::Sell, TimeInForce::Gtc, None)
+                .unwrap();
+        }
+        (book, ids)
+    }
+
+    #[test]
+    fn test_iter_orders_at_price_streams_in_priority_order() {
+        let (book, ids) = queued_book(5);
+
+        let mut orders = book.iter_orders_at_price(100, Side::Sell);
+        assert_eq!(orders.len(), 5);
+        assert_eq!(orders.next().unwrap().id(), ids[0]);
+        assert_eq!(
+            orders.map(|order| order.id()).collect::<Vec<_>>(),
+            ids[1..].to_vec()
+        );
+        assert_eq!(book.iter_orders_at_price(100, Side::Buy).count(), 0);
+    }
+
+    #[test]
+    fn test_pages_cover_the_level_once() {
+        let (book, ids) = queued_book(5);
+
+        let first = book.get_orders_at_price_page(100, Side::Sell, None, 2);
+        assert_eq!(
+            first
+                .orders
+                .iter()
+                .map(|order| order.id())
+                .collect::<Vec<_>>(),
+            ids[..2].to_vec()
+        );
+        let second = book.get_orders_at_price_page(100, Side::Sell, first.next_cursor, 2);
+        assert_eq!(second.orders[0].id(), ids[2]);
+        let last = book.get_orders_at_price_page(100, Side::Sell, second.next_cursor, 2);
+        assert_eq!(last.orders.len(), 1);
+        assert_eq!(last.orders[0].id(), ids[4]);
+        assert_eq!(last.next_cursor, None);
+
+        let whole = book.get_orders_at_price_page(100, Side::Sell, None, 5);
+        assert_eq!(whole.orders.len(), 5);
+        assert_eq!(whole.next_cursor, None);
+    }
+
+    #[test]
+    fn test_paging_resumes_after_the_cursor_order_leaves() {
+        let (book, ids) = queued_book(4);
+
+        let first = book.get_orders_at_price_page(100, Side::Sell, None, 2);
+        book.cancel_order(ids[1]).unwrap();
+        book.cancel_order(ids[0]).unwrap();
+
+        let rest = book.get_orders_at_price_page(100, Side::Sell, first.next_cursor, 10);
+        assert_eq!(
+            rest.orders
+                .iter()
+                .map(|order| order.id())
+                .collect::<Vec<_>>(),
+            ids[2..].to_vec()
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Loom model tests of the lock-free parts of the modification paths.
+//!
+//! The best price cache and the level ticket locks are compiled a second time
+//! against loom's atomics, through a local `sync` module standing in for
+//! `orderbook::sync`, and every interleaving of small concurrent scenarios is
+//! explored. The order location index and the per-level entries live in
+//! `DashMap`s, which loom cannot instrument, so their protocol is modelled with
+//! loom mutexes around the real ticket locks. Run with
+//! `cargo test --release --features loom loom`.
+
+#[cfg(test)]
+mod sync {
+    pub(crate) use ::loom::sync::Mutex;
+    pub(crate) use ::loom::sync::atomic;
+    pub(crate) use ::loom::{hint, thread};
+}
+
+// Compiling the sources a second time against loom is the point
+#[cfg(test)]
+#[allow(dead_code, clippy::duplicate_mod)]
+#[path = "../cache.rs"]
+mod cache;
+
+#[cfg(test)]
+#[allow(dead_code, clippy::duplicate_mod)]
+#[path = "../ticket.rs"]
+mod ticket;
+
+#[cfg(test)]
+mod tests {
+    use super::cache::{CacheInvalidation, PriceLevelCache};
+    use super::ticket::LevelTickets;
+    use ::loom::sync::atomic::{AtomicU64, Ordering};
+    use ::loom::sync::{Arc, Mutex};
+    use ::loom::thread;
+    use pricelevel::Side;
+    use std::collections::HashMap;
+
+    /// Best bid of the modelled book, 0 when the side is empty
+    struct Book {
+        best_bid: AtomicU64,
+        cache: PriceLevelCache,
+    }
+
+    impl Book {
+        fn new(granularity: CacheInvalidation, best_bid: u64) -> Arc<Self> {
+            let cache = PriceLevelCache::new();
+            cache.set_granularity(granularity);
+            Arc::new(Self {
+                best_bid: AtomicU64::new(best_bid),
+                cache,
+            })
+        }
+
+        /// Compute the best bid the way `OrderBook::best_bid` does on a miss:
+        /// generation first, then the levels, then publish
+        fn recompute(&self) {
+            let generation = self.cache.generation(Side::Buy);
+            let best = self.best_bid.load(Ordering::SeqCst);
+            self.cache
+                .update_best_price(Side::Buy, generation, (best > 0).then_some(best));
+        }
+
+        fn assert_not_stale(&self) {
+            if let Some(cached) = self.cache.get_cached_best(Side::Buy) {
+                assert_eq!(cached, self.best_bid.load(Ordering::SeqCst));
+            }
+        }
+    }
+
+    #[test]
+    fn test_recompute_racing_invalidation_keeps_no_stale_price() {
+        ::loom::model(|| {
+            let book = Book::new(CacheInvalidation::PerSide, 100);
+            let reader = {
+                let book = Arc::clone(&book);
+                thread::spawn(move || book.recompute())
+            };
+            book.best_bid.store(101, Ordering::SeqCst);
+            book.cache.invalidate_side(Side::Buy);
+            reader.join().unwrap();
+            book.assert_not_stale();
+        });
+    }
+
+    #[test]
+    fn test_recompute_racing_better_level_keeps_no_stale_price() {
+        ::loom::model(|| {
+            let book = Book::new(CacheInvalidation::PriceAware, 100);
+            book.recompute();
+            let reader = {
+                let book = Arc::clone(&book);
+                thread::spawn(move || {
+                    book.cache.invalidate();
+                    book.recompute();
+                })
+            };
+            book.best_bid.store(101, Ordering::SeqCst);
+            book.cache.level_added(Side::Buy, 101);
+            reader.join().unwrap();
+            book.assert_not_stale();
+        });
+    }
+
+    #[test]
+    fn test_recompute_racing_best_level_removal_keeps_no_stale_price() {
+        ::loom::model(|| {
+            let book = Book::new(CacheInvalidation::PriceAware, 101);
+            let reader = {
+                let book = Arc::clone(&book);
+                thread::spawn(move || book.recompute())
+            };
+            book.best_bid.store(100, Ordering::SeqCst);
+            book.cache.level_removed(Side::Buy, 101);
+            reader.join().unwrap();
+            book.assert_not_stale();
+        });
+    }
+
+    #[test]
+    fn test_concurrent_recomputes_converge() {
+        // Three threads are out of reach exhaustively, bound the preemptions
+        let mut builder = ::loom::model::Builder::new();
+        builder.preemption_bound = Some(3);
+        builder.check(|| {
+            let book = Book::new(CacheInvalidation::Wholesale, 100);
+            let readers: Vec<_> = (0..2)
+                .map(|_| {
+                    let book = Arc::clone(&book);
+                    thread::spawn(move || book.recompute())
+                })
+                .collect();
+            book.best_bid.store(0, Ordering::SeqCst);
+            book.cache.levels_removed(Side::Buy, &[100]);
+            for reader in readers {
+                reader.join().unwrap();
+            }
+            book.assert_not_stale();
+        });
+    }
+
+    #[test]
+    fn test_ticket_lock_excludes_and_admits_everyone() {
+        ::loom::model(|| {
+            let tickets = Arc::new(LevelTickets::default());
+            // A read-modify-write split in two, so overlapping holders lose an increment
+            let counter = Arc::new(AtomicU64::new(0));
+            let workers: Vec<_> = (0..2)
+                .map(|_| {
+                    let tickets = Arc::clone(&tickets);
+                    let counter = Arc::clone(&counter);
+                    thread::spawn(move || {
+                        let _guard = tickets.lock(Side::Sell, 100);
+                        let value = counter.load(Ordering::Relaxed);
+                        counter.store(value + 1, Ordering::Relaxed);
+                    })
+                })
+                .collect();
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            let _guard = tickets.lock(Side::Sell, 100);
+            assert_eq!(counter.load(Ordering::Relaxed), 2);
+        });
+    }
+
+    /// Order location index and one price level, mirroring the `DashMap`
+    /// entries `update_resting_quantity` and `cancel_order` touch
+    struct Level {
+        locations: Mutex<HashMap<u64, u64>>,
+        orders: Mutex<Vec<(u64, u64)>>,
+        /// Aggregate quantity the level keeps next to its orders
+        quantity: AtomicU64,
+        tickets: LevelTickets,
+    }
+
+    const PRICE: u64 = 100;
+
+    impl Level {
+        fn new(orders: &[(u64, u64)]) -> Arc<Self> {
+            Arc::new(Self {
+                locations: Mutex::new(orders.iter().map(|&(id, _)| (id, PRICE)).collect()),
+                orders: Mutex::new(orders.to_vec()),
+                quantity: AtomicU64::new(orders.iter().map(|&(_, qty)| qty).sum()),
+                tickets: LevelTickets::default(),
+            })
+        }
+
+        /// Lookup, ticket, lookup again, then modify under the level entry
+        fn update_quantity(&self, id: u64, new_quantity: u64) -> bool {
+            let Some(price) = self.locations.lock().unwrap().get(&id).copied() else {
+                return false;
+            };
+            let _ticket = self.tickets.lock(Side::Buy, price);
+            if self.locations.lock().unwrap().get(&id) != Some(&price) {
+                return false;
+            }
+            let mut orders = self.orders.lock().unwrap();
+            let Some(order) = orders.iter_mut().find(|(order_id, _)| *order_id == id) else {
+                return false;
+            };
+            let old = std::mem::replace(&mut order.1, new_quantity);
+            self.quantity.fetch_add(new_quantity, Ordering::SeqCst);
+            self.quantity.fetch_sub(old, Ordering::SeqCst);
+            true
+        }
+
+        /// Remove from the level entry first, then forget the location
+        fn cancel(&self, id: u64) -> Option<u64> {
+            let removed = {
+                let mut orders = self.orders.lock().unwrap();
+                let position = orders.iter().position(|(order_id, _)| *order_id == id)?;
+                let (_, quantity) = orders.remove(position);
+                self.quantity.fetch_sub(quantity, Ordering::SeqCst);
+                quantity
+            };
+            self.locations.lock().unwrap().remove(&id);
+            Some(removed)
+        }
+
+        fn quantity_of(&self, id: u64) -> Option<u64> {
+            let orders = self.orders.lock().unwrap();
+            orders
+                .iter()
+                .find(|(order_id, _)| *order_id == id)
+                .map(|&(_, quantity)| quantity)
+        }
+
+        fn assert_aggregate(&self) {
+            let orders = self.orders.lock().unwrap();
+            let total: u64 = orders.iter().map(|&(_, quantity)| quantity).sum();
+            assert_eq!(self.quantity.load(Ordering::SeqCst), total);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_quantity_updates_are_not_lost() {
+        ::loom::model(|| {
+            let level = Level::new(&[(1, 10), (2, 10)]);
+            let updaters: Vec<_> = [(1, 4), (2, 7)]
+                .into_iter()
+                .map(|(id, quantity)| {
+                    let level = Arc::clone(&level);
+                    thread::spawn(move || level.update_quantity(id, quantity))
+                })
+                .collect();
+            for updater in updaters {
+                assert!(updater.join().unwrap());
+            }
+            assert_eq!(level.quantity_of(1), Some(4));
+            assert_eq!(level.quantity_of(2), Some(7));
+            level.assert_aggregate();
+        });
+    }
+
+    #[test]
+    fn test_quantity_update_racing_cancel_stays_consistent() {
+        ::loom::model(|| {
+            let level = Level::new(&[(1, 10), (2, 10)]);
+            let updater = {
+                let level = Arc::clone(&level);
+                thread::spawn(move || level.update_quantity(1, 4))
+            };
+            let cancelled = level.cancel(1);
+            let updated = updater.join().unwrap();
+
+            // The cancel sees the quantity the update left, if it ran first
+            match (updated, cancelled) {
+                (true, Some(quantity)) => assert_eq!(quantity, 4),
+                (false, Some(quantity)) => assert_eq!(quantity, 10),
+                (_, None) => panic!("the order was resting when cancelled"),
+            }
+            assert_eq!(level.quantity_of(1), None);
+            assert!(level.locations.lock().unwrap().get(&1).is_none());
+            level.assert_aggregate();
+        });
+    }
+}
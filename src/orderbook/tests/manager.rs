@@ -0,0 +1,143 @@
+//! Unit tests for the multi-book manager.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::manager::OrderBookManager;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_add_and_get_books() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        assert!(manager.is_empty());
+
+        let btc = manager.add_book("BTC");
+        manager.add_book("ETH");
+        assert!(Arc::ptr_eq(&btc, &manager.add_book("BTC")));
+        assert_eq!(manager.len(), 2);
+        assert_eq!(manager.symbols(), vec!["BTC", "ETH"]);
+
+        assert!(manager.remove_book("ETH").is_some());
+        assert!(manager.get_book("ETH").is_none());
+        assert!(manager.with_book("ETH", |book| book.version()).is_none());
+    }
+
+    #[test]
+    fn test_version_tracks_changes() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        let book = manager.add_book("BTC");
+        assert_eq!(book.version(), 0);
+
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        assert_eq!(book.version(), 1);
+        book.match_order(OrderId::from_u64(2), Side::Buy, 5, None)
+            .unwrap();
+        assert_eq!(book.version(), 2);
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(book.version(), 3);
+
+        // Nothing changes when the order is unknown
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(book.version(), 3);
+    }
+
+    #[test]
+    fn test_snapshot_all_selected_symbols() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        for symbol in ["BTC", "ETH", "SOL"] {
+            manager.add_book(symbol);
+        }
+        manager
+            .with_book("BTC", |book| {
+                book.add_limit_order(
+                    OrderId::from_u64(1),
+                    100,
+                    10,
+                    Side::Buy,
+                    TimeInForce::Gtc,
+                    None,
+                )
+            })
+            .unwrap()
+            .unwrap();
+
+        let snapshot = manager.snapshot_all(&["BTC", "ETH"], 5).unwrap();
+        assert_eq!(snapshot.books.len(), 2);
+        assert_eq!(snapshot.get("BTC").unwrap().best_bid(), Some((100, 10)));
+        assert_eq!(snapshot.books["BTC"].version, 1);
+        assert!(snapshot.get("SOL").is_none());
+
+        assert_eq!(manager.snapshot_all(&[], 5).unwrap().books.len(), 3);
+        assert!(manager.snapshot_all(&["XRP"], 5).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_all_is_consistent_across_books() {
+        // A writer keeps moving a single order between two books under the
+        // manager, so every consistent snapshot sees exactly one of them
+        let manager: Arc<OrderBookManager> = Arc::new(OrderBookManager::new());
+        manager.add_book("A");
+        manager.add_book("B");
+        manager.with_book("A", |book| {
+            book.add_limit_order(
+                OrderId::from_u64(0),
+                100,
+                1,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        });
+
+        let running = Arc::new(AtomicBool::new(true));
+        let writer = {
+            let manager = Arc::clone(&manager);
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let mut id = 1;
+                while running.load(Ordering::Relaxed) {
+                    let (from, to) = if id % 2 == 1 { ("A", "B") } else { ("B", "A") };
+                    let target = manager.get_book(to).unwrap();
+                    manager.with_book(from, |book| {
+                        book.cancel_order(OrderId::from_u64(id - 1)).unwrap();
+                        target
+                            .add_limit_order(
+                                OrderId::from_u64(id),
+                                100,
+                                1,
+                                Side::Buy,
+                                TimeInForce::Gtc,
+                                None,
+                            )
+                            .unwrap();
+                    });
+                    id += 1;
+                }
+            })
+        };
+
+        for _ in 0..200 {
+            let snapshot = manager.snapshot_all(&["A", "B"], 1).unwrap();
+            let total: u64 = snapshot
+                .books
+                .values()
+                .map(|book| book.snapshot.total_bid_volume())
+                .sum();
+            assert_eq!(total, 1);
+        }
+
+        running.store(false, Ordering::Relaxed);
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+//! Unit tests for market-to-limit orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::session::{SessionSchedule, TradingState};
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+    use std::sync::Arc;
+
+    fn market_to_limit(id: u64, quantity: u64, side: Side) -> OrderType<()> {
+        OrderType::MarketToLimit {
+            id: OrderId::from_u64(id),
+            price: 0,
+            quantity,
+            side,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        }
+    }
+
+    fn book_with_asks() -> OrderBook {
+        let book: OrderBook = OrderBook::new("TEST");
+        for (id, price) in [(1, 100), (2, 100), (3, 101)] {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_partial_fill_rests_remainder_at_execution_price() {
+        let book = book_with_asks();
+        let rested = book.add_order(market_to_limit(10, 25, Side::Buy)).unwrap();
+
+        // Only the best level trades, the next one is left alone
+        assert_eq!(book.get_orders_at_price(100, Side::Sell).len(), 0);
+        assert_eq!(book.best_ask(), Some(101));
+        assert_eq!(book.get_orders_at_price(101, Side::Sell).len(), 1);
+
+        // The remainder is a plain limit order at the execution price
+        assert!(matches!(*rested, OrderType::Standard { price: 100, .. }));
+        assert_eq!(book.best_bid(), Some(100));
+        let resting = book.get_order(OrderId::from_u64(10)).unwrap();
+        assert!(matches!(*resting, OrderType::Standard { .. }));
+        assert_eq!(resting.visible_quantity(), 5);
+        assert_eq!(book.executed_so_far(OrderId::from_u64(10)), Some(20));
+    }
+
+    #[test]
+    fn test_partial_fill_within_level_leaves_nothing_resting() {
+        let book = book_with_asks();
+        book.add_order(market_to_limit(10, 15, Side::Buy)).unwrap();
+        assert!(book.get_order(OrderId::from_u64(10)).is_none());
+        assert_eq!(book.best_ask(), Some(100));
+        let queue = book.get_orders_at_price(100, Side::Sell);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].visible_quantity(), 5);
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_immediate_market_to_limit_does_not_rest() {
+        let book = book_with_asks();
+        let mut order = market_to_limit(10, 25, Side::Buy);
+        if let OrderType::MarketToLimit { time_in_force, .. } = &mut order {
+            *time_in_force = TimeInForce::Ioc;
+        }
+        assert!(matches!(
+            book.add_order(order),
+            Err(OrderBookError::InsufficientLiquidity { available: 20, .. })
+        ));
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), Some(101));
+    }
+
+    #[test]
+    fn test_market_to_limit_needs_an_opposite_level() {
+        let book = book_with_asks();
+        assert!(matches!(
+            book.add_order(market_to_limit(10, 5, Side::Sell)),
+            Err(OrderBookError::InsufficientLiquidity { available: 0, .. })
+        ));
+        assert!(book.get_order(OrderId::from_u64(10)).is_none());
+    }
+
+    #[test]
+    fn test_market_to_limit_rejected_during_auction_call() {
+        let clock = Arc::new(ManualClock::new(1_500));
+        let mut book: OrderBook = OrderBook::new("TEST");
+        book.set_clock(clock);
+        book.set_session_schedule(SessionSchedule::new(1_000, 2_000, 9_000));
+        book.advance_session();
+        assert!(matches!(
+            book.add_order(market_to_limit(10, 5, Side::Buy)),
+            Err(OrderBookError::InvalidTradingState {
+                state: TradingState::PreOpen
+            })
+        ));
+    }
+}
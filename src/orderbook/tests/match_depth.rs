@@ -0,0 +1,109 @@
+//! Unit tests for the maximum match depth of aggressive orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::options::{DepthLimitAction, MatchDepthLimit};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    /// Asks of 10 at 101, 102, 103 and 104
+    fn book_with_asks() -> OrderBook<()> {
+        let book = OrderBook::new("TEST");
+        for id in 1..=4 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100 + id,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book
+    }
+
+    fn buy(book: &OrderBook<()>, id: u64, price: u64, quantity: u64, tif: TimeInForce) {
+        book.add_limit_order(OrderId::from_u64(id), price, quantity, Side::Buy, tif, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_remainder_beyond_depth_is_cancelled() {
+        let book = book_with_asks();
+        book.set_max_match_depth(Some(MatchDepthLimit::new(2)));
+
+        buy(&book, 10, 104, 35, TimeInForce::Gtc);
+        assert_eq!(book.best_ask(), Some(103));
+        assert_eq!(book.best_bid(), None);
+        assert!(book.get_order(OrderId::from_u64(10)).is_none());
+        assert_eq!(book.executed_so_far(OrderId::from_u64(3)), Some(0));
+    }
+
+    #[test]
+    fn test_remainder_beyond_depth_rests_at_deepest_level() {
+        let book = book_with_asks();
+        book.set_max_match_depth(Some(
+            MatchDepthLimit::new(2).with_action(DepthLimitAction::Rest),
+        ));
+
+        buy(&book, 10, 104, 35, TimeInForce::Gtc);
+        let rested = book.get_order(OrderId::from_u64(10)).unwrap();
+        assert_eq!(rested.price(), 102);
+        assert_eq!(rested.visible_quantity(), 15);
+        assert_eq!(book.best_bid(), Some(102));
+        assert_eq!(book.best_ask(), Some(103));
+    }
+
+    #[test]
+    fn test_order_within_depth_is_unaffected() {
+        let book = book_with_asks();
+        book.set_max_match_depth(Some(MatchDepthLimit::new(2)));
+
+        // Its own limit stops it before the depth limit does
+        buy(&book, 10, 101, 15, TimeInForce::Gtc);
+        let rested = book.get_order(OrderId::from_u64(10)).unwrap();
+        assert_eq!(rested.price(), 101);
+        assert_eq!(rested.visible_quantity(), 5);
+    }
+
+    #[test]
+    fn test_fill_or_kill_counts_only_reachable_levels() {
+        let book = book_with_asks();
+        book.set_max_match_depth(Some(MatchDepthLimit::new(2)));
+
+        let result = book.add_limit_order(
+            OrderId::from_u64(10),
+            104,
+            25,
+            Side::Buy,
+            TimeInForce::Fok,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InsufficientLiquidity { available: 20, .. })
+        ));
+        assert_eq!(book.best_ask(), Some(101));
+    }
+
+    #[test]
+    fn test_market_order_stops_at_depth() {
+        let book = book_with_asks();
+        book.set_max_match_depth(Some(MatchDepthLimit::new(3)));
+
+        let result = book
+            .submit_market_order(OrderId::from_u64(10), 100, Side::Buy)
+            .unwrap();
+        assert_eq!(result.executed_quantity(), 30);
+        assert_eq!(result.remaining_quantity, 70);
+        assert_eq!(book.best_ask(), Some(104));
+
+        book.set_max_match_depth(None);
+        let result = book
+            .submit_market_order(OrderId::from_u64(11), 100, Side::Buy)
+            .unwrap();
+        assert_eq!(result.executed_quantity(), 10);
+    }
+}
@@ -4,6 +4,7 @@
 mod tests {
     use crate::orderbook::OrderBookError;
     use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
     use pricelevel::{OrderId, OrderType, Side, TimeInForce};
 
     // Helper function to create a new order book for testing.
@@ -112,10 +113,10 @@ mod tests {
 
     #[test]
     fn test_match_across_multiple_price_levels() {
-        let book = setup_book();
-        add_limit_order(&book, Side::Sell, 100, 20);
-        add_limit_order(&book, Side::Sell, 101, 30);
-        add_limit_order(&book, Side::Sell, 102, 40);
+        let book: OrderBook = BookBuilder::new("TEST_SYMBOL")
+            .asks([(100, 20), (101, 30), (102, 40)])
+            .build()
+            .unwrap();
 
         let taker_order_id = OrderId::new();
         // Market order to buy 70 shares, should consume the first two levels and part of the third
@@ -184,4 +185,127 @@ mod tests {
         let matched_quantity = book.peek_match(Side::Buy, 10, None);
         assert_eq!(matched_quantity, 0);
     }
+
+    #[test]
+    fn test_executed_so_far_tracks_aggressor_and_maker_fills() {
+        let book: OrderBook<()> = BookBuilder::new("TEST").ask(100, 4).build().unwrap();
+        let aggressor = OrderId::from_u64(10);
+        book.add_limit_order(aggressor, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        // 4 executed on entry, 6 rest
+        assert_eq!(book.executed_so_far(aggressor), Some(4));
+        assert_eq!(book.executed_so_far(OrderId::from_u64(1)), None);
+
+        book.submit_market_order(OrderId::from_u64(11), 3, Side::Sell)
+            .unwrap();
+        assert_eq!(book.executed_so_far(aggressor), Some(7));
+        let snapshot = book.create_l3_snapshot(1);
+        assert_eq!(snapshot.bids[0].orders[0].executed_quantity, 7);
+        assert_eq!(snapshot.bids[0].orders[0].visible_quantity, 3);
+
+        book.cancel_order(aggressor).unwrap();
+        assert_eq!(book.executed_so_far(aggressor), None);
+    }
+
+    #[test]
+    fn test_executed_so_far_is_zero_for_untouched_orders() {
+        let book: OrderBook<()> = BookBuilder::new("TEST").bid(100, 5).build().unwrap();
+        assert_eq!(book.executed_so_far(OrderId::from_u64(1)), Some(0));
+
+        book.submit_market_order(OrderId::from_u64(2), 5, Side::Sell)
+            .unwrap();
+        assert_eq!(book.executed_so_far(OrderId::from_u64(1)), None);
+        assert_eq!(book.execution_state(OrderId::from_u64(1)), None);
+    }
+}
+
+#[cfg(test)]
+mod test_execution_state {
+    use crate::orderbook::execution::ExecutionState;
+    use crate::{BookBuilder, OrderBook};
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+    #[test]
+    fn test_execution_state_of_an_untouched_order() {
+        let book: OrderBook<()> = BookBuilder::new("TEST").bid(100, 5).build().unwrap();
+
+        let state = book.execution_state(OrderId::from_u64(1)).unwrap();
+        assert_eq!(
+            state,
+            ExecutionState {
+                original_quantity: 5,
+                executed_quantity: 0,
+                executed_value: 0,
+            }
+        );
+        assert_eq!(state.average_price(), None);
+    }
+
+    #[test]
+    fn test_execution_state_accumulates_entry_and_maker_fills() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            4,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(2),
+            102,
+            2,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        // Takes both asks on entry and rests the remaining 4
+        let bid = OrderId::from_u64(3);
+        book.add_limit_order(bid, 102, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        let state = book.execution_state(bid).unwrap();
+        assert_eq!(state.original_quantity, 10);
+        assert_eq!(state.executed_quantity, 6);
+        assert_eq!(state.executed_value, 4 * 100 + 2 * 102);
+
+        // Then trades as a maker
+        book.submit_market_order(OrderId::from_u64(4), 2, Side::Sell)
+            .unwrap();
+        let state = book.execution_state(bid).unwrap();
+        assert_eq!(state.executed_quantity, 8);
+        assert_eq!(state.executed_value, 4 * 100 + 4 * 102);
+        let average = state.average_price().unwrap();
+        assert!((average - 101.0).abs() < 1e-9);
+
+        // Amending the quantity keeps the state
+        book.update_order(OrderUpdate::UpdateQuantity {
+            order_id: bid,
+            new_quantity: 5,
+        })
+        .unwrap();
+        assert_eq!(book.execution_state(bid).unwrap().original_quantity, 10);
+
+        book.submit_market_order(OrderId::from_u64(5), 5, Side::Sell)
+            .unwrap();
+        assert_eq!(book.execution_state(bid), None);
+    }
+
+    #[test]
+    fn test_execution_state_dropped_on_cancel() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let id = OrderId::from_u64(1);
+        book.add_limit_order(id, 100, 4, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.submit_market_order(OrderId::from_u64(2), 1, Side::Buy)
+            .unwrap();
+        assert_eq!(book.execution_state(id).unwrap().executed_quantity, 1);
+
+        book.cancel_order(id).unwrap();
+        assert_eq!(book.execution_state(id), None);
+    }
 }
@@ -0,0 +1,121 @@
+//! Unit tests for the memory usage breakdown of the book.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use pricelevel::{OrderId, PriceLevel, Side, TimeInForce};
+    use std::sync::Arc;
+
+    fn add(book: &OrderBook<()>, id: u64, price: u64, side: Side) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            10,
+            side,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_memory_stats_counts_levels_and_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        add(&book, 1, 100, Side::Buy);
+        add(&book, 2, 100, Side::Buy);
+        add(&book, 3, 99, Side::Buy);
+        add(&book, 4, 101, Side::Sell);
+
+        let stats = book.memory_stats();
+        assert_eq!(stats.bids.levels, 2);
+        assert_eq!(stats.bids.orders, 3);
+        assert_eq!(stats.asks.levels, 1);
+        assert_eq!(stats.asks.orders, 1);
+        assert_eq!(stats.order_locations.entries, 4);
+        assert_eq!(stats.executions.entries, 4);
+        assert!(stats.bids.bytes > stats.asks.bytes);
+        assert!(stats.order_locations.bytes > 0);
+    }
+
+    #[test]
+    fn test_memory_stats_total_grows_with_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let empty = book.memory_stats();
+        assert_eq!(empty.bids.orders, 0);
+        assert_eq!(empty.order_locations.entries, 0);
+
+        for id in 0..1_000 {
+            add(&book, id, 1_000 + id % 50, Side::Buy);
+        }
+        let loaded = book.memory_stats();
+        assert_eq!(loaded.bids.levels, 50);
+        assert_eq!(loaded.bids.orders, 1_000);
+        assert!(loaded.total_bytes() > empty.total_bytes());
+        assert_eq!(
+            loaded.total_bytes(),
+            loaded.bids.bytes
+                + loaded.asks.bytes
+                + loaded.order_locations.bytes
+                + loaded.order_tables.bytes
+                + loaded.executions.bytes
+                + loaded.matching_pool.bytes
+                + loaded.cache.bytes
+        );
+    }
+
+    #[test]
+    fn test_memory_stats_tracks_trades_and_finished_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        add(&book, 1, 100, Side::Sell);
+        book.submit_market_order(OrderId::from_u64(2), 10, Side::Buy)
+            .unwrap();
+
+        let stats = book.memory_stats();
+        assert_eq!(stats.asks.orders, 0);
+        assert_eq!(stats.order_locations.entries, 0);
+        // Final statuses of the filled maker and the market order
+        assert_eq!(stats.executions.entries, 2);
+        assert!(stats.executions.bytes > 0);
+    }
+
+    #[test]
+    fn test_compact_releases_capacity_left_by_cancelled_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        for id in 0..2_000 {
+            add(&book, id, 1_000 + id, Side::Sell);
+        }
+        add(&book, 5_000, 900, Side::Buy);
+        for id in 1..2_000 {
+            book.cancel_order(OrderId::from_u64(id)).unwrap();
+        }
+        let before = book.memory_stats();
+
+        assert!(book.compact() > 0);
+        let after = book.memory_stats();
+        assert!(after.asks.bytes < before.asks.bytes);
+        assert!(after.order_locations.bytes < before.order_locations.bytes);
+        assert_eq!(after.order_locations.entries, 2);
+
+        // Resting orders are untouched and keep trading
+        assert_eq!(book.best_ask(), Some(1_000));
+        assert_eq!(book.best_bid(), Some(900));
+        let result = book
+            .submit_market_order(OrderId::from_u64(6_000), 10, Side::Buy)
+            .unwrap();
+        assert_eq!(result.filled_order_ids, vec![OrderId::from_u64(0)]);
+    }
+
+    #[test]
+    fn test_compact_removes_empty_levels() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        add(&book, 1, 100, Side::Buy);
+        book.bids.insert(105, Arc::new(PriceLevel::new(105)));
+        assert_eq!(book.best_bid(), Some(105));
+        let version = book.version();
+
+        book.compact();
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.bids.indexed_prices(), vec![100]);
+        assert!(book.version() > version);
+    }
+}
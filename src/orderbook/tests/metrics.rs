@@ -0,0 +1,56 @@
+//! Unit tests for the latency histograms of the `metrics` feature.
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::metrics::{LatencyStats, Operation};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    #[test]
+    fn test_records_each_operation() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        for id in 1..=3 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        book.submit_market_order(OrderId::from_u64(4), 5, Side::Buy)
+            .unwrap();
+
+        let report = book.metrics_report();
+        // Each add also runs the matching of the incoming order
+        assert_eq!(report.add_order.count, 3);
+        assert_eq!(report.cancel_order.count, 1);
+        assert_eq!(report.match_order.count, 4);
+        for stats in [report.add_order, report.cancel_order, report.match_order] {
+            assert!(stats.min_ns >= 1);
+            assert!(stats.min_ns <= stats.p50_ns);
+            assert!(stats.p50_ns <= stats.p99_ns);
+            assert!(stats.p99_ns <= stats.max_ns);
+        }
+    }
+
+    #[test]
+    fn test_percentiles_and_reset() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        for latency in 1..=1000 {
+            book.metrics.record(Operation::Cancel, latency * 1000);
+        }
+
+        let stats = book.metrics_report().cancel_order;
+        assert_eq!(stats.count, 1000);
+        assert!(stats.p50_ns.abs_diff(500_000) <= 500);
+        assert!(stats.p99_ns.abs_diff(990_000) <= 1_000);
+        assert!((stats.mean_ns - 500_500.0).abs() < 1_000.0);
+
+        book.reset_metrics();
+        assert_eq!(book.metrics_report().cancel_order, LatencyStats::default());
+    }
+}
@@ -1,9 +1,89 @@
+mod accounts;
+mod amendments;
+mod analytics;
+mod anonymize;
+mod arena;
+mod async_api;
+mod audit;
+mod bbo;
+mod binary;
 mod book;
+mod builder;
+mod cache;
+mod caps;
+mod checksum;
+mod circuit_breaker;
+mod client_ids;
+mod compact;
+mod consistency;
+mod constraints;
+mod contingent;
+mod dark;
+mod depth_feed;
+mod deterministic;
+mod disconnect;
+mod duplicate_ids;
+mod engine;
 mod error;
+mod events;
+mod execution_report;
+mod expiry;
+mod export;
+mod fairness;
+mod faults;
+mod feed;
+mod fees;
+mod fills;
+mod fixed_point;
+mod follower;
+mod fuzz;
+#[cfg(test)]
+mod helpers;
+mod implied;
+mod itch;
+mod kill_switch;
+mod level_events;
+mod level_fills;
+mod levels;
+#[cfg(feature = "loom")]
+mod loom;
+mod manager;
+mod market_to_limit;
+mod match_depth;
 mod matching;
+mod memory;
+mod metrics;
 mod modifications;
 mod operations;
+mod options;
 mod order;
+mod order_status;
+mod parallel;
+mod pegs;
+mod pool;
+mod price_scale;
+mod quotes;
+mod rate_limit;
+mod reference;
+mod registry;
+mod replay;
+mod reserve;
+mod sequence;
+mod session;
+mod shard;
+mod short_sale;
+mod side;
+mod signals;
+mod signed;
 mod snapshot;
+mod special;
+mod stats;
+mod stress;
+mod tape;
 mod time_in_force;
+mod trade;
+mod trade_channel;
 mod uuid;
+mod validation;
+mod views;
+mod watermarks;
@@ -1,14 +1,11 @@
 #[cfg(test)]
 mod test_order_modifications {
     use crate::orderbook::modifications::OrderQuantity;
+    use crate::orderbook::tests::helpers::create_order_id;
     use crate::{OrderBook, OrderBookError};
-    use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+    use pricelevel::{OrderType, OrderUpdate, Side, TimeInForce};
 
     // Helper function to create a unique order ID
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     #[test]
     fn test_update_price_same_value() {
         let book: OrderBook<()> = OrderBook::new("TEST");
@@ -219,11 +216,8 @@ mod test_order_modifications {
 mod test_modifications_remaining {
     use crate::OrderBook;
 
-    use pricelevel::{OrderId, OrderType, OrderUpdate, PegReferenceType, Side, TimeInForce};
-
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
+    use crate::orderbook::tests::helpers::create_order_id;
+    use pricelevel::{OrderType, OrderUpdate, PegReferenceType, Side, TimeInForce};
 
     #[test]
     fn test_update_price_error_cases() {
@@ -321,10 +315,20 @@ mod test_modifications_remaining {
             extra_fields: (),
         };
 
-        // Add all orders to the book
+        // Add all orders to the book. The market to limit order takes the
+        // level it is priced off and rests the rest of its quantity there
+        book.add_limit_order(
+            create_order_id(),
+            1000,
+            1,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        let _ = book.add_order(mtl_order);
         let _ = book.add_order(trail_order);
         let _ = book.add_order(peg_order);
-        let _ = book.add_order(mtl_order);
         let _ = book.add_order(reserve_order);
 
         // Test updating all order types
@@ -452,12 +456,9 @@ mod test_modifications_remaining {
 
 #[cfg(test)]
 mod test_modifications_specific {
+    use crate::orderbook::tests::helpers::create_order_id;
     use crate::{OrderBook, OrderBookError};
-    use pricelevel::{OrderId, OrderType, OrderUpdate, PegReferenceType, Side, TimeInForce};
-
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
+    use pricelevel::{OrderType, OrderUpdate, PegReferenceType, Side, TimeInForce};
 
     #[test]
     fn test_update_price_edge_cases() {
@@ -611,33 +612,16 @@ mod test_modifications_specific {
 mod tests {
     use crate::orderbook::OrderBookError;
     use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
     use crate::orderbook::modifications::OrderQuantity;
     use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
 
     fn setup_book_with_orders() -> OrderBook<()> {
-        let book: OrderBook<()> = OrderBook::new("TEST");
-        let sell_order = OrderType::Standard {
-            id: OrderId::new(),
-            side: Side::Sell,
-            price: 100,
-            quantity: 10,
-            time_in_force: TimeInForce::Gtc,
-            timestamp: 0,
-            extra_fields: (),
-        };
-        book.add_order(sell_order).unwrap();
-
-        let buy_order = OrderType::Standard {
-            id: OrderId::new(),
-            side: Side::Buy,
-            price: 90,
-            quantity: 10,
-            time_in_force: TimeInForce::Gtc,
-            timestamp: 0,
-            extra_fields: (),
-        };
-        book.add_order(buy_order).unwrap();
-        book
+        BookBuilder::new("TEST")
+            .ask(100, 10)
+            .bid(90, 10)
+            .build()
+            .unwrap()
     }
 
     #[test]
@@ -764,3 +748,272 @@ mod tests {
         }
     }
 }
+
+#[cfg(test)]
+mod test_mass_cancel {
+    use crate::OrderBook;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn populated_book() -> OrderBook<()> {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        for (id, price, side) in [
+            (1, 98, Side::Buy),
+            (2, 99, Side::Buy),
+            (3, 99, Side::Buy),
+            (4, 100, Side::Buy),
+            (5, 101, Side::Sell),
+            (6, 102, Side::Sell),
+            (7, 103, Side::Sell),
+        ] {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                10,
+                side,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        book
+    }
+
+    fn ids(orders: &[std::sync::Arc<pricelevel::OrderType<()>>]) -> Vec<OrderId> {
+        orders.iter().map(|order| order.id()).collect()
+    }
+
+    #[test]
+    fn test_cancel_all() {
+        let book = populated_book();
+        let version = book.version();
+
+        let cancelled = book.cancel_all();
+
+        assert_eq!(cancelled.len(), 7);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), None);
+        assert!(book.order_locations.is_empty());
+        assert!(book.order_sequences.is_empty());
+        assert_eq!(book.version(), version + 1);
+        assert!(book.cancel_all().is_empty());
+        assert_eq!(book.version(), version + 1);
+    }
+
+    #[test]
+    fn test_cancel_side_returns_orders_best_price_first() {
+        let book = populated_book();
+
+        let cancelled = book.cancel_side(Side::Buy);
+
+        assert_eq!(
+            ids(&cancelled),
+            [4, 2, 3, 1].map(OrderId::from_u64).to_vec()
+        );
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), Some(101));
+        assert!(book.get_order(OrderId::from_u64(2)).is_none());
+        assert!(book.get_order(OrderId::from_u64(5)).is_some());
+    }
+
+    #[test]
+    fn test_cancel_price_range_is_inclusive() {
+        let book = populated_book();
+
+        let cancelled = book.cancel_price_range(Side::Sell, 102, 103);
+        assert_eq!(ids(&cancelled), [6, 7].map(OrderId::from_u64).to_vec());
+        assert_eq!(book.best_ask(), Some(101));
+
+        let cancelled = book.cancel_price_range(Side::Buy, 98, 99);
+        assert_eq!(ids(&cancelled), [2, 3, 1].map(OrderId::from_u64).to_vec());
+        assert_eq!(book.best_bid(), Some(100));
+
+        assert!(book.cancel_price_range(Side::Buy, 50, 60).is_empty());
+        assert!(book.cancel_price_range(Side::Buy, 100, 99).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_clear_and_seed {
+    use crate::{OrderBook, OrderBookError};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    #[test]
+    fn test_seed_rests_orders_without_matching() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let ids = book
+            .seed(&[
+                (99, 10, Side::Buy),
+                (100, 5, Side::Buy),
+                (100, 7, Side::Buy),
+                (101, 8, Side::Sell),
+            ])
+            .unwrap();
+
+        assert_eq!(ids.len(), 4);
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+        let at_100 = book.get_orders_at_price(100, Side::Buy);
+        assert_eq!(
+            at_100.iter().map(|order| order.id()).collect::<Vec<_>>(),
+            vec![ids[1], ids[2]]
+        );
+        assert!(book.order_sequence(ids[1]) < book.order_sequence(ids[2]));
+        assert!(book.validate().is_valid());
+
+        // Seeded orders trade like any other
+        let result = book
+            .submit_market_order(OrderId::new(), 6, Side::Sell)
+            .unwrap();
+        assert_eq!(result.transactions.as_vec()[0].maker_order_id, ids[1]);
+    }
+
+    #[test]
+    fn test_seed_rejects_crossing_levels_atomically() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        book.add_limit_order(OrderId::new(), 105, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let result = book.seed(&[(100, 10, Side::Buy), (105, 10, Side::Buy)]);
+        assert!(matches!(
+            result,
+            Err(OrderBookError::PriceCrossing {
+                price: 105,
+                opposite_price: 105,
+                ..
+            })
+        ));
+        assert_eq!(book.best_bid(), None);
+
+        let result = book.seed(&[(100, 0, Side::Buy)]);
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_clear_empties_the_book() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let ids = book
+            .seed(&[(99, 10, Side::Buy), (101, 10, Side::Sell)])
+            .unwrap();
+        book.submit_market_order(OrderId::new(), 4, Side::Buy)
+            .unwrap();
+        let version = book.version();
+
+        book.clear();
+
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), None);
+        assert!(book.get_all_orders().is_empty());
+        assert!(book.order_locations.is_empty());
+        assert!(ids.iter().all(|id| book.execution_state(*id).is_none()));
+        assert!(book.version() > version);
+        assert_eq!(book.last_trade_price(), Some(101));
+
+        // The book is usable again right away
+        book.seed(&[(50, 1, Side::Buy)]).unwrap();
+        assert_eq!(book.best_bid(), Some(50));
+    }
+}
+
+#[cfg(test)]
+mod test_reserve_amend {
+    use crate::orderbook::modifications::OrderQuantity;
+    use crate::{OrderBook, OrderBookError};
+    use pricelevel::{OrderId, OrderType, OrderUpdate, Side, TimeInForce};
+
+    #[test]
+    fn test_iceberg_price_amend_keeps_display_and_hidden_quantity() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let id = OrderId::from_u64(1);
+        book.add_iceberg_order(id, 100, 5, 15, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        assert_eq!(book.display_size(id), Some(5));
+
+        // Work part of the visible slice
+        book.submit_market_order(OrderId::from_u64(2), 3, Side::Buy)
+            .unwrap();
+        let worked = book.get_order(id).unwrap();
+        assert_eq!(worked.total_quantity(), 17);
+
+        let amended = book
+            .update_order(OrderUpdate::UpdatePrice {
+                order_id: id,
+                new_price: 102,
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(amended.price(), 102);
+        assert_eq!(amended.visible_quantity(), 5);
+        assert_eq!(amended.hidden_quantity(), 12);
+        assert_eq!(book.display_size(id), Some(5));
+
+        let amended = book
+            .update_order(OrderUpdate::UpdatePriceAndQuantity {
+                order_id: id,
+                new_price: 103,
+                new_quantity: 8,
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(amended.visible_quantity(), 5);
+        assert_eq!(amended.hidden_quantity(), 3);
+
+        assert!(matches!(
+            book.update_order(OrderUpdate::UpdatePrice {
+                order_id: id,
+                new_price: 103,
+            }),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reserve_price_amend_keeps_replenishment() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        let id = OrderId::from_u64(1);
+        book.add_order(OrderType::ReserveOrder {
+            id,
+            price: 100,
+            visible_quantity: 4,
+            hidden_quantity: 16,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            replenish_threshold: 1,
+            replenish_amount: Some(4),
+            auto_replenish: true,
+            extra_fields: (),
+        })
+        .unwrap();
+
+        let amended = book
+            .update_order(OrderUpdate::UpdatePrice {
+                order_id: id,
+                new_price: 99,
+            })
+            .unwrap()
+            .unwrap();
+        match amended.as_ref() {
+            OrderType::ReserveOrder {
+                price,
+                visible_quantity,
+                hidden_quantity,
+                replenish_amount,
+                auto_replenish,
+                ..
+            } => {
+                assert_eq!(*price, 99);
+                assert_eq!(*visible_quantity, 4);
+                assert_eq!(*hidden_quantity, 16);
+                assert_eq!(*replenish_amount, Some(4));
+                assert!(*auto_replenish);
+            }
+            other => panic!("expected a reserve order, got {other:?}"),
+        }
+
+        book.cancel_order(id).unwrap();
+        assert_eq!(book.display_size(id), None);
+    }
+}
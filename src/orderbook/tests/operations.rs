@@ -461,7 +461,8 @@ mod tests {
 #[cfg(test)]
 mod test_extra_fields {
     use crate::OrderBook;
-    use pricelevel::{OrderId, Side, TimeInForce};
+    use crate::orderbook::tests::helpers::create_order_id;
+    use pricelevel::{Side, TimeInForce};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
@@ -471,10 +472,6 @@ mod test_extra_fields {
         priority: u32,
     }
 
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     fn create_test_metadata() -> OrderMetadata {
         OrderMetadata {
             client_id: "client_123".to_string(),
@@ -651,11 +648,8 @@ mod test_extra_fields {
 #[cfg(test)]
 mod test_operations_remaining {
     use crate::OrderBook;
-    use pricelevel::{OrderId, Side, TimeInForce};
-
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
+    use crate::orderbook::tests::helpers::create_order_id;
+    use pricelevel::{Side, TimeInForce};
 
     #[test]
     fn test_add_limit_order_with_trace() {
@@ -735,14 +729,11 @@ mod test_operations_remaining {
 #[cfg(test)]
 mod test_operations_specific {
     use crate::OrderBook;
-    use pricelevel::{OrderId, Side, TimeInForce};
+    use pricelevel::{Side, TimeInForce};
 
+    use crate::orderbook::tests::helpers::create_order_id;
     use tracing::trace;
 
-    fn create_order_id() -> OrderId {
-        OrderId::new_uuid()
-    }
-
     #[test]
     fn test_submit_market_order_with_tracing() {
         let book: OrderBook<()> = OrderBook::new("TEST");
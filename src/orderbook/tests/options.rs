@@ -0,0 +1,183 @@
+//! Unit tests for versioned book options.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::dark::DarkMatching;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::options::{
+        DuplicateOrderIdAction, OrderBookOptions, ReserveRefresh, VersionedOptions,
+    };
+    use crate::orderbook::pegs::MidpointPeg;
+    use crate::orderbook::tests::helpers::record_trades;
+    use crate::orderbook::trade::TradeCondition;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    type Events = Arc<Mutex<Vec<String>>>;
+
+    fn book_with_recorder() -> (OrderBook, Events) {
+        let events: Events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            let entry = match event {
+                OrderBookEvent::OptionsChanged(installed) => {
+                    format!("options v{}", installed.version)
+                }
+                OrderBookEvent::OrderRejected(reject) => {
+                    format!("reject v{}", reject.config_version)
+                }
+                OrderBookEvent::OrderExpired(expired) => format!("expired {}", expired.sequence),
+                OrderBookEvent::OrderAccepted(accepted) => format!("accept {}", accepted.order_id),
+                OrderBookEvent::TradeExecuted(report) => {
+                    format!("trade {}", report.transaction.taker_order_id)
+                }
+                OrderBookEvent::OrderFilled(fill) => format!("fill {}", fill.order_id),
+                OrderBookEvent::OrderCancelled(cancel) => format!("cancel {}", cancel.order_id),
+                OrderBookEvent::PriceLevelFault(error) => format!("fault {error}"),
+                OrderBookEvent::TradingStateChanged(transition) => {
+                    format!("state {}", transition.to)
+                }
+                OrderBookEvent::TradingHalted(halt) => format!("halt {:?}", halt.side),
+                OrderBookEvent::QuotesPulled(pulled) => format!("pulled {}", pulled.owner),
+                OrderBookEvent::LevelAdded(level) => format!("level+ {}", level.price),
+                OrderBookEvent::LevelRemoved(level) => format!("level- {}", level.price),
+            };
+            recorded.lock().unwrap().push(entry);
+        }));
+        (book, events)
+    }
+
+    #[test]
+    fn test_changes_are_stamped_with_increasing_versions() {
+        let (book, events) = book_with_recorder();
+        assert_eq!(*book.options(), VersionedOptions::default());
+
+        book.set_round_lot(100);
+        book.set_market_close_timestamp(1_000);
+        let version = book.reload_options(OrderBookOptions {
+            round_lot: 10,
+            market_close_timestamp: None,
+            fee_schedule: None,
+            max_match_depth: None,
+            midpoint_peg: MidpointPeg::default(),
+            dark_matching: DarkMatching::default(),
+            resting_caps: None,
+            duplicate_order_ids: DuplicateOrderIdAction::Reject,
+            reserve_refresh: ReserveRefresh::BackOfQueue,
+            sweep_guard: None,
+            quote_protection: None,
+        });
+
+        assert_eq!(version, 3);
+        assert_eq!(book.config_version(), 3);
+        assert_eq!(book.options().options.round_lot, 10);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["options v1", "options v2", "options v3"]
+        );
+    }
+
+    #[test]
+    fn test_setters_keep_the_other_options() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.set_market_close_timestamp(1_000);
+        book.set_round_lot(100);
+        book.clear_market_close_timestamp();
+
+        assert_eq!(
+            book.options().options,
+            OrderBookOptions {
+                round_lot: 100,
+                market_close_timestamp: None,
+                fee_schedule: None,
+                max_match_depth: None,
+                midpoint_peg: MidpointPeg::default(),
+                dark_matching: DarkMatching::default(),
+                resting_caps: None,
+                duplicate_order_ids: DuplicateOrderIdAction::Reject,
+                reserve_refresh: ReserveRefresh::BackOfQueue,
+                sweep_guard: None,
+                quote_protection: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_held_options_are_not_affected_by_reload() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.set_round_lot(100);
+        let held = book.options();
+
+        book.set_round_lot(5);
+        assert_eq!(held.version, 1);
+        assert_eq!(held.options.round_lot, 100);
+        assert_eq!(book.options().options.round_lot, 5);
+    }
+
+    #[test]
+    fn test_reject_carries_config_version() {
+        let (book, events) = book_with_recorder();
+        book.set_round_lot(100);
+        assert!(
+            book.submit_market_order(OrderId::from_u64(1), 10, Side::Buy)
+                .is_err()
+        );
+
+        assert_eq!(*events.lock().unwrap(), vec!["options v1", "reject v1"]);
+    }
+
+    #[test]
+    fn test_match_runs_under_a_single_version() {
+        const LEVELS: u64 = 8;
+        let mut book = OrderBook::new("TEST_SYMBOL");
+        let reports = record_trades(&mut book);
+        let book: Arc<OrderBook> = Arc::new(book);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let reloader = {
+            let book = Arc::clone(&book);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                let mut round_lot = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    round_lot = 100 - round_lot;
+                    book.set_round_lot(round_lot);
+                }
+            })
+        };
+
+        for round in 0..200u64 {
+            for level in 0..LEVELS {
+                book.add_limit_order(
+                    OrderId::from_u64(round * 100 + level),
+                    100 + level,
+                    1,
+                    Side::Sell,
+                    TimeInForce::Gtc,
+                    None,
+                )
+                .unwrap();
+            }
+            book.submit_market_order(OrderId::from_u64(round * 100 + 99), LEVELS, Side::Buy)
+                .unwrap();
+
+            let odd_lots = reports
+                .lock()
+                .unwrap()
+                .drain(..)
+                .filter(|report| report.conditions.contains(TradeCondition::OddLot))
+                .count() as u64;
+            assert!(
+                odd_lots == 0 || odd_lots == LEVELS,
+                "round {round} mixed round lot rules: {odd_lots} of {LEVELS} odd-lot"
+            );
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        reloader.join().unwrap();
+    }
+}
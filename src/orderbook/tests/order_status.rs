@@ -0,0 +1,180 @@
+//! Unit tests for the order status query.
+
+#[cfg(test)]
+mod tests {
+    use crate::current_time_millis;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::execution_report::OrderStatus;
+    use crate::orderbook::kill_switch::KillScope;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+
+    #[test]
+    fn test_unknown_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        assert_eq!(
+            book.order_status(OrderId::from_u64(1)),
+            OrderStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_resting_order_is_new_then_partially_filled() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let maker = rest(&book, 1, 100, 10, Side::Sell);
+        assert_eq!(book.order_status(maker), OrderStatus::New);
+
+        book.submit_market_order(OrderId::from_u64(2), 4, Side::Buy)
+            .unwrap();
+        assert_eq!(book.order_status(maker), OrderStatus::PartiallyFilled);
+    }
+
+    #[test]
+    fn test_maker_and_taker_filled() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let maker = rest(&book, 1, 100, 10, Side::Sell);
+        let taker = rest(&book, 2, 100, 10, Side::Buy);
+
+        assert_eq!(book.order_status(maker), OrderStatus::Filled);
+        assert_eq!(book.order_status(taker), OrderStatus::Filled);
+    }
+
+    #[test]
+    fn test_crossing_limit_order_rests_partially_filled() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 4, Side::Sell);
+        let taker = rest(&book, 2, 100, 10, Side::Buy);
+        assert_eq!(book.order_status(taker), OrderStatus::PartiallyFilled);
+    }
+
+    #[test]
+    fn test_cancelled_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let single = rest(&book, 1, 100, 10, Side::Buy);
+        let through_update = rest(&book, 2, 99, 10, Side::Buy);
+        let mass = rest(&book, 3, 110, 10, Side::Sell);
+
+        book.cancel_order(single).unwrap();
+        book.update_order(OrderUpdate::Cancel {
+            order_id: through_update,
+        })
+        .unwrap();
+        book.cancel_side(Side::Sell);
+
+        for order_id in [single, through_update, mass] {
+            assert_eq!(book.order_status(order_id), OrderStatus::Cancelled);
+        }
+    }
+
+    #[test]
+    fn test_amended_order_stays_working() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let order_id = rest(&book, 1, 100, 10, Side::Buy);
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id,
+            new_price: 101,
+        })
+        .unwrap();
+        assert_eq!(book.order_status(order_id), OrderStatus::New);
+    }
+
+    #[test]
+    fn test_expired_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let now = current_time_millis();
+        let order_id = OrderId::from_u64(1);
+        book.add_limit_order(
+            order_id,
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtd(now + 1_000),
+            None,
+        )
+        .unwrap();
+        book.expire_orders_at(now + 1_000);
+        assert_eq!(book.order_status(order_id), OrderStatus::Expired);
+    }
+
+    #[test]
+    fn test_rejected_orders() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 5, Side::Sell);
+
+        let post_only = OrderId::from_u64(2);
+        assert!(
+            book.add_post_only_order(post_only, 100, 5, Side::Buy, TimeInForce::Gtc, None)
+                .is_err()
+        );
+        let fok = OrderId::from_u64(3);
+        assert!(
+            book.add_limit_order(fok, 100, 10, Side::Buy, TimeInForce::Fok, None)
+                .is_err()
+        );
+        let market = OrderId::from_u64(4);
+        assert!(book.submit_market_order(market, 5, Side::Sell).is_err());
+
+        for order_id in [post_only, fok, market] {
+            assert_eq!(book.order_status(order_id), OrderStatus::Rejected);
+        }
+    }
+
+    #[test]
+    fn test_unfilled_remainders_are_cancelled() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 5, Side::Sell);
+        rest(&book, 2, 101, 5, Side::Sell);
+
+        // The IOC order trades and reports its missing remainder as an error
+        let ioc = OrderId::from_u64(3);
+        assert!(
+            book.add_limit_order(ioc, 100, 10, Side::Buy, TimeInForce::Ioc, None)
+                .is_err()
+        );
+        assert_eq!(book.order_status(ioc), OrderStatus::Cancelled);
+
+        let market = OrderId::from_u64(4);
+        book.submit_market_order(market, 10, Side::Buy).unwrap();
+        assert_eq!(book.order_status(market), OrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_resubmitted_id_reports_its_latest_outcome() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 5, Side::Sell);
+        let order_id = OrderId::from_u64(2);
+        assert!(
+            book.add_post_only_order(order_id, 100, 5, Side::Buy, TimeInForce::Gtc, None)
+                .is_err()
+        );
+        assert_eq!(book.order_status(order_id), OrderStatus::Rejected);
+
+        book.add_post_only_order(order_id, 99, 5, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        assert_eq!(book.order_status(order_id), OrderStatus::New);
+    }
+
+    #[test]
+    fn test_rejected_resubmission_keeps_the_final_status() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let order_id = rest(&book, 1, 100, 5, Side::Sell);
+        book.cancel_order(order_id).unwrap();
+        book.kill_switch(KillScope::Book);
+
+        assert!(
+            book.add_limit_order(order_id, 100, 5, Side::Sell, TimeInForce::Gtc, None)
+                .is_err()
+        );
+        assert!(book.submit_market_order(order_id, 5, Side::Buy).is_err());
+        assert_eq!(book.order_status(order_id), OrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_status_flags() {
+        assert!(!OrderStatus::New.is_terminal());
+        assert!(!OrderStatus::Unknown.is_terminal());
+        assert!(OrderStatus::Expired.is_terminal());
+        assert_eq!(OrderStatus::New.fix_value(), '0');
+        assert_eq!(OrderStatus::Unknown.fix_value(), '8');
+    }
+}
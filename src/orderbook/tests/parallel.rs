@@ -0,0 +1,73 @@
+//! Unit tests for the parallel snapshot construction of the `parallel` feature.
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use crate::orderbook::book::{DEFAULT_PARALLEL_SNAPSHOT_DEPTH, OrderBook};
+    use crate::orderbook::builder::BookBuilder;
+    use pricelevel::Side;
+
+    fn deep_book() -> OrderBook {
+        let mut builder = BookBuilder::new("TEST_SYMBOL");
+        for level in 0..300 {
+            builder = builder
+                .bid(10_000 - level, 1 + level % 7)
+                .bid(10_000 - level, 2)
+                .ask(10_001 + level, 1 + level % 5);
+        }
+        builder.iceberg(Side::Sell, 10_050, 3, 9).build().unwrap()
+    }
+
+    fn level_summary(snapshot: &crate::OrderBookSnapshot) -> Vec<(u64, u64, u64, usize)> {
+        snapshot
+            .bids
+            .iter()
+            .chain(&snapshot.asks)
+            .map(|level| {
+                (
+                    level.price,
+                    level.visible_quantity,
+                    level.hidden_quantity,
+                    level.order_count,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_snapshot_matches_sequential() {
+        let mut book = deep_book();
+        assert_eq!(
+            book.parallel_snapshot_depth(),
+            DEFAULT_PARALLEL_SNAPSHOT_DEPTH
+        );
+
+        book.set_parallel_snapshot_depth(usize::MAX);
+        let sequential = book.create_snapshot(usize::MAX);
+        let sequential_l3 = book.create_l3_snapshot(usize::MAX);
+
+        book.set_parallel_snapshot_depth(0);
+        let parallel = book.create_snapshot(usize::MAX);
+        let mut parallel_l3 = book.create_l3_snapshot(usize::MAX);
+
+        assert_eq!(parallel.bids.len(), 300);
+        assert_eq!(level_summary(&parallel), level_summary(&sequential));
+        parallel_l3.timestamp = sequential_l3.timestamp;
+        assert_eq!(parallel_l3, sequential_l3);
+    }
+
+    #[test]
+    fn test_parallel_snapshot_respects_depth() {
+        let mut book = deep_book();
+        book.set_parallel_snapshot_depth(8);
+
+        let snapshot = book.create_snapshot(10);
+        assert_eq!(snapshot.bids.len(), 10);
+        assert_eq!(snapshot.bids[0].price, 10_000);
+        assert_eq!(snapshot.bids[9].price, 9_991);
+        assert_eq!(snapshot.asks[0].price, 10_001);
+
+        // Below the threshold the sequential path produces the same levels
+        let shallow = book.create_snapshot(4);
+        assert_eq!(level_summary(&shallow)[..4], level_summary(&snapshot)[..4]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Unit tests for pegged order repricing.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::pegs::{MidpointPeg, MidpointRounding, RepricedOrder, SubTickHandling};
+    use crate::orderbook::price_scale::PriceScale;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, OrderType, PegReferenceType, Side, TimeInForce};
+
+    fn peg(
+        book: &OrderBook<()>,
+        id: u64,
+        price: u64,
+        side: Side,
+        reference_price_type: PegReferenceType,
+        reference_price_offset: i64,
+    ) -> OrderId {
+        let order_id = OrderId::from_u64(id);
+        book.add_order(OrderType::PeggedOrder {
+            id: order_id,
+            price,
+            quantity: 10,
+            side,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            reference_price_offset,
+            reference_price_type,
+            extra_fields: (),
+        })
+        .unwrap();
+        order_id
+    }
+
+    fn price_of(book: &OrderBook<()>, order_id: OrderId) -> u64 {
+        book.get_order(order_id).unwrap().price()
+    }
+
+    #[test]
+    fn test_exact_midpoint_needs_no_rounding() {
+        let peg = MidpointPeg::default();
+        assert_eq!(peg.price(Side::Buy, 100, 110, 1), Some(105));
+        assert_eq!(peg.price(Side::Sell, 100, 110, 5), Some(105));
+    }
+
+    #[test]
+    fn test_rounding_toward_maker_and_taker() {
+        let maker = MidpointPeg::new(MidpointRounding::TowardMaker, SubTickHandling::RoundToTick);
+        let taker = MidpointPeg::new(MidpointRounding::TowardTaker, SubTickHandling::RoundToTick);
+        // Midpoint 107, between the ticks 105 and 110
+        assert_eq!(maker.price(Side::Buy, 100, 114, 5), Some(105));
+        assert_eq!(maker.price(Side::Sell, 100, 114, 5), Some(110));
+        assert_eq!(taker.price(Side::Buy, 100, 114, 5), Some(110));
+        assert_eq!(taker.price(Side::Sell, 100, 114, 5), Some(105));
+    }
+
+    #[test]
+    fn test_bankers_rounding() {
+        let bankers = MidpointPeg::new(MidpointRounding::Bankers, SubTickHandling::RoundToTick);
+        // Nearest tick whatever the side
+        assert_eq!(bankers.price(Side::Buy, 100, 114, 5), Some(105));
+        assert_eq!(bankers.price(Side::Sell, 100, 118, 5), Some(110));
+        // Exact halves go to the even multiple of the tick: 102.5 and 107.5
+        assert_eq!(bankers.price(Side::Buy, 100, 105, 5), Some(100));
+        assert_eq!(bankers.price(Side::Sell, 100, 115, 5), Some(110));
+        assert_eq!(bankers.price(Side::Buy, 100, 101, 1), Some(100));
+        // 101.5 rounds to 102, the ask, so the buy stays at 101
+        assert_eq!(bankers.price(Side::Buy, 101, 102, 1), Some(101));
+    }
+
+    #[test]
+    fn test_sub_tick_midpoint() {
+        let sub_tick =
+            MidpointPeg::new(MidpointRounding::TowardMaker, SubTickHandling::AllowSubTick);
+        assert_eq!(sub_tick.price(Side::Buy, 100, 114, 5), Some(107));
+        assert_eq!(sub_tick.price(Side::Sell, 100, 115, 5), Some(108));
+    }
+
+    #[test]
+    fn test_midpoint_never_reaches_the_opposite_touch() {
+        let taker = MidpointPeg::new(MidpointRounding::TowardTaker, SubTickHandling::RoundToTick);
+        // One tick wide: rounding toward the taker would lock the market
+        assert_eq!(taker.price(Side::Buy, 100, 101, 1), Some(100));
+        assert_eq!(taker.price(Side::Sell, 100, 101, 1), Some(101));
+    }
+
+    #[test]
+    fn test_locked_market_has_no_midpoint() {
+        let peg = MidpointPeg::default();
+        assert_eq!(peg.price(Side::Buy, 100, 100, 1), None);
+        assert_eq!(peg.price(Side::Buy, 101, 100, 1), None);
+    }
+
+    #[test]
+    fn test_reprice_follows_the_references() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 110, 10, Side::Sell);
+        let primary = peg(&book, 3, 90, Side::Buy, PegReferenceType::BestBid, -1);
+        let midpoint = peg(&book, 4, 120, Side::Sell, PegReferenceType::MidPrice, 0);
+
+        let mut repriced = book.reprice_pegged_orders();
+        repriced.sort_by_key(|repricing| repricing.old_price);
+        assert_eq!(
+            repriced,
+            vec![
+                RepricedOrder {
+                    order_id: primary,
+                    old_price: 90,
+                    new_price: 99,
+                },
+                RepricedOrder {
+                    order_id: midpoint,
+                    old_price: 120,
+                    new_price: 105,
+                },
+            ]
+        );
+        assert_eq!(price_of(&book, primary), 99);
+        assert_eq!(price_of(&book, midpoint), 105);
+
+        // Nothing moves when the references have not changed
+        assert!(book.reprice_pegged_orders().is_empty());
+    }
+
+    #[test]
+    fn test_reprice_uses_the_configured_rounding() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_price_scale(PriceScale::new(0, 5).unwrap());
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 115, 10, Side::Sell);
+        let midpoint = peg(&book, 3, 100, Side::Buy, PegReferenceType::MidPrice, 0);
+
+        book.reprice_pegged_orders();
+        assert_eq!(price_of(&book, midpoint), 105);
+
+        book.set_midpoint_peg(MidpointPeg::new(
+            MidpointRounding::TowardTaker,
+            SubTickHandling::RoundToTick,
+        ));
+        book.reprice_pegged_orders();
+        assert_eq!(price_of(&book, midpoint), 110);
+        assert_eq!(book.midpoint_peg().rounding, MidpointRounding::TowardTaker);
+    }
+
+    #[test]
+    fn test_peg_without_reference_keeps_its_price() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        let midpoint = peg(&book, 2, 95, Side::Buy, PegReferenceType::MidPrice, 0);
+        let last_trade = peg(&book, 3, 96, Side::Buy, PegReferenceType::LastTrade, 0);
+
+        assert!(book.reprice_pegged_orders().is_empty());
+        assert_eq!(price_of(&book, midpoint), 95);
+        assert_eq!(price_of(&book, last_trade), 96);
+    }
+}
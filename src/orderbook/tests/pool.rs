@@ -0,0 +1,201 @@
+//! Unit tests for the matching pool configuration and statistics.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::pool::{PoolConfig, PoolStats};
+    use crate::orderbook::tests::helpers::{limit, rest};
+    use crate::orderbook::trade_channel::OverflowPolicy;
+    use pricelevel::{OrderId, PriceLevel, Side, TimeInForce, UuidGenerator};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::thread;
+    use uuid::Uuid;
+
+    /// Counts the allocations made by the current thread while counting is on
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get().map(|n| n + 1)));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get().map(|n| n + 1)));
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Allocations `f` makes on the current thread
+    fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        ALLOCATIONS.with(|count| count.set(Some(0)));
+        let result = f();
+        let count = ALLOCATIONS.with(|count| count.take()).unwrap_or_default();
+        (result, count)
+    }
+
+    fn rest_asks(book: &OrderBook<()>, count: u64) {
+        for id in 1..=count {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100 + id,
+                1,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_default_config() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        assert_eq!(book.pool_config(), PoolConfig::default());
+        assert_eq!(book.pool_stats(), PoolStats::default());
+    }
+
+    #[test]
+    fn test_initial_capacity_is_capped() {
+        let book: OrderBook<()> = OrderBook::new_with_config(
+            "TEST_SYMBOL",
+            PoolConfig {
+                initial_capacity: 100,
+                max_capacity: 10,
+            },
+        );
+        assert_eq!(book.pool_config().initial_capacity, 10);
+    }
+
+    #[test]
+    fn test_hits_misses_and_growths() {
+        // A fresh thread has an empty pool
+        thread::spawn(|| {
+            let book: OrderBook<()> = OrderBook::new_with_config(
+                "TEST_SYMBOL",
+                PoolConfig {
+                    initial_capacity: 2,
+                    max_capacity: 64,
+                },
+            );
+            rest_asks(&book, 8);
+
+            // First match allocates both vectors, then grows them sweeping 4 levels
+            book.submit_market_order(OrderId::from_u64(100), 4, Side::Buy)
+                .unwrap();
+            assert_eq!(
+                book.pool_stats(),
+                PoolStats {
+                    hits: 0,
+                    misses: 2,
+                    growths: 2,
+                }
+            );
+
+            // The grown vectors are reused and are now large enough
+            book.submit_market_order(OrderId::from_u64(101), 4, Side::Buy)
+                .unwrap();
+            assert_eq!(
+                book.pool_stats(),
+                PoolStats {
+                    hits: 2,
+                    misses: 2,
+                    growths: 2,
+                }
+            );
+        })
+        .join()
+        .unwrap();
+    }
+
+    /// Allocations the price level itself makes to fill one order in full:
+    /// the trade id and the vectors of its own result
+    fn level_match_allocations() -> usize {
+        let level = PriceLevel::new(100);
+        let generator = UuidGenerator::new(Uuid::new_v4());
+        level.add_order(limit(1, 100, 1, Side::Sell));
+        allocations(|| level.match_order(1, OrderId::from_u64(2), &generator)).1
+    }
+
+    /// Rest two orders at one level and match one of them in full per round,
+    /// resting it again afterwards, so the level never empties and the
+    /// book's tables stop growing. Over an even number of rounds, order 1 is
+    /// next in the queue
+    fn warm_up(book: &OrderBook<()>, rounds: usize, mut round: impl FnMut(&OrderBook<()>)) {
+        rest(book, 1, 100, 1, Side::Sell);
+        rest(book, 2, 100, 1, Side::Sell);
+        for filled in [1, 2].into_iter().cycle().take(rounds) {
+            round(book);
+            rest(book, filled, 100, 1, Side::Sell);
+        }
+    }
+
+    #[test]
+    fn test_single_level_match_allocates_only_in_the_level() {
+        thread::spawn(|| {
+            let level_allocations = level_match_allocations();
+            let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+            warm_up(&book, 4, |book| {
+                book.submit_market_order(OrderId::from_u64(100), 1, Side::Buy)
+                    .unwrap();
+            });
+
+            // The result is not recycled, yet is built in pooled buffers
+            let (result, count) = allocations(|| {
+                book.submit_market_order(OrderId::from_u64(100), 1, Side::Buy)
+                    .unwrap()
+            });
+            assert_eq!(result.transactions.as_vec().len(), 1);
+            assert_eq!(result.filled_order_ids, vec![OrderId::from_u64(1)]);
+            assert_eq!(count, level_allocations);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_crossing_add_allocates_only_the_level_and_the_order() {
+        let level_allocations = level_match_allocations();
+        let crossing = |book: &OrderBook<()>| {
+            allocations(|| {
+                book.add_limit_order(
+                    OrderId::from_u64(100),
+                    100,
+                    1,
+                    Side::Buy,
+                    TimeInForce::Ioc,
+                    None,
+                )
+                .unwrap()
+            })
+            .1
+        };
+
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        warm_up(&book, 4, |book| {
+            crossing(book);
+        });
+        // Besides the level's own allocations, only the returned order
+        assert_eq!(crossing(&book), level_allocations + 1);
+
+        // Handing the trades to a channel does not copy them
+        let book: OrderBook<()> =
+            OrderBook::with_trade_channel("TEST_SYMBOL", 16, OverflowPolicy::Drop, |_| {});
+        warm_up(&book, 4, |book| {
+            crossing(book);
+        });
+        assert_eq!(crossing(&book), level_allocations + 1);
+    }
+}
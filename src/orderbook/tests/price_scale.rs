@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::price_scale::{MAX_PRICE_DECIMALS, PriceScale, RoundingMode};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    #[test]
+    fn test_default_scale_is_identity_for_whole_prices() {
+        let scale = PriceScale::default();
+        assert_eq!(
+            scale
+                .to_integer_price(100.0, RoundingMode::Exact, true)
+                .unwrap(),
+            100
+        );
+        assert_eq!(scale.to_decimal_price(100), 100.0);
+    }
+
+    #[test]
+    fn test_zero_tick_size_rejected() {
+        assert!(PriceScale::new(2, 0).is_err());
+    }
+
+    #[test]
+    fn test_decimals_beyond_u64_range_rejected() {
+        assert!(PriceScale::new(MAX_PRICE_DECIMALS, 1).is_ok());
+        assert!(matches!(
+            PriceScale::new(MAX_PRICE_DECIMALS + 1, 1),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        assert!(PriceScale::new(29, 1).is_err());
+    }
+
+    #[test]
+    fn test_float_noise_is_absorbed() {
+        // 100.1 * 100.0 is 10009.999999999998 in binary floating point
+        let scale = PriceScale::new(2, 1).unwrap();
+        assert_eq!(
+            scale
+                .to_integer_price(100.1, RoundingMode::Down, true)
+                .unwrap(),
+            10010
+        );
+        assert_eq!(
+            scale
+                .to_integer_price(100.1, RoundingMode::Exact, true)
+                .unwrap(),
+            10010
+        );
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        // Two decimals with a tick of 0.05
+        let scale = PriceScale::new(2, 5).unwrap();
+        let price = 100.12;
+
+        assert_eq!(
+            scale
+                .to_integer_price(price, RoundingMode::Down, true)
+                .unwrap(),
+            10010
+        );
+        assert_eq!(
+            scale
+                .to_integer_price(price, RoundingMode::Up, true)
+                .unwrap(),
+            10015
+        );
+        assert_eq!(
+            scale
+                .to_integer_price(price, RoundingMode::Nearest, true)
+                .unwrap(),
+            10010
+        );
+        assert_eq!(
+            scale
+                .to_integer_price(price, RoundingMode::Passive, true)
+                .unwrap(),
+            10010
+        );
+        assert_eq!(
+            scale
+                .to_integer_price(price, RoundingMode::Passive, false)
+                .unwrap(),
+            10015
+        );
+        assert!(matches!(
+            scale.to_integer_price(price, RoundingMode::Exact, true),
+            Err(OrderBookError::InvalidPrice { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_decimal_prices() {
+        let scale = PriceScale::default();
+        assert!(
+            scale
+                .to_integer_price(-1.0, RoundingMode::Down, true)
+                .is_err()
+        );
+        assert!(
+            scale
+                .to_integer_price(f64::NAN, RoundingMode::Down, true)
+                .is_err()
+        );
+        assert!(
+            scale
+                .to_integer_price(f64::INFINITY, RoundingMode::Down, true)
+                .is_err()
+        );
+        assert!(
+            scale
+                .to_integer_price(1e30, RoundingMode::Down, true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_add_limit_order_f64_returns_integer_price() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST");
+        book.set_price_scale(PriceScale::new(2, 5).unwrap());
+
+        let (price, order) = book
+            .add_limit_order_f64(
+                OrderId::from_u64(1),
+                100.12,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                RoundingMode::Passive,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(price, 10015);
+        assert_eq!(order.price(), 10015);
+        assert_eq!(book.best_ask(), Some(10015));
+    }
+
+    #[test]
+    fn test_add_post_only_order_f64_rejects_off_tick_exact_price() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST");
+        book.set_price_scale(PriceScale::new(2, 5).unwrap());
+
+        let result = book.add_post_only_order_f64(
+            OrderId::from_u64(1),
+            99.99,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            RoundingMode::Exact,
+            None,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(book.best_bid(), None);
+    }
+}
@@ -0,0 +1,251 @@
+//! Unit tests for two-sided market maker quotes.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::quotes::{PulledQuotes, QuoteProtection};
+    use crate::orderbook::session::{SessionSchedule, TradingState};
+    use crate::orderbook::tests::helpers::rest;
+    use crate::utils::ManualClock;
+    use pricelevel::Side;
+    use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+    type Pulls = Arc<Mutex<Vec<PulledQuotes>>>;
+
+    /// A book pulling an owner's quotes after `max_fills` fills within a second
+    fn protected(max_fills: usize, max_notional: u128) -> (OrderBook, Arc<ManualClock>, Pulls) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let pulls: Pulls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&pulls);
+        let mut book = OrderBook::new("TEST");
+        book.set_clock(clock.clone());
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::QuotesPulled(pulled) = event {
+                recorded.lock().unwrap().push(pulled.clone());
+            }
+        }));
+        book.set_quote_protection(Some(QuoteProtection::new(max_fills, max_notional, 1_000)));
+        (book, clock, pulls)
+    }
+
+    #[test]
+    fn test_quote_rests_both_sides() {
+        let book: OrderBook = OrderBook::new("TEST");
+        let quote = book.submit_quote("MM1", 99, 10, 101, 20).unwrap();
+        let (bid, ask) = (quote.bid.unwrap(), quote.ask.unwrap());
+        assert_eq!((bid.price, bid.quantity), (99, 10));
+        assert_eq!((ask.price, ask.quantity), (101, 20));
+        assert_eq!(book.best_bid(), Some(99));
+        assert_eq!(book.best_ask(), Some(101));
+        assert!(book.get_order(bid.order_id).is_some());
+        assert_eq!(book.quote("MM1"), Some(quote));
+    }
+
+    #[test]
+    fn test_requote_replaces_both_sides() {
+        let book: OrderBook = OrderBook::new("TEST");
+        let first = book.submit_quote("MM1", 99, 10, 101, 10).unwrap();
+        // The new bid would cross the quote's own ask, which it replaces
+        let second = book.submit_quote("MM1", 101, 5, 103, 5).unwrap();
+        assert!(book.get_order(first.bid.unwrap().order_id).is_none());
+        assert!(book.get_order(first.ask.unwrap().order_id).is_none());
+        assert_eq!(book.best_bid(), Some(101));
+        assert_eq!(book.best_ask(), Some(103));
+        assert_eq!(book.get_all_orders().len(), 2);
+        assert_eq!(book.quote("MM1"), Some(second));
+    }
+
+    #[test]
+    fn test_crossing_quote_is_rejected_and_previous_kept() {
+        let book: OrderBook = OrderBook::new("TEST");
+        rest(&book, 1, 100, 10, Side::Sell);
+        let first = book.submit_quote("MM1", 95, 10, 105, 10).unwrap();
+
+        assert!(matches!(
+            book.submit_quote("MM1", 100, 10, 105, 10),
+            Err(OrderBookError::PriceCrossing {
+                price: 100,
+                side: Side::Buy,
+                opposite_price: 100,
+            })
+        ));
+        assert!(matches!(
+            book.submit_quote("MM1", 99, 10, 98, 10),
+            Err(OrderBookError::PriceCrossing {
+                opposite_price: 98,
+                ..
+            })
+        ));
+        assert_eq!(book.quote("MM1"), Some(first));
+        assert_eq!(book.best_bid(), Some(95));
+        assert_eq!(book.get_all_orders().len(), 3);
+    }
+
+    #[test]
+    fn test_one_sided_quote_and_pulling() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.submit_quote("MM1", 99, 10, 101, 10).unwrap();
+        let quote = book.submit_quote("MM1", 98, 10, 0, 0).unwrap();
+        assert!(quote.ask.is_none());
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.best_bid(), Some(98));
+
+        let pulled = book.cancel_quote("MM1").unwrap();
+        assert_eq!(pulled, Some(quote));
+        assert_eq!(book.get_all_orders().len(), 0);
+        assert_eq!(book.cancel_quote("MM1").unwrap(), None);
+
+        book.submit_quote("MM2", 99, 10, 101, 10).unwrap();
+        let empty = book.submit_quote("MM2", 0, 0, 0, 0).unwrap();
+        assert!(empty.bid.is_none() && empty.ask.is_none());
+        assert_eq!(book.quote("MM2"), None);
+        assert_eq!(book.get_all_orders().len(), 0);
+    }
+
+    #[test]
+    fn test_quotes_are_refused_before_the_open() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.set_session_schedule(SessionSchedule::new(
+            u64::MAX - 3,
+            u64::MAX - 2,
+            u64::MAX - 1,
+        ));
+        assert!(matches!(
+            book.submit_quote("MM1", 99, 10, 101, 10),
+            Err(OrderBookError::InvalidTradingState {
+                state: TradingState::Closed
+            })
+        ));
+    }
+
+    #[test]
+    fn test_fills_breaching_protection_pull_all_owner_quotes() {
+        let (book, _clock, pulls) = protected(2, 0);
+        book.submit_quote_for("MM1", "Q1", 99, 10, 101, 10).unwrap();
+        book.submit_quote_for("MM1", "Q2", 98, 10, 102, 10).unwrap();
+        book.submit_quote_for("MM2", "Q3", 97, 10, 103, 10).unwrap();
+
+        rest(&book, 1, 99, 1, Side::Sell);
+        assert!(pulls.lock().unwrap().is_empty());
+        rest(&book, 2, 99, 1, Side::Sell);
+
+        assert_eq!(
+            *pulls.lock().unwrap(),
+            vec![PulledQuotes {
+                owner: "MM1".to_string(),
+                quote_ids: vec!["Q1".to_string(), "Q2".to_string()],
+                fills: 2,
+                notional: 198,
+                timestamp: 1_000,
+            }]
+        );
+        assert_eq!(book.quote("Q1"), None);
+        assert_eq!(book.quote("Q2"), None);
+        assert!(book.quote("Q3").is_some());
+        assert_eq!(book.best_bid(), Some(97));
+        assert_eq!(book.best_ask(), Some(103));
+    }
+
+    #[test]
+    fn test_notional_threshold_and_window() {
+        let (book, clock, pulls) = protected(0, 1_000);
+        book.submit_quote_for("MM1", "Q1", 100, 20, 110, 20)
+            .unwrap();
+        rest(&book, 1, 100, 6, Side::Sell);
+        clock.advance(1_001);
+
+        // The first fill left the window, so 500 of notional is below the threshold
+        rest(&book, 2, 100, 5, Side::Sell);
+        assert!(pulls.lock().unwrap().is_empty());
+        rest(&book, 3, 100, 5, Side::Sell);
+        let pulled = pulls.lock().unwrap();
+        assert_eq!(pulled.len(), 1);
+        assert_eq!((pulled[0].fills, pulled[0].notional), (2, 1_000));
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_quotes_without_owner_are_not_protected() {
+        let (book, _clock, pulls) = protected(1, 0);
+        book.submit_quote("Q1", 99, 10, 101, 10).unwrap();
+        rest(&book, 1, 99, 1, Side::Sell);
+        assert!(pulls.lock().unwrap().is_empty());
+        assert!(book.quote("Q1").is_some());
+    }
+
+    #[test]
+    fn test_quote_id_cannot_change_owner() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.submit_quote_for("MM1", "Q1", 99, 10, 101, 10).unwrap();
+        assert!(matches!(
+            book.submit_quote_for("MM2", "Q1", 98, 10, 102, 10),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        assert!(matches!(
+            book.submit_quote("Q1", 98, 10, 102, 10),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        assert_eq!(book.quote("Q1").unwrap().owner.as_deref(), Some("MM1"));
+    }
+
+    type Shared = Arc<OnceLock<Weak<OrderBook>>>;
+
+    /// A shared book whose event listener calls `on_event` with it
+    fn reentrant(on_event: fn(&OrderBook, &OrderBookEvent)) -> Arc<OrderBook> {
+        let shared: Shared = Arc::new(OnceLock::new());
+        let handle = Arc::clone(&shared);
+        let mut book = OrderBook::new("TEST");
+        book.set_event_listener(Arc::new(move |event| {
+            if let Some(book) = handle.get().and_then(Weak::upgrade) {
+                on_event(&book, event);
+            }
+        }));
+        let book = Arc::new(book);
+        shared.set(Arc::downgrade(&book)).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_listener_can_quote_while_a_quote_is_replaced() {
+        // Another firm quotes as soon as it sees an order cancelled
+        let book = reentrant(|book, event| {
+            if let OrderBookEvent::OrderCancelled(_) = event
+                && book.quote("MM2").is_none()
+            {
+                book.submit_quote("MM2", 90, 5, 110, 5).unwrap();
+            }
+        });
+        book.submit_quote("MM1", 99, 10, 101, 10).unwrap();
+        let second = book.submit_quote("MM1", 98, 10, 102, 10).unwrap();
+        assert_eq!(book.quote("MM1"), Some(second));
+        assert!(book.quote("MM2").is_some());
+        assert_eq!(book.get_all_orders().len(), 4);
+
+        assert!(book.cancel_quote("MM1").unwrap().is_some());
+        assert_eq!(book.get_all_orders().len(), 2);
+    }
+
+    #[test]
+    fn test_quote_entered_again_by_a_listener_stands() {
+        // The firm requotes once it sees the bid of its first quote accepted
+        let book = reentrant(|book, event| {
+            if let OrderBookEvent::OrderAccepted(accepted) = event
+                && accepted.price == 99
+            {
+                book.submit_quote("MM1", 97, 5, 103, 5).unwrap();
+            }
+        });
+        assert!(matches!(
+            book.submit_quote("MM1", 99, 10, 101, 10),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        let quote = book.quote("MM1").unwrap();
+        assert_eq!(quote.bid.map(|bid| bid.price), Some(97));
+        assert_eq!(quote.ask.map(|ask| ask.price), Some(103));
+        assert_eq!(book.best_bid(), Some(97));
+        assert_eq!(book.best_ask(), Some(103));
+        assert_eq!(book.get_all_orders().len(), 2);
+    }
+}
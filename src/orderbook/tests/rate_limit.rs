@@ -0,0 +1,88 @@
+//! Unit tests for order entry rate limiting.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::rate_limit::{RateLimit, RateLimitScope, RateLimiter};
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+
+    fn order(id: u64) -> OrderType<()> {
+        OrderType::Standard {
+            id: OrderId::from_u64(id),
+            price: 100,
+            quantity: 1,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        }
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::global(RateLimit::new(2, 10));
+        assert_eq!(limiter.scope(), RateLimitScope::Global);
+
+        assert!(limiter.try_acquire(None, 1_000));
+        assert!(limiter.try_acquire(None, 1_000));
+        assert!(!limiter.try_acquire(None, 1_000));
+        // One token every 100ms
+        assert!(!limiter.try_acquire(None, 1_099));
+        assert!(limiter.try_acquire(None, 1_100));
+        // Refills stop at the burst size
+        assert!(limiter.try_acquire(None, 60_000));
+        assert!(limiter.try_acquire(None, 60_000));
+        assert!(!limiter.try_acquire(None, 60_000));
+    }
+
+    #[test]
+    fn test_accounts_have_their_own_buckets() {
+        let limiter = RateLimiter::per_account(RateLimit::new(1, 0));
+        assert!(limiter.try_acquire(Some("A"), 0));
+        assert!(!limiter.try_acquire(Some("A"), 0));
+        assert!(limiter.try_acquire(Some("B"), 0));
+        assert!(limiter.try_acquire(None, 0));
+        assert!(!limiter.try_acquire(None, 0));
+
+        limiter.reset();
+        assert!(limiter.try_acquire(Some("A"), 0));
+    }
+
+    #[test]
+    fn test_book_rejects_orders_over_the_limit() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_rate_limiter(RateLimiter::per_account(RateLimit::new(2, 0)));
+
+        assert!(book.add_order_for_account(order(1), "A").is_ok());
+        assert!(book.add_order_for_account(order(2), "A").is_ok());
+        match book.add_order_for_account(order(3), "A") {
+            Err(OrderBookError::RateLimited { account_id }) => {
+                assert_eq!(account_id.as_deref(), Some("A"));
+            }
+            other => panic!("expected a rate limit rejection, got {other:?}"),
+        }
+        assert!(book.add_order_for_account(order(4), "B").is_ok());
+        assert!(book.get_order(OrderId::from_u64(3)).is_none());
+        assert_eq!(book.stats().orders_rejected, 1);
+
+        book.clear_rate_limiter();
+        assert!(book.add_order_for_account(order(5), "A").is_ok());
+    }
+
+    #[test]
+    fn test_global_limit_covers_every_account() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_rate_limiter(RateLimiter::global(RateLimit::new(1, 0)));
+
+        assert!(book.add_order_for_account(order(1), "A").is_ok());
+        assert!(matches!(
+            book.add_order_for_account(order(2), "B"),
+            Err(OrderBookError::RateLimited { account_id: None })
+        ));
+        assert!(matches!(
+            book.add_order(order(3)),
+            Err(OrderBookError::RateLimited { .. })
+        ));
+    }
+}
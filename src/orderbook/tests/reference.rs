@@ -0,0 +1,183 @@
+//! Unit tests for the reference model and the checker comparing it with the book.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::testing::{
+        BookOp, ReferenceBook, ReferenceFill, ReferenceLevel, check_against_reference,
+    };
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn id(n: u64) -> OrderId {
+        OrderId::from_u64(n)
+    }
+
+    fn limit(n: u64, side: Side, price: u64, quantity: u64, tif: TimeInForce) -> BookOp {
+        BookOp::Limit {
+            id: id(n),
+            side,
+            price,
+            quantity,
+            time_in_force: tif,
+        }
+    }
+
+    #[test]
+    fn test_reference_matches_in_price_time_priority() {
+        let mut book = ReferenceBook::new();
+        book.add_limit_order(id(1), 101, 5, Side::Sell, TimeInForce::Gtc)
+            .unwrap();
+        book.add_limit_order(id(2), 100, 5, Side::Sell, TimeInForce::Gtc)
+            .unwrap();
+        book.add_limit_order(id(3), 100, 5, Side::Sell, TimeInForce::Gtc)
+            .unwrap();
+
+        let fills = book
+            .add_limit_order(id(4), 101, 12, Side::Buy, TimeInForce::Gtc)
+            .unwrap();
+        let makers: Vec<(OrderId, u64, u64)> = fills
+            .iter()
+            .map(|fill| (fill.maker_order_id, fill.price, fill.quantity))
+            .collect();
+        assert_eq!(
+            makers,
+            vec![(id(2), 100, 5), (id(3), 100, 5), (id(1), 101, 2)]
+        );
+        assert_eq!(
+            book.levels(),
+            vec![ReferenceLevel {
+                side: Side::Sell,
+                price: 101,
+                orders: vec![(id(1), 3)],
+            }]
+        );
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.order_count(), 1);
+    }
+
+    #[test]
+    fn test_reference_immediate_orders() {
+        let mut book = ReferenceBook::new();
+        book.add_limit_order(id(1), 100, 5, Side::Sell, TimeInForce::Gtc)
+            .unwrap();
+
+        // FOK that cannot fill completely leaves the book untouched
+        assert!(
+            book.add_limit_order(id(2), 100, 6, Side::Buy, TimeInForce::Fok)
+                .is_err()
+        );
+        assert_eq!(book.order_count(), 1);
+
+        // IOC keeps its fills but reports the unfilled remainder
+        assert!(
+            book.add_limit_order(id(3), 100, 3, Side::Buy, TimeInForce::Ioc)
+                .is_ok()
+        );
+        assert!(
+            book.add_limit_order(id(4), 100, 3, Side::Buy, TimeInForce::Ioc)
+                .is_err()
+        );
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.best_bid(), None);
+
+        assert!(book.submit_market_order(id(5), 1, Side::Buy).is_err());
+    }
+
+    #[test]
+    fn test_reference_cancel() {
+        let mut book = ReferenceBook::new();
+        book.add_limit_order(id(1), 99, 5, Side::Buy, TimeInForce::Gtc)
+            .unwrap();
+        assert_eq!(book.cancel_order(id(1)), Some(5));
+        assert_eq!(book.cancel_order(id(1)), None);
+        assert!(book.levels().is_empty());
+    }
+
+    #[test]
+    fn test_market_order_fills_are_reported() {
+        let mut book = ReferenceBook::new();
+        book.add_limit_order(id(1), 99, 5, Side::Buy, TimeInForce::Gtc)
+            .unwrap();
+        let outcome = book.apply(&BookOp::Market {
+            id: id(2),
+            side: Side::Sell,
+            quantity: 8,
+        });
+        assert!(outcome.accepted);
+        assert_eq!(
+            outcome.fills,
+            Some(vec![ReferenceFill {
+                maker_order_id: id(1),
+                price: 99,
+                quantity: 5,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_book_agrees_with_reference() {
+        let ops = vec![
+            limit(1, Side::Buy, 99, 10, TimeInForce::Gtc),
+            limit(2, Side::Buy, 100, 4, TimeInForce::Gtc),
+            limit(3, Side::Sell, 102, 6, TimeInForce::Gtc),
+            limit(4, Side::Sell, 101, 3, TimeInForce::Gtc),
+            limit(5, Side::Sell, 99, 20, TimeInForce::Fok),
+            limit(6, Side::Sell, 99, 6, TimeInForce::Ioc),
+            BookOp::Market {
+                id: id(7),
+                side: Side::Buy,
+                quantity: 5,
+            },
+            BookOp::Cancel { id: id(3) },
+            BookOp::Cancel { id: id(3) },
+            limit(8, Side::Sell, 98, 20, TimeInForce::Gtc),
+            BookOp::Market {
+                id: id(9),
+                side: Side::Sell,
+                quantity: 1,
+            },
+        ];
+        let book = OrderBook::<()>::new("TEST");
+        if let Err(divergence) = check_against_reference(&book, &ops) {
+            panic!("{divergence}");
+        }
+    }
+
+    #[test]
+    fn test_divergence_is_reported() {
+        let book = OrderBook::<()>::new("TEST");
+        // A resting order the reference model does not know about
+        book.add_limit_order(id(100), 100, 5, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        let ops = vec![BookOp::Market {
+            id: id(1),
+            side: Side::Buy,
+            quantity: 2,
+        }];
+
+        let divergence = check_against_reference(&book, &ops).unwrap_err();
+        assert_eq!(divergence.step, 0);
+        assert!(!divergence.expected.accepted);
+        assert!(divergence.actual.accepted);
+    }
+
+    #[cfg(feature = "proptest")]
+    mod properties {
+        use crate::orderbook::book::OrderBook;
+        use crate::orderbook::testing::check_against_reference;
+        use crate::orderbook::testing::strategies::op_sequence;
+        use proptest::prelude::*;
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn book_matches_reference_model(ops in op_sequence(60)) {
+                let book = OrderBook::<()>::new("PROP");
+                if let Err(divergence) = check_against_reference(&book, &ops) {
+                    prop_assert!(false, "{}", divergence);
+                }
+            }
+        }
+    }
+}
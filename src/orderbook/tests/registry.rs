@@ -0,0 +1,112 @@
+//! Unit tests for the symbol reference data registry.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::manager::OrderBookManager;
+    use crate::orderbook::registry::{SymbolInfo, SymbolRegistry};
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+    use std::sync::Arc;
+
+    fn btc() -> SymbolInfo {
+        SymbolInfo::new(50, 2, "USD")
+            .unwrap()
+            .with_lot_size(10)
+            .unwrap()
+            .with_multiplier(5)
+            .unwrap()
+    }
+
+    fn limit(price: u64, quantity: u64) -> OrderType<()> {
+        OrderType::Standard {
+            id: OrderId::new(),
+            price,
+            quantity,
+            side: Side::Buy,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            extra_fields: (),
+        }
+    }
+
+    #[test]
+    fn test_symbol_info_rejects_zero_sizes() {
+        assert!(SymbolInfo::new(0, 2, "USD").is_err());
+        let info = SymbolInfo::new(1, 2, "USD").unwrap();
+        assert!(info.clone().with_lot_size(0).is_err());
+        assert!(info.with_multiplier(0).is_err());
+    }
+
+    #[test]
+    fn test_validate_tick_and_lot() {
+        let info = btc();
+        assert!(info.validate(10_050, 20).is_ok());
+        assert!(matches!(
+            info.validate(10_025, 20),
+            Err(OrderBookError::InvalidPriceLevel(10_025))
+        ));
+        assert!(matches!(
+            info.validate(10_050, 15),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        assert!(info.validate(10_050, 0).is_err());
+    }
+
+    #[test]
+    fn test_notional_applies_multiplier_and_precision() {
+        let info = btc();
+        assert_eq!(info.notional(10_050, 20), 10_050 * 20 * 5);
+        assert!((info.notional_value(10_050, 20) - 10_050.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = SymbolRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.register("BTC", btc()).is_none());
+        registry.register("ETH", SymbolInfo::new(1, 2, "USD").unwrap());
+        assert_eq!(registry.symbols(), vec!["BTC", "ETH"]);
+        assert_eq!(registry.get("BTC").unwrap().currency, "USD");
+        // Symbols without reference data accept any order
+        assert!(registry.validate("SOL", 7, 3).is_ok());
+        assert!(registry.remove("ETH").is_some());
+        assert!(!registry.contains("ETH"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_manager_configures_books_from_registry() {
+        let registry = Arc::new(SymbolRegistry::new());
+        registry.register("BTC", btc());
+        let manager: OrderBookManager = OrderBookManager::with_registry(Arc::clone(&registry));
+
+        let book = manager.add_book("BTC");
+        assert_eq!(book.price_scale(), btc().price_scale());
+        assert_eq!(book.options().options.round_lot, 10);
+        assert_eq!(manager.notional("BTC", 10_050, 20), Some(10_050 * 20 * 5));
+        assert_eq!(manager.notional("ETH", 100, 1), None);
+        let notional = book.stats().notional.unwrap();
+        assert_eq!(notional.currency, btc().currency);
+        assert_eq!(notional.multiplier, 5);
+        assert_eq!(manager.add_book("ETH").stats().notional, None);
+    }
+
+    #[test]
+    fn test_manager_add_order_validates_against_registry() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        manager.registry().register("BTC", btc());
+        manager.add_book("BTC");
+
+        assert!(manager.add_order("BTC", limit(10_050, 20)).is_ok());
+        assert!(matches!(
+            manager.add_order("BTC", limit(10_010, 20)),
+            Err(OrderBookError::InvalidPriceLevel(10_010))
+        ));
+        assert!(manager.add_order("BTC", limit(10_050, 5)).is_err());
+        assert!(matches!(
+            manager.add_order("ETH", limit(100, 1)),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        assert_eq!(manager.get_book("BTC").unwrap().best_bid(), Some(10_050));
+    }
+}
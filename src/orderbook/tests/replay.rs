@@ -0,0 +1,159 @@
+//! Unit tests for rebuilding a book from an operation log.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::replay::{ReplayEngine, ReplayOperation, ReplayRecord, ReplayStop};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn limit(sequence: u64, id: u64, price: u64, quantity: u64, side: Side) -> ReplayRecord {
+        ReplayRecord {
+            sequence,
+            operation: ReplayOperation::AddLimit {
+                order_id: OrderId::from_u64(id),
+                price,
+                quantity,
+                side,
+                time_in_force: TimeInForce::Gtc,
+            },
+        }
+    }
+
+    fn log() -> Vec<ReplayRecord> {
+        vec![
+            limit(10, 1, 100, 10, Side::Buy),
+            limit(11, 2, 105, 5, Side::Sell),
+            ReplayRecord {
+                sequence: 12,
+                operation: ReplayOperation::AddIceberg {
+                    order_id: OrderId::from_u64(3),
+                    price: 99,
+                    visible_quantity: 2,
+                    hidden_quantity: 8,
+                    side: Side::Buy,
+                    time_in_force: TimeInForce::Gtc,
+                },
+            },
+            ReplayRecord {
+                sequence: 13,
+                operation: ReplayOperation::Market {
+                    order_id: OrderId::from_u64(4),
+                    quantity: 4,
+                    side: Side::Sell,
+                },
+            },
+            ReplayRecord {
+                sequence: 14,
+                operation: ReplayOperation::UpdatePrice {
+                    order_id: OrderId::from_u64(2),
+                    new_price: 104,
+                },
+            },
+            ReplayRecord {
+                sequence: 15,
+                operation: ReplayOperation::Cancel {
+                    order_id: OrderId::from_u64(1),
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_replay_rebuilds_the_book() {
+        let mut engine: ReplayEngine = ReplayEngine::new("TEST", log());
+        assert_eq!(engine.run(), ReplayStop::Finished);
+        assert!(engine.is_finished());
+        assert!(engine.failed_sequences().is_empty());
+
+        let book = engine.into_book();
+        assert_eq!(book.best_bid(), Some(99));
+        assert_eq!(book.best_ask(), Some(104));
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+    }
+
+    #[test]
+    fn test_stepping_and_breakpoints() {
+        let mut engine: ReplayEngine = ReplayEngine::new("TEST", log());
+        engine.add_breakpoint(13);
+        engine.add_breakpoint(15);
+
+        let step = engine.step().unwrap();
+        assert_eq!(step.sequence, 10);
+        assert!(step.result.is_ok());
+
+        assert_eq!(engine.run(), ReplayStop::Breakpoint(13));
+        assert_eq!(engine.applied(), 3);
+        assert_eq!(engine.next_sequence(), Some(13));
+        assert_eq!(
+            engine
+                .book()
+                .get_order(OrderId::from_u64(1))
+                .map(|order| order.visible_quantity()),
+            Some(10)
+        );
+
+        // Resuming applies the record the replay stopped on
+        assert_eq!(engine.run(), ReplayStop::Breakpoint(15));
+        assert_eq!(
+            engine
+                .book()
+                .get_order(OrderId::from_u64(1))
+                .map(|order| order.visible_quantity()),
+            Some(6)
+        );
+
+        assert!(engine.remove_breakpoint(15));
+        assert_eq!(engine.run(), ReplayStop::Finished);
+        assert!(engine.step().is_none());
+    }
+
+    #[test]
+    fn test_run_until_ignores_breakpoints() {
+        let mut engine: ReplayEngine = ReplayEngine::new("TEST", log());
+        engine.add_breakpoint(11);
+        assert_eq!(engine.run_until(12), 3);
+        assert_eq!(engine.next_sequence(), Some(13));
+        assert_eq!(engine.run_until(12), 0);
+    }
+
+    #[test]
+    fn test_failed_operations_are_recorded_and_skipped() {
+        let records = vec![
+            limit(1, 1, 100, 10, Side::Buy),
+            ReplayRecord {
+                sequence: 2,
+                operation: ReplayOperation::Cancel {
+                    order_id: OrderId::from_u64(99),
+                },
+            },
+            limit(3, 2, 101, 10, Side::Buy),
+        ];
+        let mut engine: ReplayEngine = ReplayEngine::new("TEST", records);
+        engine.step();
+        let failed = engine.step().unwrap();
+        assert!(matches!(
+            failed.result,
+            Err(OrderBookError::OrderNotFound(_))
+        ));
+        assert_eq!(engine.run(), ReplayStop::Finished);
+        assert_eq!(engine.failed_sequences(), &[2]);
+        assert_eq!(engine.book().best_bid(), Some(101));
+    }
+
+    #[test]
+    fn test_json_lines_log() {
+        let input: String = log()
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap() + "\n")
+            .collect();
+        assert!(input.contains(r#""type":"add_limit""#));
+
+        let mut engine: ReplayEngine = ReplayEngine::from_json_lines("TEST", &input).unwrap();
+        assert_eq!(engine.run(), ReplayStop::Finished);
+        assert_eq!(engine.book().best_ask(), Some(104));
+
+        let broken = format!("{input}\n{{\"sequence\": 16}}\n");
+        let error = ReplayRecord::parse_json_lines(&broken).unwrap_err();
+        assert!(error.to_string().contains("line 8"), "{error}");
+    }
+}
@@ -0,0 +1,180 @@
+//! Unit tests for reserve order replenishment during matching.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::modifications::OrderQuantity;
+    use crate::orderbook::options::ReserveRefresh;
+    use pricelevel::{OrderId, OrderType, Side, TimeInForce};
+
+    fn reserve(
+        id: u64,
+        visible: u64,
+        hidden: u64,
+        threshold: u64,
+        amount: Option<u64>,
+        auto_replenish: bool,
+    ) -> OrderType<()> {
+        OrderType::ReserveOrder {
+            id: OrderId::from_u64(id),
+            price: 100,
+            visible_quantity: visible,
+            hidden_quantity: hidden,
+            side: Side::Sell,
+            timestamp: 0,
+            time_in_force: TimeInForce::Gtc,
+            replenish_threshold: threshold,
+            replenish_amount: amount,
+            auto_replenish,
+            extra_fields: (),
+        }
+    }
+
+    fn sell(book: &OrderBook, id: u64, price: u64, quantity: u64) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    fn buy(book: &OrderBook, id: u64, quantity: u64) {
+        book.submit_market_order(OrderId::from_u64(id), quantity, Side::Buy)
+            .unwrap();
+    }
+
+    /// Ids and visible quantities of the sell queue at 100, front first
+    fn queue(book: &OrderBook) -> Vec<(OrderId, u64)> {
+        book.get_orders_at_price(100, Side::Sell)
+            .iter()
+            .map(|order| (order.id(), order.visible_quantity()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reserve_replenishes_below_threshold_and_loses_priority() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order(reserve(1, 10, 30, 4, Some(10), true))
+            .unwrap();
+        sell(&book, 2, 100, 10);
+
+        buy(&book, 10, 7);
+        assert_eq!(
+            queue(&book),
+            vec![(OrderId::from_u64(2), 10), (OrderId::from_u64(1), 13)]
+        );
+        let refreshed = book.get_order(OrderId::from_u64(1)).unwrap();
+        assert_eq!(refreshed.hidden_quantity(), 20);
+    }
+
+    #[test]
+    fn test_reserve_replenish_can_keep_its_place() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.set_reserve_refresh(ReserveRefresh::KeepPlace);
+        book.add_order(reserve(1, 10, 30, 4, None, true)).unwrap();
+        sell(&book, 2, 100, 10);
+        sell(&book, 3, 100, 10);
+
+        buy(&book, 10, 7);
+        // Without a replenish amount the display is restored to its entered size
+        assert_eq!(
+            queue(&book),
+            vec![
+                (OrderId::from_u64(1), 10),
+                (OrderId::from_u64(2), 10),
+                (OrderId::from_u64(3), 10),
+            ]
+        );
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .hidden_quantity(),
+            23
+        );
+
+        buy(&book, 11, 3);
+        assert_eq!(
+            queue(&book),
+            vec![
+                (OrderId::from_u64(1), 7),
+                (OrderId::from_u64(2), 10),
+                (OrderId::from_u64(3), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserve_without_auto_replenish_waits_for_exhaustion() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order(reserve(1, 10, 30, 4, Some(10), false))
+            .unwrap();
+        sell(&book, 2, 100, 10);
+
+        buy(&book, 10, 7);
+        assert_eq!(
+            queue(&book),
+            vec![(OrderId::from_u64(1), 3), (OrderId::from_u64(2), 10)]
+        );
+
+        // The reduced reserve still trades first
+        buy(&book, 11, 2);
+        assert_eq!(
+            queue(&book),
+            vec![(OrderId::from_u64(1), 1), (OrderId::from_u64(2), 10)]
+        );
+    }
+
+    #[test]
+    fn test_sweep_keeps_trading_a_refreshed_reserve_at_its_level() {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order(reserve(1, 5, 10, 1, None, true)).unwrap();
+        sell(&book, 2, 101, 10);
+
+        let result = book
+            .submit_market_order(OrderId::from_u64(10), 12, Side::Buy)
+            .unwrap();
+        // Every lot trades at 100 before the sweep moves to 101
+        assert!(
+            result
+                .transactions
+                .as_vec()
+                .iter()
+                .all(|trade| trade.price == 100)
+        );
+        assert_eq!(result.executed_quantity(), 12);
+        assert_eq!(book.best_ask(), Some(100));
+        assert_eq!(
+            book.get_order(OrderId::from_u64(1))
+                .unwrap()
+                .total_quantity(),
+            3
+        );
+        assert_eq!(book.get_orders_at_price(101, Side::Sell).len(), 1);
+    }
+
+    #[test]
+    fn test_refresh_priority_mid_sweep() {
+        for (refresh, expected) in [
+            (
+                ReserveRefresh::BackOfQueue,
+                vec![(OrderId::from_u64(2), 7), (OrderId::from_u64(1), 5)],
+            ),
+            (
+                ReserveRefresh::KeepPlace,
+                vec![(OrderId::from_u64(1), 2), (OrderId::from_u64(2), 10)],
+            ),
+        ] {
+            let book: OrderBook = OrderBook::new("TEST");
+            book.set_reserve_refresh(refresh);
+            book.add_order(reserve(1, 5, 10, 1, None, true)).unwrap();
+            sell(&book, 2, 100, 10);
+
+            buy(&book, 10, 8);
+            assert_eq!(queue(&book), expected, "{refresh:?}");
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Unit tests for the acceptance sequence of resting orders.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, OrderUpdate, Side, TimeInForce};
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::thread;
+
+    const THREADS: u64 = 8;
+    const ORDERS_PER_THREAD: u64 = 50;
+
+    #[test]
+    fn test_sequence_numbers_resting_orders() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        for id in 1..=3 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100 + id,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+        assert_eq!(book.order_sequence(OrderId::from_u64(2)), Some(2));
+        assert_eq!(book.last_sequence(), 3);
+
+        // Fully filled takers never rest, so they are not numbered
+        book.add_limit_order(
+            OrderId::from_u64(4),
+            101,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        assert_eq!(book.order_sequence(OrderId::from_u64(1)), None);
+        assert_eq!(book.order_sequence(OrderId::from_u64(4)), None);
+        assert_eq!(book.last_sequence(), 3);
+
+        // A price change loses priority and is numbered again
+        book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(2),
+            new_price: 105,
+        })
+        .unwrap();
+        assert_eq!(book.order_sequence(OrderId::from_u64(2)), Some(4));
+
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        assert_eq!(book.order_sequence(OrderId::from_u64(3)), None);
+    }
+
+    #[test]
+    fn test_fills_follow_acceptance_sequence_across_threads() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        let barrier = Barrier::new(THREADS as usize);
+
+        thread::scope(|scope| {
+            for thread_index in 0..THREADS {
+                let (book, barrier) = (&book, &barrier);
+                scope.spawn(move || {
+                    barrier.wait();
+                    for i in 0..ORDERS_PER_THREAD {
+                        book.add_limit_order(
+                            OrderId::from_u64(thread_index * 1000 + i),
+                            100,
+                            1,
+                            Side::Sell,
+                            TimeInForce::Gtc,
+                            None,
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+
+        let total = THREADS * ORDERS_PER_THREAD;
+        let mut accepted: Vec<(u64, OrderId)> = (0..THREADS)
+            .flat_map(|t| (0..ORDERS_PER_THREAD).map(move |i| OrderId::from_u64(t * 1000 + i)))
+            .map(|id| (book.order_sequence(id).unwrap(), id))
+            .collect();
+        accepted.sort_unstable_by_key(|(sequence, _)| *sequence);
+        let sequences: Vec<u64> = accepted.iter().map(|(sequence, _)| *sequence).collect();
+        assert_eq!(sequences, (1..=total).collect::<Vec<_>>());
+
+        let result = book
+            .match_order(OrderId::from_u64(u64::MAX), Side::Buy, total, None)
+            .unwrap();
+        let fill_order: Vec<OrderId> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|transaction| transaction.maker_order_id)
+            .collect();
+        let acceptance_order: Vec<OrderId> = accepted.into_iter().map(|(_, id)| id).collect();
+        assert_eq!(fill_order, acceptance_order);
+    }
+
+    #[test]
+    fn test_acceptance_times_are_unique_while_the_clock_stands_still() {
+        let mut book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.set_clock(Arc::new(ManualClock::new(1_000)));
+        for id in 1..=3 {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                100,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+
+        let times: Vec<u64> = (1..=3)
+            .map(|id| book.accepted_at_nanos(OrderId::from_u64(id)).unwrap())
+            .collect();
+        assert_eq!(times, vec![1_000_000_000, 1_000_000_001, 1_000_000_002]);
+
+        let snapshot = book.create_l3_snapshot(1);
+        let snapshot_times: Vec<Option<u64>> = snapshot
+            .orders()
+            .map(|order| order.accepted_at_nanos)
+            .collect();
+        assert_eq!(
+            snapshot_times,
+            times.into_iter().map(Some).collect::<Vec<_>>()
+        );
+        assert_eq!(book.accepted_at_nanos(OrderId::from_u64(9)), None);
+    }
+
+    #[test]
+    fn test_acceptance_times_follow_queue_order_across_threads() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        let barrier = Barrier::new(THREADS as usize);
+
+        thread::scope(|scope| {
+            for thread_index in 0..THREADS {
+                let (book, barrier) = (&book, &barrier);
+                scope.spawn(move || {
+                    barrier.wait();
+                    for i in 0..ORDERS_PER_THREAD {
+                        book.add_limit_order(
+                            OrderId::from_u64(thread_index * 1000 + i),
+                            100,
+                            1,
+                            Side::Sell,
+                            TimeInForce::Gtc,
+                            None,
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+
+        let snapshot = book.create_l3_snapshot(1);
+        let times: Vec<u64> = snapshot
+            .orders()
+            .map(|order| order.accepted_at_nanos.unwrap())
+            .collect();
+        assert_eq!(times.len() as u64, THREADS * ORDERS_PER_THREAD);
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_accepted_event_carries_sequence_and_time() {
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&accepted);
+        let mut book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderAccepted(order) = event {
+                recorded.lock().unwrap().push(*order);
+            }
+        }));
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            4,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        // Trades 4 on entry and rests the other 6
+        let order_id = OrderId::from_u64(2);
+        book.add_limit_order(order_id, 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let accepted = accepted.lock().unwrap();
+        assert_eq!(accepted.len(), 2);
+        let order = accepted[1];
+        assert_eq!(order.order_id, order_id);
+        assert_eq!(order.quantity, 6);
+        assert_eq!(order.executed_quantity, 4);
+        assert_eq!(Some(order.sequence), book.order_sequence(order_id));
+        assert_eq!(
+            Some(order.accepted_at_nanos),
+            book.accepted_at_nanos(order_id)
+        );
+        assert!(order.accepted_at_nanos > accepted[0].accepted_at_nanos);
+    }
+
+    fn sell(book: &OrderBook, id: u64, price: u64, quantity: u64) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            quantity,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    fn maker_of_buy(book: &OrderBook, id: u64, quantity: u64) -> OrderId {
+        let result = book
+            .match_order(OrderId::from_u64(id), Side::Buy, quantity, None)
+            .unwrap();
+        result.transactions.as_vec()[0].maker_order_id
+    }
+
+    #[test]
+    fn test_partially_filled_maker_keeps_its_priority() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        sell(&book, 1, 101, 11);
+        sell(&book, 2, 101, 1);
+
+        assert_eq!(maker_of_buy(&book, 3, 1), OrderId::from_u64(1));
+        assert_eq!(maker_of_buy(&book, 4, 1), OrderId::from_u64(1));
+
+        // Back on the level's own matching once the maker has left
+        assert!(!book.asks.is_fifo(101));
+        assert_eq!(maker_of_buy(&book, 5, 9), OrderId::from_u64(1));
+        assert!(book.asks.is_fifo(101));
+        assert_eq!(maker_of_buy(&book, 6, 1), OrderId::from_u64(2));
+    }
+
+    #[test]
+    fn test_fifo_matching_is_decided_per_level() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        sell(&book, 1, 101, 10);
+        sell(&book, 2, 101, 10);
+        book.add_iceberg_order(
+            OrderId::from_u64(3),
+            102,
+            5,
+            20,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        book.add_all_or_none_order(
+            OrderId::from_u64(4),
+            103,
+            50,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        assert!(book.asks.is_fifo(101));
+        assert!(!book.asks.is_fifo(102));
+        assert!(!book.asks.is_fifo(103));
+
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        assert!(book.asks.is_fifo(102));
+        book.cancel_order(OrderId::from_u64(4)).unwrap();
+        assert!(book.asks.is_fifo(103));
+    }
+
+    /// Ids of the queue kept for the ask level at `price`, put back after reading
+    fn kept_queue(book: &OrderBook, price: u64) -> Option<Vec<OrderId>> {
+        let queue = book.asks.take_queue(price)?;
+        let ids = queue.iter().map(|order| order.id()).collect();
+        book.asks.keep_queue(price, queue);
+        Some(ids)
+    }
+
+    #[test]
+    fn test_irregular_level_keeps_its_queue_between_matches() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.add_all_or_none_order(
+            OrderId::from_u64(1),
+            101,
+            50,
+            Side::Sell,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+        sell(&book, 2, 101, 10);
+        sell(&book, 3, 101, 10);
+        assert_eq!(kept_queue(&book, 101), None);
+
+        // The all-or-none order is passed over and keeps its place
+        assert_eq!(maker_of_buy(&book, 10, 1), OrderId::from_u64(2));
+        let ids = |ids: &[u64]| Some(ids.iter().copied().map(OrderId::from_u64).collect());
+        assert_eq!(kept_queue(&book, 101), ids(&[1, 2, 3]));
+
+        // New orders join the kept queue at the back
+        sell(&book, 4, 101, 10);
+        assert_eq!(kept_queue(&book, 101), ids(&[1, 2, 3, 4]));
+
+        // Any other change drops it, and the next match sorts the level again
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        assert_eq!(kept_queue(&book, 101), None);
+        assert_eq!(maker_of_buy(&book, 11, 9), OrderId::from_u64(2));
+        assert_eq!(kept_queue(&book, 101), ids(&[1, 4]));
+        assert_eq!(maker_of_buy(&book, 12, 10), OrderId::from_u64(4));
+    }
+
+    #[test]
+    fn test_order_repriced_back_to_its_level_queues_behind() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        sell(&book, 1, 101, 10);
+        sell(&book, 2, 101, 10);
+
+        for new_price in [102, 101] {
+            book.update_order(OrderUpdate::UpdatePrice {
+                order_id: OrderId::from_u64(1),
+                new_price,
+            })
+            .unwrap();
+        }
+
+        // The order's first entry is still in the level's queue, ahead of order 2
+        assert_eq!(maker_of_buy(&book, 3, 10), OrderId::from_u64(2));
+        assert_eq!(maker_of_buy(&book, 4, 10), OrderId::from_u64(1));
+    }
+}
@@ -0,0 +1,400 @@
+//! Unit tests for the session schedule, trading states and auctions.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::execution_report::OrderStatus;
+    use crate::orderbook::session::{AuctionTimeInForce, SessionSchedule, TradingState};
+    use crate::orderbook::tests::helpers::{record_trades, rest};
+    use crate::orderbook::trade::TradeCondition;
+    use crate::utils::ManualClock;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    const PRE_OPEN: u64 = 1_000;
+    const OPEN: u64 = 2_000;
+    const PRE_CLOSE: u64 = 8_000;
+    const CLOSE: u64 = 9_000;
+
+    fn scheduled_book(now: u64) -> (OrderBook<()>, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(now));
+        let mut book = OrderBook::new("TEST");
+        book.set_clock(clock.clone());
+        book.set_session_schedule(
+            SessionSchedule::new(PRE_OPEN, OPEN, CLOSE).with_pre_close(PRE_CLOSE),
+        );
+        book.advance_session();
+        (book, clock)
+    }
+
+    #[test]
+    fn test_schedule_state_at() {
+        let schedule = SessionSchedule::new(PRE_OPEN, OPEN, CLOSE);
+        assert_eq!(schedule.state_at(0), TradingState::Closed);
+        assert_eq!(schedule.state_at(PRE_OPEN), TradingState::PreOpen);
+        assert_eq!(schedule.state_at(OPEN), TradingState::Open);
+        assert_eq!(schedule.state_at(PRE_CLOSE), TradingState::Open);
+        assert_eq!(schedule.state_at(CLOSE), TradingState::PostClose);
+
+        let schedule = schedule.with_pre_close(PRE_CLOSE);
+        assert_eq!(schedule.state_at(PRE_CLOSE), TradingState::PreClose);
+        assert_eq!(schedule.state_at(CLOSE - 1), TradingState::PreClose);
+    }
+
+    #[test]
+    fn test_unscheduled_book_trades_continuously() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        assert_eq!(book.trading_state(), TradingState::Open);
+        assert!(book.advance_session().is_empty());
+    }
+
+    #[test]
+    fn test_closed_book_rejects_orders() {
+        let (book, _clock) = scheduled_book(0);
+        assert_eq!(book.trading_state(), TradingState::Closed);
+
+        let result = book.add_limit_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(OrderBookError::InvalidTradingState {
+                state: TradingState::Closed
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pre_open_collects_orders_without_matching() {
+        let (book, _clock) = scheduled_book(PRE_OPEN);
+        assert_eq!(book.trading_state(), TradingState::PreOpen);
+
+        rest(&book, 1, 105, 10, Side::Buy);
+        rest(&book, 2, 100, 10, Side::Sell);
+        assert_eq!(book.best_bid(), Some(105));
+        assert_eq!(book.best_ask(), Some(100));
+        assert_eq!(book.last_trade_price(), None);
+
+        let ioc = book.add_limit_order(
+            OrderId::from_u64(3),
+            100,
+            5,
+            Side::Buy,
+            TimeInForce::Ioc,
+            None,
+        );
+        assert!(matches!(
+            ioc,
+            Err(OrderBookError::InvalidTradingState { .. })
+        ));
+        assert!(matches!(
+            book.submit_market_order(OrderId::from_u64(4), 5, Side::Buy),
+            Err(OrderBookError::InvalidTradingState { .. })
+        ));
+    }
+
+    #[test]
+    fn test_auction_equilibrium_maximizes_volume() {
+        let (book, _clock) = scheduled_book(PRE_OPEN);
+        rest(&book, 1, 103, 10, Side::Buy);
+        rest(&book, 2, 101, 20, Side::Buy);
+        rest(&book, 3, 100, 15, Side::Sell);
+        rest(&book, 4, 102, 10, Side::Sell);
+
+        let equilibrium = book.auction_equilibrium().unwrap();
+        // 100 and 101 both trade 15 leaving 15 unmatched; 102 and 103 trade 10.
+        // Without a last trade price the lower of the tied prices wins
+        assert_eq!(equilibrium.volume, 15);
+        assert_eq!(equilibrium.price, 100);
+        assert_eq!(equilibrium.imbalance(), 15);
+    }
+
+    #[test]
+    fn test_opening_auction_uncrosses_at_a_single_price() {
+        let (mut book, clock) = scheduled_book(PRE_OPEN);
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&transitions);
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded_reports = Arc::clone(&reports);
+        book.set_event_listener(Arc::new(move |event| match event {
+            OrderBookEvent::TradingStateChanged(transition) => {
+                recorded.lock().unwrap().push(transition.clone())
+            }
+            OrderBookEvent::TradeExecuted(report) => recorded_reports.lock().unwrap().push(*report),
+            _ => {}
+        }));
+        rest(&book, 1, 103, 10, Side::Buy);
+        rest(&book, 2, 101, 20, Side::Buy);
+        rest(&book, 3, 100, 15, Side::Sell);
+        rest(&book, 4, 102, 10, Side::Sell);
+
+        clock.set(OPEN);
+        let applied = book.advance_session();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(book.trading_state(), TradingState::Open);
+
+        let auction = applied[0].auction.as_ref().unwrap();
+        assert_eq!(auction.equilibrium.price, 100);
+        let traded: u64 = auction.trades.iter().map(|trade| trade.quantity).sum();
+        assert_eq!(traded, 15);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), auction.trades.len());
+        for (trade, report) in auction.trades.iter().zip(reports.iter()) {
+            assert_eq!(trade.price, 100);
+            assert_eq!(report.transaction, *trade);
+            assert!(report.conditions.contains(TradeCondition::AuctionCross));
+        }
+        assert!(reports[0].conditions.contains(TradeCondition::OpeningTrade));
+
+        // Bid 1 filled in full, bid 2 keeps the remaining 15 at its own price
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert!(book.get_order(OrderId::from_u64(3)).is_none());
+        assert_eq!(book.executed_so_far(OrderId::from_u64(2)), Some(5));
+        assert_eq!(book.best_bid(), Some(101));
+        assert_eq!(book.best_ask(), Some(102));
+        assert_eq!(book.last_trade_price(), Some(100));
+        assert_eq!(transitions.lock().unwrap().as_slice(), applied.as_slice());
+    }
+
+    #[test]
+    fn test_each_day_opens_with_an_opening_trade() {
+        let (mut book, clock) = scheduled_book(PRE_OPEN);
+        let reports = record_trades(&mut book);
+        rest(&book, 1, 101, 10, Side::Buy);
+        rest(&book, 2, 100, 10, Side::Sell);
+        clock.set(OPEN);
+        book.advance_session();
+        rest(&book, 3, 102, 5, Side::Sell);
+        rest(&book, 4, 102, 5, Side::Buy);
+
+        // The next day's opening auction opens it again
+        clock.set(CLOSE);
+        book.advance_session();
+        book.set_session_schedule(SessionSchedule::new(
+            CLOSE + PRE_OPEN,
+            CLOSE + OPEN,
+            CLOSE + CLOSE,
+        ));
+        clock.set(CLOSE + PRE_OPEN);
+        book.advance_session();
+        rest(&book, 5, 101, 10, Side::Buy);
+        rest(&book, 6, 100, 10, Side::Sell);
+        clock.set(CLOSE + OPEN);
+        book.advance_session();
+
+        let opening: Vec<bool> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|report| report.conditions.contains(TradeCondition::OpeningTrade))
+            .collect();
+        assert_eq!(opening, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_order_entry_advances_the_session() {
+        let (book, clock) = scheduled_book(PRE_OPEN);
+        rest(&book, 1, 100, 10, Side::Sell);
+
+        clock.set(OPEN);
+        // Entered in continuous trading, so it matches right away
+        let order = book
+            .add_limit_order(
+                OrderId::from_u64(2),
+                100,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        assert_eq!(book.trading_state(), TradingState::Open);
+        assert_eq!(order.visible_quantity(), 10);
+        assert!(book.get_order(OrderId::from_u64(1)).is_none());
+        assert!(book.best_bid().is_none());
+    }
+
+    #[test]
+    fn test_close_runs_closing_auction_and_expires_day_orders() {
+        let (book, clock) = scheduled_book(OPEN);
+        assert_eq!(book.options().options.market_close_timestamp, Some(CLOSE));
+        book.add_limit_order(
+            OrderId::from_u64(1),
+            90,
+            10,
+            Side::Buy,
+            TimeInForce::Day,
+            None,
+        )
+        .unwrap();
+
+        clock.set(PRE_CLOSE);
+        book.advance_session();
+        assert_eq!(book.trading_state(), TradingState::PreClose);
+        rest(&book, 2, 101, 10, Side::Buy);
+        rest(&book, 3, 99, 4, Side::Sell);
+
+        clock.set(CLOSE);
+        let applied = book.advance_session();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].to, TradingState::PostClose);
+        let auction = applied[0].auction.as_ref().unwrap();
+        assert_eq!(auction.equilibrium.volume, 4);
+        assert_eq!(applied[0].expired.len(), 1);
+        assert_eq!(applied[0].expired[0].order_id, OrderId::from_u64(1));
+        assert!(!book.trading_state().accepts_orders());
+    }
+
+    #[test]
+    fn test_late_schedule_passes_through_every_phase() {
+        let (book, clock) = scheduled_book(0);
+        clock.set(CLOSE);
+        let states: Vec<TradingState> = book
+            .advance_session()
+            .iter()
+            .map(|transition| transition.to)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                TradingState::PreOpen,
+                TradingState::Open,
+                TradingState::PreClose,
+                TradingState::PostClose,
+            ]
+        );
+
+        // The next day's schedule moves the book back to before its pre-open
+        book.set_session_schedule(SessionSchedule::new(
+            CLOSE + PRE_OPEN,
+            CLOSE + OPEN,
+            CLOSE + CLOSE,
+        ));
+        book.advance_session();
+        assert_eq!(book.trading_state(), TradingState::Closed);
+    }
+
+    #[test]
+    fn test_clear_schedule_returns_to_continuous_trading() {
+        let (book, _clock) = scheduled_book(0);
+        book.clear_session_schedule();
+        assert_eq!(book.trading_state(), TradingState::Open);
+        assert!(book.session_schedule().is_none());
+        rest(&book, 1, 100, 10, Side::Buy);
+    }
+
+    #[test]
+    fn test_auction_orders_only_enter_their_call() {
+        let (book, clock) = scheduled_book(0);
+        let entered = |id: u64, auction: AuctionTimeInForce| {
+            book.add_auction_order(OrderId::from_u64(id), 100, 10, Side::Buy, auction, None)
+        };
+        assert!(matches!(
+            entered(1, AuctionTimeInForce::GoodForAuction),
+            Err(OrderBookError::InvalidTradingState {
+                state: TradingState::Closed
+            })
+        ));
+
+        clock.set(PRE_OPEN);
+        book.advance_session();
+        assert!(entered(2, AuctionTimeInForce::GoodForAuction).is_ok());
+        assert!(entered(3, AuctionTimeInForce::AtTheOpen).is_ok());
+        assert!(entered(4, AuctionTimeInForce::AtTheClose).is_err());
+
+        clock.set(OPEN);
+        book.advance_session();
+        assert!(entered(5, AuctionTimeInForce::GoodForAuction).is_err());
+        assert!(entered(6, AuctionTimeInForce::AtTheOpen).is_err());
+
+        clock.set(PRE_CLOSE);
+        book.advance_session();
+        assert!(entered(7, AuctionTimeInForce::AtTheOpen).is_err());
+        assert!(entered(8, AuctionTimeInForce::AtTheClose).is_ok());
+    }
+
+    #[test]
+    fn test_auction_orders_expire_with_their_call() {
+        let (book, clock) = scheduled_book(PRE_OPEN);
+        let expired_events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&expired_events);
+        let mut book = book;
+        book.set_event_listener(Arc::new(move |event: &OrderBookEvent| {
+            if let OrderBookEvent::OrderExpired(expired) = event {
+                recorded.lock().unwrap().push(expired.auction);
+            }
+        }));
+        book.add_auction_order(
+            OrderId::from_u64(1),
+            101,
+            10,
+            Side::Buy,
+            AuctionTimeInForce::AtTheOpen,
+            None,
+        )
+        .unwrap();
+        book.add_auction_order(
+            OrderId::from_u64(2),
+            98,
+            10,
+            Side::Buy,
+            AuctionTimeInForce::GoodForAuction,
+            None,
+        )
+        .unwrap();
+        rest(&book, 3, 97, 10, Side::Buy);
+        rest(&book, 4, 100, 4, Side::Sell);
+
+        clock.set(OPEN);
+        let applied = book.advance_session();
+        assert_eq!(applied[0].auction.as_ref().unwrap().equilibrium.volume, 4);
+        // Partly executed and never reached, both auction orders expire; the
+        // regular order carries on into continuous trading
+        let expired: Vec<_> = applied[0]
+            .expired
+            .iter()
+            .map(|order| (order.order_id, order.quantity, order.executed_quantity))
+            .collect();
+        assert_eq!(
+            expired,
+            vec![(OrderId::from_u64(1), 6, 4), (OrderId::from_u64(2), 10, 0),]
+        );
+        assert_eq!(
+            applied[0].expired[0].auction,
+            Some(AuctionTimeInForce::AtTheOpen)
+        );
+        assert_eq!(expired_events.lock().unwrap().len(), 2);
+        assert_eq!(
+            book.order_status(OrderId::from_u64(1)),
+            OrderStatus::Expired
+        );
+        assert_eq!(book.best_bid(), Some(97));
+    }
+
+    #[test]
+    fn test_clearing_schedule_expires_auction_orders() {
+        let (book, _clock) = scheduled_book(PRE_OPEN);
+        book.add_auction_order(
+            OrderId::from_u64(1),
+            100,
+            10,
+            Side::Sell,
+            AuctionTimeInForce::GoodForAuction,
+            None,
+        )
+        .unwrap();
+        book.clear_session_schedule();
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(
+            book.order_status(OrderId::from_u64(1)),
+            OrderStatus::Expired
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Unit tests for the shard-per-symbol executor.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::engine::{Command, CommandOutcome};
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::manager::OrderBookManager;
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, Side};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_symbol_always_maps_to_the_same_shard() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        let executor = manager.start_executor(4, 16);
+        assert_eq!(executor.shard_count(), 4);
+        for symbol in ["BTC-USD", "ETH-USD", "SOL-USD"] {
+            let shard = executor.shard_of(symbol);
+            assert!(shard < 4);
+            assert_eq!(executor.shard_of(symbol), shard);
+        }
+    }
+
+    #[test]
+    fn test_call_runs_on_the_symbol_shard() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        manager.add_book("BTC-USD");
+        let executor = manager.start_executor(2, 16);
+
+        let first = executor
+            .call("BTC-USD", |_| thread::current().name().map(str::to_string))
+            .unwrap();
+        let second = executor
+            .call("BTC-USD", |_| thread::current().name().map(str::to_string))
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            Some(format!("orderbook-shard-{}", executor.shard_of("BTC-USD")))
+        );
+    }
+
+    #[test]
+    fn test_commands_apply_in_queue_order() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        manager.add_book("BTC-USD");
+        manager.add_book("ETH-USD");
+        let executor = manager.start_executor(2, 4);
+
+        for id in 1..=20 {
+            executor
+                .submit("BTC-USD", Command::Add(limit(id, 100 + id, 1, Side::Sell)))
+                .unwrap();
+        }
+        executor
+            .submit("ETH-USD", Command::Cancel(OrderId::from_u64(99)))
+            .unwrap();
+        let outcome = executor
+            .execute(
+                "BTC-USD",
+                Command::MarketOrder {
+                    order_id: OrderId::from_u64(100),
+                    quantity: 5,
+                    side: Side::Buy,
+                },
+            )
+            .unwrap();
+        assert!(matches!(outcome, CommandOutcome::Match(_)));
+
+        let best_ask = executor.call("BTC-USD", |book| book.best_ask()).unwrap();
+        assert_eq!(best_ask, Some(106));
+        // The other symbol may sit on another shard; wait for it to drain too
+        executor.call("ETH-USD", |_| ()).unwrap();
+        assert_eq!(executor.applied_count(), 21);
+        assert_eq!(manager.get_book("BTC-USD").unwrap().best_ask(), Some(106));
+    }
+
+    #[test]
+    fn test_unknown_symbol_is_rejected() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        let executor = manager.start_executor(1, 4);
+        assert!(matches!(
+            executor.submit("NOPE", Command::Cancel(OrderId::from_u64(1))),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+
+        // Books added after the executor started are reachable
+        manager.add_book("LATE");
+        assert_eq!(executor.call("LATE", |book| book.best_bid()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_submitters_on_many_threads_and_shutdown_drains() {
+        let manager: OrderBookManager = OrderBookManager::new();
+        let symbols = ["A", "B", "C", "D"];
+        for symbol in symbols {
+            manager.add_book(symbol);
+        }
+        let executor = Arc::new(manager.start_executor(2, 8));
+
+        let submitters: Vec<_> = symbols
+            .into_iter()
+            .map(|symbol| {
+                let executor = Arc::clone(&executor);
+                thread::spawn(move || {
+                    for id in 1..=50 {
+                        executor
+                            .submit(symbol, Command::Add(limit(id, 100, 1, Side::Buy)))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for submitter in submitters {
+            submitter.join().unwrap();
+        }
+
+        Arc::try_unwrap(executor).ok().unwrap().shutdown();
+        for symbol in symbols {
+            let book = manager.get_book(symbol).unwrap();
+            assert_eq!(book.create_snapshot(1).bids[0].order_count, 50);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Unit tests for short sale orders and the short sale rule hook.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::constraints::OrderConstraints;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::{OrderBookEvent, RejectReason};
+    use crate::orderbook::short_sale::{ShortSaleCheck, ShortSaleReference, short_sale_price_test};
+    use crate::orderbook::tests::helpers::limit;
+    use pricelevel::{OrderId, OrderUpdate, Side};
+    use std::sync::{Arc, Mutex};
+
+    /// A book that last traded at 100, with 99 bid and 105 offered
+    fn traded_book() -> OrderBook<()> {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_short_sale_rule(short_sale_price_test(ShortSaleReference::LastTrade));
+        book.add_order(limit(1, 100, 5, Side::Sell)).unwrap();
+        book.add_order(limit(2, 100, 5, Side::Buy)).unwrap();
+        book.add_order(limit(3, 99, 5, Side::Buy)).unwrap();
+        book.add_order(limit(4, 105, 5, Side::Sell)).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_short_sales_are_free_without_a_restriction() {
+        let book = traded_book();
+        book.add_order_with_constraints(
+            limit(10, 100, 5, Side::Sell),
+            OrderConstraints::short_sale(),
+        )
+        .unwrap();
+        assert!(book.get_order(OrderId::from_u64(10)).is_some());
+        assert!(book.get_order_constraints(OrderId::from_u64(10)).short_sale);
+    }
+
+    #[test]
+    fn test_restricted_short_sale_must_be_above_the_last_trade() {
+        let book = traded_book();
+        book.set_short_sale_restriction(true);
+        assert!(book.is_short_sale_restricted());
+
+        let result = book.add_order_with_constraints(
+            limit(10, 100, 5, Side::Sell),
+            OrderConstraints::short_sale(),
+        );
+        assert!(matches!(
+            result,
+            Err(OrderBookError::ShortSaleRestricted { price: 100, .. })
+        ));
+        assert!(book.get_order(OrderId::from_u64(10)).is_none());
+
+        book.add_order_with_constraints(
+            limit(11, 101, 5, Side::Sell),
+            OrderConstraints::short_sale(),
+        )
+        .unwrap();
+        // Long sales are not restricted
+        book.add_order(limit(12, 100, 5, Side::Sell)).unwrap();
+
+        book.set_short_sale_restriction(false);
+        book.add_order_with_constraints(
+            limit(13, 100, 1, Side::Sell),
+            OrderConstraints::short_sale(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_repricing_a_short_sale_is_checked_again() {
+        let book = traded_book();
+        book.add_order_with_constraints(
+            limit(10, 103, 5, Side::Sell),
+            OrderConstraints::min_quantity(2).as_short_sale(),
+        )
+        .unwrap();
+        book.set_short_sale_restriction(true);
+
+        let result = book.update_order(OrderUpdate::UpdatePrice {
+            order_id: OrderId::from_u64(10),
+            new_price: 100,
+        });
+        assert!(matches!(
+            result,
+            Err(OrderBookError::ShortSaleRestricted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_alternative_uptick_rule_uses_the_best_bid() {
+        let mut book = traded_book();
+        book.set_short_sale_rule(short_sale_price_test(ShortSaleReference::BestBid));
+        book.set_short_sale_restriction(true);
+        assert!(
+            book.add_order_with_constraints(
+                limit(10, 99, 5, Side::Sell),
+                OrderConstraints::short_sale()
+            )
+            .is_err()
+        );
+        book.add_order_with_constraints(
+            limit(11, 100, 5, Side::Sell),
+            OrderConstraints::short_sale(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_custom_rule_and_reject_event() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&seen);
+        let rejects = Arc::new(Mutex::new(Vec::new()));
+        let rejected = Arc::clone(&rejects);
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderRejected(reject) = event {
+                rejected.lock().unwrap().push(reject.reason);
+            }
+        }));
+        book.set_short_sale_rule(Arc::new(move |check: &ShortSaleCheck| {
+            recorded.lock().unwrap().push(*check);
+            if check.quantity > 100 {
+                Err("Too large".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+
+        book.add_order_with_constraints(
+            limit(1, 100, 10, Side::Sell),
+            OrderConstraints::short_sale(),
+        )
+        .unwrap();
+        assert!(
+            book.add_order_with_constraints(
+                limit(2, 100, 200, Side::Sell),
+                OrderConstraints::short_sale()
+            )
+            .is_err()
+        );
+        book.add_order(limit(3, 100, 500, Side::Sell)).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].order_id, OrderId::from_u64(1));
+        assert!(!seen[0].restricted);
+        assert_eq!(
+            *rejects.lock().unwrap(),
+            vec![RejectReason::ShortSaleRestricted]
+        );
+    }
+}
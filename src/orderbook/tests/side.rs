@@ -0,0 +1,97 @@
+//! Unit tests for the sorted price index of each side.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_best_prices_follow_level_changes() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 98, 10, Side::Buy);
+        rest(&book, 3, 105, 10, Side::Sell);
+        rest(&book, 4, 103, 10, Side::Sell);
+        assert_eq!(book.bids.best_prices(usize::MAX), vec![100, 98]);
+        assert_eq!(book.asks.best_prices(1), vec![103]);
+
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        book.submit_market_order(OrderId::from_u64(5), 10, Side::Buy)
+            .unwrap();
+
+        assert_eq!(book.bids.best(), Some(98));
+        assert_eq!(book.asks.best(), Some(105));
+        assert_eq!(book.asks.next_after(Some(105)), None);
+        assert_eq!(book.bids.next_after(Some(99)), Some(98));
+        assert!(book.validate().is_valid());
+    }
+
+    #[test]
+    fn test_matching_walks_levels_in_price_order() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        for (id, price) in [(1, 104), (2, 101), (3, 103), (4, 102)] {
+            rest(&book, id, price, 5, Side::Sell);
+        }
+
+        let result = book
+            .match_order(OrderId::from_u64(5), Side::Buy, 17, Some(103))
+            .unwrap();
+
+        let prices: Vec<u64> = result
+            .transactions
+            .as_vec()
+            .iter()
+            .map(|transaction| transaction.price)
+            .collect();
+        assert_eq!(prices, vec![101, 102, 103]);
+        assert_eq!(result.remaining_quantity, 2);
+        assert_eq!(book.asks.best_prices(usize::MAX), vec![104]);
+        assert_eq!(book.peek_match(Side::Buy, 10, Some(103)), 0);
+    }
+
+    #[test]
+    fn test_clear_and_mass_cancel_empty_the_index() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        for id in 1..=5 {
+            rest(&book, id, 100 + id, 1, Side::Sell);
+            rest(&book, 10 + id, 90 + id, 1, Side::Buy);
+        }
+
+        book.cancel_price_range(Side::Sell, 102, 104);
+        assert_eq!(book.asks.best_prices(usize::MAX), vec![101, 105]);
+
+        book.clear();
+        assert_eq!(book.bids.best(), None);
+        assert!(book.asks.indexed_prices().is_empty());
+    }
+
+    #[test]
+    fn test_index_stays_consistent_under_concurrent_churn() {
+        let book: Arc<OrderBook<()>> = Arc::new(OrderBook::new("TEST_SYMBOL"));
+        let handles: Vec<_> = (0..4u64)
+            .map(|thread_index| {
+                let book = Arc::clone(&book);
+                thread::spawn(move || {
+                    for i in 0..200u64 {
+                        let id = thread_index * 1_000 + i;
+                        // Every thread adds and removes orders at the same few prices
+                        rest(&book, id, 100 + i % 4, 1, Side::Sell);
+                        if i % 3 != 0 {
+                            book.cancel_order(OrderId::from_u64(id)).unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let report = book.validate();
+        assert!(report.is_valid(), "{report}");
+        assert_eq!(book.asks.best(), Some(100));
+    }
+}
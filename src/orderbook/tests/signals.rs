@@ -0,0 +1,113 @@
+//! Unit tests for the signals evaluated on book changes.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::signals::{DepthTotals, SignalFired};
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    type Fired = Arc<Mutex<Vec<SignalFired>>>;
+
+    fn register_ratio_above(book: &OrderBook, levels: usize, ratio: f64) -> (u64, Fired) {
+        let fired: Fired = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&fired);
+        let signal_id = book.register_signal(
+            levels,
+            Arc::new(move |depth: &DepthTotals| depth.bid_ask_ratio().is_some_and(|r| r > ratio)),
+            Arc::new(move |signal: &SignalFired| sink.lock().unwrap().push(signal.clone())),
+        );
+        (signal_id, fired)
+    }
+
+    #[test]
+    fn test_signal_fires_once_when_condition_starts_to_hold() {
+        let book: OrderBook = OrderBook::new("TEST");
+        rest(&book, 1, 101, 10, Side::Sell);
+        rest(&book, 2, 100, 10, Side::Buy);
+        let (signal_id, fired) = register_ratio_above(&book, 5, 2.0);
+
+        rest(&book, 3, 99, 15, Side::Buy);
+        assert_eq!(fired.lock().unwrap().len(), 1);
+        let signal = fired.lock().unwrap()[0].clone();
+        assert_eq!(signal.signal_id, signal_id);
+        assert_eq!(signal.symbol, "TEST");
+        assert_eq!(
+            signal.depth,
+            DepthTotals {
+                levels: 5,
+                bid_quantity: 25,
+                ask_quantity: 10,
+            }
+        );
+        assert_eq!(signal.version, book.version());
+
+        // Still holding: not reported again
+        rest(&book, 4, 98, 5, Side::Buy);
+        assert_eq!(fired.lock().unwrap().len(), 1);
+
+        // Re-armed once it stops holding
+        rest(&book, 5, 102, 20, Side::Sell);
+        rest(&book, 6, 97, 100, Side::Buy);
+        assert_eq!(fired.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_signal_only_sees_its_levels() {
+        let book: OrderBook = OrderBook::new("TEST");
+        rest(&book, 1, 101, 10, Side::Sell);
+        rest(&book, 2, 100, 10, Side::Buy);
+        let (_, top_only) = register_ratio_above(&book, 1, 2.0);
+        let (_, top_two) = register_ratio_above(&book, 2, 2.0);
+
+        rest(&book, 3, 99, 30, Side::Buy);
+        assert!(top_only.lock().unwrap().is_empty());
+        assert_eq!(top_two.lock().unwrap().len(), 1);
+        assert_eq!(book.depth_totals(1).bid_quantity, 10);
+        assert_eq!(book.depth_totals(2).bid_quantity, 40);
+    }
+
+    #[test]
+    fn test_condition_holding_at_registration_does_not_fire() {
+        let book: OrderBook = OrderBook::new("TEST");
+        rest(&book, 1, 100, 50, Side::Buy);
+        rest(&book, 2, 101, 10, Side::Sell);
+        let (signal_id, fired) = register_ratio_above(&book, 5, 2.0);
+
+        rest(&book, 3, 99, 10, Side::Buy);
+        assert!(fired.lock().unwrap().is_empty());
+
+        assert!(book.remove_signal(signal_id));
+        assert!(!book.remove_signal(signal_id));
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        rest(&book, 4, 100, 50, Side::Buy);
+        assert!(fired.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_listener_may_change_the_book() {
+        let book: Arc<OrderBook> = Arc::new(OrderBook::new("TEST"));
+        rest(&book, 1, 101, 10, Side::Sell);
+        let weak = Arc::downgrade(&book);
+        book.register_signal(
+            1,
+            Arc::new(|depth: &DepthTotals| depth.imbalance().is_some_and(|i| i > 0.5)),
+            Arc::new(move |_: &SignalFired| {
+                let book = weak.upgrade().unwrap();
+                book.add_limit_order(
+                    OrderId::from_u64(99),
+                    101,
+                    90,
+                    Side::Sell,
+                    TimeInForce::Gtc,
+                    None,
+                )
+                .unwrap();
+            }),
+        );
+
+        rest(&book, 2, 100, 100, Side::Buy);
+        assert_eq!(book.depth_totals(1).ask_quantity, 100);
+    }
+}
@@ -0,0 +1,174 @@
+//! Unit tests for signed prices.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::events::{LevelChange, OrderBookEvent};
+    use crate::orderbook::signed::{SignedLevel, SignedOrderUpdate};
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    fn spread_book() -> OrderBook<()> {
+        let mut book = OrderBook::new("SPREAD");
+        book.set_price_floor(-10_000).unwrap();
+        book
+    }
+
+    fn add(book: &OrderBook<()>, price: i64, quantity: u64, side: Side) -> OrderId {
+        let id = OrderId::new();
+        book.add_limit_order_signed(id, price, quantity, side, TimeInForce::Gtc, None)
+            .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_default_floor_is_the_identity() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        assert_eq!(book.price_floor(), 0);
+        assert_eq!(book.to_book_price(150).unwrap(), 150);
+        assert_eq!(book.to_signed_price(150), Some(150));
+        assert!(matches!(
+            book.to_book_price(-1),
+            Err(OrderBookError::InvalidPrice { .. })
+        ));
+    }
+
+    #[test]
+    fn test_negative_prices_keep_their_order() {
+        let book = spread_book();
+        add(&book, -250, 10, Side::Buy);
+        add(&book, -300, 10, Side::Buy);
+        add(&book, -200, 10, Side::Sell);
+        add(&book, 50, 10, Side::Sell);
+
+        assert_eq!(book.best_bid_signed(), Some(-250));
+        assert_eq!(book.best_ask_signed(), Some(-200));
+        assert_eq!(book.spread(), Some(50));
+    }
+
+    #[test]
+    fn test_negative_prices_match() {
+        let book = spread_book();
+        let maker = add(&book, -120, 10, Side::Sell);
+        add(&book, -100, 4, Side::Buy);
+
+        assert_eq!(book.last_trade_price_signed(), Some(-120));
+        let remaining = book.get_order(maker).unwrap();
+        assert_eq!(remaining.visible_quantity(), 6);
+        assert!(book.best_bid_signed().is_none());
+    }
+
+    #[test]
+    fn test_prices_must_be_above_the_floor() {
+        let book = spread_book();
+        let result = book.add_limit_order_signed(
+            OrderId::new(),
+            -10_000,
+            1,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        );
+        assert!(matches!(result, Err(OrderBookError::InvalidPrice { .. })));
+    }
+
+    #[test]
+    fn test_floor_cannot_change_while_orders_rest() {
+        let mut book = spread_book();
+        add(&book, -5, 1, Side::Buy);
+        assert!(book.set_price_floor(0).is_err());
+        assert_eq!(book.price_floor(), -10_000);
+    }
+
+    #[test]
+    fn test_prices_beyond_the_signed_range_are_not_truncated() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST");
+        book.set_price_floor(1).unwrap();
+        assert_eq!(book.to_signed_price(i64::MAX as u64 - 1), Some(i64::MAX));
+        assert_eq!(book.to_signed_price(i64::MAX as u64), None);
+        assert_eq!(book.to_signed_price(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_signed_updates_and_replace() {
+        let book = spread_book();
+        let id = add(&book, -300, 10, Side::Buy);
+
+        book.update_order_signed(SignedOrderUpdate::UpdatePrice {
+            order_id: id,
+            new_price: -280,
+        })
+        .unwrap();
+        assert_eq!(book.best_bid_signed(), Some(-280));
+
+        book.update_order_signed(SignedOrderUpdate::UpdatePriceAndQuantity {
+            order_id: id,
+            new_price: -270,
+            new_quantity: 4,
+        })
+        .unwrap();
+        assert_eq!(book.best_bid_signed(), Some(-270));
+        assert_eq!(book.get_order(id).unwrap().visible_quantity(), 4);
+
+        book.update_order_signed(SignedOrderUpdate::Replace {
+            order_id: id,
+            price: -260,
+            quantity: 6,
+            side: Side::Buy,
+        })
+        .unwrap();
+        assert_eq!(book.best_bid_signed(), Some(-260));
+
+        assert!(matches!(
+            book.update_order_signed(SignedOrderUpdate::UpdatePrice {
+                order_id: id,
+                new_price: -10_001,
+            }),
+            Err(OrderBookError::InvalidPrice { .. })
+        ));
+        assert_eq!(book.best_bid_signed(), Some(-260));
+    }
+
+    #[test]
+    fn test_signed_matching_snapshots_and_trades() {
+        let book = spread_book();
+        add(&book, -120, 10, Side::Sell);
+        add(&book, -110, 10, Side::Sell);
+        add(&book, -150, 5, Side::Buy);
+
+        let result = book
+            .match_limit_order_signed(OrderId::new(), 15, Side::Buy, -115)
+            .unwrap();
+        let trades = book.trades_signed(&result).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].price, trades[0].quantity), (-120, 10));
+
+        let snapshot = book.create_snapshot_signed(10).unwrap();
+        let prices =
+            |levels: &[SignedLevel]| levels.iter().map(|level| level.price).collect::<Vec<_>>();
+        assert_eq!(prices(&snapshot.bids), vec![-150]);
+        assert_eq!(prices(&snapshot.asks), vec![-110]);
+    }
+
+    #[test]
+    fn test_event_prices_read_signed() {
+        let events: Arc<Mutex<Vec<u64>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let mut book = spread_book();
+        book.set_event_listener(Arc::new(move |event: &OrderBookEvent| {
+            if let (OrderBookEvent::OrderAccepted(_), Some(price)) = (event, event.price()) {
+                recorded.lock().unwrap().push(price);
+            }
+        }));
+        add(&book, -42, 1, Side::Buy);
+
+        let price = events.lock().unwrap()[0];
+        assert_eq!(book.to_signed_price(price), Some(-42));
+        let event = OrderBookEvent::LevelAdded(LevelChange {
+            side: Side::Buy,
+            price,
+        });
+        assert_eq!(book.event_price_signed(&event), Some(-42));
+    }
+}
@@ -510,10 +510,10 @@ mod tests_bis {
         let mut snapshot = create_unordered_snapshot();
 
         // Sort the bids by price in descending order
-        snapshot.bids.sort_by(|a, b| b.price.cmp(&a.price));
+        snapshot.bids.sort_by_key(|b| std::cmp::Reverse(b.price));
 
         // Sort the asks by price in ascending order
-        snapshot.asks.sort_by(|a, b| a.price.cmp(&b.price));
+        snapshot.asks.sort_by_key(|a| a.price);
 
         // Now the first element should be the best price
         let best_bid = snapshot
@@ -754,3 +754,186 @@ mod test_snapshot_specific {
         assert_eq!(best_ask, Some((1010, 15)));
     }
 }
+
+#[cfg(test)]
+mod test_l3_snapshot {
+    use crate::{BookBuilder, OrderBook, OrderBookL3Snapshot};
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn book() -> OrderBook {
+        BookBuilder::new("TEST")
+            .bid(100, 10)
+            .bid(100, 5)
+            .bid(99, 7)
+            .iceberg(Side::Sell, 105, 2, 8)
+            .ask(106, 3)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_l3_snapshot_lists_every_order_in_priority_order() {
+        let book = book();
+        let snapshot = book.create_l3_snapshot(10);
+
+        assert_eq!(snapshot.symbol, "TEST");
+        assert_eq!(snapshot.order_count(), 5);
+        assert_eq!(
+            snapshot
+                .bids
+                .iter()
+                .map(|level| level.price)
+                .collect::<Vec<_>>(),
+            vec![100, 99]
+        );
+        assert_eq!(
+            snapshot
+                .asks
+                .iter()
+                .map(|level| level.price)
+                .collect::<Vec<_>>(),
+            vec![105, 106]
+        );
+
+        let best_bids = &snapshot.bids[0].orders;
+        assert_eq!(best_bids[0].id, OrderId::from_u64(1));
+        assert_eq!(best_bids[1].id, OrderId::from_u64(2));
+        assert!(best_bids[0].sequence < best_bids[1].sequence);
+        assert_eq!(snapshot.best_bid(), Some((100, 15)));
+
+        let iceberg = snapshot.get_order(OrderId::from_u64(4)).unwrap();
+        assert_eq!(iceberg.side, Side::Sell);
+        assert_eq!(iceberg.visible_quantity, 2);
+        assert_eq!(iceberg.hidden_quantity, 8);
+        assert_eq!(iceberg.remaining_quantity(), 10);
+        assert_eq!(snapshot.best_ask(), Some((105, 2)));
+        assert_eq!(snapshot.asks[0].total_quantity(), 10);
+    }
+
+    #[test]
+    fn test_l3_snapshot_reflects_partial_fills_and_depth() {
+        let book = book();
+        book.submit_market_order(OrderId::from_u64(10), 12, Side::Sell)
+            .unwrap();
+        book.add_limit_order(
+            OrderId::from_u64(11),
+            98,
+            1,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+
+        let snapshot = book.create_l3_snapshot(1);
+        assert_eq!(snapshot.bids.len(), 1);
+        assert_eq!(snapshot.asks.len(), 1);
+        let remaining = &snapshot.bids[0].orders;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, OrderId::from_u64(2));
+        assert_eq!(remaining[0].visible_quantity, 3);
+    }
+
+    #[test]
+    fn test_l3_snapshot_serialization_roundtrip() {
+        let snapshot = book().create_l3_snapshot(10);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: OrderBookL3Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+}
+
+#[cfg(test)]
+mod test_snapshot_diff {
+    use crate::OrderBookSnapshot;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::snapshot::LevelDelta;
+    use pricelevel::{OrderId, PriceLevelSnapshot, Side, TimeInForce};
+
+    fn level(price: u64, visible_quantity: u64, order_count: usize) -> PriceLevelSnapshot {
+        PriceLevelSnapshot {
+            price,
+            visible_quantity,
+            hidden_quantity: 0,
+            order_count,
+            orders: Vec::new(),
+        }
+    }
+
+    fn snapshot(bids: Vec<PriceLevelSnapshot>, asks: Vec<PriceLevelSnapshot>) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: "TEST".to_string(),
+            timestamp: 0,
+            bids,
+            asks,
+        }
+    }
+
+    #[test]
+    fn test_identical_snapshots_have_no_diff() {
+        let book = snapshot(vec![level(100, 10, 1)], vec![level(101, 5, 2)]);
+        assert!(book.diff(&book.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_classifies_levels() {
+        let before = snapshot(
+            vec![level(100, 10, 1), level(99, 4, 1)],
+            vec![level(101, 5, 1)],
+        );
+        let after = snapshot(
+            vec![level(100, 7, 2), level(98, 3, 1)],
+            vec![level(101, 5, 1), level(102, 8, 1)],
+        );
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.added,
+            vec![
+                LevelDelta {
+                    side: Side::Buy,
+                    price: 98,
+                    visible_delta: 3,
+                    hidden_delta: 0,
+                    order_count_delta: 1,
+                },
+                LevelDelta {
+                    side: Side::Sell,
+                    price: 102,
+                    visible_delta: 8,
+                    hidden_delta: 0,
+                    order_count_delta: 1,
+                },
+            ]
+        );
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].price, 99);
+        assert_eq!(diff.removed[0].total_delta(), -4);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].visible_delta, -3);
+        assert_eq!(diff.changed[0].order_count_delta, 1);
+        assert_eq!(diff.iter().count(), 4);
+
+        // The reverse diff swaps added and removed and negates every delta
+        let reverse = after.diff(&before);
+        assert_eq!(reverse.added.len(), 1);
+        assert_eq!(reverse.removed.len(), 2);
+        assert_eq!(reverse.changed[0].visible_delta, 3);
+    }
+
+    #[test]
+    fn test_diff_reconciles_a_replica() {
+        let book: OrderBook<()> = OrderBook::new("TEST");
+        book.add_limit_order(OrderId::new(), 100, 10, Side::Buy, TimeInForce::Gtc, None)
+            .unwrap();
+        let replica = book.create_snapshot(10);
+        book.add_limit_order(OrderId::new(), 105, 3, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+
+        let diff = replica.diff(&book.create_snapshot(10));
+        assert!(diff.removed.is_empty() && diff.changed.is_empty());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].side, Side::Sell);
+        assert_eq!(diff.added[0].price, 105);
+    }
+}
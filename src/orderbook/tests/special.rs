@@ -0,0 +1,108 @@
+//! Unit tests for the special price (trade-at-settlement) section.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::OrderBookError;
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::record_trades;
+    use crate::orderbook::trade::TradeCondition;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    fn id(n: u64) -> OrderId {
+        OrderId::from_u64(n)
+    }
+
+    #[test]
+    fn test_settlement_matches_by_offset_and_arrival() {
+        let mut book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        let reports = record_trades(&mut book);
+        book.add_special_price_order(id(1), Side::Sell, -1, 10)
+            .unwrap();
+        book.add_special_price_order(id(2), Side::Buy, 2, 4)
+            .unwrap();
+        book.add_special_price_order(id(3), Side::Buy, 0, 10)
+            .unwrap();
+        book.add_special_price_order(id(4), Side::Sell, 1, 5)
+            .unwrap();
+        book.add_special_price_order(id(5), Side::Sell, 3, 5)
+            .unwrap();
+
+        let settlement = book.settle_special_prices(1_000);
+        let trades: Vec<(OrderId, OrderId, u64, u64)> = settlement
+            .trades
+            .iter()
+            .map(|t| (t.taker_order_id, t.maker_order_id, t.price, t.quantity))
+            .collect();
+        assert_eq!(
+            trades,
+            vec![
+                // Highest buy against lowest sell, at the earlier (sell) order's price
+                (id(2), id(1), 999, 4),
+                (id(3), id(1), 999, 6),
+            ]
+        );
+        let expired: Vec<(OrderId, u64)> = settlement
+            .expired
+            .iter()
+            .map(|order| (order.id, order.quantity))
+            .collect();
+        assert_eq!(expired, vec![(id(3), 4), (id(4), 5), (id(5), 5)]);
+
+        assert!(book.special_price_orders().is_empty());
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), settlement.trades.len());
+        assert!(reports.iter().all(|report| {
+            report
+                .conditions
+                .contains(TradeCondition::TradeAtSettlement)
+        }));
+        assert_eq!(reports[0].transaction, settlement.trades[0]);
+    }
+
+    #[test]
+    fn test_section_is_separate_from_regular_book() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        book.add_limit_order(id(1), 100, 10, Side::Sell, TimeInForce::Gtc, None)
+            .unwrap();
+        book.add_special_price_order(id(2), Side::Buy, 0, 5)
+            .unwrap();
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.get_order(id(1)).unwrap().price(), 100);
+
+        book.add_special_price_order(id(3), Side::Sell, 0, 5)
+            .unwrap();
+        let settlement = book.settle_special_prices(100);
+        assert_eq!(settlement.trades.len(), 1);
+        assert_eq!(settlement.trades[0].maker_order_id, id(2));
+        assert_eq!(book.last_trade_price(), None);
+        assert_eq!(book.get_order(id(1)).unwrap().price(), 100);
+    }
+
+    #[test]
+    fn test_add_cancel_and_unpriceable_orders() {
+        let book: OrderBook = OrderBook::new("TEST_SYMBOL");
+        assert!(matches!(
+            book.add_special_price_order(id(1), Side::Buy, 0, 0),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        book.add_special_price_order(id(1), Side::Buy, -20, 5)
+            .unwrap();
+        assert!(matches!(
+            book.add_special_price_order(id(1), Side::Sell, 0, 5),
+            Err(OrderBookError::InvalidOperation { .. })
+        ));
+        book.add_special_price_order(id(2), Side::Sell, -20, 5)
+            .unwrap();
+        book.add_special_price_order(id(3), Side::Sell, -30, 5)
+            .unwrap();
+
+        let cancelled = book.cancel_special_price_order(id(3)).unwrap();
+        assert_eq!(cancelled.offset, -30);
+        assert!(book.cancel_special_price_order(id(3)).is_none());
+
+        // Priced below zero, so neither can trade
+        let settlement = book.settle_special_prices(10);
+        assert!(settlement.trades.is_empty());
+        assert_eq!(settlement.expired.len(), 2);
+    }
+}
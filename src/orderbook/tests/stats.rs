@@ -0,0 +1,162 @@
+//! Unit tests for the book's activity counters.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::notional::AccountNotional;
+    use crate::orderbook::registry::SymbolInfo;
+    use crate::orderbook::stats::BookStats;
+    use crate::orderbook::tests::helpers::rest;
+    use pricelevel::{OrderId, Side, TimeInForce};
+
+    #[test]
+    fn test_new_book_has_no_activity() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        assert_eq!(book.stats(), BookStats::default());
+    }
+
+    #[test]
+    fn test_counts_adds_matches_and_trades() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Sell);
+        rest(&book, 2, 101, 10, Side::Sell);
+        // Crosses both levels
+        rest(&book, 3, 101, 15, Side::Buy);
+        book.submit_market_order(OrderId::from_u64(4), 3, Side::Buy)
+            .unwrap();
+
+        let stats = book.stats();
+        assert_eq!(stats.orders_added, 3);
+        assert_eq!(stats.orders_matched, 2);
+        assert_eq!(stats.trades, 3);
+        assert_eq!(stats.traded_volume, 18);
+    }
+
+    #[test]
+    fn test_counts_cancels_and_rejects() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        rest(&book, 2, 99, 10, Side::Buy);
+        rest(&book, 3, 98, 10, Side::Buy);
+        rest(&book, 4, 110, 10, Side::Sell);
+
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        // Unknown orders are not counted
+        book.cancel_order(OrderId::from_u64(99)).unwrap();
+        book.cancel_side(Side::Buy);
+
+        // A post-only order that would cross
+        assert!(
+            book.add_post_only_order(
+                OrderId::from_u64(5),
+                110,
+                1,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None
+            )
+            .is_err()
+        );
+        assert!(
+            book.submit_market_order(OrderId::from_u64(6), 1, Side::Buy)
+                .is_ok()
+        );
+        assert!(
+            book.submit_market_order(OrderId::from_u64(7), 1, Side::Sell)
+                .is_err()
+        );
+
+        let stats = book.stats();
+        assert_eq!(stats.orders_cancelled, 3);
+        assert_eq!(stats.orders_rejected, 2);
+        assert_eq!(stats.orders_added, 4);
+    }
+
+    #[test]
+    fn test_reset() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Buy);
+        book.cancel_order(OrderId::from_u64(1)).unwrap();
+        assert_ne!(book.stats(), BookStats::default());
+
+        book.reset_stats();
+        assert_eq!(book.stats(), BookStats::default());
+    }
+
+    fn add_for(
+        book: &OrderBook<()>,
+        id: u64,
+        price: u64,
+        quantity: u64,
+        side: Side,
+        account: &str,
+    ) {
+        book.add_order_for_account(
+            pricelevel::OrderType::Standard {
+                id: OrderId::from_u64(id),
+                price,
+                quantity,
+                side,
+                timestamp: 0,
+                time_in_force: TimeInForce::Gtc,
+                extra_fields: (),
+            },
+            account,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_notional_is_not_tracked_by_default() {
+        let book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        rest(&book, 1, 100, 10, Side::Sell);
+        rest(&book, 2, 100, 10, Side::Buy);
+        assert_eq!(book.stats().notional, None);
+    }
+
+    #[test]
+    fn test_notional_by_side_and_account() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let info = SymbolInfo::new(1, 2, "EUR")
+            .unwrap()
+            .with_multiplier(10)
+            .unwrap();
+        book.track_notional(&info);
+
+        add_for(&book, 1, 100, 5, Side::Sell, "maker");
+        add_for(&book, 2, 101, 5, Side::Sell, "maker");
+        add_for(&book, 3, 101, 8, Side::Buy, "taker");
+        rest(&book, 4, 99, 4, Side::Buy);
+        book.submit_market_order(OrderId::from_u64(5), 4, Side::Sell)
+            .unwrap();
+
+        let notional = book.stats().notional.unwrap();
+        assert_eq!(notional.currency, "EUR");
+        assert_eq!(notional.multiplier, 10);
+        // 5 at 100 and 3 at 101, then 4 at 99
+        assert_eq!(notional.buy_initiated, (500 + 303) * 10);
+        assert_eq!(notional.sell_initiated, 396 * 10);
+        assert_eq!(notional.total(), (500 + 303 + 396) * 10);
+        assert_eq!(
+            notional.accounts["maker"],
+            AccountNotional {
+                bought: 0,
+                sold: 8030,
+            }
+        );
+        assert_eq!(
+            notional.accounts["taker"],
+            AccountNotional {
+                bought: 8030,
+                sold: 0,
+            }
+        );
+        assert_eq!(notional.accounts.len(), 2);
+
+        book.reset_stats();
+        let notional = book.stats().notional.unwrap();
+        assert_eq!(notional.total(), 0);
+        assert!(notional.accounts.is_empty());
+        assert_eq!(notional.currency, "EUR");
+    }
+}
@@ -0,0 +1,119 @@
+//! Unit tests for the stress testing harness.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::testing::{RunLength, StressConfig, StressHarness};
+    use pricelevel::OrderId;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    struct Tag {
+        origin: u64,
+    }
+
+    fn book_levels(book: &OrderBook<()>) -> Vec<(u64, u64, u64)> {
+        let snapshot = book.create_snapshot(usize::MAX);
+        snapshot
+            .bids
+            .iter()
+            .chain(snapshot.asks.iter())
+            .map(|level| {
+                (
+                    level.price,
+                    level.visible_quantity + level.hidden_quantity,
+                    level.order_count as u64,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_thread_run_is_reproducible() {
+        let config = StressConfig::default()
+            .with_threads(1)
+            .with_seed(42)
+            .with_run_length(RunLength::Operations(2_000));
+
+        let first = Arc::new(OrderBook::<()>::new("STRESS"));
+        let second = Arc::new(OrderBook::<()>::new("STRESS"));
+        let first_report = StressHarness::new(config.clone()).run(&first);
+        let second_report = StressHarness::new(config).run(&second);
+
+        first_report.assert_invariants();
+        assert_eq!(first_report.orders_added, second_report.orders_added);
+        assert_eq!(
+            first_report.orders_cancelled,
+            second_report.orders_cancelled
+        );
+        assert_eq!(
+            first_report.market_orders_filled,
+            second_report.market_orders_filled
+        );
+        assert_eq!(book_levels(&first), book_levels(&second));
+    }
+
+    #[test]
+    fn test_different_seeds_diverge() {
+        let run = |seed| {
+            let book = Arc::new(OrderBook::<()>::new("STRESS"));
+            let config = StressConfig::default()
+                .with_threads(1)
+                .with_seed(seed)
+                .with_run_length(RunLength::Operations(500));
+            StressHarness::new(config).run(&book);
+            book_levels(&book)
+        };
+        assert_ne!(run(1), run(2));
+    }
+
+    #[test]
+    fn test_ratios_select_actions() {
+        let book = Arc::new(OrderBook::<()>::new("STRESS"));
+        let config = StressConfig::default()
+            .with_threads(2)
+            .with_ratios(1, 0, 0)
+            .with_preload(0)
+            .with_run_length(RunLength::Operations(300));
+        let report = StressHarness::new(config).run(&book);
+
+        report.assert_invariants();
+        assert_eq!(report.market_orders, 0);
+        assert_eq!(report.cancels_attempted, 0);
+        assert_eq!(report.orders_added + report.orders_rejected, 600);
+        assert_eq!(report.total_operations(), 600);
+    }
+
+    #[test]
+    fn test_multi_threaded_timed_run_keeps_invariants() {
+        let book = Arc::new(OrderBook::<Tag>::new("STRESS"));
+        let config = StressConfig::default()
+            .with_threads(4)
+            .with_seed(7)
+            .with_run_length(RunLength::Time(Duration::from_millis(200)));
+        let built = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&built);
+        let harness = StressHarness::new(config).with_extra_fields(move |_: OrderId| Tag {
+            origin: counter.fetch_add(1, Ordering::Relaxed),
+        });
+        let report = harness.run(&book);
+
+        report.assert_invariants();
+        assert!(report.total_operations() > 0);
+        assert!(report.elapsed >= Duration::from_millis(200));
+        assert_eq!(
+            built.load(Ordering::Relaxed),
+            report.orders_added + report.orders_rejected
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "stress run needs a positive ratio")]
+    fn test_zero_ratios_are_rejected() {
+        let book = Arc::new(OrderBook::<()>::new("STRESS"));
+        let config = StressConfig::default().with_ratios(0, 0, 0);
+        StressHarness::new(config).run(&book);
+    }
+}
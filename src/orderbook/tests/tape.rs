@@ -0,0 +1,126 @@
+//! Unit tests for the trade tape and its OHLCV aggregation.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tape::{OhlcvBar, TradeTape};
+    use pricelevel::{OrderId, Side, TimeInForce, Transaction};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn trade(timestamp: u64, price: u64, quantity: u64) -> Transaction {
+        let mut transaction = Transaction::new(
+            Uuid::new_v4(),
+            OrderId::new(),
+            OrderId::new(),
+            price,
+            quantity,
+            Side::Buy,
+        );
+        transaction.timestamp = timestamp;
+        transaction
+    }
+
+    #[test]
+    fn test_book_records_its_trades() {
+        let tape = Arc::new(TradeTape::new(Duration::from_secs(300)));
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_trade_tape(Arc::clone(&tape));
+        for (id, price) in [(1, 100), (2, 101)] {
+            book.add_limit_order(
+                OrderId::from_u64(id),
+                price,
+                5,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+
+        let result = book
+            .submit_market_order(OrderId::from_u64(3), 8, Side::Buy)
+            .unwrap();
+
+        assert_eq!(tape.trades(), *result.transactions.as_vec());
+        assert!(Arc::ptr_eq(book.trade_tape().unwrap(), &tape));
+    }
+
+    #[test]
+    fn test_bars_are_aligned_on_the_interval() {
+        let tape = TradeTape::new(Duration::from_secs(300));
+        tape.record(&[
+            trade(60_000, 100, 1),
+            trade(60_500, 104, 2),
+            trade(61_200, 98, 3),
+            trade(119_999, 101, 4),
+            trade(180_000, 99, 5),
+        ]);
+
+        let bars = tape.bars(Duration::from_secs(60));
+        assert_eq!(
+            bars,
+            vec![
+                OhlcvBar {
+                    start: 60_000,
+                    end: 120_000,
+                    open: 100,
+                    high: 104,
+                    low: 98,
+                    close: 101,
+                    volume: 10,
+                    notional: 100 + 208 + 294 + 404,
+                    trade_count: 4,
+                },
+                OhlcvBar {
+                    start: 180_000,
+                    end: 240_000,
+                    open: 99,
+                    high: 99,
+                    low: 99,
+                    close: 99,
+                    volume: 5,
+                    notional: 495,
+                    trade_count: 1,
+                },
+            ]
+        );
+        assert_eq!(tape.bars(Duration::from_secs(1)).len(), 4);
+        assert_eq!(tape.bars(Duration::from_secs(300)).len(), 1);
+    }
+
+    #[test]
+    fn test_rolling_bar_and_vwap() {
+        let tape = TradeTape::new(Duration::from_secs(300));
+        tape.record(&[
+            trade(1_000, 90, 10),
+            trade(5_000, 100, 1),
+            trade(5_500, 110, 3),
+        ]);
+
+        let bar = tape.rolling_bar(Duration::from_secs(1), 6_000).unwrap();
+        assert_eq!((bar.start, bar.end), (5_000, 6_000));
+        assert_eq!((bar.open, bar.close, bar.volume), (100, 110, 4));
+        assert_eq!(tape.vwap(Duration::from_secs(1), 6_000), Some(107.5));
+        assert_eq!(
+            tape.vwap(Duration::from_secs(60), 6_000),
+            Some((900.0 + 100.0 + 330.0) / 14.0)
+        );
+        assert_eq!(tape.vwap(Duration::from_secs(1), 3_000), None);
+    }
+
+    #[test]
+    fn test_trades_beyond_retention_are_discarded() {
+        let tape = TradeTape::new(Duration::from_secs(60));
+        tape.record(&[trade(0, 100, 1), trade(30_000, 101, 1)]);
+        assert_eq!(tape.len(), 2);
+
+        tape.record(&[trade(75_000, 102, 1)]);
+
+        let prices: Vec<u64> = tape.trades().iter().map(|t| t.price).collect();
+        assert_eq!(prices, vec![101, 102]);
+        tape.record(&[]);
+        assert!(!tape.is_empty());
+    }
+}
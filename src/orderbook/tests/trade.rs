@@ -0,0 +1,158 @@
+//! Unit tests for trade condition codes.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::events::OrderBookEvent;
+    use crate::orderbook::tests::helpers::{limit, record_trades, rest};
+    use crate::orderbook::trade::{TradeCondition, TradeConditions, TradeReport};
+    use pricelevel::{OrderId, Side};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    fn book_with_asks() -> OrderBook<()> {
+        BookBuilder::new("TEST_SYMBOL")
+            .asks([(100, 50), (101, 500)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_condition_codes_round_trip() {
+        for condition in TradeCondition::ALL {
+            let code = condition.to_string();
+            assert_eq!(TradeCondition::from_str(&code).unwrap(), condition);
+        }
+        assert!(TradeCondition::from_str("Z").is_err());
+        assert!(TradeCondition::from_str("OL").is_err());
+    }
+
+    #[test]
+    fn test_conditions_set() {
+        let mut conditions = TradeConditions::none();
+        assert!(conditions.is_empty());
+        conditions.insert(TradeCondition::OddLot);
+        conditions.insert(TradeCondition::OpeningTrade);
+        assert!(conditions.contains(TradeCondition::OddLot));
+        assert!(!conditions.contains(TradeCondition::AuctionCross));
+        assert_eq!(conditions.to_string(), "OL");
+        assert_eq!(
+            conditions.iter().collect::<Vec<_>>(),
+            vec![TradeCondition::OpeningTrade, TradeCondition::OddLot]
+        );
+    }
+
+    #[test]
+    fn test_first_trade_is_opening_trade() {
+        let mut book = book_with_asks();
+        let reports = record_trades(&mut book);
+        book.match_order(OrderId::from_u64(3), Side::Buy, 60, None)
+            .unwrap();
+        book.match_order(OrderId::from_u64(4), Side::Buy, 10, None)
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(
+            reports[0].conditions,
+            TradeConditions::from(TradeCondition::OpeningTrade)
+        );
+        assert!(reports[1].conditions.is_empty());
+        assert!(reports[2].conditions.is_empty());
+    }
+
+    #[test]
+    fn test_trade_after_clear_is_opening_trade() {
+        let mut book = book_with_asks();
+        let reports = record_trades(&mut book);
+        book.match_order(OrderId::from_u64(3), Side::Buy, 10, None)
+            .unwrap();
+        book.clear();
+        rest(&book, 4, 100, 10, Side::Sell);
+        book.match_order(OrderId::from_u64(5), Side::Buy, 10, None)
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.conditions.contains(TradeCondition::OpeningTrade))
+        );
+        // The last trade price survives the clear
+        assert_eq!(book.last_trade_price(), Some(100));
+    }
+
+    #[test]
+    fn test_odd_lot_trades_flagged() {
+        let mut book = book_with_asks();
+        let reports = record_trades(&mut book);
+        book.set_round_lot(100);
+        book.match_order(OrderId::from_u64(3), Side::Buy, 150, None)
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert!(reports[0].conditions.contains(TradeCondition::OddLot));
+        assert!(!reports[1].conditions.contains(TradeCondition::OddLot));
+        assert_eq!(reports[1].transaction.quantity, 100);
+    }
+
+    #[test]
+    fn test_trades_between_orders_of_one_account_are_internal_crosses() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        let reports = record_trades(&mut book);
+        book.add_order_for_account(limit(1, 100, 10, Side::Sell), "ACME")
+            .unwrap();
+        book.add_order_for_account(limit(2, 100, 10, Side::Sell), "OTHER")
+            .unwrap();
+        book.add_order_for_account(limit(3, 100, 20, Side::Buy), "ACME")
+            .unwrap();
+        book.add_order(limit(4, 101, 5, Side::Sell)).unwrap();
+        book.add_order(limit(5, 101, 5, Side::Buy)).unwrap();
+
+        let internal: Vec<bool> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|report| report.conditions.contains(TradeCondition::InternalCross))
+            .collect();
+        assert_eq!(internal, vec![true, false, false]);
+    }
+
+    #[test]
+    fn test_trade_reports_precede_fills() {
+        let mut book = book_with_asks();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        book.set_event_listener(Arc::new(move |event| {
+            let kind = match event {
+                OrderBookEvent::TradeExecuted(_) => "trade",
+                OrderBookEvent::OrderFilled(_) => "fill",
+                _ => return,
+            };
+            recorded.lock().unwrap().push(kind);
+        }));
+        book.match_order(OrderId::from_u64(3), Side::Buy, 60, None)
+            .unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["trade", "trade", "fill", "fill", "fill", "fill"]
+        );
+    }
+
+    #[test]
+    fn test_trade_report_serialization() {
+        let mut book = book_with_asks();
+        let reports = record_trades(&mut book);
+        book.match_order(OrderId::from_u64(3), Side::Buy, 10, None)
+            .unwrap();
+        let report = reports.lock().unwrap()[0];
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"conditions\":[\"OpeningTrade\"]"));
+        let decoded: TradeReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, report);
+    }
+}
@@ -0,0 +1,136 @@
+//! Unit tests for delivering match results through the trade channel.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::trade_channel::{OverflowPolicy, TradeChannel};
+    use pricelevel::{MatchResult, OrderId, Side, TimeInForce};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn add_crossing_orders(book: &OrderBook<()>, pairs: u64) {
+        for i in 0..pairs {
+            book.add_limit_order(
+                OrderId::from_u64(i * 2),
+                100,
+                10,
+                Side::Sell,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+            book.add_limit_order(
+                OrderId::from_u64(i * 2 + 1),
+                100,
+                10,
+                Side::Buy,
+                TimeInForce::Gtc,
+                None,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_trade_channel_delivers_match_results() {
+        let (sender, receiver) = mpsc::channel();
+        let book = OrderBook::<()>::with_trade_channel(
+            "TEST_SYMBOL",
+            16,
+            OverflowPolicy::Park,
+            move |result: MatchResult| {
+                sender.send(result.order_id).unwrap();
+            },
+        );
+
+        add_crossing_orders(&book, 3);
+
+        let received: Vec<OrderId> = (0..3)
+            .map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                OrderId::from_u64(1),
+                OrderId::from_u64(3),
+                OrderId::from_u64(5)
+            ]
+        );
+        let channel = book.trade_channel().unwrap();
+        assert_eq!(channel.published_count(), 3);
+        assert_eq!(channel.dropped_count(), 0);
+    }
+
+    #[test]
+    fn test_trade_channel_drains_on_drop() {
+        let (sender, receiver) = mpsc::channel();
+        let book = OrderBook::<()>::with_trade_channel(
+            "TEST_SYMBOL",
+            64,
+            OverflowPolicy::Park,
+            move |result: MatchResult| {
+                sender.send(result.order_id).unwrap();
+            },
+        );
+
+        add_crossing_orders(&book, 20);
+        drop(book);
+
+        assert_eq!(receiver.try_iter().count(), 20);
+    }
+
+    #[test]
+    fn test_drop_policy_counts_dropped_results() {
+        let (release, blocked) = mpsc::channel::<()>();
+        let channel = TradeChannel::new("TEST_SYMBOL", 1, OverflowPolicy::Drop, move |_| {
+            // Hold the consumer until the test releases it
+            let _ = blocked.recv();
+        });
+
+        let mut accepted = 0;
+        for i in 0..10 {
+            if channel.publish(MatchResult::new(OrderId::from_u64(i), 1)) {
+                accepted += 1;
+            }
+        }
+
+        // At most one result is being consumed and one is queued
+        assert!(accepted <= 2);
+        assert_eq!(channel.dropped_count(), 10 - accepted);
+        assert_eq!(channel.capacity(), 1);
+        drop(release);
+    }
+
+    #[test]
+    fn test_park_policy_never_drops() {
+        let (sender, receiver) = mpsc::channel();
+        let channel = TradeChannel::new("TEST_SYMBOL", 2, OverflowPolicy::Park, move |result| {
+            std::thread::sleep(Duration::from_micros(100));
+            sender.send(result).unwrap();
+        });
+
+        for i in 0..50 {
+            assert!(channel.publish(MatchResult::new(OrderId::from_u64(i), 1)));
+        }
+        drop(channel);
+
+        assert_eq!(receiver.try_iter().count(), 50);
+    }
+
+    #[test]
+    fn test_park_policy_drops_once_the_consumer_is_gone() {
+        let channel = TradeChannel::new("TEST_SYMBOL", 1, OverflowPolicy::Park, |_| {
+            panic!("consumer failed");
+        });
+        assert!(channel.publish(MatchResult::new(OrderId::from_u64(1), 1)));
+        while channel.pending() > 0 {
+            std::thread::yield_now();
+        }
+
+        // The dead consumer never frees the slot taken by the next result
+        assert!(channel.publish(MatchResult::new(OrderId::from_u64(2), 1)));
+        assert!(!channel.publish(MatchResult::new(OrderId::from_u64(3), 1)));
+        assert_eq!(channel.dropped_count(), 1);
+        assert_eq!(channel.published_count(), 2);
+    }
+}
@@ -0,0 +1,105 @@
+//! Unit tests for order book invariant validation.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::builder::BookBuilder;
+    use crate::orderbook::validation::ValidationIssue;
+    use pricelevel::{OrderId, PriceLevel, Side};
+    use std::sync::Arc;
+
+    fn populated_book() -> OrderBook<()> {
+        BookBuilder::new("TEST_SYMBOL")
+            .bids([(99, 10), (99, 10), (98, 10)])
+            .ask(101, 10)
+            .iceberg(Side::Sell, 102, 5, 20)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_valid_book() {
+        let book = populated_book();
+        book.match_order(OrderId::from_u64(6), Side::Sell, 15, None)
+            .unwrap();
+        book.cancel_order(OrderId::from_u64(4)).unwrap();
+        let _ = book.best_bid();
+
+        let report = book.validate();
+        assert!(report.is_valid(), "{report}");
+        assert_eq!(report.levels_checked, 3);
+        assert_eq!(report.orders_checked, 3);
+    }
+
+    #[test]
+    fn test_detects_index_mismatches() {
+        let book = populated_book();
+        book.order_locations.remove(&OrderId::from_u64(1));
+        book.order_locations
+            .insert(OrderId::from_u64(2), (98, Side::Buy));
+        book.order_locations
+            .insert(OrderId::from_u64(42), (99, Side::Buy));
+
+        let issues = book.validate().issues;
+        assert_eq!(issues.len(), 3);
+        assert!(issues.contains(&ValidationIssue::UnindexedOrder {
+            order_id: OrderId::from_u64(1),
+            price: 99,
+            side: Side::Buy,
+        }));
+        assert!(issues.contains(&ValidationIssue::WrongLocation {
+            order_id: OrderId::from_u64(2),
+            indexed: (98, Side::Buy),
+            actual: (99, Side::Buy),
+        }));
+        assert!(issues.contains(&ValidationIssue::MissingOrder {
+            order_id: OrderId::from_u64(42),
+            price: 99,
+            side: Side::Buy,
+        }));
+    }
+
+    #[test]
+    fn test_detects_crossed_book_and_empty_level() {
+        let book = populated_book();
+        book.bids.insert(105, Arc::new(PriceLevel::new(105)));
+
+        let issues = book.validate().issues;
+        assert!(issues.contains(&ValidationIssue::EmptyLevel {
+            price: 105,
+            side: Side::Buy,
+        }));
+        assert!(issues.contains(&ValidationIssue::CrossedBook {
+            best_bid: 105,
+            best_ask: 101,
+        }));
+    }
+
+    #[test]
+    fn test_detects_stale_cache_and_orphaned_constraints() {
+        let book = populated_book();
+        assert_eq!(book.best_ask(), Some(101));
+        book.asks.remove(&101);
+        book.order_locations.remove(&OrderId::from_u64(4));
+        book.order_constraints.insert(
+            OrderId::from_u64(4),
+            crate::orderbook::constraints::OrderConstraints::all_or_none(),
+        );
+
+        let report = book.validate();
+        assert!(!report.is_valid());
+        assert!(report.issues.contains(&ValidationIssue::StaleCache {
+            side: Side::Sell,
+            cached: 101,
+            actual: Some(102),
+        }));
+        assert!(
+            report
+                .issues
+                .contains(&ValidationIssue::OrphanedConstraints {
+                    order_id: OrderId::from_u64(4)
+                })
+        );
+        assert!(report.to_string().contains("cached best SELL price 101"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Unit tests for the read-only side views.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::tests::helpers::limit;
+    use crate::orderbook::views::{AskView, BidView};
+    use pricelevel::{OrderId, Side};
+
+    fn book() -> OrderBook {
+        let book: OrderBook = OrderBook::new("TEST");
+        book.add_order(limit(1, 100, 10, Side::Buy)).unwrap();
+        book.add_order(limit(2, 100, 5, Side::Buy)).unwrap();
+        book.add_order(limit(3, 98, 7, Side::Buy)).unwrap();
+        book.add_order(limit(4, 103, 4, Side::Sell)).unwrap();
+        book
+    }
+
+    fn best_and_volume<T>(view: BidView<'_, T>) -> (Option<u64>, u64)
+    where
+        T: Clone + Send + Sync + Default + 'static,
+    {
+        (view.best_price(), view.total_volume())
+    }
+
+    #[test]
+    fn test_bid_view_reads_the_bids() {
+        let book = book();
+        let bids = book.bid_view();
+        assert_eq!(BidView::<()>::SIDE, Side::Buy);
+        assert_eq!(bids.symbol(), "TEST");
+        assert_eq!(best_and_volume(bids), (Some(100), 22));
+        assert_eq!(bids.visible_volume(), 22);
+        assert_eq!(bids.level_count(), 2);
+
+        let levels: Vec<(u64, u64)> = bids
+            .levels()
+            .map(|(price, summary)| (price, summary.total_quantity()))
+            .collect();
+        assert_eq!(levels, vec![(100, 15), (98, 7)]);
+        assert_eq!(bids.level(100).map(|summary| summary.order_count), Some(2));
+        assert_eq!(bids.level(103), None);
+
+        let ids: Vec<OrderId> = bids.orders_at(100).map(|order| order.id()).collect();
+        assert_eq!(ids, vec![OrderId::from_u64(1), OrderId::from_u64(2)]);
+    }
+
+    #[test]
+    fn test_ask_view_follows_the_book() {
+        let book = book();
+        let asks: AskView<'_, ()> = book.ask_view();
+        assert_eq!(asks.best_price(), Some(103));
+        assert_eq!(asks.total_volume(), 4);
+        let version = asks.version();
+
+        book.add_order(limit(5, 102, 6, Side::Sell)).unwrap();
+        assert_eq!(asks.best_price(), Some(102));
+        assert_eq!(asks.level_count(), 2);
+        assert!(asks.version() > version);
+
+        book.cancel_all();
+        assert!(asks.is_empty());
+        assert_eq!(asks.best_price(), None);
+        assert_eq!(asks.levels().count(), 0);
+    }
+}
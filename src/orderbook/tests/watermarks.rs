@@ -0,0 +1,142 @@
+//! Unit tests for memory watermarks and pressure callbacks.
+
+#[cfg(test)]
+mod tests {
+    use crate::orderbook::book::OrderBook;
+    use crate::orderbook::error::OrderBookError;
+    use crate::orderbook::watermarks::{
+        ESTIMATED_LEVEL_BYTES, ESTIMATED_ORDER_BYTES, MemoryPressure, MemoryPressureEvent,
+        MemoryUsage, MemoryWatermarks, Watermark,
+    };
+    use pricelevel::{OrderId, Side, TimeInForce};
+    use std::sync::{Arc, Mutex};
+
+    fn add_bid(book: &OrderBook<()>, id: u64, price: u64) {
+        book.add_limit_order(
+            OrderId::from_u64(id),
+            price,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_watermark_levels() {
+        let watermark = Watermark::new(10, 20);
+        assert_eq!(watermark.pressure(9), MemoryPressure::Normal);
+        assert_eq!(watermark.pressure(10), MemoryPressure::Warning);
+        assert_eq!(watermark.pressure(20), MemoryPressure::Critical);
+    }
+
+    #[test]
+    fn test_highest_pressure_wins() {
+        let watermarks = MemoryWatermarks {
+            orders: Some(Watermark::new(100, 200)),
+            levels: Some(Watermark::new(2, 3)),
+            ..Default::default()
+        };
+        let usage = MemoryUsage::estimate(10, 3);
+        assert_eq!(watermarks.pressure(&usage), MemoryPressure::Critical);
+        assert_eq!(
+            usage.estimated_bytes,
+            10 * ESTIMATED_ORDER_BYTES + 3 * ESTIMATED_LEVEL_BYTES
+        );
+        assert_eq!(
+            MemoryWatermarks::default().pressure(&usage),
+            MemoryPressure::Normal
+        );
+    }
+
+    #[test]
+    fn test_listener_notified_on_transitions() {
+        let events: Arc<Mutex<Vec<MemoryPressureEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_memory_pressure_listener(Arc::new(move |event| {
+            recorded.lock().unwrap().push(event.clone());
+        }));
+        book.set_memory_watermarks(MemoryWatermarks {
+            orders: Some(Watermark::new(2, 3)),
+            ..Default::default()
+        });
+
+        add_bid(&book, 1, 100);
+        add_bid(&book, 2, 100);
+        assert_eq!(book.memory_pressure(), MemoryPressure::Warning);
+        add_bid(&book, 3, 100);
+        assert_eq!(book.memory_pressure(), MemoryPressure::Critical);
+        book.cancel_order(OrderId::from_u64(3)).unwrap();
+        book.cancel_order(OrderId::from_u64(2)).unwrap();
+        assert_eq!(book.memory_pressure(), MemoryPressure::Normal);
+
+        let transitions: Vec<(MemoryPressure, MemoryPressure)> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event.previous, event.current))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (MemoryPressure::Normal, MemoryPressure::Warning),
+                (MemoryPressure::Warning, MemoryPressure::Critical),
+                (MemoryPressure::Critical, MemoryPressure::Warning),
+                (MemoryPressure::Warning, MemoryPressure::Normal),
+            ]
+        );
+        assert_eq!(events.lock().unwrap()[1].usage.orders, 3);
+    }
+
+    #[test]
+    fn test_matching_relieves_pressure() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_memory_watermarks(MemoryWatermarks {
+            levels: Some(Watermark::new(1, 2)),
+            ..Default::default()
+        });
+        add_bid(&book, 1, 100);
+        add_bid(&book, 2, 99);
+        assert_eq!(book.memory_pressure(), MemoryPressure::Critical);
+
+        book.match_order(OrderId::from_u64(3), Side::Sell, 10, None)
+            .unwrap();
+        assert_eq!(book.memory_pressure(), MemoryPressure::Warning);
+    }
+
+    #[test]
+    fn test_far_orders_rejected_at_critical() {
+        let mut book: OrderBook<()> = OrderBook::new("TEST_SYMBOL");
+        book.set_memory_watermarks(MemoryWatermarks {
+            orders: Some(Watermark::new(1, 2)),
+            reject_far_orders_beyond: Some(5),
+            ..Default::default()
+        });
+        add_bid(&book, 1, 100);
+        add_bid(&book, 2, 100);
+        assert_eq!(book.memory_pressure(), MemoryPressure::Critical);
+
+        let result = book.add_limit_order(
+            OrderId::from_u64(3),
+            90,
+            10,
+            Side::Buy,
+            TimeInForce::Gtc,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(OrderBookError::MemoryPressure {
+                price: 90,
+                reference_price: 100
+            })
+        ));
+
+        // Orders near the touch are still accepted
+        add_bid(&book, 4, 96);
+        assert!(book.get_order(OrderId::from_u64(4)).is_some());
+    }
+}
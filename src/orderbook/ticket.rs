@@ -0,0 +1,69 @@
+//! Ticket locks admitting modifications to a price level in arrival order.
+//!
+//! The atomics come from [`super::sync`], so the loom test suite can check the
+//! lock under every interleaving.
+
+use super::sync::atomic::{AtomicU64, Ordering};
+use super::sync::{hint, thread};
+use pricelevel::Side;
+
+/// Number of ticket locks levels are spread over
+const TICKET_STRIPES: usize = 64;
+
+/// Spins before a waiting update starts yielding its thread
+const SPINS_BEFORE_YIELD: u32 = 64;
+
+/// A first-come, first-served lock: callers are admitted in the order they
+/// took their ticket
+#[derive(Default)]
+struct TicketLock {
+    next: AtomicU64,
+    serving: AtomicU64,
+}
+
+impl TicketLock {
+    fn lock(&self) -> TicketGuard<'_> {
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut spins = 0;
+        while self.serving.load(Ordering::Acquire) != ticket {
+            if spins < SPINS_BEFORE_YIELD {
+                spins += 1;
+                hint::spin_loop();
+            } else {
+                thread::yield_now();
+            }
+        }
+        TicketGuard { lock: self }
+    }
+}
+
+pub(crate) struct TicketGuard<'a> {
+    lock: &'a TicketLock,
+}
+
+impl Drop for TicketGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.serving.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Ticket locks admitting modifications to each level in arrival order.
+/// Levels share a lock when their `(side, price)` falls in the same stripe
+pub(crate) struct LevelTickets {
+    stripes: [TicketLock; TICKET_STRIPES],
+}
+
+impl Default for LevelTickets {
+    fn default() -> Self {
+        Self {
+            stripes: std::array::from_fn(|_| TicketLock::default()),
+        }
+    }
+}
+
+impl LevelTickets {
+    pub(crate) fn lock(&self, side: Side, price: u64) -> TicketGuard<'_> {
+        let stripe = (price as usize).wrapping_mul(2) + usize::from(side == Side::Sell);
+        self.stripes[stripe % TICKET_STRIPES].lock()
+    }
+}
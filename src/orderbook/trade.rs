@@ -0,0 +1,158 @@
+//! Venue-style condition codes attached to trades
+
+use pricelevel::Transaction;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::error::OrderBookError;
+use super::fees::TradeFees;
+
+/// A condition qualifying how or why a trade happened.
+///
+/// Each condition has a single-letter code, which is what `Display` and
+/// `FromStr` use, so it can be carried on compact market data formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TradeCondition {
+    /// First trade of the trading day, or since the book was cleared
+    OpeningTrade,
+    /// Trade resulting from an auction uncross
+    AuctionCross,
+    /// Both sides of the trade were entered for the same account
+    InternalCross,
+    /// Trade for less than the configured round lot
+    OddLot,
+    /// Trade priced off the settlement price, from the special price section
+    TradeAtSettlement,
+    /// Trade at the lit midpoint in the non-displayed segment
+    DarkCross,
+}
+
+impl TradeCondition {
+    /// All conditions, in code order
+    pub const ALL: [TradeCondition; 6] = [
+        TradeCondition::OpeningTrade,
+        TradeCondition::AuctionCross,
+        TradeCondition::InternalCross,
+        TradeCondition::OddLot,
+        TradeCondition::TradeAtSettlement,
+        TradeCondition::DarkCross,
+    ];
+
+    /// Single-letter venue code for this condition
+    pub fn code(&self) -> char {
+        match self {
+            TradeCondition::OpeningTrade => 'O',
+            TradeCondition::AuctionCross => 'X',
+            TradeCondition::InternalCross => 'I',
+            TradeCondition::OddLot => 'L',
+            TradeCondition::TradeAtSettlement => 'T',
+            TradeCondition::DarkCross => 'D',
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+impl fmt::Display for TradeCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for TradeCondition {
+    type Err = OrderBookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TradeCondition::ALL
+            .into_iter()
+            .find(|condition| s.len() == 1 && s.starts_with(condition.code()))
+            .ok_or_else(|| OrderBookError::InvalidOperation {
+                message: format!("Unknown trade condition code: {s}"),
+            })
+    }
+}
+
+/// The set of conditions attached to a trade. An empty set is a regular trade.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "Vec<TradeCondition>", into = "Vec<TradeCondition>")]
+pub struct TradeConditions(u8);
+
+impl TradeConditions {
+    /// An empty set, i.e. a regular trade
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    /// Returns true for a regular trade without conditions
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if the set contains `condition`
+    pub fn contains(&self, condition: TradeCondition) -> bool {
+        self.0 & condition.bit() != 0
+    }
+
+    /// Add a condition to the set
+    pub fn insert(&mut self, condition: TradeCondition) {
+        self.0 |= condition.bit();
+    }
+
+    /// Returns a copy of the set with `condition` added
+    pub fn with(mut self, condition: TradeCondition) -> Self {
+        self.insert(condition);
+        self
+    }
+
+    /// Iterate over the conditions in the set, in code order
+    pub fn iter(&self) -> impl Iterator<Item = TradeCondition> + '_ {
+        TradeCondition::ALL
+            .into_iter()
+            .filter(|condition| self.contains(*condition))
+    }
+}
+
+impl From<TradeCondition> for TradeConditions {
+    fn from(condition: TradeCondition) -> Self {
+        Self::none().with(condition)
+    }
+}
+
+impl From<Vec<TradeCondition>> for TradeConditions {
+    fn from(conditions: Vec<TradeCondition>) -> Self {
+        conditions
+            .into_iter()
+            .fold(Self::none(), |set, condition| set.with(condition))
+    }
+}
+
+impl From<TradeConditions> for Vec<TradeCondition> {
+    fn from(conditions: TradeConditions) -> Self {
+        conditions.iter().collect()
+    }
+}
+
+impl fmt::Display for TradeConditions {
+    /// Concatenated condition codes, e.g. `OL` for an opening odd-lot trade
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for condition in self.iter() {
+            write!(f, "{condition}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A transaction together with its trade conditions, as published to downstream consumers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TradeReport {
+    /// The executed transaction
+    pub transaction: Transaction,
+    /// Conditions qualifying the trade
+    pub conditions: TradeConditions,
+    /// Fees charged on the trade, if the book had a fee schedule
+    #[serde(default)]
+    pub fees: Option<TradeFees>,
+}
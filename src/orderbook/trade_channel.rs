@@ -0,0 +1,231 @@
+//! Bounded, lock-free delivery of match results to a consumer thread
+//!
+//! `wasm32` targets cannot spawn threads, so there the consumer is run on the
+//! publishing thread, draining the queue after every publish.
+
+use crossbeam_queue::ArrayQueue;
+use pricelevel::MatchResult;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+use tracing::trace;
+
+/// How long the consumer sleeps between checks when it was not woken up explicitly
+#[cfg(not(target_arch = "wasm32"))]
+const CONSUMER_PARK_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// The consumer thread, joined on drop
+#[cfg(not(target_arch = "wasm32"))]
+type Consumer = Option<JoinHandle<()>>;
+
+/// The consumer callback, run by whichever thread publishes
+#[cfg(target_arch = "wasm32")]
+type Consumer = std::sync::Mutex<Box<dyn FnMut(MatchResult) + Send>>;
+
+/// What the producer does when the trade queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the new match result and count it, never blocking the matching thread
+    Drop,
+    /// Wait until the consumer frees a slot, so no result is lost while the
+    /// consumer runs. Once the consumer has stopped, or cannot run because it
+    /// is the one publishing, results are dropped and counted as with `Drop`
+    Park,
+}
+
+/// State shared between the order book and the consumer thread
+struct Shared {
+    queue: ArrayQueue<MatchResult>,
+    // Only the consumer thread waits for it
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    closed: AtomicBool,
+    published: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// A bounded queue of match results drained by a dedicated consumer thread.
+///
+/// Publishing only pushes onto a lock-free `ArrayQueue`, so a slow consumer
+/// does not add latency to order entry. When the book is dropped the consumer
+/// drains whatever is left in the queue and the thread is joined.
+pub struct TradeChannel {
+    shared: Arc<Shared>,
+    policy: OverflowPolicy,
+    consumer: Consumer,
+}
+
+impl TradeChannel {
+    /// Spawn the consumer thread and create a channel holding up to `capacity` results.
+    /// On `wasm32` no thread is spawned and `name` is only used for tracing
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero or the consumer thread cannot be spawned.
+    pub fn new<F>(name: &str, capacity: usize, policy: OverflowPolicy, consumer: F) -> Self
+    where
+        F: FnMut(MatchResult) + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            queue: ArrayQueue::new(capacity),
+            closed: AtomicBool::new(false),
+            published: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let consumer = Some(spawn_consumer(name, Arc::clone(&shared), consumer));
+        #[cfg(target_arch = "wasm32")]
+        let consumer: Consumer = {
+            trace!("Trade channel {} consumed inline", name);
+            std::sync::Mutex::new(Box::new(consumer))
+        };
+
+        Self {
+            shared,
+            policy,
+            consumer,
+        }
+    }
+
+    /// Queue a match result for the consumer, applying the overflow policy if the queue is full.
+    /// Returns false if the result was dropped.
+    pub fn publish(&self, match_result: MatchResult) -> bool {
+        let mut pending = match_result;
+        loop {
+            match self.shared.queue.push(pending) {
+                Ok(()) => {
+                    self.shared.published.fetch_add(1, Ordering::Relaxed);
+                    self.wake_consumer();
+                    return true;
+                }
+                Err(rejected) => match self.policy {
+                    OverflowPolicy::Drop => return self.drop_result(&rejected),
+                    OverflowPolicy::Park => {
+                        self.wake_consumer();
+                        if !self.consumer_running() {
+                            // Nothing will ever free a slot
+                            return self.drop_result(&rejected);
+                        }
+                        pending = rejected;
+                        thread::yield_now();
+                    }
+                },
+            }
+        }
+    }
+
+    fn drop_result(&self, match_result: &MatchResult) -> bool {
+        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        trace!(
+            "Trade channel full, dropping match result for order {}",
+            match_result.order_id
+        );
+        false
+    }
+
+    /// Whether the consumer thread is still there to free slots. It stops
+    /// early if the consumer callback panicked
+    #[cfg(not(target_arch = "wasm32"))]
+    fn consumer_running(&self) -> bool {
+        self.consumer
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Whether the consumer can be run by this thread, which it cannot from
+    /// inside the consumer itself
+    #[cfg(target_arch = "wasm32")]
+    fn consumer_running(&self) -> bool {
+        self.consumer.try_lock().is_ok()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn wake_consumer(&self) {
+        if let Some(handle) = &self.consumer {
+            handle.thread().unpark();
+        }
+    }
+
+    /// Drain the queue into the consumer. A consumer publishing into its own
+    /// channel finds the lock taken and leaves its result to the running drain
+    #[cfg(target_arch = "wasm32")]
+    fn wake_consumer(&self) {
+        let Ok(mut consumer) = self.consumer.try_lock() else {
+            return;
+        };
+        while let Some(match_result) = self.shared.queue.pop() {
+            consumer(match_result);
+        }
+    }
+
+    /// The overflow policy of this channel
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Maximum number of results the queue can hold
+    pub fn capacity(&self) -> usize {
+        self.shared.queue.capacity()
+    }
+
+    /// Number of results waiting for the consumer
+    pub fn pending(&self) -> usize {
+        self.shared.queue.len()
+    }
+
+    /// Number of results successfully queued so far
+    pub fn published_count(&self) -> u64 {
+        self.shared.published.load(Ordering::Relaxed)
+    }
+
+    /// Number of results dropped because the queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_consumer<F>(name: &str, consumer_shared: Arc<Shared>, mut consumer: F) -> JoinHandle<()>
+where
+    F: FnMut(MatchResult) + Send + 'static,
+{
+    thread::Builder::new()
+        .name(format!("{name}-trades"))
+        .spawn(move || {
+            loop {
+                while let Some(match_result) = consumer_shared.queue.pop() {
+                    consumer(match_result);
+                }
+                if consumer_shared.closed.load(Ordering::Acquire) {
+                    // Results published before closing are still delivered
+                    while let Some(match_result) = consumer_shared.queue.pop() {
+                        consumer(match_result);
+                    }
+                    break;
+                }
+                thread::park_timeout(CONSUMER_PARK_TIMEOUT);
+            }
+        })
+        .expect("failed to spawn trade channel consumer thread")
+}
+
+impl Drop for TradeChannel {
+    #[cfg(target_arch = "wasm32")]
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.wake_consumer();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        if let Some(handle) = self.consumer.take() {
+            handle.thread().unpark();
+            // A panicking consumer has already reported its panic
+            let _ = handle.join();
+        }
+    }
+}
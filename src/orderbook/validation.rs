@@ -0,0 +1,385 @@
+//! Consistency checks over the internal structures of an order book
+
+use super::book::OrderBook;
+use super::side::BookSide;
+use pricelevel::{OrderId, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A single broken invariant found by [`OrderBook::validate`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidationIssue {
+    /// The best bid is at or above the best ask outside of a match
+    CrossedBook {
+        /// Best bid price
+        best_bid: u64,
+        /// Best ask price
+        best_ask: u64,
+    },
+    /// An id in the location index has no order at the recorded level
+    MissingOrder {
+        /// Id of the order
+        order_id: OrderId,
+        /// Recorded price
+        price: u64,
+        /// Recorded side
+        side: Side,
+    },
+    /// An order rests at a level but is missing from the location index
+    UnindexedOrder {
+        /// Id of the order
+        order_id: OrderId,
+        /// Price of the level holding it
+        price: u64,
+        /// Side of the level holding it
+        side: Side,
+    },
+    /// The location index points at a different price or side than where the order rests
+    WrongLocation {
+        /// Id of the order
+        order_id: OrderId,
+        /// Price and side recorded in the index
+        indexed: (u64, Side),
+        /// Price and side where the order actually rests
+        actual: (u64, Side),
+    },
+    /// A level's aggregate does not match the sum over its orders
+    LevelAggregateMismatch {
+        /// Price of the level
+        price: u64,
+        /// Side of the level
+        side: Side,
+        /// Which aggregate is off (`visible_quantity`, `hidden_quantity` or `order_count`)
+        field: String,
+        /// Value reported by the level
+        reported: u64,
+        /// Value computed from the orders
+        computed: u64,
+    },
+    /// An order sits on a level with a different price or on the wrong side of the book
+    MisplacedOrder {
+        /// Id of the order
+        order_id: OrderId,
+        /// Price of the level holding it
+        level_price: u64,
+        /// Side of the level holding it
+        level_side: Side,
+    },
+    /// A level with no orders was left in the book
+    EmptyLevel {
+        /// Price of the level
+        price: u64,
+        /// Side of the level
+        side: Side,
+    },
+    /// The cached best price differs from the best price in the book
+    StaleCache {
+        /// Side of the cached price
+        side: Side,
+        /// Cached price
+        cached: u64,
+        /// Price computed from the levels
+        actual: Option<u64>,
+    },
+    /// The sorted price index and the price levels of a side disagree
+    PriceIndexMismatch {
+        /// Price present in one but not the other
+        price: u64,
+        /// Side of the book
+        side: Side,
+        /// True if a level exists at the price but the index lacks it, false
+        /// if the index lists a price that has no level
+        has_level: bool,
+    },
+    /// Execution constraints are kept for an order that no longer rests in the book
+    OrphanedConstraints {
+        /// Id of the order
+        order_id: OrderId,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::CrossedBook { best_bid, best_ask } => {
+                write!(
+                    f,
+                    "crossed book: best bid {best_bid} >= best ask {best_ask}"
+                )
+            }
+            ValidationIssue::MissingOrder {
+                order_id,
+                price,
+                side,
+            } => write!(
+                f,
+                "order {order_id} indexed at {side} {price} but not found there"
+            ),
+            ValidationIssue::UnindexedOrder {
+                order_id,
+                price,
+                side,
+            } => write!(
+                f,
+                "order {order_id} rests at {side} {price} but is not indexed"
+            ),
+            ValidationIssue::WrongLocation {
+                order_id,
+                indexed,
+                actual,
+            } => write!(
+                f,
+                "order {order_id} indexed at {} {} but rests at {} {}",
+                indexed.1, indexed.0, actual.1, actual.0
+            ),
+            ValidationIssue::LevelAggregateMismatch {
+                price,
+                side,
+                field,
+                reported,
+                computed,
+            } => write!(
+                f,
+                "level {side} {price}: {field} is {reported} but orders add up to {computed}"
+            ),
+            ValidationIssue::MisplacedOrder {
+                order_id,
+                level_price,
+                level_side,
+            } => write!(
+                f,
+                "order {order_id} does not belong to level {level_side} {level_price}"
+            ),
+            ValidationIssue::EmptyLevel { price, side } => {
+                write!(f, "empty level left at {side} {price}")
+            }
+            ValidationIssue::StaleCache {
+                side,
+                cached,
+                actual,
+            } => write!(
+                f,
+                "cached best {side} price {cached} differs from actual {actual:?}"
+            ),
+            ValidationIssue::PriceIndexMismatch {
+                price,
+                side,
+                has_level: true,
+            } => write!(f, "level {side} {price} is missing from the price index"),
+            ValidationIssue::PriceIndexMismatch {
+                price,
+                side,
+                has_level: false,
+            } => write!(f, "price index lists {side} {price} which has no level"),
+            ValidationIssue::OrphanedConstraints { order_id } => {
+                write!(
+                    f,
+                    "constraints kept for order {order_id} which is not resting"
+                )
+            }
+        }
+    }
+}
+
+/// Result of [`OrderBook::validate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Number of price levels inspected
+    pub levels_checked: usize,
+    /// Number of resting orders inspected
+    pub orders_checked: usize,
+    /// Every broken invariant found
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns true if no invariant is broken
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} levels, {} orders checked, {} issues",
+            self.levels_checked,
+            self.orders_checked,
+            self.issues.len()
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Check the internal invariants of the book and report every violation.
+    ///
+    /// Verifies that the book is not crossed, that the order location index and
+    /// the price levels agree in both directions, that level aggregates equal the
+    /// sums over their orders, that no empty level is left behind, that the price
+    /// index lists exactly the prices with a level, that the cached best prices
+    /// are current and that constraints are only kept for resting
+    /// orders. The checks read the book without locking it as a whole, so running
+    /// them while other threads modify the book can report transient issues.
+    pub fn validate(&self) -> ValidationReport {
+        if let Some(storage) = self.compact_storage() {
+            return storage.validate();
+        }
+        let mut report = ValidationReport::default();
+        let mut resting: HashSet<OrderId> = HashSet::new();
+
+        self.validate_side(Side::Buy, &self.bids, &mut report, &mut resting);
+        self.validate_side(Side::Sell, &self.asks, &mut report, &mut resting);
+
+        for entry in self.order_locations.iter() {
+            let (price, side) = *entry.value();
+            if !resting.contains(entry.key()) {
+                report.issues.push(ValidationIssue::MissingOrder {
+                    order_id: *entry.key(),
+                    price,
+                    side,
+                });
+            }
+        }
+
+        for entry in self.order_constraints.iter() {
+            if !resting.contains(entry.key()) {
+                report.issues.push(ValidationIssue::OrphanedConstraints {
+                    order_id: *entry.key(),
+                });
+            }
+        }
+
+        let best_bid = self.bids.iter().map(|item| *item.key()).max();
+        let best_ask = self.asks.iter().map(|item| *item.key()).min();
+        if let (Some(best_bid), Some(best_ask)) = (best_bid, best_ask)
+            && best_bid >= best_ask
+        {
+            report
+                .issues
+                .push(ValidationIssue::CrossedBook { best_bid, best_ask });
+        }
+
+        for (side, cached, actual) in [
+            (Side::Buy, self.cache.get_cached_best_bid(), best_bid),
+            (Side::Sell, self.cache.get_cached_best_ask(), best_ask),
+        ] {
+            if let Some(cached) = cached
+                && Some(cached) != actual
+            {
+                report.issues.push(ValidationIssue::StaleCache {
+                    side,
+                    cached,
+                    actual,
+                });
+            }
+        }
+
+        report
+    }
+
+    fn validate_side(
+        &self,
+        side: Side,
+        levels: &BookSide,
+        report: &mut ValidationReport,
+        resting: &mut HashSet<OrderId>,
+    ) {
+        let mut unlevelled: HashSet<u64> = levels.indexed_prices().into_iter().collect();
+        for entry in levels.iter() {
+            let price = *entry.key();
+            let level = entry.value();
+            let orders = level.iter_orders();
+            if !unlevelled.remove(&price) {
+                report.issues.push(ValidationIssue::PriceIndexMismatch {
+                    price,
+                    side,
+                    has_level: true,
+                });
+            }
+            report.levels_checked += 1;
+            report.orders_checked += orders.len();
+
+            if orders.is_empty() {
+                report
+                    .issues
+                    .push(ValidationIssue::EmptyLevel { price, side });
+            }
+
+            let mut visible = 0;
+            let mut hidden = 0;
+            for order in &orders {
+                let order_id = order.id();
+                resting.insert(order_id);
+                visible += order.visible_quantity();
+                hidden += order.hidden_quantity();
+
+                if order.price() != price || order.side() != side {
+                    report.issues.push(ValidationIssue::MisplacedOrder {
+                        order_id,
+                        level_price: price,
+                        level_side: side,
+                    });
+                }
+
+                match self
+                    .order_locations
+                    .get(&order_id)
+                    .map(|location| *location)
+                {
+                    None => report.issues.push(ValidationIssue::UnindexedOrder {
+                        order_id,
+                        price,
+                        side,
+                    }),
+                    Some(indexed) if indexed != (price, side) => {
+                        report.issues.push(ValidationIssue::WrongLocation {
+                            order_id,
+                            indexed,
+                            actual: (price, side),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            for (field, reported, computed) in [
+                ("visible_quantity", level.visible_quantity(), visible),
+                ("hidden_quantity", level.hidden_quantity(), hidden),
+                (
+                    "order_count",
+                    level.order_count() as u64,
+                    orders.len() as u64,
+                ),
+            ] {
+                if reported != computed {
+                    report.issues.push(ValidationIssue::LevelAggregateMismatch {
+                        price,
+                        side,
+                        field: field.to_string(),
+                        reported,
+                        computed,
+                    });
+                }
+            }
+        }
+
+        let mut unlevelled: Vec<u64> = unlevelled.into_iter().collect();
+        unlevelled.sort_unstable();
+        for price in unlevelled {
+            report.issues.push(ValidationIssue::PriceIndexMismatch {
+                price,
+                side,
+                has_level: false,
+            });
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Read-only handles onto one side of a book.
+//!
+//! Every method of [`OrderBook`] takes `&self`, so a reference to the book is
+//! enough to add, cancel or match orders. A [`BidView`] or [`AskView`] wraps
+//! that reference and exposes only reads of its side, for market-data
+//! components that have no business changing the book.
+
+use super::book::OrderBook;
+use super::levels::{LevelIter, LevelSummary, OrderIter};
+use pricelevel::Side;
+
+macro_rules! side_view {
+    ($(#[$meta:meta])* $name:ident, $side:expr, $levels:ident, $best:ident, $iter:ident) => {
+        $(#[$meta])*
+        pub struct $name<'a, T> {
+            book: &'a OrderBook<T>,
+        }
+
+        // Manual impls, as deriving would require `T: Clone`
+        impl<T> Clone for $name<'_, T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T> Copy for $name<'_, T> {}
+
+        impl<'a, T> $name<'a, T>
+        where
+            T: Clone + Send + Sync + Default + 'static,
+        {
+            /// Side of the book the view reads
+            pub const SIDE: Side = $side;
+
+            /// Symbol of the book
+            pub fn symbol(&self) -> &'a str {
+                &self.book.symbol
+            }
+
+            /// Version of the book, see [`OrderBook::version`]
+            pub fn version(&self) -> u64 {
+                self.book.version()
+            }
+
+            /// Best price of the side, if any
+            pub fn best_price(&self) -> Option<u64> {
+                self.book.$best()
+            }
+
+            /// Levels of the side best price first, summarized lazily
+            pub fn levels(&self) -> LevelIter<'a> {
+                self.book.$iter()
+            }
+
+            /// Number of price levels of the side
+            pub fn level_count(&self) -> usize {
+                self.book.$levels.len()
+            }
+
+            /// Returns true if no order rests on the side
+            pub fn is_empty(&self) -> bool {
+                self.book.$levels.is_empty()
+            }
+
+            /// Summary of the level at `price`, if one rests there
+            pub fn level(&self, price: u64) -> Option<LevelSummary> {
+                self.book
+                    .$levels
+                    .get(&price)
+                    .map(|price_level| LevelSummary::of(&price_level))
+            }
+
+            /// Orders resting at `price`, in priority order
+            pub fn orders_at(&self, price: u64) -> OrderIter<T> {
+                self.book.iter_orders_at_price(price, $side)
+            }
+
+            /// Quantity displayed on the side
+            pub fn visible_volume(&self) -> u64 {
+                self.book
+                    .$levels
+                    .iter()
+                    .map(|price_level| price_level.visible_quantity())
+                    .sum()
+            }
+
+            /// Quantity resting on the side, visible and hidden
+            pub fn total_volume(&self) -> u64 {
+                self.book
+                    .$levels
+                    .iter()
+                    .map(|price_level| price_level.total_quantity())
+                    .sum()
+            }
+        }
+    };
+}
+
+side_view!(
+    /// Read-only handle onto the bids of a book, see [`OrderBook::bid_view`]
+    BidView,
+    Side::Buy,
+    bids,
+    best_bid,
+    iter_bids
+);
+
+side_view!(
+    /// Read-only handle onto the asks of a book, see [`OrderBook::ask_view`]
+    AskView,
+    Side::Sell,
+    asks,
+    best_ask,
+    iter_asks
+);
+
+impl<T> OrderBook<T>
+where
+    T: Clone + Send + Sync + Default + 'static,
+{
+    /// Read-only handle onto the bids, to hand to components that only read them
+    pub fn bid_view(&self) -> BidView<'_, T> {
+        BidView { book: self }
+    }
+
+    /// Read-only handle onto the asks, to hand to components that only read them
+    pub fn ask_view(&self) -> AskView<'_, T> {
+        AskView { book: self }
+    }
+}
@@ -0,0 +1,134 @@
+//! Memory watermarks that let a long-running book degrade gracefully under load
+
+use pricelevel::{OrderId, OrderType, PriceLevel, Side};
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Rough size of one resting order: the order itself behind an `Arc`, plus its
+/// entry in the order location index
+pub const ESTIMATED_ORDER_BYTES: u64 = (size_of::<OrderType<()>>()
+    + 2 * size_of::<usize>()
+    + size_of::<OrderId>()
+    + size_of::<(u64, Side)>()) as u64;
+
+/// Rough size of one price level: the level behind an `Arc`, plus its map entry
+pub const ESTIMATED_LEVEL_BYTES: u64 =
+    (size_of::<PriceLevel>() + 2 * size_of::<usize>() + size_of::<u64>()) as u64;
+
+/// How close the book is to its configured limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum MemoryPressure {
+    /// Below every warning threshold
+    Normal = 0,
+    /// At or above a warning threshold
+    Warning = 1,
+    /// At or above a critical threshold
+    Critical = 2,
+}
+
+impl MemoryPressure {
+    pub(super) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => MemoryPressure::Normal,
+            1 => MemoryPressure::Warning,
+            _ => MemoryPressure::Critical,
+        }
+    }
+}
+
+/// A pair of thresholds for one measure of usage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Usage at which the book reports `MemoryPressure::Warning`
+    pub warning: u64,
+    /// Usage at which the book reports `MemoryPressure::Critical`
+    pub critical: u64,
+}
+
+impl Watermark {
+    /// Create a watermark with the given warning and critical thresholds
+    pub fn new(warning: u64, critical: u64) -> Self {
+        Self { warning, critical }
+    }
+
+    /// Pressure level for the given usage
+    pub fn pressure(&self, usage: u64) -> MemoryPressure {
+        if usage >= self.critical {
+            MemoryPressure::Critical
+        } else if usage >= self.warning {
+            MemoryPressure::Warning
+        } else {
+            MemoryPressure::Normal
+        }
+    }
+}
+
+/// Watermarks monitored by the book. Measures left as `None` are not monitored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryWatermarks {
+    /// Thresholds on the number of resting orders
+    pub orders: Option<Watermark>,
+    /// Thresholds on the number of price levels across both sides
+    pub levels: Option<Watermark>,
+    /// Thresholds on the estimated memory used by orders and levels, in bytes
+    pub estimated_bytes: Option<Watermark>,
+    /// While pressure is critical, reject new orders priced further than this
+    /// from the best price on their side (or the opposite side if theirs is empty)
+    pub reject_far_orders_beyond: Option<u64>,
+}
+
+impl MemoryWatermarks {
+    /// Highest pressure level across all monitored measures
+    pub fn pressure(&self, usage: &MemoryUsage) -> MemoryPressure {
+        [
+            self.orders.map(|w| w.pressure(usage.orders)),
+            self.levels.map(|w| w.pressure(usage.levels)),
+            self.estimated_bytes
+                .map(|w| w.pressure(usage.estimated_bytes)),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(MemoryPressure::Normal)
+    }
+}
+
+/// Current usage of the book as measured against its watermarks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// Number of resting orders
+    pub orders: u64,
+    /// Number of price levels across both sides
+    pub levels: u64,
+    /// Estimated bytes used by orders and levels
+    pub estimated_bytes: u64,
+}
+
+impl MemoryUsage {
+    /// Build a usage record, estimating the bytes from the order and level counts
+    pub fn estimate(orders: u64, levels: u64) -> Self {
+        Self {
+            orders,
+            levels,
+            estimated_bytes: orders * ESTIMATED_ORDER_BYTES + levels * ESTIMATED_LEVEL_BYTES,
+        }
+    }
+}
+
+/// Emitted when the pressure level of a book changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryPressureEvent {
+    /// Symbol of the book
+    pub symbol: String,
+    /// Pressure level before the change
+    pub previous: MemoryPressure,
+    /// Pressure level after the change
+    pub current: MemoryPressure,
+    /// Usage that triggered the change
+    pub usage: MemoryUsage,
+}
+
+/// Callback invoked whenever the memory pressure level changes
+pub type MemoryPressureListener = Arc<dyn Fn(&MemoryPressureEvent) + Send + Sync>;
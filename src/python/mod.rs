@@ -0,0 +1,393 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! The extension module is named `orderbook_rs` and exposes an `OrderBook`
+//! class working with integer order ids, prices and quantities, and sides and
+//! times in force given as strings. Build it with maturin:
+//!
+//! ```text
+//! maturin develop --features python
+//! ```
+//!
+//! ```python
+//! from orderbook_rs import OrderBook
+//!
+//! book = OrderBook("BTC/USD")
+//! book.on_trade(lambda trade: print(trade.price, trade.quantity))
+//! book.add_limit_order(1, 100, 10, "sell")
+//! trades = book.submit_market_order(2, 4, "buy")
+//! ```
+
+mod tests;
+
+use crate::orderbook::{
+    FillNotification, OrderBook, OrderBookError, OrderBookEvent, OrderBookSnapshot,
+};
+use pricelevel::{OrderId, PriceLevelSnapshot, Side, TimeInForce};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+fn to_py_err(error: OrderBookError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn parse_side(side: &str) -> PyResult<Side> {
+    match side.to_ascii_lowercase().as_str() {
+        "buy" | "b" | "bid" => Ok(Side::Buy),
+        "sell" | "s" | "ask" => Ok(Side::Sell),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid side {side:?}, expected \"buy\" or \"sell\""
+        ))),
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+fn parse_time_in_force(time_in_force: &str, expiry: Option<u64>) -> PyResult<TimeInForce> {
+    match (time_in_force.to_ascii_uppercase().as_str(), expiry) {
+        ("GTC", None) => Ok(TimeInForce::Gtc),
+        ("IOC", None) => Ok(TimeInForce::Ioc),
+        ("FOK", None) => Ok(TimeInForce::Fok),
+        ("DAY", None) => Ok(TimeInForce::Day),
+        ("GTD", Some(expiry)) => Ok(TimeInForce::Gtd(expiry)),
+        ("GTD", None) => Err(PyValueError::new_err("GTD orders need an expiry")),
+        (_, Some(_)) => Err(PyValueError::new_err(
+            "an expiry is only allowed for GTD orders",
+        )),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid time in force {time_in_force:?}, expected GTC, IOC, FOK, DAY or GTD"
+        ))),
+    }
+}
+
+/// A trade between an incoming order and a resting one
+#[pyclass(name = "Trade", module = "orderbook_rs", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyTrade {
+    /// Id of the trade
+    pub transaction_id: String,
+    /// Price of the trade
+    pub price: u64,
+    /// Quantity traded
+    pub quantity: u64,
+    /// Side of the incoming order, "buy" or "sell"
+    pub taker_side: &'static str,
+    /// Id of the incoming order
+    pub taker_order_id: String,
+    /// Id of the resting order
+    pub maker_order_id: String,
+    /// When the trade happened (milliseconds since epoch)
+    pub timestamp: u64,
+}
+
+#[pymethods]
+impl PyTrade {
+    fn __repr__(&self) -> String {
+        format!(
+            "Trade(price={}, quantity={}, taker_side={:?}, taker_order_id={:?}, maker_order_id={:?})",
+            self.price, self.quantity, self.taker_side, self.taker_order_id, self.maker_order_id
+        )
+    }
+}
+
+/// Pair the taker and maker notifications of each trade, in trade order
+fn trades_from_fills(fills: &[FillNotification]) -> Vec<PyTrade> {
+    let takers: HashMap<_, _> = fills
+        .iter()
+        .filter(|fill| !fill.is_maker)
+        .map(|fill| (fill.transaction_id, fill))
+        .collect();
+    fills
+        .iter()
+        .filter(|fill| fill.is_maker)
+        .filter_map(|maker| {
+            let taker = takers.get(&maker.transaction_id)?;
+            Some(PyTrade {
+                transaction_id: maker.transaction_id.to_string(),
+                price: maker.price,
+                quantity: maker.quantity,
+                taker_side: side_name(taker.side),
+                taker_order_id: taker.order_id.to_string(),
+                maker_order_id: maker.order_id.to_string(),
+                timestamp: maker.timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Price levels of one side as `(price, visible_quantity, hidden_quantity, order_count)`
+type PyLevels = Vec<(u64, u64, u64, usize)>;
+
+fn py_levels(levels: &[PriceLevelSnapshot]) -> PyLevels {
+    levels
+        .iter()
+        .map(|level| {
+            (
+                level.price,
+                level.visible_quantity,
+                level.hidden_quantity,
+                level.order_count,
+            )
+        })
+        .collect()
+}
+
+/// Aggregated price levels of both sides at a point in time, best price first
+#[pyclass(name = "Snapshot", module = "orderbook_rs", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PySnapshot {
+    /// Symbol of the book
+    pub symbol: String,
+    /// When the snapshot was taken (milliseconds since epoch)
+    pub timestamp: u64,
+    /// Bid levels as `(price, visible_quantity, hidden_quantity, order_count)`
+    pub bids: PyLevels,
+    /// Ask levels as `(price, visible_quantity, hidden_quantity, order_count)`
+    pub asks: PyLevels,
+}
+
+impl From<OrderBookSnapshot> for PySnapshot {
+    fn from(snapshot: OrderBookSnapshot) -> Self {
+        Self {
+            bids: py_levels(&snapshot.bids),
+            asks: py_levels(&snapshot.asks),
+            symbol: snapshot.symbol,
+            timestamp: snapshot.timestamp,
+        }
+    }
+}
+
+/// A limit order book, driven from Python.
+///
+/// Order ids are integers. Trade callbacks registered with `on_trade` run
+/// after the call that produced the trades returns to the book, on the
+/// calling thread.
+#[pyclass(name = "OrderBook", module = "orderbook_rs")]
+pub struct PyOrderBook {
+    book: OrderBook<()>,
+    fills: Arc<Mutex<Vec<FillNotification>>>,
+    trade_callbacks: Vec<Py<PyAny>>,
+}
+
+impl PyOrderBook {
+    /// Hand the trades of the last call to the callbacks, returning them
+    fn take_trades(&self, py: Python<'_>) -> PyResult<Vec<PyTrade>> {
+        let fills = std::mem::take(
+            &mut *self
+                .fills
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        let trades = trades_from_fills(&fills);
+        for trade in &trades {
+            for callback in &self.trade_callbacks {
+                callback.call1(py, (trade.clone(),))?;
+            }
+        }
+        Ok(trades)
+    }
+
+    /// Run an order entry call, then dispatch the trades it made
+    fn submit<R>(
+        &self,
+        py: Python<'_>,
+        submit: impl FnOnce(&OrderBook<()>) -> Result<R, OrderBookError>,
+    ) -> PyResult<Vec<PyTrade>> {
+        let result = submit(&self.book);
+        // Trades made before a failure still happened
+        let trades = self.take_trades(py)?;
+        result.map_err(to_py_err)?;
+        Ok(trades)
+    }
+}
+
+#[pymethods]
+impl PyOrderBook {
+    #[new]
+    fn new(symbol: &str) -> Self {
+        let fills = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&fills);
+        let mut book = OrderBook::new(symbol);
+        book.set_event_listener(Arc::new(move |event| {
+            if let OrderBookEvent::OrderFilled(fill) = event {
+                recorded
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(*fill);
+            }
+        }));
+        Self {
+            book,
+            fills,
+            trade_callbacks: Vec::new(),
+        }
+    }
+
+    /// Symbol of the book
+    #[getter]
+    fn symbol(&self) -> &str {
+        self.book.symbol()
+    }
+
+    /// Call `callback(trade)` for every trade from now on
+    fn on_trade(&mut self, callback: Py<PyAny>) {
+        self.trade_callbacks.push(callback);
+    }
+
+    /// Add a limit order, matching it if it crosses. Returns the trades it made
+    #[pyo3(signature = (order_id, price, quantity, side, time_in_force = "GTC", expiry = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn add_limit_order(
+        &self,
+        py: Python<'_>,
+        order_id: u64,
+        price: u64,
+        quantity: u64,
+        side: &str,
+        time_in_force: &str,
+        expiry: Option<u64>,
+    ) -> PyResult<Vec<PyTrade>> {
+        let side = parse_side(side)?;
+        let time_in_force = parse_time_in_force(time_in_force, expiry)?;
+        self.submit(py, |book| {
+            book.add_limit_order(
+                OrderId::from_u64(order_id),
+                price,
+                quantity,
+                side,
+                time_in_force,
+                None,
+            )
+        })
+    }
+
+    /// Add an iceberg order showing `visible_quantity` and hiding `hidden_quantity`
+    #[pyo3(signature = (order_id, price, visible_quantity, hidden_quantity, side, time_in_force = "GTC", expiry = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn add_iceberg_order(
+        &self,
+        py: Python<'_>,
+        order_id: u64,
+        price: u64,
+        visible_quantity: u64,
+        hidden_quantity: u64,
+        side: &str,
+        time_in_force: &str,
+        expiry: Option<u64>,
+    ) -> PyResult<Vec<PyTrade>> {
+        let side = parse_side(side)?;
+        let time_in_force = parse_time_in_force(time_in_force, expiry)?;
+        self.submit(py, |book| {
+            book.add_iceberg_order(
+                OrderId::from_u64(order_id),
+                price,
+                visible_quantity,
+                hidden_quantity,
+                side,
+                time_in_force,
+                None,
+            )
+        })
+    }
+
+    /// Add a post-only order, rejected with `ValueError` if it would cross
+    #[pyo3(signature = (order_id, price, quantity, side, time_in_force = "GTC", expiry = None))]
+    fn add_post_only_order(
+        &self,
+        order_id: u64,
+        price: u64,
+        quantity: u64,
+        side: &str,
+        time_in_force: &str,
+        expiry: Option<u64>,
+    ) -> PyResult<()> {
+        self.book
+            .add_post_only_order(
+                OrderId::from_u64(order_id),
+                price,
+                quantity,
+                parse_side(side)?,
+                parse_time_in_force(time_in_force, expiry)?,
+                None,
+            )
+            .map(|_| ())
+            .map_err(to_py_err)
+    }
+
+    /// Execute a market order against the book. Returns the trades it made
+    fn submit_market_order(
+        &self,
+        py: Python<'_>,
+        order_id: u64,
+        quantity: u64,
+        side: &str,
+    ) -> PyResult<Vec<PyTrade>> {
+        let side = parse_side(side)?;
+        self.submit(py, |book| {
+            book.submit_market_order(OrderId::from_u64(order_id), quantity, side)
+        })
+    }
+
+    /// Cancel a resting order. Returns `False` if it was not resting
+    fn cancel_order(&self, order_id: u64) -> PyResult<bool> {
+        self.book
+            .cancel_order(OrderId::from_u64(order_id))
+            .map(|cancelled| cancelled.is_some())
+            .map_err(to_py_err)
+    }
+
+    /// Highest bid price, or `None`
+    fn best_bid(&self) -> Option<u64> {
+        self.book.best_bid()
+    }
+
+    /// Lowest ask price, or `None`
+    fn best_ask(&self) -> Option<u64> {
+        self.book.best_ask()
+    }
+
+    /// Midpoint of the best bid and ask, or `None`
+    fn mid_price(&self) -> Option<f64> {
+        self.book.mid_price()
+    }
+
+    /// Best ask minus best bid, or `None`
+    fn spread(&self) -> Option<u64> {
+        self.book.spread()
+    }
+
+    /// Price of the last trade, or `None`
+    fn last_trade_price(&self) -> Option<u64> {
+        self.book.last_trade_price()
+    }
+
+    /// The `depth` best levels of each side
+    #[pyo3(signature = (depth = 10))]
+    fn snapshot(&self, depth: usize) -> PySnapshot {
+        self.book.create_snapshot(depth).into()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderBook(symbol={:?}, best_bid={:?}, best_ask={:?})",
+            self.book.symbol(),
+            self.book.best_bid(),
+            self.book.best_ask()
+        )
+    }
+}
+
+/// The `orderbook_rs` Python extension module
+#[pymodule]
+fn orderbook_rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyOrderBook>()?;
+    module.add_class::<PyTrade>()?;
+    module.add_class::<PySnapshot>()?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use crate::python::{PyOrderBook, orderbook_rs};
+    use pyo3::ffi::c_str;
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyList};
+
+    #[test]
+    fn test_trades_are_returned_and_dispatched() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut book = PyOrderBook::new("TEST_SYMBOL");
+            let seen = PyList::empty(py);
+            book.on_trade(seen.getattr("append").unwrap().unbind());
+
+            book.add_limit_order(py, 1, 100, 10, "sell", "GTC", None)
+                .unwrap();
+            book.add_limit_order(py, 2, 101, 10, "sell", "GTC", None)
+                .unwrap();
+            let trades = book.submit_market_order(py, 3, 12, "buy").unwrap();
+
+            assert_eq!(trades.len(), 2);
+            assert_eq!((trades[0].price, trades[0].quantity), (100, 10));
+            assert_eq!((trades[1].price, trades[1].quantity), (101, 2));
+            assert_eq!(trades[0].taker_side, "buy");
+            assert_eq!(seen.len(), 2);
+            assert_eq!(book.last_trade_price(), Some(101));
+            assert_eq!(book.snapshot(10).asks, vec![(101, 8, 0, 1)]);
+        });
+    }
+
+    #[test]
+    fn test_invalid_arguments_raise() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let book = PyOrderBook::new("TEST_SYMBOL");
+            assert!(
+                book.add_limit_order(py, 1, 100, 10, "up", "GTC", None)
+                    .is_err()
+            );
+            assert!(
+                book.add_limit_order(py, 1, 100, 10, "buy", "GTD", None)
+                    .is_err()
+            );
+            assert!(
+                book.add_limit_order(py, 1, 100, 10, "buy", "GTD", Some(u64::MAX))
+                    .is_ok()
+            );
+            assert!(book.cancel_order(1).unwrap());
+            assert!(!book.cancel_order(1).unwrap());
+            assert!(book.submit_market_order(py, 2, 1, "sell").is_err());
+        });
+    }
+
+    #[test]
+    fn test_module_from_python() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "orderbook_rs").unwrap();
+            orderbook_rs(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("orderbook_rs", module).unwrap();
+            py.run(
+                c_str!(
+                    r#"
+book = orderbook_rs.OrderBook("BTC/USD")
+trades = []
+book.on_trade(trades.append)
+book.add_limit_order(1, 100, 10, "buy")
+book.add_iceberg_order(2, 102, 2, 8, "sell", time_in_force="DAY")
+book.add_limit_order(3, 100, 4, "sell", "IOC")
+snapshot = book.snapshot(depth=5)
+assert book.symbol == "BTC/USD"
+assert [trade.quantity for trade in trades] == [4]
+assert snapshot.bids == [(100, 6, 0, 1)]
+assert snapshot.asks == [(102, 2, 8, 1)]
+assert book.spread() == 2
+try:
+    book.add_post_only_order(4, 102, 1, "buy")
+    raise AssertionError("post-only order crossed")
+except ValueError:
+    pass
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+}
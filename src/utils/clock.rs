@@ -0,0 +1,60 @@
+use super::time::{current_time_millis, current_time_nanos};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the current time used by an order book to stamp orders, check
+/// expiry and throttle order entry
+pub trait Clock: Send + Sync {
+    /// Current time in milliseconds since UNIX epoch
+    fn now_millis(&self) -> u64;
+
+    /// Current time in nanoseconds since UNIX epoch. Clocks without a finer
+    /// resolution report whole milliseconds
+    fn now_nanos(&self) -> u64 {
+        self.now_millis().saturating_mul(1_000_000)
+    }
+}
+
+/// The system wall clock, the default time source of a book
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        current_time_millis()
+    }
+
+    fn now_nanos(&self) -> u64 {
+        current_time_nanos()
+    }
+}
+
+/// A clock that only moves when told to, for tests and backtests
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    /// A clock reading `now` milliseconds since UNIX epoch
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: AtomicU64::new(now),
+        }
+    }
+
+    /// Move the clock to `now`, backwards included
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::Release);
+    }
+
+    /// Move the clock forward by `millis`, returning the new time
+    pub fn advance(&self, millis: u64) -> u64 {
+        self.now.fetch_add(millis, Ordering::AcqRel) + millis
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> u64 {
+        self.now.load(Ordering::Acquire)
+    }
+}
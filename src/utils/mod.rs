@@ -1,5 +1,7 @@
+mod clock;
 mod time;
 
 mod tests;
 
-pub use time::current_time_millis;
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use time::{current_time_millis, current_time_nanos};
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::current_time_millis;
+    use crate::utils::{Clock, ManualClock, SystemClock};
+
+    #[test]
+    fn test_system_clock_reads_the_wall_clock() {
+        let before = current_time_millis();
+        let now = SystemClock.now_millis();
+        assert!(now >= before);
+        assert!(now - before <= 10);
+    }
+
+    #[test]
+    fn test_manual_clock_moves_only_when_told() {
+        let clock = ManualClock::new(1_000);
+        assert_eq!(clock.now_millis(), 1_000);
+        assert_eq!(clock.advance(500), 1_500);
+        assert_eq!(clock.now_millis(), 1_500);
+        clock.set(200);
+        assert_eq!(clock.now_millis(), 200);
+    }
+}
@@ -1,4 +1,8 @@
+#[cfg(not(feature = "wasm"))]
 use std::time::{SystemTime, UNIX_EPOCH};
+// `std::time::SystemTime::now` panics on wasm32-unknown-unknown
+#[cfg(feature = "wasm")]
+use web_time::{SystemTime, UNIX_EPOCH};
 
 /// Returns the current time in milliseconds since UNIX epoch
 pub fn current_time_millis() -> u64 {
@@ -7,3 +11,11 @@ pub fn current_time_millis() -> u64 {
         .expect("Time went backwards")
         .as_millis() as u64
 }
+
+/// Returns the current time in nanoseconds since UNIX epoch
+pub fn current_time_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos() as u64
+}